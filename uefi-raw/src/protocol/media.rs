@@ -35,3 +35,20 @@ pub struct LoadFile2Protocol {
 impl LoadFile2Protocol {
     pub const GUID: Guid = guid!("4006c0c1-fcb3-403e-996d-4a6c8724e06d");
 }
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct RamDiskProtocol {
+    pub register: unsafe extern "efiapi" fn(
+        ram_disk_base: u64,
+        ram_disk_size: u64,
+        ram_disk_type: *const Guid,
+        parent_device_path: *const DevicePathProtocol,
+        device_path: *mut *const DevicePathProtocol,
+    ) -> Status,
+    pub unregister: unsafe extern "efiapi" fn(device_path: *const DevicePathProtocol) -> Status,
+}
+
+impl RamDiskProtocol {
+    pub const GUID: Guid = guid!("ab38a0df-6873-44a9-87e6-d4eb56148449");
+}
@@ -13,6 +13,7 @@ pub fn test() {
 
     global::alloc_vec();
     global::alloc_alignment();
+    global::alloc_tracking();
 
     test_memory_map();
 }
@@ -96,6 +97,43 @@ mod global {
             );
         }
     }
+
+    /// Test that allocations made while tracking is enabled are counted and
+    /// recorded.
+    pub fn alloc_tracking() {
+        use uefi::allocator::tracking;
+
+        info!("Testing allocation tracking");
+
+        tracking::reset();
+        tracking::enable();
+        let value = {
+            let _scope = tracking::track_location();
+            Box::new([0u8; 100])
+        };
+        let stats = tracking::stats();
+        assert_eq!(stats.live_allocations, 1);
+        assert_eq!(stats.live_bytes, 100);
+
+        let mut found = false;
+        tracking::for_each_outstanding(|record| {
+            if record.addr == value.as_ptr() as usize {
+                assert_eq!(record.size, 100);
+                assert_eq!(record.location.unwrap().file(), file!());
+                found = true;
+            }
+        });
+        assert!(found, "allocation was not recorded");
+        tracking::log_outstanding(log::Level::Info);
+
+        drop(value);
+        let stats = tracking::stats();
+        assert_eq!(stats.live_allocations, 0);
+        assert_eq!(stats.peak_bytes, 100);
+        assert!(!tracking::is_approximate());
+        tracking::disable();
+        tracking::log_summary(log::Level::Info);
+    }
 }
 
 fn test_memory_map() {
@@ -4,10 +4,17 @@
 
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
-use uefi::boot::ScopedProtocol;
-use uefi::fs::{FileSystem, IoError, IoErrorContext, PathBuf};
+use uefi::boot::{self, ScopedProtocol};
+use uefi::fs::{
+    FileSystem, FileSystemProvider, InstalledFileSystem, IoError, IoErrorContext, NodeInfo,
+    OpenOptions, Path, PathBuf, VolumeInfo,
+};
+use uefi::io::{Read, Seek, SeekFrom, Write};
+use uefi::proto::device_path::build::{self, DevicePathBuilder};
+use uefi::proto::media::file::{FileAttribute, FileMode};
 use uefi::proto::media::fs::SimpleFileSystem;
-use uefi::{cstr16, fs, Status};
+use uefi::proto::string::unicode_collation::UnicodeCollation;
+use uefi::{cstr16, fs, guid, CString16, Status};
 
 /// Tests functionality from the `uefi::fs` module. This test relies on a
 /// working File System Protocol, which is tested at a dedicated place.
@@ -60,6 +67,8 @@ pub fn test(sfs: ScopedProtocol<SimpleFileSystem>) -> Result<(), fs::Error> {
     test_copy_error(&mut fs)?;
     test_copy_success(&mut fs)?;
     test_copy_success_chunks(&mut fs)?;
+    test_file_handle(&mut fs)?;
+    test_walk_dir(&mut fs)?;
 
     Ok(())
 }
@@ -170,3 +179,211 @@ fn test_copy_success_chunks(fs: &mut FileSystem) -> Result<(), fs::Error> {
 
     Ok(())
 }
+
+fn test_file_handle(fs: &mut FileSystem) -> Result<(), fs::Error> {
+    let path = cstr16!("handle_file");
+
+    // Create a file and write to it in multiple steps.
+    let mut file = fs.create(path)?;
+    file.write_all(b"hello").unwrap();
+    file.write_all(b" world").unwrap();
+    file.sync_all()?;
+    assert_eq!(file.len()?, 11);
+    drop(file);
+    assert_eq!(fs.read(path)?, b"hello world");
+
+    // Seek and read part of the file.
+    let mut file = fs.open(path)?;
+    assert_eq!(file.seek(SeekFrom::Start(6)), Ok(6));
+    let mut buf = [0; 5];
+    file.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"world");
+    drop(file);
+
+    // Append mode always writes at the end.
+    let mut file = OpenOptions::new().append(true).open(fs, path)?;
+    file.rewind().unwrap();
+    file.write_all(b"!").unwrap();
+    drop(file);
+    assert_eq!(fs.read(path)?, b"hello world!");
+
+    // Truncate and extend with `set_len`.
+    let mut file = OpenOptions::new().write(true).open(fs, path)?;
+    file.set_len(5)?;
+    assert_eq!(file.len()?, 5);
+    drop(file);
+    assert_eq!(fs.read(path)?, b"hello");
+
+    // `create_new` fails if the file exists.
+    let err = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(fs, path)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        fs::Error::Io(IoError {
+            context: IoErrorContext::AlreadyExists,
+            ..
+        })
+    ));
+
+    // `create` truncates existing files.
+    drop(fs.create(path)?);
+    assert_eq!(fs.read(path)?, b"");
+
+    fs.remove_file(path)?;
+
+    Ok(())
+}
+
+fn test_walk_dir(fs: &mut FileSystem) -> Result<(), fs::Error> {
+    fs.create_dir_all(cstr16!("walk\\EFI\\boot"))?;
+    fs.create_dir_all(cstr16!("walk\\EFI\\vendor\\nested"))?;
+    fs.write(cstr16!("walk\\EFI\\boot\\BOOTX64.EFI"), "a")?;
+    fs.write(cstr16!("walk\\EFI\\vendor\\grub.efi"), "b")?;
+    fs.write(cstr16!("walk\\EFI\\vendor\\grub.cfg"), "c")?;
+    fs.write(cstr16!("walk\\EFI\\vendor\\nested\\deep.efi"), "d")?;
+
+    let walk = |fs: &mut FileSystem, max_depth| -> Result<Vec<String>, fs::Error> {
+        let mut paths = fs
+            .walk_dir(cstr16!("walk"))?
+            .max_depth(max_depth)
+            .map(|entry| entry.map(|entry| entry.path().to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+        paths.sort();
+        Ok(paths)
+    };
+
+    assert_eq!(walk(fs, 1)?, ["walk\\EFI"]);
+    assert_eq!(
+        walk(fs, usize::MAX)?,
+        [
+            "walk\\EFI",
+            "walk\\EFI\\boot",
+            "walk\\EFI\\boot\\BOOTX64.EFI",
+            "walk\\EFI\\vendor",
+            "walk\\EFI\\vendor\\grub.cfg",
+            "walk\\EFI\\vendor\\grub.efi",
+            "walk\\EFI\\vendor\\nested",
+            "walk\\EFI\\vendor\\nested\\deep.efi",
+        ]
+    );
+
+    // Pruned directories are not descended into.
+    let files = fs
+        .walk_dir(cstr16!("walk"))?
+        .filter_entry(|entry| entry.file_name() != cstr16!("vendor"))
+        .count();
+    assert_eq!(files, 3);
+
+    // Matches use the names on disk, not the casing of the pattern.
+    let expected = [
+        "\\walk\\EFI\\boot\\BOOTX64.EFI",
+        "\\walk\\EFI\\vendor\\grub.efi",
+    ];
+    let mut bootloaders = fs
+        .glob(cstr16!("\\walk\\efi\\*\\*.efi"))?
+        .map(|path| path.map(|path| path.to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    bootloaders.sort();
+    assert_eq!(bootloaders, expected);
+
+    if let Some(&handle) = boot::find_handles::<UnicodeCollation>()
+        .unwrap_or_default()
+        .first()
+    {
+        let collation = boot::open_protocol_exclusive::<UnicodeCollation>(handle).unwrap();
+        let mut bootloaders = fs
+            .glob_with_collation(cstr16!("\\walk\\efi\\*\\*.EFI"), &collation)?
+            .map(|path| path.map(|path| path.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+        bootloaders.sort();
+        assert_eq!(bootloaders, expected);
+    } else {
+        info!("Skipping glob_with_collation test: no UnicodeCollation protocol");
+    }
+
+    fs.remove_dir_all(cstr16!("walk"))?;
+
+    Ok(())
+}
+
+/// A read-only file system with a single file, implemented in Rust.
+struct HelloFs;
+
+impl FileSystemProvider for HelloFs {
+    type Node = bool;
+
+    fn open(&mut self, path: &Path, mode: FileMode, _: FileAttribute) -> uefi::Result<bool> {
+        if mode != FileMode::Read {
+            return Err(Status::WRITE_PROTECTED.into());
+        }
+        match path.to_cstr16() {
+            p if p == cstr16!("\\") => Ok(false),
+            p if p == cstr16!("\\hello.txt") => Ok(true),
+            _ => Err(Status::NOT_FOUND.into()),
+        }
+    }
+
+    fn info(&mut self, is_file: &bool) -> uefi::Result<NodeInfo> {
+        Ok(if *is_file {
+            NodeInfo::file(cstr16!("hello.txt").into(), 5)
+        } else {
+            NodeInfo::directory(CString16::new())
+        })
+    }
+
+    fn read(&mut self, _: &mut bool, offset: u64, buf: &mut [u8]) -> uefi::Result<usize> {
+        let data = b"hello".get(offset as usize..).unwrap_or_default();
+        let len = buf.len().min(data.len());
+        buf[..len].copy_from_slice(&data[..len]);
+        Ok(len)
+    }
+
+    fn read_dir(&mut self, _: &mut bool, index: usize) -> uefi::Result<Option<NodeInfo>> {
+        (index == 0).then(|| self.info(&true)).transpose()
+    }
+
+    fn volume_info(&mut self) -> uefi::Result<VolumeInfo> {
+        Ok(VolumeInfo {
+            label: cstr16!("HelloFs").into(),
+            read_only: true,
+            ..Default::default()
+        })
+    }
+}
+
+/// Tests installing a file system implemented in Rust and accessing it
+/// through the `SimpleFileSystem` protocol.
+pub fn test_provider() {
+    info!("Testing file system provider");
+
+    let mut buf = Vec::new();
+    let device_path = DevicePathBuilder::with_vec(&mut buf)
+        .push(&build::hardware::Vendor {
+            vendor_guid: guid!("0f7c9b6e-5d3a-4b1e-8c2f-7a6e5d4c3b2a"),
+            vendor_defined_data: &[],
+        })
+        .and_then(DevicePathBuilder::finalize)
+        .unwrap();
+    let installed = InstalledFileSystem::install(HelloFs, device_path).unwrap();
+
+    let handle = boot::locate_device_path::<SimpleFileSystem>(&mut &*device_path).unwrap();
+    assert_eq!(handle, installed.handle());
+
+    {
+        let sfs = boot::open_protocol_exclusive::<SimpleFileSystem>(handle).unwrap();
+        let mut fs = FileSystem::new(sfs);
+        assert_eq!(fs.read_to_string(cstr16!("\\hello.txt")).unwrap(), "hello");
+        let names: Vec<String> = fs
+            .read_dir(cstr16!("\\"))
+            .unwrap()
+            .map(|info| info.unwrap().file_name().to_string())
+            .collect();
+        assert_eq!(names, ["hello.txt"]);
+        assert!(fs.write(cstr16!("\\new.txt"), "x").is_err());
+    }
+
+    installed.uninstall().unwrap();
+}
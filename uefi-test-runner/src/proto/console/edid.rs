@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use uefi::boot::{self, OpenProtocolAttributes, OpenProtocolParams, ScopedProtocol};
+use uefi::proto::console::edid::{EdidActive, EdidDiscovered};
+use uefi::proto::console::gop::GraphicsOutput;
+use uefi::proto::ProtocolPointer;
+use uefi::{Handle, Result};
+
+/// Open a protocol without disconnecting the drivers that use it, such as
+/// the console on the GOP handle.
+fn open<P: ProtocolPointer + ?Sized>(handle: Handle) -> Result<ScopedProtocol<P>> {
+    unsafe {
+        boot::open_protocol::<P>(
+            OpenProtocolParams {
+                handle,
+                agent: boot::image_handle(),
+                controller: None,
+            },
+            OpenProtocolAttributes::GetProtocol,
+        )
+    }
+}
+
+pub fn test() {
+    info!("Running EDID protocol test");
+    let handle =
+        boot::get_handle_for_protocol::<GraphicsOutput>().expect("missing GraphicsOutput protocol");
+
+    // Not every GOP driver reads the EDID of the display.
+    let Ok(active) = open::<EdidActive>(handle) else {
+        info!("No EdidActive protocol, skipping test");
+        return;
+    };
+    if let Ok(discovered) = open::<EdidDiscovered>(handle) {
+        info!("Discovered EDID is {} bytes", discovered.raw_edid().len());
+    }
+
+    let edid = match active.edid() {
+        Ok(edid) => edid,
+        Err(err) => {
+            info!("No valid active EDID: {err}");
+            return;
+        }
+    };
+    info!(
+        "Display {:?} by {:?}, native resolution {:?}",
+        edid.display_name(),
+        core::str::from_utf8(&edid.manufacturer_id()),
+        edid.native_resolution()
+    );
+
+    let gop = open::<GraphicsOutput>(handle).expect("failed to open Graphics Output Protocol");
+    if edid.native_resolution().is_some() {
+        let mode = edid.best_mode(&gop).expect("no graphics mode");
+        info!("Best graphics mode: {:?}", mode.info().resolution());
+    }
+}
@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use uefi::boot;
+use uefi::proto::console::text::{InputEx, Key, KeyData, KeyShiftState, KeyState, ScanCode};
+use uefi::Status;
+use uefi_raw::protocol::console::KeyData as RawKeyData;
+
+unsafe extern "efiapi" fn key_notify(_key_data: *mut RawKeyData) -> Status {
+    Status::SUCCESS
+}
+
+pub fn test() {
+    info!("Running extended text input protocol test");
+    let handle = boot::get_handle_for_protocol::<InputEx>().expect("missing InputEx protocol");
+    let mut input =
+        boot::open_protocol_exclusive::<InputEx>(handle).expect("failed to open InputEx protocol");
+
+    input.reset(false).expect("Failed to reset input device");
+
+    // No keys are pressed during the test.
+    assert_eq!(input.read_key_stroke_ex(), Ok(None));
+    assert!(input.wait_for_key_event().is_some());
+
+    let ctrl_f1 = KeyData {
+        key: Key::Special(ScanCode::FUNCTION_1),
+        state: KeyState {
+            shift_state: KeyShiftState::SHIFT_STATE_VALID | KeyShiftState::LEFT_CONTROL_PRESSED,
+            ..Default::default()
+        },
+    };
+    let mut notification = input
+        .register_key_notify(&ctrl_f1, key_notify)
+        .expect("Failed to register key notification");
+    assert_eq!(notification.read_key_stroke_ex(), Ok(None));
+    notification
+        .unregister()
+        .expect("Failed to unregister key notification");
+}
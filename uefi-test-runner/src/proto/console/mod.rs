@@ -11,10 +11,17 @@ pub fn test() {
         serial::test();
         gop::test();
     }
+    edid::test();
     pointer::test();
+    pointer::test_absolute();
+    input::test();
+    splitter::test();
 }
 
+mod edid;
 mod gop;
+mod input;
 mod pointer;
 mod serial;
+mod splitter;
 mod stdout;
@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use uefi::boot;
-use uefi::proto::console::pointer::Pointer;
+use uefi::proto::console::pointer::{AbsolutePointer, Pointer};
 
 pub fn test() {
     info!("Running pointer protocol test");
@@ -23,3 +23,34 @@ pub fn test() {
         info!("Pointer state has not changed since the last query");
     }
 }
+
+pub fn test_absolute() {
+    info!("Running absolute pointer protocol test");
+    // QEMU only provides an absolute pointer if a tablet device is attached.
+    let Ok(handle) = boot::get_handle_for_protocol::<AbsolutePointer>() else {
+        info!("No AbsolutePointer protocol, skipping test");
+        return;
+    };
+    let mut pointer = boot::open_protocol_exclusive::<AbsolutePointer>(handle)
+        .expect("failed to open absolute pointer protocol");
+
+    pointer
+        .reset(false)
+        .expect("Failed to reset absolute pointer device");
+
+    let mode = *pointer.mode();
+    info!("Absolute pointer mode: {:?}", mode);
+    assert!(mode.x_range().start() <= mode.x_range().end());
+    assert!(mode.y_range().start() <= mode.y_range().end());
+    assert!(pointer.wait_for_input_event().is_some());
+
+    let state = pointer
+        .read_state()
+        .expect("Failed to retrieve absolute pointer state");
+    if let Some(state) = state {
+        info!(
+            "Absolute pointer at {:?}",
+            mode.to_screen(&state, 1024, 768)
+        );
+    }
+}
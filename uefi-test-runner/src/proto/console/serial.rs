@@ -1,7 +1,8 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use crate::reconnect_serial_to_console;
-use uefi::proto::console::serial::{ControlBits, Serial};
+use core::time::Duration;
+use uefi::proto::console::serial::{ControlBits, Serial, SerialTerminal};
 use uefi::{boot, Result, ResultExt, Status};
 
 // For the duration of this function, the serial device is opened in
@@ -31,11 +32,22 @@ fn serial_test_helper(serial: &mut Serial) -> Result {
     let mut input = [0u8; MSG_LEN];
     serial.read(&mut input).discard_errdata()?;
 
+    // The terminal uses a receive FIFO of one byte, so send the bytes one
+    // at a time.
+    let mut terminal = SerialTerminal::new(serial)?;
+    let mut terminal_ok = true;
+    for &byte in b"ok" {
+        terminal.write(&[byte])?;
+        terminal_ok &= terminal.read_byte(Duration::from_millis(100))? == Some(byte);
+    }
+    terminal_ok &= terminal.read_byte(Duration::from_millis(10))?.is_none();
+    let serial = terminal.serial();
+
     // Clean up after ourselves
     serial.reset()?;
     serial.set_control_bits(old_ctrl_bits & ControlBits::SETTABLE)?;
 
-    if OUTPUT == input {
+    if OUTPUT == input && terminal_ok {
         Ok(())
     } else {
         Err(Status::ABORTED.into())
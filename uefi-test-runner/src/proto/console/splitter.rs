@@ -0,0 +1,27 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use core::fmt::Write;
+use uefi::helpers;
+use uefi::ui::splitter::{ConsoleSplitter, InputMux};
+
+pub fn test() {
+    info!("Running console splitter test");
+
+    let mut splitter = ConsoleSplitter::new();
+    splitter.add_outputs().expect("failed to add outputs");
+    assert!(splitter.device_count() > 0, "no text output devices");
+    // Adding the devices again does nothing.
+    splitter.add_outputs().expect("failed to add outputs");
+    let count = splitter.device_count();
+    info!("Splitting output to {count} devices");
+    writeln!(splitter, "Written by the console splitter").unwrap();
+
+    unsafe { helpers::set_print_output(&mut splitter) };
+    uefi::println!("Printed through the console splitter");
+    unsafe { helpers::set_print_output(core::ptr::null_mut()) };
+
+    let mut input = InputMux::new();
+    input.add_inputs().expect("failed to add inputs");
+    info!("Reading keys from {} devices", input.device_count());
+    input.read_key().expect("failed to read key");
+}
@@ -2,11 +2,14 @@
 
 use alloc::boxed::Box;
 use alloc::vec::Vec;
+use uefi::fs::PathBuf;
 use uefi::proto::device_path::build::{self, DevicePathBuilder};
 use uefi::proto::device_path::text::{
     AllowShortcuts, DevicePathFromText, DevicePathToText, DisplayOnly,
 };
-use uefi::proto::device_path::{messaging, DevicePath, DevicePathNode, LoadedImageDevicePath};
+use uefi::proto::device_path::{
+    messaging, DevicePath, DevicePathNode, DevicePathNodeEnum, DeviceType, LoadedImageDevicePath,
+};
 use uefi::proto::loaded_image::LoadedImage;
 use uefi::proto::media::disk::DiskIo;
 use uefi::{boot, cstr16};
@@ -44,6 +47,60 @@ pub fn test() {
     // Test finding a handle by device path.
     let mut dp = &*device_path;
     boot::locate_device_path::<DiskIo>(&mut dp).unwrap();
+
+    test_device_path_matching(&device_path, &loaded_image_device_path);
+    test_for_image_handle_file(&device_path, &loaded_image_device_path);
+}
+
+/// Test `DevicePath::for_image_handle_file` by rebuilding the path of the
+/// running image.
+fn test_for_image_handle_file(device_path: &DevicePath, image_path: &DevicePath) {
+    let file_path = PathBuf::from_device_path(image_path).unwrap();
+    let path =
+        DevicePath::for_image_handle_file(boot::image_handle(), file_path.to_cstr16()).unwrap();
+    assert!(path.starts_with(device_path));
+    assert_eq!(
+        path.node_iter().count(),
+        device_path.node_iter().count() + 1
+    );
+    assert_eq!(PathBuf::from_device_path(&path).unwrap(), file_path);
+}
+
+/// Test `DevicePath` prefix matching, handle lookup, and short-form
+/// expansion using the device path of the running image.
+fn test_device_path_matching(device_path: &DevicePath, image_path: &DevicePath) {
+    assert!(image_path.starts_with(device_path));
+    let file_path = image_path.strip_prefix(device_path).unwrap();
+    assert!(file_path
+        .node_iter()
+        .all(|node| node.device_type() == DeviceType::MEDIA));
+
+    let (handle, rest) = device_path.locate_device_path::<DiskIo>().unwrap();
+    let mut dp = device_path;
+    assert_eq!(boot::locate_device_path::<DiskIo>(&mut dp).unwrap(), handle);
+    assert_eq!(rest, dp);
+
+    // A non-short-form path expands to itself.
+    let expanded = device_path.expand_short_form().unwrap();
+    assert_eq!(expanded.len(), 1);
+    assert_eq!(&*expanded[0], device_path);
+
+    // If the image is on a partition, build the hard drive media short
+    // form of its path and check that it expands to the full path.
+    let Some(hd_index) = image_path
+        .node_iter()
+        .position(|node| matches!(node.as_enum(), Ok(DevicePathNodeEnum::MediaHardDrive(_))))
+    else {
+        return;
+    };
+    let mut v = Vec::new();
+    let mut builder = DevicePathBuilder::with_vec(&mut v);
+    for node in image_path.node_iter().skip(hd_index) {
+        builder = builder.push(&node).unwrap();
+    }
+    let short_form = builder.finalize().unwrap();
+    let expanded = short_form.expand_short_form().unwrap();
+    assert!(expanded.iter().any(|path| **path == *image_path));
 }
 
 fn create_test_device_path() -> Box<DevicePath> {
@@ -1,13 +1,19 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use alloc::string::ToString;
+use alloc::vec::Vec;
 use core::cell::RefCell;
 use core::ptr::NonNull;
 use uefi::boot::{
     self, EventType, OpenProtocolAttributes, OpenProtocolParams, ScopedProtocol, Tpl,
 };
 use uefi::data_types::Align;
+use uefi::disk::mbr::Mbr;
+use uefi::disk::ram::RamDiskMemory;
+use uefi::disk::PartitionTable;
 use uefi::prelude::*;
+use uefi::proto::device_path::media::RamDiskType;
+use uefi::proto::device_path::{DevicePath, DevicePathBuf};
 use uefi::proto::media::block::BlockIO;
 use uefi::proto::media::disk::{DiskIo, DiskIo2, DiskIo2Token};
 use uefi::proto::media::disk_info::{DiskInfo, DiskInfoInterface};
@@ -197,6 +203,20 @@ fn test_create_file(directory: &mut Directory) {
 
     let mut file = file.into_regular_file().expect("not a regular file");
     file.write(b"test output data").unwrap();
+
+    // Access the file through the generic I/O traits.
+    {
+        use uefi::io::{Read, Seek, SeekFrom, Write};
+
+        assert_eq!(file.seek(SeekFrom::End(-4)), Ok(12));
+        Write::write_all(&mut file, b"DATA").unwrap();
+        assert_eq!(file.stream_position(), Ok(16));
+
+        file.rewind().unwrap();
+        let mut buf = vec![0; 16];
+        file.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, b"test output DATA");
+    }
 }
 
 /// Test directory creation by
@@ -285,6 +305,19 @@ fn test_raw_disk_io(handle: Handle) {
     assert_eq!(buf[510], 0x55);
     assert_eq!(buf[511], 0xaa);
 
+    // Read the signature again through the generic I/O traits.
+    {
+        use uefi::io::{Read, Seek, SeekFrom};
+        use uefi::proto::media::disk::DiskIoCursor;
+
+        let mut disk_io = disk_io;
+        let mut cursor = DiskIoCursor::with_size(&mut disk_io, media_id, 512);
+        assert_eq!(cursor.seek(SeekFrom::End(-2)), Ok(510));
+        let mut signature = [0; 4];
+        assert_eq!(cursor.read(&mut signature), Ok(2));
+        assert_eq!(signature[..2], [0x55, 0xaa]);
+    }
+
     info!("Raw disk I/O succeeded");
 }
 
@@ -393,6 +426,85 @@ fn test_partition_info(disk_handle: Handle) {
     assert_eq!(mbr.os_type, MbrOsType(6));
 }
 
+/// Parse the partition table of the disk containing `partition_handle`
+/// and check that it matches what the firmware reports.
+fn test_partition_table(partition_handle: Handle) {
+    info!("Testing partition table parsing");
+
+    // The disk is the parent of the partition's device path.
+    let mut disk_path = DevicePathBuf::from(
+        &*boot::open_protocol_exclusive::<DevicePath>(partition_handle)
+            .expect("Failed to get partition device path"),
+    );
+    assert!(disk_path.pop());
+    let (disk_handle, _) = disk_path
+        .locate_device_path::<BlockIO>()
+        .expect("Failed to find disk handle");
+
+    // Open in `GetProtocol` mode so that the partition handles created by
+    // the firmware stay connected.
+    let mut block_io = unsafe {
+        boot::open_protocol::<BlockIO>(
+            OpenProtocolParams {
+                handle: disk_handle,
+                agent: boot::image_handle(),
+                controller: None,
+            },
+            OpenProtocolAttributes::GetProtocol,
+        )
+        .expect("Failed to get block I/O protocol")
+    };
+
+    let mbr = Mbr::read(&mut *block_io)
+        .expect("Failed to read disk")
+        .expect("Disk has no MBR");
+    assert!(!mbr.is_protective());
+    let partitions: Vec<_> = mbr
+        .partitions()
+        .map(|(i, record)| (i, record.os_type, record.starting_lba, record.size_in_lba))
+        .collect();
+    assert_eq!(partitions, [(0, MbrOsType(6), 1, 20479)]);
+
+    assert!(matches!(
+        PartitionTable::read(&mut *block_io),
+        Ok(Some(PartitionTable::Mbr(_)))
+    ));
+}
+
+/// Register a RAM disk, access it through `BlockIO`, and unregister it.
+fn test_ram_disk() {
+    info!("Testing RAM disk");
+
+    let mut memory = RamDiskMemory::new(64 * 1024, boot::MemoryType::BOOT_SERVICES_DATA)
+        .expect("Failed to allocate RAM disk memory");
+    memory[512..1024].fill(0xab);
+    let disk = memory
+        .register(RamDiskType::VIRTUAL_DISK, None)
+        .expect("Failed to register RAM disk");
+    info!("RAM disk emulated: {}", disk.is_emulated());
+
+    {
+        let mut block_io = boot::open_protocol_exclusive::<BlockIO>(disk.handle())
+            .expect("Failed to open block I/O protocol");
+        let media_id = block_io.media().media_id();
+        let mut buf = [0; 512];
+        block_io.read_blocks(media_id, 1, &mut buf).unwrap();
+        assert_eq!(buf, [0xab; 512]);
+
+        block_io.write_blocks(media_id, 2, &[0x12; 512]).unwrap();
+        block_io.read_blocks(media_id, 2, &mut buf).unwrap();
+        assert_eq!(buf, [0x12; 512]);
+    }
+
+    let (handle, _) = disk
+        .device_path()
+        .locate_device_path::<BlockIO>()
+        .expect("Failed to locate RAM disk");
+    assert_eq!(handle, disk.handle());
+
+    disk.unregister().expect("Failed to unregister RAM disk");
+}
+
 /// Find the disk with the "MbrTestDisk" label. Return the handle and opened
 /// `SimpleFileSystem` protocol for that disk.
 fn find_test_disk() -> (Handle, ScopedProtocol<SimpleFileSystem>) {
@@ -474,6 +586,7 @@ pub fn test() {
         test_create_directory(&mut root_directory);
 
         test_partition_info(handle);
+        test_partition_table(handle);
     }
 
     // Invoke the fs test after the basic low-level file system protocol
@@ -482,8 +595,10 @@ pub fn test() {
     // This will also drop the `SimpleFileSystem` protocol so that the raw disk
     // tests work.
     crate::fs::test(sfs).unwrap();
+    crate::fs::test_provider();
 
     test_raw_disk_io(handle);
     test_raw_disk_io2(handle);
     test_disk_info();
+    test_ram_disk();
 }
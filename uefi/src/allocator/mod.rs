@@ -5,8 +5,13 @@
 //! The allocator can be used as global Rust allocator using the
 //! `global_allocator` crate feature. See [`helpers`] for more info.
 //!
+//! Allocations made through the allocator can be counted and recorded with
+//! the opt-in [`tracking`] layer.
+//!
 //! [`helpers`]: uefi::helpers
 
+pub mod tracking;
+
 use crate::boot::{self, AllocateType};
 use crate::mem::memory_map::MemoryType;
 use crate::proto::loaded_image::LoadedImage;
@@ -115,7 +120,7 @@ unsafe impl GlobalAlloc for Allocator {
         let memory_type = get_memory_type();
         let use_page_shortcut = layout_allows_page_alloc_shortcut(&layout);
 
-        match (use_page_shortcut, layout.align()) {
+        let ptr = match (use_page_shortcut, layout.align()) {
             // Allocating pages is actually very expected in UEFI OS loaders, so
             // it makes sense to provide this optimization.
             (true, _) => {
@@ -135,7 +140,12 @@ unsafe impl GlobalAlloc for Allocator {
                     .unwrap_or(ptr::null_mut())
             }
             (false, 9..) => alloc_pool_aligned(memory_type, layout.size(), layout.align()),
+        };
+
+        if !ptr.is_null() {
+            tracking::on_alloc(ptr, layout.size(), layout.align(), memory_type);
         }
+        ptr
     }
 
     /// Deallocate memory using the UEFI boot services.
@@ -144,6 +154,8 @@ unsafe impl GlobalAlloc for Allocator {
     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
         let ptr = NonNull::new(ptr).unwrap();
 
+        tracking::on_dealloc(ptr.as_ptr(), layout.size(), get_memory_type());
+
         let use_page_shortcut = layout_allows_page_alloc_shortcut(&layout);
 
         match (use_page_shortcut, layout.align()) {
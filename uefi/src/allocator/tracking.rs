@@ -0,0 +1,504 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Optional allocation tracking for [`Allocator`].
+//!
+//! Tracking is disabled by default. Once enabled with [`enable`], every
+//! allocation and deallocation made through [`Allocator`] updates a set of
+//! counters (live allocations, live bytes, peak usage) per [`MemoryType`], and
+//! outstanding allocations are recorded in a fixed-size table. No memory is
+//! allocated by the tracker itself, so it can be used from within the global
+//! allocator.
+//!
+//! This is meant for debugging long-running applications: call
+//! [`log_summary`] and [`log_outstanding`] before handing off to the OS to
+//! find pool allocations that were never freed.
+//!
+//! ```no_run
+//! use uefi::allocator::tracking;
+//!
+//! tracking::enable();
+//!
+//! {
+//!     // Allocations made while this guard is alive are attributed to
+//!     // this source location.
+//!     let _scope = tracking::track_location();
+//!     // ...
+//! }
+//!
+//! tracking::log_summary(log::Level::Info);
+//! tracking::log_outstanding(log::Level::Warn);
+//! ```
+//!
+//! [`Allocator`]: super::Allocator
+
+use crate::boot::{self, Tpl};
+use crate::mem::memory_map::MemoryType;
+use core::cell::UnsafeCell;
+use core::panic::Location;
+use core::ptr;
+use core::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+
+/// Maximum number of outstanding allocations that are recorded individually.
+///
+/// Allocations beyond this limit are still reflected in the counters, but
+/// are not reported by [`for_each_outstanding`], and their deallocations
+/// cannot be recognized. See [`untracked_records`] and [`is_approximate`].
+pub const MAX_RECORDS: usize = 512;
+
+/// Maximum number of distinct memory types with separate counters.
+pub const MAX_MEMORY_TYPES: usize = 8;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static LOCKED: AtomicBool = AtomicBool::new(false);
+static CURRENT_LOCATION: AtomicPtr<Location<'static>> = AtomicPtr::new(ptr::null_mut());
+static TRACKER: TrackerCell = TrackerCell(UnsafeCell::new(Tracker::new()));
+
+struct TrackerCell(UnsafeCell<Tracker>);
+
+// SAFETY: all access to the inner tracker goes through `with_tracker`, which
+// serializes access.
+unsafe impl Sync for TrackerCell {}
+
+/// Counters describing the allocations made through [`Allocator`].
+///
+/// [`Allocator`]: super::Allocator
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AllocationStats {
+    /// Number of allocations that have not been freed yet.
+    pub live_allocations: usize,
+    /// Number of bytes in allocations that have not been freed yet.
+    pub live_bytes: usize,
+    /// Highest value of `live_allocations` observed.
+    pub peak_allocations: usize,
+    /// Highest value of `live_bytes` observed.
+    pub peak_bytes: usize,
+    /// Total number of allocations made.
+    pub total_allocations: usize,
+    /// Total number of deallocations made.
+    pub total_deallocations: usize,
+}
+
+impl AllocationStats {
+    /// Returns a zeroed set of counters.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            live_allocations: 0,
+            live_bytes: 0,
+            peak_allocations: 0,
+            peak_bytes: 0,
+            total_allocations: 0,
+            total_deallocations: 0,
+        }
+    }
+
+    fn record_alloc(&mut self, size: usize) {
+        self.live_allocations += 1;
+        self.live_bytes += size;
+        self.total_allocations += 1;
+        if self.live_allocations > self.peak_allocations {
+            self.peak_allocations = self.live_allocations;
+        }
+        if self.live_bytes > self.peak_bytes {
+            self.peak_bytes = self.live_bytes;
+        }
+    }
+
+    fn record_dealloc(&mut self, size: usize) {
+        // Only called for recorded allocations, which were counted.
+        self.live_allocations -= 1;
+        self.live_bytes -= size;
+        self.total_deallocations += 1;
+    }
+}
+
+/// An outstanding allocation recorded by the tracker.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AllocationRecord {
+    /// Address of the allocation.
+    pub addr: usize,
+    /// Size of the allocation in bytes.
+    pub size: usize,
+    /// Alignment of the allocation in bytes.
+    pub align: usize,
+    /// Memory type the allocation was made with.
+    pub memory_type: MemoryType,
+    /// Source location of the innermost active [`track_location`] scope at
+    /// the time of the allocation, if any.
+    pub location: Option<&'static Location<'static>>,
+}
+
+struct Tracker {
+    total: AllocationStats,
+    per_type: [Option<(MemoryType, AllocationStats)>; MAX_MEMORY_TYPES],
+    records: [Option<AllocationRecord>; MAX_RECORDS],
+    /// Allocations that were counted but did not fit into `records`.
+    untracked_records: usize,
+}
+
+impl Tracker {
+    const fn new() -> Self {
+        Self {
+            total: AllocationStats::new(),
+            per_type: [None; MAX_MEMORY_TYPES],
+            records: [None; MAX_RECORDS],
+            untracked_records: 0,
+        }
+    }
+
+    fn type_stats(&mut self, memory_type: MemoryType) -> Option<&mut AllocationStats> {
+        let index = self
+            .per_type
+            .iter()
+            .position(|e| matches!(e, Some((ty, _)) if *ty == memory_type))
+            .or_else(|| self.per_type.iter().position(Option::is_none))?;
+        let entry = self.per_type[index].get_or_insert((memory_type, AllocationStats::new()));
+        Some(&mut entry.1)
+    }
+
+    fn on_alloc(&mut self, record: AllocationRecord) {
+        self.total.record_alloc(record.size);
+        if let Some(stats) = self.type_stats(record.memory_type) {
+            stats.record_alloc(record.size);
+        }
+
+        if let Some(slot) = self.records.iter_mut().find(|r| r.is_none()) {
+            *slot = Some(record);
+        } else {
+            self.untracked_records += 1;
+        }
+    }
+
+    fn on_dealloc(&mut self, addr: usize, size: usize, memory_type: MemoryType) {
+        // Memory that was allocated before tracking was enabled, or that did
+        // not fit into the table, was never counted or cannot be told apart
+        // from such memory, so leave the counters alone.
+        let Some(slot) = self
+            .records
+            .iter_mut()
+            .find(|r| matches!(r, Some(r) if r.addr == addr))
+        else {
+            return;
+        };
+        *slot = None;
+
+        self.total.record_dealloc(size);
+        if let Some(stats) = self.type_stats(memory_type) {
+            stats.record_dealloc(size);
+        }
+    }
+
+    fn stats_for(&self, memory_type: MemoryType) -> Option<AllocationStats> {
+        self.per_type.iter().find_map(|e| match e {
+            Some((ty, stats)) if *ty == memory_type => Some(*stats),
+            _ => None,
+        })
+    }
+}
+
+/// Run `f` with exclusive access to the global tracker.
+///
+/// While boot services are active, the task priority level is raised to
+/// [`Tpl::HIGH_LEVEL`] so that event callbacks cannot interrupt the update.
+/// Returns `None` if the tracker is already in use, which can only happen on
+/// re-entrant use (e.g. from another processor).
+fn with_tracker<R>(f: impl FnOnce(&mut Tracker) -> R) -> Option<R> {
+    let _tpl = boot::are_boot_services_active().then(|| {
+        // SAFETY: interrupts are only disabled briefly: the guard restores
+        // the TPL at the end of this function. While it is raised, `f` only
+        // updates the tracker and calls no boot services; the only other
+        // call is `restore_tpl` when the guard is dropped.
+        unsafe { boot::raise_tpl(Tpl::HIGH_LEVEL) }
+    });
+
+    if LOCKED
+        .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+        .is_err()
+    {
+        return None;
+    }
+    // SAFETY: `LOCKED` guarantees exclusive access.
+    let result = f(unsafe { &mut *TRACKER.0.get() });
+    LOCKED.store(false, Ordering::Release);
+    Some(result)
+}
+
+/// Enables allocation tracking.
+///
+/// Allocations made before tracking was enabled are not known to the
+/// tracker.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Release);
+}
+
+/// Disables allocation tracking. The collected data is kept.
+pub fn disable() {
+    ENABLED.store(false, Ordering::Release);
+}
+
+/// Returns whether allocation tracking is enabled.
+#[must_use]
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Acquire)
+}
+
+/// Clears all counters and outstanding allocation records.
+pub fn reset() {
+    with_tracker(|tracker| *tracker = Tracker::new());
+}
+
+/// Called by [`Allocator`] after a successful allocation.
+///
+/// [`Allocator`]: super::Allocator
+pub(super) fn on_alloc(ptr: *mut u8, size: usize, align: usize, memory_type: MemoryType) {
+    if !is_enabled() {
+        return;
+    }
+    let location = CURRENT_LOCATION.load(Ordering::Acquire);
+    // SAFETY: `CURRENT_LOCATION` only ever holds null or a `&'static Location`.
+    let location = unsafe { location.as_ref() };
+    let record = AllocationRecord {
+        addr: ptr as usize,
+        size,
+        align,
+        memory_type,
+        location,
+    };
+    with_tracker(|tracker| tracker.on_alloc(record));
+}
+
+/// Called by [`Allocator`] before memory is freed.
+///
+/// [`Allocator`]: super::Allocator
+pub(super) fn on_dealloc(ptr: *mut u8, size: usize, memory_type: MemoryType) {
+    if !is_enabled() {
+        return;
+    }
+    with_tracker(|tracker| tracker.on_dealloc(ptr as usize, size, memory_type));
+}
+
+/// Returns the counters across all memory types.
+#[must_use]
+pub fn stats() -> AllocationStats {
+    with_tracker(|tracker| tracker.total).unwrap_or_default()
+}
+
+/// Returns the counters for a single memory type, or `None` if no
+/// allocation of that type has been tracked.
+#[must_use]
+pub fn stats_for(memory_type: MemoryType) -> Option<AllocationStats> {
+    with_tracker(|tracker| tracker.stats_for(memory_type)).flatten()
+}
+
+/// Returns the number of allocations that did not fit into the record table
+/// (see [`MAX_RECORDS`]) since tracking was enabled or [`reset`].
+#[must_use]
+pub fn untracked_records() -> usize {
+    with_tracker(|tracker| tracker.untracked_records).unwrap_or_default()
+}
+
+/// Returns whether the record table has overflowed since tracking was
+/// enabled or [`reset`].
+///
+/// Deallocations are only counted for recorded allocations. After an
+/// overflow, freeing an allocation that was not recorded leaves the counters
+/// unchanged, so the live counts may be higher than the actual usage.
+/// Without an overflow, the counters are exact for allocations made while
+/// tracking was enabled.
+#[must_use]
+pub fn is_approximate() -> bool {
+    untracked_records() > 0
+}
+
+/// Calls `f` for each recorded outstanding allocation.
+///
+/// The records are copied before `f` is invoked, so `f` may allocate.
+pub fn for_each_outstanding(mut f: impl FnMut(&AllocationRecord)) {
+    for index in 0..MAX_RECORDS {
+        if let Some(record) = with_tracker(|tracker| tracker.records[index]).flatten() {
+            f(&record);
+        }
+    }
+}
+
+/// Logs the counters for all memory types with the given log level.
+pub fn log_summary(level: log::Level) {
+    let stats = stats();
+    log::log!(
+        level,
+        "allocations: {} live ({} bytes), peak {} ({} bytes), {} allocs, {} frees",
+        stats.live_allocations,
+        stats.live_bytes,
+        stats.peak_allocations,
+        stats.peak_bytes,
+        stats.total_allocations,
+        stats.total_deallocations
+    );
+
+    let per_type = with_tracker(|tracker| tracker.per_type).unwrap_or_default();
+    for (memory_type, stats) in per_type.iter().flatten() {
+        log::log!(
+            level,
+            "  {:?}: {} live ({} bytes), peak {} bytes",
+            memory_type,
+            stats.live_allocations,
+            stats.live_bytes,
+            stats.peak_bytes
+        );
+    }
+}
+
+/// Logs every recorded outstanding allocation with the given log level.
+pub fn log_outstanding(level: log::Level) {
+    for_each_outstanding(|record| match record.location {
+        Some(location) => log::log!(
+            level,
+            "outstanding allocation at {:#x}: size={}, align={}, type={:?}, from {}",
+            record.addr,
+            record.size,
+            record.align,
+            record.memory_type,
+            location
+        ),
+        None => log::log!(
+            level,
+            "outstanding allocation at {:#x}: size={}, align={}, type={:?}",
+            record.addr,
+            record.size,
+            record.align,
+            record.memory_type
+        ),
+    });
+
+    let untracked = untracked_records();
+    if untracked > 0 {
+        log::log!(
+            level,
+            "{untracked} allocations were not recorded, so the counters are approximate"
+        );
+    }
+}
+
+/// Attributes allocations to the caller's source location.
+///
+/// Allocations made while the returned guard is alive are recorded with the
+/// location of the call to this function. Scopes can be nested; dropping the
+/// guard restores the previous location.
+#[track_caller]
+#[must_use]
+pub fn track_location() -> LocationScope {
+    let location: &'static Location<'static> = Location::caller();
+    let previous = CURRENT_LOCATION.swap(ptr::from_ref(location).cast_mut(), Ordering::AcqRel);
+    LocationScope { previous }
+}
+
+/// Guard returned by [`track_location`].
+#[derive(Debug)]
+pub struct LocationScope {
+    previous: *mut Location<'static>,
+}
+
+impl Drop for LocationScope {
+    fn drop(&mut self) {
+        CURRENT_LOCATION.store(self.previous, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::boxed::Box;
+
+    fn record(addr: usize, size: usize, memory_type: MemoryType) -> AllocationRecord {
+        AllocationRecord {
+            addr,
+            size,
+            align: 8,
+            memory_type,
+            location: None,
+        }
+    }
+
+    #[test]
+    fn test_tracker_counters() {
+        let mut tracker = Box::new(Tracker::new());
+        tracker.on_alloc(record(0x1000, 16, MemoryType::LOADER_DATA));
+        tracker.on_alloc(record(0x2000, 32, MemoryType::LOADER_DATA));
+        tracker.on_alloc(record(0x3000, 64, MemoryType::BOOT_SERVICES_DATA));
+        tracker.on_dealloc(0x2000, 32, MemoryType::LOADER_DATA);
+
+        assert_eq!(
+            tracker.total,
+            AllocationStats {
+                live_allocations: 2,
+                live_bytes: 80,
+                peak_allocations: 3,
+                peak_bytes: 112,
+                total_allocations: 3,
+                total_deallocations: 1,
+            }
+        );
+        let loader = tracker.stats_for(MemoryType::LOADER_DATA).unwrap();
+        assert_eq!(loader.live_allocations, 1);
+        assert_eq!(loader.live_bytes, 16);
+        assert_eq!(loader.peak_bytes, 48);
+        let bs = tracker.stats_for(MemoryType::BOOT_SERVICES_DATA).unwrap();
+        assert_eq!(bs.live_bytes, 64);
+        assert!(tracker
+            .stats_for(MemoryType::RUNTIME_SERVICES_DATA)
+            .is_none());
+
+        let outstanding: alloc::vec::Vec<_> = tracker.records.iter().flatten().collect();
+        assert_eq!(outstanding.len(), 2);
+        assert!(outstanding.iter().all(|r| r.addr != 0x2000));
+    }
+
+    #[test]
+    fn test_tracker_untracked() {
+        let mut tracker = Box::new(Tracker::new());
+        for i in 0..MAX_RECORDS + 2 {
+            tracker.on_alloc(record(i * 16, 16, MemoryType::LOADER_DATA));
+        }
+        assert_eq!(tracker.untracked_records, 2);
+        assert_eq!(tracker.total.live_allocations, MAX_RECORDS + 2);
+
+        // Freeing an allocation that is not in the table can't be told apart
+        // from freeing memory allocated before tracking was enabled, so the
+        // counters are left alone.
+        tracker.on_dealloc(MAX_RECORDS * 16, 16, MemoryType::LOADER_DATA);
+        assert_eq!(tracker.untracked_records, 2);
+        assert_eq!(tracker.total.live_allocations, MAX_RECORDS + 2);
+        assert_eq!(tracker.total.total_deallocations, 0);
+
+        // Freeing a recorded allocation is counted.
+        tracker.on_dealloc(0, 16, MemoryType::LOADER_DATA);
+        assert_eq!(tracker.total.live_allocations, MAX_RECORDS + 1);
+        assert_eq!(tracker.total.total_deallocations, 1);
+
+        // Freeing memory allocated before tracking was enabled doesn't hide
+        // allocations made afterwards.
+        let mut tracker = Box::new(Tracker::new());
+        tracker.on_alloc(record(0x2000, 32, MemoryType::LOADER_DATA));
+        tracker.on_dealloc(0x1000, 16, MemoryType::LOADER_DATA);
+        assert_eq!(tracker.total.live_allocations, 1);
+        assert_eq!(tracker.total.live_bytes, 32);
+        assert_eq!(tracker.total.total_deallocations, 0);
+        assert_eq!(tracker.untracked_records, 0);
+    }
+
+    #[test]
+    fn test_track_location() {
+        assert!(CURRENT_LOCATION.load(Ordering::Acquire).is_null());
+        {
+            let _outer = track_location();
+            let outer = CURRENT_LOCATION.load(Ordering::Acquire);
+            assert!(!outer.is_null());
+            {
+                let _inner = track_location();
+                assert_ne!(CURRENT_LOCATION.load(Ordering::Acquire), outer);
+            }
+            assert_eq!(CURRENT_LOCATION.load(Ordering::Acquire), outer);
+        }
+        assert!(CURRENT_LOCATION.load(Ordering::Acquire).is_null());
+    }
+}
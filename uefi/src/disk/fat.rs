@@ -0,0 +1,942 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Read-only FAT12, FAT16 and FAT32 file system.
+//!
+//! This can read files from FAT partitions that the firmware did not
+//! create a [`SimpleFileSystem`] for:
+//!
+//! ```no_run
+//! use uefi::disk::fat::FatFileSystem;
+//! use uefi::disk::gpt::Gpt;
+//! use uefi::disk::{BlockRange, ReadOnlyFileSystem};
+//! use uefi::proto::media::block::BlockIO;
+//! use uefi::{boot, cstr16, Handle};
+//!
+//! # fn f(disk: Handle) -> Result<(), Box<dyn core::error::Error>> {
+//! let mut block_io = boot::open_protocol_exclusive::<BlockIO>(disk)?;
+//! let gpt = Gpt::read(&mut *block_io)?;
+//! let (_, partition) = gpt.partitions().next().unwrap();
+//! let (start, num_blocks) = (partition.starting_lba, partition.num_blocks().unwrap());
+//!
+//! let partition = BlockRange::new(&mut *block_io, start, num_blocks)?;
+//! let mut fs = FatFileSystem::new(partition)?;
+//! let config = fs.read_to_string(cstr16!("\\config.txt"))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`SimpleFileSystem`]: crate::proto::media::fs::SimpleFileSystem
+
+use super::volume::{BlockCache, DirEntry, ReadOnlyFileSystem, Volume};
+use super::BlockDevice;
+use crate::fs::{Error, FileSystemResult, IoError, IoErrorContext, NodeInfo, PathBuf};
+use crate::proto::media::file::FileAttribute;
+use crate::runtime::{Daylight, Time, TimeParams};
+use crate::{CStr16, CString16, Char16, Status};
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
+
+/// Size of a directory entry in bytes.
+const DIR_ENTRY_SIZE: usize = 32;
+
+/// Attribute value of a long file name entry.
+const ATTR_LONG_NAME: u8 = 0x0f;
+
+/// Attribute bit of a volume label entry.
+const ATTR_VOLUME_ID: u8 = 0x08;
+
+/// Variant of the FAT file system, determined by the number of clusters.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FatType {
+    /// 12-bit cluster numbers, fewer than 4085 clusters.
+    Fat12,
+    /// 16-bit cluster numbers, fewer than 65525 clusters.
+    Fat16,
+    /// 28-bit cluster numbers.
+    Fat32,
+}
+
+/// Location of the root directory.
+#[derive(Clone, Copy, Debug)]
+enum Root {
+    /// FAT12 and FAT16: a fixed region after the FATs.
+    Fixed { offset: u64, len: u64 },
+    /// FAT32: a cluster chain.
+    Cluster(u32),
+}
+
+/// Location of a file or directory.
+#[derive(Clone, Debug)]
+pub enum FatNode {
+    /// The fixed root directory of FAT12 and FAT16.
+    FixedRoot,
+    /// A cluster chain. Empty files have no clusters.
+    Chain {
+        /// First cluster, or zero for an empty file.
+        first_cluster: u32,
+        /// File size in bytes. Zero for directories.
+        size: u64,
+    },
+}
+
+/// Position in a cluster chain, kept so that sequential reads do not have
+/// to walk the chain from the start every time.
+#[derive(Clone, Copy, Debug)]
+struct ChainPosition {
+    first_cluster: u32,
+    index: u64,
+    cluster: u32,
+}
+
+/// A read-only FAT file system on a [`BlockDevice`].
+///
+/// The device must start with the FAT boot sector; use a [`BlockRange`]
+/// to mount a partition. Long file names are supported. The file system
+/// is accessed through the [`ReadOnlyFileSystem`] trait.
+///
+/// [`BlockRange`]: super::BlockRange
+pub struct FatFileSystem<D> {
+    cache: BlockCache<D>,
+    fat_type: FatType,
+    cluster_size: u32,
+    cluster_count: u32,
+    fat_offset: u64,
+    data_offset: u64,
+    root: Root,
+    volume_id: u32,
+    volume_label: CString16,
+    position: Option<ChainPosition>,
+}
+
+const fn le16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn le32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn mount_error(status: Status) -> Error {
+    Error::Io(IoError {
+        path: PathBuf::new(),
+        context: IoErrorContext::CantOpenVolume,
+        uefi_error: status.into(),
+    })
+}
+
+fn latin1_char(b: u8) -> Char16 {
+    Char16::try_from(char::from(b)).unwrap()
+}
+
+/// Decode a space-padded 8.3 name. Bytes outside ASCII are taken as
+/// Latin-1.
+fn decode_short_name(raw: &[u8], lower_base: bool, lower_ext: bool) -> CString16 {
+    fn push(name: &mut CString16, bytes: &[u8], lower: bool) {
+        for &b in bytes {
+            let b = if lower { b.to_ascii_lowercase() } else { b };
+            name.push(latin1_char(b));
+        }
+    }
+
+    let mut name = CString16::new();
+    let mut base = raw[..8].trim_ascii_end().to_vec();
+    // 0x05 stands for a leading 0xe5, which marks deleted entries.
+    if base.first() == Some(&0x05) {
+        base[0] = 0xe5;
+    }
+    let ext = raw[8..11].trim_ascii_end();
+    push(&mut name, &base, lower_base);
+    if !ext.is_empty() {
+        name.push(latin1_char(b'.'));
+        push(&mut name, ext, lower_ext);
+    }
+    name
+}
+
+/// Checksum of a short name, stored in each of its long name entries.
+fn short_name_checksum(raw: &[u8]) -> u8 {
+    raw[..11]
+        .iter()
+        .fold(0u8, |sum, b| sum.rotate_right(1).wrapping_add(*b))
+}
+
+/// Convert a FAT date and time to a [`Time`]. A zero date is invalid.
+fn decode_time(date: u16, time: u16, centiseconds: u8) -> Time {
+    if date == 0 {
+        return Time::invalid();
+    }
+    let centiseconds = u32::from(centiseconds);
+    Time::new(TimeParams {
+        year: 1980 + (date >> 9),
+        month: ((date >> 5) & 0xf) as u8,
+        day: (date & 0x1f) as u8,
+        hour: (time >> 11) as u8,
+        minute: ((time >> 5) & 0x3f) as u8,
+        second: ((time & 0x1f) * 2) as u8 + (centiseconds / 100) as u8,
+        nanosecond: (centiseconds % 100) * 10_000_000,
+        time_zone: None,
+        daylight: Daylight::empty(),
+    })
+    .unwrap_or_else(|_| Time::invalid())
+}
+
+/// Accumulates the long name entries preceding a short entry.
+#[derive(Default)]
+struct LongName {
+    chars: Vec<u16>,
+    checksum: u8,
+    next_ordinal: u8,
+}
+
+impl LongName {
+    fn clear(&mut self) {
+        self.chars.clear();
+        self.next_ordinal = 0;
+    }
+
+    fn push(&mut self, entry: &[u8]) {
+        let ordinal = entry[0] & 0x1f;
+        if entry[0] & 0x40 != 0 {
+            // The last part of the name comes first.
+            self.chars = alloc::vec![0xffff; usize::from(ordinal) * 13];
+            self.checksum = entry[13];
+        } else if ordinal != self.next_ordinal || entry[13] != self.checksum {
+            self.clear();
+            return;
+        }
+        if ordinal == 0 || self.chars.is_empty() {
+            self.clear();
+            return;
+        }
+
+        let start = usize::from(ordinal - 1) * 13;
+        let units = (1..11)
+            .step_by(2)
+            .chain((14..26).step_by(2))
+            .chain((28..32).step_by(2));
+        for (i, offset) in units.enumerate() {
+            self.chars[start + i] = le16(entry, offset);
+        }
+        self.next_ordinal = ordinal - 1;
+    }
+
+    /// The complete long name, if it belongs to the short entry `raw`.
+    fn take(&mut self, raw: &[u8]) -> Option<CString16> {
+        let complete = !self.chars.is_empty()
+            && self.next_ordinal == 0
+            && self.checksum == short_name_checksum(raw);
+        let name = complete.then(|| {
+            let mut name = CString16::new();
+            self.chars
+                .iter()
+                .take_while(|c| **c != 0 && **c != 0xffff)
+                .for_each(|c| {
+                    name.push(Char16::try_from(*c).unwrap_or(latin1_char(b'?')));
+                });
+            name
+        });
+        self.clear();
+        name.filter(|name| !name.is_empty())
+    }
+}
+
+impl<D: BlockDevice> FatFileSystem<D> {
+    /// Mount the FAT file system on `device`.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Io`] with [`IoErrorContext::CantOpenVolume`] if the boot
+    /// sector cannot be read or does not describe a valid FAT file system.
+    pub fn new(device: D) -> FileSystemResult<Self> {
+        let mut cache = BlockCache::new(device);
+        let bs = cache
+            .read_vec(0, 512)
+            .map_err(|err| mount_error(err.status()))?;
+
+        let corrupted = || mount_error(Status::VOLUME_CORRUPTED);
+        if bs[510..512] != [0x55, 0xaa] || !matches!(bs[0], 0xeb | 0xe9) {
+            return Err(mount_error(Status::UNSUPPORTED));
+        }
+
+        let bytes_per_sector = u32::from(le16(&bs, 11));
+        let sectors_per_cluster = u32::from(bs[13]);
+        let reserved_sectors = u32::from(le16(&bs, 14));
+        let num_fats = u32::from(bs[16]);
+        let root_entry_count = u32::from(le16(&bs, 17));
+        let total_sectors = match le16(&bs, 19) {
+            0 => le32(&bs, 32),
+            n => u32::from(n),
+        };
+        let fat_size = match le16(&bs, 22) {
+            0 => le32(&bs, 36),
+            n => u32::from(n),
+        };
+
+        if !matches!(bytes_per_sector, 512 | 1024 | 2048 | 4096)
+            || !sectors_per_cluster.is_power_of_two()
+            || reserved_sectors == 0
+            || num_fats == 0
+            || fat_size == 0
+        {
+            return Err(corrupted());
+        }
+
+        let root_dir_sectors =
+            (root_entry_count * DIR_ENTRY_SIZE as u32).div_ceil(bytes_per_sector);
+        let data_sector = u64::from(reserved_sectors)
+            + u64::from(num_fats) * u64::from(fat_size)
+            + u64::from(root_dir_sectors);
+        let data_sectors = u64::from(total_sectors)
+            .checked_sub(data_sector)
+            .ok_or_else(corrupted)?;
+        let cluster_count = (data_sectors / u64::from(sectors_per_cluster)) as u32;
+        if u64::from(total_sectors) * u64::from(bytes_per_sector) > cache.len() {
+            return Err(corrupted());
+        }
+
+        let fat_type = match cluster_count {
+            0..4085 => FatType::Fat12,
+            4085..65525 => FatType::Fat16,
+            _ => FatType::Fat32,
+        };
+
+        let sector = |n: u64| n * u64::from(bytes_per_sector);
+        let (root, label_offset) = if fat_type == FatType::Fat32 {
+            (Root::Cluster(le32(&bs, 44)), 64)
+        } else {
+            let root = Root::Fixed {
+                offset: sector(
+                    u64::from(reserved_sectors) + u64::from(num_fats) * u64::from(fat_size),
+                ),
+                len: u64::from(root_entry_count) * DIR_ENTRY_SIZE as u64,
+            };
+            (root, 36)
+        };
+
+        // The volume ID and label are only present with the extended boot
+        // signature.
+        let (volume_id, volume_label) = if bs[label_offset + 2] == 0x29 {
+            let label = &bs[label_offset + 7..label_offset + 18];
+            let mut volume_label = CString16::new();
+            if label != b"NO NAME    " {
+                label
+                    .trim_ascii_end()
+                    .iter()
+                    .for_each(|b| volume_label.push(latin1_char(*b)));
+            }
+            let label = volume_label;
+            (le32(&bs, label_offset + 3), label)
+        } else {
+            (0, CString16::new())
+        };
+
+        Ok(Self {
+            cache,
+            fat_type,
+            cluster_size: bytes_per_sector * sectors_per_cluster,
+            cluster_count,
+            fat_offset: sector(u64::from(reserved_sectors)),
+            data_offset: sector(data_sector),
+            root,
+            volume_id,
+            volume_label,
+            position: None,
+        })
+    }
+
+    /// Consumes the file system, returning the underlying device.
+    pub fn into_inner(self) -> D {
+        self.cache.into_inner()
+    }
+
+    /// Returns a reference to the underlying device.
+    pub const fn device(&self) -> &D {
+        self.cache.device()
+    }
+
+    /// The FAT variant of the file system.
+    #[must_use]
+    pub const fn fat_type(&self) -> FatType {
+        self.fat_type
+    }
+
+    /// Volume serial number.
+    #[must_use]
+    pub const fn volume_id(&self) -> u32 {
+        self.volume_id
+    }
+
+    /// Size of a cluster in bytes.
+    #[must_use]
+    pub const fn cluster_size(&self) -> u32 {
+        self.cluster_size
+    }
+
+    fn is_valid_cluster(&self, cluster: u32) -> bool {
+        (2..self.cluster_count.saturating_add(2)).contains(&cluster)
+    }
+
+    /// Look up the cluster after `cluster` in the FAT. Returns `None` at
+    /// the end of the chain.
+    fn next_cluster(&mut self, cluster: u32) -> crate::Result<Option<u32>> {
+        let cluster_offset = u64::from(cluster);
+        let (next, end_of_chain) = match self.fat_type {
+            FatType::Fat12 => {
+                let mut entry = [0; 2];
+                self.cache
+                    .read_at(self.fat_offset + cluster_offset * 3 / 2, &mut entry)?;
+                let entry = u16::from_le_bytes(entry);
+                let next = if cluster % 2 == 0 {
+                    entry & 0xfff
+                } else {
+                    entry >> 4
+                };
+                (u32::from(next), 0xff8)
+            }
+            FatType::Fat16 => {
+                let mut entry = [0; 2];
+                self.cache
+                    .read_at(self.fat_offset + cluster_offset * 2, &mut entry)?;
+                (u32::from(u16::from_le_bytes(entry)), 0xfff8)
+            }
+            FatType::Fat32 => {
+                let mut entry = [0; 4];
+                self.cache
+                    .read_at(self.fat_offset + cluster_offset * 4, &mut entry)?;
+                (u32::from_le_bytes(entry) & 0x0fff_ffff, 0x0fff_fff8)
+            }
+        };
+
+        if next >= end_of_chain {
+            Ok(None)
+        } else if self.is_valid_cluster(next) {
+            Ok(Some(next))
+        } else {
+            Err(Status::VOLUME_CORRUPTED.into())
+        }
+    }
+
+    /// Find the cluster at `index` in the chain starting at
+    /// `first_cluster`. Returns `None` if the chain is shorter.
+    fn cluster_at(&mut self, first_cluster: u32, index: u64) -> crate::Result<Option<u32>> {
+        if !self.is_valid_cluster(first_cluster) {
+            return Err(Status::VOLUME_CORRUPTED.into());
+        }
+
+        let mut position = match self.position {
+            Some(p) if p.first_cluster == first_cluster && p.index <= index => p,
+            _ => ChainPosition {
+                first_cluster,
+                index: 0,
+                cluster: first_cluster,
+            },
+        };
+        while position.index < index {
+            match self.next_cluster(position.cluster)? {
+                Some(next) => {
+                    position.cluster = next;
+                    position.index += 1;
+                }
+                None => return Ok(None),
+            }
+            // A chain cannot be longer than the number of clusters.
+            if position.index > u64::from(self.cluster_count) {
+                return Err(Status::VOLUME_CORRUPTED.into());
+            }
+        }
+        self.position = Some(position);
+        Ok(Some(position.cluster))
+    }
+
+    fn cluster_offset(&self, cluster: u32) -> u64 {
+        self.data_offset + u64::from(cluster - 2) * u64::from(self.cluster_size)
+    }
+
+    /// Read data from a cluster chain at `offset`, up to the end of the
+    /// chain.
+    fn read_chain(
+        &mut self,
+        first_cluster: u32,
+        mut offset: u64,
+        buf: &mut [u8],
+    ) -> crate::Result<usize> {
+        let cluster_size = u64::from(self.cluster_size);
+        let mut read = 0;
+        while read < buf.len() {
+            let Some(cluster) = self.cluster_at(first_cluster, offset / cluster_size)? else {
+                break;
+            };
+            let within = offset % cluster_size;
+            let len = (buf.len() - read).min((cluster_size - within) as usize);
+            self.cache.read_at(
+                self.cluster_offset(cluster) + within,
+                &mut buf[read..read + len],
+            )?;
+            read += len;
+            offset += len as u64;
+        }
+        Ok(read)
+    }
+
+    /// Read the raw contents of a directory.
+    fn read_dir_data(&mut self, dir: &FatNode) -> crate::Result<Vec<u8>> {
+        match (dir, self.root) {
+            (FatNode::FixedRoot, Root::Fixed { offset, len }) => {
+                self.cache.read_vec(offset, len as usize)
+            }
+            (FatNode::FixedRoot, Root::Cluster(_)) => unreachable!(),
+            (FatNode::Chain { first_cluster, .. }, _) => {
+                let mut data = Vec::new();
+                let mut offset = 0;
+                loop {
+                    data.resize(data.len() + self.cluster_size as usize, 0);
+                    let read =
+                        self.read_chain(*first_cluster, offset, &mut data[offset as usize..])?;
+                    if read == 0 {
+                        data.truncate(offset as usize);
+                        return Ok(data);
+                    }
+                    offset += read as u64;
+                }
+            }
+        }
+    }
+}
+
+impl<D: BlockDevice> Volume for FatFileSystem<D> {
+    type Node = FatNode;
+
+    fn root(&self) -> FatNode {
+        match self.root {
+            Root::Fixed { .. } => FatNode::FixedRoot,
+            Root::Cluster(first_cluster) => FatNode::Chain {
+                first_cluster,
+                size: 0,
+            },
+        }
+    }
+
+    fn read_dir_entries(&mut self, dir: &FatNode) -> crate::Result<Vec<DirEntry<FatNode>>> {
+        let data = self.read_dir_data(dir)?;
+        let mut entries = Vec::new();
+        let mut long_name = LongName::default();
+
+        for raw in data.chunks_exact(DIR_ENTRY_SIZE) {
+            let attr = raw[11];
+            match raw[0] {
+                // End of directory.
+                0x00 => break,
+                // Deleted entry.
+                0xe5 => {
+                    long_name.clear();
+                    continue;
+                }
+                _ if attr & 0x3f == ATTR_LONG_NAME => {
+                    long_name.push(raw);
+                    continue;
+                }
+                _ if attr & ATTR_VOLUME_ID != 0 || raw[..2] == *b". " || raw[..3] == *b".. " => {
+                    long_name.clear();
+                    continue;
+                }
+                _ => {}
+            }
+
+            let name = long_name.take(raw).unwrap_or_else(|| {
+                decode_short_name(raw, raw[12] & 0x08 != 0, raw[12] & 0x10 != 0)
+            });
+
+            let mut first_cluster = u32::from(le16(raw, 26));
+            if self.fat_type == FatType::Fat32 {
+                first_cluster |= u32::from(le16(raw, 20)) << 16;
+            }
+            let attribute = FileAttribute::from_bits_truncate(u64::from(attr & 0x37));
+            let file_size = if attribute.contains(FileAttribute::DIRECTORY) {
+                0
+            } else {
+                u64::from(le32(raw, 28))
+            };
+            let cluster_size = u64::from(self.cluster_size);
+
+            entries.push(DirEntry {
+                info: NodeInfo {
+                    name,
+                    attribute,
+                    file_size,
+                    physical_size: file_size.div_ceil(cluster_size) * cluster_size,
+                    create_time: decode_time(le16(raw, 16), le16(raw, 14), raw[13]),
+                    last_access_time: decode_time(le16(raw, 18), 0, 0),
+                    modification_time: decode_time(le16(raw, 24), le16(raw, 22), 0),
+                },
+                node: FatNode::Chain {
+                    first_cluster,
+                    size: file_size,
+                },
+            });
+        }
+        Ok(entries)
+    }
+
+    fn read_file_at(
+        &mut self,
+        file: &FatNode,
+        offset: u64,
+        buf: &mut [u8],
+    ) -> crate::Result<usize> {
+        let FatNode::Chain {
+            first_cluster,
+            size,
+        } = *file
+        else {
+            return Err(Status::INVALID_PARAMETER.into());
+        };
+        let len = size.saturating_sub(offset).min(buf.len() as u64) as usize;
+        if len == 0 {
+            return Ok(0);
+        }
+        let read = self.read_chain(first_cluster, offset, &mut buf[..len])?;
+        if read < len {
+            // The chain is shorter than the file size.
+            return Err(Status::VOLUME_CORRUPTED.into());
+        }
+        Ok(read)
+    }
+}
+
+impl<D: BlockDevice> ReadOnlyFileSystem for FatFileSystem<D> {
+    fn volume_label(&self) -> &CStr16 {
+        &self.volume_label
+    }
+}
+
+impl<D> Debug for FatFileSystem<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FatFileSystem")
+            .field("fat_type", &self.fat_type)
+            .field("cluster_size", &self.cluster_size)
+            .field("cluster_count", &self.cluster_count)
+            .field("volume_id", &self.volume_id)
+            .field("volume_label", &self.volume_label)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disk::IoBlockDevice;
+    use crate::io::{Cursor, Read, Seek, SeekFrom};
+    use crate::{cstr16, CString16};
+    use alloc::vec;
+
+    const SECTOR: usize = 512;
+
+    /// 2024-05-17 12:34:56.
+    const DATE: u16 = ((2024 - 1980) << 9) | (5 << 5) | 17;
+    const TIME: u16 = (12 << 11) | (34 << 5) | (56 / 2);
+
+    /// An in-memory FAT image with one sector per cluster.
+    struct Image {
+        data: Vec<u8>,
+        fat_type: FatType,
+        fat_offset: usize,
+        fat_size: usize,
+        num_fats: usize,
+        root_offset: usize,
+        data_offset: usize,
+    }
+
+    impl Image {
+        fn format(fat_type: FatType) -> Self {
+            // (total sectors, reserved sectors, FATs, sectors per FAT, root entries)
+            let (total, reserved, num_fats, fat_size, root_entries) = match fat_type {
+                FatType::Fat12 => (2048, 1, 2, 6, 512),
+                FatType::Fat16 => (16384, 1, 2, 64, 512),
+                FatType::Fat32 => (32 + 520 + 65600, 32, 1, 520, 0),
+            };
+            let mut data = vec![0; total * SECTOR];
+
+            let bs = &mut data[..SECTOR];
+            bs[..3].copy_from_slice(&[0xeb, 0x3c, 0x90]);
+            bs[3..11].copy_from_slice(b"MSWIN4.1");
+            bs[11..13].copy_from_slice(&(SECTOR as u16).to_le_bytes());
+            bs[13] = 1;
+            bs[14..16].copy_from_slice(&(reserved as u16).to_le_bytes());
+            bs[16] = num_fats as u8;
+            bs[17..19].copy_from_slice(&(root_entries as u16).to_le_bytes());
+            bs[21] = 0xf8;
+            let label_offset = if fat_type == FatType::Fat32 {
+                bs[32..36].copy_from_slice(&(total as u32).to_le_bytes());
+                bs[36..40].copy_from_slice(&(fat_size as u32).to_le_bytes());
+                bs[44..48].copy_from_slice(&2u32.to_le_bytes());
+                64
+            } else {
+                bs[19..21].copy_from_slice(&(total as u16).to_le_bytes());
+                bs[22..24].copy_from_slice(&(fat_size as u16).to_le_bytes());
+                36
+            };
+            bs[label_offset + 2] = 0x29;
+            bs[label_offset + 3..label_offset + 7].copy_from_slice(&0x1234_abcdu32.to_le_bytes());
+            bs[label_offset + 7..label_offset + 18].copy_from_slice(b"TESTVOL    ");
+            bs[510..512].copy_from_slice(&[0x55, 0xaa]);
+
+            let fat_offset = reserved * SECTOR;
+            let root_offset = fat_offset + num_fats * fat_size * SECTOR;
+            let data_offset = root_offset + root_entries * DIR_ENTRY_SIZE;
+            let mut image = Self {
+                data,
+                fat_type,
+                fat_offset,
+                fat_size,
+                num_fats,
+                root_offset,
+                data_offset,
+            };
+            if fat_type == FatType::Fat32 {
+                image.root_offset = image.cluster_offset(2);
+                image.set_fat(2, u32::MAX);
+            }
+            image
+        }
+
+        fn cluster_offset(&self, cluster: u32) -> usize {
+            self.data_offset + (cluster as usize - 2) * SECTOR
+        }
+
+        /// Set a FAT entry in all FATs. `u32::MAX` marks the end of a chain.
+        fn set_fat(&mut self, cluster: u32, value: u32) {
+            let cluster = cluster as usize;
+            for n in 0..self.num_fats {
+                let fat = &mut self.data[self.fat_offset + n * self.fat_size * SECTOR..];
+                match self.fat_type {
+                    FatType::Fat12 => {
+                        let value = (value & 0xfff) as u16;
+                        let offset = cluster * 3 / 2;
+                        let old = u16::from_le_bytes([fat[offset], fat[offset + 1]]);
+                        let new = if cluster % 2 == 0 {
+                            (old & 0xf000) | value
+                        } else {
+                            (old & 0x000f) | (value << 4)
+                        };
+                        fat[offset..offset + 2].copy_from_slice(&new.to_le_bytes());
+                    }
+                    FatType::Fat16 => {
+                        fat[cluster * 2..cluster * 2 + 2]
+                            .copy_from_slice(&(value as u16).to_le_bytes());
+                    }
+                    FatType::Fat32 => {
+                        fat[cluster * 4..cluster * 4 + 4]
+                            .copy_from_slice(&(value & 0x0fff_ffff).to_le_bytes());
+                    }
+                }
+            }
+        }
+
+        /// Write `content` to a chain made of `clusters`.
+        fn write_chain(&mut self, clusters: &[u32], content: &[u8]) {
+            for (i, cluster) in clusters.iter().enumerate() {
+                self.set_fat(*cluster, clusters.get(i + 1).copied().unwrap_or(u32::MAX));
+                let chunk = content.chunks(SECTOR).nth(i).unwrap_or(&[]);
+                let offset = self.cluster_offset(*cluster);
+                self.data[offset..offset + chunk.len()].copy_from_slice(chunk);
+            }
+        }
+
+        fn write_root(&mut self, entries: &[[u8; 32]]) {
+            let offset = self.root_offset;
+            self.data[offset..offset + entries.len() * 32].copy_from_slice(&entries.concat());
+        }
+
+        fn device(self) -> IoBlockDevice<Cursor<Vec<u8>>> {
+            IoBlockDevice::new(Cursor::new(self.data), SECTOR as u32).unwrap()
+        }
+    }
+
+    fn short_entry(name: &[u8; 11], attr: u8, case: u8, cluster: u32, size: u32) -> [u8; 32] {
+        let mut entry = [0; 32];
+        entry[..11].copy_from_slice(name);
+        entry[11] = attr;
+        entry[12] = case;
+        entry[14..16].copy_from_slice(&TIME.to_le_bytes());
+        entry[16..18].copy_from_slice(&DATE.to_le_bytes());
+        entry[18..20].copy_from_slice(&DATE.to_le_bytes());
+        entry[20..22].copy_from_slice(&((cluster >> 16) as u16).to_le_bytes());
+        entry[22..24].copy_from_slice(&TIME.to_le_bytes());
+        entry[24..26].copy_from_slice(&DATE.to_le_bytes());
+        entry[26..28].copy_from_slice(&(cluster as u16).to_le_bytes());
+        entry[28..32].copy_from_slice(&size.to_le_bytes());
+        entry
+    }
+
+    /// Long name entries for `name`, in on-disk order.
+    fn long_entries(name: &str, short_name: &[u8; 11]) -> Vec<[u8; 32]> {
+        let mut units: Vec<u16> = name.encode_utf16().collect();
+        units.push(0);
+        while units.len() % 13 != 0 {
+            units.push(0xffff);
+        }
+        let count = units.len() / 13;
+        (1..=count)
+            .rev()
+            .map(|ordinal| {
+                let mut entry = [0; 32];
+                entry[0] = ordinal as u8 | if ordinal == count { 0x40 } else { 0 };
+                entry[11] = ATTR_LONG_NAME;
+                entry[13] = short_name_checksum(short_name);
+                let offsets = (1..11)
+                    .step_by(2)
+                    .chain((14..26).step_by(2))
+                    .chain((28..32).step_by(2));
+                for (unit, offset) in units[(ordinal - 1) * 13..ordinal * 13].iter().zip(offsets) {
+                    entry[offset..offset + 2].copy_from_slice(&unit.to_le_bytes());
+                }
+                entry
+            })
+            .collect()
+    }
+
+    fn long_file_content() -> Vec<u8> {
+        (0..1300u32).map(|i| (i % 251) as u8).collect()
+    }
+
+    /// Build a volume with a label, a file with a long name stored in
+    /// non-contiguous clusters, a lowercase short name, a deleted entry,
+    /// an empty file and a nested directory.
+    fn build(fat_type: FatType) -> Image {
+        let mut image = Image::format(fat_type);
+
+        let long_short = b"LONGFI~1TXT";
+        let mut root = vec![short_entry(b"TESTVOL    ", ATTR_VOLUME_ID, 0, 0, 0)];
+        root.extend(long_entries("Long File Name.txt", long_short));
+        root.push(short_entry(long_short, 0x20, 0, 5, 1300));
+        root.push(short_entry(b"\xe5ELETED TXT", 0x20, 0, 0, 0));
+        root.push(short_entry(b"README  TXT", 0x21, 0x18, 4, 5));
+        root.push(short_entry(b"EMPTY      ", 0x20, 0, 0, 0));
+        root.push(short_entry(b"EFI        ", 0x10, 0, 10, 0));
+        image.write_root(&root);
+
+        image.write_chain(&[5, 9, 6], &long_file_content());
+        image.write_chain(&[4], b"hello");
+
+        let efi = [
+            short_entry(b".          ", 0x10, 0, 10, 0),
+            short_entry(b"..         ", 0x10, 0, 0, 0),
+            short_entry(b"BOOT       ", 0x10, 0, 11, 0),
+        ];
+        image.write_chain(&[10], &efi.concat());
+
+        // On FAT32, check that the high half of the cluster number is used.
+        let loader_clusters = if fat_type == FatType::Fat32 {
+            [65540, 65541]
+        } else {
+            [12, 13]
+        };
+        let boot = [
+            short_entry(b".          ", 0x10, 0, 11, 0),
+            short_entry(b"..         ", 0x10, 0, 10, 0),
+            short_entry(b"BOOTX64 EFI", 0x20, 0, loader_clusters[0], 600),
+        ];
+        image.write_chain(&[11], &boot.concat());
+        image.write_chain(&loader_clusters, &[0x4d; 600]);
+        image
+    }
+
+    fn check(fat_type: FatType) {
+        let mut fs = FatFileSystem::new(build(fat_type).device()).unwrap();
+        assert_eq!(fs.fat_type(), fat_type);
+        assert_eq!(fs.volume_id(), 0x1234_abcd);
+        assert_eq!(fs.cluster_size(), 512);
+        assert_eq!(fs.volume_label(), cstr16!("TESTVOL"));
+
+        let names: Vec<CString16> = fs
+            .read_dir(cstr16!("\\"))
+            .unwrap()
+            .map(|info| info.file_name().into())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                cstr16!("Long File Name.txt"),
+                cstr16!("readme.txt"),
+                cstr16!("EMPTY"),
+                cstr16!("EFI"),
+            ]
+        );
+
+        assert_eq!(fs.read_to_string(cstr16!("\\readme.txt")).unwrap(), "hello");
+        assert_eq!(
+            fs.read(cstr16!("\\LONG FILE NAME.TXT")).unwrap(),
+            long_file_content()
+        );
+        assert_eq!(fs.read(cstr16!("\\LONGFI~1.TXT")).ok(), None);
+        assert!(fs.read(cstr16!("\\empty")).unwrap().is_empty());
+        assert_eq!(
+            fs.read(cstr16!("\\EFI\\BOOT\\BOOTX64.EFI")).unwrap(),
+            [0x4d; 600]
+        );
+
+        let info = fs.metadata(cstr16!("\\readme.txt")).unwrap();
+        assert_eq!(info.file_size(), 5);
+        assert!(info.attribute().contains(FileAttribute::READ_ONLY));
+        assert_eq!(info.modification_time().year(), 2024);
+        assert_eq!(info.modification_time().second(), 56);
+        let info = fs.metadata(cstr16!("\\EFI\\boot")).unwrap();
+        assert!(info.attribute().contains(FileAttribute::DIRECTORY));
+
+        assert!(fs.try_exists(cstr16!("\\EFI\\BOOT")).unwrap());
+        assert!(!fs.try_exists(cstr16!("\\EFI\\missing")).unwrap());
+        assert!(!fs.try_exists(cstr16!("\\deleted.txt")).unwrap());
+        assert!(fs.read_dir(cstr16!("\\readme.txt")).is_err());
+        assert!(fs.open(cstr16!("\\EFI")).is_err());
+
+        // Read across a cluster boundary after seeking backwards.
+        let mut file = fs.open(cstr16!("\\Long File Name.txt")).unwrap();
+        assert_eq!(file.len(), 1300);
+        file.seek(SeekFrom::Start(1100)).unwrap();
+        let mut buf = [0; 100];
+        file.read_exact(&mut buf).unwrap();
+        file.seek(SeekFrom::Start(500)).unwrap();
+        file.read_exact(&mut buf).unwrap();
+        assert_eq!(buf[..], long_file_content()[500..600]);
+        file.seek(SeekFrom::End(-5)).unwrap();
+        assert_eq!(file.read(&mut [0; 16]).unwrap(), 5);
+        assert_eq!(file.read(&mut [0; 16]).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_fat12() {
+        check(FatType::Fat12);
+    }
+
+    #[test]
+    fn test_fat16() {
+        check(FatType::Fat16);
+    }
+
+    #[test]
+    fn test_fat32() {
+        check(FatType::Fat32);
+    }
+
+    #[test]
+    fn test_corrupted_chain() {
+        let mut image = build(FatType::Fat16);
+        // Point the chain of the long file at a cluster outside the volume.
+        image.set_fat(9, 0xfff0);
+        let mut fs = FatFileSystem::new(image.device()).unwrap();
+        let err = fs.read(cstr16!("\\Long File Name.txt")).unwrap_err();
+        let Error::Io(err) = err else { panic!() };
+        assert_eq!(err.context, IoErrorContext::ReadFailure);
+        assert_eq!(err.uefi_error.status(), Status::VOLUME_CORRUPTED);
+    }
+
+    #[test]
+    fn test_not_fat() {
+        let device = IoBlockDevice::new(Cursor::new(vec![0; 64 * SECTOR]), 512).unwrap();
+        let Err(Error::Io(err)) = FatFileSystem::new(device) else {
+            panic!()
+        };
+        assert_eq!(err.context, IoErrorContext::CantOpenVolume);
+        assert_eq!(err.uefi_error.status(), Status::UNSUPPORTED);
+    }
+}
@@ -0,0 +1,1196 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! GUID Partition Table.
+//!
+//! A GPT disk stores two copies of the partition table: the primary copy
+//! at the start of the disk, with the header at LBA 1, and a backup copy
+//! at the end of the disk, with the header in the last block. Each header
+//! carries a CRC32 of itself and of its partition entry array.
+//!
+//! [`Gpt::read`] validates both copies and falls back to the backup if
+//! the primary copy is damaged.
+//!
+//! A table can also be created with [`Gpt::new`], edited, and written
+//! back with [`Gpt::write`], which updates both copies along with the
+//! protective MBR:
+//!
+//! ```no_run
+//! use uefi::disk::gpt::Gpt;
+//! use uefi::disk::{rescan_partitions, BlockDevice};
+//! use uefi::proto::media::block::BlockIO;
+//! use uefi::proto::media::partition::GptPartitionType;
+//! use uefi::{boot, cstr16, guid, Handle};
+//!
+//! # fn f(disk: Handle) -> Result<(), Box<dyn core::error::Error>> {
+//! {
+//!     // Opening the disk exclusively disconnects the partition driver.
+//!     let mut block_io = boot::open_protocol_exclusive::<BlockIO>(disk)?;
+//!
+//!     let mut gpt = Gpt::new(&*block_io, guid!("c3c0e2a4-2f2a-4f4b-9d7e-3a8c1f0e2b5d"))?;
+//!     gpt.add_partition(
+//!         GptPartitionType::EFI_SYSTEM_PARTITION,
+//!         guid!("0b1f6c52-9a3e-4c0d-8d1a-5e2f7b9c4a31"),
+//!         cstr16!("EFI system partition"),
+//!         Some(512 * 1024 * 1024 / u64::from(block_io.block_size())),
+//!     )?;
+//!     gpt.write(&mut *block_io)?;
+//! }
+//!
+//! // Let the firmware pick up the new partitions.
+//! rescan_partitions(disk)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::mbr::{Mbr, MBR_SIZE};
+use super::{BlockDevice, BlockDeviceMut};
+use crate::data_types::chars::NUL_16;
+use crate::proto::media::block::Lba;
+use crate::proto::media::partition::{GptPartitionAttributes, GptPartitionEntry, GptPartitionType};
+use crate::util::{crc32, crc32_update};
+use crate::{CStr16, Char16, Guid, Status};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use core::ops::RangeInclusive;
+use core::{mem, ptr};
+
+/// LBA of the primary GPT header.
+pub const PRIMARY_HEADER_LBA: Lba = 1;
+
+/// Minimum size of a partition entry array in bytes, as required by the
+/// UEFI specification.
+pub const MIN_ENTRY_ARRAY_SIZE: usize = 16 * 1024;
+
+/// Upper bound on the size of a partition entry array accepted when
+/// reading, to avoid huge allocations for corrupted headers.
+const MAX_ENTRY_ARRAY_SIZE: usize = 4 * 1024 * 1024;
+
+/// Size of the portion of a partition entry described by
+/// [`GptPartitionEntry`].
+const ENTRY_SIZE: usize = mem::size_of::<GptPartitionEntry>();
+
+/// Offset of [`GptHeader::header_crc32`] within the header.
+const HEADER_CRC32_OFFSET: usize = 16;
+
+/// Default alignment of new partitions in bytes.
+pub const DEFAULT_ALIGNMENT: u64 = 1024 * 1024;
+
+/// An entry that is not in use.
+const UNUSED_ENTRY: GptPartitionEntry = GptPartitionEntry {
+    partition_type_guid: GptPartitionType::UNUSED_ENTRY,
+    unique_partition_guid: Guid::ZERO,
+    starting_lba: 0,
+    ending_lba: 0,
+    attributes: GptPartitionAttributes::empty(),
+    partition_name: [NUL_16; 36],
+};
+
+fn is_used(entry: &GptPartitionEntry) -> bool {
+    let partition_type = entry.partition_type_guid;
+    partition_type != GptPartitionType::UNUSED_ENTRY
+}
+
+/// Default partition alignment in blocks for `block_size`.
+fn default_alignment(block_size: u32) -> u64 {
+    (DEFAULT_ALIGNMENT / u64::from(block_size)).max(1)
+}
+
+/// GPT header.
+///
+/// Refer to the UEFI specification (5.3.2 "GPT Header") for details.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GptHeader {
+    /// Must be [`GptHeader::SIGNATURE`].
+    pub signature: u64,
+
+    /// Revision of the GPT format, [`GptHeader::REVISION_1_0`].
+    pub revision: u32,
+
+    /// Size of the header in bytes.
+    pub header_size: u32,
+
+    /// CRC32 of the first `header_size` bytes of the header, computed
+    /// with this field set to zero.
+    pub header_crc32: u32,
+
+    /// Must be zero.
+    pub reserved: u32,
+
+    /// LBA containing this header.
+    pub my_lba: Lba,
+
+    /// LBA containing the other copy of the header.
+    pub alternate_lba: Lba,
+
+    /// First LBA that may be used by a partition.
+    pub first_usable_lba: Lba,
+
+    /// Last LBA that may be used by a partition.
+    pub last_usable_lba: Lba,
+
+    /// Unique identifier of the disk.
+    pub disk_guid: Guid,
+
+    /// Starting LBA of the partition entry array.
+    pub partition_entry_lba: Lba,
+
+    /// Number of entries in the partition entry array.
+    pub number_of_partition_entries: u32,
+
+    /// Size of each partition entry in bytes. A multiple of 128.
+    pub size_of_partition_entry: u32,
+
+    /// CRC32 of the partition entry array.
+    pub partition_entry_array_crc32: u32,
+}
+
+impl GptHeader {
+    /// `"EFI PART"` in little-endian byte order.
+    pub const SIGNATURE: u64 = u64::from_le_bytes(*b"EFI PART");
+
+    /// GPT revision 1.0.
+    pub const REVISION_1_0: u32 = 0x0001_0000;
+
+    /// Size of the header structure in bytes.
+    pub const SIZE: usize = mem::size_of::<Self>();
+
+    /// Read the header from the start of `bytes` without any validation.
+    ///
+    /// Returns `None` if `bytes` is shorter than [`GptHeader::SIZE`].
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes = bytes.get(..Self::SIZE)?;
+        // SAFETY: `bytes` is exactly the size of the header, the header is
+        // packed, and every bit pattern is valid for its fields.
+        Some(unsafe { ptr::read_unaligned(bytes.as_ptr().cast::<Self>()) })
+    }
+
+    /// The raw bytes of the header.
+    #[must_use]
+    pub const fn to_bytes(&self) -> [u8; Self::SIZE] {
+        // SAFETY: the header is packed, so it has no padding bytes.
+        unsafe { mem::transmute_copy(self) }
+    }
+
+    /// Compute the header CRC32 over the first `header_size` bytes of
+    /// `block`, treating the CRC32 field as zero.
+    fn compute_crc32(block: &[u8], header_size: usize) -> u32 {
+        let crc_range = HEADER_CRC32_OFFSET..HEADER_CRC32_OFFSET + 4;
+        let crc = crc32_update(0, &block[..crc_range.start]);
+        let crc = crc32_update(crc, &[0; 4]);
+        crc32_update(crc, &block[crc_range.end..header_size])
+    }
+
+    /// Parse and validate a header read from block `lba` of a disk with
+    /// `num_blocks` blocks.
+    fn parse(block: &[u8], lba: Lba, num_blocks: u64) -> Result<Self, GptError> {
+        let header = Self::from_bytes(block).ok_or(GptError::InvalidBlockSize)?;
+
+        if header.signature != Self::SIGNATURE {
+            return Err(GptError::InvalidSignature);
+        }
+
+        let header_size = header.header_size as usize;
+        if header_size < Self::SIZE || header_size > block.len() {
+            return Err(GptError::InvalidHeaderSize);
+        }
+
+        if Self::compute_crc32(block, header_size) != header.header_crc32 {
+            return Err(GptError::HeaderCrcMismatch);
+        }
+
+        if header.my_lba != lba {
+            return Err(GptError::UnexpectedLba);
+        }
+
+        let (first, last) = (header.first_usable_lba, header.last_usable_lba);
+        if first > last || last >= num_blocks {
+            return Err(GptError::InvalidUsableRange);
+        }
+
+        Ok(header)
+    }
+
+    /// Size of the partition entry array in bytes.
+    fn entry_array_size(&self) -> Result<usize, GptError> {
+        let entry_size = self.size_of_partition_entry as usize;
+        if entry_size < ENTRY_SIZE || entry_size % ENTRY_SIZE != 0 {
+            return Err(GptError::InvalidEntryArray);
+        }
+        (self.number_of_partition_entries as usize)
+            .checked_mul(entry_size)
+            .filter(|size| *size <= MAX_ENTRY_ARRAY_SIZE)
+            .ok_or(GptError::InvalidEntryArray)
+    }
+
+    /// Read and validate the partition entry array described by this
+    /// header.
+    fn read_entries<D: BlockDevice + ?Sized>(
+        &self,
+        device: &mut D,
+    ) -> Result<Vec<GptPartitionEntry>, GptError> {
+        let array_size = self.entry_array_size()?;
+        let block_size = device.block_size() as usize;
+        let num_array_blocks = array_size.div_ceil(block_size);
+
+        let start = self.partition_entry_lba;
+        match start.checked_add(num_array_blocks as u64) {
+            Some(end) if start > PRIMARY_HEADER_LBA && end <= device.num_blocks() => {}
+            _ => return Err(GptError::InvalidEntryArray),
+        }
+
+        let mut buffer = vec![0; num_array_blocks * block_size];
+        device
+            .read_at_lba(start, &mut buffer)
+            .map_err(|err| GptError::Io(err.status()))?;
+
+        let array = &buffer[..array_size];
+        if crc32(array) != self.partition_entry_array_crc32 {
+            return Err(GptError::EntryArrayCrcMismatch);
+        }
+
+        Ok(array
+            .chunks_exact(self.size_of_partition_entry as usize)
+            .map(|entry| {
+                // SAFETY: each chunk is at least the size of an entry, the
+                // entry is packed, and every bit pattern is valid for its
+                // fields.
+                unsafe { ptr::read_unaligned(entry.as_ptr().cast::<GptPartitionEntry>()) }
+            })
+            .collect())
+    }
+
+    /// True if the fields that must agree between the primary and backup
+    /// headers match.
+    fn matches_alternate(&self, alternate: &Self) -> bool {
+        let (a, b) = (self, alternate);
+        a.my_lba == b.alternate_lba
+            && a.alternate_lba == b.my_lba
+            && a.first_usable_lba == b.first_usable_lba
+            && a.last_usable_lba == b.last_usable_lba
+            && { a.disk_guid } == { b.disk_guid }
+            && a.number_of_partition_entries == b.number_of_partition_entries
+            && a.size_of_partition_entry == b.size_of_partition_entry
+            && a.partition_entry_array_crc32 == b.partition_entry_array_crc32
+    }
+}
+
+/// Read a header and its entry array from block `lba`.
+fn read_table<D: BlockDevice + ?Sized>(
+    device: &mut D,
+    lba: Lba,
+) -> Result<(GptHeader, Vec<GptPartitionEntry>), GptError> {
+    let mut block = vec![0; device.block_size() as usize];
+    device
+        .read_at_lba(lba, &mut block)
+        .map_err(|err| GptError::Io(err.status()))?;
+    let header = GptHeader::parse(&block, lba, device.num_blocks())?;
+    let entries = header.read_entries(device)?;
+    Ok((header, entries))
+}
+
+/// Convert `name` to the fixed-size partition name field.
+fn encode_name(name: &CStr16) -> Result<[Char16; 36], GptError> {
+    let chars = name.as_slice();
+    let mut partition_name = [NUL_16; 36];
+    partition_name
+        .get_mut(..chars.len())
+        .ok_or(GptError::NameTooLong)?
+        .copy_from_slice(chars);
+    Ok(partition_name)
+}
+
+/// A GUID Partition Table.
+///
+/// Tables are either read from a disk with [`read`], or created with
+/// [`new`]. Partitions can then be added, removed and resized, and the
+/// table written back with [`write`].
+///
+/// [`new`]: Self::new
+/// [`read`]: Self::read
+/// [`write`]: Self::write
+#[derive(Clone, Debug)]
+pub struct Gpt {
+    header: GptHeader,
+    entries: Vec<GptPartitionEntry>,
+    block_size: u32,
+    alignment: u64,
+    primary_error: Option<GptError>,
+    backup_error: Option<GptError>,
+}
+
+impl Gpt {
+    /// Read the GPT of `device`.
+    ///
+    /// Both the primary and the backup table are read and validated. The
+    /// primary table is used if it is valid, otherwise the backup table
+    /// is used. Problems with either copy are available through
+    /// [`primary_error`] and [`backup_error`].
+    ///
+    /// The protective MBR is not checked; use [`PartitionTable::read`] to
+    /// detect whether the disk uses a GPT at all.
+    ///
+    /// # Errors
+    ///
+    /// * [`GptError::InvalidBlockSize`] if the device blocks are too small
+    ///   to hold a GPT header.
+    /// * The error from the primary table if neither table is valid.
+    ///
+    /// [`primary_error`]: Self::primary_error
+    /// [`backup_error`]: Self::backup_error
+    /// [`PartitionTable::read`]: super::PartitionTable::read
+    pub fn read<D: BlockDevice + ?Sized>(device: &mut D) -> Result<Self, GptError> {
+        let block_size = device.block_size();
+        if (block_size as usize) < MBR_SIZE {
+            return Err(GptError::InvalidBlockSize);
+        }
+
+        let primary = read_table(device, PRIMARY_HEADER_LBA);
+        let backup_lba = match &primary {
+            Ok((header, _)) => header.alternate_lba,
+            Err(_) => device.num_blocks().saturating_sub(1),
+        };
+        let backup = read_table(device, backup_lba);
+
+        let (header, entries, primary_error, backup_error) = match (primary, backup) {
+            (Ok((header, entries)), Ok((backup, _))) => {
+                let backup_error =
+                    (!header.matches_alternate(&backup)).then_some(GptError::HeaderMismatch);
+                (header, entries, None, backup_error)
+            }
+            (Ok((header, entries)), Err(backup_error)) => {
+                (header, entries, None, Some(backup_error))
+            }
+            (Err(primary_error), Ok((header, entries))) => {
+                (header, entries, Some(primary_error), None)
+            }
+            (Err(primary_error), Err(_)) => return Err(primary_error),
+        };
+
+        Ok(Self {
+            header,
+            entries,
+            block_size,
+            alignment: default_alignment(block_size),
+            primary_error,
+            backup_error,
+        })
+    }
+
+    /// Create an empty partition table covering all of `device`.
+    ///
+    /// The table has room for 128 partitions, and the usable range spans
+    /// all blocks between the primary and backup partition entry arrays.
+    /// Nothing is written until [`write`] is called.
+    ///
+    /// # Errors
+    ///
+    /// * [`GptError::InvalidBlockSize`] if the device blocks are too small
+    ///   to hold a GPT header.
+    /// * [`GptError::InvalidUsableRange`] if the device is too small to
+    ///   hold a GPT.
+    ///
+    /// [`write`]: Self::write
+    pub fn new<D: BlockDevice + ?Sized>(device: &D, disk_guid: Guid) -> Result<Self, GptError> {
+        let block_size = device.block_size();
+        if (block_size as usize) < MBR_SIZE {
+            return Err(GptError::InvalidBlockSize);
+        }
+
+        let num_entries = MIN_ENTRY_ARRAY_SIZE / ENTRY_SIZE;
+        let array_blocks = (MIN_ENTRY_ARRAY_SIZE as u64).div_ceil(u64::from(block_size));
+        let first_usable_lba = PRIMARY_HEADER_LBA + 1 + array_blocks;
+        let backup_lba = device.num_blocks().saturating_sub(1);
+        let last_usable_lba = backup_lba
+            .checked_sub(array_blocks + 1)
+            .filter(|lba| *lba >= first_usable_lba)
+            .ok_or(GptError::InvalidUsableRange)?;
+
+        let header = GptHeader {
+            signature: GptHeader::SIGNATURE,
+            revision: GptHeader::REVISION_1_0,
+            header_size: GptHeader::SIZE as u32,
+            header_crc32: 0,
+            reserved: 0,
+            my_lba: PRIMARY_HEADER_LBA,
+            alternate_lba: backup_lba,
+            first_usable_lba,
+            last_usable_lba,
+            disk_guid,
+            partition_entry_lba: PRIMARY_HEADER_LBA + 1,
+            number_of_partition_entries: num_entries as u32,
+            size_of_partition_entry: ENTRY_SIZE as u32,
+            partition_entry_array_crc32: 0,
+        };
+
+        Ok(Self {
+            header,
+            entries: vec![UNUSED_ENTRY; num_entries],
+            block_size,
+            alignment: default_alignment(block_size),
+            primary_error: None,
+            backup_error: None,
+        })
+    }
+
+    /// The header of the table in use. This is the backup header if the
+    /// primary table is invalid.
+    #[must_use]
+    pub const fn header(&self) -> &GptHeader {
+        &self.header
+    }
+
+    /// Unique identifier of the disk.
+    #[must_use]
+    pub const fn disk_guid(&self) -> Guid {
+        self.header.disk_guid
+    }
+
+    /// All partition entries, including unused ones.
+    #[must_use]
+    pub fn entries(&self) -> &[GptPartitionEntry] {
+        &self.entries
+    }
+
+    /// Iterator over the partition entries that are in use, along with
+    /// their index in the partition entry array.
+    pub fn partitions(&self) -> impl Iterator<Item = (usize, &GptPartitionEntry)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| is_used(entry))
+    }
+
+    /// Mutable access to the partition entry at `index`, for example to
+    /// change its name or attributes.
+    ///
+    /// The partition ranges are checked again by [`write`].
+    ///
+    /// [`write`]: Self::write
+    pub fn entry_mut(&mut self, index: usize) -> Option<&mut GptPartitionEntry> {
+        self.entries.get_mut(index)
+    }
+
+    /// Alignment of new partitions, in blocks.
+    ///
+    /// Defaults to [`DEFAULT_ALIGNMENT`] bytes.
+    #[must_use]
+    pub const fn alignment(&self) -> u64 {
+        self.alignment
+    }
+
+    /// Set the alignment of new partitions, in blocks. An alignment of
+    /// zero is treated as one.
+    pub fn set_alignment(&mut self, alignment: u64) {
+        self.alignment = alignment.max(1);
+    }
+
+    /// Unused block ranges within the usable range of the disk, in
+    /// ascending order.
+    #[must_use]
+    pub fn free_regions(&self) -> Vec<RangeInclusive<Lba>> {
+        let (first, last) = (self.header.first_usable_lba, self.header.last_usable_lba);
+        let mut used: Vec<_> = self
+            .partitions()
+            .map(|(_, entry)| (entry.starting_lba, entry.ending_lba))
+            .collect();
+        used.sort_unstable();
+
+        let mut regions = Vec::new();
+        let mut next = first;
+        for (start, end) in used {
+            if start > next && next <= last {
+                regions.push(next..=(start - 1).min(last));
+            }
+            next = next.max(end.saturating_add(1));
+        }
+        if next <= last {
+            regions.push(next..=last);
+        }
+        regions
+    }
+
+    /// Find an aligned range of `num_blocks` blocks in the first free
+    /// region that is large enough, or the largest aligned free range if
+    /// `num_blocks` is `None`.
+    fn find_space(&self, num_blocks: Option<u64>) -> Result<RangeInclusive<Lba>, GptError> {
+        let mut aligned = self.free_regions().into_iter().filter_map(|region| {
+            let start = region.start().checked_next_multiple_of(self.alignment)?;
+            (start <= *region.end()).then(|| start..=*region.end())
+        });
+
+        match num_blocks {
+            Some(0) => Err(GptError::InvalidPartitionRange),
+            Some(num_blocks) => aligned
+                .find_map(|region| {
+                    let end = region.start().checked_add(num_blocks - 1)?;
+                    (end <= *region.end()).then(|| *region.start()..=end)
+                })
+                .ok_or(GptError::NoSpace),
+            None => aligned
+                .reduce(|a, b| {
+                    if b.end() - b.start() > a.end() - a.start() {
+                        b
+                    } else {
+                        a
+                    }
+                })
+                .ok_or(GptError::NoSpace),
+        }
+    }
+
+    /// Add a partition of `num_blocks` blocks in the first free region
+    /// that can hold it. If `num_blocks` is `None`, the partition fills
+    /// the largest free region. The start of the partition is aligned to
+    /// [`alignment`].
+    ///
+    /// Returns the index of the new entry.
+    ///
+    /// # Errors
+    ///
+    /// * [`GptError::InvalidPartitionType`] if `partition_type` is
+    ///   [`GptPartitionType::UNUSED_ENTRY`].
+    /// * [`GptError::NameTooLong`] if `name` is longer than 36 characters.
+    /// * [`GptError::TableFull`] if all entries are in use.
+    /// * [`GptError::InvalidPartitionRange`] if `num_blocks` is zero.
+    /// * [`GptError::NoSpace`] if no free region is large enough.
+    ///
+    /// [`alignment`]: Self::alignment
+    pub fn add_partition(
+        &mut self,
+        partition_type: GptPartitionType,
+        unique_partition_guid: Guid,
+        name: &CStr16,
+        num_blocks: Option<u64>,
+    ) -> Result<usize, GptError> {
+        if partition_type == GptPartitionType::UNUSED_ENTRY {
+            return Err(GptError::InvalidPartitionType);
+        }
+        let partition_name = encode_name(name)?;
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| !is_used(entry))
+            .ok_or(GptError::TableFull)?;
+        let range = self.find_space(num_blocks)?;
+
+        self.entries[index] = GptPartitionEntry {
+            partition_type_guid: partition_type,
+            unique_partition_guid,
+            starting_lba: *range.start(),
+            ending_lba: *range.end(),
+            attributes: GptPartitionAttributes::empty(),
+            partition_name,
+        };
+        Ok(index)
+    }
+
+    /// Remove the partition at `index`, returning its entry.
+    ///
+    /// # Errors
+    ///
+    /// [`GptError::InvalidPartitionIndex`] if there is no partition at
+    /// `index`.
+    pub fn remove_partition(&mut self, index: usize) -> Result<GptPartitionEntry, GptError> {
+        let entry = self.used_entry_mut(index)?;
+        Ok(mem::replace(entry, UNUSED_ENTRY))
+    }
+
+    /// Change the size of the partition at `index` to `num_blocks`
+    /// blocks. The start of the partition does not move.
+    ///
+    /// # Errors
+    ///
+    /// * [`GptError::InvalidPartitionIndex`] if there is no partition at
+    ///   `index`.
+    /// * [`GptError::InvalidPartitionRange`] if `num_blocks` is zero, or
+    ///   the resized partition would overlap another partition or extend
+    ///   past the usable range.
+    pub fn resize_partition(&mut self, index: usize, num_blocks: u64) -> Result<(), GptError> {
+        let start = self.used_entry_mut(index)?.starting_lba;
+        let end = num_blocks
+            .checked_sub(1)
+            .and_then(|n| start.checked_add(n))
+            .filter(|end| *end <= self.header.last_usable_lba)
+            .ok_or(GptError::InvalidPartitionRange)?;
+
+        let overlaps = self
+            .partitions()
+            .any(|(i, other)| i != index && other.starting_lba <= end && other.ending_lba >= start);
+        if overlaps {
+            return Err(GptError::InvalidPartitionRange);
+        }
+
+        self.entries[index].ending_lba = end;
+        Ok(())
+    }
+
+    fn used_entry_mut(&mut self, index: usize) -> Result<&mut GptPartitionEntry, GptError> {
+        self.entries
+            .get_mut(index)
+            .filter(|entry| is_used(entry))
+            .ok_or(GptError::InvalidPartitionIndex)
+    }
+
+    /// Check that all partitions are within the usable range and do not
+    /// overlap.
+    fn check_partitions(&self) -> Result<(), GptError> {
+        let (first, last) = (self.header.first_usable_lba, self.header.last_usable_lba);
+        let mut used: Vec<_> = self
+            .partitions()
+            .map(|(_, entry)| (entry.starting_lba, entry.ending_lba))
+            .collect();
+        used.sort_unstable();
+
+        let in_range = used
+            .iter()
+            .all(|(start, end)| first <= *start && start <= end && *end <= last);
+        let overlapping = used.windows(2).any(|pair| pair[1].0 <= pair[0].1);
+        if !in_range || overlapping {
+            return Err(GptError::InvalidPartitionRange);
+        }
+        Ok(())
+    }
+
+    /// The primary and backup headers to write, without CRCs.
+    fn layout(&self) -> Result<(GptHeader, GptHeader), GptError> {
+        let array_blocks =
+            (self.header.entry_array_size()? as u64).div_ceil(u64::from(self.block_size));
+        let header = GptHeader {
+            revision: GptHeader::REVISION_1_0,
+            header_size: GptHeader::SIZE as u32,
+            reserved: 0,
+            ..self.header
+        };
+
+        let (primary, backup) = if header.my_lba == PRIMARY_HEADER_LBA {
+            let backup_lba = header.alternate_lba;
+            let backup = GptHeader {
+                my_lba: backup_lba,
+                alternate_lba: PRIMARY_HEADER_LBA,
+                partition_entry_lba: backup_lba.saturating_sub(array_blocks),
+                ..header
+            };
+            (header, backup)
+        } else {
+            // The primary table is being restored from the backup.
+            let primary = GptHeader {
+                my_lba: PRIMARY_HEADER_LBA,
+                alternate_lba: header.my_lba,
+                partition_entry_lba: PRIMARY_HEADER_LBA + 1,
+                ..header
+            };
+            (primary, header)
+        };
+
+        // Each entry array must lie between its header and the usable
+        // range.
+        let primary_array_end = primary.partition_entry_lba.saturating_add(array_blocks);
+        let backup_array_end = backup.partition_entry_lba.saturating_add(array_blocks);
+        if primary.partition_entry_lba <= PRIMARY_HEADER_LBA
+            || primary_array_end > header.first_usable_lba
+            || backup.partition_entry_lba <= header.last_usable_lba
+            || backup_array_end > backup.my_lba
+        {
+            return Err(GptError::InvalidEntryArray);
+        }
+
+        Ok((primary, backup))
+    }
+
+    /// Write the table to `device`.
+    ///
+    /// Both the primary and backup copies are written with freshly
+    /// computed CRC32s, the backup first. The first block is updated with
+    /// a protective MBR, keeping any existing boot code. If the table was
+    /// read from a disk with a damaged copy, this repairs it.
+    ///
+    /// Entries larger than 128 bytes are written with the extra bytes
+    /// zeroed.
+    ///
+    /// After writing, the firmware must be told to rescan the disk before
+    /// the new partitions are visible as handles; see
+    /// [`rescan_partitions`].
+    ///
+    /// # Errors
+    ///
+    /// * [`GptError::InvalidBlockSize`] if `device` does not have the
+    ///   block size that the table was created or read with.
+    /// * [`GptError::UnexpectedLba`] if the backup table lies past the end
+    ///   of `device`.
+    /// * [`GptError::InvalidPartitionRange`] if partitions overlap or lie
+    ///   outside the usable range.
+    /// * [`GptError::InvalidEntryArray`] if an entry array would overlap
+    ///   the usable range.
+    /// * [`GptError::Io`] if writing to the device fails.
+    ///
+    /// [`rescan_partitions`]: super::rescan_partitions
+    pub fn write<D: BlockDeviceMut + ?Sized>(&mut self, device: &mut D) -> Result<(), GptError> {
+        if device.block_size() != self.block_size {
+            return Err(GptError::InvalidBlockSize);
+        }
+        self.check_partitions()?;
+        let (mut primary, mut backup) = self.layout()?;
+        if backup.my_lba >= device.num_blocks() {
+            return Err(GptError::UnexpectedLba);
+        }
+
+        let block_size = self.block_size as usize;
+        let entry_size = self.header.size_of_partition_entry as usize;
+        let array_size = self.header.entry_array_size()?;
+        let mut array = vec![0; array_size.div_ceil(block_size) * block_size];
+        for (entry, dst) in self.entries.iter().zip(array.chunks_exact_mut(entry_size)) {
+            // SAFETY: each chunk is at least the size of an entry.
+            unsafe { ptr::write_unaligned(dst.as_mut_ptr().cast::<GptPartitionEntry>(), *entry) }
+        }
+        let array_crc32 = crc32(&array[..array_size]);
+
+        let io_error = |err: crate::Error| GptError::Io(err.status());
+        let mut block = vec![0; block_size];
+        for header in [&mut backup, &mut primary] {
+            header.partition_entry_array_crc32 = array_crc32;
+            header.header_crc32 = 0;
+            header.header_crc32 = crc32(&header.to_bytes());
+
+            device
+                .write_at_lba(header.partition_entry_lba, &array)
+                .map_err(io_error)?;
+            block.fill(0);
+            block[..GptHeader::SIZE].copy_from_slice(&header.to_bytes());
+            device
+                .write_at_lba(header.my_lba, &block)
+                .map_err(io_error)?;
+        }
+
+        device.read_at_lba(0, &mut block).map_err(io_error)?;
+        Mbr::protective(device.num_blocks()).write_to(&mut block);
+        device.write_at_lba(0, &block).map_err(io_error)?;
+        device.flush().map_err(io_error)?;
+
+        self.header = primary;
+        self.primary_error = None;
+        self.backup_error = None;
+        Ok(())
+    }
+
+    /// True if both the primary and backup tables are valid and agree
+    /// with each other.
+    #[must_use]
+    pub const fn is_consistent(&self) -> bool {
+        self.primary_error.is_none() && self.backup_error.is_none()
+    }
+
+    /// Why the primary table could not be used, if it is invalid.
+    #[must_use]
+    pub const fn primary_error(&self) -> Option<GptError> {
+        self.primary_error
+    }
+
+    /// Why the backup table is invalid or disagrees with the primary
+    /// table.
+    #[must_use]
+    pub const fn backup_error(&self) -> Option<GptError> {
+        self.backup_error
+    }
+}
+
+/// Errors that can occur when reading or editing a GPT.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GptError {
+    /// The underlying device returned an error.
+    Io(Status),
+
+    /// The device block size is too small for a GPT.
+    InvalidBlockSize,
+
+    /// The header signature is not `"EFI PART"`.
+    InvalidSignature,
+
+    /// The header size is smaller than the header structure or larger
+    /// than a block.
+    InvalidHeaderSize,
+
+    /// The header CRC32 does not match.
+    HeaderCrcMismatch,
+
+    /// The header's `my_lba` does not match the block it was read from.
+    UnexpectedLba,
+
+    /// The usable block range is empty or extends past the disk.
+    InvalidUsableRange,
+
+    /// The partition entry size, count or location is invalid.
+    InvalidEntryArray,
+
+    /// The partition entry array CRC32 does not match.
+    EntryArrayCrcMismatch,
+
+    /// The primary and backup headers are both valid but disagree.
+    HeaderMismatch,
+
+    /// There is no partition at the given index.
+    InvalidPartitionIndex,
+
+    /// [`GptPartitionType::UNUSED_ENTRY`] cannot be used for a partition.
+    InvalidPartitionType,
+
+    /// A partition is empty, lies outside the usable range, or overlaps
+    /// another partition.
+    InvalidPartitionRange,
+
+    /// The partition name does not fit in 36 UCS-2 characters.
+    NameTooLong,
+
+    /// All partition entries are in use.
+    TableFull,
+
+    /// No free region is large enough for the partition.
+    NoSpace,
+}
+
+impl Display for GptError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(status) => write!(f, "device error: {status}"),
+            Self::InvalidBlockSize => f.write_str("block size too small for GPT"),
+            Self::InvalidSignature => f.write_str("invalid GPT header signature"),
+            Self::InvalidHeaderSize => f.write_str("invalid GPT header size"),
+            Self::HeaderCrcMismatch => f.write_str("GPT header CRC32 mismatch"),
+            Self::UnexpectedLba => f.write_str("GPT header found at unexpected LBA"),
+            Self::InvalidUsableRange => f.write_str("invalid GPT usable block range"),
+            Self::InvalidEntryArray => f.write_str("invalid GPT partition entry array"),
+            Self::EntryArrayCrcMismatch => f.write_str("GPT partition entry array CRC32 mismatch"),
+            Self::HeaderMismatch => f.write_str("primary and backup GPT headers disagree"),
+            Self::InvalidPartitionIndex => f.write_str("no partition at the given index"),
+            Self::InvalidPartitionType => f.write_str("invalid partition type"),
+            Self::InvalidPartitionRange => f.write_str("invalid partition block range"),
+            Self::NameTooLong => f.write_str("partition name too long"),
+            Self::TableFull => f.write_str("no unused partition entries"),
+            Self::NoSpace => f.write_str("not enough free space for partition"),
+        }
+    }
+}
+
+impl core::error::Error for GptError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disk::{IoBlockDevice, PartitionTable};
+    use crate::io::Cursor;
+    use crate::{cstr16, guid};
+
+    const BLOCK_SIZE: usize = 512;
+    const NUM_BLOCKS: u64 = 128;
+    const NUM_ENTRIES: u32 = 128;
+    const DISK_GUID: Guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+
+    fn entry(type_guid: GptPartitionType, start: Lba, end: Lba) -> GptPartitionEntry {
+        GptPartitionEntry {
+            partition_type_guid: type_guid,
+            unique_partition_guid: guid!("11111111-2222-3333-4444-555555555555"),
+            starting_lba: start,
+            ending_lba: end,
+            attributes: GptPartitionAttributes::empty(),
+            partition_name: [Char16::try_from('a').unwrap(); 36],
+        }
+    }
+
+    fn write_header(image: &mut [u8], mut header: GptHeader) {
+        header.header_crc32 = 0;
+        header.header_crc32 = crc32(&header.to_bytes());
+        let offset = header.my_lba as usize * BLOCK_SIZE;
+        image[offset..offset + GptHeader::SIZE].copy_from_slice(&header.to_bytes());
+    }
+
+    /// Build a disk image with a protective MBR and valid primary and
+    /// backup tables.
+    fn disk_image() -> Vec<u8> {
+        let mut image = vec![0; NUM_BLOCKS as usize * BLOCK_SIZE];
+
+        // Protective MBR.
+        image[446 + 4] = 0xee;
+        image[446 + 8..446 + 12].copy_from_slice(&1u32.to_le_bytes());
+        image[446 + 12..446 + 16].copy_from_slice(&(NUM_BLOCKS as u32 - 1).to_le_bytes());
+        image[510] = 0x55;
+        image[511] = 0xaa;
+
+        let mut entries = vec![0; NUM_ENTRIES as usize * ENTRY_SIZE];
+        let used = [
+            entry(GptPartitionType::EFI_SYSTEM_PARTITION, 34, 63),
+            entry(GptPartitionType::LEGACY_MBR, 64, 94),
+        ];
+        for (i, entry) in [(0, used[0]), (2, used[1])] {
+            // SAFETY: the entry is packed and has no padding.
+            let bytes: [u8; ENTRY_SIZE] = unsafe { mem::transmute_copy(&entry) };
+            entries[i * ENTRY_SIZE..(i + 1) * ENTRY_SIZE].copy_from_slice(&bytes);
+        }
+        let entries_blocks = entries.len() / BLOCK_SIZE;
+        let backup_entries_lba = NUM_BLOCKS - 1 - entries_blocks as u64;
+        for lba in [2, backup_entries_lba] {
+            let offset = lba as usize * BLOCK_SIZE;
+            image[offset..offset + entries.len()].copy_from_slice(&entries);
+        }
+
+        let primary = GptHeader {
+            signature: GptHeader::SIGNATURE,
+            revision: GptHeader::REVISION_1_0,
+            header_size: GptHeader::SIZE as u32,
+            header_crc32: 0,
+            reserved: 0,
+            my_lba: 1,
+            alternate_lba: NUM_BLOCKS - 1,
+            first_usable_lba: 2 + entries_blocks as u64,
+            last_usable_lba: backup_entries_lba - 1,
+            disk_guid: DISK_GUID,
+            partition_entry_lba: 2,
+            number_of_partition_entries: NUM_ENTRIES,
+            size_of_partition_entry: ENTRY_SIZE as u32,
+            partition_entry_array_crc32: crc32(&entries),
+        };
+        write_header(&mut image, primary);
+        write_header(
+            &mut image,
+            GptHeader {
+                my_lba: NUM_BLOCKS - 1,
+                alternate_lba: 1,
+                partition_entry_lba: backup_entries_lba,
+                ..primary
+            },
+        );
+        image
+    }
+
+    fn read(image: &[u8]) -> Result<Gpt, GptError> {
+        let mut device = IoBlockDevice::new(Cursor::new(image), BLOCK_SIZE as u32).unwrap();
+        Gpt::read(&mut device)
+    }
+
+    #[test]
+    fn test_header_layout() {
+        assert_eq!(GptHeader::SIZE, 92);
+        assert_eq!(ENTRY_SIZE, 128);
+    }
+
+    #[test]
+    fn test_read_gpt() {
+        let image = disk_image();
+        let gpt = read(&image).unwrap();
+        assert!(gpt.is_consistent());
+        assert_eq!(gpt.disk_guid(), DISK_GUID);
+        assert_eq!({ gpt.header().my_lba }, 1);
+        assert_eq!(gpt.entries().len(), NUM_ENTRIES as usize);
+
+        let partitions: Vec<_> = gpt
+            .partitions()
+            .map(|(i, e)| (i, e.partition_type_guid, e.num_blocks()))
+            .collect();
+        assert_eq!(
+            partitions,
+            [
+                (0, GptPartitionType::EFI_SYSTEM_PARTITION, Some(30)),
+                (2, GptPartitionType::LEGACY_MBR, Some(31)),
+            ]
+        );
+
+        let mut device = IoBlockDevice::new(Cursor::new(&image[..]), BLOCK_SIZE as u32).unwrap();
+        assert!(matches!(
+            PartitionTable::read(&mut device),
+            Ok(Some(PartitionTable::Gpt(_)))
+        ));
+    }
+
+    #[test]
+    fn test_primary_corrupted() {
+        let mut image = disk_image();
+        // Corrupt a byte covered by the primary header CRC.
+        image[BLOCK_SIZE + 40] ^= 1;
+        let gpt = read(&image).unwrap();
+        assert!(!gpt.is_consistent());
+        assert_eq!(gpt.primary_error(), Some(GptError::HeaderCrcMismatch));
+        assert_eq!(gpt.backup_error(), None);
+        assert_eq!({ gpt.header().my_lba }, NUM_BLOCKS - 1);
+        assert_eq!(gpt.partitions().count(), 2);
+    }
+
+    #[test]
+    fn test_entry_array_corrupted() {
+        let mut image = disk_image();
+        // Corrupt the primary entry array.
+        image[2 * BLOCK_SIZE] ^= 1;
+        let gpt = read(&image).unwrap();
+        assert_eq!(gpt.primary_error(), Some(GptError::EntryArrayCrcMismatch));
+        assert_eq!({ gpt.header().my_lba }, NUM_BLOCKS - 1);
+
+        // Corrupt the backup header as well.
+        image[(NUM_BLOCKS as usize - 1) * BLOCK_SIZE] ^= 1;
+        assert_eq!(read(&image).unwrap_err(), GptError::EntryArrayCrcMismatch);
+    }
+
+    #[test]
+    fn test_header_mismatch() {
+        let mut image = disk_image();
+        let offset = (NUM_BLOCKS as usize - 1) * BLOCK_SIZE;
+        let mut backup = GptHeader::from_bytes(&image[offset..]).unwrap();
+        backup.disk_guid = Guid::ZERO;
+        write_header(&mut image, backup);
+
+        let gpt = read(&image).unwrap();
+        assert_eq!(gpt.primary_error(), None);
+        assert_eq!(gpt.backup_error(), Some(GptError::HeaderMismatch));
+        assert_eq!(gpt.disk_guid(), DISK_GUID);
+    }
+
+    fn new_device(num_blocks: usize) -> IoBlockDevice<Cursor<Vec<u8>>> {
+        let image = vec![0; num_blocks * BLOCK_SIZE];
+        IoBlockDevice::new(Cursor::new(image), BLOCK_SIZE as u32).unwrap()
+    }
+
+    fn ranges(gpt: &Gpt) -> Vec<(usize, Lba, Lba)> {
+        gpt.partitions()
+            .map(|(i, e)| (i, e.starting_lba, e.ending_lba))
+            .collect()
+    }
+
+    #[test]
+    fn test_create_gpt() {
+        // 4 MiB disk.
+        let mut device = new_device(8192);
+        let mut gpt = Gpt::new(&device, DISK_GUID).unwrap();
+        assert_eq!({ gpt.header().first_usable_lba }, 34);
+        assert_eq!({ gpt.header().last_usable_lba }, 8158);
+        assert_eq!(gpt.alignment(), 2048);
+        assert_eq!(gpt.free_regions(), vec![34..=8158]);
+
+        let esp = gpt
+            .add_partition(
+                GptPartitionType::EFI_SYSTEM_PARTITION,
+                guid!("11111111-2222-3333-4444-555555555555"),
+                cstr16!("EFI system partition"),
+                Some(2048),
+            )
+            .unwrap();
+        let data = gpt
+            .add_partition(
+                GptPartitionType(guid!("0fc63daf-8483-4772-8e79-3d69d8477de4")),
+                guid!("66666666-7777-8888-9999-aaaaaaaaaaaa"),
+                cstr16!("data"),
+                None,
+            )
+            .unwrap();
+        assert_eq!(ranges(&gpt), [(esp, 2048, 4095), (data, 4096, 8158)]);
+        assert_eq!(gpt.free_regions(), vec![34..=2047]);
+
+        gpt.write(&mut device).unwrap();
+
+        // Read the table back.
+        let mut device =
+            IoBlockDevice::new(Cursor::new(device.into_inner().into_inner()), 512).unwrap();
+        let read = Gpt::read(&mut device).unwrap();
+        assert!(read.is_consistent());
+        assert_eq!(read.disk_guid(), DISK_GUID);
+        assert_eq!(ranges(&read), ranges(&gpt));
+        assert_eq!(read.entries(), gpt.entries());
+        assert_eq!(read.header(), gpt.header());
+
+        let mbr = Mbr::read(&mut device).unwrap().unwrap();
+        assert!(mbr.is_protective());
+        assert!(matches!(
+            PartitionTable::read(&mut device),
+            Ok(Some(PartitionTable::Gpt(_)))
+        ));
+    }
+
+    #[test]
+    fn test_edit_gpt() {
+        let device = new_device(8192);
+        let mut gpt = Gpt::new(&device, DISK_GUID).unwrap();
+        gpt.set_alignment(8);
+        let name = cstr16!("p");
+        let ty = GptPartitionType::EFI_SYSTEM_PARTITION;
+
+        let a = gpt.add_partition(ty, Guid::ZERO, name, Some(100)).unwrap();
+        let b = gpt.add_partition(ty, Guid::ZERO, name, Some(100)).unwrap();
+        assert_eq!(ranges(&gpt), [(a, 40, 139), (b, 144, 243)]);
+
+        // Growing `a` would overlap `b`.
+        assert_eq!(
+            gpt.resize_partition(a, 200),
+            Err(GptError::InvalidPartitionRange)
+        );
+        gpt.resize_partition(a, 104).unwrap();
+        assert_eq!(ranges(&gpt)[0], (a, 40, 143));
+
+        // Free the space at the start, then fill it again.
+        gpt.remove_partition(a).unwrap();
+        assert_eq!(
+            gpt.remove_partition(a),
+            Err(GptError::InvalidPartitionIndex)
+        );
+        let c = gpt.add_partition(ty, Guid::ZERO, name, Some(50)).unwrap();
+        assert_eq!(c, a);
+        assert_eq!(ranges(&gpt), [(c, 40, 89), (b, 144, 243)]);
+
+        // Shrinking and growing to the end of the usable range.
+        gpt.resize_partition(b, 8158 - 144 + 1).unwrap();
+        assert_eq!(
+            gpt.resize_partition(b, 8158 - 144 + 2),
+            Err(GptError::InvalidPartitionRange)
+        );
+        assert_eq!(
+            gpt.add_partition(ty, Guid::ZERO, name, Some(60)),
+            Err(GptError::NoSpace)
+        );
+
+        assert_eq!(
+            gpt.add_partition(GptPartitionType::UNUSED_ENTRY, Guid::ZERO, name, None),
+            Err(GptError::InvalidPartitionType)
+        );
+        assert_eq!(
+            gpt.add_partition(
+                ty,
+                Guid::ZERO,
+                cstr16!("a partition name that is far too long"),
+                None
+            ),
+            Err(GptError::NameTooLong)
+        );
+        assert_eq!(
+            gpt.add_partition(ty, Guid::ZERO, name, Some(0)),
+            Err(GptError::InvalidPartitionRange)
+        );
+
+        // Partitions edited directly are checked on write.
+        let mut device = device;
+        gpt.entry_mut(c).unwrap().ending_lba = 150;
+        assert_eq!(gpt.write(&mut device), Err(GptError::InvalidPartitionRange));
+    }
+
+    #[test]
+    fn test_repair_gpt() {
+        let mut image = disk_image();
+        image[BLOCK_SIZE + 40] ^= 1;
+        let mut device = IoBlockDevice::new(Cursor::new(image), BLOCK_SIZE as u32).unwrap();
+
+        let mut gpt = Gpt::read(&mut device).unwrap();
+        assert_eq!(gpt.primary_error(), Some(GptError::HeaderCrcMismatch));
+        gpt.write(&mut device).unwrap();
+        assert!(gpt.is_consistent());
+        assert_eq!({ gpt.header().my_lba }, 1);
+
+        let read = Gpt::read(&mut device).unwrap();
+        assert!(read.is_consistent());
+        assert_eq!(read.partitions().count(), 2);
+    }
+
+    #[test]
+    fn test_no_gpt() {
+        let image = vec![0; NUM_BLOCKS as usize * BLOCK_SIZE];
+        assert_eq!(read(&image).unwrap_err(), GptError::InvalidSignature);
+
+        let mut device = IoBlockDevice::new(Cursor::new(&image[..]), BLOCK_SIZE as u32).unwrap();
+        assert!(PartitionTable::read(&mut device).unwrap().is_none());
+    }
+}
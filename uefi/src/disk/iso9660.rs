@@ -0,0 +1,729 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Read-only ISO 9660 file system, with Joliet long names and El Torito
+//! boot catalog parsing.
+//!
+//! Rock Ridge extensions and multi-extent files are not supported. If a
+//! Joliet volume descriptor is present, its directory tree is used, so
+//! that file names keep their case and are not limited to 8.3.
+//!
+//! The EFI boot image of a bootable CD is usually a FAT image. It can be
+//! located with [`IsoFileSystem::boot_images`] and mounted with
+//! [`FatFileSystem`]:
+//!
+//! ```no_run
+//! use uefi::disk::fat::FatFileSystem;
+//! use uefi::disk::iso9660::{BootPlatform, IsoFileSystem};
+//! use uefi::disk::{BlockDevice, BlockRange, ReadOnlyFileSystem};
+//! use uefi::proto::media::block::BlockIO;
+//! use uefi::{boot, cstr16, Handle};
+//!
+//! # fn f(cd: Handle) -> Result<(), Box<dyn core::error::Error>> {
+//! let mut block_io = boot::open_protocol_exclusive::<BlockIO>(cd)?;
+//! let mut iso = IsoFileSystem::new(&mut *block_io)?;
+//! let image = iso
+//!     .boot_images()?
+//!     .into_iter()
+//!     .find(|image| image.platform == BootPlatform::EFI)
+//!     .unwrap();
+//!
+//! let block_io = iso.into_inner();
+//! let start = image.offset / u64::from(block_io.block_size());
+//! let num_blocks = block_io.num_blocks() - start;
+//! let mut esp = FatFileSystem::new(BlockRange::new(block_io, start, num_blocks)?)?;
+//! let loader = esp.read(cstr16!("\\EFI\\BOOT\\BOOTX64.EFI"))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`FatFileSystem`]: super::fat::FatFileSystem
+
+use super::volume::{BlockCache, DirEntry, ReadOnlyFileSystem, Volume};
+use super::BlockDevice;
+use crate::data_types::chars::NUL_16;
+use crate::fs::{Error, FileSystemResult, IoError, IoErrorContext, NodeInfo, PathBuf};
+use crate::proto::media::file::FileAttribute;
+use crate::runtime::{Daylight, Time, TimeParams};
+use crate::{CStr16, CString16, Char16, Status};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
+
+/// Size of a CD-ROM sector, which is the unit of volume descriptors and
+/// El Torito boot catalog addresses.
+const SECTOR_SIZE: u64 = 2048;
+
+/// Sector of the first volume descriptor.
+const FIRST_DESCRIPTOR_SECTOR: u64 = 16;
+
+/// Upper bound on the number of volume descriptors that are read.
+const MAX_DESCRIPTORS: u64 = 64;
+
+/// Size of the virtual sectors used by El Torito sector counts.
+const VIRTUAL_SECTOR_SIZE: u64 = 512;
+
+/// Directory record file flag: the entry is hidden.
+const FLAG_HIDDEN: u8 = 0x01;
+
+/// Directory record file flag: the entry is a directory.
+const FLAG_DIRECTORY: u8 = 0x02;
+
+/// Location of a file or directory.
+#[derive(Clone, Debug)]
+pub struct IsoNode {
+    /// Byte offset of the data on the volume.
+    pub offset: u64,
+    /// Size of the data in bytes.
+    pub size: u64,
+}
+
+newtype_enum! {
+    /// Platform of an El Torito boot image.
+    pub enum BootPlatform: u8 => {
+        /// 80x86 BIOS.
+        X86 = 0x00,
+        /// PowerPC.
+        POWER_PC = 0x01,
+        /// Mac.
+        MAC = 0x02,
+        /// UEFI.
+        EFI = 0xef,
+    }
+}
+
+/// A boot image from the El Torito boot catalog.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BootImage {
+    /// Platform the image is meant for.
+    pub platform: BootPlatform,
+    /// Whether the entry is marked bootable.
+    pub bootable: bool,
+    /// Emulated media type. Zero means no emulation.
+    pub media_type: u8,
+    /// Byte offset of the image on the volume.
+    pub offset: u64,
+    /// Size of the image in bytes, from the boot catalog. Firmware often
+    /// ignores this for EFI images, which may have a size of zero or one
+    /// sector here; the real size must then be taken from the image
+    /// itself.
+    pub size: u64,
+}
+
+/// A read-only ISO 9660 file system on a [`BlockDevice`].
+///
+/// The device must start at the beginning of the volume. The file system
+/// is accessed through the [`ReadOnlyFileSystem`] trait.
+pub struct IsoFileSystem<D> {
+    cache: BlockCache<D>,
+    block_size: u64,
+    root: IsoNode,
+    joliet: bool,
+    volume_label: CString16,
+    boot_catalog: Option<u64>,
+}
+
+const fn le16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn le32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn mount_error(status: Status) -> Error {
+    Error::Io(IoError {
+        path: PathBuf::new(),
+        context: IoErrorContext::CantOpenVolume,
+        uefi_error: status.into(),
+    })
+}
+
+/// Decode a name: UCS-2 big endian for Joliet, otherwise bytes taken as
+/// Latin-1. Trailing spaces are removed.
+fn decode_name(bytes: &[u8], joliet: bool) -> CString16 {
+    let mut name = CString16::new();
+    let replacement = Char16::try_from('?').unwrap();
+    // Null characters cannot be part of a `CString16`.
+    let decode = |c: u16| match Char16::try_from(c) {
+        Ok(c) if c != NUL_16 => c,
+        _ => replacement,
+    };
+    if joliet {
+        bytes
+            .chunks_exact(2)
+            .map(|c| decode(u16::from_be_bytes([c[0], c[1]])))
+            .for_each(|c| name.push(c));
+    } else {
+        bytes
+            .iter()
+            .map(|b| decode(u16::from(*b)))
+            .for_each(|c| name.push(c));
+    }
+
+    let trimmed = name.as_slice().iter().rposition(|c| *c != ' ');
+    let mut result = CString16::new();
+    name.as_slice()[..trimmed.map_or(0, |i| i + 1)]
+        .iter()
+        .for_each(|c| result.push(*c));
+    result
+}
+
+/// Remove the `;1` version suffix from a file identifier, and the trailing
+/// dot of names without an extension.
+fn strip_version(name: &CStr16) -> CString16 {
+    let chars = name.as_slice();
+    let end = chars.iter().position(|c| *c == ';').unwrap_or(chars.len());
+    let chars = &chars[..end];
+    let chars = chars
+        .strip_suffix(&[Char16::try_from('.').unwrap()])
+        .unwrap_or(chars);
+    let mut result = CString16::new();
+    chars.iter().for_each(|c| result.push(*c));
+    result
+}
+
+/// Convert a 7-byte directory record date to a [`Time`].
+fn decode_time(raw: &[u8]) -> Time {
+    if raw[..6].iter().all(|b| *b == 0) {
+        return Time::invalid();
+    }
+    Time::new(TimeParams {
+        year: 1900 + u16::from(raw[0]),
+        month: raw[1],
+        day: raw[2],
+        hour: raw[3],
+        minute: raw[4],
+        second: raw[5],
+        nanosecond: 0,
+        // Offset from GMT in 15 minute intervals.
+        time_zone: Some(i16::from(raw[6] as i8) * 15),
+        daylight: Daylight::empty(),
+    })
+    .unwrap_or_else(|_| Time::invalid())
+}
+
+impl<D: BlockDevice> IsoFileSystem<D> {
+    /// Mount the ISO 9660 file system on `device`.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Io`] with [`IoErrorContext::CantOpenVolume`] if the volume
+    /// descriptors cannot be read or no primary volume descriptor is found.
+    pub fn new(device: D) -> FileSystemResult<Self> {
+        let mut cache = BlockCache::new(device);
+        let mut primary = None;
+        let mut joliet = None;
+        let mut boot_catalog = None;
+
+        for sector in FIRST_DESCRIPTOR_SECTOR..FIRST_DESCRIPTOR_SECTOR + MAX_DESCRIPTORS {
+            let offset = sector * SECTOR_SIZE;
+            if offset + SECTOR_SIZE > cache.len() {
+                break;
+            }
+            let descriptor = cache
+                .read_vec(offset, SECTOR_SIZE as usize)
+                .map_err(|err| mount_error(err.status()))?;
+            if &descriptor[1..6] != b"CD001" {
+                return Err(mount_error(Status::UNSUPPORTED));
+            }
+
+            match descriptor[0] {
+                // Boot record.
+                0 if descriptor[7..30] == *b"EL TORITO SPECIFICATION" => {
+                    boot_catalog = Some(u64::from(le32(&descriptor, 71)) * SECTOR_SIZE);
+                }
+                // Primary volume descriptor.
+                1 => primary = primary.or(Some(descriptor)),
+                // Supplementary volume descriptor with a Joliet escape
+                // sequence for UCS-2 level 1, 2 or 3.
+                2 if matches!(&descriptor[88..91], b"%/@" | b"%/C" | b"%/E") => {
+                    joliet = joliet.or(Some(descriptor));
+                }
+                // Terminator.
+                255 => break,
+                _ => {}
+            }
+        }
+
+        let is_joliet = joliet.is_some();
+        let descriptor = joliet
+            .or(primary)
+            .ok_or_else(|| mount_error(Status::UNSUPPORTED))?;
+
+        let block_size = u64::from(le16(&descriptor, 128));
+        if !block_size.is_power_of_two() || block_size < 512 {
+            return Err(mount_error(Status::VOLUME_CORRUPTED));
+        }
+
+        let root_record = &descriptor[156..190];
+        let root = IsoNode {
+            offset: u64::from(le32(root_record, 2)) * block_size,
+            size: u64::from(le32(root_record, 10)),
+        };
+
+        Ok(Self {
+            cache,
+            block_size,
+            root,
+            joliet: is_joliet,
+            volume_label: decode_name(&descriptor[40..72], is_joliet),
+            boot_catalog,
+        })
+    }
+
+    /// Consumes the file system, returning the underlying device.
+    pub fn into_inner(self) -> D {
+        self.cache.into_inner()
+    }
+
+    /// Returns a reference to the underlying device.
+    pub const fn device(&self) -> &D {
+        self.cache.device()
+    }
+
+    /// Whether the Joliet directory tree is used.
+    #[must_use]
+    pub const fn is_joliet(&self) -> bool {
+        self.joliet
+    }
+
+    /// Read the El Torito boot catalog. Returns an empty list if the volume
+    /// is not bootable.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Io`] with [`IoErrorContext::ReadFailure`] if the catalog
+    /// cannot be read or is invalid.
+    pub fn boot_images(&mut self) -> FileSystemResult<Vec<BootImage>> {
+        let Some(offset) = self.boot_catalog else {
+            return Ok(Vec::new());
+        };
+        let read_error = |status: Status| {
+            Error::Io(IoError {
+                path: PathBuf::new(),
+                context: IoErrorContext::ReadFailure,
+                uefi_error: status.into(),
+            })
+        };
+        let catalog = self
+            .cache
+            .read_vec(offset, SECTOR_SIZE as usize)
+            .map_err(|err| read_error(err.status()))?;
+
+        // The validation entry checksums to zero and ends with 0x55 0xaa.
+        let validation = &catalog[..32];
+        let checksum = (0..32)
+            .step_by(2)
+            .fold(0u16, |sum, i| sum.wrapping_add(le16(validation, i)));
+        if validation[0] != 1 || validation[30..32] != [0x55, 0xaa] || checksum != 0 {
+            return Err(read_error(Status::VOLUME_CORRUPTED));
+        }
+
+        let image = |platform: BootPlatform, entry: &[u8]| BootImage {
+            platform,
+            bootable: entry[0] == 0x88,
+            media_type: entry[1] & 0x0f,
+            offset: u64::from(le32(entry, 8)) * SECTOR_SIZE,
+            size: u64::from(le16(entry, 6)) * VIRTUAL_SECTOR_SIZE,
+        };
+
+        // The initial entry, followed by sections of further entries.
+        let mut images = alloc::vec![image(BootPlatform(validation[1]), &catalog[32..64])];
+        let mut entries = catalog[64..].chunks_exact(32);
+        while let Some(header) = entries.next() {
+            // 0x90: section header, 0x91: final section header.
+            if !matches!(header[0], 0x90 | 0x91) {
+                break;
+            }
+            let platform = BootPlatform(header[1]);
+            for _ in 0..le16(header, 2) {
+                let Some(entry) = entries.next() else {
+                    break;
+                };
+                images.push(image(platform, entry));
+            }
+            if header[0] == 0x91 {
+                break;
+            }
+        }
+        Ok(images)
+    }
+
+    /// Parse the directory records in one block of a directory.
+    fn parse_dir_block(&self, data: &[u8], entries: &mut Vec<DirEntry<IsoNode>>) -> crate::Result {
+        let mut pos = 0;
+        // Records do not cross block boundaries; a zero length means the
+        // rest of the block is padding.
+        while let Some(&len) = data.get(pos).filter(|&&len| len != 0) {
+            let len = usize::from(len);
+            let record = data
+                .get(pos..pos + len)
+                .filter(|r| r.len() >= 33)
+                .ok_or(Status::VOLUME_CORRUPTED)?;
+            pos += len;
+
+            let name_len = usize::from(record[32]);
+            let raw_name = record
+                .get(33..33 + name_len)
+                .ok_or(Status::VOLUME_CORRUPTED)?;
+            // Skip the `.` and `..` entries.
+            if raw_name == [0] || raw_name == [1] {
+                continue;
+            }
+
+            let flags = record[25];
+            let is_dir = flags & FLAG_DIRECTORY != 0;
+            let name = decode_name(raw_name, self.joliet);
+            let name = if is_dir { name } else { strip_version(&name) };
+
+            let mut attribute = FileAttribute::READ_ONLY;
+            if is_dir {
+                attribute |= FileAttribute::DIRECTORY;
+            }
+            if flags & FLAG_HIDDEN != 0 {
+                attribute |= FileAttribute::HIDDEN;
+            }
+
+            let size = u64::from(le32(record, 10));
+            let time = decode_time(&record[18..25]);
+            entries.push(DirEntry {
+                info: NodeInfo {
+                    name,
+                    attribute,
+                    file_size: if is_dir { 0 } else { size },
+                    physical_size: size.div_ceil(self.block_size) * self.block_size,
+                    create_time: time,
+                    last_access_time: time,
+                    modification_time: time,
+                },
+                node: IsoNode {
+                    offset: u64::from(le32(record, 2)) * self.block_size,
+                    size,
+                },
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<D: BlockDevice> Volume for IsoFileSystem<D> {
+    type Node = IsoNode;
+
+    fn root(&self) -> IsoNode {
+        self.root.clone()
+    }
+
+    fn read_dir_entries(&mut self, dir: &IsoNode) -> crate::Result<Vec<DirEntry<IsoNode>>> {
+        // The size comes from the disk, so read one block at a time rather
+        // than allocating it in one go.
+        let mut block = vec![0; self.block_size as usize];
+        let mut entries = Vec::new();
+        let mut block_offset = 0;
+        while block_offset < dir.size {
+            let len = (dir.size - block_offset).min(self.block_size) as usize;
+            let data = &mut block[..len];
+            self.cache.read_at(dir.offset + block_offset, data)?;
+            block_offset += self.block_size;
+            self.parse_dir_block(data, &mut entries)?;
+        }
+        Ok(entries)
+    }
+
+    fn read_file_at(
+        &mut self,
+        file: &IsoNode,
+        offset: u64,
+        buf: &mut [u8],
+    ) -> crate::Result<usize> {
+        let len = file.size.saturating_sub(offset).min(buf.len() as u64) as usize;
+        self.cache.read_at(file.offset + offset, &mut buf[..len])?;
+        Ok(len)
+    }
+}
+
+impl<D: BlockDevice> ReadOnlyFileSystem for IsoFileSystem<D> {
+    fn volume_label(&self) -> &CStr16 {
+        &self.volume_label
+    }
+}
+
+impl<D> Debug for IsoFileSystem<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IsoFileSystem")
+            .field("block_size", &self.block_size)
+            .field("joliet", &self.joliet)
+            .field("volume_label", &self.volume_label)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disk::IoBlockDevice;
+    use crate::io::{Cursor, Read, Seek, SeekFrom};
+    use crate::{cstr16, CString16};
+    use alloc::vec;
+
+    const SECTOR: usize = SECTOR_SIZE as usize;
+
+    fn both16(value: u16) -> [u8; 4] {
+        let (le, be) = (value.to_le_bytes(), value.to_be_bytes());
+        [le[0], le[1], be[0], be[1]]
+    }
+
+    fn both32(value: u32) -> [u8; 8] {
+        let mut bytes = [0; 8];
+        bytes[..4].copy_from_slice(&value.to_le_bytes());
+        bytes[4..].copy_from_slice(&value.to_be_bytes());
+        bytes
+    }
+
+    fn ucs2(name: &str) -> Vec<u8> {
+        name.encode_utf16().flat_map(u16::to_be_bytes).collect()
+    }
+
+    fn record(extent: u32, size: u32, flags: u8, name: &[u8]) -> Vec<u8> {
+        let len = (33 + name.len()).next_multiple_of(2);
+        let mut record = vec![0; len];
+        record[0] = len as u8;
+        record[2..10].copy_from_slice(&both32(extent));
+        record[10..18].copy_from_slice(&both32(size));
+        // 2024-05-17 12:34:56, UTC+2.
+        record[18..25].copy_from_slice(&[124, 5, 17, 12, 34, 56, 8]);
+        record[25] = flags;
+        record[28..32].copy_from_slice(&both16(1));
+        record[32] = name.len() as u8;
+        record[33..33 + name.len()].copy_from_slice(name);
+        record
+    }
+
+    /// Directory data with `.` and `..` records.
+    fn directory(extent: u32, parent: u32, records: &[Vec<u8>]) -> Vec<u8> {
+        let mut data = record(extent, SECTOR as u32, FLAG_DIRECTORY, &[0]);
+        data.extend(record(parent, SECTOR as u32, FLAG_DIRECTORY, &[1]));
+        records.iter().for_each(|r| data.extend(r));
+        data
+    }
+
+    fn descriptor(ty: u8) -> Vec<u8> {
+        let mut descriptor = vec![0; SECTOR];
+        descriptor[0] = ty;
+        descriptor[1..6].copy_from_slice(b"CD001");
+        descriptor[6] = 1;
+        descriptor
+    }
+
+    fn volume_descriptor(ty: u8, label: &[u8], root: u32, root_size: u32) -> Vec<u8> {
+        let mut descriptor = descriptor(ty);
+        descriptor[40..72].fill(b' ');
+        descriptor[40..40 + label.len()].copy_from_slice(label);
+        descriptor[80..88].copy_from_slice(&both32(30));
+        descriptor[128..132].copy_from_slice(&both16(SECTOR as u16));
+        descriptor[156..190].copy_from_slice(&record(root, root_size, FLAG_DIRECTORY, &[0]));
+        descriptor
+    }
+
+    fn big_file_content() -> Vec<u8> {
+        (0..3000u32).map(|i| (i % 253) as u8).collect()
+    }
+
+    /// Build an image with a primary and, if `joliet` is set, a Joliet
+    /// directory tree, and a boot catalog with a BIOS and an EFI image.
+    fn build(joliet: bool) -> Vec<u8> {
+        let mut image = vec![0; 30 * SECTOR];
+        let mut write = |sector: usize, data: &[u8]| {
+            image[sector * SECTOR..sector * SECTOR + data.len()].copy_from_slice(data);
+        };
+
+        write(16, &volume_descriptor(1, b"TEST_ISO", 21, SECTOR as u32));
+        let mut boot_record = descriptor(0);
+        boot_record[7..30].copy_from_slice(b"EL TORITO SPECIFICATION");
+        boot_record[71..75].copy_from_slice(&20u32.to_le_bytes());
+        write(17, &boot_record);
+        let mut svd = volume_descriptor(2, &ucs2("Test ISO"), 22, 2 * SECTOR as u32);
+        for c in svd[40..72].chunks_exact_mut(2).skip(8) {
+            c.copy_from_slice(&[0, b' ']);
+        }
+        if joliet {
+            svd[88..91].copy_from_slice(b"%/E");
+        }
+        write(18, &svd);
+        write(19, &descriptor(255));
+
+        // Boot catalog.
+        let mut catalog = vec![0; 128];
+        catalog[0] = 1;
+        catalog[4..12].copy_from_slice(b"uefi-rs ");
+        catalog[30..32].copy_from_slice(&[0x55, 0xaa]);
+        let sum = (0..32)
+            .step_by(2)
+            .fold(0u16, |sum, i| sum.wrapping_add(le16(&catalog, i)));
+        catalog[28..30].copy_from_slice(&sum.wrapping_neg().to_le_bytes());
+        catalog[32] = 0x88;
+        catalog[38..40].copy_from_slice(&4u16.to_le_bytes());
+        catalog[40..44].copy_from_slice(&27u32.to_le_bytes());
+        catalog[64] = 0x91;
+        catalog[65] = 0xef;
+        catalog[66..68].copy_from_slice(&1u16.to_le_bytes());
+        catalog[96] = 0x88;
+        catalog[102..104].copy_from_slice(&1u16.to_le_bytes());
+        catalog[104..108].copy_from_slice(&27u32.to_le_bytes());
+        write(20, &catalog);
+
+        // Primary tree.
+        write(
+            21,
+            &directory(
+                21,
+                21,
+                &[
+                    record(24, SECTOR as u32, FLAG_DIRECTORY, b"EFI"),
+                    record(28, 3000, 0, b"BIG.BIN;1"),
+                    record(26, 5, 0, b"README.TXT;1"),
+                ],
+            ),
+        );
+        write(24, &directory(24, 21, &[record(26, 5, 0, b"NOTES.;1")]));
+
+        // Joliet tree. The root directory spans two sectors, with the last
+        // record at the start of the second one.
+        write(
+            22,
+            &directory(
+                22,
+                22,
+                &[
+                    record(25, SECTOR as u32, FLAG_DIRECTORY, &ucs2("EFI")),
+                    record(26, 5, 0, &ucs2("ReadMe.txt;1")),
+                ],
+            ),
+        );
+        write(23, &record(28, 3000, FLAG_HIDDEN, &ucs2("big.bin;1")));
+        write(25, &directory(25, 22, &[record(26, 5, 0, &ucs2("Notes"))]));
+
+        write(26, b"hello");
+        write(27, &[0xef; SECTOR]);
+        write(28, &big_file_content());
+        image
+    }
+
+    fn mount(joliet: bool) -> IsoFileSystem<IoBlockDevice<Cursor<Vec<u8>>>> {
+        let device = IoBlockDevice::new(Cursor::new(build(joliet)), 512).unwrap();
+        IsoFileSystem::new(device).unwrap()
+    }
+
+    fn names(fs: &mut impl ReadOnlyFileSystem, path: &CStr16) -> Vec<CString16> {
+        fs.read_dir(path)
+            .unwrap()
+            .map(|info| info.file_name().into())
+            .collect()
+    }
+
+    #[test]
+    fn test_joliet() {
+        let mut fs = mount(true);
+        assert!(fs.is_joliet());
+        assert_eq!(fs.volume_label(), cstr16!("Test ISO"));
+        assert_eq!(
+            names(&mut fs, cstr16!("\\")),
+            [cstr16!("EFI"), cstr16!("ReadMe.txt"), cstr16!("big.bin")]
+        );
+        assert_eq!(names(&mut fs, cstr16!("\\efi")), [cstr16!("Notes")]);
+
+        assert_eq!(fs.read_to_string(cstr16!("\\readme.TXT")).unwrap(), "hello");
+        assert_eq!(fs.read(cstr16!("\\EFI\\Notes")).unwrap(), b"hello");
+
+        let info = fs.metadata(cstr16!("\\big.bin")).unwrap();
+        assert_eq!(info.file_size(), 3000);
+        assert_eq!(
+            info.attribute(),
+            FileAttribute::READ_ONLY | FileAttribute::HIDDEN
+        );
+        assert_eq!(info.modification_time().year(), 2024);
+        assert_eq!(info.modification_time().time_zone(), Some(120));
+        let info = fs.metadata(cstr16!("\\EFI")).unwrap();
+        assert!(info.attribute().contains(FileAttribute::DIRECTORY));
+
+        let mut file = fs.open(cstr16!("\\big.bin")).unwrap();
+        file.seek(SeekFrom::Start(2000)).unwrap();
+        let mut buf = [0; 100];
+        file.read_exact(&mut buf).unwrap();
+        assert_eq!(buf[..], big_file_content()[2000..2100]);
+        assert_eq!(fs.read(cstr16!("\\big.bin")).unwrap(), big_file_content());
+
+        assert!(!fs.try_exists(cstr16!("\\missing")).unwrap());
+    }
+
+    #[test]
+    fn test_primary() {
+        let mut fs = mount(false);
+        assert!(!fs.is_joliet());
+        assert_eq!(fs.volume_label(), cstr16!("TEST_ISO"));
+        assert_eq!(
+            names(&mut fs, cstr16!("\\")),
+            [cstr16!("EFI"), cstr16!("BIG.BIN"), cstr16!("README.TXT")]
+        );
+        assert_eq!(names(&mut fs, cstr16!("\\EFI")), [cstr16!("NOTES")]);
+        assert_eq!(fs.read_to_string(cstr16!("\\readme.txt")).unwrap(), "hello");
+        assert_eq!(fs.read(cstr16!("\\BIG.BIN")).unwrap(), big_file_content());
+        let info = fs.metadata(cstr16!("\\BIG.BIN")).unwrap();
+        assert!(!info.attribute().contains(FileAttribute::HIDDEN));
+    }
+
+    #[test]
+    fn test_huge_directory() {
+        // Claim that `\EFI` is 4 GiB. Reading it must fail at the end of
+        // the device instead of allocating the whole size.
+        let mut image = build(false);
+        let efi_record = 21 * SECTOR + 2 * 34;
+        image[efi_record + 10..efi_record + 18].copy_from_slice(&both32(u32::MAX));
+        let device = IoBlockDevice::new(Cursor::new(image), 512).unwrap();
+        let mut fs = IsoFileSystem::new(device).unwrap();
+        assert!(fs.read_dir(cstr16!("\\EFI")).is_err());
+    }
+
+    #[test]
+    fn test_boot_images() {
+        let mut fs = mount(true);
+        assert_eq!(
+            fs.boot_images().unwrap(),
+            [
+                BootImage {
+                    platform: BootPlatform::X86,
+                    bootable: true,
+                    media_type: 0,
+                    offset: 27 * SECTOR_SIZE,
+                    size: 2048,
+                },
+                BootImage {
+                    platform: BootPlatform::EFI,
+                    bootable: true,
+                    media_type: 0,
+                    offset: 27 * SECTOR_SIZE,
+                    size: 512,
+                },
+            ]
+        );
+
+        // Break the validation entry checksum.
+        let mut image = build(true);
+        image[20 * SECTOR + 4] ^= 1;
+        let device = IoBlockDevice::new(Cursor::new(image), 512).unwrap();
+        let mut fs = IsoFileSystem::new(device).unwrap();
+        let Err(Error::Io(err)) = fs.boot_images() else {
+            panic!()
+        };
+        assert_eq!(err.uefi_error.status(), Status::VOLUME_CORRUPTED);
+    }
+
+    #[test]
+    fn test_not_iso() {
+        let device = IoBlockDevice::new(Cursor::new(vec![0; 40 * SECTOR]), 512).unwrap();
+        let Err(Error::Io(err)) = IsoFileSystem::new(device) else {
+            panic!()
+        };
+        assert_eq!(err.context, IoErrorContext::CantOpenVolume);
+        assert_eq!(err.uefi_error.status(), Status::UNSUPPORTED);
+    }
+}
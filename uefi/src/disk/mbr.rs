@@ -0,0 +1,257 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Master Boot Record.
+
+use super::BlockDevice;
+use crate::proto::media::partition::{MbrOsType, MbrPartitionRecord};
+use crate::Result;
+use core::{mem, ptr};
+
+/// Size of the MBR structure in bytes.
+pub const MBR_SIZE: usize = 512;
+
+/// Offset of the unique MBR disk signature.
+const DISK_SIGNATURE_OFFSET: usize = 440;
+
+/// Offset of the first partition record.
+const PARTITION_RECORD_OFFSET: usize = 446;
+
+/// Offset of the boot signature.
+const BOOT_SIGNATURE_OFFSET: usize = 510;
+
+/// Boot signature, `0x55 0xaa` on disk.
+const BOOT_SIGNATURE: u16 = 0xaa55;
+
+/// Legacy Master Boot Record, stored in the first block of a disk.
+///
+/// On a GPT disk, the MBR is a *protective MBR* with a single partition
+/// of type [`MbrOsType::GPT_PROTECTIVE`] covering the disk. See
+/// [`is_protective`].
+///
+/// [`is_protective`]: Self::is_protective
+#[derive(Clone, Copy, Debug)]
+pub struct Mbr {
+    /// Unique MBR disk signature.
+    pub disk_signature: u32,
+
+    /// The four primary partition records.
+    pub partition_records: [MbrPartitionRecord; 4],
+}
+
+impl Mbr {
+    /// Parse an MBR from the first [`MBR_SIZE`] bytes of `bytes`.
+    ///
+    /// Returns `None` if `bytes` is too short or the boot signature is
+    /// missing.
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes: &[u8; MBR_SIZE] = bytes.get(..MBR_SIZE)?.try_into().ok()?;
+
+        let signature = u16::from_le_bytes([
+            bytes[BOOT_SIGNATURE_OFFSET],
+            bytes[BOOT_SIGNATURE_OFFSET + 1],
+        ]);
+        if signature != BOOT_SIGNATURE {
+            return None;
+        }
+
+        let disk_signature = u32::from_le_bytes(
+            bytes[DISK_SIGNATURE_OFFSET..DISK_SIGNATURE_OFFSET + 4]
+                .try_into()
+                .unwrap(),
+        );
+
+        let record_size = mem::size_of::<MbrPartitionRecord>();
+        let partition_records = core::array::from_fn(|i| {
+            let offset = PARTITION_RECORD_OFFSET + i * record_size;
+            let record = &bytes[offset..offset + record_size];
+            // SAFETY: `record` is exactly the size of a record, the record
+            // is packed, and every bit pattern is valid for its fields.
+            unsafe { ptr::read_unaligned(record.as_ptr().cast::<MbrPartitionRecord>()) }
+        });
+
+        Some(Self {
+            disk_signature,
+            partition_records,
+        })
+    }
+
+    /// Create a protective MBR for a GPT disk with `num_blocks` blocks.
+    ///
+    /// The single partition record covers the disk from LBA 1, capped at
+    /// the largest size an MBR can describe.
+    #[must_use]
+    pub fn protective(num_blocks: u64) -> Self {
+        let size_in_lba = u32::try_from(num_blocks.saturating_sub(1)).unwrap_or(u32::MAX);
+        let empty = MbrPartitionRecord {
+            boot_indicator: 0,
+            starting_chs: [0; 3],
+            os_type: MbrOsType(0),
+            ending_chs: [0; 3],
+            starting_lba: 0,
+            size_in_lba: 0,
+        };
+        let protective = MbrPartitionRecord {
+            // CHS addresses are not used; these are the values that the
+            // UEFI specification gives for a protective MBR.
+            starting_chs: [0x00, 0x02, 0x00],
+            os_type: MbrOsType::GPT_PROTECTIVE,
+            ending_chs: [0xff, 0xff, 0xff],
+            starting_lba: 1,
+            size_in_lba,
+            ..empty
+        };
+        Self {
+            disk_signature: 0,
+            partition_records: [protective, empty, empty, empty],
+        }
+    }
+
+    /// Write the MBR into the first [`MBR_SIZE`] bytes of `bytes`.
+    ///
+    /// The boot code and any other bytes not described by [`Mbr`] are
+    /// left unchanged, so an existing first block can be updated in place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes` is shorter than [`MBR_SIZE`].
+    pub fn write_to(&self, bytes: &mut [u8]) {
+        let bytes = &mut bytes[..MBR_SIZE];
+
+        bytes[DISK_SIGNATURE_OFFSET..DISK_SIGNATURE_OFFSET + 4]
+            .copy_from_slice(&self.disk_signature.to_le_bytes());
+
+        let record_size = mem::size_of::<MbrPartitionRecord>();
+        for (i, record) in self.partition_records.iter().enumerate() {
+            let offset = PARTITION_RECORD_OFFSET + i * record_size;
+            let dst = &mut bytes[offset..offset + record_size];
+            // SAFETY: `dst` is exactly the size of a record.
+            unsafe { ptr::write_unaligned(dst.as_mut_ptr().cast::<MbrPartitionRecord>(), *record) }
+        }
+
+        bytes[BOOT_SIGNATURE_OFFSET..].copy_from_slice(&BOOT_SIGNATURE.to_le_bytes());
+    }
+
+    /// Read the MBR from the first block of `device`.
+    ///
+    /// Returns `Ok(None)` if the block does not contain a valid MBR.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from reading the device. Devices with blocks
+    /// smaller than [`MBR_SIZE`] or larger than 4 KiB are rejected with
+    /// [`Status::UNSUPPORTED`].
+    ///
+    /// [`Status::UNSUPPORTED`]: crate::Status::UNSUPPORTED
+    pub fn read<D: BlockDevice + ?Sized>(device: &mut D) -> Result<Option<Self>> {
+        let block_size = device.block_size() as usize;
+        if block_size < MBR_SIZE {
+            return Err(crate::Status::UNSUPPORTED.into());
+        }
+
+        // Logical blocks larger than 4 KiB are not supported.
+        let mut block = [0; 4096];
+        let block = block
+            .get_mut(..block_size)
+            .ok_or(crate::Status::UNSUPPORTED)?;
+        device.read_at_lba(0, block)?;
+        Ok(Self::from_bytes(block))
+    }
+
+    /// True if this is a protective MBR, indicating that the disk uses a
+    /// GUID Partition Table.
+    #[must_use]
+    pub fn is_protective(&self) -> bool {
+        self.partition_records
+            .iter()
+            .any(|record| record.os_type == MbrOsType::GPT_PROTECTIVE)
+    }
+
+    /// Iterator over the partition records that are in use, along with
+    /// their index in [`partition_records`].
+    ///
+    /// [`partition_records`]: Self::partition_records
+    pub fn partitions(&self) -> impl Iterator<Item = (usize, &MbrPartitionRecord)> {
+        self.partition_records
+            .iter()
+            .enumerate()
+            .filter(|(_, record)| {
+                let (os_type, size_in_lba) = (record.os_type, record.size_in_lba);
+                os_type != MbrOsType(0) && size_in_lba != 0
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mbr_bytes(records: &[(u8, u32, u32)]) -> [u8; MBR_SIZE] {
+        let mut bytes = [0; MBR_SIZE];
+        bytes[DISK_SIGNATURE_OFFSET..DISK_SIGNATURE_OFFSET + 4]
+            .copy_from_slice(&0x1234_5678u32.to_le_bytes());
+        for (i, (os_type, start, size)) in records.iter().enumerate() {
+            let offset = PARTITION_RECORD_OFFSET + i * 16;
+            bytes[offset + 4] = *os_type;
+            bytes[offset + 8..offset + 12].copy_from_slice(&start.to_le_bytes());
+            bytes[offset + 12..offset + 16].copy_from_slice(&size.to_le_bytes());
+        }
+        bytes[BOOT_SIGNATURE_OFFSET] = 0x55;
+        bytes[BOOT_SIGNATURE_OFFSET + 1] = 0xaa;
+        bytes
+    }
+
+    #[test]
+    fn test_mbr() {
+        let bytes = mbr_bytes(&[(0x83, 2048, 4096), (0, 0, 0), (0x0c, 8192, 100)]);
+        let mbr = Mbr::from_bytes(&bytes).unwrap();
+        assert_eq!(mbr.disk_signature, 0x1234_5678);
+        assert!(!mbr.is_protective());
+
+        let parts: alloc::vec::Vec<_> = mbr
+            .partitions()
+            .map(|(i, r)| (i, r.os_type, r.starting_lba, r.size_in_lba))
+            .collect();
+        assert_eq!(
+            parts,
+            [
+                (0, MbrOsType(0x83), 2048, 4096),
+                (2, MbrOsType(0x0c), 8192, 100)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_protective_mbr() {
+        let bytes = mbr_bytes(&[(0xee, 1, 0xffff_ffff)]);
+        assert!(Mbr::from_bytes(&bytes).unwrap().is_protective());
+    }
+
+    #[test]
+    fn test_write_mbr() {
+        let mut bytes = mbr_bytes(&[(0x83, 2048, 4096)]);
+        bytes[0] = 0xeb;
+        let mbr = Mbr::protective(0x1_0000_0000);
+        mbr.write_to(&mut bytes);
+
+        // The boot code is preserved.
+        assert_eq!(bytes[0], 0xeb);
+
+        let parsed = Mbr::from_bytes(&bytes).unwrap();
+        assert!(parsed.is_protective());
+        assert_eq!(parsed.disk_signature, 0);
+        let parts: alloc::vec::Vec<_> = parsed
+            .partitions()
+            .map(|(i, r)| (i, r.starting_lba, r.size_in_lba))
+            .collect();
+        assert_eq!(parts, [(0, 1, 0xffff_ffff)]);
+    }
+
+    #[test]
+    fn test_invalid_mbr() {
+        let mut bytes = mbr_bytes(&[]);
+        assert!(Mbr::from_bytes(&bytes[..511]).is_none());
+        bytes[BOOT_SIGNATURE_OFFSET] = 0;
+        assert!(Mbr::from_bytes(&bytes).is_none());
+    }
+}
@@ -0,0 +1,543 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! On-disk structures, parsed in Rust.
+//!
+//! The [`PartitionInfo`] protocol only describes partitions that the
+//! firmware has already recognized. This module reads partition tables
+//! directly, so that disks can be inspected even if the firmware did not
+//! create partition handles for them:
+//!
+//! - [`mbr`]: the legacy Master Boot Record, including detection of the
+//!   protective MBR that precedes a GPT.
+//! - [`gpt`]: the GUID Partition Table, with header and entry array CRC
+//!   validation and a comparison of the primary and backup headers.
+//!   Tables can also be created, edited and written back. Requires the
+//!   `alloc` feature.
+//!
+//! - [`fat`]: a read-only FAT12, FAT16 and FAT32 file system, with long
+//!   file names. Requires the `alloc` feature.
+//! - [`iso9660`]: a read-only ISO 9660 file system, with Joliet names and
+//!   El Torito boot catalog parsing. Requires the `alloc` feature.
+//!
+//! The file systems are accessed through [`ReadOnlyFileSystem`], and can
+//! be mounted on a single partition with [`BlockRange`].
+//!
+//! Disk images in memory can be registered as [`ram`] disks, so that the
+//! firmware's own partition and file system drivers bind to them.
+//! Requires the `alloc` feature.
+//!
+//! After writing a partition table, call [`rescan_partitions`] so that
+//! the firmware creates handles for the new partitions.
+//!
+//! Parsers operate on a [`BlockDevice`]. It is implemented for the
+//! [`BlockIO`] protocol and, via [`IoBlockDevice`], for anything that
+//! implements [`Read`] and [`Seek`], such as a [`DiskIoCursor`] or an
+//! in-memory [`Cursor`]:
+//!
+//! ```
+//! # #[cfg(feature = "alloc")]
+//! # fn main() -> uefi::Result {
+//! use uefi::disk::{IoBlockDevice, PartitionTable};
+//! use uefi::io::Cursor;
+//!
+//! let image = vec![0; 512 * 64];
+//! let mut device = IoBlockDevice::new(Cursor::new(image), 512)?;
+//! assert!(PartitionTable::read(&mut device).unwrap().is_none());
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "alloc"))]
+//! # fn main() {}
+//! ```
+//!
+//! [`BlockIO`]: crate::proto::media::block::BlockIO
+//! [`Cursor`]: crate::io::Cursor
+//! [`DiskIoCursor`]: crate::proto::media::disk::DiskIoCursor
+//! [`PartitionInfo`]: crate::proto::media::partition::PartitionInfo
+
+#[cfg(feature = "alloc")]
+pub mod fat;
+#[cfg(feature = "alloc")]
+pub mod gpt;
+#[cfg(feature = "alloc")]
+pub mod iso9660;
+pub mod mbr;
+#[cfg(feature = "alloc")]
+pub mod ram;
+#[cfg(feature = "alloc")]
+mod volume;
+
+#[cfg(feature = "alloc")]
+pub use volume::{ReadOnlyFile, ReadOnlyFileSystem};
+
+use crate::io::{Read, Seek, SeekFrom, Write};
+use crate::proto::media::block::{BlockIO, Lba};
+use crate::{boot, Handle, Result, Status};
+
+#[cfg(feature = "alloc")]
+use {gpt::Gpt, gpt::GptError, mbr::Mbr};
+
+/// A device that is read in units of logical blocks.
+pub trait BlockDevice {
+    /// Size of a logical block in bytes.
+    fn block_size(&self) -> u32;
+
+    /// Number of logical blocks on the device.
+    fn num_blocks(&self) -> u64;
+
+    /// Read blocks starting at `lba` into `buffer`. The length of
+    /// `buffer` must be a multiple of the block size.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::BAD_BUFFER_SIZE`] if the buffer length is not a
+    ///   multiple of the block size.
+    /// * [`Status::INVALID_PARAMETER`] if the read extends past the end of
+    ///   the device.
+    /// * Any error reported by the underlying device.
+    fn read_at_lba(&mut self, lba: Lba, buffer: &mut [u8]) -> Result;
+}
+
+/// A [`BlockDevice`] that can also be written.
+pub trait BlockDeviceMut: BlockDevice {
+    /// Write blocks starting at `lba` from `buffer`. The length of
+    /// `buffer` must be a multiple of the block size.
+    ///
+    /// # Errors
+    ///
+    /// See [`BlockDevice::read_at_lba`]. Additionally,
+    /// [`Status::WRITE_PROTECTED`] if the device is read-only.
+    fn write_at_lba(&mut self, lba: Lba, buffer: &[u8]) -> Result;
+
+    /// Flush any cached writes to the device.
+    fn flush(&mut self) -> Result;
+}
+
+impl<D: BlockDevice + ?Sized> BlockDevice for &mut D {
+    fn block_size(&self) -> u32 {
+        (**self).block_size()
+    }
+
+    fn num_blocks(&self) -> u64 {
+        (**self).num_blocks()
+    }
+
+    fn read_at_lba(&mut self, lba: Lba, buffer: &mut [u8]) -> Result {
+        (**self).read_at_lba(lba, buffer)
+    }
+}
+
+impl<D: BlockDeviceMut + ?Sized> BlockDeviceMut for &mut D {
+    fn write_at_lba(&mut self, lba: Lba, buffer: &[u8]) -> Result {
+        (**self).write_at_lba(lba, buffer)
+    }
+
+    fn flush(&mut self) -> Result {
+        (**self).flush()
+    }
+}
+
+/// Check that a transfer of `len` bytes at `lba` is whole blocks and
+/// lies within a device of `num_blocks` blocks.
+fn check_transfer(block_size: u32, num_blocks: u64, lba: Lba, len: usize) -> Result {
+    let block_size = u64::from(block_size);
+    let len = len as u64;
+    if len % block_size != 0 {
+        return Err(Status::BAD_BUFFER_SIZE.into());
+    }
+    match lba.checked_add(len / block_size) {
+        Some(end) if end <= num_blocks => Ok(()),
+        _ => Err(Status::INVALID_PARAMETER.into()),
+    }
+}
+
+/// Runs `f` with a buffer that satisfies the device's I/O alignment,
+/// copying through a temporary buffer if `buffer` is misaligned.
+#[cfg(feature = "alloc")]
+fn with_aligned_buffer(
+    io_align: u32,
+    buffer: &mut [u8],
+    copy_in: bool,
+    f: impl FnOnce(&mut [u8]) -> Result,
+) -> Result {
+    use crate::mem::AlignedBuffer;
+
+    let align = (io_align as usize).max(1);
+    if buffer.is_empty() || buffer.as_ptr() as usize % align == 0 {
+        return f(buffer);
+    }
+
+    let mut bounce = AlignedBuffer::from_size_align(buffer.len(), align)
+        .map_err(|_| Status::INVALID_PARAMETER)?;
+    // SAFETY: the allocation is `buffer.len()` bytes long and is zeroed
+    // or filled from `buffer` before it is read.
+    let bounce = unsafe {
+        let ptr = bounce.ptr_mut();
+        if copy_in {
+            ptr.copy_from_nonoverlapping(buffer.as_ptr(), buffer.len());
+        } else {
+            ptr.write_bytes(0, buffer.len());
+        }
+        core::slice::from_raw_parts_mut(ptr, buffer.len())
+    };
+    f(bounce)?;
+    buffer.copy_from_slice(bounce);
+    Ok(())
+}
+
+/// Transfers use the current media ID. With the `alloc` feature, buffers
+/// that do not meet the media's I/O alignment are transparently copied
+/// through an aligned buffer.
+impl BlockDevice for BlockIO {
+    fn block_size(&self) -> u32 {
+        self.media().block_size()
+    }
+
+    fn num_blocks(&self) -> u64 {
+        self.media().last_block().saturating_add(1)
+    }
+
+    fn read_at_lba(&mut self, lba: Lba, buffer: &mut [u8]) -> Result {
+        check_transfer(self.block_size(), self.num_blocks(), lba, buffer.len())?;
+        let media_id = self.media().media_id();
+        #[cfg(feature = "alloc")]
+        {
+            let io_align = self.media().io_align();
+            with_aligned_buffer(io_align, buffer, false, |buffer| {
+                Self::read_blocks(self, media_id, lba, buffer)
+            })
+        }
+        #[cfg(not(feature = "alloc"))]
+        Self::read_blocks(self, media_id, lba, buffer)
+    }
+}
+
+impl BlockDeviceMut for BlockIO {
+    fn write_at_lba(&mut self, lba: Lba, buffer: &[u8]) -> Result {
+        check_transfer(self.block_size(), self.num_blocks(), lba, buffer.len())?;
+        let media_id = self.media().media_id();
+        #[cfg(feature = "alloc")]
+        {
+            let align = (self.media().io_align() as usize).max(1);
+            if buffer.as_ptr() as usize % align == 0 {
+                return Self::write_blocks(self, media_id, lba, buffer);
+            }
+            let mut copy = alloc::vec::Vec::from(buffer);
+            with_aligned_buffer(align as u32, &mut copy, true, |buffer| {
+                Self::write_blocks(self, media_id, lba, buffer)
+            })
+        }
+        #[cfg(not(feature = "alloc"))]
+        Self::write_blocks(self, media_id, lba, buffer)
+    }
+
+    fn flush(&mut self) -> Result {
+        Self::flush_blocks(self)
+    }
+}
+
+/// Adapts a seekable byte stream to the [`BlockDevice`] interface.
+///
+/// This can be used with a [`DiskIoCursor`] to parse a disk through the
+/// [`DiskIo`] protocol, or with an in-memory [`Cursor`] to parse a disk
+/// image.
+///
+/// [`Cursor`]: crate::io::Cursor
+/// [`DiskIo`]: crate::proto::media::disk::DiskIo
+/// [`DiskIoCursor`]: crate::proto::media::disk::DiskIoCursor
+#[derive(Debug)]
+pub struct IoBlockDevice<T> {
+    inner: T,
+    block_size: u32,
+    num_blocks: u64,
+}
+
+impl<T: Seek> IoBlockDevice<T> {
+    /// Create a block device from `inner`, using the stream length to
+    /// determine the number of blocks. Any partial block at the end of
+    /// the stream is ignored.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`] if `block_size` is zero.
+    /// * Any error returned when seeking `inner`.
+    pub fn new(mut inner: T, block_size: u32) -> Result<Self> {
+        if block_size == 0 {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        let len = inner.seek(SeekFrom::End(0))?;
+        Ok(Self::with_num_blocks(
+            inner,
+            block_size,
+            len / u64::from(block_size),
+        ))
+    }
+}
+
+impl<T> IoBlockDevice<T> {
+    /// Create a block device from `inner` with an explicit size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is zero.
+    pub const fn with_num_blocks(inner: T, block_size: u32, num_blocks: u64) -> Self {
+        assert!(block_size != 0, "block size must not be zero");
+        Self {
+            inner,
+            block_size,
+            num_blocks,
+        }
+    }
+
+    /// Returns a reference to the underlying stream.
+    pub const fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying stream.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Consumes the device, returning the underlying stream.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn seek_to(&mut self, lba: Lba, len: usize) -> Result
+    where
+        T: Seek,
+    {
+        check_transfer(self.block_size, self.num_blocks, lba, len)?;
+        let offset = lba
+            .checked_mul(u64::from(self.block_size))
+            .ok_or(Status::INVALID_PARAMETER)?;
+        self.inner.seek(SeekFrom::Start(offset)).map(|_| ())
+    }
+}
+
+impl<T: Read + Seek> BlockDevice for IoBlockDevice<T> {
+    fn block_size(&self) -> u32 {
+        self.block_size
+    }
+
+    fn num_blocks(&self) -> u64 {
+        self.num_blocks
+    }
+
+    fn read_at_lba(&mut self, lba: Lba, buffer: &mut [u8]) -> Result {
+        self.seek_to(lba, buffer.len())?;
+        self.inner.read_exact(buffer)
+    }
+}
+
+impl<T: Read + Write + Seek> BlockDeviceMut for IoBlockDevice<T> {
+    fn write_at_lba(&mut self, lba: Lba, buffer: &[u8]) -> Result {
+        self.seek_to(lba, buffer.len())?;
+        self.inner.write_all(buffer)
+    }
+
+    fn flush(&mut self) -> Result {
+        self.inner.flush()
+    }
+}
+
+/// A contiguous range of blocks of another [`BlockDevice`], such as a
+/// partition, presented as a device of its own.
+///
+/// ```
+/// # #[cfg(feature = "alloc")]
+/// # fn main() -> uefi::Result {
+/// use uefi::disk::{BlockDevice, BlockRange, IoBlockDevice};
+/// use uefi::io::Cursor;
+///
+/// let disk = IoBlockDevice::new(Cursor::new(vec![0; 512 * 64]), 512)?;
+/// let partition = BlockRange::new(disk, 34, 16)?;
+/// assert_eq!(partition.num_blocks(), 16);
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "alloc"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug)]
+pub struct BlockRange<D> {
+    device: D,
+    first_lba: Lba,
+    num_blocks: u64,
+}
+
+impl<D: BlockDevice> BlockRange<D> {
+    /// Create a device covering `num_blocks` blocks of `device`, starting
+    /// at `first_lba`.
+    ///
+    /// # Errors
+    ///
+    /// [`Status::INVALID_PARAMETER`] if the range extends past the end of
+    /// `device`.
+    pub fn new(device: D, first_lba: Lba, num_blocks: u64) -> Result<Self> {
+        match first_lba.checked_add(num_blocks) {
+            Some(end) if end <= device.num_blocks() => Ok(Self {
+                device,
+                first_lba,
+                num_blocks,
+            }),
+            _ => Err(Status::INVALID_PARAMETER.into()),
+        }
+    }
+}
+
+impl<D> BlockRange<D> {
+    /// First block of the range on the underlying device.
+    #[must_use]
+    pub const fn first_lba(&self) -> Lba {
+        self.first_lba
+    }
+
+    /// Returns a reference to the underlying device.
+    pub const fn get_ref(&self) -> &D {
+        &self.device
+    }
+
+    /// Consumes the range, returning the underlying device.
+    pub fn into_inner(self) -> D {
+        self.device
+    }
+}
+
+impl<D: BlockDevice> BlockDevice for BlockRange<D> {
+    fn block_size(&self) -> u32 {
+        self.device.block_size()
+    }
+
+    fn num_blocks(&self) -> u64 {
+        self.num_blocks
+    }
+
+    fn read_at_lba(&mut self, lba: Lba, buffer: &mut [u8]) -> Result {
+        check_transfer(self.block_size(), self.num_blocks, lba, buffer.len())?;
+        self.device.read_at_lba(self.first_lba + lba, buffer)
+    }
+}
+
+impl<D: BlockDeviceMut> BlockDeviceMut for BlockRange<D> {
+    fn write_at_lba(&mut self, lba: Lba, buffer: &[u8]) -> Result {
+        check_transfer(self.block_size(), self.num_blocks, lba, buffer.len())?;
+        self.device.write_at_lba(self.first_lba + lba, buffer)
+    }
+
+    fn flush(&mut self) -> Result {
+        self.device.flush()
+    }
+}
+
+/// Make the firmware rescan the partitions of `disk` after its partition
+/// table was modified.
+///
+/// All drivers are disconnected from the disk handle, destroying the
+/// child handles of its old partitions, and then recursively connected
+/// again, creating handles for the new partitions. Any protocols opened
+/// exclusively on the disk must be closed first.
+///
+/// # Errors
+///
+/// Returns errors from [`boot::disconnect_controller`] and
+/// [`boot::connect_controller`]. It is not an error if no driver is
+/// started for the disk, as is the case when it has no partitions.
+pub fn rescan_partitions(disk: Handle) -> Result {
+    boot::disconnect_controller(disk, None, None)?;
+    match boot::connect_controller(disk, None, None, true) {
+        Err(err) if err.status() == Status::NOT_FOUND => Ok(()),
+        result => result,
+    }
+}
+
+/// Partition table of a disk.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug)]
+pub enum PartitionTable {
+    /// Legacy MBR partition table.
+    Mbr(Mbr),
+
+    /// GUID Partition Table. The protective MBR that precedes it is not
+    /// included.
+    Gpt(Gpt),
+}
+
+#[cfg(feature = "alloc")]
+impl PartitionTable {
+    /// Read the partition table of `device`.
+    ///
+    /// If the MBR is a protective MBR, the GPT is read and validated with
+    /// [`Gpt::read`]. Otherwise the MBR partitions are returned. Returns
+    /// `Ok(None)` if the disk has no MBR boot signature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading the device fails, or if the disk has a
+    /// protective MBR but neither GPT header is valid.
+    pub fn read<D: BlockDevice + ?Sized>(
+        device: &mut D,
+    ) -> core::result::Result<Option<Self>, GptError> {
+        let Some(mbr) = Mbr::read(device).map_err(|err| GptError::Io(err.status()))? else {
+            return Ok(None);
+        };
+        if mbr.is_protective() {
+            Gpt::read(device).map(|gpt| Some(Self::Gpt(gpt)))
+        } else {
+            Ok(Some(Self::Mbr(mbr)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::Cursor;
+    use alloc::vec;
+
+    #[test]
+    fn test_io_block_device() {
+        let image: alloc::vec::Vec<u8> = (0..=255).cycle().take(512 * 3 + 100).collect();
+        let mut device = IoBlockDevice::new(Cursor::new(image), 512).unwrap();
+        assert_eq!(device.block_size(), 512);
+        assert_eq!(device.num_blocks(), 3);
+
+        let mut buf = vec![0; 1024];
+        device.read_at_lba(1, &mut buf).unwrap();
+        assert_eq!(buf[0], 0);
+        assert_eq!(buf[1], 1);
+
+        assert_eq!(
+            device.read_at_lba(2, &mut buf),
+            Err(Status::INVALID_PARAMETER.into())
+        );
+        assert_eq!(
+            device.read_at_lba(0, &mut buf[..100]),
+            Err(Status::BAD_BUFFER_SIZE.into())
+        );
+
+        device.write_at_lba(2, &[0xaa; 512]).unwrap();
+        device.read_at_lba(2, &mut buf[..512]).unwrap();
+        assert!(buf[..512].iter().all(|b| *b == 0xaa));
+    }
+
+    #[test]
+    fn test_with_aligned_buffer() {
+        let mut storage = [0u8; 17];
+        // Pick a subslice that is misaligned for an alignment of 16.
+        let start = if storage.as_ptr() as usize % 16 == 0 {
+            1
+        } else {
+            0
+        };
+        let buffer = &mut storage[start..start + 16];
+        buffer.fill(7);
+        with_aligned_buffer(16, buffer, true, |aligned| {
+            assert_eq!(aligned.as_ptr() as usize % 16, 0);
+            assert!(aligned.iter().all(|b| *b == 7));
+            aligned.fill(9);
+            Ok(())
+        })
+        .unwrap();
+        assert!(buffer.iter().all(|b| *b == 9));
+    }
+}
@@ -0,0 +1,555 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! RAM disks.
+//!
+//! A RAM disk makes a range of memory available as a [`BlockIO`] device,
+//! for example to boot from an ISO image that was downloaded into memory.
+//! Once registered, the firmware connects its drivers to the disk, so
+//! partitions and file systems on it show up like on any other disk.
+//!
+//! Registration uses the firmware's [`RamDisk`] protocol. If the firmware
+//! does not implement it, a [`BlockIO`] implemented in Rust is installed
+//! instead, together with the same kind of device path the firmware
+//! would create.
+//!
+//! ```no_run
+//! use uefi::boot::MemoryType;
+//! use uefi::disk::ram::RamDiskMemory;
+//! use uefi::proto::device_path::media::RamDiskType;
+//!
+//! # fn get_iso_image() -> &'static [u8] { &[] }
+//! # fn main() -> uefi::Result {
+//! let image: &[u8] = get_iso_image();
+//! let memory = RamDiskMemory::from_bytes(image, MemoryType::BOOT_SERVICES_DATA)?;
+//! let disk = memory.register(RamDiskType::VIRTUAL_CD, None)?;
+//!
+//! // ... load an image from a file system on `disk.handle()` ...
+//!
+//! disk.unregister()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`BlockIO`]: crate::proto::media::block::BlockIO
+//! [`RamDisk`]: crate::proto::media::ram_disk::RamDisk
+
+use super::check_transfer;
+use crate::boot::{self, AllocateType, MemoryType, PAGE_SIZE};
+use crate::proto::device_path::build::{self, DevicePathBuilder};
+use crate::proto::device_path::media::RamDiskType;
+use crate::proto::device_path::DevicePath;
+use crate::proto::media::block::{BlockIO, Lba};
+use crate::proto::media::ram_disk::RamDisk;
+use crate::{Handle, Identify, Result, Status};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::ffi::c_void;
+use core::fmt::{self, Debug, Formatter};
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, NonNull};
+use core::slice;
+use uefi_raw::protocol::block::{BlockIoMedia, BlockIoProtocol};
+use uefi_raw::Boolean;
+
+/// Memory for a RAM disk, allocated in whole pages.
+///
+/// The memory is freed when dropped. Fill it with the disk image, then
+/// [`register`] it.
+///
+/// [`register`]: Self::register
+pub struct RamDiskMemory {
+    ptr: NonNull<u8>,
+    len: usize,
+}
+
+impl RamDiskMemory {
+    /// Allocate `len` zeroed bytes of memory of type `memory_type`.
+    ///
+    /// Use [`MemoryType::RESERVED`] or one of the persistent memory types
+    /// if the disk should remain usable by the operating system, and
+    /// [`MemoryType::BOOT_SERVICES_DATA`] otherwise.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `len` is zero.
+    /// * [`Status::OUT_OF_RESOURCES`]: the memory could not be allocated.
+    pub fn new(len: usize, memory_type: MemoryType) -> Result<Self> {
+        if len == 0 {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        let ptr =
+            boot::allocate_pages(AllocateType::AnyPages, memory_type, len.div_ceil(PAGE_SIZE))?;
+        unsafe { ptr.as_ptr().write_bytes(0, len) };
+        Ok(Self { ptr, len })
+    }
+
+    /// Allocate memory of type `memory_type` and copy `data` into it.
+    ///
+    /// # Errors
+    ///
+    /// See [`new`](Self::new).
+    pub fn from_bytes(data: &[u8], memory_type: MemoryType) -> Result<Self> {
+        let mut memory = Self::new(data.len(), memory_type)?;
+        memory.copy_from_slice(data);
+        Ok(memory)
+    }
+
+    /// Register the memory as a RAM disk of type `disk_type`.
+    ///
+    /// The device path of the disk is `parent`, if given, followed by a
+    /// [`RamDisk`] node. The disk stays registered until
+    /// [`RegisteredRamDisk::unregister`] is called.
+    ///
+    /// If the firmware does not implement the RAM disk [protocol], a
+    /// [`BlockIO`] protocol implemented in Rust is installed instead, with
+    /// a block size of 2048 bytes for CD types and 512 bytes otherwise.
+    /// Bytes past the last whole block are not part of the disk in that
+    /// case. CD types are read-only.
+    ///
+    /// The memory is freed if registration fails.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::UNSUPPORTED`]: the firmware does not support
+    ///   `disk_type`.
+    /// * [`Status::INVALID_PARAMETER`]: the memory is smaller than one
+    ///   block.
+    ///
+    /// Errors from registering the disk or installing the protocols are
+    /// also returned.
+    ///
+    /// [`BlockIO`]: crate::proto::media::block::BlockIO
+    /// [`RamDisk`]: crate::proto::device_path::media::RamDisk
+    /// [protocol]: crate::proto::media::ram_disk::RamDisk
+    pub fn register(
+        self,
+        disk_type: RamDiskType,
+        parent: Option<&DevicePath>,
+    ) -> Result<RegisteredRamDisk> {
+        match boot::get_handle_for_protocol::<RamDisk>() {
+            Ok(handle) => self.register_with_protocol(handle, disk_type, parent),
+            Err(err) if err.status() == Status::NOT_FOUND => {
+                self.register_emulated(disk_type, parent)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn register_with_protocol(
+        self,
+        protocol_handle: Handle,
+        disk_type: RamDiskType,
+        parent: Option<&DevicePath>,
+    ) -> Result<RegisteredRamDisk> {
+        let ram_disk = boot::open_protocol_exclusive::<RamDisk>(protocol_handle)?;
+        let device_path = unsafe {
+            ram_disk.register(self.ptr.as_ptr() as u64, self.len as u64, disk_type, parent)?
+        }
+        .to_boxed();
+
+        // The firmware does not return the handle of the new disk; find it
+        // from the device path.
+        let handle = match device_path.locate_device_path::<BlockIO>() {
+            Ok((handle, rest)) if rest.node_iter().next().is_none() => handle,
+            result => {
+                // The disk must not use the memory anymore before it is
+                // freed. If it cannot be unregistered, leak the memory.
+                if ram_disk.unregister(&device_path).is_err() {
+                    let _ = ManuallyDrop::new(self);
+                }
+                return Err(result.err().unwrap_or_else(|| Status::NOT_FOUND.into()));
+            }
+        };
+
+        Ok(RegisteredRamDisk {
+            memory: ManuallyDrop::new(self),
+            device_path: NonNull::from(Box::leak(device_path)),
+            handle,
+            emulated: None,
+        })
+    }
+
+    fn register_emulated(
+        self,
+        disk_type: RamDiskType,
+        parent: Option<&DevicePath>,
+    ) -> Result<RegisteredRamDisk> {
+        let is_cd =
+            disk_type == RamDiskType::VIRTUAL_CD || disk_type == RamDiskType::PERSISTENT_VIRTUAL_CD;
+        let block_size: u32 = if is_cd { 2048 } else { 512 };
+        if self.len < block_size as usize {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+
+        let base = self.ptr.as_ptr() as u64;
+        let mut buf = Vec::new();
+        let mut builder = DevicePathBuilder::with_vec(&mut buf);
+        for node in parent.into_iter().flat_map(DevicePath::node_iter) {
+            builder = builder.push(&node).map_err(|_| Status::INVALID_PARAMETER)?;
+        }
+        let device_path = builder
+            .push(&build::media::RamDisk {
+                starting_address: base,
+                ending_address: base + self.len as u64 - 1,
+                disk_type,
+                disk_instance: 0,
+            })
+            .and_then(DevicePathBuilder::finalize)
+            .map_err(|_| Status::INVALID_PARAMETER)?;
+        let device_path = NonNull::from(Box::leak(device_path.to_boxed()));
+
+        let block_io = MemoryBlockIo::new(self.ptr, self.len, block_size, is_cd);
+        let block_io = NonNull::from(Box::leak(block_io));
+
+        let handle = match unsafe { install_emulated(device_path, block_io) } {
+            Ok(handle) => handle,
+            Err(err) => {
+                unsafe {
+                    drop(Box::from_raw(block_io.as_ptr()));
+                    drop(Box::from_raw(device_path.as_ptr()));
+                }
+                return Err(err);
+            }
+        };
+
+        // Let drivers bind to the new disk. This fails if no driver
+        // supports it, which is not an error for the disk itself.
+        let _ = boot::connect_controller(handle, None, None, true);
+
+        Ok(RegisteredRamDisk {
+            memory: ManuallyDrop::new(self),
+            device_path,
+            handle,
+            emulated: Some(block_io),
+        })
+    }
+}
+
+impl Debug for RamDiskMemory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RamDiskMemory")
+            .field("ptr", &self.ptr)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl Deref for RamDiskMemory {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl DerefMut for RamDiskMemory {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Drop for RamDiskMemory {
+    fn drop(&mut self) {
+        let _ = unsafe { boot::free_pages(self.ptr, self.len.div_ceil(PAGE_SIZE)) };
+    }
+}
+
+/// A registered RAM disk.
+///
+/// Dropping this leaves the disk registered and leaks its memory; call
+/// [`unregister`] to remove the disk and free the memory.
+///
+/// [`unregister`]: Self::unregister
+pub struct RegisteredRamDisk {
+    memory: ManuallyDrop<RamDiskMemory>,
+    device_path: NonNull<DevicePath>,
+    handle: Handle,
+    emulated: Option<NonNull<MemoryBlockIo>>,
+}
+
+impl RegisteredRamDisk {
+    /// The handle of the disk, which has the [`BlockIO`] and
+    /// [`DevicePath`] protocols installed.
+    ///
+    /// [`BlockIO`]: crate::proto::media::block::BlockIO
+    #[must_use]
+    pub const fn handle(&self) -> Handle {
+        self.handle
+    }
+
+    /// The device path of the disk.
+    #[must_use]
+    pub const fn device_path(&self) -> &DevicePath {
+        unsafe { self.device_path.as_ref() }
+    }
+
+    /// True if the disk's [`BlockIO`] is implemented in Rust because the
+    /// firmware does not support the [`RamDisk`] protocol.
+    ///
+    /// [`BlockIO`]: crate::proto::media::block::BlockIO
+    /// [`RamDisk`]: crate::proto::media::ram_disk::RamDisk
+    #[must_use]
+    pub const fn is_emulated(&self) -> bool {
+        self.emulated.is_some()
+    }
+
+    /// Unregister the disk and free its memory.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::ACCESS_DENIED`]: a driver still uses the disk. The disk
+    ///   stays registered and its memory is leaked.
+    ///
+    /// Errors from unregistering the disk are also returned, with the
+    /// same effect.
+    pub fn unregister(self) -> Result {
+        let mut this = ManuallyDrop::new(self);
+        match this.emulated {
+            Some(block_io) => {
+                unsafe { uninstall_emulated(this.handle, this.device_path, block_io) }?
+            }
+            None => {
+                let handle = boot::get_handle_for_protocol::<RamDisk>()?;
+                boot::open_protocol_exclusive::<RamDisk>(handle)?.unregister(this.device_path())?;
+            }
+        }
+
+        unsafe {
+            drop(Box::from_raw(this.device_path.as_ptr()));
+            if let Some(block_io) = this.emulated {
+                drop(Box::from_raw(block_io.as_ptr()));
+            }
+            ManuallyDrop::drop(&mut this.memory);
+        }
+        Ok(())
+    }
+}
+
+impl Debug for RegisteredRamDisk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegisteredRamDisk")
+            .field("memory", &*self.memory)
+            .field("handle", &self.handle)
+            .field("emulated", &self.is_emulated())
+            .finish()
+    }
+}
+
+/// Install `device_path` and `block_io` on a new handle.
+unsafe fn install_emulated(
+    device_path: NonNull<DevicePath>,
+    block_io: NonNull<MemoryBlockIo>,
+) -> Result<Handle> {
+    let device_path = device_path.as_ptr().cast_const().cast();
+    let handle = unsafe { boot::install_protocol_interface(None, &DevicePath::GUID, device_path) }?;
+    let block_io = block_io.as_ptr().cast_const().cast();
+    if let Err(err) =
+        unsafe { boot::install_protocol_interface(Some(handle), &BlockIoProtocol::GUID, block_io) }
+    {
+        let _ =
+            unsafe { boot::uninstall_protocol_interface(handle, &DevicePath::GUID, device_path) };
+        return Err(err);
+    }
+    Ok(handle)
+}
+
+/// Disconnect the drivers from `handle` and uninstall the protocols that
+/// [`install_emulated`] installed.
+unsafe fn uninstall_emulated(
+    handle: Handle,
+    device_path: NonNull<DevicePath>,
+    block_io: NonNull<MemoryBlockIo>,
+) -> Result {
+    match boot::disconnect_controller(handle, None, None) {
+        Err(err) if err.status() != Status::NOT_FOUND => return Err(err),
+        _ => {}
+    }
+    let block_io = block_io.as_ptr().cast_const().cast();
+    if let Err(err) =
+        unsafe { boot::uninstall_protocol_interface(handle, &BlockIoProtocol::GUID, block_io) }
+    {
+        let _ = boot::connect_controller(handle, None, None, true);
+        return Err(err);
+    }
+    let device_path = device_path.as_ptr().cast_const().cast();
+    unsafe { boot::uninstall_protocol_interface(handle, &DevicePath::GUID, device_path) }
+}
+
+/// A [`BlockIoProtocol`] that reads and writes a range of memory.
+#[repr(C)]
+struct MemoryBlockIo {
+    protocol: BlockIoProtocol,
+    media: BlockIoMedia,
+    data: NonNull<u8>,
+}
+
+impl MemoryBlockIo {
+    /// Create a block device of `len / block_size` blocks at `data`.
+    fn new(data: NonNull<u8>, len: usize, block_size: u32, read_only: bool) -> Box<Self> {
+        let mut this = Box::new(Self {
+            protocol: BlockIoProtocol {
+                revision: 0x0001_0000,
+                media: ptr::null(),
+                reset: Self::reset,
+                read_blocks: Self::read_blocks,
+                write_blocks: Self::write_blocks,
+                flush_blocks: Self::flush_blocks,
+            },
+            media: BlockIoMedia {
+                media_id: 0,
+                removable_media: Boolean::FALSE,
+                media_present: Boolean::TRUE,
+                logical_partition: Boolean::FALSE,
+                read_only: read_only.into(),
+                write_caching: Boolean::FALSE,
+                block_size,
+                io_align: 0,
+                last_block: (len / block_size as usize) as Lba - 1,
+                lowest_aligned_lba: 0,
+                logical_blocks_per_physical_block: 0,
+                optimal_transfer_length_granularity: 0,
+            },
+            data,
+        });
+        this.protocol.media = &this.media;
+        this
+    }
+
+    /// Check a transfer and return its byte offset into the memory.
+    fn offset(&self, media_id: u32, lba: Lba, len: usize, buffer: *const c_void) -> Result<usize> {
+        if media_id != self.media.media_id {
+            return Err(Status::MEDIA_CHANGED.into());
+        }
+        if buffer.is_null() && len != 0 {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        let block_size = self.media.block_size;
+        check_transfer(block_size, self.media.last_block + 1, lba, len)?;
+        Ok(lba as usize * block_size as usize)
+    }
+
+    const unsafe extern "efiapi" fn reset(
+        _this: *mut BlockIoProtocol,
+        _extended: Boolean,
+    ) -> Status {
+        Status::SUCCESS
+    }
+
+    unsafe extern "efiapi" fn read_blocks(
+        this: *const BlockIoProtocol,
+        media_id: u32,
+        lba: Lba,
+        buffer_size: usize,
+        buffer: *mut c_void,
+    ) -> Status {
+        let this = unsafe { &*this.cast::<Self>() };
+        match this.offset(media_id, lba, buffer_size, buffer) {
+            Ok(offset) => {
+                unsafe {
+                    ptr::copy(this.data.as_ptr().add(offset), buffer.cast(), buffer_size);
+                }
+                Status::SUCCESS
+            }
+            Err(err) => err.status(),
+        }
+    }
+
+    unsafe extern "efiapi" fn write_blocks(
+        this: *mut BlockIoProtocol,
+        media_id: u32,
+        lba: Lba,
+        buffer_size: usize,
+        buffer: *const c_void,
+    ) -> Status {
+        let this = unsafe { &*this.cast::<Self>() };
+        if this.media.read_only.into() {
+            return Status::WRITE_PROTECTED;
+        }
+        match this.offset(media_id, lba, buffer_size, buffer) {
+            Ok(offset) => {
+                unsafe {
+                    ptr::copy(buffer.cast(), this.data.as_ptr().add(offset), buffer_size);
+                }
+                Status::SUCCESS
+            }
+            Err(err) => err.status(),
+        }
+    }
+
+    const unsafe extern "efiapi" fn flush_blocks(_this: *mut BlockIoProtocol) -> Status {
+        Status::SUCCESS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn block_io(instance: &mut MemoryBlockIo) -> &mut BlockIO {
+        unsafe { &mut *ptr::from_mut(&mut instance.protocol).cast::<BlockIO>() }
+    }
+
+    #[test]
+    fn test_memory_block_io() {
+        let mut data = vec![0u8; 512 * 4 + 100];
+        let ptr = NonNull::new(data.as_mut_ptr()).unwrap();
+        let mut instance = MemoryBlockIo::new(ptr, data.len(), 512, false);
+        let block_io = block_io(&mut instance);
+
+        let media = block_io.media();
+        assert_eq!(media.block_size(), 512);
+        assert_eq!(media.last_block(), 3);
+        assert!(!media.is_read_only());
+
+        block_io.write_blocks(0, 1, &[0xab; 1024]).unwrap();
+        let mut buf = [0; 512];
+        block_io.read_blocks(0, 2, &mut buf).unwrap();
+        assert_eq!(buf, [0xab; 512]);
+        block_io.read_blocks(0, 0, &mut buf).unwrap();
+        assert_eq!(buf, [0; 512]);
+
+        // Out of range, partial blocks and a wrong media ID.
+        assert_eq!(
+            block_io.read_blocks(0, 4, &mut buf).unwrap_err().status(),
+            Status::INVALID_PARAMETER
+        );
+        assert_eq!(
+            block_io
+                .read_blocks(0, 0, &mut buf[..100])
+                .unwrap_err()
+                .status(),
+            Status::BAD_BUFFER_SIZE
+        );
+        assert_eq!(
+            block_io.write_blocks(1, 0, &buf).unwrap_err().status(),
+            Status::MEDIA_CHANGED
+        );
+        block_io.flush_blocks().unwrap();
+
+        drop(instance);
+        assert_eq!(&data[512..1536], &[0xab; 1024]);
+        assert!(data[1536..].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_memory_block_io_read_only() {
+        let mut data = vec![0x55u8; 2048 * 2];
+        let ptr = NonNull::new(data.as_mut_ptr()).unwrap();
+        let mut instance = MemoryBlockIo::new(ptr, data.len(), 2048, true);
+        let block_io = block_io(&mut instance);
+
+        assert!(block_io.media().is_read_only());
+        assert_eq!(
+            block_io
+                .write_blocks(0, 0, &[0; 2048])
+                .unwrap_err()
+                .status(),
+            Status::WRITE_PROTECTED
+        );
+        let mut buf = [0; 2048];
+        block_io.read_blocks(0, 1, &mut buf).unwrap();
+        assert_eq!(buf, [0x55; 2048]);
+    }
+}
@@ -0,0 +1,282 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Common interface of the file systems implemented in Rust.
+
+use super::BlockDevice;
+use crate::fs::{Error, FileSystemResult, IoError, IoErrorContext, NodeInfo, Path, PathBuf};
+use crate::io::{Read, Seek, SeekFrom};
+use crate::proto::media::block::Lba;
+use crate::proto::media::file::FileInfo;
+use crate::{CStr16, CString16, Status};
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+/// Byte-granular reads from a [`BlockDevice`], caching the last block
+/// read so that small, nearby reads do not hit the device every time.
+#[derive(Debug)]
+pub struct BlockCache<D> {
+    device: D,
+    block: Vec<u8>,
+    cached: Option<Lba>,
+}
+
+impl<D: BlockDevice> BlockCache<D> {
+    pub fn new(device: D) -> Self {
+        let block = vec![0; device.block_size() as usize];
+        Self {
+            device,
+            block,
+            cached: None,
+        }
+    }
+
+    pub const fn device(&self) -> &D {
+        &self.device
+    }
+
+    pub fn into_inner(self) -> D {
+        self.device
+    }
+
+    /// Size of the device in bytes.
+    pub fn len(&self) -> u64 {
+        self.device
+            .num_blocks()
+            .saturating_mul(u64::from(self.device.block_size()))
+    }
+
+    /// Fill `buf` with the bytes at `offset`.
+    pub fn read_at(&mut self, mut offset: u64, mut buf: &mut [u8]) -> crate::Result {
+        let block_size = self.block.len();
+        while !buf.is_empty() {
+            let lba = offset / block_size as u64;
+            let within = (offset % block_size as u64) as usize;
+
+            // Whole blocks are read straight into the caller's buffer.
+            if within == 0 && buf.len() >= block_size {
+                let len = buf.len() - buf.len() % block_size;
+                self.device.read_at_lba(lba, &mut buf[..len])?;
+                offset += len as u64;
+                buf = &mut buf[len..];
+                continue;
+            }
+
+            if self.cached != Some(lba) {
+                self.cached = None;
+                self.device.read_at_lba(lba, &mut self.block)?;
+                self.cached = Some(lba);
+            }
+            let len = buf.len().min(block_size - within);
+            buf[..len].copy_from_slice(&self.block[within..within + len]);
+            offset += len as u64;
+            buf = &mut buf[len..];
+        }
+        Ok(())
+    }
+
+    /// Read `len` bytes at `offset` into a new buffer.
+    pub fn read_vec(&mut self, offset: u64, len: usize) -> crate::Result<Vec<u8>> {
+        let mut buf = vec![0; len];
+        self.read_at(offset, &mut buf)?;
+        Ok(buf)
+    }
+}
+
+/// A directory entry as seen by the generic file system code.
+#[derive(Clone, Debug)]
+pub struct DirEntry<N> {
+    pub info: NodeInfo,
+    pub node: N,
+}
+
+impl<N> DirEntry<N> {
+    /// Entry for the root directory, which has no entry of its own.
+    pub fn root(node: N) -> Self {
+        Self {
+            info: NodeInfo::directory(CString16::new()),
+            node,
+        }
+    }
+}
+
+/// Implementation side of [`ReadOnlyFileSystem`]. This is in a private
+/// module so that the trait cannot be implemented outside of this crate.
+pub trait Volume {
+    /// Location of a file or directory on the volume.
+    type Node: Clone + Debug;
+
+    /// The root directory.
+    fn root(&self) -> Self::Node;
+
+    /// Read the entries of a directory, excluding `.` and `..`.
+    fn read_dir_entries(&mut self, dir: &Self::Node) -> crate::Result<Vec<DirEntry<Self::Node>>>;
+
+    /// Read file data at `offset`, returning the number of bytes read.
+    /// Reads are truncated at the end of the file.
+    fn read_file_at(
+        &mut self,
+        file: &Self::Node,
+        offset: u64,
+        buf: &mut [u8],
+    ) -> crate::Result<usize>;
+}
+
+fn io_error(path: &Path, context: IoErrorContext, status: Status) -> Error {
+    Error::Io(IoError {
+        path: path.to_path_buf(),
+        context,
+        uefi_error: status.into(),
+    })
+}
+
+/// Find the entry for `path`, starting at the root directory.
+fn lookup<V: Volume + ?Sized>(volume: &mut V, path: &Path) -> FileSystemResult<DirEntry<V::Node>> {
+    let mut entry = DirEntry::root(volume.root());
+    for component in path.components().filter(|c| !c.is_empty()) {
+        if !entry.info.is_directory() {
+            return Err(io_error(
+                path,
+                IoErrorContext::NotADirectory,
+                Status::NOT_FOUND,
+            ));
+        }
+        entry = volume
+            .read_dir_entries(&entry.node)
+            .map_err(|err| io_error(path, IoErrorContext::ReadFailure, err.status()))?
+            .into_iter()
+            .find(|e| Path::new(&e.info.name).eq_ignore_case(Path::new(&component)))
+            .ok_or_else(|| io_error(path, IoErrorContext::OpenError, Status::NOT_FOUND))?;
+    }
+    Ok(entry)
+}
+
+/// Read-only access to a file system implemented in Rust, with an API
+/// modelled on [`fs::FileSystem`].
+///
+/// Paths are resolved from the root of the volume; names are compared
+/// without regard to ASCII case.
+///
+/// [`fs::FileSystem`]: crate::fs::FileSystem
+pub trait ReadOnlyFileSystem: Volume {
+    /// The volume label.
+    fn volume_label(&self) -> &CStr16;
+
+    /// Returns `Ok(true)` if the path points at an existing file or
+    /// directory.
+    ///
+    /// If it does not exist, `Ok(false)` is returned. If it cannot be
+    /// determined whether it exists or not, an error is returned.
+    fn try_exists(&mut self, path: impl AsRef<Path>) -> FileSystemResult<bool> {
+        match lookup(self, path.as_ref()) {
+            Ok(_) => Ok(true),
+            Err(Error::Io(err)) if err.uefi_error.status() == Status::NOT_FOUND => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Get the metadata of a file or directory.
+    fn metadata(&mut self, path: impl AsRef<Path>) -> FileSystemResult<Box<FileInfo>> {
+        lookup(self, path.as_ref()).map(|entry| entry.info.to_file_info())
+    }
+
+    /// Read the entire contents of a file into a bytes vector.
+    fn read(&mut self, path: impl AsRef<Path>) -> FileSystemResult<Vec<u8>> {
+        let mut file = self.open(path)?;
+        let mut content = vec![0; file.len() as usize];
+        file.read_exact(&mut content)
+            .map_err(|err| io_error(&file.path, IoErrorContext::ReadFailure, err.status()))?;
+        Ok(content)
+    }
+
+    /// Read the entire contents of a file into a Rust string.
+    fn read_to_string(&mut self, path: impl AsRef<Path>) -> FileSystemResult<String> {
+        String::from_utf8(self.read(path)?).map_err(Error::Utf8Encoding)
+    }
+
+    /// Returns an iterator over the entries of a directory. The `.` and
+    /// `..` entries are not included.
+    fn read_dir(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> FileSystemResult<vec::IntoIter<Box<FileInfo>>> {
+        let path = path.as_ref();
+        let dir = lookup(self, path)?;
+        if !dir.info.is_directory() {
+            return Err(io_error(
+                path,
+                IoErrorContext::NotADirectory,
+                Status::INVALID_PARAMETER,
+            ));
+        }
+        let entries = self
+            .read_dir_entries(&dir.node)
+            .map_err(|err| io_error(path, IoErrorContext::ReadFailure, err.status()))?;
+        let infos: Vec<_> = entries.iter().map(|e| e.info.to_file_info()).collect();
+        Ok(infos.into_iter())
+    }
+
+    /// Open a file for reading.
+    fn open(&mut self, path: impl AsRef<Path>) -> FileSystemResult<ReadOnlyFile<'_, Self>> {
+        let path = path.as_ref();
+        let entry = lookup(self, path)?;
+        if entry.info.is_directory() {
+            return Err(io_error(
+                path,
+                IoErrorContext::NotAFile,
+                Status::INVALID_PARAMETER,
+            ));
+        }
+        Ok(ReadOnlyFile {
+            fs: self,
+            node: entry.node,
+            len: entry.info.file_size,
+            pos: 0,
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+/// A file opened with [`ReadOnlyFileSystem::open`].
+///
+/// The file implements [`Read`] and [`Seek`].
+#[derive(Debug)]
+pub struct ReadOnlyFile<'a, F: ReadOnlyFileSystem + ?Sized> {
+    fs: &'a mut F,
+    node: F::Node,
+    len: u64,
+    pos: u64,
+    path: PathBuf,
+}
+
+impl<F: ReadOnlyFileSystem + ?Sized> ReadOnlyFile<'_, F> {
+    /// Size of the file in bytes.
+    #[allow(clippy::len_without_is_empty)]
+    #[must_use]
+    pub const fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Path the file was opened with.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl<F: ReadOnlyFileSystem + ?Sized> Read for ReadOnlyFile<'_, F> {
+    fn read(&mut self, buf: &mut [u8]) -> crate::Result<usize> {
+        let read = self.fs.read_file_at(&self.node, self.pos, buf)?;
+        self.pos += read as u64;
+        Ok(read)
+    }
+}
+
+impl<F: ReadOnlyFileSystem + ?Sized> Seek for ReadOnlyFile<'_, F> {
+    fn seek(&mut self, pos: SeekFrom) -> crate::Result<u64> {
+        self.pos = pos.resolve(self.pos, self.len)?;
+        Ok(self.pos)
+    }
+}
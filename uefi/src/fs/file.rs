@@ -0,0 +1,309 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Module for [`File`] and [`OpenOptions`].
+
+use super::*;
+use crate::data_types::Align;
+use crate::io::{Read, Seek, SeekFrom, Write};
+use crate::proto::media::file::RegularFile;
+use crate::Result;
+use alloc::boxed::Box;
+use alloc::vec;
+use core::mem;
+
+/// Options and flags which can be used to configure how a [`File`] is
+/// opened. This mirrors `std::fs::OpenOptions`.
+///
+/// ```no_run
+/// use uefi::cstr16;
+/// use uefi::fs::{FileSystem, FileSystemResult, OpenOptions};
+/// use uefi::io::Write;
+///
+/// fn append_log(fs: &mut FileSystem) -> FileSystemResult<()> {
+///     let mut log = OpenOptions::new()
+///         .append(true)
+///         .create(true)
+///         .open(fs, cstr16!("\\log.txt"))?;
+///     log.write_all(b"booted\n").unwrap();
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OpenOptions {
+    read: bool,
+    write: bool,
+    append: bool,
+    truncate: bool,
+    create: bool,
+    create_new: bool,
+}
+
+impl OpenOptions {
+    /// Creates a blank set of options with all flags set to `false`.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            read: false,
+            write: false,
+            append: false,
+            truncate: false,
+            create: false,
+            create_new: false,
+        }
+    }
+
+    /// Sets the option for read access.
+    pub fn read(&mut self, read: bool) -> &mut Self {
+        self.read = read;
+        self
+    }
+
+    /// Sets the option for write access.
+    pub fn write(&mut self, write: bool) -> &mut Self {
+        self.write = write;
+        self
+    }
+
+    /// Sets the option for append mode. Every write goes to the end of the
+    /// file. Implies write access.
+    pub fn append(&mut self, append: bool) -> &mut Self {
+        self.append = append;
+        self
+    }
+
+    /// Sets the option for truncating an existing file to zero length.
+    /// Requires write access.
+    pub fn truncate(&mut self, truncate: bool) -> &mut Self {
+        self.truncate = truncate;
+        self
+    }
+
+    /// Sets the option to create the file if it does not exist. Requires
+    /// write access.
+    pub fn create(&mut self, create: bool) -> &mut Self {
+        self.create = create;
+        self
+    }
+
+    /// Sets the option to create a new file, failing if it already exists.
+    /// Requires write access.
+    ///
+    /// UEFI has no exclusive create, so the check for an existing file and
+    /// the creation are two separate steps. Unlike in `std`, a file that is
+    /// created by someone else in between is opened instead of reported as
+    /// existing.
+    pub fn create_new(&mut self, create_new: bool) -> &mut Self {
+        self.create_new = create_new;
+        self
+    }
+
+    /// Opens the file at `path` on `fs` with these options. Equivalent to
+    /// [`FileSystem::open_with`].
+    pub fn open(&self, fs: &mut FileSystem, path: impl AsRef<Path>) -> FileSystemResult<File> {
+        fs.open_with(path, self)
+    }
+
+    const fn writable(&self) -> bool {
+        self.write || self.append
+    }
+
+    /// Returns the UEFI file mode matching these options, or `None` if the
+    /// combination of options is invalid.
+    pub(super) const fn mode(&self) -> Option<UefiFileMode> {
+        let writable = self.writable();
+        if !self.read && !writable {
+            return None;
+        }
+        if (self.truncate || self.create || self.create_new) && !writable {
+            return None;
+        }
+        // UEFI truncation is done via `set_len`; in append mode it makes no
+        // sense.
+        if self.truncate && self.append {
+            return None;
+        }
+
+        Some(if self.create || self.create_new {
+            UefiFileMode::CreateReadWrite
+        } else if writable {
+            UefiFileMode::ReadWrite
+        } else {
+            UefiFileMode::Read
+        })
+    }
+
+    pub(super) const fn wants_truncate(&self) -> bool {
+        self.truncate
+    }
+
+    pub(super) const fn wants_create_new(&self) -> bool {
+        self.create_new
+    }
+
+    pub(super) const fn wants_append(&self) -> bool {
+        self.append
+    }
+}
+
+/// An open file on a [`FileSystem`], similar to `std::fs::File`.
+///
+/// A `File` is returned by [`FileSystem::open`], [`FileSystem::create`], and
+/// [`FileSystem::open_with`]. It implements the [`Read`], [`Write`], and
+/// [`Seek`] traits from [`uefi::io`] for streaming access, so large files do
+/// not have to be loaded into memory at once.
+///
+/// The underlying UEFI file handle is closed when the `File` is dropped.
+/// Buffered data is not flushed on drop; use [`File::sync_all`] to make sure
+/// data has been written to the device.
+///
+/// [`uefi::io`]: crate::io
+#[derive(Debug)]
+pub struct File {
+    handle: RegularFile,
+    path: PathBuf,
+    append: bool,
+}
+
+impl File {
+    pub(super) const fn new(handle: RegularFile, path: PathBuf, append: bool) -> Self {
+        Self {
+            handle,
+            path,
+            append,
+        }
+    }
+
+    /// Returns the path that was used to open this file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn io_error(&self, context: IoErrorContext, uefi_error: crate::Error) -> Error {
+        Error::Io(IoError {
+            path: self.path.clone(),
+            context,
+            uefi_error,
+        })
+    }
+
+    /// Queries metadata about the file.
+    pub fn metadata(&mut self) -> FileSystemResult<Box<UefiFileInfo>> {
+        self.handle
+            .get_boxed_info::<UefiFileInfo>()
+            .map_err(|err| self.io_error(IoErrorContext::Metadata, err))
+    }
+
+    /// Returns the size of the file in bytes.
+    pub fn len(&mut self) -> FileSystemResult<u64> {
+        self.metadata().map(|info| info.file_size())
+    }
+
+    /// Returns `true` if the file has a size of zero bytes.
+    pub fn is_empty(&mut self) -> FileSystemResult<bool> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Truncates or extends the file to `size` bytes. When extending, the
+    /// new bytes are zero-filled by the file system driver.
+    ///
+    /// The file position is not changed.
+    pub fn set_len(&mut self, size: u64) -> FileSystemResult<()> {
+        let info = self.metadata()?;
+
+        let mut buf = vec![0; mem::size_of_val(&*info) + UefiFileInfo::alignment()];
+        let new_info = UefiFileInfo::new(
+            &mut buf,
+            size,
+            info.physical_size(),
+            *info.create_time(),
+            *info.last_access_time(),
+            *info.modification_time(),
+            info.attribute(),
+            info.file_name(),
+        )
+        .expect("buffer is large enough");
+
+        self.handle
+            .set_info(new_info)
+            .map_err(|err| self.io_error(IoErrorContext::WriteFailure, err))
+    }
+
+    /// Flushes all data to the device.
+    pub fn sync_all(&mut self) -> FileSystemResult<()> {
+        UefiFileTrait::flush(&mut self.handle)
+            .map_err(|err| self.io_error(IoErrorContext::FlushFailure, err))
+    }
+
+    /// Flushes all data to the device. UEFI does not distinguish between
+    /// data and metadata, so this is the same as [`File::sync_all`].
+    pub fn sync_data(&mut self) -> FileSystemResult<()> {
+        self.sync_all()
+    }
+
+    /// Returns the underlying UEFI file handle.
+    #[must_use]
+    pub fn into_inner(self) -> RegularFile {
+        self.handle
+    }
+}
+
+impl Read for File {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.handle.read(buf)
+    }
+}
+
+impl Write for File {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        if self.append {
+            self.handle.set_position(RegularFile::END_OF_FILE)?;
+        }
+        Write::write(&mut self.handle, buf)
+    }
+
+    fn flush(&mut self) -> Result {
+        Write::flush(&mut self.handle)
+    }
+}
+
+impl Seek for File {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        self.handle.seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_options_mode() {
+        assert_eq!(OpenOptions::new().mode(), None);
+        assert_eq!(
+            OpenOptions::new().read(true).mode(),
+            Some(UefiFileMode::Read)
+        );
+        assert_eq!(
+            OpenOptions::new().write(true).mode(),
+            Some(UefiFileMode::ReadWrite)
+        );
+        assert_eq!(
+            OpenOptions::new().append(true).mode(),
+            Some(UefiFileMode::ReadWrite)
+        );
+        assert_eq!(
+            OpenOptions::new().write(true).create(true).mode(),
+            Some(UefiFileMode::CreateReadWrite)
+        );
+        assert_eq!(
+            OpenOptions::new().append(true).create_new(true).mode(),
+            Some(UefiFileMode::CreateReadWrite)
+        );
+
+        // Invalid combinations.
+        assert_eq!(OpenOptions::new().read(true).create(true).mode(), None);
+        assert_eq!(OpenOptions::new().read(true).truncate(true).mode(), None);
+        assert_eq!(OpenOptions::new().append(true).truncate(true).mode(), None);
+    }
+}
@@ -47,6 +47,8 @@ impl Display for IoError {
 /// Enum that further specifies the context in that an [`Error`] occurred.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum IoErrorContext {
+    /// The file already exists but a new file was requested.
+    AlreadyExists,
     /// Can't delete the directory.
     CantDeleteDirectory,
     /// Can't delete the file.
@@ -75,6 +77,7 @@ pub enum IoErrorContext {
 impl Display for IoErrorContext {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let s = match self {
+            Self::AlreadyExists => "file already exists",
             Self::CantDeleteDirectory => "failed to delete directory",
             Self::CantDeleteFile => "failed to delete file",
             Self::FlushFailure => "failed to flush file",
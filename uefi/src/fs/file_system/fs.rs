@@ -2,8 +2,10 @@
 
 //! Module for [`FileSystem`].
 
+use crate::fs::glob::split_pattern;
 use crate::fs::*;
-use crate::Status;
+use crate::proto::string::unicode_collation::UnicodeCollation;
+use crate::{CStr16, CString16, Status};
 use alloc::boxed::Box;
 use alloc::string::String;
 use alloc::vec;
@@ -36,7 +38,7 @@ impl FileSystem {
     /// If the file does not exist, `Ok(false)` is returned. If it cannot be
     /// determined whether the file exists or not, an error is returned.
     pub fn try_exists(&mut self, path: impl AsRef<Path>) -> FileSystemResult<bool> {
-        match self.open(path.as_ref(), UefiFileMode::Read, false) {
+        match self.open_handle(path.as_ref(), UefiFileMode::Read, false) {
             Ok(_) => Ok(true),
             Err(Error::Io(err)) => {
                 if err.uefi_error.status() == Status::NOT_FOUND {
@@ -61,7 +63,7 @@ impl FileSystem {
 
         // Open the source file for reading.
         let mut src = self
-            .open(src_path, UefiFileMode::Read, false)?
+            .open_handle(src_path, UefiFileMode::Read, false)?
             .into_regular_file()
             .ok_or(Error::Io(IoError {
                 path: src_path.to_path_buf(),
@@ -89,7 +91,7 @@ impl FileSystem {
 
         // Create and open the destination file.
         let mut dest = self
-            .open(dest_path, UefiFileMode::CreateReadWrite, false)?
+            .open_handle(dest_path, UefiFileMode::CreateReadWrite, false)?
             .into_regular_file()
             .ok_or(Error::Io(IoError {
                 path: dest_path.to_path_buf(),
@@ -145,10 +147,69 @@ impl FileSystem {
         Ok(())
     }
 
+    /// Opens a file in read-only mode. Equivalent to `std::fs::File::open`.
+    pub fn open(&mut self, path: impl AsRef<Path>) -> FileSystemResult<File> {
+        self.open_with(path, OpenOptions::new().read(true))
+    }
+
+    /// Opens a file in write mode, creating it if it does not exist and
+    /// truncating it if it does. Equivalent to `std::fs::File::create`.
+    pub fn create(&mut self, path: impl AsRef<Path>) -> FileSystemResult<File> {
+        self.open_with(
+            path,
+            OpenOptions::new().write(true).create(true).truncate(true),
+        )
+    }
+
+    /// Opens a file with the given [`OpenOptions`].
+    ///
+    /// # Errors
+    ///
+    /// * [`IoErrorContext::OpenError`] with [`Status::INVALID_PARAMETER`] if
+    ///   the combination of options is invalid, e.g. `create` without write
+    ///   access.
+    /// * [`IoErrorContext::AlreadyExists`] if `create_new` is set and the
+    ///   file exists. This is checked before opening the file, so it is not
+    ///   atomic; see [`OpenOptions::create_new`].
+    /// * [`IoErrorContext::NotAFile`] if the path points to a directory.
+    pub fn open_with(
+        &mut self,
+        path: impl AsRef<Path>,
+        options: &OpenOptions,
+    ) -> FileSystemResult<File> {
+        let path = path.as_ref();
+        let error = |context, status: Status| {
+            Error::Io(IoError {
+                path: path.to_path_buf(),
+                context,
+                uefi_error: status.into(),
+            })
+        };
+
+        let mode = options
+            .mode()
+            .ok_or_else(|| error(IoErrorContext::OpenError, Status::INVALID_PARAMETER))?;
+
+        if options.wants_create_new() && self.try_exists(path)? {
+            return Err(error(IoErrorContext::AlreadyExists, Status::ACCESS_DENIED));
+        }
+
+        let handle = self
+            .open_handle(path, mode, false)?
+            .into_regular_file()
+            .ok_or_else(|| error(IoErrorContext::NotAFile, Status::INVALID_PARAMETER))?;
+
+        let mut file = File::new(handle, path.to_path_buf(), options.wants_append());
+        if options.wants_truncate() {
+            file.set_len(0)?;
+        }
+        Ok(file)
+    }
+
     /// Creates a new, empty directory at the provided path
     pub fn create_dir(&mut self, path: impl AsRef<Path>) -> FileSystemResult<()> {
         let path = path.as_ref();
-        self.open(path, UefiFileMode::CreateReadWrite, true)
+        self.open_handle(path, UefiFileMode::CreateReadWrite, true)
             .map(|_| ())
     }
 
@@ -181,7 +242,7 @@ impl FileSystem {
     /// directory, etc. Returns [`UefiFileInfo`].
     pub fn metadata(&mut self, path: impl AsRef<Path>) -> FileSystemResult<Box<UefiFileInfo>> {
         let path = path.as_ref();
-        let mut file = self.open(path, UefiFileMode::Read, false)?;
+        let mut file = self.open_handle(path, UefiFileMode::Read, false)?;
         file.get_boxed_info().map_err(|err| {
             Error::Io(IoError {
                 path: path.to_path_buf(),
@@ -196,7 +257,7 @@ impl FileSystem {
         let path = path.as_ref();
 
         let mut file = self
-            .open(path, UefiFileMode::Read, false)?
+            .open_handle(path, UefiFileMode::Read, false)?
             .into_regular_file()
             .ok_or(Error::Io(IoError {
                 path: path.to_path_buf(),
@@ -233,20 +294,89 @@ impl FileSystem {
 
     /// Returns an iterator over the entries within a directory.
     pub fn read_dir(&mut self, path: impl AsRef<Path>) -> FileSystemResult<UefiDirectoryIter> {
-        let path = path.as_ref();
-        let dir = self
-            .open(path, UefiFileMode::Read, false)?
-            .into_directory()
-            .ok_or(Error::Io(IoError {
-                path: path.to_path_buf(),
-                context: IoErrorContext::NotADirectory,
-                // We do not have a real UEFI error here as we have a logical
-                // problem.
-                uefi_error: Status::INVALID_PARAMETER.into(),
-            }))?;
+        let dir = self.open_dir(path.as_ref())?;
         Ok(UefiDirectoryIter::new(dir))
     }
 
+    /// Returns an iterator over all entries below `path`, recursively. See
+    /// [`WalkDir`].
+    pub fn walk_dir(&mut self, path: impl AsRef<Path>) -> FileSystemResult<WalkDir<'static>> {
+        let path = path.as_ref();
+        let dir = self.open_dir(path)?;
+        Ok(WalkDir::new(dir, path.to_path_buf()))
+    }
+
+    /// Returns an iterator over all paths matching `pattern`. See [`Glob`]
+    /// and [`Pattern`].
+    ///
+    /// ```no_run
+    /// use uefi::cstr16;
+    /// use uefi::fs::{FileSystem, FileSystemResult, PathBuf};
+    ///
+    /// fn find_bootloaders(fs: &mut FileSystem) -> FileSystemResult<Vec<PathBuf>> {
+    ///     fs.glob(cstr16!("\\EFI\\*\\*.efi"))?.collect()
+    /// }
+    /// ```
+    pub fn glob(&mut self, pattern: &CStr16) -> FileSystemResult<Glob<'static>> {
+        self.glob_impl(pattern, |pattern, name| pattern.matches(name))
+    }
+
+    /// Like [`FileSystem::glob`], but uses the firmware's
+    /// [`UnicodeCollation::metai_match`] for matching, so that case
+    /// folding is consistent with the firmware.
+    pub fn glob_with_collation<'a>(
+        &mut self,
+        pattern: &CStr16,
+        collation: &'a UnicodeCollation,
+    ) -> FileSystemResult<Glob<'a>> {
+        self.glob_impl(pattern, move |pattern, name| {
+            pattern.matches_with(name, collation)
+        })
+    }
+
+    fn glob_impl<'a>(
+        &mut self,
+        pattern: &CStr16,
+        matches: impl Fn(&Pattern, &CStr16) -> bool + 'a,
+    ) -> FileSystemResult<Glob<'a>> {
+        let mut components = split_pattern(pattern);
+
+        // Leading components without wildcards are opened directly instead
+        // of being matched against every directory entry.
+        let literal = components
+            .iter()
+            .take(components.len().saturating_sub(1))
+            .take_while(|c| !c.has_wildcards())
+            .count();
+        let mut base = PathBuf::from(SEPARATOR_STR);
+        for component in components.drain(..literal) {
+            // Use the name of the directory entry, so that the paths have
+            // the case of the names on disk rather than of the pattern. If
+            // there is no such entry, `walk_dir` reports the error.
+            let name = self.read_dir(&base).ok().and_then(|mut entries| {
+                entries
+                    .find_map(|info| {
+                        info.ok()
+                            .filter(|info| matches(&component, info.file_name()))
+                    })
+                    .map(|info| CString16::from(info.file_name()))
+            });
+            base.push(name.as_deref().unwrap_or(component.as_cstr16()));
+        }
+
+        let depth = components.len();
+        let walk = self
+            .walk_dir(&base)?
+            .max_depth(depth)
+            .filter_entry(move |entry| {
+                components
+                    .get(entry.depth() - 1)
+                    .is_some_and(|pattern| matches(pattern, entry.file_name()))
+                    && (entry.depth() == depth || entry.is_directory())
+            });
+        Ok(Glob::new(walk, depth))
+    }
+
     /// Read the entire contents of a file into a Rust string.
     pub fn read_to_string(&mut self, path: impl AsRef<Path>) -> FileSystemResult<String> {
         String::from_utf8(self.read(path)?).map_err(Error::Utf8Encoding)
@@ -257,7 +387,7 @@ impl FileSystem {
         let path = path.as_ref();
 
         let file = self
-            .open(path, UefiFileMode::ReadWrite, false)?
+            .open_handle(path, UefiFileMode::ReadWrite, false)?
             .into_type()
             .unwrap();
 
@@ -285,24 +415,18 @@ impl FileSystem {
     /// carefully!
     pub fn remove_dir_all(&mut self, path: impl AsRef<Path>) -> FileSystemResult<()> {
         let path = path.as_ref();
-        for file_info in self
-            .read_dir(path)?
-            .filter_map(|file_info_result| file_info_result.ok())
-        {
-            if COMMON_SKIP_DIRS.contains(&file_info.file_name()) {
-                continue;
-            }
-
-            let mut abs_entry_path = PathBuf::new();
-            abs_entry_path.push(path);
-            abs_entry_path.push(file_info.file_name());
-            if file_info.is_directory() {
-                // delete all inner files
-                // This recursion is fine as there are no links in UEFI/FAT file
-                // systems. No cycles possible.
-                self.remove_dir_all(&abs_entry_path)?;
+        // Collect the entries first, so that no directory is modified while
+        // the walk is reading it.
+        let entries = self
+            .walk_dir(path)?
+            .collect::<FileSystemResult<Vec<WalkDirEntry>>>()?;
+        // The walk yields directories before their contents, so removing in
+        // reverse order empties each directory before it is removed.
+        for entry in entries.into_iter().rev() {
+            if entry.is_directory() {
+                self.remove_dir(entry.path())?;
             } else {
-                self.remove_file(abs_entry_path)?;
+                self.remove_file(entry.path())?;
             }
         }
         // Now that the dir is empty, we delete it as final step.
@@ -315,7 +439,7 @@ impl FileSystem {
         let path = path.as_ref();
 
         let file = self
-            .open(path, UefiFileMode::ReadWrite, false)?
+            .open_handle(path, UefiFileMode::ReadWrite, false)?
             .into_type()
             .unwrap();
 
@@ -365,7 +489,7 @@ impl FileSystem {
         }
 
         let mut handle = self
-            .open(path, UefiFileMode::CreateReadWrite, false)?
+            .open_handle(path, UefiFileMode::CreateReadWrite, false)?
             .into_regular_file()
             .unwrap();
 
@@ -386,6 +510,19 @@ impl FileSystem {
         Ok(())
     }
 
+    /// Opens the provided path as directory.
+    fn open_dir(&mut self, path: &Path) -> FileSystemResult<UefiDirectoryHandle> {
+        self.open_handle(path, UefiFileMode::Read, false)?
+            .into_directory()
+            .ok_or(Error::Io(IoError {
+                path: path.to_path_buf(),
+                context: IoErrorContext::NotADirectory,
+                // We do not have a real UEFI error here as we have a logical
+                // problem.
+                uefi_error: Status::INVALID_PARAMETER.into(),
+            }))
+    }
+
     /// Opens a fresh handle to the root directory of the volume.
     fn open_root(&mut self) -> FileSystemResult<UefiDirectoryHandle> {
         self.0.open_volume().map_err(|err| {
@@ -407,7 +544,7 @@ impl FileSystem {
     /// May create a file if [`UefiFileMode::CreateReadWrite`] is set. May
     /// create a directory if [`UefiFileMode::CreateReadWrite`] and `create_dir`
     /// is set. The parameter `create_dir` is ignored otherwise.
-    fn open(
+    fn open_handle(
         &mut self,
         path: &Path,
         mode: UefiFileMode,
@@ -0,0 +1,257 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Module for glob-style path matching. See [`Pattern`] and [`Glob`].
+
+use super::path::fold_case;
+use super::*;
+use crate::proto::string::unicode_collation::UnicodeCollation;
+use crate::{CStr16, CString16, Char16};
+use alloc::vec::Vec;
+
+/// A wildcard pattern for a single path component.
+///
+/// The syntax is the one used by [`UnicodeCollation::metai_match`]:
+///
+/// | Pattern       | Meaning                                        |
+/// |---------------|------------------------------------------------|
+/// | `*`           | Match 0 or more characters                     |
+/// | `?`           | Match any one character                        |
+/// | `[abc]`       | Match any character in the set                 |
+/// | `[a-z]`       | Match any character between `a` and `z`        |
+/// | other         | Match the character itself                     |
+///
+/// Matching is case-insensitive for ASCII letters, like on FAT file systems.
+/// Use [`Pattern::matches_with`] to let the firmware decide which characters
+/// are equal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pattern(CString16);
+
+impl Pattern {
+    /// Creates a pattern from a string.
+    #[must_use]
+    pub fn new(pattern: &CStr16) -> Self {
+        Self(CString16::from(pattern))
+    }
+
+    /// Returns the underlying pattern string.
+    #[must_use]
+    pub fn as_cstr16(&self) -> &CStr16 {
+        &self.0
+    }
+
+    /// Returns whether the pattern contains any wildcard characters.
+    #[must_use]
+    pub fn has_wildcards(&self) -> bool {
+        self.0
+            .iter()
+            .any(|c| matches!(char::from(*c), '*' | '?' | '['))
+    }
+
+    /// Checks whether `name` matches this pattern. ASCII letters are
+    /// compared case-insensitively.
+    #[must_use]
+    pub fn matches(&self, name: &CStr16) -> bool {
+        match_chars(self.0.as_slice(), name.as_slice())
+    }
+
+    /// Checks whether `name` matches this pattern using the firmware's
+    /// [`UnicodeCollation::metai_match`], which applies the case folding
+    /// rules of the platform language.
+    #[must_use]
+    pub fn matches_with(&self, name: &CStr16, collation: &UnicodeCollation) -> bool {
+        collation.metai_match(name, &self.0)
+    }
+}
+
+/// Matches a `[...]` set starting after the opening bracket. Returns whether
+/// `c` is in the set and the remainder of the pattern after the closing
+/// bracket, or `None` if the set is not terminated.
+fn match_set(pattern: &[Char16], c: Char16) -> Option<(bool, &[Char16])> {
+    let c = fold_case(c);
+    let end = pattern.iter().position(|p| char::from(*p) == ']')?;
+    let set = &pattern[..end];
+
+    let mut found = false;
+    let mut i = 0;
+    while i < set.len() {
+        let lo = fold_case(set[i]);
+        if i + 2 < set.len() && char::from(set[i + 1]) == '-' {
+            let hi = fold_case(set[i + 2]);
+            found |= (lo..=hi).contains(&c);
+            i += 3;
+        } else {
+            found |= lo == c;
+            i += 1;
+        }
+    }
+    Some((found, &pattern[end + 1..]))
+}
+
+/// Matches a single character against the pattern element at the start of
+/// `pattern`, which must not be `*`. Returns the length of the element if
+/// it matches.
+fn match_one(pattern: &[Char16], c: Char16) -> Option<usize> {
+    let (&p, rest) = pattern.split_first()?;
+    match char::from(p) {
+        '?' => Some(1),
+        '[' => match match_set(rest, c) {
+            Some((true, rest)) => Some(pattern.len() - rest.len()),
+            Some((false, _)) => None,
+            // Unterminated set: treat `[` as a literal.
+            None => (c == p).then_some(1),
+        },
+        _ => (fold_case(c) == fold_case(p)).then_some(1),
+    }
+}
+
+/// Matches `name` against `pattern` without recursion. On a mismatch, the
+/// last `*` is retried with one more character, which is enough because
+/// every other pattern element matches exactly one character. This keeps
+/// the run time linear in practice for any number of stars.
+fn match_chars(pattern: &[Char16], name: &[Char16]) -> bool {
+    let is_star = |c: &Char16| char::from(*c) == '*';
+    let (mut p, mut n) = (0, 0);
+    // Pattern position after the last `*` and the name position at which
+    // its match ends.
+    let mut star = None;
+    while n < name.len() {
+        if pattern.get(p).is_some_and(is_star) {
+            p += 1;
+            star = Some((p, n));
+        } else if let Some(len) = match_one(&pattern[p..], name[n]) {
+            p += len;
+            n += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p;
+            n = star_n + 1;
+            star = Some((star_p, n));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(is_star)
+}
+
+/// Splits a glob pattern into per-component patterns. Both `\` and `/` are
+/// accepted as separators; empty components are ignored.
+pub(super) fn split_pattern(pattern: &CStr16) -> Vec<Pattern> {
+    let mut components = Vec::new();
+    let mut current = CString16::new();
+    for c in pattern.iter() {
+        if *c == SEPARATOR || char::from(*c) == '/' {
+            if !current.is_empty() {
+                components.push(Pattern(core::mem::take(&mut current)));
+            }
+        } else {
+            current.push(*c);
+        }
+    }
+    if !current.is_empty() {
+        components.push(Pattern(current));
+    }
+    components
+}
+
+/// Iterator over the paths matching a glob pattern, created with
+/// [`FileSystem::glob`] or [`FileSystem::glob_with_collation`].
+///
+/// Each component of the pattern is matched against the entry name at the
+/// same depth, so `\EFI\*\*.efi` finds all `.efi` files exactly two levels
+/// below `\EFI`. Directories whose names do not match are not descended into.
+///
+/// As with [`WalkDir`], errors are reported per entry.
+#[derive(Debug)]
+pub struct Glob<'a> {
+    walk: WalkDir<'a>,
+    depth: usize,
+}
+
+impl<'a> Glob<'a> {
+    pub(super) const fn new(walk: WalkDir<'a>, depth: usize) -> Self {
+        Self { walk, depth }
+    }
+}
+
+impl Iterator for Glob<'_> {
+    type Item = FileSystemResult<PathBuf>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for entry in self.walk.by_ref() {
+            match entry {
+                Ok(entry) if entry.depth() == self.depth => return Some(Ok(entry.into_path())),
+                Ok(_) => {}
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cstr16;
+
+    #[test]
+    fn test_pattern_matches() {
+        let efi = Pattern::new(cstr16!("*.efi"));
+        assert!(efi.matches(cstr16!("bootx64.efi")));
+        assert!(efi.matches(cstr16!("BOOTX64.EFI")));
+        assert!(efi.matches(cstr16!(".efi")));
+        assert!(!efi.matches(cstr16!("bootx64.efi.bak")));
+        assert!(!efi.matches(cstr16!("efi")));
+
+        let question = Pattern::new(cstr16!("d?.*"));
+        assert!(question.matches(cstr16!("Dx.txt")));
+        assert!(question.matches(cstr16!("da.")));
+        assert!(!question.matches(cstr16!("d.txt")));
+
+        let set = Pattern::new(cstr16!("boot[a-z0-9][xy]64.efi"));
+        assert!(set.matches(cstr16!("bootAx64.efi")));
+        assert!(set.matches(cstr16!("boot7y64.efi")));
+        assert!(!set.matches(cstr16!("boot_x64.efi")));
+        assert!(!set.matches(cstr16!("bootaz64.efi")));
+
+        let literal = Pattern::new(cstr16!("[abc"));
+        assert!(literal.matches(cstr16!("[abc")));
+        assert!(!literal.matches(cstr16!("a")));
+
+        assert!(Pattern::new(cstr16!("*")).matches(cstr16!("")));
+        assert!(Pattern::new(cstr16!("**a*")).matches(cstr16!("bab")));
+        assert!(Pattern::new(cstr16!("*[ab]?c")).matches(cstr16!("xxbxbyc")));
+        assert!(!Pattern::new(cstr16!("a*b")).matches(cstr16!("ab_")));
+    }
+
+    #[test]
+    fn test_pattern_many_stars() {
+        // Backtracking over every star would take far too long here.
+        let cstring = |s: &str| CString16::try_from(s).unwrap();
+        let pattern = Pattern::new(&cstring(&alloc::format!("{}b", "a*".repeat(30))));
+        let name = "a".repeat(200);
+        assert!(!pattern.matches(&cstring(&name)));
+        let name = cstring(&alloc::format!("{name}b"));
+        assert!(pattern.matches(&name));
+    }
+
+    #[test]
+    fn test_has_wildcards() {
+        assert!(!Pattern::new(cstr16!("EFI")).has_wildcards());
+        assert!(Pattern::new(cstr16!("*.efi")).has_wildcards());
+        assert!(Pattern::new(cstr16!("a?")).has_wildcards());
+        assert!(Pattern::new(cstr16!("[ab]")).has_wildcards());
+    }
+
+    #[test]
+    fn test_split_pattern() {
+        let components = split_pattern(cstr16!("\\EFI\\*/*.efi\\"));
+        assert_eq!(
+            components,
+            [
+                Pattern::new(cstr16!("EFI")),
+                Pattern::new(cstr16!("*")),
+                Pattern::new(cstr16!("*.efi")),
+            ]
+        );
+        assert!(split_pattern(cstr16!("\\")).is_empty());
+    }
+}
@@ -41,22 +41,33 @@
 //! ```
 //!
 //! # API Hints
-//! There is no `File` abstraction as in the Rust `std` library. Instead, it is
-//! intended to work with the file system via dedicated functions, similar to
-//! the public functions of the `std::fs` module.
+//! Whole-file operations are available as dedicated functions on
+//! [`FileSystem`], similar to the public functions of the `std::fs` module.
+//! For streaming access, [`FileSystem::open`], [`FileSystem::create`], and
+//! [`OpenOptions`] return a [`File`] handle that implements the traits of
+//! [`uefi::io`].
 //!
 //! There is no automatic synchronization of the file system for concurrent
 //! accesses. This is in the responsibility of the user.
 //!
 //! [`cstr16!`]: crate::cstr16
+//! [`uefi::io`]: crate::io
 
 mod dir_entry_iter;
+mod file;
 mod file_system;
+mod glob;
 mod path;
+mod provider;
 mod uefi_types;
+mod walk_dir;
 
 pub use dir_entry_iter::*;
+pub use file::*;
 pub use file_system::*;
+pub use glob::{Glob, Pattern};
 pub use path::*;
+pub use provider::{FileSystemProvider, InstalledFileSystem, NodeInfo, VolumeInfo};
+pub use walk_dir::{WalkDir, WalkDirEntry};
 
 use uefi_types::*;
@@ -11,9 +11,23 @@
 //!
 //! # Path Structure
 //!
-//! Paths use the [`SEPARATOR`] character as separator. Paths are absolute and
-//! do not contain `.` or `..` components. However, this can be implemented in
-//! the future.
+//! Paths use the [`SEPARATOR`] character as separator. Paths passed to
+//! [`FileSystem`] are always treated as absolute, i.e., relative to the root
+//! of the volume, with or without a leading separator. Relative paths are
+//! still useful for building paths with [`Path::join`] and
+//! [`Path::strip_prefix`].
+//!
+//! `.` and `..` components are not resolved by the firmware on all platforms.
+//! Use [`Path::normalize`] to resolve them before opening a path.
+//!
+//! # Case Sensitivity
+//!
+//! The [`PartialEq`] implementations of [`Path`] and [`PathBuf`] compare
+//! components exactly. As UEFI file systems are FAT-like and thus
+//! case-insensitive, use [`Path::eq_ignore_case`] to check whether two paths
+//! refer to the same file.
+//!
+//! [`FileSystem`]: crate::fs::FileSystem
 
 mod path;
 mod pathbuf;
@@ -50,3 +64,17 @@ pub const CHARACTER_DENY_LIST: [Char16; 10] = unsafe {
         Char16::from_u16_unchecked('|' as u16),
     ]
 };
+
+/// Folds ASCII letters to lowercase for case-insensitive comparisons. FAT
+/// file systems compare names case-insensitively; the full upcase table is
+/// only known to the firmware, see [`UnicodeCollation`].
+///
+/// [`UnicodeCollation`]: crate::proto::string::unicode_collation::UnicodeCollation
+pub(super) fn fold_case(c: Char16) -> u16 {
+    let c = u16::from(c);
+    if (u16::from(b'A')..=u16::from(b'Z')).contains(&c) {
+        c + u16::from(b'a' - b'A')
+    } else {
+        c
+    }
+}
@@ -3,11 +3,20 @@
 // allow "path.rs" in "path"
 #![allow(clippy::module_inception)]
 
-use crate::fs::path::{PathBuf, SEPARATOR};
-use crate::{CStr16, CString16};
+use crate::fs::path::{fold_case, PathBuf, SEPARATOR};
+use crate::proto::string::unicode_collation::UnicodeCollation;
+use crate::{cstr16, CStr16, CString16, Char16};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
 use core::fmt::{Display, Formatter};
 use core::ptr;
 
+/// The `.` component, referring to the current directory.
+const CUR_DIR: &CStr16 = cstr16!(".");
+
+/// The `..` component, referring to the parent directory.
+const PARENT_DIR: &CStr16 = cstr16!("..");
+
 /// A path similar to the `Path` of the standard library, but based on
 /// [`CStr16`] strings and [`SEPARATOR`] as separator.
 ///
@@ -85,6 +94,204 @@ impl Path {
     pub const fn is_empty(&self) -> bool {
         self.to_cstr16().is_empty()
     }
+
+    /// Returns whether the path starts with a [`SEPARATOR`], i.e., is
+    /// relative to the root of the volume.
+    #[must_use]
+    pub fn is_absolute(&self) -> bool {
+        self.0.as_slice().first() == Some(&SEPARATOR)
+    }
+
+    /// Returns the final component of the path.
+    ///
+    /// Returns `None` if the path is empty, ends with a separator, or ends
+    /// with `.` or `..`.
+    #[must_use]
+    pub fn file_name(&self) -> Option<CString16> {
+        self.components()
+            .last()
+            .filter(|name| !name.is_empty() && *name != CUR_DIR && *name != PARENT_DIR)
+    }
+
+    /// Returns the [`file_name`] without its extension.
+    ///
+    /// Like in the standard library, a leading `.` does not start an
+    /// extension, so the stem of `.hidden` is `.hidden`.
+    ///
+    /// [`file_name`]: Self::file_name
+    #[must_use]
+    pub fn file_stem(&self) -> Option<CString16> {
+        let name = self.file_name()?;
+        let (stem, _) = split_extension(name.as_slice());
+        Some(cstring_from_slice(stem))
+    }
+
+    /// Returns the extension of the [`file_name`], without the leading `.`.
+    ///
+    /// [`file_name`]: Self::file_name
+    #[must_use]
+    pub fn extension(&self) -> Option<CString16> {
+        let name = self.file_name()?;
+        let (_, extension) = split_extension(name.as_slice());
+        extension.map(cstring_from_slice)
+    }
+
+    /// Returns a copy of the path with the extension replaced by
+    /// `extension`. See [`PathBuf::set_extension`].
+    #[must_use]
+    pub fn with_extension(&self, extension: &CStr16) -> PathBuf {
+        let mut path = self.to_path_buf();
+        path.set_extension(extension);
+        path
+    }
+
+    /// Returns a copy of the path with `path` appended. See
+    /// [`PathBuf::push`].
+    #[must_use]
+    pub fn join<P: AsRef<Self>>(&self, path: P) -> PathBuf {
+        let mut buf = self.to_path_buf();
+        buf.push(path);
+        buf
+    }
+
+    /// Returns whether the leading components of the path are equal to the
+    /// components of `base`. Only whole components are compared, so `\ab`
+    /// does not start with `\a`.
+    ///
+    /// Like the [`PartialEq`] implementation, this ignores the leading
+    /// separator and compares case-sensitively.
+    #[must_use]
+    pub fn starts_with<P: AsRef<Self>>(&self, base: P) -> bool {
+        let mut components = self.non_empty_components();
+        base.as_ref()
+            .non_empty_components()
+            .all(|c| components.next().as_ref() == Some(&c))
+    }
+
+    /// Returns the path relative to `base`, or `None` if the path does not
+    /// [start with] `base`. The returned path is relative.
+    ///
+    /// [start with]: Self::starts_with
+    #[must_use]
+    pub fn strip_prefix<P: AsRef<Self>>(&self, base: P) -> Option<PathBuf> {
+        let base = base.as_ref();
+        if !self.starts_with(base) {
+            return None;
+        }
+        let components = self
+            .non_empty_components()
+            .skip(base.non_empty_components().count());
+        Some(join_components(components, false))
+    }
+
+    /// Returns a normalized copy of the path.
+    ///
+    /// Empty and `.` components are removed and `..` components remove the
+    /// preceding component. `..` components that would leave the root of an
+    /// absolute path are dropped; leading `..` components of a relative path
+    /// are kept. The leading separator is preserved.
+    #[must_use]
+    pub fn normalize(&self) -> PathBuf {
+        let is_absolute = self.is_absolute();
+        let mut components: Vec<CString16> = Vec::new();
+        for component in self.non_empty_components() {
+            if component == CUR_DIR {
+                continue;
+            }
+            if component == PARENT_DIR {
+                match components.last() {
+                    Some(last) if *last != PARENT_DIR => {
+                        components.pop();
+                    }
+                    _ if is_absolute => {}
+                    _ => components.push(component),
+                }
+                continue;
+            }
+            components.push(component);
+        }
+        join_components(components.into_iter(), is_absolute)
+    }
+
+    /// Compares two paths component by component, treating ASCII letters
+    /// case-insensitively like FAT file systems do.
+    ///
+    /// Use [`eq_with_collation`] to let the firmware decide which characters
+    /// are equal.
+    ///
+    /// [`eq_with_collation`]: Self::eq_with_collation
+    #[must_use]
+    pub fn eq_ignore_case<P: AsRef<Self>>(&self, other: P) -> bool {
+        self.eq_by(other.as_ref(), |c1, c2| {
+            c1.num_chars() == c2.num_chars()
+                && c1
+                    .iter()
+                    .zip(c2.iter())
+                    .all(|(a, b)| fold_case(*a) == fold_case(*b))
+        })
+    }
+
+    /// Compares two paths component by component using the firmware's
+    /// [`UnicodeCollation::stri_coll`], which applies the case folding rules
+    /// of the platform language.
+    #[must_use]
+    pub fn eq_with_collation<P: AsRef<Self>>(
+        &self,
+        other: P,
+        collation: &UnicodeCollation,
+    ) -> bool {
+        self.eq_by(other.as_ref(), |c1, c2| {
+            collation.stri_coll(c1, c2) == Ordering::Equal
+        })
+    }
+
+    fn eq_by(&self, other: &Self, mut eq: impl FnMut(&CStr16, &CStr16) -> bool) -> bool {
+        let mut components = self.non_empty_components();
+        let mut other_components = other.non_empty_components();
+        loop {
+            match (components.next(), other_components.next()) {
+                (None, None) => return true,
+                (Some(c1), Some(c2)) if eq(&c1, &c2) => {}
+                _ => return false,
+            }
+        }
+    }
+
+    /// Like [`Self::components`], but skips empty components caused by
+    /// repeated or trailing separators.
+    fn non_empty_components(&self) -> impl Iterator<Item = CString16> + '_ {
+        self.components().filter(|c| !c.is_empty())
+    }
+}
+
+/// Splits a file name into stem and extension. A leading `.` does not start
+/// an extension.
+fn split_extension(name: &[Char16]) -> (&[Char16], Option<&[Char16]>) {
+    match name.iter().rposition(|c| char::from(*c) == '.') {
+        Some(0) | None => (name, None),
+        Some(dot) => (&name[..dot], Some(&name[dot + 1..])),
+    }
+}
+
+fn cstring_from_slice(chars: &[Char16]) -> CString16 {
+    let mut string = CString16::new();
+    chars.iter().for_each(|c| string.push(*c));
+    string
+}
+
+/// Joins components with [`SEPARATOR`], optionally with a leading separator.
+fn join_components(components: impl Iterator<Item = CString16>, absolute: bool) -> PathBuf {
+    let mut path = CString16::new();
+    if absolute {
+        path.push(SEPARATOR);
+    }
+    for (i, component) in components.enumerate() {
+        if i > 0 {
+            path.push(SEPARATOR);
+        }
+        path.push_str(&component);
+    }
+    PathBuf::from(path)
 }
 
 impl Display for Path {
@@ -159,6 +366,7 @@ impl Iterator for Components<'_> {
 
 mod convenience_impls {
     use super::*;
+    use crate::proto::device_path::build;
     use core::borrow::Borrow;
 
     impl AsRef<Path> for &Path {
@@ -196,6 +404,20 @@ mod convenience_impls {
             Path::new(self)
         }
     }
+
+    /// Creates a [`FilePath`] device path node, e.g., for use with
+    /// [`DevicePathBuilder`] or [`DevicePath::append_node`].
+    ///
+    /// [`FilePath`]: build::media::FilePath
+    /// [`DevicePathBuilder`]: build::DevicePathBuilder
+    /// [`DevicePath::append_node`]: crate::proto::device_path::DevicePath::append_node
+    impl<'a> From<&'a Path> for build::media::FilePath<'a> {
+        fn from(path: &'a Path) -> Self {
+            Self {
+                path_name: path.to_cstr16(),
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -294,4 +516,124 @@ mod tests {
         assert_ne!(path1, path3);
         assert_ne!(path3, path1);
     }
+
+    #[test]
+    fn test_file_name_stem_extension() {
+        let path = Path::new(cstr16!(r"\EFI\BOOT\BOOTX64.EFI"));
+        assert_eq!(path.file_name().unwrap(), cstr16!("BOOTX64.EFI"));
+        assert_eq!(path.file_stem().unwrap(), cstr16!("BOOTX64"));
+        assert_eq!(path.extension().unwrap(), cstr16!("EFI"));
+
+        let path = Path::new(cstr16!(r"a\archive.tar.gz"));
+        assert_eq!(path.file_stem().unwrap(), cstr16!("archive.tar"));
+        assert_eq!(path.extension().unwrap(), cstr16!("gz"));
+
+        let path = Path::new(cstr16!(r"\.hidden"));
+        assert_eq!(path.file_stem().unwrap(), cstr16!(".hidden"));
+        assert_eq!(path.extension(), None);
+
+        let path = Path::new(cstr16!("trailing."));
+        assert_eq!(path.file_stem().unwrap(), cstr16!("trailing"));
+        assert_eq!(path.extension().unwrap(), cstr16!(""));
+
+        assert_eq!(Path::new(cstr16!("")).file_name(), None);
+        assert_eq!(Path::new(cstr16!(r"\")).file_name(), None);
+        assert_eq!(Path::new(cstr16!(r"a\")).file_name(), None);
+        assert_eq!(Path::new(cstr16!(r"a\..")).file_name(), None);
+        assert_eq!(Path::new(cstr16!(r"a\.")).file_stem(), None);
+    }
+
+    #[test]
+    fn test_with_extension() {
+        let path = Path::new(cstr16!(r"\EFI\BOOT\BOOTX64.EFI"));
+        assert_eq!(
+            path.with_extension(cstr16!("BAK")).to_cstr16(),
+            cstr16!(r"\EFI\BOOT\BOOTX64.BAK")
+        );
+        assert_eq!(
+            path.with_extension(cstr16!("")).to_cstr16(),
+            cstr16!(r"\EFI\BOOT\BOOTX64")
+        );
+        assert_eq!(
+            Path::new(cstr16!("file"))
+                .with_extension(cstr16!("txt"))
+                .to_cstr16(),
+            cstr16!("file.txt")
+        );
+        assert_eq!(
+            Path::new(cstr16!(r"a\"))
+                .with_extension(cstr16!("txt"))
+                .to_cstr16(),
+            cstr16!(r"a\")
+        );
+    }
+
+    #[test]
+    fn test_join() {
+        let path = Path::new(cstr16!(r"\EFI"));
+        assert_eq!(
+            path.join(cstr16!("BOOT")).to_cstr16(),
+            cstr16!(r"\EFI\BOOT")
+        );
+        assert_eq!(
+            path.join(cstr16!(r"\BOOT")).to_cstr16(),
+            cstr16!(r"\EFI\BOOT")
+        );
+        assert_eq!(path.join(cstr16!("a/b")).to_cstr16(), cstr16!(r"\EFI\a\b"));
+        assert_eq!(path.join(cstr16!("")).to_cstr16(), cstr16!(r"\EFI"));
+    }
+
+    #[test]
+    fn test_is_absolute() {
+        assert!(Path::new(cstr16!(r"\")).is_absolute());
+        assert!(Path::new(cstr16!(r"\a\b")).is_absolute());
+        assert!(!Path::new(cstr16!(r"a\b")).is_absolute());
+        assert!(!Path::new(cstr16!("")).is_absolute());
+    }
+
+    #[test]
+    fn test_starts_with_strip_prefix() {
+        let path = Path::new(cstr16!(r"\EFI\BOOT\BOOTX64.EFI"));
+        assert!(path.starts_with(cstr16!(r"\EFI")));
+        assert!(path.starts_with(cstr16!(r"EFI\BOOT\")));
+        assert!(path.starts_with(cstr16!("")));
+        assert!(path.starts_with(path));
+        assert!(!path.starts_with(cstr16!(r"\EF")));
+        assert!(!path.starts_with(cstr16!(r"\efi")));
+        assert!(!path.starts_with(cstr16!(r"\EFI\BOOT\BOOTX64.EFI\x")));
+
+        assert_eq!(
+            path.strip_prefix(cstr16!(r"\EFI")).unwrap().to_cstr16(),
+            cstr16!(r"BOOT\BOOTX64.EFI")
+        );
+        assert!(path.strip_prefix(path).unwrap().is_empty());
+        assert_eq!(path.strip_prefix(cstr16!(r"\BOOT")), None);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let normalize = |s: &CStr16| Path::new(s).normalize();
+        assert_eq!(
+            normalize(cstr16!(r"\EFI\.\BOOT\..\ubuntu\\grub.efi")).to_cstr16(),
+            cstr16!(r"\EFI\ubuntu\grub.efi")
+        );
+        assert_eq!(
+            normalize(cstr16!(r"\..\a\..\..")).to_cstr16(),
+            cstr16!(r"\")
+        );
+        assert_eq!(
+            normalize(cstr16!(r"..\a\..\..\b\")).to_cstr16(),
+            cstr16!(r"..\..\b")
+        );
+        assert_eq!(normalize(cstr16!(r".\.")).to_cstr16(), cstr16!(""));
+    }
+
+    #[test]
+    fn test_eq_ignore_case() {
+        let path = Path::new(cstr16!(r"\EFI\BOOT\BOOTX64.EFI"));
+        assert!(path.eq_ignore_case(cstr16!(r"\efi\boot\bootx64.efi")));
+        assert!(path.eq_ignore_case(cstr16!(r"Efi\Boot\BootX64.Efi\")));
+        assert!(!path.eq_ignore_case(cstr16!(r"\efi\boot")));
+        assert!(!path.eq_ignore_case(cstr16!(r"\efi\boot\bootx64.efl")));
+    }
 }
@@ -2,6 +2,8 @@
 
 use crate::fs::path::Path;
 use crate::fs::SEPARATOR;
+use crate::proto::device_path::media::FilePath;
+use crate::proto::device_path::DevicePath;
 use crate::{CStr16, CString16, Char16};
 use core::fmt::{Display, Formatter};
 
@@ -26,9 +28,30 @@ impl PathBuf {
         Self(string)
     }
 
+    /// Builds a path from the [`FilePath`] nodes of a device path, such as
+    /// the file path of a loaded image.
+    ///
+    /// Firmware may split a path across multiple consecutive nodes, e.g.,
+    /// `\EFI\BOOT` and `BOOTX64.EFI`; all of them are joined. Returns `None`
+    /// if the device path contains no file path node or a node is not a
+    /// valid null-terminated string.
+    #[must_use]
+    pub fn from_device_path(device_path: &DevicePath) -> Option<Self> {
+        let mut path = None;
+        for node in device_path.node_iter() {
+            if let Ok(node) = <&FilePath>::try_from(node) {
+                let name = Self::try_from(node).ok()?;
+                path.get_or_insert_with(Self::new).push(name);
+            }
+        }
+        path
+    }
+
     /// Extends self with path.
     ///
-    /// UNIX separators (`/`) will be replaced by [`SEPARATOR`] on the fly.
+    /// UNIX separators (`/`) will be replaced by [`SEPARATOR`] on the fly. A
+    /// leading separator of `path` is not duplicated, so pushing `\b` onto
+    /// `\a` results in `\a\b`.
     pub fn push<P: AsRef<Path>>(&mut self, path: P) {
         const SEARCH: Char16 = unsafe { Char16::from_u16_unchecked('/' as u16) };
 
@@ -37,20 +60,50 @@ impl PathBuf {
             return;
         }
 
+        let mut path = path.as_ref().to_cstr16();
         let empty = self.0.is_empty();
-        let needs_sep = *self
-            .0
-            .as_slice_with_nul()
-            .last()
-            .expect("Should have at least null character")
-            != SEPARATOR;
-        if !empty && needs_sep {
-            self.0.push(SEPARATOR)
+        let needs_sep = self.0.as_slice().last() != Some(&SEPARATOR);
+        if !empty {
+            // Skip a leading separator of `path`; one is added below if needed.
+            if let [first, rest @ ..] = path.as_slice_with_nul() {
+                if *first == SEPARATOR || *first == SEARCH {
+                    path = unsafe { CStr16::from_char16_with_nul_unchecked(rest) };
+                }
+            }
+            if needs_sep {
+                self.0.push(SEPARATOR)
+            }
         }
 
-        self.0.push_str(path.as_ref().to_cstr16());
+        self.0.push_str(path);
         self.0.replace_char(SEARCH, SEPARATOR);
     }
+
+    /// Replaces the extension of the [`file_name`] with `extension`, or
+    /// removes it if `extension` is empty.
+    ///
+    /// Returns `false` and does nothing if the path has no [`file_name`].
+    ///
+    /// [`file_name`]: Path::file_name
+    pub fn set_extension(&mut self, extension: &CStr16) -> bool {
+        let (Some(name), Some(stem)) = (self.file_name(), self.file_stem()) else {
+            return false;
+        };
+
+        // The file name is always the end of the string.
+        let parent_len = self.0.num_chars() - name.num_chars();
+        let mut path = CString16::new();
+        self.0.as_slice()[..parent_len]
+            .iter()
+            .for_each(|c| path.push(*c));
+        path.push_str(&stem);
+        if !extension.is_empty() {
+            path.push(unsafe { Char16::from_u16_unchecked('.' as u16) });
+            path.push_str(extension);
+        }
+        *self = Self::new_from_cstring16(path);
+        true
+    }
 }
 
 impl PartialEq for PathBuf {
@@ -69,6 +122,7 @@ impl Display for PathBuf {
 
 mod convenience_impls {
     use super::*;
+    use crate::data_types::FromSliceWithNulError;
     use core::borrow::Borrow;
     use core::ops::Deref;
 
@@ -84,6 +138,16 @@ mod convenience_impls {
         }
     }
 
+    impl TryFrom<&FilePath> for PathBuf {
+        type Error = FromSliceWithNulError;
+
+        fn try_from(node: &FilePath) -> Result<Self, Self::Error> {
+            node.path_name()
+                .to_cstring16()
+                .map(Self::new_from_cstring16)
+        }
+    }
+
     impl Deref for PathBuf {
         type Target = Path;
 
@@ -123,7 +187,10 @@ mod convenience_impls {
 mod tests {
     use super::*;
     use crate::cstr16;
+    use crate::mem::memory_map::MemoryType;
+    use crate::proto::device_path::build::{self, DevicePathBuilder};
     use alloc::string::ToString;
+    use alloc::vec::Vec;
 
     #[test]
     fn from_cstr16() {
@@ -169,6 +236,62 @@ mod tests {
         assert_eq!(pathbuf.to_cstr16(), cstr16!("first\\second"));
     }
 
+    #[test]
+    fn push_separator() {
+        let mut pathbuf = PathBuf::from(cstr16!("\\first\\"));
+        pathbuf.push(cstr16!("\\second"));
+        pathbuf.push(cstr16!("/third"));
+        assert_eq!(pathbuf.to_cstr16(), cstr16!("\\first\\second\\third"));
+    }
+
+    #[test]
+    fn set_extension() {
+        let mut pathbuf = PathBuf::from(cstr16!("\\dir.d\\file"));
+        assert!(pathbuf.set_extension(cstr16!("txt")));
+        assert_eq!(pathbuf.to_cstr16(), cstr16!("\\dir.d\\file.txt"));
+        assert!(pathbuf.set_extension(cstr16!("")));
+        assert_eq!(pathbuf.to_cstr16(), cstr16!("\\dir.d\\file"));
+
+        let mut pathbuf = PathBuf::new();
+        assert!(!pathbuf.set_extension(cstr16!("txt")));
+        assert!(pathbuf.is_empty());
+    }
+
+    #[test]
+    fn device_path_conversion() {
+        let mut v = Vec::new();
+        let device_path = DevicePathBuilder::with_vec(&mut v)
+            .push(&build::hardware::MemoryMapped {
+                memory_type: MemoryType::LOADER_CODE,
+                start_address: 0x1000,
+                end_address: 0x2000,
+            })
+            .unwrap()
+            .push(&build::media::FilePath::from(Path::new(cstr16!(
+                "\\EFI\\BOOT"
+            ))))
+            .unwrap()
+            .push(&build::media::FilePath {
+                path_name: cstr16!("BOOTX64.EFI"),
+            })
+            .unwrap()
+            .finalize()
+            .unwrap();
+
+        let node: &FilePath = device_path.node_iter().nth(1).unwrap().try_into().unwrap();
+        assert_eq!(
+            PathBuf::try_from(node).unwrap().to_cstr16(),
+            cstr16!("\\EFI\\BOOT")
+        );
+        assert_eq!(
+            PathBuf::from_device_path(device_path).unwrap().to_cstr16(),
+            cstr16!("\\EFI\\BOOT\\BOOTX64.EFI")
+        );
+
+        let device_path = DevicePathBuilder::with_vec(&mut v).finalize().unwrap();
+        assert_eq!(PathBuf::from_device_path(device_path), None);
+    }
+
     #[test]
     fn partial_eq() {
         let mut pathbuf1 = PathBuf::new();
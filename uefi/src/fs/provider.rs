@@ -0,0 +1,1326 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Implementation of the [`SimpleFileSystem`] and file protocols in Rust.
+//!
+//! [`SimpleFileSystem`]: crate::proto::media::fs::SimpleFileSystem
+
+use super::{Path, PathBuf, SEPARATOR_STR};
+use crate::mem::make_boxed;
+use crate::proto::device_path::DevicePath;
+use crate::proto::media::file::{
+    FileAttribute, FileInfo, FileInfoCreationError, FileMode, FileSystemInfo,
+    FileSystemVolumeLabel, FromUefi,
+};
+use crate::runtime::Time;
+use crate::{
+    boot, CStr16, CString16, Event, Guid, Handle, Identify, Result, ResultExt, Status, StatusExt,
+};
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use core::cell::{RefCell, RefMut};
+use core::ffi::c_void;
+use core::fmt::{self, Debug, Formatter};
+use core::{mem, ptr};
+use uefi_raw::protocol::file_system::{
+    FileInfo as RawFileInfo, FileIoToken, FileMode as RawFileMode, FileProtocolRevision,
+    FileProtocolV1, FileProtocolV2, FileSystemInfo as RawFileSystemInfo,
+    FileSystemVolumeLabel as RawFileSystemVolumeLabel, SimpleFileSystemProtocol,
+};
+use uefi_raw::Char16;
+
+/// Information about a file or directory, returned by a
+/// [`FileSystemProvider`]. This is converted to a [`FileInfo`] for the
+/// caller of the file protocol.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NodeInfo {
+    /// File name, without the path. Empty for the root directory.
+    pub name: CString16,
+    /// Size of the file in bytes.
+    pub file_size: u64,
+    /// Size of the file on the volume in bytes.
+    pub physical_size: u64,
+    /// Creation time.
+    pub create_time: Time,
+    /// Last access time.
+    pub last_access_time: Time,
+    /// Last modification time.
+    pub modification_time: Time,
+    /// Attributes. [`FileAttribute::DIRECTORY`] marks directories.
+    pub attribute: FileAttribute,
+}
+
+impl NodeInfo {
+    /// Information for a directory named `name`, with invalid timestamps.
+    #[must_use]
+    pub const fn directory(name: CString16) -> Self {
+        Self {
+            name,
+            file_size: 0,
+            physical_size: 0,
+            create_time: Time::invalid(),
+            last_access_time: Time::invalid(),
+            modification_time: Time::invalid(),
+            attribute: FileAttribute::DIRECTORY,
+        }
+    }
+
+    /// Information for a regular file named `name` of `size` bytes, with
+    /// invalid timestamps.
+    #[must_use]
+    pub const fn file(name: CString16, size: u64) -> Self {
+        Self {
+            name,
+            file_size: size,
+            physical_size: size,
+            create_time: Time::invalid(),
+            last_access_time: Time::invalid(),
+            modification_time: Time::invalid(),
+            attribute: FileAttribute::empty(),
+        }
+    }
+
+    /// Whether this is a directory.
+    #[must_use]
+    pub const fn is_directory(&self) -> bool {
+        self.attribute.contains(FileAttribute::DIRECTORY)
+    }
+
+    /// Convert to a [`FileInfo`].
+    #[must_use]
+    pub fn to_file_info(&self) -> Box<FileInfo> {
+        let fetch_data_fn = |buf| {
+            FileInfo::new(
+                buf,
+                self.file_size,
+                self.physical_size,
+                self.create_time,
+                self.last_access_time,
+                self.modification_time,
+                self.attribute,
+                &self.name,
+            )
+            .map_err(|FileInfoCreationError::InsufficientStorage(size)| {
+                crate::Error::new(Status::BUFFER_TOO_SMALL, Some(size))
+            })
+        };
+        #[cfg(not(feature = "unstable"))]
+        let info = make_boxed::<FileInfo, _>(fetch_data_fn);
+        #[cfg(feature = "unstable")]
+        let info = make_boxed::<FileInfo, _, _>(fetch_data_fn, alloc::alloc::Global);
+        info.expect("failed to allocate file info")
+    }
+}
+
+/// Information about the volume of a [`FileSystemProvider`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct VolumeInfo {
+    /// Volume label.
+    pub label: CString16,
+    /// Whether the volume only supports read access.
+    pub read_only: bool,
+    /// Size of the volume in bytes.
+    pub volume_size: u64,
+    /// Free space on the volume in bytes.
+    pub free_space: u64,
+    /// Block size of the volume in bytes.
+    pub block_size: u32,
+}
+
+impl VolumeInfo {
+    fn to_file_system_info(&self) -> Box<FileSystemInfo> {
+        let fetch_data_fn = |buf| {
+            FileSystemInfo::new(
+                buf,
+                self.read_only,
+                self.volume_size,
+                self.free_space,
+                self.block_size,
+                &self.label,
+            )
+            .map_err(|FileInfoCreationError::InsufficientStorage(size)| {
+                crate::Error::new(Status::BUFFER_TOO_SMALL, Some(size))
+            })
+        };
+        #[cfg(not(feature = "unstable"))]
+        let info = make_boxed::<FileSystemInfo, _>(fetch_data_fn);
+        #[cfg(feature = "unstable")]
+        let info = make_boxed::<FileSystemInfo, _, _>(fetch_data_fn, alloc::alloc::Global);
+        info.expect("failed to allocate file system info")
+    }
+
+    fn to_volume_label(&self) -> Box<FileSystemVolumeLabel> {
+        let fetch_data_fn = |buf| {
+            FileSystemVolumeLabel::new(buf, &self.label).map_err(
+                |FileInfoCreationError::InsufficientStorage(size)| {
+                    crate::Error::new(Status::BUFFER_TOO_SMALL, Some(size))
+                },
+            )
+        };
+        #[cfg(not(feature = "unstable"))]
+        let info = make_boxed::<FileSystemVolumeLabel, _>(fetch_data_fn);
+        #[cfg(feature = "unstable")]
+        let info = make_boxed::<FileSystemVolumeLabel, _, _>(fetch_data_fn, alloc::alloc::Global);
+        info.expect("failed to allocate volume label")
+    }
+}
+
+/// A file system implemented in Rust, which can be exposed to other UEFI
+/// images through the [`SimpleFileSystem`] protocol with
+/// [`InstalledFileSystem::install`].
+///
+/// Files are identified by a [`Node`], created by [`open`] and released by
+/// [`close`] or [`delete`]. The file protocol tracks the position in files
+/// and directories, validates open modes and access rights, and resolves
+/// the `.` and `..` components of paths, so that providers only deal with
+/// absolute, normalized paths.
+///
+/// Only [`open`], [`info`], [`read`], [`read_dir`] and [`volume_info`] are
+/// required, which is enough for a read-only file system. The defaults of
+/// the other methods fail with [`Status::WRITE_PROTECTED`].
+///
+/// Errors are passed to the caller of the file protocol as their status
+/// code.
+///
+/// [`Node`]: Self::Node
+/// [`SimpleFileSystem`]: crate::proto::media::fs::SimpleFileSystem
+/// [`close`]: Self::close
+/// [`delete`]: Self::delete
+/// [`info`]: Self::info
+/// [`open`]: Self::open
+/// [`read`]: Self::read
+/// [`read_dir`]: Self::read_dir
+/// [`volume_info`]: Self::volume_info
+pub trait FileSystemProvider: 'static {
+    /// An open file or directory.
+    type Node: 'static;
+
+    /// Open the file or directory at `path`, creating a file if `mode` is
+    /// [`FileMode::CreateReadWrite`] and it does not exist yet. New files
+    /// get the attributes `attributes`; if it contains
+    /// [`FileAttribute::DIRECTORY`], a directory is created.
+    ///
+    /// `path` is absolute and normalized. The root directory is `\`.
+    fn open(
+        &mut self,
+        path: &Path,
+        mode: FileMode,
+        attributes: FileAttribute,
+    ) -> Result<Self::Node>;
+
+    /// Get information about an open file or directory.
+    fn info(&mut self, node: &Self::Node) -> Result<NodeInfo>;
+
+    /// Read file data at `offset`, returning the number of bytes read.
+    /// Reads at or past the end of the file return zero bytes.
+    fn read(&mut self, file: &mut Self::Node, offset: u64, buf: &mut [u8]) -> Result<usize>;
+
+    /// Get the entry at `index` of a directory, or `None` past the last
+    /// entry. The `.` and `..` entries are optional.
+    fn read_dir(&mut self, dir: &mut Self::Node, index: usize) -> Result<Option<NodeInfo>>;
+
+    /// Get information about the volume.
+    fn volume_info(&mut self) -> Result<VolumeInfo>;
+
+    /// Write `buf` to a file at `offset`, growing the file if needed.
+    /// Returns the number of bytes written.
+    fn write(&mut self, _file: &mut Self::Node, _offset: u64, _buf: &[u8]) -> Result<usize> {
+        Err(Status::WRITE_PROTECTED.into())
+    }
+
+    /// Change the information of a file or directory. This can rename,
+    /// resize or change the attributes and timestamps of the file.
+    fn set_info(&mut self, _node: &mut Self::Node, _info: &FileInfo) -> Result {
+        Err(Status::WRITE_PROTECTED.into())
+    }
+
+    /// Change the volume label.
+    fn set_volume_label(&mut self, _label: &CStr16) -> Result {
+        Err(Status::WRITE_PROTECTED.into())
+    }
+
+    /// Write buffered data of a file to the volume.
+    fn flush(&mut self, _node: &mut Self::Node) -> Result {
+        Ok(())
+    }
+
+    /// Delete a file or directory. The node is not used anymore
+    /// afterwards. If the file cannot be deleted, return
+    /// [`Status::WARN_DELETE_FAILURE`].
+    fn delete(&mut self, node: Self::Node) -> Result {
+        self.close(node);
+        Err(Status::WARN_DELETE_FAILURE.into())
+    }
+
+    /// Release a file or directory.
+    fn close(&mut self, _node: Self::Node) {}
+}
+
+/// Instance of the simple file system protocol.
+#[repr(C)]
+struct FsInstance<P> {
+    protocol: SimpleFileSystemProtocol,
+    provider: Rc<RefCell<P>>,
+}
+
+/// Instance of the file protocol for one open file or directory.
+#[repr(C)]
+struct FileInstance<P: FileSystemProvider> {
+    protocol: FileProtocolV2,
+    provider: Rc<RefCell<P>>,
+    node: P::Node,
+    path: PathBuf,
+    is_directory: bool,
+    writable: bool,
+    /// Byte offset in a file, or entry index in a directory.
+    position: u64,
+}
+
+/// Convert the open mode of the file protocol. Only the combinations
+/// allowed by the UEFI Specification are accepted.
+const fn file_mode(mode: RawFileMode) -> Option<FileMode> {
+    const READ_WRITE: RawFileMode = RawFileMode::READ.union(RawFileMode::WRITE);
+    const CREATE: RawFileMode = READ_WRITE.union(RawFileMode::CREATE);
+    match mode {
+        RawFileMode::READ => Some(FileMode::Read),
+        READ_WRITE => Some(FileMode::ReadWrite),
+        CREATE => Some(FileMode::CreateReadWrite),
+        _ => None,
+    }
+}
+
+/// Copy an info structure to the caller's buffer, updating `buffer_size`.
+unsafe fn write_info<T: ?Sized>(info: &T, buffer_size: *mut usize, buffer: *mut c_void) -> Status {
+    let size = mem::size_of_val(info);
+    let available = unsafe { buffer_size.replace(size) };
+    if available < size {
+        return Status::BUFFER_TOO_SMALL;
+    }
+    if buffer.is_null() {
+        return Status::INVALID_PARAMETER;
+    }
+    unsafe { ptr::copy_nonoverlapping(ptr::from_ref(info).cast::<u8>(), buffer.cast(), size) };
+    Status::SUCCESS
+}
+
+/// Check that the caller's `buffer` of `buffer_size` bytes holds an info
+/// structure whose name starts at `name_offset`, including the name's null
+/// terminator, so that it can be read without going past the buffer.
+unsafe fn info_fits(buffer: *const c_void, buffer_size: usize, name_offset: usize) -> bool {
+    let Some(name_bytes) = buffer_size.checked_sub(name_offset) else {
+        return false;
+    };
+    let name = unsafe { buffer.cast::<u8>().add(name_offset).cast::<Char16>() };
+    (0..name_bytes / mem::size_of::<Char16>()).any(|i| unsafe { name.add(i).read_unaligned() } == 0)
+}
+
+/// Store the result of an `*_ex` function in its token and signal the
+/// token's event. The operation is always completed synchronously.
+unsafe fn complete(token: *mut FileIoToken, status: Status) -> Status {
+    let token = unsafe { &mut *token };
+    token.status = status;
+    if let Some(event) = unsafe { Event::from_ptr(token.event) } {
+        let _ = boot::signal_event(&event);
+    }
+    status
+}
+
+impl<P: FileSystemProvider> FsInstance<P> {
+    fn new(provider: P) -> Box<Self> {
+        Box::new(Self {
+            protocol: SimpleFileSystemProtocol {
+                revision: 0x0001_0000,
+                open_volume: Self::open_volume,
+            },
+            provider: Rc::new(RefCell::new(provider)),
+        })
+    }
+
+    unsafe extern "efiapi" fn open_volume(
+        this: *mut SimpleFileSystemProtocol,
+        root: *mut *mut FileProtocolV1,
+    ) -> Status {
+        if root.is_null() {
+            return Status::INVALID_PARAMETER;
+        }
+        let this = unsafe { &*this.cast::<Self>() };
+        let path = Path::new(SEPARATOR_STR).to_path_buf();
+        match FileInstance::open(&this.provider, path, FileMode::Read, FileAttribute::empty()) {
+            Ok(file) => {
+                unsafe { root.write(Box::into_raw(file).cast()) };
+                Status::SUCCESS
+            }
+            Err(err) => err.status(),
+        }
+    }
+}
+
+impl<P: FileSystemProvider> FileInstance<P> {
+    fn open(
+        provider: &Rc<RefCell<P>>,
+        path: PathBuf,
+        mode: FileMode,
+        attributes: FileAttribute,
+    ) -> Result<Box<Self>> {
+        let mut fs = provider
+            .try_borrow_mut()
+            .map_err(|_| Status::ACCESS_DENIED)?;
+        let node = fs.open(&path, mode, attributes)?;
+        let is_directory = match fs.info(&node) {
+            Ok(info) => info.is_directory(),
+            Err(err) => {
+                fs.close(node);
+                return Err(err);
+            }
+        };
+        Ok(Box::new(Self {
+            protocol: FileProtocolV2 {
+                v1: FileProtocolV1 {
+                    revision: FileProtocolRevision::REVISION_2,
+                    open: Self::open_fn,
+                    close: Self::close,
+                    delete: Self::delete,
+                    read: Self::read,
+                    write: Self::write,
+                    get_position: Self::get_position,
+                    set_position: Self::set_position,
+                    get_info: Self::get_info,
+                    set_info: Self::set_info,
+                    flush: Self::flush,
+                },
+                open_ex: Self::open_ex,
+                read_ex: Self::read_ex,
+                write_ex: Self::write_ex,
+                flush_ex: Self::flush_ex,
+            },
+            provider: provider.clone(),
+            node,
+            path,
+            is_directory,
+            writable: mode != FileMode::Read,
+            position: 0,
+        }))
+    }
+
+    /// Run `f` with the instance and the provider, which must not be
+    /// borrowed already.
+    unsafe fn with(
+        this: *const FileProtocolV1,
+        f: impl FnOnce(&mut Self, &mut P) -> Result,
+    ) -> Status {
+        let this = unsafe { &mut *this.cast::<Self>().cast_mut() };
+        let provider = this.provider.clone();
+        let Ok(mut fs) = provider.try_borrow_mut() else {
+            return Status::ACCESS_DENIED;
+        };
+        f(this, &mut fs).status()
+    }
+
+    unsafe fn do_open(
+        this: *mut FileProtocolV1,
+        new_handle: *mut *mut FileProtocolV1,
+        file_name: *const Char16,
+        open_mode: RawFileMode,
+        attributes: FileAttribute,
+    ) -> Status {
+        if new_handle.is_null() || file_name.is_null() {
+            return Status::INVALID_PARAMETER;
+        }
+        let Some(mode) = file_mode(open_mode) else {
+            return Status::INVALID_PARAMETER;
+        };
+        let this = unsafe { &*this.cast::<Self>() };
+        let name = Path::new(unsafe { CStr16::from_ptr(file_name.cast()) });
+
+        let path = if name.is_absolute() {
+            name.normalize()
+        } else if this.is_directory {
+            this.path.join(name).normalize()
+        } else {
+            return Status::NOT_FOUND;
+        };
+        let path = if path.is_empty() {
+            Path::new(SEPARATOR_STR).to_path_buf()
+        } else {
+            path
+        };
+
+        match Self::open(&this.provider, path, mode, attributes) {
+            Ok(file) => {
+                unsafe { new_handle.write(Box::into_raw(file).cast()) };
+                Status::SUCCESS
+            }
+            Err(err) => err.status(),
+        }
+    }
+
+    unsafe extern "efiapi" fn open_fn(
+        this: *mut FileProtocolV1,
+        new_handle: *mut *mut FileProtocolV1,
+        file_name: *const Char16,
+        open_mode: RawFileMode,
+        attributes: FileAttribute,
+    ) -> Status {
+        unsafe { Self::do_open(this, new_handle, file_name, open_mode, attributes) }
+    }
+
+    unsafe extern "efiapi" fn close(this: *mut FileProtocolV1) -> Status {
+        let this = unsafe { Box::from_raw(this.cast::<Self>()) };
+        let Self { provider, node, .. } = *this;
+        // Closing cannot fail. The provider is only borrowed from within
+        // the protocol functions, so it is not borrowed here unless the
+        // provider closes a file itself; the node is leaked in that case.
+        if let Ok(mut fs) = provider.try_borrow_mut() {
+            fs.close(node);
+        }
+        Status::SUCCESS
+    }
+
+    unsafe extern "efiapi" fn delete(this: *mut FileProtocolV1) -> Status {
+        let this = unsafe { Box::from_raw(this.cast::<Self>()) };
+        let Self {
+            provider,
+            node,
+            writable,
+            ..
+        } = *this;
+        let Ok(mut fs) = provider.try_borrow_mut() else {
+            return Status::WARN_DELETE_FAILURE;
+        };
+        if !writable {
+            fs.close(node);
+            return Status::WARN_DELETE_FAILURE;
+        }
+        fs.delete(node).status()
+    }
+
+    unsafe fn do_read(
+        this: *mut FileProtocolV1,
+        buffer_size: *mut usize,
+        buffer: *mut c_void,
+    ) -> Status {
+        if buffer_size.is_null() {
+            return Status::INVALID_PARAMETER;
+        }
+        let len = unsafe { *buffer_size };
+        unsafe {
+            Self::with(this, |file, fs| {
+                if file.is_directory {
+                    let index = usize::try_from(file.position).unwrap();
+                    let Some(entry) = fs.read_dir(&mut file.node, index)? else {
+                        *buffer_size = 0;
+                        return Ok(());
+                    };
+                    let info = entry.to_file_info();
+                    // The entry is not consumed if the buffer is too small.
+                    write_info(&*info, buffer_size, buffer).to_result()?;
+                    file.position += 1;
+                    Ok(())
+                } else {
+                    if len > 0 && buffer.is_null() {
+                        return Err(Status::INVALID_PARAMETER.into());
+                    }
+                    let buf = if len == 0 {
+                        &mut []
+                    } else {
+                        core::slice::from_raw_parts_mut(buffer.cast::<u8>(), len)
+                    };
+                    let read = fs.read(&mut file.node, file.position, buf)?;
+                    file.position += read as u64;
+                    *buffer_size = read;
+                    Ok(())
+                }
+            })
+        }
+    }
+
+    unsafe extern "efiapi" fn read(
+        this: *mut FileProtocolV1,
+        buffer_size: *mut usize,
+        buffer: *mut c_void,
+    ) -> Status {
+        unsafe { Self::do_read(this, buffer_size, buffer) }
+    }
+
+    unsafe fn do_write(
+        this: *mut FileProtocolV1,
+        buffer_size: *mut usize,
+        buffer: *const c_void,
+    ) -> Status {
+        if buffer_size.is_null() {
+            return Status::INVALID_PARAMETER;
+        }
+        let len = unsafe { *buffer_size };
+        unsafe {
+            Self::with(this, |file, fs| {
+                if file.is_directory {
+                    return Err(Status::UNSUPPORTED.into());
+                }
+                if !file.writable {
+                    return Err(Status::ACCESS_DENIED.into());
+                }
+                if len > 0 && buffer.is_null() {
+                    return Err(Status::INVALID_PARAMETER.into());
+                }
+                let buf = if len == 0 {
+                    &[]
+                } else {
+                    core::slice::from_raw_parts(buffer.cast::<u8>(), len)
+                };
+                let written = fs.write(&mut file.node, file.position, buf)?;
+                file.position += written as u64;
+                *buffer_size = written;
+                Ok(())
+            })
+        }
+    }
+
+    unsafe extern "efiapi" fn write(
+        this: *mut FileProtocolV1,
+        buffer_size: *mut usize,
+        buffer: *const c_void,
+    ) -> Status {
+        unsafe { Self::do_write(this, buffer_size, buffer) }
+    }
+
+    unsafe extern "efiapi" fn get_position(
+        this: *const FileProtocolV1,
+        position: *mut u64,
+    ) -> Status {
+        let this = unsafe { &*this.cast::<Self>() };
+        if this.is_directory {
+            return Status::UNSUPPORTED;
+        }
+        if position.is_null() {
+            return Status::INVALID_PARAMETER;
+        }
+        unsafe { position.write(this.position) };
+        Status::SUCCESS
+    }
+
+    unsafe extern "efiapi" fn set_position(this: *mut FileProtocolV1, position: u64) -> Status {
+        unsafe {
+            Self::with(this, |file, fs| {
+                if file.is_directory {
+                    // Directories can only be rewound.
+                    if position != 0 {
+                        return Err(Status::UNSUPPORTED.into());
+                    }
+                    file.position = 0;
+                } else if position == u64::MAX {
+                    file.position = fs.info(&file.node)?.file_size;
+                } else {
+                    file.position = position;
+                }
+                Ok(())
+            })
+        }
+    }
+
+    unsafe extern "efiapi" fn get_info(
+        this: *mut FileProtocolV1,
+        information_type: *const Guid,
+        buffer_size: *mut usize,
+        buffer: *mut c_void,
+    ) -> Status {
+        if information_type.is_null() || buffer_size.is_null() {
+            return Status::INVALID_PARAMETER;
+        }
+        let information_type = unsafe { *information_type };
+        unsafe {
+            Self::with(this, |file, fs| {
+                let status = if information_type == FileInfo::GUID {
+                    write_info(&*fs.info(&file.node)?.to_file_info(), buffer_size, buffer)
+                } else if information_type == FileSystemInfo::GUID {
+                    write_info(
+                        &*fs.volume_info()?.to_file_system_info(),
+                        buffer_size,
+                        buffer,
+                    )
+                } else if information_type == FileSystemVolumeLabel::GUID {
+                    write_info(&*fs.volume_info()?.to_volume_label(), buffer_size, buffer)
+                } else {
+                    Status::UNSUPPORTED
+                };
+                status.to_result()
+            })
+        }
+    }
+
+    unsafe extern "efiapi" fn set_info(
+        this: *mut FileProtocolV1,
+        information_type: *const Guid,
+        buffer_size: usize,
+        buffer: *const c_void,
+    ) -> Status {
+        if information_type.is_null() || buffer.is_null() {
+            return Status::INVALID_PARAMETER;
+        }
+        let information_type = unsafe { *information_type };
+        unsafe {
+            Self::with(this, |file, fs| {
+                let name_offset = if information_type == FileInfo::GUID {
+                    mem::offset_of!(RawFileInfo, file_name)
+                } else if information_type == FileSystemInfo::GUID {
+                    mem::offset_of!(RawFileSystemInfo, volume_label)
+                } else if information_type == FileSystemVolumeLabel::GUID {
+                    mem::offset_of!(RawFileSystemVolumeLabel, volume_label)
+                } else {
+                    return Err(Status::UNSUPPORTED.into());
+                };
+                if !info_fits(buffer, buffer_size, name_offset) {
+                    return Err(Status::BAD_BUFFER_SIZE.into());
+                }
+
+                if information_type == FileInfo::GUID {
+                    if !file.writable {
+                        return Err(Status::ACCESS_DENIED.into());
+                    }
+                    let info = FileInfo::from_uefi(buffer.cast_mut());
+                    fs.set_info(&mut file.node, info)
+                } else if information_type == FileSystemInfo::GUID {
+                    let info = FileSystemInfo::from_uefi(buffer.cast_mut());
+                    fs.set_volume_label(info.volume_label())
+                } else {
+                    let info = FileSystemVolumeLabel::from_uefi(buffer.cast_mut());
+                    fs.set_volume_label(info.volume_label())
+                }
+            })
+        }
+    }
+
+    unsafe fn do_flush(this: *mut FileProtocolV1) -> Status {
+        unsafe {
+            Self::with(this, |file, fs| {
+                if !file.writable {
+                    return Err(Status::ACCESS_DENIED.into());
+                }
+                fs.flush(&mut file.node)
+            })
+        }
+    }
+
+    unsafe extern "efiapi" fn flush(this: *mut FileProtocolV1) -> Status {
+        unsafe { Self::do_flush(this) }
+    }
+
+    unsafe extern "efiapi" fn open_ex(
+        this: *mut FileProtocolV2,
+        new_handle: *mut *mut FileProtocolV2,
+        file_name: *const Char16,
+        open_mode: RawFileMode,
+        attributes: FileAttribute,
+        token: *mut FileIoToken,
+    ) -> Status {
+        if token.is_null() {
+            return Status::INVALID_PARAMETER;
+        }
+        unsafe {
+            let status = Self::do_open(
+                this.cast(),
+                new_handle.cast(),
+                file_name,
+                open_mode,
+                attributes,
+            );
+            complete(token, status)
+        }
+    }
+
+    unsafe extern "efiapi" fn read_ex(
+        this: *mut FileProtocolV2,
+        token: *mut FileIoToken,
+    ) -> Status {
+        if token.is_null() {
+            return Status::INVALID_PARAMETER;
+        }
+        unsafe {
+            let status = Self::do_read(this.cast(), &mut (*token).buffer_size, (*token).buffer);
+            complete(token, status)
+        }
+    }
+
+    unsafe extern "efiapi" fn write_ex(
+        this: *mut FileProtocolV2,
+        token: *mut FileIoToken,
+    ) -> Status {
+        if token.is_null() {
+            return Status::INVALID_PARAMETER;
+        }
+        unsafe {
+            let status = Self::do_write(this.cast(), &mut (*token).buffer_size, (*token).buffer);
+            complete(token, status)
+        }
+    }
+
+    unsafe extern "efiapi" fn flush_ex(
+        this: *mut FileProtocolV2,
+        token: *mut FileIoToken,
+    ) -> Status {
+        if token.is_null() {
+            return Status::INVALID_PARAMETER;
+        }
+        unsafe { complete(token, Self::do_flush(this.cast())) }
+    }
+}
+
+/// A [`FileSystemProvider`] installed as a [`SimpleFileSystem`] on a new
+/// handle.
+///
+/// The handle also gets a [`DevicePath`], so that the file system shows up
+/// in the UEFI Shell and files on it can be loaded with
+/// [`boot::load_image`]. A vendor-defined hardware node with a GUID that
+/// identifies the provider is a good choice for the path.
+///
+/// Dropping this value does not uninstall the file system; it stays
+/// available until [`uninstall`] is called or boot services are exited.
+///
+/// # Example
+///
+/// Serve a single file from memory, e.g. an initrd for a Linux kernel
+/// loaded with its EFI stub:
+///
+/// ```no_run
+/// use uefi::fs::{FileSystemProvider, InstalledFileSystem, NodeInfo, Path, VolumeInfo};
+/// use uefi::proto::device_path::build::{self, DevicePathBuilder};
+/// use uefi::proto::media::file::{FileAttribute, FileMode};
+/// use uefi::{cstr16, guid, CString16, Status};
+///
+/// struct Initrd(&'static [u8]);
+///
+/// #[derive(PartialEq)]
+/// enum Node {
+///     Root,
+///     Initrd,
+/// }
+///
+/// impl FileSystemProvider for Initrd {
+///     type Node = Node;
+///
+///     fn open(&mut self, path: &Path, mode: FileMode, _: FileAttribute) -> uefi::Result<Node> {
+///         if mode != FileMode::Read {
+///             return Err(Status::WRITE_PROTECTED.into());
+///         }
+///         match path.to_cstr16() {
+///             p if p == cstr16!("\\") => Ok(Node::Root),
+///             p if p == cstr16!("\\initrd") => Ok(Node::Initrd),
+///             _ => Err(Status::NOT_FOUND.into()),
+///         }
+///     }
+///
+///     fn info(&mut self, node: &Node) -> uefi::Result<NodeInfo> {
+///         Ok(match node {
+///             Node::Root => NodeInfo::directory(CString16::new()),
+///             Node::Initrd => NodeInfo::file(cstr16!("initrd").into(), self.0.len() as u64),
+///         })
+///     }
+///
+///     fn read(&mut self, _: &mut Node, offset: u64, buf: &mut [u8]) -> uefi::Result<usize> {
+///         let data = self.0.get(offset as usize..).unwrap_or_default();
+///         let len = buf.len().min(data.len());
+///         buf[..len].copy_from_slice(&data[..len]);
+///         Ok(len)
+///     }
+///
+///     fn read_dir(&mut self, _: &mut Node, index: usize) -> uefi::Result<Option<NodeInfo>> {
+///         (index == 0).then(|| self.info(&Node::Initrd)).transpose()
+///     }
+///
+///     fn volume_info(&mut self) -> uefi::Result<VolumeInfo> {
+///         Ok(VolumeInfo {
+///             read_only: true,
+///             ..Default::default()
+///         })
+///     }
+/// }
+///
+/// # fn f(initrd: &'static [u8]) -> uefi::Result {
+/// let mut buf = Vec::new();
+/// let device_path = DevicePathBuilder::with_vec(&mut buf)
+///     .push(&build::hardware::Vendor {
+///         vendor_guid: guid!("8a6e3a1c-02c4-4a8e-9a76-6c1c2d3e4f50"),
+///         vendor_defined_data: &[],
+///     })
+///     .and_then(DevicePathBuilder::finalize)
+///     .unwrap();
+/// let fs = InstalledFileSystem::install(Initrd(initrd), device_path)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`SimpleFileSystem`]: crate::proto::media::fs::SimpleFileSystem
+/// [`uninstall`]: Self::uninstall
+pub struct InstalledFileSystem<P: FileSystemProvider> {
+    handle: Handle,
+    instance: *mut FsInstance<P>,
+    device_path: *mut DevicePath,
+}
+
+impl<P: FileSystemProvider> InstalledFileSystem<P> {
+    /// Install `provider` as a [`SimpleFileSystem`] on a new handle that
+    /// also has a copy of `device_path` installed.
+    ///
+    /// # Errors
+    ///
+    /// Errors from [`boot::install_protocol_interface`] are returned.
+    ///
+    /// [`SimpleFileSystem`]: crate::proto::media::fs::SimpleFileSystem
+    pub fn install(provider: P, device_path: &DevicePath) -> Result<Self> {
+        let device_path = Box::into_raw(device_path.to_boxed());
+        let handle = match unsafe {
+            boot::install_protocol_interface(
+                None,
+                &DevicePath::GUID,
+                device_path.cast_const().cast(),
+            )
+        } {
+            Ok(handle) => handle,
+            Err(err) => {
+                drop(unsafe { Box::from_raw(device_path) });
+                return Err(err);
+            }
+        };
+
+        let instance = Box::into_raw(FsInstance::new(provider));
+        let result = unsafe {
+            boot::install_protocol_interface(
+                Some(handle),
+                &SimpleFileSystemProtocol::GUID,
+                instance.cast_const().cast(),
+            )
+        };
+        if let Err(err) = result {
+            unsafe {
+                let _ = boot::uninstall_protocol_interface(
+                    handle,
+                    &DevicePath::GUID,
+                    device_path.cast_const().cast(),
+                );
+                drop(Box::from_raw(instance));
+                drop(Box::from_raw(device_path));
+            }
+            return Err(err);
+        }
+
+        Ok(Self {
+            handle,
+            instance,
+            device_path,
+        })
+    }
+
+    /// The handle the file system is installed on.
+    #[must_use]
+    pub const fn handle(&self) -> Handle {
+        self.handle
+    }
+
+    /// Borrow the provider, e.g. to add files to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from within one of the provider's methods.
+    #[must_use]
+    pub fn provider(&self) -> RefMut<'_, P> {
+        unsafe { &*self.instance }.provider.borrow_mut()
+    }
+
+    /// Uninstall the file system and its device path from the handle.
+    ///
+    /// Files that are still open keep working, since they share ownership
+    /// of the provider.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::ACCESS_DENIED`]: the protocol is still opened by a
+    ///   driver and cannot be uninstalled. The file system stays
+    ///   installed.
+    pub fn uninstall(self) -> Result {
+        unsafe {
+            boot::uninstall_protocol_interface(
+                self.handle,
+                &SimpleFileSystemProtocol::GUID,
+                self.instance.cast_const().cast(),
+            )?;
+            drop(Box::from_raw(self.instance));
+
+            // The device path is leaked if it cannot be uninstalled, since
+            // it must stay valid while it is installed.
+            if boot::uninstall_protocol_interface(
+                self.handle,
+                &DevicePath::GUID,
+                self.device_path.cast_const().cast(),
+            )
+            .is_ok()
+            {
+                drop(Box::from_raw(self.device_path));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<P: FileSystemProvider> Debug for InstalledFileSystem<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InstalledFileSystem")
+            .field("handle", &self.handle)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cstr16;
+    use crate::proto::media::file::{File, FileHandle, RegularFile};
+    use crate::proto::media::fs::SimpleFileSystem;
+    use alloc::collections::BTreeMap;
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+    use alloc::{format, vec};
+
+    /// An in-memory file system. Nodes are absolute paths.
+    #[derive(Default)]
+    struct MemFs {
+        files: BTreeMap<String, Vec<u8>>,
+        dirs: Vec<String>,
+        label: CString16,
+        open_nodes: usize,
+    }
+
+    fn parent(path: &str) -> &str {
+        match path.rfind('\\') {
+            Some(0) => "\\",
+            Some(i) => &path[..i],
+            None => "",
+        }
+    }
+
+    fn name(path: &str) -> CString16 {
+        CString16::try_from(&path[path.rfind('\\').unwrap() + 1..]).unwrap()
+    }
+
+    impl MemFs {
+        fn new() -> Self {
+            let mut fs = Self {
+                label: cstr16!("MEMFS").into(),
+                dirs: vec!["\\".into(), "\\dir".into()],
+                ..Default::default()
+            };
+            fs.files
+                .insert("\\dir\\file.txt".into(), b"hello world".to_vec());
+            fs.files.insert("\\top.bin".into(), vec![7; 300]);
+            fs
+        }
+    }
+
+    impl FileSystemProvider for MemFs {
+        type Node = String;
+
+        fn open(
+            &mut self,
+            path: &Path,
+            mode: FileMode,
+            attributes: FileAttribute,
+        ) -> Result<String> {
+            let path = path.to_string();
+            let exists = self.dirs.contains(&path) || self.files.contains_key(&path);
+            if !exists {
+                if mode != FileMode::CreateReadWrite
+                    || !self.dirs.iter().any(|d| d == parent(&path))
+                {
+                    return Err(Status::NOT_FOUND.into());
+                }
+                if attributes.contains(FileAttribute::DIRECTORY) {
+                    self.dirs.push(path.clone());
+                } else {
+                    self.files.insert(path.clone(), Vec::new());
+                }
+            }
+            self.open_nodes += 1;
+            Ok(path)
+        }
+
+        fn info(&mut self, node: &String) -> Result<NodeInfo> {
+            Ok(match self.files.get(node) {
+                Some(data) => NodeInfo::file(name(node), data.len() as u64),
+                None => NodeInfo::directory(name(node)),
+            })
+        }
+
+        fn read(&mut self, file: &mut String, offset: u64, buf: &mut [u8]) -> Result<usize> {
+            let data = self.files[file.as_str()]
+                .get(offset as usize..)
+                .unwrap_or_default();
+            let len = buf.len().min(data.len());
+            buf[..len].copy_from_slice(&data[..len]);
+            Ok(len)
+        }
+
+        fn read_dir(&mut self, dir: &mut String, index: usize) -> Result<Option<NodeInfo>> {
+            let mut children: Vec<String> = self
+                .files
+                .keys()
+                .chain(self.dirs.iter())
+                .filter(|p| *p != "\\" && parent(p) == dir.as_str())
+                .cloned()
+                .collect();
+            children.sort();
+            children.get(index).map(|p| self.info(p)).transpose()
+        }
+
+        fn volume_info(&mut self) -> Result<VolumeInfo> {
+            Ok(VolumeInfo {
+                label: self.label.clone(),
+                volume_size: 1 << 20,
+                free_space: 1 << 19,
+                block_size: 512,
+                ..Default::default()
+            })
+        }
+
+        fn write(&mut self, file: &mut String, offset: u64, buf: &[u8]) -> Result<usize> {
+            let data = self.files.get_mut(file.as_str()).unwrap();
+            let end = offset as usize + buf.len();
+            if data.len() < end {
+                data.resize(end, 0);
+            }
+            data[offset as usize..end].copy_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn set_info(&mut self, node: &mut String, info: &FileInfo) -> Result {
+            let new = format!(
+                "{}\\{}",
+                parent(node).trim_end_matches('\\'),
+                info.file_name()
+            );
+            let data = self
+                .files
+                .remove(node.as_str())
+                .ok_or(Status::ACCESS_DENIED)?;
+            self.files.insert(new.clone(), data);
+            *node = new;
+            Ok(())
+        }
+
+        fn set_volume_label(&mut self, label: &CStr16) -> Result {
+            self.label = label.into();
+            Ok(())
+        }
+
+        fn delete(&mut self, node: String) -> Result {
+            self.open_nodes -= 1;
+            self.files
+                .remove(&node)
+                .ok_or(Status::WARN_DELETE_FAILURE)?;
+            Ok(())
+        }
+
+        fn close(&mut self, _node: String) {
+            self.open_nodes -= 1;
+        }
+    }
+
+    fn open_file(handle: FileHandle) -> RegularFile {
+        handle.into_regular_file().unwrap()
+    }
+
+    #[test]
+    fn test_read() {
+        let mut instance = FsInstance::new(MemFs::new());
+        let sfs = unsafe { &mut *ptr::addr_of_mut!(instance.protocol).cast::<SimpleFileSystem>() };
+        let mut root = sfs.open_volume().unwrap();
+
+        let read_all = |file: &mut RegularFile| {
+            let mut data = Vec::new();
+            let mut buf = [0; 4];
+            loop {
+                match file.read(&mut buf).unwrap() {
+                    0 => return data,
+                    n => data.extend_from_slice(&buf[..n]),
+                }
+            }
+        };
+
+        let mut file = open_file(
+            root.open(
+                cstr16!("dir\\file.txt"),
+                FileMode::Read,
+                FileAttribute::empty(),
+            )
+            .unwrap(),
+        );
+        assert_eq!(read_all(&mut file), b"hello world");
+        assert_eq!(file.get_position().unwrap(), 11);
+        file.set_position(6).unwrap();
+        assert_eq!(read_all(&mut file), b"world");
+        file.set_position(RegularFile::END_OF_FILE).unwrap();
+        assert_eq!(file.get_position().unwrap(), 11);
+
+        // Read-only files cannot be written to.
+        assert_eq!(
+            file.write(b"x").unwrap_err().status(),
+            Status::ACCESS_DENIED
+        );
+        // Paths relative to a file cannot be resolved.
+        assert_eq!(
+            file.open(cstr16!("x"), FileMode::Read, FileAttribute::empty())
+                .unwrap_err()
+                .status(),
+            Status::NOT_FOUND
+        );
+
+        let info = file.get_boxed_info::<FileInfo>().unwrap();
+        assert_eq!(info.file_name(), cstr16!("file.txt"));
+        assert_eq!(info.file_size(), 11);
+        drop(file);
+
+        // Absolute paths and `.` and `..` components.
+        let dir = root
+            .open(cstr16!("dir"), FileMode::Read, FileAttribute::empty())
+            .unwrap();
+        let mut dir = dir.into_directory().unwrap();
+        let mut file = open_file(
+            dir.open(
+                cstr16!("..\\dir\\.\\file.txt"),
+                FileMode::Read,
+                FileAttribute::empty(),
+            )
+            .unwrap(),
+        );
+        assert_eq!(read_all(&mut file), b"hello world");
+        let mut top = open_file(
+            dir.open(cstr16!("\\top.bin"), FileMode::Read, FileAttribute::empty())
+                .unwrap(),
+        );
+        assert_eq!(read_all(&mut top), [7; 300]);
+        assert_eq!(
+            dir.open(cstr16!("missing"), FileMode::Read, FileAttribute::empty())
+                .unwrap_err()
+                .status(),
+            Status::NOT_FOUND
+        );
+
+        // Directory enumeration, including a rewind.
+        let mut names = Vec::new();
+        for _ in 0..2 {
+            while let Some(entry) = root.read_entry_boxed().unwrap() {
+                names.push(entry.file_name().to_string());
+            }
+            root.reset_entry_readout().unwrap();
+        }
+        assert_eq!(names, ["dir", "top.bin", "dir", "top.bin"]);
+        let info = root.get_boxed_info::<FileInfo>().unwrap();
+        assert!(info.is_directory());
+        assert!(info.file_name().is_empty());
+
+        drop((root, dir, file, top));
+        assert_eq!(instance.provider.borrow().open_nodes, 0);
+    }
+
+    #[test]
+    fn test_write() {
+        let mut instance = FsInstance::new(MemFs::new());
+        let sfs = unsafe { &mut *ptr::addr_of_mut!(instance.protocol).cast::<SimpleFileSystem>() };
+        let mut root = sfs.open_volume().unwrap();
+
+        let mut file = open_file(
+            root.open(
+                cstr16!("dir\\new.txt"),
+                FileMode::CreateReadWrite,
+                FileAttribute::empty(),
+            )
+            .unwrap(),
+        );
+        file.write(b"abc").unwrap();
+        file.set_position(1).unwrap();
+        file.write(b"XYZ").unwrap();
+        file.flush().unwrap();
+        assert_eq!(instance.provider.borrow().files["\\dir\\new.txt"], b"aXYZ");
+
+        // Rename.
+        let info = NodeInfo::file(cstr16!("renamed.txt").into(), 4).to_file_info();
+        file.set_info(&*info).unwrap();
+        assert!(instance
+            .provider
+            .borrow()
+            .files
+            .contains_key("\\dir\\renamed.txt"));
+        file.delete().unwrap();
+        assert!(!instance
+            .provider
+            .borrow()
+            .files
+            .contains_key("\\dir\\renamed.txt"));
+
+        // Deleting a file opened read-only fails, and closes the file.
+        let file = root
+            .open(cstr16!("top.bin"), FileMode::Read, FileAttribute::empty())
+            .unwrap();
+        assert_eq!(
+            file.delete().unwrap_err().status(),
+            Status::WARN_DELETE_FAILURE
+        );
+
+        // Volume information and label.
+        let info = root.get_boxed_info::<FileSystemInfo>().unwrap();
+        assert_eq!(info.volume_label(), cstr16!("MEMFS"));
+        assert_eq!(info.volume_size(), 1 << 20);
+        assert!(!info.read_only());
+        let mut storage = vec![0u8; 64];
+        let label = FileSystemVolumeLabel::new(&mut storage, cstr16!("NEW")).unwrap();
+        root.set_info(label).unwrap();
+        let label = root.get_boxed_info::<FileSystemVolumeLabel>().unwrap();
+        assert_eq!(label.volume_label(), cstr16!("NEW"));
+
+        drop(root);
+        assert_eq!(instance.provider.borrow().open_nodes, 0);
+    }
+
+    #[test]
+    fn test_set_info_buffer_size() {
+        let mut instance = FsInstance::new(MemFs::new());
+        let mut root = ptr::null_mut();
+        let status = unsafe { (instance.protocol.open_volume)(&mut instance.protocol, &mut root) };
+        assert_eq!(status, Status::SUCCESS);
+        let set_info = |guid: Guid, buffer: &[u16], size: usize| unsafe {
+            ((*root).set_info)(root, &guid, size, buffer.as_ptr().cast())
+        };
+
+        // The label is not terminated within the buffer.
+        let label = [u16::from(b'N'), u16::from(b'E'), 0];
+        assert_eq!(
+            set_info(FileSystemVolumeLabel::GUID, &label, 4),
+            Status::BAD_BUFFER_SIZE
+        );
+        assert_eq!(
+            set_info(FileSystemVolumeLabel::GUID, &label, 6),
+            Status::SUCCESS
+        );
+        assert_eq!(instance.provider.borrow().label, cstr16!("NE"));
+
+        // The buffer is shorter than the header.
+        let header = [0; 8];
+        assert_eq!(
+            set_info(FileSystemInfo::GUID, &header, 16),
+            Status::BAD_BUFFER_SIZE
+        );
+        assert_eq!(
+            set_info(FileInfo::GUID, &header, 16),
+            Status::BAD_BUFFER_SIZE
+        );
+
+        assert_eq!(unsafe { ((*root).close)(root) }, Status::SUCCESS);
+    }
+
+    #[test]
+    fn test_file_mode() {
+        assert_eq!(file_mode(RawFileMode::READ), Some(FileMode::Read));
+        assert_eq!(
+            file_mode(RawFileMode::all()),
+            Some(FileMode::CreateReadWrite)
+        );
+        assert_eq!(file_mode(RawFileMode::WRITE), None);
+        assert_eq!(file_mode(RawFileMode::READ | RawFileMode::CREATE), None);
+    }
+}
@@ -0,0 +1,218 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Module for recursive directory iteration. See [`WalkDir`].
+
+use super::*;
+use crate::{CStr16, Status};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
+
+/// An entry yielded by [`WalkDir`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct WalkDirEntry {
+    path: PathBuf,
+    info: Box<UefiFileInfo>,
+    depth: usize,
+}
+
+impl WalkDirEntry {
+    /// Returns the absolute path of the entry.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Consumes the entry and returns its path.
+    #[must_use]
+    pub fn into_path(self) -> PathBuf {
+        self.path
+    }
+
+    /// Returns the file info of the entry as reported by the directory
+    /// listing.
+    #[must_use]
+    pub fn info(&self) -> &UefiFileInfo {
+        &self.info
+    }
+
+    /// Returns the file name of the entry.
+    #[must_use]
+    pub fn file_name(&self) -> &CStr16 {
+        self.info.file_name()
+    }
+
+    /// Returns whether the entry is a directory.
+    #[must_use]
+    pub fn is_directory(&self) -> bool {
+        self.info.is_directory()
+    }
+
+    /// Returns the depth of the entry relative to the directory the walk
+    /// started in. Direct children of that directory have a depth of `1`.
+    #[must_use]
+    pub const fn depth(&self) -> usize {
+        self.depth
+    }
+}
+
+/// A directory that is currently being iterated.
+#[derive(Debug)]
+struct OpenDir {
+    handle: UefiDirectoryHandle,
+    path: PathBuf,
+    depth: usize,
+}
+
+type EntryFilter<'a> = Box<dyn FnMut(&WalkDirEntry) -> bool + 'a>;
+
+/// Recursive iterator over the entries of a directory tree, created with
+/// [`FileSystem::walk_dir`].
+///
+/// Entries are yielded in depth-first pre-order: a directory is yielded
+/// before its contents. The `.` and `..` entries are skipped, and the
+/// directory the walk started in is not yielded itself.
+///
+/// Errors are reported per entry: if a directory cannot be opened or read,
+/// an error is yielded and the walk continues with the remaining entries.
+///
+/// ```no_run
+/// use uefi::cstr16;
+/// use uefi::fs::{FileSystem, FileSystemResult};
+///
+/// fn print_tree(fs: &mut FileSystem) -> FileSystemResult<()> {
+///     for entry in fs.walk_dir(cstr16!("\\EFI"))?.max_depth(2) {
+///         let entry = entry?;
+///         log::info!("{}", entry.path());
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct WalkDir<'a> {
+    stack: Vec<OpenDir>,
+    max_depth: usize,
+    filter: Option<EntryFilter<'a>>,
+}
+
+impl<'a> WalkDir<'a> {
+    pub(super) fn new(root: UefiDirectoryHandle, path: PathBuf) -> Self {
+        Self {
+            stack: Vec::from([OpenDir {
+                handle: root,
+                path,
+                depth: 0,
+            }]),
+            max_depth: usize::MAX,
+            filter: None,
+        }
+    }
+
+    /// Sets the maximum depth of entries to yield. A depth of `1` only
+    /// yields the direct children of the start directory, like
+    /// [`FileSystem::read_dir`]. A depth of `0` yields nothing.
+    #[must_use]
+    pub const fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Only yields entries for which `predicate` returns `true`.
+    ///
+    /// If `predicate` returns `false` for a directory, the directory is not
+    /// descended into. This allows pruning whole subtrees.
+    #[must_use]
+    pub fn filter_entry(mut self, predicate: impl FnMut(&WalkDirEntry) -> bool + 'a) -> Self {
+        self.filter = Some(Box::new(predicate));
+        self
+    }
+
+    fn accept(&mut self, entry: &WalkDirEntry) -> bool {
+        self.filter.as_mut().map_or(true, |f| f(entry))
+    }
+}
+
+impl Iterator for WalkDir<'_> {
+    type Item = FileSystemResult<WalkDirEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let dir = self.stack.last_mut()?;
+            let depth = dir.depth + 1;
+            if depth > self.max_depth {
+                self.stack.pop();
+                continue;
+            }
+
+            let info = match dir.handle.read_entry_boxed() {
+                Ok(None) => {
+                    self.stack.pop();
+                    continue;
+                }
+                Ok(Some(info)) => info,
+                Err(err) => {
+                    // The directory cannot be read any further.
+                    let dir = self.stack.pop().unwrap();
+                    return Some(Err(Error::Io(IoError {
+                        path: dir.path,
+                        context: IoErrorContext::ReadFailure,
+                        uefi_error: err,
+                    })));
+                }
+            };
+            if COMMON_SKIP_DIRS.contains(&info.file_name()) {
+                continue;
+            }
+
+            let mut path = dir.path.clone();
+            path.push(info.file_name());
+            let entry = WalkDirEntry { path, info, depth };
+            if !self.accept(&entry) {
+                continue;
+            }
+
+            if entry.is_directory() && depth < self.max_depth {
+                let dir = self.stack.last_mut().unwrap();
+                let subdir = dir
+                    .handle
+                    .open(
+                        entry.file_name(),
+                        UefiFileMode::Read,
+                        UefiFileAttribute::empty(),
+                    )
+                    .map_err(|err| (IoErrorContext::OpenError, err))
+                    .and_then(|handle| {
+                        handle.into_directory().ok_or((
+                            IoErrorContext::NotADirectory,
+                            Status::INVALID_PARAMETER.into(),
+                        ))
+                    });
+                match subdir {
+                    Ok(subdir) => self.stack.push(OpenDir {
+                        handle: subdir,
+                        path: entry.path.clone(),
+                        depth,
+                    }),
+                    Err((context, uefi_error)) => {
+                        return Some(Err(Error::Io(IoError {
+                            path: entry.path,
+                            context,
+                            uefi_error,
+                        })))
+                    }
+                }
+            }
+
+            return Some(Ok(entry));
+        }
+    }
+}
+
+impl Debug for WalkDir<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WalkDir")
+            .field("stack", &self.stack)
+            .field("max_depth", &self.max_depth)
+            .field("filter", &self.filter.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use core::fmt;
+
+/// Writer to the QEMU debugcon device and the debug-console of
+/// cloud-hypervisor.
+///
+/// More info: <https://phip1611.de/blog/how-to-use-qemus-debugcon-feature/>
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct DebugconWriter;
+
+impl DebugconWriter {
+    const IO_PORT: u16 = 0xe9;
+}
+
+impl fmt::Write for DebugconWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for &byte in s.as_bytes() {
+            unsafe {
+                core::arch::asm!("outb %al, %dx", in("al") byte, in("dx") Self::IO_PORT, options(att_syntax))
+            };
+        }
+        Ok(())
+    }
+}
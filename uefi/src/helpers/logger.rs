@@ -6,6 +6,9 @@
 //! The main export of this module is the `Logger` structure,
 //! which implements the `log` crate's trait `Log`.
 //!
+//! Besides the text output protocol, the logger can write to a
+//! [`FbConsole`], which keeps working after boot services have been exited.
+//!
 //! # Implementation details
 //!
 //! The implementation is not the most efficient, since there is no buffering done,
@@ -14,8 +17,14 @@
 //! The last part also means that some Unicode characters might not be
 //! supported by the UEFI console. Don't expect emoji output support.
 
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    feature = "log-debugcon"
+))]
+use super::debugcon::DebugconWriter;
 use crate::proto::console::text::Output;
 use crate::system;
+use crate::ui::fb_console::FbConsole;
 use core::fmt::{self, Write};
 use core::ptr;
 use core::sync::atomic::{AtomicPtr, Ordering};
@@ -44,38 +53,24 @@ pub fn disable() {
     LOGGER.disable();
 }
 
-/// Writer to the QEMU debugcon device and the debug-console of
-/// cloud-hypervisor.
+/// Also send log messages to `console`, or stop sending them to a
+/// console if `console` is null.
 ///
-/// More info: <https://phip1611.de/blog/how-to-use-qemus-debugcon-feature/>
-#[cfg(all(
-    any(target_arch = "x86", target_arch = "x86_64"),
-    feature = "log-debugcon"
-))]
-#[derive(Copy, Clone, Debug)]
-struct DebugconWriter;
-
-#[cfg(all(
-    any(target_arch = "x86", target_arch = "x86_64"),
-    feature = "log-debugcon"
-))]
-impl DebugconWriter {
-    const IO_PORT: u16 = 0xe9;
-}
-
-#[cfg(all(
-    any(target_arch = "x86", target_arch = "x86_64"),
-    feature = "log-debugcon"
-))]
-impl core::fmt::Write for DebugconWriter {
-    fn write_str(&mut self, s: &str) -> fmt::Result {
-        for &byte in s.as_bytes() {
-            unsafe {
-                core::arch::asm!("outb %al, %dx", in("al") byte, in("dx") Self::IO_PORT, options(att_syntax))
-            };
-        }
-        Ok(())
-    }
+/// Unlike logging to the text output protocol, this is not disabled when
+/// boot services are exited, unless the console draws with
+/// [`GraphicsOutput::blt`] because the mode has no framebuffer. Use a
+/// console created with [`FbConsole::from_raw_parts`] to keep logging to
+/// the screen after that.
+///
+/// [`GraphicsOutput::blt`]: crate::proto::console::gop::GraphicsOutput::blt
+///
+/// # Safety
+///
+/// The `console` pointer must either be null or point to a valid
+/// [`FbConsole`]. That console must remain valid, and must not be used
+/// otherwise, until this function is called with a different `console`.
+pub unsafe fn set_log_console(console: *mut FbConsole<'_>) {
+    unsafe { LOGGER.set_console(console) }
 }
 
 /// Logging implementation which writes to a UEFI output stream.
@@ -86,6 +81,7 @@ impl core::fmt::Write for DebugconWriter {
 #[derive(Debug)]
 pub struct Logger {
     writer: AtomicPtr<Output>,
+    console: AtomicPtr<FbConsole<'static>>,
 }
 
 impl Logger {
@@ -98,6 +94,7 @@ impl Logger {
     pub const fn new() -> Self {
         Self {
             writer: AtomicPtr::new(ptr::null_mut()),
+            console: AtomicPtr::new(ptr::null_mut()),
         }
     }
 
@@ -126,9 +123,32 @@ impl Logger {
         self.writer.store(output, Ordering::Release);
     }
 
-    /// Disable the logger.
+    /// Set the [`FbConsole`] to which the logger will write, in addition to
+    /// the [`Output`]. A null pointer removes the console.
+    ///
+    /// # Safety
+    ///
+    /// The `console` pointer must either be null or point to a valid
+    /// [`FbConsole`]. That console must remain valid until `set_console` is
+    /// called with a different `console`.
+    pub unsafe fn set_console(&self, console: *mut FbConsole<'_>) {
+        self.console.store(console.cast(), Ordering::Release);
+    }
+
+    /// Disable logging to the [`Output`].
+    ///
+    /// A console set with [`set_console`] is kept if it draws directly to
+    /// the framebuffer, since it can be used after boot services have been
+    /// exited. A console that draws with [`GraphicsOutput::blt`] is removed.
+    ///
+    /// [`GraphicsOutput::blt`]: crate::proto::console::gop::GraphicsOutput::blt
+    /// [`set_console`]: Self::set_console
     pub fn disable(&self) {
         unsafe { self.set_output(ptr::null_mut()) }
+        let console = self.console.load(Ordering::Acquire);
+        if unsafe { console.as_ref() }.is_some_and(FbConsole::uses_blt) {
+            unsafe { self.set_console(ptr::null_mut()) }
+        }
     }
 }
 
@@ -153,6 +173,17 @@ impl log::Log for Logger {
             );
         }
 
+        if let Some(console) = unsafe { self.console.load(Ordering::Acquire).as_mut() } {
+            // Ignore all errors, for the same reasons as above.
+            let _ = DecoratedLog::write(
+                console,
+                record.level(),
+                record.args(),
+                record.file().unwrap_or("<unknown file>"),
+                record.line().unwrap_or(0),
+            );
+        }
+
         #[cfg(all(
             any(target_arch = "x86", target_arch = "x86_64"),
             feature = "log-debugcon"
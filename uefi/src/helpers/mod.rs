@@ -9,21 +9,35 @@
 //! - an implementation of  [`log::Log`] (feature `logger`) which logs to
 //!   the stdout text protocol of UEFI (as long as boot services were not
 //!   excited) and to the [debugcon device](https://phip1611.de/blog/how-to-use-qemus-debugcon-feature/)
-//!   (only on x86)  (feature `log-debugcon`).
+//!   (only on x86)  (feature `log-debugcon`). Log messages can also be
+//!   drawn on the screen with a [`FbConsole`] by calling [`set_log_console`].
 //! - [`print!`][print_macro] and [`println!`][println_macro] macros defaulting
-//!   to the uefi boot service stdout stream
+//!   to the uefi boot service stdout stream. With the `alloc` feature, their
+//!   output can be mirrored to several devices by calling
+//!   [`set_print_output`] with a [`ConsoleSplitter`].
 //! - default panic handler (feature `panic_handler`)
 //!
 //! **PLEASE NOTE** that these helpers are meant for the pre exit boot service
 //! epoch.
 //!
+//! [`ConsoleSplitter`]: uefi::ui::splitter::ConsoleSplitter
+//! [`FbConsole`]: uefi::ui::fb_console::FbConsole
 //! [print_macro]: uefi::print!
 //! [println_macro]: uefi::println!
 
 use crate::Result;
+#[cfg(feature = "logger")]
+pub use logger::set_log_console;
 #[doc(hidden)]
 pub use println::_print;
+#[cfg(feature = "alloc")]
+pub use println::set_print_output;
 
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    any(feature = "alloc", all(feature = "logger", feature = "log-debugcon"))
+))]
+pub(crate) mod debugcon;
 #[cfg(feature = "global_allocator")]
 mod global_allocator;
 #[cfg(feature = "logger")]
@@ -59,4 +73,6 @@ pub fn init() -> Result<()> {
 pub(crate) fn exit() {
     #[cfg(feature = "logger")]
     logger::disable();
+    #[cfg(feature = "alloc")]
+    println::disable();
 }
@@ -1,12 +1,55 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+#[cfg(feature = "alloc")]
+use crate::ui::splitter::ConsoleSplitter;
 use crate::{boot, system};
 use core::fmt::Write;
+#[cfg(feature = "alloc")]
+use core::ptr;
+#[cfg(feature = "alloc")]
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+/// Output of the print macros instead of stdout, if not null.
+#[cfg(feature = "alloc")]
+static PRINT_OUTPUT: AtomicPtr<ConsoleSplitter> = AtomicPtr::new(ptr::null_mut());
+
+/// Send the output of [`print!`] and [`println!`] to `splitter` instead of
+/// stdout, or back to stdout if `splitter` is null.
+///
+/// The splitter is removed again when boot services are exited. Unlike
+/// writing to stdout, errors of the splitter's devices are ignored.
+///
+/// # Safety
+///
+/// The `splitter` pointer must either be null or point to a valid
+/// [`ConsoleSplitter`]. That splitter must remain valid, and must not be
+/// used otherwise, until this function is called with a different
+/// `splitter` or boot services are exited.
+///
+/// [`print!`]: crate::print!
+/// [`println!`]: crate::println!
+#[cfg(feature = "alloc")]
+pub unsafe fn set_print_output(splitter: *mut ConsoleSplitter) {
+    PRINT_OUTPUT.store(splitter, Ordering::Release);
+}
+
+/// Stop using the splitter set with [`set_print_output`].
+#[cfg(feature = "alloc")]
+pub(super) fn disable() {
+    PRINT_OUTPUT.store(ptr::null_mut(), Ordering::Release);
+}
 
 /// INTERNAL API! Helper for print macros.
 #[doc(hidden)]
 pub fn _print(args: core::fmt::Arguments) {
     if boot::are_boot_services_active() {
+        #[cfg(feature = "alloc")]
+        if let Some(splitter) = unsafe { PRINT_OUTPUT.load(Ordering::Acquire).as_mut() } {
+            // The splitter writes to all devices that still work, so one
+            // failing device must not make every print panic.
+            let _ = splitter.write_fmt(args);
+            return;
+        }
         system::with_stdout(|stdout| {
             stdout.write_fmt(args).expect("Failed to write to stdout");
         });
@@ -0,0 +1,172 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::{BufRead, Read, Seek, SeekFrom, DEFAULT_BUF_SIZE};
+use crate::Result;
+use alloc::boxed::Box;
+use alloc::vec;
+
+/// Adds buffering to a [`Read`]er.
+///
+/// Many small reads on a [`RegularFile`] or a disk each result in a call into
+/// the firmware. `BufReader` reads large chunks into an internal buffer and
+/// serves small reads from there.
+///
+/// [`RegularFile`]: crate::proto::media::file::RegularFile
+#[derive(Debug)]
+pub struct BufReader<R> {
+    inner: R,
+    buf: Box<[u8]>,
+    pos: usize,
+    filled: usize,
+}
+
+impl<R: Read> BufReader<R> {
+    /// Default capacity of the internal buffer.
+    pub const DEFAULT_CAPACITY: usize = 8 * DEFAULT_BUF_SIZE;
+
+    /// Creates a new `BufReader` with a default buffer capacity.
+    pub fn new(inner: R) -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY, inner)
+    }
+
+    /// Creates a new `BufReader` with the given buffer capacity.
+    pub fn with_capacity(capacity: usize, inner: R) -> Self {
+        Self {
+            inner,
+            buf: vec![0; capacity].into_boxed_slice(),
+            pos: 0,
+            filled: 0,
+        }
+    }
+}
+
+impl<R> BufReader<R> {
+    /// Returns a reference to the underlying reader.
+    pub const fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    ///
+    /// Reading from the underlying reader directly skips the buffered data.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns the currently buffered data.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[self.pos..self.filled]
+    }
+
+    /// Returns the capacity of the internal buffer.
+    pub const fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Consumes the `BufReader`, returning the underlying reader. Buffered
+    /// data is lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    const fn discard_buffer(&mut self) {
+        self.pos = 0;
+        self.filled = 0;
+    }
+}
+
+impl<R: Read> Read for BufReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        // Bypass the internal buffer for large reads if it is empty.
+        if self.pos == self.filled && buf.len() >= self.capacity() {
+            self.discard_buffer();
+            return self.inner.read(buf);
+        }
+        let n = self.fill_buf()?.read(buf)?;
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: Read> BufRead for BufReader<R> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        if self.pos >= self.filled {
+            self.filled = self.inner.read(&mut self.buf)?;
+            self.pos = 0;
+        }
+        Ok(self.buffer())
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.filled);
+    }
+}
+
+impl<R: Read + Seek> Seek for BufReader<R> {
+    /// Seeks in the underlying reader and discards the buffer.
+    ///
+    /// [`SeekFrom::Current`] is relative to the logical position, i.e. it
+    /// takes data that is buffered but not yet consumed into account.
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        let result = match pos {
+            SeekFrom::Current(offset) => {
+                let remaining = (self.filled - self.pos) as i64;
+                self.inner.seek(SeekFrom::Current(offset - remaining))?
+            }
+            _ => self.inner.seek(pos)?,
+        };
+        self.discard_buffer();
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::Cursor;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_buf_reader() {
+        let data: Vec<u8> = (0..100).collect();
+        let mut reader = BufReader::with_capacity(16, Cursor::new(&data[..]));
+
+        let mut buf = [0; 4];
+        assert_eq!(reader.read(&mut buf), Ok(4));
+        assert_eq!(buf, [0, 1, 2, 3]);
+        assert_eq!(reader.buffer().len(), 12);
+        assert_eq!(reader.get_ref().position(), 16);
+
+        // The logical position accounts for buffered data.
+        assert_eq!(reader.stream_position(), Ok(4));
+        assert_eq!(reader.seek(SeekFrom::Current(6)), Ok(10));
+        assert_eq!(reader.read(&mut buf), Ok(4));
+        assert_eq!(buf, [10, 11, 12, 13]);
+
+        // Large reads bypass the buffer.
+        let mut large = [0; 32];
+        reader.seek(SeekFrom::Start(50)).unwrap();
+        assert_eq!(reader.read(&mut large), Ok(32));
+        assert_eq!(large[0], 50);
+        assert!(reader.buffer().is_empty());
+    }
+
+    #[test]
+    fn test_buf_reader_lines() {
+        let mut reader = BufReader::with_capacity(4, &b"first line\nsecond\n\nlast"[..]);
+        let mut line = String::new();
+        assert_eq!(reader.read_line(&mut line), Ok(11));
+        assert_eq!(line, "first line\n");
+
+        let mut bytes = Vec::new();
+        assert_eq!(reader.read_until(b'\n', &mut bytes), Ok(7));
+        assert_eq!(bytes, b"second\n");
+
+        line.clear();
+        assert_eq!(reader.read_line(&mut line), Ok(1));
+        assert_eq!(reader.read_line(&mut line), Ok(4));
+        assert_eq!(line, "\nlast");
+        assert_eq!(reader.read_line(&mut line), Ok(0));
+    }
+}
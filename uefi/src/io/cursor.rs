@@ -0,0 +1,163 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::{BufRead, Read, Seek, SeekFrom, Write};
+use crate::Result;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Adds a seekable position to an in-memory buffer.
+///
+/// `Cursor<T>` implements [`Read`], [`BufRead`], and [`Seek`] for any
+/// `T: AsRef<[u8]>`, and [`Write`] for `&mut [u8]` and `Vec<u8>`. This is
+/// the in-memory counterpart to files and disks, which is useful for tests
+/// and for parsing data that has already been loaded.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Cursor<T> {
+    inner: T,
+    pos: u64,
+}
+
+impl<T> Cursor<T> {
+    /// Creates a new cursor at position zero.
+    pub const fn new(inner: T) -> Self {
+        Self { inner, pos: 0 }
+    }
+
+    /// Consumes the cursor, returning the underlying value.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Returns a reference to the underlying value.
+    pub const fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying value.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Returns the current position.
+    pub const fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Sets the current position.
+    pub fn set_position(&mut self, pos: u64) {
+        self.pos = pos;
+    }
+}
+
+impl<T: AsRef<[u8]>> Cursor<T> {
+    /// Returns the bytes from the current position to the end, or an empty
+    /// slice if the position is past the end.
+    fn remaining_slice(&self) -> &[u8] {
+        let inner = self.inner.as_ref();
+        let start = usize::try_from(self.pos).map_or(inner.len(), |pos| pos.min(inner.len()));
+        &inner[start..]
+    }
+}
+
+impl<T: AsRef<[u8]>> Read for Cursor<T> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.remaining_slice().read(buf)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl<T: AsRef<[u8]>> BufRead for Cursor<T> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        Ok(self.remaining_slice())
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos += amt as u64;
+    }
+}
+
+impl<T: AsRef<[u8]>> Seek for Cursor<T> {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        let len = self.inner.as_ref().len() as u64;
+        self.pos = pos.resolve(self.pos, len)?;
+        Ok(self.pos)
+    }
+}
+
+/// Writing past the end of the slice is not possible; [`Write::write`]
+/// returns `0` once the end is reached.
+impl Write for Cursor<&mut [u8]> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let len = self.inner.len();
+        let start = usize::try_from(self.pos).map_or(len, |pos| pos.min(len));
+        let n = (&mut self.inner[start..]).write(buf)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result {
+        Ok(())
+    }
+}
+
+/// Writing past the end of the vector grows it, filling any gap with zeros.
+#[cfg(feature = "alloc")]
+impl Write for Cursor<Vec<u8>> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let start = usize::try_from(self.pos).map_err(|_| crate::Status::OUT_OF_RESOURCES)?;
+        let end = start + buf.len();
+        if self.inner.len() < end {
+            self.inner.resize(end, 0);
+        }
+        self.inner[start..end].copy_from_slice(buf);
+        self.pos = end as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_cursor_read_seek() {
+        let mut cursor = Cursor::new([1u8, 2, 3, 4, 5]);
+        let mut buf = [0; 2];
+        assert_eq!(cursor.read(&mut buf), Ok(2));
+        assert_eq!(buf, [1, 2]);
+        assert_eq!(cursor.seek(SeekFrom::End(-1)), Ok(4));
+        assert_eq!(cursor.read(&mut buf), Ok(1));
+        assert_eq!(buf[0], 5);
+        assert_eq!(cursor.read(&mut buf), Ok(0));
+
+        // Reading past the end yields no data.
+        assert_eq!(cursor.seek(SeekFrom::Start(100)), Ok(100));
+        assert_eq!(cursor.read(&mut buf), Ok(0));
+
+        cursor.rewind().unwrap();
+        assert_eq!(cursor.fill_buf().unwrap(), [1, 2, 3, 4, 5]);
+        cursor.consume(3);
+        assert_eq!(cursor.stream_position(), Ok(3));
+    }
+
+    #[test]
+    fn test_cursor_write() {
+        let mut storage = [0u8; 4];
+        let mut cursor = Cursor::new(&mut storage[..]);
+        cursor.seek(SeekFrom::Start(2)).unwrap();
+        assert_eq!(cursor.write(&[7, 8, 9]), Ok(2));
+        assert_eq!(storage, [0, 0, 7, 8]);
+
+        let mut cursor = Cursor::new(vec![1, 2]);
+        cursor.seek(SeekFrom::Start(4)).unwrap();
+        cursor.write_all(&[5, 6]).unwrap();
+        assert_eq!(cursor.into_inner(), [1, 2, 0, 0, 5, 6]);
+    }
+}
@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Implementations of the I/O traits for foreign and protocol types.
+
+use super::{BufRead, Read, Seek, SeekFrom, Write};
+use crate::proto::media::file::{File, RegularFile};
+use crate::{Result, ResultExt};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+impl Read for &[u8] {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = buf.len().min(self.len());
+        let (head, tail) = self.split_at(n);
+        buf[..n].copy_from_slice(head);
+        *self = tail;
+        Ok(n)
+    }
+}
+
+impl BufRead for &[u8] {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        Ok(self)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        *self = &self[amt..];
+    }
+}
+
+/// Writing to a mutable slice overwrites its contents and advances the
+/// slice past the written bytes.
+impl Write for &mut [u8] {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let n = buf.len().min(self.len());
+        let (head, tail) = core::mem::take(self).split_at_mut(n);
+        head.copy_from_slice(&buf[..n]);
+        *self = tail;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Write for Vec<u8> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result {
+        Ok(())
+    }
+}
+
+impl Read for RegularFile {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        Self::read(self, buf)
+    }
+}
+
+impl Write for RegularFile {
+    /// Writes the entire buffer. The firmware either writes all of `buf` or
+    /// fails.
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        Self::write(self, buf).discard_errdata()?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result {
+        File::flush(self)
+    }
+}
+
+impl Seek for RegularFile {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        let current = self.get_position()?;
+        let len = match pos {
+            SeekFrom::End(_) => {
+                self.set_position(Self::END_OF_FILE)?;
+                let len = self.get_position();
+                // Don't leave the file at the end if the seek fails.
+                self.set_position(current)?;
+                len?
+            }
+            _ => 0,
+        };
+        let target = pos.resolve(current, len)?;
+        self.set_position(target)?;
+        Ok(target)
+    }
+}
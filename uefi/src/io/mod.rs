@@ -0,0 +1,304 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Traits for byte-oriented I/O, modeled after `std::io`.
+//!
+//! The [`Read`], [`Write`], [`Seek`], and [`BufRead`] traits allow generic
+//! code, such as ELF loaders, decompressors, or archive readers, to operate
+//! uniformly on files, disks, and in-memory buffers. Errors are reported as
+//! a regular [`uefi::Result`].
+//!
+//! Implementations are provided for:
+//! - [`RegularFile`]
+//! - [`DiskIoCursor`], a seekable view of a [`DiskIo`] device
+//! - byte slices (`&[u8]` and `&mut [u8]`) and [`Cursor`]
+//! - `Vec<u8>` (with the `alloc` feature)
+//!
+//! With the `alloc` feature, [`BufReader`] adds buffering to any reader.
+//!
+//! [`DiskIo`]: crate::proto::media::disk::DiskIo
+//! [`DiskIoCursor`]: crate::proto::media::disk::DiskIoCursor
+//! [`RegularFile`]: crate::proto::media::file::RegularFile
+
+#[cfg(feature = "alloc")]
+mod buffered;
+mod cursor;
+mod impls;
+
+#[cfg(feature = "alloc")]
+pub use buffered::BufReader;
+pub use cursor::Cursor;
+
+use crate::{Result, Status};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Size of the temporary buffers used by the provided trait methods.
+#[cfg(feature = "alloc")]
+const DEFAULT_BUF_SIZE: usize = 512;
+
+/// Source of bytes.
+pub trait Read {
+    /// Pulls some bytes from this source into `buf`, returning how many bytes
+    /// were read. A return value of `0` indicates the end of the data (or an
+    /// empty `buf`).
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
+
+    /// Reads exactly `buf.len()` bytes.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::END_OF_FILE`] if the source ends before `buf` is filled.
+    ///   The contents of `buf` are unspecified in this case.
+    /// * Any error returned by [`Read::read`].
+    fn read_exact(&mut self, mut buf: &mut [u8]) -> Result {
+        while !buf.is_empty() {
+            match self.read(buf)? {
+                0 => return Err(Status::END_OF_FILE.into()),
+                n => buf = &mut buf[n..],
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads all bytes until the end of the source and appends them to
+    /// `buf`. Returns the number of bytes read.
+    #[cfg(feature = "alloc")]
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> Result<usize> {
+        let start_len = buf.len();
+        let mut chunk = [0; DEFAULT_BUF_SIZE];
+        loop {
+            match self.read(&mut chunk)? {
+                0 => return Ok(buf.len() - start_len),
+                n => buf.extend_from_slice(&chunk[..n]),
+            }
+        }
+    }
+
+    /// Creates a "by reference" adapter for this instance.
+    fn by_ref(&mut self) -> &mut Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+}
+
+/// Sink for bytes.
+pub trait Write {
+    /// Writes some bytes from `buf`, returning how many bytes were written.
+    fn write(&mut self, buf: &[u8]) -> Result<usize>;
+
+    /// Flushes buffered data to the underlying device.
+    fn flush(&mut self) -> Result;
+
+    /// Writes the entire buffer.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::END_OF_MEDIA`] if the sink does not accept any more data.
+    /// * Any error returned by [`Write::write`].
+    fn write_all(&mut self, mut buf: &[u8]) -> Result {
+        while !buf.is_empty() {
+            match self.write(buf)? {
+                0 => return Err(Status::END_OF_MEDIA.into()),
+                n => buf = &buf[n..],
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates a "by reference" adapter for this instance.
+    fn by_ref(&mut self) -> &mut Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+}
+
+/// Position to seek to, used by [`Seek::seek`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SeekFrom {
+    /// Offset in bytes from the start.
+    Start(u64),
+    /// Offset in bytes from the end. May be negative.
+    End(i64),
+    /// Offset in bytes from the current position. May be negative.
+    Current(i64),
+}
+
+impl SeekFrom {
+    /// Resolves the seek target to an absolute position, given the current
+    /// position and the total length.
+    ///
+    /// Returns [`Status::INVALID_PARAMETER`] if the target is before the
+    /// start or overflows.
+    pub(crate) fn resolve(self, current: u64, len: u64) -> Result<u64> {
+        let pos = match self {
+            Self::Start(pos) => Some(pos),
+            Self::End(offset) => len.checked_add_signed(offset),
+            Self::Current(offset) => current.checked_add_signed(offset),
+        };
+        pos.ok_or_else(|| Status::INVALID_PARAMETER.into())
+    }
+}
+
+/// Cursor within a stream of bytes that can be moved.
+pub trait Seek {
+    /// Seeks to the given position and returns the new position from the
+    /// start of the stream.
+    ///
+    /// Seeking beyond the end is allowed; the behavior of subsequent reads
+    /// and writes depends on the implementation.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`] when seeking before the start.
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64>;
+
+    /// Seeks to the start of the stream.
+    fn rewind(&mut self) -> Result {
+        self.seek(SeekFrom::Start(0)).map(|_| ())
+    }
+
+    /// Returns the current position from the start of the stream.
+    fn stream_position(&mut self) -> Result<u64> {
+        self.seek(SeekFrom::Current(0))
+    }
+}
+
+/// A [`Read`]er with an internal buffer.
+pub trait BufRead: Read {
+    /// Returns the contents of the internal buffer, filling it from the
+    /// inner reader if it is empty. An empty slice indicates the end of the
+    /// data.
+    fn fill_buf(&mut self) -> Result<&[u8]>;
+
+    /// Marks `amt` bytes of the buffer returned by [`BufRead::fill_buf`] as
+    /// consumed.
+    fn consume(&mut self, amt: usize);
+
+    /// Reads bytes into `buf` until the `delimiter` byte or the end of the
+    /// data is reached. The delimiter is included in `buf`. Returns the
+    /// number of bytes read.
+    #[cfg(feature = "alloc")]
+    fn read_until(&mut self, delimiter: u8, buf: &mut Vec<u8>) -> Result<usize> {
+        let mut read = 0;
+        loop {
+            let (done, used) = {
+                let available = self.fill_buf()?;
+                match available.iter().position(|&b| b == delimiter) {
+                    Some(i) => {
+                        buf.extend_from_slice(&available[..=i]);
+                        (true, i + 1)
+                    }
+                    None => {
+                        buf.extend_from_slice(available);
+                        (available.is_empty(), available.len())
+                    }
+                }
+            };
+            self.consume(used);
+            read += used;
+            if done {
+                return Ok(read);
+            }
+        }
+    }
+
+    /// Reads bytes until a newline (`0x0a`) or the end of the data is
+    /// reached and appends them to `buf`. The newline is included.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`] if the data is not valid UTF-8. In
+    ///   this case `buf` is left unchanged, but the bytes of the line have
+    ///   already been consumed from the reader and are lost.
+    /// * Any error returned by [`BufRead::fill_buf`].
+    #[cfg(feature = "alloc")]
+    fn read_line(&mut self, buf: &mut alloc::string::String) -> Result<usize> {
+        let mut bytes = Vec::new();
+        let read = self.read_until(b'\n', &mut bytes)?;
+        let line = core::str::from_utf8(&bytes).map_err(|_| Status::INVALID_PARAMETER)?;
+        buf.push_str(line);
+        Ok(read)
+    }
+}
+
+impl<R: Read + ?Sized> Read for &mut R {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        (**self).read(buf)
+    }
+}
+
+impl<W: Write + ?Sized> Write for &mut W {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        (**self).write(buf)
+    }
+
+    fn flush(&mut self) -> Result {
+        (**self).flush()
+    }
+}
+
+impl<S: Seek + ?Sized> Seek for &mut S {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        (**self).seek(pos)
+    }
+}
+
+impl<B: BufRead + ?Sized> BufRead for &mut B {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        (**self).fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        (**self).consume(amt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seek_from_resolve() {
+        assert_eq!(SeekFrom::Start(5).resolve(2, 10), Ok(5));
+        assert_eq!(SeekFrom::End(-3).resolve(2, 10), Ok(7));
+        assert_eq!(SeekFrom::End(3).resolve(2, 10), Ok(13));
+        assert_eq!(SeekFrom::Current(4).resolve(2, 10), Ok(6));
+        assert_eq!(
+            SeekFrom::Current(-3).resolve(2, 10),
+            Err(Status::INVALID_PARAMETER.into())
+        );
+    }
+
+    #[test]
+    fn test_read_exact() {
+        let mut data: &[u8] = &[1, 2, 3, 4];
+        let mut buf = [0; 3];
+        data.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [1, 2, 3]);
+        assert_eq!(data.read_exact(&mut buf), Err(Status::END_OF_FILE.into()));
+    }
+
+    #[test]
+    fn test_write_all() {
+        let mut storage = [0; 4];
+        let mut sink: &mut [u8] = &mut storage;
+        sink.write_all(&[1, 2]).unwrap();
+        sink.write_all(&[3, 4]).unwrap();
+        assert_eq!(sink.write_all(&[5]), Err(Status::END_OF_MEDIA.into()));
+        assert_eq!(storage, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_read_to_end() {
+        let data: alloc::vec::Vec<u8> = (0..=255).cycle().take(1300).collect();
+        let mut reader = &data[..];
+        let mut out = Vec::new();
+        assert_eq!(reader.read_to_end(&mut out), Ok(1300));
+        assert_eq!(out, data);
+    }
+}
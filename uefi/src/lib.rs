@@ -142,6 +142,9 @@
 //! - `qemu`: Enable some code paths to adapt their execution when executed
 //!   in QEMU, such as using the special `qemu-exit` device when the panic
 //!   handler is called.
+//! - `serde`: Implement [serde]'s `Serialize` for device paths and their
+//!   nodes, and (in conjunction with the `alloc`-feature) `Deserialize` for
+//!   owned device paths.
 //!
 //! Some of these features, such as the `logger` or `panic_handler` features,
 //! only unfold their potential when you invoke `uefi::helpers::init` as soon
@@ -210,6 +213,7 @@
 //! [spec]: https://uefi.org/specifications
 //! [template]: https://github.com/rust-osdev/uefi-rs/tree/main/template
 //! [uefi-std-tr-issue]: https://github.com/rust-lang/rust/issues/100499
+//! [serde]: https://serde.rs/
 //! [unstable features]: https://doc.rust-lang.org/unstable-book/
 
 #![cfg_attr(all(feature = "unstable", feature = "alloc"), feature(allocator_api))]
@@ -240,15 +244,18 @@ extern crate uefi_raw;
 pub mod data_types;
 pub mod allocator;
 pub mod boot;
+pub mod disk;
 #[cfg(feature = "alloc")]
 pub mod fs;
 pub mod helpers;
+pub mod io;
 pub mod mem;
 pub mod prelude;
 pub mod proto;
 pub mod runtime;
 pub mod system;
 pub mod table;
+pub mod ui;
 
 pub(crate) mod polyfill;
 
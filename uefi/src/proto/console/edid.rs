@@ -0,0 +1,526 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Extended Display Identification Data (EDID) of video output devices.
+//!
+//! The GOP driver installs [`EdidDiscovered`] and [`EdidActive`] on the
+//! handle of each [`GraphicsOutput`] of a display. The platform can replace
+//! the EDID of a display through [`EdidOverride`].
+//!
+//! [`Edid`] parses the base block of the EDID, which describes the
+//! manufacturer, the physical size and the preferred timing of the display.
+//! [`Edid::best_mode`] uses this to find the graphics mode that best matches
+//! the native resolution of the panel:
+//!
+//! ```no_run
+//! use uefi::boot;
+//! use uefi::proto::console::edid::EdidActive;
+//! use uefi::proto::console::gop::GraphicsOutput;
+//!
+//! # fn main() -> uefi::Result {
+//! let handle = boot::get_handle_for_protocol::<GraphicsOutput>()?;
+//! let mut gop = boot::open_protocol_exclusive::<GraphicsOutput>(handle)?;
+//! let edid = boot::open_protocol_exclusive::<EdidActive>(handle)?;
+//!
+//! if let Ok(edid) = edid.edid() {
+//!     if let Some(mode) = edid.best_mode(&gop) {
+//!         gop.set_mode(&mode)?;
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::proto::console::gop::{GraphicsOutput, Mode};
+use crate::proto::unsafe_protocol;
+use crate::{Handle, Result, StatusExt};
+use core::fmt::{self, Display, Formatter};
+use core::{ptr, slice};
+use uefi_raw::protocol::console::{
+    EdidActiveProtocol, EdidDiscoveredProtocol, EdidOverrideProtocol,
+};
+
+pub use uefi_raw::protocol::console::EdidOverrideProtocolAttributes;
+
+/// Size of an EDID block.
+pub const EDID_BLOCK_SIZE: usize = 128;
+
+/// Fixed pattern at the start of an EDID.
+const HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+
+/// Offset of the four 18-byte descriptors in the base block.
+const DESCRIPTORS_OFFSET: usize = 54;
+const DESCRIPTOR_SIZE: usize = 18;
+
+/// Tag of a display descriptor with the product name.
+const DISPLAY_NAME_TAG: u8 = 0xfc;
+
+/// EDID of a video output device, as read from the display by the GOP
+/// driver.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(EdidDiscoveredProtocol::GUID)]
+pub struct EdidDiscovered(EdidDiscoveredProtocol);
+
+impl EdidDiscovered {
+    /// The EDID, including extension blocks. Empty if the display has no
+    /// EDID.
+    #[must_use]
+    pub fn raw_edid(&self) -> &[u8] {
+        unsafe { edid_slice(self.0.edid, self.0.size_of_edid as usize) }
+    }
+
+    /// Parse the base block of the EDID.
+    ///
+    /// # Errors
+    ///
+    /// See [`Edid::parse`]. [`EdidError::TooShort`] is returned if the
+    /// display has no EDID.
+    pub fn edid(&self) -> core::result::Result<Edid, EdidError> {
+        Edid::parse(self.raw_edid())
+    }
+}
+
+/// EDID that the GOP driver uses for a video output device. This is either
+/// the [`EdidDiscovered`] or the EDID from [`EdidOverride`].
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(EdidActiveProtocol::GUID)]
+pub struct EdidActive(EdidActiveProtocol);
+
+impl EdidActive {
+    /// The EDID, including extension blocks. Empty if the display has no
+    /// EDID.
+    #[must_use]
+    pub fn raw_edid(&self) -> &[u8] {
+        unsafe { edid_slice(self.0.edid, self.0.size_of_edid as usize) }
+    }
+
+    /// Parse the base block of the EDID.
+    ///
+    /// # Errors
+    ///
+    /// See [`Edid::parse`]. [`EdidError::TooShort`] is returned if the
+    /// display has no EDID.
+    pub fn edid(&self) -> core::result::Result<Edid, EdidError> {
+        Edid::parse(self.raw_edid())
+    }
+}
+
+/// Provided by the platform to override the EDID of video output devices.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(EdidOverrideProtocol::GUID)]
+pub struct EdidOverride(EdidOverrideProtocol);
+
+impl EdidOverride {
+    /// Get the override for the video output device on `child_handle`.
+    ///
+    /// Returns the attributes of the override and the EDID to use, which is
+    /// empty if the discovered EDID should be used.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::UNSUPPORTED`]: there is no override for the device.
+    ///
+    /// [`Status::UNSUPPORTED`]: crate::Status::UNSUPPORTED
+    pub fn get_edid(
+        &self,
+        child_handle: Handle,
+    ) -> Result<(EdidOverrideProtocolAttributes, &[u8])> {
+        let child_handle = child_handle.as_ptr();
+        let mut attributes = EdidOverrideProtocolAttributes::empty();
+        let mut size = 0;
+        let mut edid = ptr::null();
+        unsafe {
+            (self.0.get_edid)(
+                &self.0,
+                &child_handle,
+                &mut attributes,
+                &mut size,
+                &mut edid,
+            )
+        }
+        .to_result_with_val(|| (attributes, unsafe { edid_slice(edid, size) }))
+    }
+}
+
+/// Get the EDID at `edid`, which may be null.
+unsafe fn edid_slice<'a>(edid: *const u8, size: usize) -> &'a [u8] {
+    if edid.is_null() {
+        &[]
+    } else {
+        unsafe { slice::from_raw_parts(edid, size) }
+    }
+}
+
+/// The base block of an EDID, version 1.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Edid([u8; EDID_BLOCK_SIZE]);
+
+impl Edid {
+    /// Parse the base block at the start of `data`. Extension blocks are
+    /// ignored.
+    ///
+    /// # Errors
+    ///
+    /// * [`EdidError::TooShort`]: `data` is shorter than a block.
+    /// * [`EdidError::InvalidHeader`]: the block does not start with the EDID
+    ///   header.
+    /// * [`EdidError::ChecksumMismatch`]: the bytes of the block do not add
+    ///   up to zero.
+    pub fn parse(data: &[u8]) -> core::result::Result<Self, EdidError> {
+        let block: [u8; EDID_BLOCK_SIZE] = data
+            .get(..EDID_BLOCK_SIZE)
+            .ok_or(EdidError::TooShort)?
+            .try_into()
+            .unwrap();
+        if block[..HEADER.len()] != HEADER {
+            return Err(EdidError::InvalidHeader);
+        }
+        if block.iter().fold(0_u8, |sum, &byte| sum.wrapping_add(byte)) != 0 {
+            return Err(EdidError::ChecksumMismatch);
+        }
+        Ok(Self(block))
+    }
+
+    /// The bytes of the block.
+    #[must_use]
+    pub const fn as_bytes(&self) -> &[u8; EDID_BLOCK_SIZE] {
+        &self.0
+    }
+
+    /// Three-letter PNP ID of the manufacturer, such as `b"DEL"`. Invalid
+    /// letters are replaced by `b'?'`.
+    #[must_use]
+    pub fn manufacturer_id(&self) -> [u8; 3] {
+        let id = u16::from_be_bytes([self.0[8], self.0[9]]);
+        let letter = |shift: u16| match (id >> shift) & 0x1f {
+            value @ 1..=26 => b'A' + value as u8 - 1,
+            _ => b'?',
+        };
+        [letter(10), letter(5), letter(0)]
+    }
+
+    /// Product code assigned by the manufacturer.
+    #[must_use]
+    pub const fn product_code(&self) -> u16 {
+        u16::from_le_bytes([self.0[10], self.0[11]])
+    }
+
+    /// Serial number, or zero if not used.
+    #[must_use]
+    pub const fn serial_number(&self) -> u32 {
+        u32::from_le_bytes([self.0[12], self.0[13], self.0[14], self.0[15]])
+    }
+
+    /// Year of manufacture, or the model year.
+    #[must_use]
+    pub const fn manufacture_year(&self) -> u16 {
+        1990 + self.0[17] as u16
+    }
+
+    /// Version and revision of the EDID structure, such as `(1, 4)`.
+    #[must_use]
+    pub const fn version(&self) -> (u8, u8) {
+        (self.0[18], self.0[19])
+    }
+
+    /// Whether the display has a digital input.
+    #[must_use]
+    pub const fn is_digital(&self) -> bool {
+        self.0[20] & 0x80 != 0
+    }
+
+    /// Width and height of the display in centimeters, if known.
+    #[must_use]
+    pub const fn physical_size_cm(&self) -> Option<(u8, u8)> {
+        match (self.0[21], self.0[22]) {
+            (0, _) | (_, 0) => None,
+            size => Some(size),
+        }
+    }
+
+    /// Number of extension blocks that follow the base block.
+    #[must_use]
+    pub const fn extension_count(&self) -> u8 {
+        self.0[126]
+    }
+
+    /// The detailed timing descriptors. The first is the preferred timing.
+    pub fn detailed_timings(&self) -> impl Iterator<Item = DetailedTiming> + '_ {
+        self.descriptors().filter_map(DetailedTiming::parse)
+    }
+
+    /// The preferred timing, which usually is the native mode of the
+    /// display.
+    #[must_use]
+    pub fn preferred_timing(&self) -> Option<DetailedTiming> {
+        self.descriptors().next().and_then(DetailedTiming::parse)
+    }
+
+    /// Native resolution of the display, from the preferred timing.
+    #[must_use]
+    pub fn native_resolution(&self) -> Option<(usize, usize)> {
+        self.preferred_timing().map(|timing| timing.resolution())
+    }
+
+    /// Name of the display, if the EDID has a display name descriptor.
+    #[must_use]
+    pub fn display_name(&self) -> Option<&str> {
+        let descriptor = self
+            .descriptors()
+            .find(|d| d[..3] == [0, 0, 0] && d[3] == DISPLAY_NAME_TAG)?;
+        // The name is terminated by a line feed if shorter than 13 bytes.
+        let name = &descriptor[5..];
+        let name = name.split(|&byte| byte == b'\n').next().unwrap_or(name);
+        core::str::from_utf8(name).ok().map(str::trim_end)
+    }
+
+    /// Find the graphics mode that best matches the native resolution of
+    /// the display.
+    ///
+    /// The native resolution itself is preferred. Otherwise, the largest
+    /// mode that fits in the native resolution is selected, preferring the
+    /// same aspect ratio. If no mode fits, the smallest mode is selected.
+    ///
+    /// Returns `None` if the EDID has no preferred timing or `gop` has no
+    /// modes.
+    #[must_use]
+    pub fn best_mode(&self, gop: &GraphicsOutput) -> Option<Mode> {
+        let native = self.native_resolution()?;
+        best_match(native, gop.modes(), |mode| mode.info().resolution())
+    }
+
+    fn descriptors(&self) -> impl Iterator<Item = &[u8]> {
+        self.0[DESCRIPTORS_OFFSET..DESCRIPTORS_OFFSET + 4 * DESCRIPTOR_SIZE]
+            .chunks_exact(DESCRIPTOR_SIZE)
+    }
+}
+
+/// Pick the item whose resolution best matches `native`. See
+/// [`Edid::best_mode`].
+fn best_match<T>(
+    native: (usize, usize),
+    items: impl Iterator<Item = T>,
+    resolution: impl Fn(&T) -> (usize, usize),
+) -> Option<T> {
+    let (native_width, native_height) = native;
+    // Items that fit rank above those that do not. Those that fit are
+    // ranked by aspect ratio, then size; the others by how small they are.
+    let rank = |(width, height): (usize, usize)| {
+        let area = width * height;
+        if width <= native_width && height <= native_height {
+            let same_aspect = width * native_height == height * native_width;
+            (true, same_aspect, area)
+        } else {
+            (false, false, usize::MAX - area)
+        }
+    };
+    items.max_by_key(|item| rank(resolution(item)))
+}
+
+/// A detailed timing descriptor, which describes a video mode.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct DetailedTiming {
+    /// Pixel clock in kHz.
+    pub pixel_clock_khz: u32,
+    /// Visible width in pixels.
+    pub horizontal_active: u16,
+    /// Width of the horizontal blanking interval in pixels.
+    pub horizontal_blanking: u16,
+    /// Visible height in lines. For interlaced modes, this is the height of
+    /// a field.
+    pub vertical_active: u16,
+    /// Height of the vertical blanking interval in lines.
+    pub vertical_blanking: u16,
+    /// Pixels between the end of the visible area and the sync pulse.
+    pub horizontal_front_porch: u16,
+    /// Width of the horizontal sync pulse in pixels.
+    pub horizontal_sync_width: u16,
+    /// Lines between the end of the visible area and the sync pulse.
+    pub vertical_front_porch: u16,
+    /// Height of the vertical sync pulse in lines.
+    pub vertical_sync_width: u16,
+    /// Width of the visible area in millimeters.
+    pub horizontal_size_mm: u16,
+    /// Height of the visible area in millimeters.
+    pub vertical_size_mm: u16,
+    /// Whether the mode is interlaced.
+    pub interlaced: bool,
+}
+
+impl DetailedTiming {
+    /// Parse an 18-byte descriptor, returning `None` if it is a display
+    /// descriptor rather than a timing.
+    fn parse(d: &[u8]) -> Option<Self> {
+        let pixel_clock = u16::from_le_bytes([d[0], d[1]]);
+        if pixel_clock == 0 {
+            return None;
+        }
+        // The low bits of each value are in their own byte, the high bits
+        // are packed into shared bytes.
+        let low_high = |low: u8, high: u8| u16::from(low) | (u16::from(high) << 8);
+        Some(Self {
+            pixel_clock_khz: u32::from(pixel_clock) * 10,
+            horizontal_active: low_high(d[2], d[4] >> 4),
+            horizontal_blanking: low_high(d[3], d[4] & 0x0f),
+            vertical_active: low_high(d[5], d[7] >> 4),
+            vertical_blanking: low_high(d[6], d[7] & 0x0f),
+            horizontal_front_porch: low_high(d[8], d[11] >> 6),
+            horizontal_sync_width: low_high(d[9], (d[11] >> 4) & 0x03),
+            vertical_front_porch: (u16::from(d[10]) >> 4) | (u16::from((d[11] >> 2) & 0x03) << 4),
+            vertical_sync_width: (u16::from(d[10]) & 0x0f) | (u16::from(d[11] & 0x03) << 4),
+            horizontal_size_mm: low_high(d[12], d[14] >> 4),
+            vertical_size_mm: low_high(d[13], d[14] & 0x0f),
+            interlaced: d[17] & 0x80 != 0,
+        })
+    }
+
+    /// Visible (width, height) in pixels. For interlaced modes, this is the
+    /// height of a frame.
+    #[must_use]
+    pub const fn resolution(&self) -> (usize, usize) {
+        let height = if self.interlaced {
+            self.vertical_active as usize * 2
+        } else {
+            self.vertical_active as usize
+        };
+        (self.horizontal_active as usize, height)
+    }
+
+    /// Refresh rate in millihertz.
+    #[must_use]
+    pub fn refresh_rate_mhz(&self) -> u32 {
+        let horizontal = u64::from(self.horizontal_active + self.horizontal_blanking);
+        let vertical = u64::from(self.vertical_active + self.vertical_blanking);
+        (u64::from(self.pixel_clock_khz) * 1_000_000)
+            .checked_div(horizontal * vertical)
+            .unwrap_or(0) as u32
+    }
+}
+
+/// Errors that can occur when parsing an EDID.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EdidError {
+    /// The data is shorter than an EDID block.
+    TooShort,
+
+    /// The data does not start with the EDID header.
+    InvalidHeader,
+
+    /// The checksum of the base block does not match.
+    ChecksumMismatch,
+}
+
+impl Display for EdidError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooShort => f.write_str("EDID too short"),
+            Self::InvalidHeader => f.write_str("invalid EDID header"),
+            Self::ChecksumMismatch => f.write_str("EDID checksum mismatch"),
+        }
+    }
+}
+
+impl core::error::Error for EdidError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build the EDID of a 27" 1920x1080 display.
+    fn build_edid() -> [u8; EDID_BLOCK_SIZE] {
+        let mut edid = [0; EDID_BLOCK_SIZE];
+        edid[..8].copy_from_slice(&HEADER);
+        // "DEL", product 0xa0c3, serial 12345, week 10 of 2020.
+        edid[8..18].copy_from_slice(&[0x10, 0xac, 0xc3, 0xa0, 0x39, 0x30, 0, 0, 10, 30]);
+        edid[18..23].copy_from_slice(&[1, 4, 0xa5, 60, 34]);
+        // 1920x1080 at 60 Hz, 600x340 mm.
+        edid[54..72].copy_from_slice(&[
+            0x02, 0x3a, 0x80, 0x18, 0x71, 0x38, 0x2d, 0x40, 0x58, 0x2c, 0x45, 0x00, 0x58, 0x54,
+            0x21, 0, 0, 0x1e,
+        ]);
+        edid[72..77].copy_from_slice(&[0, 0, 0, 0xfc, 0]);
+        edid[77..90].copy_from_slice(b"DELL U2720Q\n ");
+        edid[93] = 0x10;
+        edid[111] = 0x10;
+        edid[127] = 0_u8.wrapping_sub(edid.iter().fold(0_u8, |sum, &b| sum.wrapping_add(b)));
+        edid
+    }
+
+    #[test]
+    fn test_parse() {
+        let data = build_edid();
+        let edid = Edid::parse(&data).unwrap();
+        assert_eq!(&edid.manufacturer_id(), b"DEL");
+        assert_eq!(edid.product_code(), 0xa0c3);
+        assert_eq!(edid.serial_number(), 12345);
+        assert_eq!(edid.manufacture_year(), 2020);
+        assert_eq!(edid.version(), (1, 4));
+        assert!(edid.is_digital());
+        assert_eq!(edid.physical_size_cm(), Some((60, 34)));
+        assert_eq!(edid.extension_count(), 0);
+        assert_eq!(edid.display_name(), Some("DELL U2720Q"));
+        assert_eq!(edid.native_resolution(), Some((1920, 1080)));
+        assert_eq!(edid.detailed_timings().count(), 1);
+
+        let timing = edid.preferred_timing().unwrap();
+        assert_eq!(
+            timing,
+            DetailedTiming {
+                pixel_clock_khz: 148_500,
+                horizontal_active: 1920,
+                horizontal_blanking: 280,
+                vertical_active: 1080,
+                vertical_blanking: 45,
+                horizontal_front_porch: 88,
+                horizontal_sync_width: 44,
+                vertical_front_porch: 4,
+                vertical_sync_width: 5,
+                horizontal_size_mm: 600,
+                vertical_size_mm: 340,
+                interlaced: false,
+            }
+        );
+        assert_eq!(timing.refresh_rate_mhz(), 60_000);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let mut data = build_edid();
+        assert_eq!(Edid::parse(&data[..127]), Err(EdidError::TooShort));
+        assert_eq!(Edid::parse(&[]), Err(EdidError::TooShort));
+
+        data[100] ^= 1;
+        assert_eq!(Edid::parse(&data), Err(EdidError::ChecksumMismatch));
+
+        data[0] = 1;
+        assert_eq!(Edid::parse(&data), Err(EdidError::InvalidHeader));
+    }
+
+    #[test]
+    fn test_best_match() {
+        let resolution = |r: &(usize, usize)| *r;
+        let modes = [
+            (640, 480),
+            (1024, 768),
+            (1280, 720),
+            (1920, 1080),
+            (2560, 1440),
+        ];
+        let best = |native, modes: &[(usize, usize)]| {
+            best_match(native, modes.iter().copied(), resolution)
+        };
+
+        // The native resolution.
+        assert_eq!(best((1920, 1080), &modes), Some((1920, 1080)));
+        // Same aspect ratio rather than larger.
+        assert_eq!(best((1280, 960), &modes), Some((1024, 768)));
+        assert_eq!(best((1600, 900), &modes), Some((1280, 720)));
+        // The largest that fits.
+        assert_eq!(best((1280, 1024), &modes), Some((1280, 720)));
+        // The smallest if none fit.
+        assert_eq!(best((320, 200), &modes), Some((640, 480)));
+        assert_eq!(best((320, 200), &[]), None);
+    }
+}
@@ -451,7 +451,7 @@ impl ExactSizeIterator for ModeIter<'_> {}
 ///
 /// This is a BGR 24-bit format with an 8-bit padding, to keep each pixel 32-bit in size.
 #[allow(missing_docs)]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 #[repr(C)]
 pub struct BltPixel {
     pub blue: u8,
@@ -0,0 +1,249 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::proto::unsafe_protocol;
+use crate::{Event, Result, Status, StatusExt};
+use core::ops::RangeInclusive;
+use uefi_raw::protocol::console::{self as raw, AbsolutePointerProtocol};
+
+pub use uefi_raw::protocol::console::AbsolutePointerModeAttributes;
+
+/// Provides information about a pointer device with absolute
+/// coordinates, such as a touchscreen or a graphics tablet.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(AbsolutePointerProtocol::GUID)]
+pub struct AbsolutePointer(AbsolutePointerProtocol);
+
+impl AbsolutePointer {
+    /// Resets the pointer device hardware.
+    ///
+    /// # Arguments
+    /// The `extended_verification` parameter is used to request that UEFI
+    /// performs an extended check and reset of the input device.
+    ///
+    /// # Errors
+    /// - `DeviceError` if the device is malfunctioning and cannot be reset.
+    pub fn reset(&mut self, extended_verification: bool) -> Result {
+        unsafe { (self.0.reset)(&mut self.0, extended_verification.into()) }.to_result()
+    }
+
+    /// Retrieves the pointer device's current state, if a state change occurred
+    /// since the last time this function was called.
+    ///
+    /// Use `wait_for_input_event()` with the [`boot::wait_for_event`]
+    /// interface in order to wait for input from the pointer device.
+    ///
+    /// # Errors
+    /// - `DeviceError` if there was an issue with the pointer device.
+    ///
+    /// [`boot::wait_for_event`]: crate::boot::wait_for_event
+    pub fn read_state(&mut self) -> Result<Option<AbsolutePointerState>> {
+        let mut state = raw::AbsolutePointerState::default();
+
+        match unsafe { (self.0.get_state)(&self.0, &mut state) } {
+            Status::NOT_READY => Ok(None),
+            other => other.to_result_with_val(|| Some(state.into())),
+        }
+    }
+
+    /// Event to be used with [`boot::wait_for_event`] in order to wait
+    /// for input from the pointer device
+    ///
+    /// [`boot::wait_for_event`]: crate::boot::wait_for_event
+    #[must_use]
+    pub fn wait_for_input_event(&self) -> Option<Event> {
+        unsafe { Event::from_ptr(self.0.wait_for_input) }
+    }
+
+    /// Returns a reference to the pointer device information.
+    #[must_use]
+    pub const fn mode(&self) -> &AbsolutePointerMode {
+        unsafe { &*self.0.mode.cast() }
+    }
+}
+
+/// Information about an absolute pointer device.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+#[repr(transparent)]
+pub struct AbsolutePointerMode(raw::AbsolutePointerMode);
+
+impl AbsolutePointerMode {
+    /// Range of values reported on the X axis.
+    #[must_use]
+    pub const fn x_range(&self) -> RangeInclusive<u64> {
+        self.0.absolute_min_x..=self.0.absolute_max_x
+    }
+
+    /// Range of values reported on the Y axis.
+    #[must_use]
+    pub const fn y_range(&self) -> RangeInclusive<u64> {
+        self.0.absolute_min_y..=self.0.absolute_max_y
+    }
+
+    /// Range of values reported on the Z axis, or `None` if the device
+    /// does not support a Z axis.
+    ///
+    /// If [`supports_pressure_as_z`] is true, the Z axis reports the
+    /// pressure applied to the device.
+    ///
+    /// [`supports_pressure_as_z`]: Self::supports_pressure_as_z
+    #[must_use]
+    pub const fn z_range(&self) -> Option<RangeInclusive<u64>> {
+        if self.0.absolute_min_z == 0 && self.0.absolute_max_z == 0 {
+            None
+        } else {
+            Some(self.0.absolute_min_z..=self.0.absolute_max_z)
+        }
+    }
+
+    /// Attributes of the device.
+    #[must_use]
+    pub const fn attributes(&self) -> AbsolutePointerModeAttributes {
+        self.0.attributes
+    }
+
+    /// Whether the device has an alternate button, reported by
+    /// [`AbsolutePointerState::is_alt_active`].
+    #[must_use]
+    pub const fn supports_alt_active(&self) -> bool {
+        self.0
+            .attributes
+            .contains(AbsolutePointerModeAttributes::SUPPORTS_ALT_ACTIVE)
+    }
+
+    /// Whether the Z axis reports pressure.
+    #[must_use]
+    pub const fn supports_pressure_as_z(&self) -> bool {
+        self.0
+            .attributes
+            .contains(AbsolutePointerModeAttributes::SUPPORTS_PRESSURE_AS_Z)
+    }
+
+    /// Map the position in `state` to a point on a screen of `width` by
+    /// `height` pixels. Positions outside of the device's range are
+    /// clamped to the edges of the screen.
+    #[must_use]
+    pub fn to_screen(
+        &self,
+        state: &AbsolutePointerState,
+        width: usize,
+        height: usize,
+    ) -> (usize, usize) {
+        (
+            scale(state.current[0], self.x_range(), width),
+            scale(state.current[1], self.y_range(), height),
+        )
+    }
+}
+
+/// Scale `value` from `range` to `0..len`.
+fn scale(value: u64, range: RangeInclusive<u64>, len: usize) -> usize {
+    let (min, max) = range.into_inner();
+    if len == 0 || max <= min {
+        return 0;
+    }
+    let offset = u128::from(value.clamp(min, max) - min);
+    let span = u128::from(max - min);
+    ((offset * (len as u128 - 1) + span / 2) / span) as usize
+}
+
+/// The state of an absolute pointer device.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct AbsolutePointerState {
+    /// The position on the X/Y/Z axis, within the ranges reported by
+    /// [`AbsolutePointerMode`].
+    ///
+    /// If the device does not support the Z axis, its value must be
+    /// ignored.
+    pub current: [u64; 3],
+
+    /// Bitmask of the active buttons. See [`is_touch_active`] and
+    /// [`is_alt_active`].
+    ///
+    /// [`is_touch_active`]: Self::is_touch_active
+    /// [`is_alt_active`]: Self::is_alt_active
+    pub active_buttons: u32,
+}
+
+impl AbsolutePointerState {
+    /// Whether the device is being touched, or its primary button is
+    /// pressed.
+    #[must_use]
+    pub const fn is_touch_active(&self) -> bool {
+        self.active_buttons & 0x1 != 0
+    }
+
+    /// Whether the alternate button is pressed.
+    #[must_use]
+    pub const fn is_alt_active(&self) -> bool {
+        self.active_buttons & 0x2 != 0
+    }
+}
+
+impl From<raw::AbsolutePointerState> for AbsolutePointerState {
+    fn from(state: raw::AbsolutePointerState) -> Self {
+        Self {
+            current: [state.current_x, state.current_y, state.current_z],
+            active_buttons: state.active_buttons,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mode(max_z: u64) -> AbsolutePointerMode {
+        AbsolutePointerMode(raw::AbsolutePointerMode {
+            absolute_min_x: 0,
+            absolute_min_y: 100,
+            absolute_min_z: 0,
+            absolute_max_x: 0x7fff,
+            absolute_max_y: 200,
+            absolute_max_z: max_z,
+            attributes: AbsolutePointerModeAttributes::SUPPORTS_ALT_ACTIVE,
+        })
+    }
+
+    #[test]
+    fn test_mode() {
+        let mode = mode(0);
+        assert_eq!(mode.x_range(), 0..=0x7fff);
+        assert_eq!(mode.y_range(), 100..=200);
+        assert_eq!(mode.z_range(), None);
+        assert!(mode.supports_alt_active());
+        assert!(!mode.supports_pressure_as_z());
+
+        assert_eq!(self::mode(255).z_range(), Some(0..=255));
+    }
+
+    #[test]
+    fn test_to_screen() {
+        let mode = mode(0);
+        let state = |x, y| AbsolutePointerState {
+            current: [x, y, 0],
+            active_buttons: 1,
+        };
+        assert_eq!(mode.to_screen(&state(0, 100), 800, 600), (0, 0));
+        assert_eq!(mode.to_screen(&state(0x7fff, 200), 800, 600), (799, 599));
+        assert_eq!(mode.to_screen(&state(0x4000, 150), 800, 600), (400, 300));
+        // Out of range positions are clamped.
+        assert_eq!(mode.to_screen(&state(0, 0), 800, 600), (0, 0));
+        assert_eq!(mode.to_screen(&state(0, 1000), 800, 600), (0, 599));
+        // Degenerate screen sizes.
+        assert_eq!(mode.to_screen(&state(0x7fff, 200), 0, 1), (0, 0));
+    }
+
+    #[test]
+    fn test_state() {
+        let state = AbsolutePointerState::from(raw::AbsolutePointerState {
+            current_x: 1,
+            current_y: 2,
+            current_z: 3,
+            active_buttons: 2,
+        });
+        assert_eq!(state.current, [1, 2, 3]);
+        assert!(!state.is_touch_active());
+        assert!(state.is_alt_active());
+    }
+}
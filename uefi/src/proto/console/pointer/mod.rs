@@ -1,6 +1,14 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 //! Pointer device access.
+//!
+//! [`Pointer`] reports relative movement, like a mouse. [`AbsolutePointer`]
+//! reports absolute coordinates, like a touchscreen or graphics tablet.
+
+mod absolute;
+pub use absolute::{
+    AbsolutePointer, AbsolutePointerMode, AbsolutePointerModeAttributes, AbsolutePointerState,
+};
 
 use crate::proto::unsafe_protocol;
 use crate::{Event, Result, Status, StatusExt};
@@ -0,0 +1,140 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::proto::console::text::Color;
+use core::fmt::{self, Display, Formatter};
+
+/// ANSI/VT100 escape sequence to control a terminal on the other end of a
+/// serial port.
+///
+/// The [`Display`] impl writes the sequence, so it can be used with
+/// [`write!`] on any [`core::fmt::Write`], such as [`Serial`] or
+/// [`SerialTerminal`].
+///
+/// [`Serial`]: super::Serial
+/// [`SerialTerminal`]: super::SerialTerminal
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Escape {
+    /// Move the cursor to a zero-based position.
+    CursorPosition {
+        /// Row, from the top.
+        row: u16,
+        /// Column, from the left.
+        column: u16,
+    },
+    /// Move the cursor up by a number of rows.
+    CursorUp(u16),
+    /// Move the cursor down by a number of rows.
+    CursorDown(u16),
+    /// Move the cursor right by a number of columns.
+    CursorForward(u16),
+    /// Move the cursor left by a number of columns.
+    CursorBack(u16),
+    /// Save the cursor position.
+    SaveCursor,
+    /// Restore the cursor position saved by [`Escape::SaveCursor`].
+    RestoreCursor,
+    /// Show or hide the cursor.
+    ShowCursor(bool),
+    /// Clear the screen and move the cursor to the upper left corner.
+    ClearScreen,
+    /// Clear the line of the cursor.
+    ClearLine,
+    /// Set the foreground and background colors.
+    ///
+    /// Terminals show the bright colors (from [`Color::DarkGray`] on) as
+    /// background colors too, unlike the UEFI console.
+    SetColors {
+        /// Color of the text.
+        foreground: Color,
+        /// Color behind the text.
+        background: Color,
+    },
+    /// Reset the colors and other attributes to the terminal's defaults.
+    ResetAttributes,
+}
+
+impl Display for Escape {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            // Positions on the terminal are one-based.
+            Self::CursorPosition { row, column } => {
+                write!(f, "\x1b[{};{}H", u32::from(row) + 1, u32::from(column) + 1)
+            }
+            Self::CursorUp(n) => write!(f, "\x1b[{n}A"),
+            Self::CursorDown(n) => write!(f, "\x1b[{n}B"),
+            Self::CursorForward(n) => write!(f, "\x1b[{n}C"),
+            Self::CursorBack(n) => write!(f, "\x1b[{n}D"),
+            Self::SaveCursor => f.write_str("\x1b7"),
+            Self::RestoreCursor => f.write_str("\x1b8"),
+            Self::ShowCursor(true) => f.write_str("\x1b[?25h"),
+            Self::ShowCursor(false) => f.write_str("\x1b[?25l"),
+            Self::ClearScreen => f.write_str("\x1b[2J\x1b[H"),
+            Self::ClearLine => f.write_str("\x1b[2K"),
+            Self::SetColors {
+                foreground,
+                background,
+            } => write!(
+                f,
+                "\x1b[{};{}m",
+                color_code(foreground),
+                color_code(background) + 10
+            ),
+            Self::ResetAttributes => f.write_str("\x1b[0m"),
+        }
+    }
+}
+
+/// SGR code of a foreground color. Add 10 for the background color.
+const fn color_code(color: Color) -> u8 {
+    // ANSI orders the colors red, green, blue by bit, UEFI blue, green, red.
+    let (base, bright) = match color {
+        Color::Black => (0, false),
+        Color::Red => (1, false),
+        Color::Green => (2, false),
+        Color::Brown => (3, false),
+        Color::Blue => (4, false),
+        Color::Magenta => (5, false),
+        Color::Cyan => (6, false),
+        Color::LightGray => (7, false),
+        Color::DarkGray => (0, true),
+        Color::LightRed => (1, true),
+        Color::LightGreen => (2, true),
+        Color::Yellow => (3, true),
+        Color::LightBlue => (4, true),
+        Color::LightMagenta => (5, true),
+        Color::LightCyan => (6, true),
+        Color::White => (7, true),
+    };
+    if bright {
+        90 + base
+    } else {
+        30 + base
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_escape() {
+        let position = Escape::CursorPosition { row: 0, column: 9 };
+        assert_eq!(position.to_string(), "\x1b[1;10H");
+        assert_eq!(Escape::CursorUp(3).to_string(), "\x1b[3A");
+        assert_eq!(Escape::CursorBack(1).to_string(), "\x1b[1D");
+        assert_eq!(Escape::ShowCursor(false).to_string(), "\x1b[?25l");
+        assert_eq!(Escape::ClearScreen.to_string(), "\x1b[2J\x1b[H");
+
+        let colors = Escape::SetColors {
+            foreground: Color::Yellow,
+            background: Color::Blue,
+        };
+        assert_eq!(colors.to_string(), "\x1b[93;44m");
+        let colors = Escape::SetColors {
+            foreground: Color::Brown,
+            background: Color::White,
+        };
+        assert_eq!(colors.to_string(), "\x1b[33;107m");
+    }
+}
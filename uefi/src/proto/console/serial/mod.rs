@@ -1,12 +1,23 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 //! Abstraction over byte stream devices, also known as serial I/O devices.
+//!
+//! [`Serial`] gives direct access to the device. [`SerialTerminal`] builds
+//! on it for talking to a terminal on the other end: reads with timeouts,
+//! line editing, [ANSI escape sequences][Escape], and XMODEM/YMODEM file
+//! transfers.
 
 use crate::proto::unsafe_protocol;
 use crate::{Result, StatusExt};
 use core::fmt::Write;
 use uefi_raw::protocol::console::serial::SerialIoProtocol;
 
+mod ansi;
+mod terminal;
+mod xmodem;
+
+pub use ansi::Escape;
+pub use terminal::SerialTerminal;
 pub use uefi_raw::protocol::console::serial::{
     ControlBits, Parity, SerialIoMode as IoMode, StopBits,
 };
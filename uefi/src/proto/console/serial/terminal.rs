@@ -0,0 +1,473 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::{xmodem, Escape, IoMode, Serial};
+use crate::{io, Result, Status};
+use core::fmt::{self, Write as _};
+use core::time::Duration;
+
+/// How long a read waits before checking again while waiting for a line.
+const LINE_POLL_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Byte stream with a timeout on reads. Implemented for serial devices, and
+/// by fakes in tests.
+pub(super) trait Port {
+    /// Read into `buf`, waiting at most `timeout` for each byte. Returns the
+    /// number of bytes read, which is less than `buf.len()` if the timeout
+    /// expired.
+    fn read(&mut self, buf: &mut [u8], timeout: Duration) -> Result<usize>;
+
+    /// Write all of `data`.
+    fn write(&mut self, data: &[u8]) -> Result;
+}
+
+/// [`Serial`] device with the timeout that was last set.
+#[derive(Debug)]
+struct SerialPort<'a> {
+    serial: &'a mut Serial,
+    /// Timeout in microseconds.
+    timeout: u32,
+}
+
+impl Port for SerialPort<'_> {
+    fn read(&mut self, buf: &mut [u8], timeout: Duration) -> Result<usize> {
+        let timeout = u32::try_from(timeout.as_micros())
+            .unwrap_or(u32::MAX)
+            .max(1);
+        if timeout != self.timeout {
+            let mode = IoMode {
+                timeout,
+                ..*self.serial.io_mode()
+            };
+            self.serial.set_attributes(&mode)?;
+            self.timeout = timeout;
+        }
+        match self.serial.read(buf) {
+            Ok(()) => Ok(buf.len()),
+            Err(err) if err.status() == Status::TIMEOUT => Ok(*err.data()),
+            Err(err) => Err(err.to_err_without_payload()),
+        }
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result {
+        self.serial
+            .write(data)
+            .map_err(|err| err.to_err_without_payload())
+    }
+}
+
+/// Terminal on the other end of a [`Serial`] device.
+///
+/// Adds reads with a timeout, reading lines with echo and line editing,
+/// [`Escape`] sequences to control the terminal, and file transfer with
+/// XMODEM and YMODEM.
+///
+/// Reads return as soon as data is available. The terminal sets the receive
+/// FIFO depth of the device to one byte for this, and sets the timeout of
+/// the device through [`Serial::set_attributes`] as needed.
+///
+/// ```no_run
+/// use core::fmt::Write;
+/// use uefi::boot;
+/// use uefi::proto::console::serial::{Escape, Serial, SerialTerminal};
+/// use uefi::proto::console::text::Color;
+///
+/// # fn main() -> uefi::Result {
+/// let handle = boot::get_handle_for_protocol::<Serial>()?;
+/// let mut serial = boot::open_protocol_exclusive::<Serial>(handle)?;
+/// let mut terminal = SerialTerminal::new(&mut serial)?;
+///
+/// terminal.write_escape(Escape::SetColors {
+///     foreground: Color::Yellow,
+///     background: Color::Black,
+/// })?;
+/// write!(terminal, "Name: ").unwrap();
+/// let mut name = [0; 64];
+/// let len = terminal.read_line(&mut name)?;
+///
+/// // Receive a file from the terminal program.
+/// let mut file = [0; 4096];
+/// let size = terminal.xmodem_receive(&mut &mut file[..])?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SerialTerminal<'a> {
+    port: SerialPort<'a>,
+    echo: bool,
+    /// A line ended with CR, so a following LF is part of the line end.
+    skip_lf: bool,
+}
+
+impl<'a> SerialTerminal<'a> {
+    /// Create a terminal on `serial`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of [`Serial::set_attributes`] if the receive FIFO
+    /// depth cannot be set.
+    pub fn new(serial: &'a mut Serial) -> Result<Self> {
+        let mode = IoMode {
+            receive_fifo_depth: 1,
+            ..*serial.io_mode()
+        };
+        serial.set_attributes(&mode)?;
+        let timeout = serial.io_mode().timeout;
+        Ok(Self {
+            port: SerialPort { serial, timeout },
+            echo: true,
+            skip_lf: false,
+        })
+    }
+
+    /// The serial device.
+    pub fn serial(&mut self) -> &mut Serial {
+        self.port.serial
+    }
+
+    /// Set whether [`read_line`] echoes the input back to the terminal.
+    /// Enabled by default.
+    ///
+    /// [`read_line`]: Self::read_line
+    pub const fn set_echo(&mut self, echo: bool) {
+        self.echo = echo;
+    }
+
+    /// Read into `buf`, waiting at most `timeout` for each byte. Returns the
+    /// number of bytes read, which is less than `buf.len()` if the timeout
+    /// expired.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Serial::read`] other than
+    /// [`Status::TIMEOUT`].
+    pub fn read(&mut self, buf: &mut [u8], timeout: Duration) -> Result<usize> {
+        self.port.read(buf, timeout)
+    }
+
+    /// Read a byte, waiting at most `timeout`. Returns `None` if the timeout
+    /// expired.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Serial::read`] other than
+    /// [`Status::TIMEOUT`].
+    pub fn read_byte(&mut self, timeout: Duration) -> Result<Option<u8>> {
+        let mut byte = [0];
+        let len = self.port.read(&mut byte, timeout)?;
+        Ok((len == 1).then_some(byte[0]))
+    }
+
+    /// Write all of `data`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Serial::write`].
+    pub fn write(&mut self, data: &[u8]) -> Result {
+        self.port.write(data)
+    }
+
+    /// Write an escape sequence.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Serial::write`].
+    pub fn write_escape(&mut self, escape: Escape) -> Result {
+        let mut writer = FmtWriter {
+            port: &mut self.port,
+            result: Ok(()),
+        };
+        // Errors are reported in `result`.
+        let _ = write!(writer, "{escape}");
+        writer.result
+    }
+
+    /// Read a line into `buf`, waiting as long as it takes. Returns the
+    /// length of the line.
+    ///
+    /// The line ends with CR, LF or CRLF, which is not included. Backspace
+    /// removes the last byte, and escape sequences, such as those of the
+    /// arrow keys, are ignored. Input that does not fit in `buf` is dropped.
+    /// If echo is enabled, the line is echoed back to the terminal.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Serial::read`] other than
+    /// [`Status::TIMEOUT`], and of [`Serial::write`].
+    pub fn read_line(&mut self, buf: &mut [u8]) -> Result<usize> {
+        read_line(&mut self.port, buf, self.echo, &mut self.skip_lf)
+    }
+
+    /// Send a file with XMODEM, using 128-byte blocks and CRC-16 or checksums
+    /// as requested by the receiver.
+    ///
+    /// The last block is padded with `0x1a` (SUB) bytes, which the receiver
+    /// cannot tell apart from the data.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::ABORTED`]: the receiver cancelled the transfer.
+    /// * [`Status::TIMEOUT`]: the receiver did not start or acknowledge a
+    ///   block after repeated attempts.
+    /// * The errors of `data`, and of [`Serial::read`] and
+    ///   [`Serial::write`].
+    ///
+    /// The transfer is cancelled on all errors.
+    pub fn xmodem_send(&mut self, data: &mut impl io::Read) -> Result {
+        xmodem::xmodem_send(&mut self.port, data)
+    }
+
+    /// Receive a file with XMODEM, writing it to `out`. Returns the number
+    /// of bytes received, which includes the padding of the last block.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::ABORTED`]: the sender cancelled the transfer.
+    /// * [`Status::TIMEOUT`]: the sender did not start or send a valid
+    ///   block after repeated attempts.
+    /// * [`Status::PROTOCOL_ERROR`]: a block was skipped.
+    /// * The errors of `out`, and of [`Serial::read`] and
+    ///   [`Serial::write`].
+    ///
+    /// The transfer is cancelled on all errors.
+    pub fn xmodem_receive(&mut self, out: &mut impl io::Write) -> Result<usize> {
+        xmodem::xmodem_receive(&mut self.port, out)
+    }
+
+    /// Send a file with YMODEM, using 1024-byte blocks. `size` bytes are
+    /// read from `data`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `name` is empty, contains a null
+    ///   byte or is longer than about 100 bytes.
+    /// * [`Status::END_OF_FILE`]: `data` ends before `size` bytes.
+    /// * The errors of [`xmodem_send`].
+    ///
+    /// [`xmodem_send`]: Self::xmodem_send
+    pub fn ymodem_send(&mut self, name: &str, size: u64, data: &mut impl io::Read) -> Result {
+        xmodem::ymodem_send(&mut self.port, name, size, data)
+    }
+
+    /// Receive a batch of files with YMODEM. Returns the number of files.
+    ///
+    /// For each file, `open` is called with the name and size that the
+    /// sender provided, and returns the writer for the contents.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::PROTOCOL_ERROR`]: the sender did not send a valid file
+    ///   header, or a block was skipped.
+    /// * The errors of `open`, and of [`xmodem_receive`].
+    ///
+    /// [`xmodem_receive`]: Self::xmodem_receive
+    pub fn ymodem_receive<W: io::Write>(
+        &mut self,
+        open: impl FnMut(&str, Option<u64>) -> Result<W>,
+    ) -> Result<usize> {
+        xmodem::ymodem_receive(&mut self.port, open)
+    }
+}
+
+impl fmt::Write for SerialTerminal<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.port.write(s.as_bytes()).map_err(|_| fmt::Error)
+    }
+}
+
+impl io::Write for SerialTerminal<'_> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.port.write(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result {
+        Ok(())
+    }
+}
+
+/// Adapter to write formatted text to a port, keeping the error.
+struct FmtWriter<'a, P: Port> {
+    port: &'a mut P,
+    result: Result,
+}
+
+impl<P: Port> fmt::Write for FmtWriter<'_, P> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.result = self.port.write(s.as_bytes());
+        self.result.clone().map_err(|_| fmt::Error)
+    }
+}
+
+/// Progress through an escape sequence in the input.
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum EscapeState {
+    None,
+    /// After ESC.
+    Start,
+    /// In a control sequence (`ESC [`), which ends with a byte in
+    /// `0x40..=0x7e`.
+    Control,
+    /// After `ESC O`, which is followed by a single byte.
+    SingleShift,
+}
+
+/// See [`SerialTerminal::read_line`].
+fn read_line<P: Port>(
+    port: &mut P,
+    buf: &mut [u8],
+    echo: bool,
+    skip_lf: &mut bool,
+) -> Result<usize> {
+    let mut len = 0;
+    let mut escape = EscapeState::None;
+    loop {
+        let mut byte = [0];
+        if port.read(&mut byte, LINE_POLL_TIMEOUT)? == 0 {
+            continue;
+        }
+        let byte = byte[0];
+        let after_cr = core::mem::take(skip_lf);
+
+        match escape {
+            EscapeState::None => {}
+            EscapeState::Start => {
+                escape = match byte {
+                    b'[' => EscapeState::Control,
+                    b'O' => EscapeState::SingleShift,
+                    _ => EscapeState::None,
+                };
+                continue;
+            }
+            EscapeState::Control => {
+                if (0x40..=0x7e).contains(&byte) {
+                    escape = EscapeState::None;
+                }
+                continue;
+            }
+            EscapeState::SingleShift => {
+                escape = EscapeState::None;
+                continue;
+            }
+        }
+
+        match byte {
+            b'\r' => {
+                *skip_lf = true;
+                break;
+            }
+            b'\n' if after_cr => {}
+            b'\n' => break,
+            // Backspace and delete.
+            0x08 | 0x7f => {
+                if len > 0 {
+                    len -= 1;
+                    if echo {
+                        port.write(b"\x08 \x08")?;
+                    }
+                }
+            }
+            0x1b => escape = EscapeState::Start,
+            _ if byte < 0x20 => {}
+            _ if len < buf.len() => {
+                buf[len] = byte;
+                len += 1;
+                if echo {
+                    port.write(&[byte])?;
+                }
+            }
+            // Ring the bell if the line is full.
+            _ if echo => port.write(b"\x07")?,
+            _ => {}
+        }
+    }
+    if echo {
+        port.write(b"\r\n")?;
+    }
+    Ok(len)
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use alloc::collections::VecDeque;
+    use alloc::vec::Vec;
+
+    /// Port that reads from a list of chunks and records what is written.
+    /// Reading stops at the end of each chunk, as if the timeout expired.
+    pub(in super::super) struct FakePort {
+        input: VecDeque<Vec<u8>>,
+        pub(in super::super) output: Vec<u8>,
+    }
+
+    impl FakePort {
+        pub(in super::super) fn new(chunks: &[&[u8]]) -> Self {
+            Self {
+                input: chunks.iter().map(|chunk| chunk.to_vec()).collect(),
+                output: Vec::new(),
+            }
+        }
+    }
+
+    impl Port for FakePort {
+        fn read(&mut self, buf: &mut [u8], _timeout: Duration) -> Result<usize> {
+            // Fail rather than wait forever once all input has been read.
+            let chunk = self.input.front_mut().ok_or(Status::DEVICE_ERROR)?;
+            if chunk.is_empty() {
+                self.input.pop_front();
+                return Ok(0);
+            }
+            let len = buf.len().min(chunk.len());
+            buf[..len].copy_from_slice(&chunk[..len]);
+            chunk.drain(..len);
+            Ok(len)
+        }
+
+        fn write(&mut self, data: &[u8]) -> Result {
+            self.output.extend_from_slice(data);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_read_line() {
+        let mut port = FakePort::new(&[b"ab\x7fc\r\n", b"x\x1b[Dy\x1bOAz\n"]);
+        let mut buf = [0; 8];
+        let mut skip_lf = false;
+
+        let len = read_line(&mut port, &mut buf, true, &mut skip_lf).unwrap();
+        assert_eq!(&buf[..len], b"ac");
+        assert_eq!(port.output, b"ab\x08 \x08c\r\n");
+
+        // The LF after the CR is skipped.
+        port.output.clear();
+        let len = read_line(&mut port, &mut buf, false, &mut skip_lf).unwrap();
+        assert_eq!(&buf[..len], b"xyz");
+        assert_eq!(port.output, b"");
+    }
+
+    #[test]
+    fn test_read_line_full() {
+        let mut port = FakePort::new(&[b"abcd\r"]);
+        let mut buf = [0; 3];
+        let len = read_line(&mut port, &mut buf, true, &mut false).unwrap();
+        assert_eq!(&buf[..len], b"abc");
+        assert_eq!(port.output, b"abc\x07\r\n");
+
+        assert_eq!(
+            read_line(&mut port, &mut buf, true, &mut false),
+            Err(Status::DEVICE_ERROR.into())
+        );
+    }
+
+    #[test]
+    fn test_write_escape() {
+        let mut port = FakePort::new(&[]);
+        let mut writer = FmtWriter {
+            port: &mut port,
+            result: Ok(()),
+        };
+        write!(writer, "{}", Escape::ClearLine).unwrap();
+        assert_eq!(writer.result, Ok(()));
+        assert_eq!(port.output, b"\x1b[2K");
+    }
+}
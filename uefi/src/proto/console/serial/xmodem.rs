@@ -0,0 +1,648 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! XMODEM and YMODEM file transfer.
+//!
+//! Blocks start with SOH (128 bytes of data) or STX (1024 bytes), followed
+//! by the block number, its complement, the data, and an 8-bit sum or a
+//! CRC-16. The receiver acknowledges each block with ACK or asks for it
+//! again with NAK, and the sender ends the file with EOT. YMODEM adds a
+//! block 0 before each file with its name and size, and ends a batch with
+//! an empty block 0.
+
+use super::terminal::Port;
+use crate::{io, Result, Status};
+use core::time::Duration;
+
+const SOH: u8 = 0x01;
+const STX: u8 = 0x02;
+const EOT: u8 = 0x04;
+const ACK: u8 = 0x06;
+const NAK: u8 = 0x15;
+const CAN: u8 = 0x18;
+const SUB: u8 = 0x1a;
+/// Sent instead of NAK to start a transfer with CRC-16.
+const CRC_START: u8 = b'C';
+
+const SMALL_BLOCK: usize = 128;
+const LARGE_BLOCK: usize = 1024;
+
+/// Attempts to send or receive a block before giving up.
+const MAX_RETRIES: usize = 10;
+/// Attempts of the receiver to start with CRC-16 before falling back to
+/// checksums.
+const CRC_ATTEMPTS: usize = 3;
+/// Seconds for which the sender waits for the receiver to start.
+const START_ATTEMPTS: usize = 60;
+
+/// Time to wait for the start of a block or a response.
+const BLOCK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Time between the receiver's requests to start.
+const START_TIMEOUT: Duration = Duration::from_secs(3);
+/// Time to wait for each byte inside a block.
+const BYTE_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Checksum {
+    /// Sum of the data bytes.
+    Sum,
+    /// CRC-16 with polynomial 0x1021, big-endian.
+    Crc,
+}
+
+impl Checksum {
+    const fn len(self) -> usize {
+        match self {
+            Self::Sum => 1,
+            Self::Crc => 2,
+        }
+    }
+
+    /// Byte sent by the receiver to start a transfer.
+    const fn start_byte(self) -> u8 {
+        match self {
+            Self::Sum => NAK,
+            Self::Crc => CRC_START,
+        }
+    }
+
+    /// Write the checksum of `data` to `out`, which is `self.len()` bytes.
+    fn write(self, data: &[u8], out: &mut [u8]) {
+        match self {
+            Self::Sum => out[0] = data.iter().fold(0, |sum, &byte| sum.wrapping_add(byte)),
+            Self::Crc => out.copy_from_slice(&crc16(data).to_be_bytes()),
+        }
+    }
+}
+
+/// CRC-16/XMODEM.
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0_u16;
+    for &byte in data {
+        crc ^= u16::from(byte) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn read_byte<P: Port>(port: &mut P, timeout: Duration) -> Result<Option<u8>> {
+    let mut byte = [0];
+    Ok((port.read(&mut byte, timeout)? == 1).then_some(byte[0]))
+}
+
+/// Discard input until the line is quiet.
+fn purge<P: Port>(port: &mut P) -> Result {
+    let mut buf = [0; 64];
+    while port.read(&mut buf, BYTE_TIMEOUT)? > 0 {}
+    Ok(())
+}
+
+/// Cancel the transfer if `result` is an error, unless the other side
+/// cancelled it.
+fn cancel_on_error<P: Port, T>(port: &mut P, result: Result<T>) -> Result<T> {
+    if let Err(err) = &result {
+        if err.status() != Status::ABORTED {
+            // The transfer failed already, so ignore errors.
+            let _ = port.write(&[CAN, CAN]);
+        }
+    }
+    result
+}
+
+/// Read a response of the other side. A timeout counts as NAK.
+///
+/// Returns [`Status::ABORTED`] if the other side cancels.
+fn read_response<P: Port>(port: &mut P, timeout: Duration) -> Result<u8> {
+    let mut cancel = false;
+    loop {
+        match read_byte(port, timeout)? {
+            None => return Ok(NAK),
+            Some(CAN) if cancel => return Err(Status::ABORTED.into()),
+            Some(CAN) => cancel = true,
+            Some(byte @ (ACK | NAK | CRC_START)) => return Ok(byte),
+            // Ignore noise.
+            Some(_) => cancel = false,
+        }
+    }
+}
+
+/// Wait for the receiver to start the transfer, returning the checksum it
+/// asked for.
+fn wait_for_start<P: Port>(port: &mut P) -> Result<Checksum> {
+    let mut cancel = false;
+    for _ in 0..START_ATTEMPTS {
+        match read_byte(port, BYTE_TIMEOUT)? {
+            Some(CRC_START) => return Ok(Checksum::Crc),
+            Some(NAK) => return Ok(Checksum::Sum),
+            Some(CAN) if cancel => return Err(Status::ABORTED.into()),
+            Some(CAN) => cancel = true,
+            // Ignore noise.
+            Some(_) | None => cancel = false,
+        }
+    }
+    Err(Status::TIMEOUT.into())
+}
+
+/// Send a block, retrying until the receiver acknowledges it.
+fn send_block<P: Port>(port: &mut P, number: u8, data: &[u8], checksum: Checksum) -> Result {
+    let mut packet = [0; 3 + LARGE_BLOCK + 2];
+    packet[0] = if data.len() == SMALL_BLOCK { SOH } else { STX };
+    packet[1] = number;
+    packet[2] = !number;
+    packet[3..3 + data.len()].copy_from_slice(data);
+    let end = 3 + data.len();
+    checksum.write(data, &mut packet[end..end + checksum.len()]);
+    let packet = &packet[..end + checksum.len()];
+
+    for _ in 0..MAX_RETRIES {
+        port.write(packet)?;
+        if read_response(port, BLOCK_TIMEOUT)? == ACK {
+            return Ok(());
+        }
+    }
+    Err(Status::TIMEOUT.into())
+}
+
+/// Send the data from `reader` in numbered blocks starting at 1, then EOT.
+/// If `size` is set, exactly that many bytes are sent.
+fn send_data<P: Port>(
+    port: &mut P,
+    reader: &mut impl io::Read,
+    block_size: usize,
+    checksum: Checksum,
+    size: Option<u64>,
+) -> Result {
+    let mut remaining = size.unwrap_or(u64::MAX);
+    let mut number = 1_u8;
+    loop {
+        let mut block = [SUB; LARGE_BLOCK];
+        let want = block_size.min(usize::try_from(remaining).unwrap_or(usize::MAX));
+        let mut len = 0;
+        while len < want {
+            match reader.read(&mut block[len..want])? {
+                0 => break,
+                n => len += n,
+            }
+        }
+        if len < want && size.is_some() {
+            return Err(Status::END_OF_FILE.into());
+        }
+        if len == 0 {
+            break;
+        }
+        // Use a small block for the end of the data if it fits.
+        let block_len = if len <= SMALL_BLOCK {
+            SMALL_BLOCK
+        } else {
+            block_size
+        };
+        send_block(port, number, &block[..block_len], checksum)?;
+        number = number.wrapping_add(1);
+        remaining -= len as u64;
+        if len < block_size {
+            break;
+        }
+    }
+
+    for _ in 0..MAX_RETRIES {
+        port.write(&[EOT])?;
+        if read_response(port, BLOCK_TIMEOUT)? == ACK {
+            return Ok(());
+        }
+    }
+    Err(Status::TIMEOUT.into())
+}
+
+/// See [`SerialTerminal::xmodem_send`].
+///
+/// [`SerialTerminal::xmodem_send`]: super::SerialTerminal::xmodem_send
+pub(super) fn xmodem_send<P: Port>(port: &mut P, data: &mut impl io::Read) -> Result {
+    let result = wait_for_start(port)
+        .and_then(|checksum| send_data(port, data, SMALL_BLOCK, checksum, None));
+    cancel_on_error(port, result)
+}
+
+/// See [`SerialTerminal::ymodem_send`].
+///
+/// [`SerialTerminal::ymodem_send`]: super::SerialTerminal::ymodem_send
+pub(super) fn ymodem_send<P: Port>(
+    port: &mut P,
+    name: &str,
+    size: u64,
+    data: &mut impl io::Read,
+) -> Result {
+    // The name, a null byte and the size in decimal.
+    let mut header = [0; SMALL_BLOCK];
+    let mut digits = [0; 20];
+    let digits = format_decimal(size, &mut digits);
+    let name = name.as_bytes();
+    if name.is_empty() || name.contains(&0) || name.len() + 1 + digits.len() > SMALL_BLOCK {
+        return Err(Status::INVALID_PARAMETER.into());
+    }
+    header[..name.len()].copy_from_slice(name);
+    header[name.len() + 1..name.len() + 1 + digits.len()].copy_from_slice(digits);
+
+    let result = (|| {
+        let checksum = wait_for_start(port)?;
+        send_block(port, 0, &header, checksum)?;
+        let checksum = wait_for_start(port)?;
+        send_data(port, data, LARGE_BLOCK, checksum, Some(size))?;
+        // An empty header ends the batch.
+        let checksum = wait_for_start(port)?;
+        send_block(port, 0, &[0; SMALL_BLOCK], checksum)
+    })();
+    cancel_on_error(port, result)
+}
+
+fn format_decimal(mut value: u64, buf: &mut [u8; 20]) -> &[u8] {
+    let mut start = buf.len();
+    loop {
+        start -= 1;
+        buf[start] = b'0' + (value % 10) as u8;
+        value /= 10;
+        if value == 0 {
+            return &buf[start..];
+        }
+    }
+}
+
+/// A block or other packet read by the receiver.
+#[derive(Debug, Eq, PartialEq)]
+enum Packet {
+    Block {
+        number: u8,
+        len: usize,
+    },
+    Eot,
+    /// Nothing arrived in time.
+    Timeout,
+    /// A block with a wrong checksum, or noise.
+    Invalid,
+}
+
+/// Read a packet, with block data going into `buf`.
+fn read_packet<P: Port>(
+    port: &mut P,
+    checksum: Checksum,
+    timeout: Duration,
+    buf: &mut [u8; LARGE_BLOCK],
+) -> Result<Packet> {
+    let len = match read_byte(port, timeout)? {
+        None => return Ok(Packet::Timeout),
+        Some(SOH) => SMALL_BLOCK,
+        Some(STX) => LARGE_BLOCK,
+        Some(EOT) => return Ok(Packet::Eot),
+        Some(CAN) => {
+            return match read_byte(port, BYTE_TIMEOUT)? {
+                Some(CAN) => Err(Status::ABORTED.into()),
+                _ => Ok(Packet::Invalid),
+            };
+        }
+        Some(_) => return Ok(Packet::Invalid),
+    };
+
+    let mut number = [0; 2];
+    let mut expected = [0; 2];
+    let mut actual = [0; 2];
+    let actual = &mut actual[..checksum.len()];
+    if port.read(&mut number, BYTE_TIMEOUT)? < 2
+        || port.read(&mut buf[..len], BYTE_TIMEOUT)? < len
+        || port.read(actual, BYTE_TIMEOUT)? < checksum.len()
+    {
+        return Ok(Packet::Invalid);
+    }
+    checksum.write(&buf[..len], &mut expected[..checksum.len()]);
+    if number[0] != !number[1] || *actual != expected[..checksum.len()] {
+        return Ok(Packet::Invalid);
+    }
+    Ok(Packet::Block {
+        number: number[0],
+        len,
+    })
+}
+
+/// Ask the sender to start until it sends a packet. Checksums are used if
+/// the sender does not react to the requests for CRC-16 and `allow_sum`
+/// is set.
+fn request_start<P: Port>(
+    port: &mut P,
+    allow_sum: bool,
+    buf: &mut [u8; LARGE_BLOCK],
+) -> Result<(Checksum, Packet)> {
+    for attempt in 0..MAX_RETRIES {
+        let checksum = if attempt < CRC_ATTEMPTS || !allow_sum {
+            Checksum::Crc
+        } else {
+            Checksum::Sum
+        };
+        port.write(&[checksum.start_byte()])?;
+        match read_packet(port, checksum, START_TIMEOUT, buf)? {
+            Packet::Timeout => {}
+            Packet::Invalid => purge(port)?,
+            packet => return Ok((checksum, packet)),
+        }
+    }
+    Err(Status::TIMEOUT.into())
+}
+
+/// Receive numbered blocks starting at 1 until EOT, beginning with `packet`,
+/// passing the data to `sink`.
+///
+/// If `nak_first_eot` is set, the first EOT is answered with NAK, so that
+/// the sender sends it again, as YMODEM does.
+fn receive_data<P: Port>(
+    port: &mut P,
+    checksum: Checksum,
+    mut packet: Packet,
+    buf: &mut [u8; LARGE_BLOCK],
+    nak_first_eot: bool,
+    mut sink: impl FnMut(&[u8]) -> Result,
+) -> Result {
+    let mut expected = 1_u8;
+    let mut errors = 0;
+    let mut eot_seen = false;
+    loop {
+        match packet {
+            Packet::Block { number, len } if number == expected => {
+                sink(&buf[..len])?;
+                port.write(&[ACK])?;
+                expected = expected.wrapping_add(1);
+                errors = 0;
+            }
+            // The ACK of the previous block was lost.
+            Packet::Block { number, .. } if number == expected.wrapping_sub(1) => {
+                port.write(&[ACK])?;
+            }
+            Packet::Block { .. } => return Err(Status::PROTOCOL_ERROR.into()),
+            Packet::Eot if nak_first_eot && !eot_seen => {
+                eot_seen = true;
+                port.write(&[NAK])?;
+            }
+            Packet::Eot => {
+                port.write(&[ACK])?;
+                return Ok(());
+            }
+            Packet::Timeout | Packet::Invalid => {
+                errors += 1;
+                if errors > MAX_RETRIES {
+                    return Err(Status::TIMEOUT.into());
+                }
+                purge(port)?;
+                port.write(&[NAK])?;
+            }
+        }
+        packet = read_packet(port, checksum, BLOCK_TIMEOUT, buf)?;
+    }
+}
+
+/// See [`SerialTerminal::xmodem_receive`].
+///
+/// [`SerialTerminal::xmodem_receive`]: super::SerialTerminal::xmodem_receive
+pub(super) fn xmodem_receive<P: Port>(port: &mut P, out: &mut impl io::Write) -> Result<usize> {
+    let mut buf = [0; LARGE_BLOCK];
+    let mut total = 0;
+    let result = request_start(port, true, &mut buf).and_then(|(checksum, packet)| {
+        receive_data(port, checksum, packet, &mut buf, false, |data| {
+            total += data.len();
+            out.write_all(data)
+        })
+    });
+    cancel_on_error(port, result).map(|()| total)
+}
+
+/// See [`SerialTerminal::ymodem_receive`].
+///
+/// [`SerialTerminal::ymodem_receive`]: super::SerialTerminal::ymodem_receive
+pub(super) fn ymodem_receive<P: Port, W: io::Write>(
+    port: &mut P,
+    mut open: impl FnMut(&str, Option<u64>) -> Result<W>,
+) -> Result<usize> {
+    let mut buf = [0; LARGE_BLOCK];
+    let mut files = 0;
+    let result = loop {
+        let (_, packet) = match request_start(port, false, &mut buf) {
+            Ok(start) => start,
+            Err(err) => break Err(err),
+        };
+        let Packet::Block { number: 0, len } = packet else {
+            break Err(Status::PROTOCOL_ERROR.into());
+        };
+        let Some((name, size)) = parse_header(&buf[..len]) else {
+            break Err(Status::PROTOCOL_ERROR.into());
+        };
+        if name.is_empty() {
+            break port.write(&[ACK]);
+        }
+        let mut out = match open(name, size) {
+            Ok(out) => out,
+            Err(err) => break Err(err),
+        };
+
+        let mut remaining = size.unwrap_or(u64::MAX);
+        let received = port.write(&[ACK]).and_then(|()| {
+            let (checksum, packet) = request_start(port, false, &mut buf)?;
+            receive_data(port, checksum, packet, &mut buf, true, |data| {
+                // Drop the padding of the last block.
+                let len = data
+                    .len()
+                    .min(usize::try_from(remaining).unwrap_or(usize::MAX));
+                remaining -= len as u64;
+                out.write_all(&data[..len])
+            })
+        });
+        if let Err(err) = received {
+            break Err(err);
+        }
+        files += 1;
+    };
+    cancel_on_error(port, result).map(|()| files)
+}
+
+/// Parse the name and size from a YMODEM header block.
+fn parse_header(header: &[u8]) -> Option<(&str, Option<u64>)> {
+    let mut fields = header.splitn(2, |&byte| byte == 0);
+    let name = core::str::from_utf8(fields.next()?).ok()?;
+    // The size is followed by a space and other optional fields.
+    let size = fields
+        .next()
+        .and_then(|rest| rest.split(|&byte| byte == b' ' || byte == 0).next())
+        .and_then(|size| core::str::from_utf8(size).ok())
+        .and_then(|size| size.parse().ok());
+    Some((name, size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::terminal::tests::FakePort;
+    use super::*;
+    use alloc::borrow::ToOwned;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    fn test_data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 7 % 251) as u8).collect()
+    }
+
+    /// Build a block like the sender does.
+    fn block(number: u8, data: &[u8], checksum: Checksum) -> Vec<u8> {
+        let mut block = vec![if data.len() == SMALL_BLOCK { SOH } else { STX }];
+        block.extend_from_slice(&[number, !number]);
+        block.extend_from_slice(data);
+        let mut sum = [0; 2];
+        checksum.write(data, &mut sum[..checksum.len()]);
+        block.extend_from_slice(&sum[..checksum.len()]);
+        block
+    }
+
+    #[test]
+    fn test_crc16() {
+        assert_eq!(crc16(b"123456789"), 0x31c3);
+        assert_eq!(crc16(b""), 0);
+    }
+
+    #[test]
+    fn test_xmodem() {
+        let data = test_data(300);
+        let mut sender = FakePort::new(&[&[CRC_START, ACK, ACK, ACK, ACK]]);
+        xmodem_send(&mut sender, &mut &data[..]).unwrap();
+
+        let mut padded = data.clone();
+        padded.resize(3 * SMALL_BLOCK, SUB);
+        let mut expected = block(1, &padded[..128], Checksum::Crc);
+        expected.extend(block(2, &padded[128..256], Checksum::Crc));
+        expected.extend(block(3, &padded[256..], Checksum::Crc));
+        expected.push(EOT);
+        assert_eq!(sender.output, expected);
+
+        let mut receiver = FakePort::new(&[&sender.output]);
+        let mut out = Vec::new();
+        assert_eq!(xmodem_receive(&mut receiver, &mut out), Ok(384));
+        assert_eq!(out, padded);
+        assert_eq!(receiver.output, [CRC_START, ACK, ACK, ACK, ACK]);
+    }
+
+    #[test]
+    fn test_xmodem_checksum_and_retry() {
+        let data = test_data(100);
+        // The first block is rejected once.
+        let mut sender = FakePort::new(&[&[NAK, NAK, ACK, ACK]]);
+        xmodem_send(&mut sender, &mut &data[..]).unwrap();
+
+        let mut padded = data.clone();
+        padded.resize(SMALL_BLOCK, SUB);
+        let first = block(1, &padded, Checksum::Sum);
+        assert_eq!(first.len(), 3 + 128 + 1);
+        let mut expected = first.clone();
+        expected.extend(&first);
+        expected.push(EOT);
+        assert_eq!(sender.output, expected);
+    }
+
+    #[test]
+    fn test_xmodem_receive_errors() {
+        let data = test_data(256);
+        let first = block(1, &data[..128], Checksum::Crc);
+        let second = block(2, &data[128..], Checksum::Crc);
+        let mut corrupt = second.clone();
+        corrupt[10] ^= 1;
+
+        // A corrupt block, which is sent again, and a duplicate block.
+        let mut input = first.clone();
+        input.extend(&corrupt);
+        let mut retry = second.clone();
+        retry.extend(&second);
+        retry.push(EOT);
+        let mut receiver = FakePort::new(&[&input, &retry]);
+        let mut out = Vec::new();
+        assert_eq!(xmodem_receive(&mut receiver, &mut out), Ok(256));
+        assert_eq!(out, data);
+        assert_eq!(receiver.output, [CRC_START, ACK, NAK, ACK, ACK, ACK]);
+
+        // A skipped block cancels the transfer.
+        let mut input = first.clone();
+        input.extend(block(3, &data[128..], Checksum::Crc));
+        let mut receiver = FakePort::new(&[&input]);
+        assert_eq!(
+            xmodem_receive(&mut receiver, &mut Vec::new()),
+            Err(Status::PROTOCOL_ERROR.into())
+        );
+        assert_eq!(receiver.output, [CRC_START, ACK, CAN, CAN]);
+
+        // Cancelled by the sender.
+        let mut receiver = FakePort::new(&[&[CAN, CAN]]);
+        assert_eq!(
+            xmodem_receive(&mut receiver, &mut Vec::new()),
+            Err(Status::ABORTED.into())
+        );
+        assert_eq!(receiver.output, [CRC_START]);
+    }
+
+    #[test]
+    fn test_ymodem() {
+        let data = test_data(1500);
+        // Header, two data blocks, EOT twice, end of batch.
+        let responses = [
+            CRC_START, ACK, CRC_START, ACK, ACK, NAK, ACK, CRC_START, ACK,
+        ];
+        let mut sender = FakePort::new(&[&responses]);
+        ymodem_send(&mut sender, "test.bin", 1500, &mut &data[..]).unwrap();
+
+        let mut header = [0; SMALL_BLOCK];
+        header[..13].copy_from_slice(b"test.bin\x001500");
+        let mut padded = data.clone();
+        padded.resize(2 * LARGE_BLOCK, SUB);
+        let mut expected = block(0, &header, Checksum::Crc);
+        expected.extend(block(1, &padded[..1024], Checksum::Crc));
+        expected.extend(block(2, &padded[1024..], Checksum::Crc));
+        expected.extend([EOT, EOT]);
+        expected.extend(block(0, &[0; SMALL_BLOCK], Checksum::Crc));
+        assert_eq!(sender.output, expected);
+
+        let mut receiver = FakePort::new(&[&sender.output]);
+        let mut files = Vec::new();
+        let mut out = [0; 1500];
+        let mut writer = Some(&mut out[..]);
+        let count = ymodem_receive(&mut receiver, |name, size| {
+            files.push((name.to_owned(), size));
+            Ok(writer.take().unwrap())
+        });
+        assert_eq!(count, Ok(1));
+        assert_eq!(files, [("test.bin".to_owned(), Some(1500))]);
+        assert_eq!(out[..], data);
+        assert_eq!(receiver.output, responses);
+    }
+
+    #[test]
+    fn test_ymodem_send_errors() {
+        let mut sender = FakePort::new(&[]);
+        assert_eq!(
+            ymodem_send(&mut sender, "", 0, &mut &[][..]),
+            Err(Status::INVALID_PARAMETER.into())
+        );
+        assert!(sender.output.is_empty());
+
+        // The data is shorter than the size.
+        let mut sender = FakePort::new(&[&[CRC_START, ACK, CRC_START]]);
+        assert_eq!(
+            ymodem_send(&mut sender, "a", 10, &mut &[1, 2][..]),
+            Err(Status::END_OF_FILE.into())
+        );
+        assert!(sender.output.ends_with(&[CAN, CAN]));
+    }
+
+    #[test]
+    fn test_parse_header() {
+        assert_eq!(
+            parse_header(b"file\x00123 14271263 100644\x00\x00"),
+            Some(("file", Some(123)))
+        );
+        assert_eq!(parse_header(b"file\x00\x00"), Some(("file", None)));
+        assert_eq!(parse_header(&[0; 128]), Some(("", None)));
+        assert_eq!(parse_header(b"\xff\x00"), None);
+    }
+}
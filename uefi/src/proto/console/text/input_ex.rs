@@ -0,0 +1,407 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::Key;
+use crate::proto::unsafe_protocol;
+use crate::{Event, Result, Status, StatusExt};
+use core::ffi::c_void;
+use core::fmt::{self, Debug, Formatter};
+use core::mem::{ManuallyDrop, MaybeUninit};
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+use uefi_raw::protocol::console::{self as raw, SimpleTextInputExProtocol};
+
+pub use uefi_raw::protocol::console::{KeyNotifyFunction, KeyShiftState, KeyToggleState};
+
+/// Extended interface for text-based input devices.
+///
+/// Unlike [`Input`], this reports the state of the modifier and toggle
+/// keys along with each keystroke, can change the toggle state, and can
+/// call a function when a specific key is pressed.
+///
+/// [`Input`]: super::Input
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(SimpleTextInputExProtocol::GUID)]
+pub struct InputEx(SimpleTextInputExProtocol);
+
+impl InputEx {
+    /// Resets the input device hardware.
+    ///
+    /// The `extended_verification` parameter is used to request that UEFI
+    /// performs an extended check and reset of the input device.
+    ///
+    /// # Errors
+    ///
+    /// - [`Status::DEVICE_ERROR`] if the device is malfunctioning and cannot
+    ///   be reset.
+    pub fn reset(&mut self, extended_verification: bool) -> Result {
+        unsafe { (self.0.reset)(&mut self.0, extended_verification.into()) }.to_result()
+    }
+
+    /// Reads the next keystroke from the input device, if any, along with
+    /// the state of the modifier and toggle keys.
+    ///
+    /// If [`KeyToggleState::KEY_STATE_EXPOSED`] was set with
+    /// [`set_state`], pressing or releasing just a modifier key also
+    /// returns a [partial keystroke].
+    ///
+    /// Use [`wait_for_key_event`] with the [`boot::wait_for_event`]
+    /// interface in order to wait for a key to be pressed.
+    ///
+    /// # Errors
+    ///
+    /// - [`Status::DEVICE_ERROR`] if there was an issue with the input device
+    /// - [`Status::UNSUPPORTED`] if the device does not support this
+    ///   function
+    ///
+    /// [`boot::wait_for_event`]: crate::boot::wait_for_event
+    /// [`set_state`]: Self::set_state
+    /// [`wait_for_key_event`]: Self::wait_for_key_event
+    /// [partial keystroke]: KeyData::is_partial
+    pub fn read_key_stroke_ex(&mut self) -> Result<Option<KeyData>> {
+        let mut key_data = MaybeUninit::<raw::KeyData>::uninit();
+
+        match unsafe { (self.0.read_key_stroke_ex)(&mut self.0, key_data.as_mut_ptr()) } {
+            Status::NOT_READY => Ok(None),
+            other => other.to_result_with_val(|| Some(unsafe { key_data.assume_init() }.into())),
+        }
+    }
+
+    /// Event to be used with [`boot::wait_for_event`] in order to wait
+    /// for a key to be available
+    ///
+    /// [`boot::wait_for_event`]: crate::boot::wait_for_event
+    #[must_use]
+    pub fn wait_for_key_event(&self) -> Option<Event> {
+        unsafe { Event::from_ptr(self.0.wait_for_key_ex) }
+    }
+
+    /// Set the state of the toggle keys, such as Caps Lock and Num Lock.
+    ///
+    /// [`KeyToggleState::TOGGLE_STATE_VALID`] is added to `state`
+    /// automatically.
+    ///
+    /// # Errors
+    ///
+    /// - [`Status::DEVICE_ERROR`] if the device is not responding
+    /// - [`Status::UNSUPPORTED`] if the device does not support setting the
+    ///   state
+    pub fn set_state(&mut self, state: KeyToggleState) -> Result {
+        let state = state | KeyToggleState::TOGGLE_STATE_VALID;
+        unsafe { (self.0.set_state)(&mut self.0, &state) }.to_result()
+    }
+
+    /// Register `function` to be called when the keystroke `key` is
+    /// pressed.
+    ///
+    /// The function receives a pointer to the raw key data, which can be
+    /// converted with [`KeyData::from`]. Shift and toggle states in `key`
+    /// are only compared if their `VALID` flag is set.
+    ///
+    /// The function stays registered until the returned
+    /// [`KeyNotification`] is dropped. The notification dereferences to
+    /// this protocol, so that further keys can be registered and keys can
+    /// still be read while it is alive.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use uefi::proto::console::text::{InputEx, Key, KeyData, KeyShiftState, KeyState};
+    /// use uefi::{Char16, Status};
+    /// use uefi_raw::protocol::console::KeyData as RawKeyData;
+    ///
+    /// unsafe extern "efiapi" fn on_ctrl_s(_key: *mut RawKeyData) -> Status {
+    ///     // Called at TPL_CALLBACK; keep it short.
+    ///     Status::SUCCESS
+    /// }
+    ///
+    /// fn run_menu(input: &mut InputEx) -> uefi::Result {
+    ///     let ctrl_s = KeyData {
+    ///         key: Key::Printable(Char16::try_from('s').unwrap()),
+    ///         state: KeyState {
+    ///             shift_state: KeyShiftState::SHIFT_STATE_VALID
+    ///                 | KeyShiftState::LEFT_CONTROL_PRESSED,
+    ///             ..Default::default()
+    ///         },
+    ///     };
+    ///     let mut input = input.register_key_notify(&ctrl_s, on_ctrl_s)?;
+    ///     if let Some(key_data) = input.read_key_stroke_ex()? {
+    ///         // ...
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [`Status::OUT_OF_RESOURCES`] if the notification could not be
+    ///   registered
+    pub fn register_key_notify(
+        &mut self,
+        key: &KeyData,
+        function: KeyNotifyFunction,
+    ) -> Result<KeyNotification<'_>> {
+        let key = raw::KeyData::from(*key);
+        let mut handle = ptr::null_mut();
+        unsafe { (self.0.register_key_notify)(&mut self.0, &key, function, &mut handle) }
+            .to_result_with_val(|| KeyNotification {
+                input: self,
+                handle,
+            })
+    }
+}
+
+/// A key notification function registered with
+/// [`InputEx::register_key_notify`].
+///
+/// The function is unregistered when this is dropped. The notification
+/// dereferences to the [`InputEx`] protocol it was registered on.
+pub struct KeyNotification<'a> {
+    input: &'a mut InputEx,
+    handle: *mut c_void,
+}
+
+impl KeyNotification<'_> {
+    /// Unregister the notification function.
+    ///
+    /// Dropping the notification does the same, but ignores errors.
+    ///
+    /// # Errors
+    ///
+    /// - [`Status::INVALID_PARAMETER`] if the notification is no longer
+    ///   registered
+    pub fn unregister(self) -> Result {
+        let mut this = ManuallyDrop::new(self);
+        unsafe { this.unregister_raw() }
+    }
+
+    /// # Safety
+    ///
+    /// Must only be called once.
+    unsafe fn unregister_raw(&mut self) -> Result {
+        let protocol = &mut self.input.0;
+        unsafe { (protocol.unregister_key_notify)(protocol, self.handle) }.to_result()
+    }
+}
+
+impl Debug for KeyNotification<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyNotification")
+            .field("handle", &self.handle)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Deref for KeyNotification<'_> {
+    type Target = InputEx;
+
+    fn deref(&self) -> &InputEx {
+        &*self.input
+    }
+}
+
+impl DerefMut for KeyNotification<'_> {
+    fn deref_mut(&mut self) -> &mut InputEx {
+        &mut *self.input
+    }
+}
+
+impl Drop for KeyNotification<'_> {
+    fn drop(&mut self) {
+        let _ = unsafe { self.unregister_raw() };
+    }
+}
+
+/// A keystroke along with the state of the modifier and toggle keys.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct KeyData {
+    /// The key that was pressed.
+    pub key: Key,
+
+    /// The state of the modifier and toggle keys.
+    pub state: KeyState,
+}
+
+impl KeyData {
+    /// True if only the state of a modifier or toggle key changed, without
+    /// a key being pressed.
+    #[must_use]
+    pub fn is_partial(&self) -> bool {
+        matches!(self.key, Key::Printable(c) if u16::from(c) == 0)
+    }
+}
+
+impl From<raw::KeyData> for KeyData {
+    fn from(data: raw::KeyData) -> Self {
+        Self {
+            key: data.key.into(),
+            state: data.key_state.into(),
+        }
+    }
+}
+
+impl From<KeyData> for raw::KeyData {
+    fn from(data: KeyData) -> Self {
+        let key = match data.key {
+            Key::Printable(c) => raw::InputKey {
+                scan_code: 0,
+                unicode_char: c.into(),
+            },
+            Key::Special(scan_code) => raw::InputKey {
+                scan_code: scan_code.0,
+                unicode_char: 0,
+            },
+        };
+        Self {
+            key,
+            key_state: data.state.into(),
+        }
+    }
+}
+
+/// State of the modifier and toggle keys.
+///
+/// Devices that do not report the state leave the `VALID` flags unset, in
+/// which case all of the query methods return `false`.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct KeyState {
+    /// State of the shift, control, alt, logo, menu and SysReq keys.
+    pub shift_state: KeyShiftState,
+
+    /// State of the toggle keys.
+    pub toggle_state: KeyToggleState,
+}
+
+impl KeyState {
+    const fn shift_pressed_any(&self, flags: KeyShiftState) -> bool {
+        self.shift_state.contains(KeyShiftState::SHIFT_STATE_VALID)
+            && self.shift_state.intersects(flags)
+    }
+
+    const fn toggle_active(&self, flag: KeyToggleState) -> bool {
+        self.toggle_state
+            .contains(KeyToggleState::TOGGLE_STATE_VALID.union(flag))
+    }
+
+    /// True if either shift key is pressed.
+    #[must_use]
+    pub const fn shift(&self) -> bool {
+        self.shift_pressed_any(
+            KeyShiftState::LEFT_SHIFT_PRESSED.union(KeyShiftState::RIGHT_SHIFT_PRESSED),
+        )
+    }
+
+    /// True if either control key is pressed.
+    #[must_use]
+    pub const fn control(&self) -> bool {
+        self.shift_pressed_any(
+            KeyShiftState::LEFT_CONTROL_PRESSED.union(KeyShiftState::RIGHT_CONTROL_PRESSED),
+        )
+    }
+
+    /// True if either alt key is pressed.
+    #[must_use]
+    pub const fn alt(&self) -> bool {
+        self.shift_pressed_any(
+            KeyShiftState::LEFT_ALT_PRESSED.union(KeyShiftState::RIGHT_ALT_PRESSED),
+        )
+    }
+
+    /// True if either logo key (e.g. the Windows key) is pressed.
+    #[must_use]
+    pub const fn logo(&self) -> bool {
+        self.shift_pressed_any(
+            KeyShiftState::LEFT_LOGO_PRESSED.union(KeyShiftState::RIGHT_LOGO_PRESSED),
+        )
+    }
+
+    /// True if Caps Lock is active.
+    #[must_use]
+    pub const fn caps_lock(&self) -> bool {
+        self.toggle_active(KeyToggleState::CAPS_LOCK_ACTIVE)
+    }
+
+    /// True if Num Lock is active.
+    #[must_use]
+    pub const fn num_lock(&self) -> bool {
+        self.toggle_active(KeyToggleState::NUM_LOCK_ACTIVE)
+    }
+
+    /// True if Scroll Lock is active.
+    #[must_use]
+    pub const fn scroll_lock(&self) -> bool {
+        self.toggle_active(KeyToggleState::SCROLL_LOCK_ACTIVE)
+    }
+}
+
+impl From<raw::KeyState> for KeyState {
+    fn from(state: raw::KeyState) -> Self {
+        Self {
+            shift_state: state.key_shift_state,
+            toggle_state: state.key_toggle_state,
+        }
+    }
+}
+
+impl From<KeyState> for raw::KeyState {
+    fn from(state: KeyState) -> Self {
+        Self {
+            key_shift_state: state.shift_state,
+            key_toggle_state: state.toggle_state,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::console::text::ScanCode;
+    use crate::Char16;
+
+    #[test]
+    fn test_key_state() {
+        let state = KeyState {
+            shift_state: KeyShiftState::RIGHT_CONTROL_PRESSED,
+            toggle_state: KeyToggleState::CAPS_LOCK_ACTIVE,
+        };
+        // Without the valid flags, nothing is reported.
+        assert!(!state.control());
+        assert!(!state.caps_lock());
+
+        let state = KeyState {
+            shift_state: state.shift_state | KeyShiftState::SHIFT_STATE_VALID,
+            toggle_state: state.toggle_state | KeyToggleState::TOGGLE_STATE_VALID,
+        };
+        assert!(state.control());
+        assert!(!state.shift());
+        assert!(!state.alt());
+        assert!(!state.logo());
+        assert!(state.caps_lock());
+        assert!(!state.num_lock());
+        assert!(!state.scroll_lock());
+    }
+
+    #[test]
+    fn test_key_data_conversion() {
+        let data = KeyData {
+            key: Key::Printable(Char16::try_from('s').unwrap()),
+            state: KeyState {
+                shift_state: KeyShiftState::SHIFT_STATE_VALID | KeyShiftState::LEFT_CONTROL_PRESSED,
+                toggle_state: KeyToggleState::empty(),
+            },
+        };
+        let raw = raw::KeyData::from(data);
+        assert_eq!(raw.key.scan_code, 0);
+        assert_eq!(raw.key.unicode_char, u16::from(b's'));
+        assert_eq!(KeyData::from(raw), data);
+        assert!(!data.is_partial());
+
+        let data = KeyData {
+            key: Key::Special(ScanCode::FUNCTION_1),
+            state: KeyState::default(),
+        };
+        assert_eq!(KeyData::from(raw::KeyData::from(data)), data);
+
+        assert!(KeyData::from(raw::KeyData::default()).is_partial());
+    }
+}
@@ -5,5 +5,10 @@
 mod input;
 pub use input::{Input, Key, ScanCode};
 
+mod input_ex;
+pub use input_ex::{
+    InputEx, KeyData, KeyNotification, KeyNotifyFunction, KeyShiftState, KeyState, KeyToggleState,
+};
+
 mod output;
 pub use output::{Color, Output, OutputMode};
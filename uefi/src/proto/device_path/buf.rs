@@ -0,0 +1,492 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Owned, growable device path.
+
+use super::build::{end, media, BuildError, BuildNode};
+use super::text::ParseDevicePathError;
+use super::{ByteConversionError, DevicePath, DevicePathHeader, DevicePathNode, DeviceSubType};
+use crate::CStr16;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::mem::{self, size_of};
+use core::ops::Deref;
+use core::str::FromStr;
+
+/// Size of the [`END_ENTIRE`] node that terminates the buffer.
+///
+/// [`END_ENTIRE`]: super::DeviceSubType::END_ENTIRE
+const END_SIZE: usize = size_of::<DevicePathHeader>();
+
+/// An owned, heap-allocated [`DevicePath`] that can be edited in place,
+/// similar to the `PathBuf` of the standard library.
+///
+/// The buffer always contains a valid device path terminated by an
+/// [`END_ENTIRE`] node, so it can be used anywhere a `&DevicePath` is
+/// expected via [`Deref`].
+///
+/// The editing methods operate on the nodes returned by
+/// [`DevicePath::node_iter`]. In a path with multiple instances this
+/// includes the [`END_INSTANCE`] nodes between instances.
+///
+/// All operations are implemented in Rust and do not use the
+/// [`DevicePathUtilities`] protocol.
+///
+/// # Examples
+///
+/// ```
+/// use uefi::cstr16;
+/// use uefi::proto::device_path::build;
+/// use uefi::proto::device_path::DevicePathBuf;
+///
+/// # fn main() -> Result<(), build::BuildError> {
+/// let mut path = DevicePathBuf::new();
+/// path.push(&build::acpi::Acpi {
+///     hid: 0x41d0_0a03,
+///     uid: 0x0000_0000,
+/// })?;
+/// path.push(&build::hardware::Pci {
+///     function: 0x00,
+///     device: 0x1f,
+/// })?;
+/// path.set_file_path(cstr16!(r"\EFI\BOOT\BOOTX64.EFI"))?;
+/// assert_eq!(path.node_iter().count(), 3);
+///
+/// assert!(path.pop());
+/// assert_eq!(path.node_iter().count(), 2);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`END_ENTIRE`]: super::DeviceSubType::END_ENTIRE
+/// [`END_INSTANCE`]: super::DeviceSubType::END_INSTANCE
+/// [`DevicePathUtilities`]: super::util::DevicePathUtilities
+#[derive(Clone, Eq, PartialEq)]
+pub struct DevicePathBuf {
+    data: Vec<u8>,
+}
+
+impl DevicePathBuf {
+    /// Create an empty device path, containing only an [`END_ENTIRE`]
+    /// node.
+    ///
+    /// [`END_ENTIRE`]: super::DeviceSubType::END_ENTIRE
+    #[must_use]
+    pub fn new() -> Self {
+        let mut path = Self { data: Vec::new() };
+        path.push_end();
+        path
+    }
+
+    /// Get the buffer as a [`DevicePath`] reference.
+    #[must_use]
+    pub fn as_device_path(&self) -> &DevicePath {
+        // SAFETY: `data` always contains a valid device path.
+        unsafe { &*ptr_meta::from_raw_parts(self.data.as_ptr().cast(), self.data.len()) }
+    }
+
+    /// Convert into a boxed [`DevicePath`].
+    #[must_use]
+    pub fn into_boxed_device_path(self) -> Box<DevicePath> {
+        // SAFETY: `data` always contains a valid device path.
+        unsafe { DevicePath::from_boxed_bytes(self.data.into_boxed_slice()) }
+    }
+
+    /// Append a node to the end of the path.
+    ///
+    /// An [`END_INSTANCE`] node may be pushed to start a new instance.
+    /// An error will be returned if an [`END_ENTIRE`] node is passed to
+    /// this function, as the buffer always ends with one. On error the
+    /// path is unchanged.
+    ///
+    /// [`END_ENTIRE`]: super::DeviceSubType::END_ENTIRE
+    /// [`END_INSTANCE`]: super::DeviceSubType::END_INSTANCE
+    pub fn push(&mut self, node: &dyn BuildNode) -> Result<(), BuildError> {
+        let node_size = usize::from(node.size_in_bytes()?);
+        let old_size = self.data.len();
+
+        self.data.reserve(node_size);
+        let buf = &mut self.data.spare_capacity_mut()[..node_size];
+        node.write_data(buf);
+        // SAFETY: `write_data` initializes all bytes of `buf`.
+        unsafe {
+            self.data.set_len(old_size + node_size);
+        }
+
+        let is_end_entire = <&DevicePathNode>::try_from(&self.data[old_size..])
+            .is_ok_and(DevicePathNode::is_end_entire);
+        if is_end_entire {
+            self.data.truncate(old_size);
+            return Err(BuildError::UnexpectedEndEntire);
+        }
+
+        // Move the new node in front of the end node.
+        self.data[old_size - END_SIZE..].rotate_right(node_size);
+        Ok(())
+    }
+
+    /// Remove the last node of the path.
+    ///
+    /// Returns `false` and does nothing if the path is empty.
+    pub fn pop(&mut self) -> bool {
+        let last = self.node_offsets().last();
+        if let Some(offset) = last {
+            self.truncate_at(offset);
+        }
+        last.is_some()
+    }
+
+    /// Shorten the path to its first `len` nodes.
+    ///
+    /// Has no effect if `len` is greater than or equal to the number of
+    /// nodes in the path.
+    pub fn truncate(&mut self, len: usize) {
+        let offset = self.node_offsets().nth(len);
+        if let Some(offset) = offset {
+            self.truncate_at(offset);
+        }
+    }
+
+    /// Replace the trailing [`FilePath`] nodes of the path with a single
+    /// node containing `path`. If the path does not end with a
+    /// `FilePath` node, one is appended.
+    ///
+    /// Firmware may split a file path across multiple consecutive
+    /// nodes; all of them are replaced.
+    ///
+    /// [`FilePath`]: super::media::FilePath
+    pub fn set_file_path(&mut self, path: &CStr16) -> Result<(), BuildError> {
+        let node = media::FilePath { path_name: path };
+        // Check the size before modifying the path.
+        node.size_in_bytes()?;
+
+        let mut file_path_offset = None;
+        let mut offset = 0;
+        for node in self.node_iter() {
+            if <&super::media::FilePath>::try_from(node).is_ok() {
+                file_path_offset.get_or_insert(offset);
+            } else {
+                file_path_offset = None;
+            }
+            offset += usize::from(node.length());
+        }
+
+        if let Some(offset) = file_path_offset {
+            self.truncate_at(offset);
+        }
+        self.push(&node)
+    }
+
+    /// Append the nodes of `other` to the end of the path.
+    ///
+    /// If `other` contains multiple instances, its first instance is
+    /// joined with the last instance of this path.
+    pub fn extend_from_path(&mut self, other: &DevicePath) {
+        let end = self.data.len() - END_SIZE;
+        self.data
+            .splice(end..end, other.data[..other.nodes_len()].iter().copied());
+    }
+
+    /// Append `other` as new instances of the path, separated from the
+    /// existing nodes by an [`END_INSTANCE`] node.
+    ///
+    /// If this path is empty, the result is a copy of `other`.
+    ///
+    /// [`END_INSTANCE`]: super::DeviceSubType::END_INSTANCE
+    pub fn append_instance(&mut self, other: &DevicePath) {
+        if self.node_iter().next().is_some() {
+            // Turn the end-entire node into an end-instance node.
+            let end = self.data.len() - END_SIZE;
+            self.data[end + 1] = DeviceSubType::END_INSTANCE.0;
+        } else {
+            self.data.clear();
+        }
+        self.data
+            .extend_from_slice(&other.data[..other.nodes_len()]);
+        self.push_end();
+    }
+
+    /// Iterate over the byte offsets of the path's nodes, excluding the
+    /// final [`END_ENTIRE`] node.
+    ///
+    /// [`END_ENTIRE`]: super::DeviceSubType::END_ENTIRE
+    fn node_offsets(&self) -> impl Iterator<Item = usize> + '_ {
+        self.node_iter().scan(0, |offset, node| {
+            let start = *offset;
+            *offset += usize::from(node.length());
+            Some(start)
+        })
+    }
+
+    /// Remove all nodes starting at byte `offset`, then add an
+    /// [`END_ENTIRE`] node.
+    ///
+    /// [`END_ENTIRE`]: super::DeviceSubType::END_ENTIRE
+    fn truncate_at(&mut self, offset: usize) {
+        self.data.truncate(offset);
+        self.push_end();
+    }
+
+    /// Add an [`END_ENTIRE`] node to the end of `data`.
+    ///
+    /// [`END_ENTIRE`]: super::DeviceSubType::END_ENTIRE
+    fn push_end(&mut self) {
+        let old_size = self.data.len();
+        self.data.reserve(END_SIZE);
+        end::Entire.write_data(&mut self.data.spare_capacity_mut()[..END_SIZE]);
+        // SAFETY: `write_data` initializes all bytes of the node.
+        unsafe {
+            self.data.set_len(old_size + END_SIZE);
+        }
+    }
+}
+
+impl Default for DevicePathBuf {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for DevicePathBuf {
+    type Target = DevicePath;
+
+    fn deref(&self) -> &DevicePath {
+        self.as_device_path()
+    }
+}
+
+impl AsRef<DevicePath> for DevicePathBuf {
+    fn as_ref(&self) -> &DevicePath {
+        self
+    }
+}
+
+impl Borrow<DevicePath> for DevicePathBuf {
+    fn borrow(&self) -> &DevicePath {
+        self
+    }
+}
+
+impl Debug for DevicePathBuf {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        Debug::fmt(self.as_device_path(), f)
+    }
+}
+
+impl Display for DevicePathBuf {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(self.as_device_path(), f)
+    }
+}
+
+impl PartialEq<DevicePath> for DevicePathBuf {
+    fn eq(&self, other: &DevicePath) -> bool {
+        self.as_device_path() == other
+    }
+}
+
+impl From<&DevicePath> for DevicePathBuf {
+    fn from(path: &DevicePath) -> Self {
+        let nodes_len = path.nodes_len();
+        let mut data = Vec::with_capacity(nodes_len + END_SIZE);
+        data.extend_from_slice(&path.data[..nodes_len]);
+        let mut path = Self { data };
+        path.push_end();
+        path
+    }
+}
+
+impl From<Box<DevicePath>> for DevicePathBuf {
+    fn from(path: Box<DevicePath>) -> Self {
+        let nodes_len = path.nodes_len();
+        // SAFETY: `DevicePath` has the same layout as `[u8]`.
+        let data: Box<[u8]> = unsafe { mem::transmute(path) };
+        let mut path = Self { data: data.into() };
+        path.truncate_at(nodes_len);
+        path
+    }
+}
+
+impl From<DevicePathBuf> for Box<DevicePath> {
+    fn from(path: DevicePathBuf) -> Self {
+        path.into_boxed_device_path()
+    }
+}
+
+impl TryFrom<Vec<u8>> for DevicePathBuf {
+    type Error = ByteConversionError;
+
+    /// Convert a byte buffer containing a device path to a
+    /// `DevicePathBuf`. Any bytes after the [`END_ENTIRE`] node are
+    /// discarded.
+    ///
+    /// [`END_ENTIRE`]: super::DeviceSubType::END_ENTIRE
+    fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+        let nodes_len = <&DevicePath>::try_from(data.as_slice())?.nodes_len();
+        let mut path = Self { data };
+        path.truncate_at(nodes_len);
+        Ok(path)
+    }
+}
+
+impl FromStr for DevicePathBuf {
+    type Err = ParseDevicePathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Box::<DevicePath>::from_str(s).map(Self::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cstr16;
+    use crate::proto::device_path::build::{hardware, DevicePathBuilder};
+    use alloc::{format, vec};
+
+    fn parse(text: &str) -> DevicePathBuf {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        let path = DevicePathBuf::new();
+        assert_eq!(path.as_bytes(), [0x7f, 0xff, 4, 0]);
+        assert_eq!(path.node_iter().count(), 0);
+        assert_eq!(path, DevicePathBuf::default());
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let mut path = DevicePathBuf::new();
+        path.push(&hardware::Pci {
+            function: 0,
+            device: 1,
+        })
+        .unwrap();
+        path.push(&hardware::Pci {
+            function: 2,
+            device: 3,
+        })
+        .unwrap();
+        assert_eq!(format!("{path}"), "Pci(0x1,0x0)/Pci(0x3,0x2)");
+
+        // Same bytes as the builder produces.
+        let mut v = Vec::new();
+        let expected = DevicePathBuilder::with_vec(&mut v)
+            .push(&hardware::Pci {
+                function: 0,
+                device: 1,
+            })
+            .unwrap()
+            .push(&hardware::Pci {
+                function: 2,
+                device: 3,
+            })
+            .unwrap()
+            .finalize()
+            .unwrap();
+        assert_eq!(path, *expected);
+
+        assert!(matches!(
+            path.push(&end::Entire),
+            Err(BuildError::UnexpectedEndEntire)
+        ));
+        assert_eq!(path, *expected);
+
+        assert!(path.pop());
+        assert_eq!(format!("{path}"), "Pci(0x1,0x0)");
+        assert!(path.pop());
+        assert_eq!(path, DevicePathBuf::new());
+        assert!(!path.pop());
+    }
+
+    #[test]
+    fn test_truncate() {
+        let mut path = parse("PciRoot(0x0)/Pci(0x1,0x0)/NVMe(0x1,00-00-00-00-00-00-00-00)");
+        path.truncate(3);
+        assert_eq!(path.node_iter().count(), 3);
+        path.truncate(2);
+        assert_eq!(format!("{path}"), "PciRoot(0x0)/Pci(0x1,0x0)");
+        path.truncate(0);
+        assert_eq!(path, DevicePathBuf::new());
+    }
+
+    #[test]
+    fn test_set_file_path() {
+        let mut path = parse("PciRoot(0x0)/Pci(0x1,0x0)");
+        path.set_file_path(cstr16!(r"\EFI")).unwrap();
+        assert_eq!(format!("{path}"), r"PciRoot(0x0)/Pci(0x1,0x0)/\EFI");
+
+        // Consecutive file path nodes are replaced as a whole.
+        path.push(&media::FilePath {
+            path_name: cstr16!("BOOT"),
+        })
+        .unwrap();
+        path.set_file_path(cstr16!(r"\EFI\BOOT\BOOTX64.EFI"))
+            .unwrap();
+        assert_eq!(
+            format!("{path}"),
+            r"PciRoot(0x0)/Pci(0x1,0x0)/\EFI\BOOT\BOOTX64.EFI"
+        );
+        assert_eq!(path.node_iter().count(), 3);
+
+        // Only trailing file path nodes are replaced.
+        let mut path = parse(r"\a/Pci(0x1,0x0)");
+        path.set_file_path(cstr16!(r"\b")).unwrap();
+        assert_eq!(format!("{path}"), r"\a/Pci(0x1,0x0)/\b");
+    }
+
+    #[test]
+    fn test_extend_and_append_instance() {
+        let mut path = DevicePathBuf::new();
+        let pci = parse("PciRoot(0x0)/Pci(0x1,0x0)");
+        let usb = parse("Pci(0x2,0x0)/USB(0x1,0x0)");
+
+        path.append_instance(&pci);
+        assert_eq!(path, pci);
+        assert!(!path.is_multi_instance());
+
+        path.append_instance(&usb);
+        assert!(path.is_multi_instance());
+        assert_eq!(
+            format!("{path}"),
+            "PciRoot(0x0)/Pci(0x1,0x0),Pci(0x2,0x0)/USB(0x1,0x0)"
+        );
+
+        path.extend_from_path(&pci);
+        assert_eq!(
+            format!("{path}"),
+            "PciRoot(0x0)/Pci(0x1,0x0),Pci(0x2,0x0)/USB(0x1,0x0)/PciRoot(0x0)/Pci(0x1,0x0)"
+        );
+        assert_eq!(path.instance_iter().count(), 2);
+
+        // End-instance nodes count as nodes when truncating.
+        path.truncate(3);
+        assert_eq!(
+            path.node_iter().last().unwrap().sub_type(),
+            DeviceSubType::END_INSTANCE
+        );
+    }
+
+    #[test]
+    fn test_conversions() {
+        let text = r"PciRoot(0x0)/Pci(0x1,0x0)/\EFI";
+        let path = parse(text);
+        let boxed: Box<DevicePath> = text.parse().unwrap();
+        assert_eq!(path, *boxed);
+        assert_eq!(DevicePathBuf::from(&*boxed), path);
+        assert_eq!(DevicePathBuf::from(boxed.to_boxed()), path);
+        assert_eq!(path.clone().into_boxed_device_path(), boxed);
+
+        // Trailing data after the end node is dropped.
+        let mut bytes = boxed.as_bytes().to_vec();
+        bytes.extend([1, 2, 3]);
+        assert_eq!(DevicePathBuf::try_from(bytes).unwrap(), path);
+        assert_eq!(
+            DevicePathBuf::try_from(vec![1, 2]),
+            Err(ByteConversionError::InvalidLength)
+        );
+    }
+}
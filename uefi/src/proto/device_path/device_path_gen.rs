@@ -11,6 +11,9 @@
 use crate::data_types::UnalignedSlice;
 use crate::mem::memory_map::MemoryType;
 use crate::polyfill::maybe_uninit_slice_as_mut_ptr;
+#[cfg(feature = "serde")]
+use crate::proto::device_path::serde_impls::field::SerdeField;
+use crate::proto::device_path::text::field::{kind, DebugAscii, DebugUcs2, FormatField};
 use crate::proto::device_path::{
     self, DevicePathHeader, DevicePathNode, DeviceSubType, DeviceType, NodeConversionError,
 };
@@ -40,6 +43,15 @@ pub mod end {
         }
     }
 
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Instance {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let state = serializer.serialize_struct("Instance", 0usize)?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Instance {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -75,6 +87,15 @@ pub mod end {
         }
     }
 
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Entire {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let state = serializer.serialize_struct("Entire", 0usize)?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Entire {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -122,12 +143,36 @@ pub mod hardware {
     impl fmt::Debug for Pci {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Pci")
-                .field("function", &{ self.function })
-                .field("device", &{ self.device })
+                .field("function", &self.function())
+                .field("device", &self.device())
                 .finish()
         }
     }
 
+    impl fmt::Display for Pci {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("Pci(")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.device(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.function(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Pci {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Pci", 2usize)?;
+            state.serialize_field(
+                "function",
+                &SerdeField::<kind::Hex, _>::new(&self.function()),
+            )?;
+            state.serialize_field("device", &SerdeField::<kind::Hex, _>::new(&self.device()))?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Pci {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -165,11 +210,32 @@ pub mod hardware {
     impl fmt::Debug for Pccard {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Pccard")
-                .field("function", &{ self.function })
+                .field("function", &self.function())
                 .finish()
         }
     }
 
+    impl fmt::Display for Pccard {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("PcCard(")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.function(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Pccard {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Pccard", 1usize)?;
+            state.serialize_field(
+                "function",
+                &SerdeField::<kind::Hex, _>::new(&self.function()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Pccard {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -221,13 +287,46 @@ pub mod hardware {
     impl fmt::Debug for MemoryMapped {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("MemoryMapped")
-                .field("memory_type", &{ self.memory_type })
-                .field("start_address", &{ self.start_address })
-                .field("end_address", &{ self.end_address })
+                .field("memory_type", &self.memory_type())
+                .field("start_address", &self.start_address())
+                .field("end_address", &self.end_address())
                 .finish()
         }
     }
 
+    impl fmt::Display for MemoryMapped {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("MemoryMapped(")?;
+            <kind::Enum as FormatField<_>>::fmt_field(&self.memory_type(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.start_address(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.end_address(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for MemoryMapped {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("MemoryMapped", 3usize)?;
+            state.serialize_field(
+                "memory_type",
+                &SerdeField::<kind::Enum, _>::new(&self.memory_type()),
+            )?;
+            state.serialize_field(
+                "start_address",
+                &SerdeField::<kind::Hex, _>::new(&self.start_address()),
+            )?;
+            state.serialize_field(
+                "end_address",
+                &SerdeField::<kind::Hex, _>::new(&self.end_address()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &MemoryMapped {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -273,17 +372,43 @@ pub mod hardware {
     impl fmt::Debug for Vendor {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Vendor")
-                .field("vendor_guid", &{ self.vendor_guid })
-                .field("vendor_defined_data", {
-                    let ptr = addr_of!(self.vendor_defined_data);
-                    let (ptr, len) = ptr_meta::to_raw_parts(ptr);
-                    let byte_len = size_of::<u8>() * len;
-                    unsafe { &slice::from_raw_parts(ptr.cast::<u8>(), byte_len) }
-                })
+                .field("vendor_guid", &self.vendor_guid())
+                .field("vendor_defined_data", &self.vendor_defined_data())
                 .finish()
         }
     }
 
+    impl fmt::Display for Vendor {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("VenHw(")?;
+            <kind::Guid as FormatField<_>>::fmt_field(&self.vendor_guid(), f)?;
+            let value = self.vendor_defined_data();
+            if !value.is_empty() {
+                f.write_str(",")?;
+                <kind::HexBytes as FormatField<_>>::fmt_field(&value, f)?;
+            }
+
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Vendor {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Vendor", 2usize)?;
+            state.serialize_field(
+                "vendor_guid",
+                &SerdeField::<kind::Guid, _>::new(&self.vendor_guid()),
+            )?;
+            state.serialize_field(
+                "vendor_defined_data",
+                &SerdeField::<kind::HexBytes, _>::new(&self.vendor_defined_data()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Vendor {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -326,11 +451,32 @@ pub mod hardware {
     impl fmt::Debug for Controller {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Controller")
-                .field("controller_number", &{ self.controller_number })
+                .field("controller_number", &self.controller_number())
                 .finish()
         }
     }
 
+    impl fmt::Display for Controller {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("Ctrl(")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.controller_number(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Controller {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Controller", 1usize)?;
+            state.serialize_field(
+                "controller_number",
+                &SerdeField::<kind::Hex, _>::new(&self.controller_number()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Controller {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -378,12 +524,39 @@ pub mod hardware {
     impl fmt::Debug for Bmc {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Bmc")
-                .field("interface_type", &{ self.interface_type })
-                .field("base_address", &{ self.base_address })
+                .field("interface_type", &self.interface_type())
+                .field("base_address", &self.base_address())
                 .finish()
         }
     }
 
+    impl fmt::Display for Bmc {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("BMC(")?;
+            <kind::Enum as FormatField<_>>::fmt_field(&self.interface_type(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.base_address(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Bmc {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Bmc", 2usize)?;
+            state.serialize_field(
+                "interface_type",
+                &SerdeField::<kind::Enum, _>::new(&self.interface_type()),
+            )?;
+            state.serialize_field(
+                "base_address",
+                &SerdeField::<kind::Hex, _>::new(&self.base_address()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Bmc {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -438,12 +611,23 @@ pub mod acpi {
     impl fmt::Debug for Acpi {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Acpi")
-                .field("hid", &{ self.hid })
-                .field("uid", &{ self.uid })
+                .field("hid", &self.hid())
+                .field("uid", &self.uid())
                 .finish()
         }
     }
 
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Acpi {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Acpi", 2usize)?;
+            state.serialize_field("hid", &SerdeField::<kind::Hex, _>::new(&self.hid()))?;
+            state.serialize_field("uid", &SerdeField::<kind::Hex, _>::new(&self.uid()))?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Acpi {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -525,10 +709,12 @@ pub mod acpi {
     impl fmt::Debug for Expanded {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Expanded")
-                .field("hid", &{ self.hid })
-                .field("uid", &{ self.uid })
-                .field("cid", &{ self.cid })
-                .field("data", &&self.data)
+                .field("hid", &self.hid())
+                .field("uid", &self.uid())
+                .field("cid", &self.cid())
+                .field("hid_str", &self.hid_str())
+                .field("uid_str", &self.uid_str())
+                .field("cid_str", &self.cid_str())
                 .finish()
         }
     }
@@ -579,14 +765,7 @@ pub mod acpi {
 
     impl fmt::Debug for Adr {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            f.debug_struct("Adr")
-                .field("adr", {
-                    let ptr = addr_of!(self.adr);
-                    let (ptr, len) = ptr_meta::to_raw_parts(ptr);
-                    let byte_len = size_of::<u32>() * len;
-                    unsafe { &slice::from_raw_parts(ptr.cast::<u8>(), byte_len) }
-                })
-                .finish()
+            f.debug_struct("Adr").field("adr", &self.adr()).finish()
         }
     }
 
@@ -631,11 +810,32 @@ pub mod acpi {
     impl fmt::Debug for Nvdimm {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Nvdimm")
-                .field("nfit_device_handle", &{ self.nfit_device_handle })
+                .field("nfit_device_handle", &self.nfit_device_handle())
                 .finish()
         }
     }
 
+    impl fmt::Display for Nvdimm {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("NVDIMM(")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.nfit_device_handle(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Nvdimm {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Nvdimm", 1usize)?;
+            state.serialize_field(
+                "nfit_device_handle",
+                &SerdeField::<kind::Hex, _>::new(&self.nfit_device_handle()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Nvdimm {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -804,13 +1004,46 @@ pub mod messaging {
     impl fmt::Debug for Atapi {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Atapi")
-                .field("primary_secondary", &{ self.primary_secondary })
-                .field("master_slave", &{ self.master_slave })
-                .field("logical_unit_number", &{ self.logical_unit_number })
+                .field("primary_secondary", &self.primary_secondary())
+                .field("master_slave", &self.master_slave())
+                .field("logical_unit_number", &self.logical_unit_number())
                 .finish()
         }
     }
 
+    impl fmt::Display for Atapi {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("Ata(")?;
+            <kind::Enum as FormatField<_>>::fmt_field(&self.primary_secondary(), f)?;
+            f.write_str(",")?;
+            <kind::Enum as FormatField<_>>::fmt_field(&self.master_slave(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.logical_unit_number(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Atapi {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Atapi", 3usize)?;
+            state.serialize_field(
+                "primary_secondary",
+                &SerdeField::<kind::Enum, _>::new(&self.primary_secondary()),
+            )?;
+            state.serialize_field(
+                "master_slave",
+                &SerdeField::<kind::Enum, _>::new(&self.master_slave()),
+            )?;
+            state.serialize_field(
+                "logical_unit_number",
+                &SerdeField::<kind::Hex, _>::new(&self.logical_unit_number()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Atapi {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -855,12 +1088,39 @@ pub mod messaging {
     impl fmt::Debug for Scsi {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Scsi")
-                .field("target_id", &{ self.target_id })
-                .field("logical_unit_number", &{ self.logical_unit_number })
+                .field("target_id", &self.target_id())
+                .field("logical_unit_number", &self.logical_unit_number())
                 .finish()
         }
     }
 
+    impl fmt::Display for Scsi {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("Scsi(")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.target_id(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.logical_unit_number(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Scsi {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Scsi", 2usize)?;
+            state.serialize_field(
+                "target_id",
+                &SerdeField::<kind::Hex, _>::new(&self.target_id()),
+            )?;
+            state.serialize_field(
+                "logical_unit_number",
+                &SerdeField::<kind::Hex, _>::new(&self.logical_unit_number()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Scsi {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -906,13 +1166,39 @@ pub mod messaging {
     impl fmt::Debug for FibreChannel {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("FibreChannel")
-                .field("_reserved", &{ self._reserved })
-                .field("world_wide_name", &{ self.world_wide_name })
-                .field("logical_unit_number", &{ self.logical_unit_number })
+                .field("world_wide_name", &self.world_wide_name())
+                .field("logical_unit_number", &self.logical_unit_number())
                 .finish()
         }
     }
 
+    impl fmt::Display for FibreChannel {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("Fibre(")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.world_wide_name(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.logical_unit_number(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for FibreChannel {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("FibreChannel", 2usize)?;
+            state.serialize_field(
+                "world_wide_name",
+                &SerdeField::<kind::Hex, _>::new(&self.world_wide_name()),
+            )?;
+            state.serialize_field(
+                "logical_unit_number",
+                &SerdeField::<kind::Hex, _>::new(&self.logical_unit_number()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &FibreChannel {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -958,13 +1244,39 @@ pub mod messaging {
     impl fmt::Debug for FibreChannelEx {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("FibreChannelEx")
-                .field("_reserved", &{ self._reserved })
-                .field("world_wide_name", &{ self.world_wide_name })
-                .field("logical_unit_number", &{ self.logical_unit_number })
+                .field("world_wide_name", &self.world_wide_name())
+                .field("logical_unit_number", &self.logical_unit_number())
                 .finish()
         }
     }
 
+    impl fmt::Display for FibreChannelEx {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("FibreEx(")?;
+            <kind::HexBytes as FormatField<_>>::fmt_field(&self.world_wide_name(), f)?;
+            f.write_str(",")?;
+            <kind::HexBytes as FormatField<_>>::fmt_field(&self.logical_unit_number(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for FibreChannelEx {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("FibreChannelEx", 2usize)?;
+            state.serialize_field(
+                "world_wide_name",
+                &SerdeField::<kind::HexBytes, _>::new(&self.world_wide_name()),
+            )?;
+            state.serialize_field(
+                "logical_unit_number",
+                &SerdeField::<kind::HexBytes, _>::new(&self.logical_unit_number()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &FibreChannelEx {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -1004,12 +1316,29 @@ pub mod messaging {
     impl fmt::Debug for Ieee1394 {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Ieee1394")
-                .field("_reserved", &{ self._reserved })
-                .field("guid", &{ self.guid })
+                .field("guid", &self.guid())
                 .finish()
         }
     }
 
+    impl fmt::Display for Ieee1394 {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("I1394(")?;
+            <kind::HexBytes as FormatField<_>>::fmt_field(&self.guid(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Ieee1394 {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Ieee1394", 1usize)?;
+            state.serialize_field("guid", &SerdeField::<kind::HexBytes, _>::new(&self.guid()))?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Ieee1394 {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -1054,12 +1383,39 @@ pub mod messaging {
     impl fmt::Debug for Usb {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Usb")
-                .field("parent_port_number", &{ self.parent_port_number })
-                .field("interface", &{ self.interface })
+                .field("parent_port_number", &self.parent_port_number())
+                .field("interface", &self.interface())
                 .finish()
         }
     }
 
+    impl fmt::Display for Usb {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("USB(")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.parent_port_number(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.interface(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Usb {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Usb", 2usize)?;
+            state.serialize_field(
+                "parent_port_number",
+                &SerdeField::<kind::Hex, _>::new(&self.parent_port_number()),
+            )?;
+            state.serialize_field(
+                "interface",
+                &SerdeField::<kind::Hex, _>::new(&self.interface()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Usb {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -1114,15 +1470,49 @@ pub mod messaging {
     impl fmt::Debug for Sata {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Sata")
-                .field("hba_port_number", &{ self.hba_port_number })
-                .field("port_multiplier_port_number", &{
-                    self.port_multiplier_port_number
-                })
-                .field("logical_unit_number", &{ self.logical_unit_number })
+                .field("hba_port_number", &self.hba_port_number())
+                .field(
+                    "port_multiplier_port_number",
+                    &self.port_multiplier_port_number(),
+                )
+                .field("logical_unit_number", &self.logical_unit_number())
                 .finish()
         }
     }
 
+    impl fmt::Display for Sata {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("Sata(")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.hba_port_number(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.port_multiplier_port_number(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.logical_unit_number(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Sata {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Sata", 3usize)?;
+            state.serialize_field(
+                "hba_port_number",
+                &SerdeField::<kind::Hex, _>::new(&self.hba_port_number()),
+            )?;
+            state.serialize_field(
+                "port_multiplier_port_number",
+                &SerdeField::<kind::Hex, _>::new(&self.port_multiplier_port_number()),
+            )?;
+            state.serialize_field(
+                "logical_unit_number",
+                &SerdeField::<kind::Hex, _>::new(&self.logical_unit_number()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Sata {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -1184,19 +1574,53 @@ pub mod messaging {
     impl fmt::Debug for UsbWwid {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("UsbWwid")
-                .field("interface_number", &{ self.interface_number })
-                .field("device_vendor_id", &{ self.device_vendor_id })
-                .field("device_product_id", &{ self.device_product_id })
-                .field("serial_number", {
-                    let ptr = addr_of!(self.serial_number);
-                    let (ptr, len) = ptr_meta::to_raw_parts(ptr);
-                    let byte_len = size_of::<u16>() * len;
-                    unsafe { &slice::from_raw_parts(ptr.cast::<u8>(), byte_len) }
-                })
+                .field("interface_number", &self.interface_number())
+                .field("device_vendor_id", &self.device_vendor_id())
+                .field("device_product_id", &self.device_product_id())
+                .field("serial_number", &DebugUcs2(self.serial_number()))
                 .finish()
         }
     }
 
+    impl fmt::Display for UsbWwid {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("UsbWwid(")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.device_vendor_id(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.device_product_id(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.interface_number(), f)?;
+            f.write_str(",")?;
+            <kind::Str as FormatField<_>>::fmt_field(&self.serial_number(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for UsbWwid {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("UsbWwid", 4usize)?;
+            state.serialize_field(
+                "interface_number",
+                &SerdeField::<kind::Hex, _>::new(&self.interface_number()),
+            )?;
+            state.serialize_field(
+                "device_vendor_id",
+                &SerdeField::<kind::Hex, _>::new(&self.device_vendor_id()),
+            )?;
+            state.serialize_field(
+                "device_product_id",
+                &SerdeField::<kind::Hex, _>::new(&self.device_product_id()),
+            )?;
+            state.serialize_field(
+                "serial_number",
+                &SerdeField::<kind::Str, _>::new(&self.serial_number()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &UsbWwid {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -1239,11 +1663,32 @@ pub mod messaging {
     impl fmt::Debug for DeviceLogicalUnit {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("DeviceLogicalUnit")
-                .field("logical_unit_number", &{ self.logical_unit_number })
+                .field("logical_unit_number", &self.logical_unit_number())
                 .finish()
         }
     }
 
+    impl fmt::Display for DeviceLogicalUnit {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("Unit(")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.logical_unit_number(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for DeviceLogicalUnit {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("DeviceLogicalUnit", 1usize)?;
+            state.serialize_field(
+                "logical_unit_number",
+                &SerdeField::<kind::Hex, _>::new(&self.logical_unit_number()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &DeviceLogicalUnit {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -1309,15 +1754,60 @@ pub mod messaging {
     impl fmt::Debug for UsbClass {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("UsbClass")
-                .field("vendor_id", &{ self.vendor_id })
-                .field("product_id", &{ self.product_id })
-                .field("device_class", &{ self.device_class })
-                .field("device_subclass", &{ self.device_subclass })
-                .field("device_protocol", &{ self.device_protocol })
+                .field("vendor_id", &self.vendor_id())
+                .field("product_id", &self.product_id())
+                .field("device_class", &self.device_class())
+                .field("device_subclass", &self.device_subclass())
+                .field("device_protocol", &self.device_protocol())
                 .finish()
         }
     }
 
+    impl fmt::Display for UsbClass {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("UsbClass(")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.vendor_id(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.product_id(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.device_class(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.device_subclass(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.device_protocol(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for UsbClass {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("UsbClass", 5usize)?;
+            state.serialize_field(
+                "vendor_id",
+                &SerdeField::<kind::Hex, _>::new(&self.vendor_id()),
+            )?;
+            state.serialize_field(
+                "product_id",
+                &SerdeField::<kind::Hex, _>::new(&self.product_id()),
+            )?;
+            state.serialize_field(
+                "device_class",
+                &SerdeField::<kind::Hex, _>::new(&self.device_class()),
+            )?;
+            state.serialize_field(
+                "device_subclass",
+                &SerdeField::<kind::Hex, _>::new(&self.device_subclass()),
+            )?;
+            state.serialize_field(
+                "device_protocol",
+                &SerdeField::<kind::Hex, _>::new(&self.device_protocol()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &UsbClass {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -1355,11 +1845,32 @@ pub mod messaging {
     impl fmt::Debug for I2o {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("I2o")
-                .field("target_id", &{ self.target_id })
+                .field("target_id", &self.target_id())
                 .finish()
         }
     }
 
+    impl fmt::Display for I2o {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("I2O(")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.target_id(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for I2o {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("I2o", 1usize)?;
+            state.serialize_field(
+                "target_id",
+                &SerdeField::<kind::Hex, _>::new(&self.target_id()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &I2o {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -1405,12 +1916,29 @@ pub mod messaging {
     impl fmt::Debug for MacAddress {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("MacAddress")
-                .field("mac_address", &{ self.mac_address })
-                .field("interface_type", &{ self.interface_type })
+                .field("mac_address", &self.mac_address())
+                .field("interface_type", &self.interface_type())
                 .finish()
         }
     }
 
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for MacAddress {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("MacAddress", 2usize)?;
+            state.serialize_field(
+                "mac_address",
+                &SerdeField::<kind::HexBytes, _>::new(&self.mac_address()),
+            )?;
+            state.serialize_field(
+                "interface_type",
+                &SerdeField::<kind::Hex, _>::new(&self.interface_type()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &MacAddress {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -1498,18 +2026,59 @@ pub mod messaging {
     impl fmt::Debug for Ipv4 {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Ipv4")
-                .field("local_ip_address", &{ self.local_ip_address })
-                .field("remote_ip_address", &{ self.remote_ip_address })
-                .field("local_port", &{ self.local_port })
-                .field("remote_port", &{ self.remote_port })
-                .field("protocol", &{ self.protocol })
-                .field("ip_address_origin", &{ self.ip_address_origin })
-                .field("gateway_ip_address", &{ self.gateway_ip_address })
-                .field("subnet_mask", &{ self.subnet_mask })
+                .field("local_ip_address", &self.local_ip_address())
+                .field("remote_ip_address", &self.remote_ip_address())
+                .field("local_port", &self.local_port())
+                .field("remote_port", &self.remote_port())
+                .field("protocol", &self.protocol())
+                .field("ip_address_origin", &self.ip_address_origin())
+                .field("gateway_ip_address", &self.gateway_ip_address())
+                .field("subnet_mask", &self.subnet_mask())
                 .finish()
         }
     }
 
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Ipv4 {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Ipv4", 8usize)?;
+            state.serialize_field(
+                "local_ip_address",
+                &SerdeField::<kind::HexBytes, _>::new(&self.local_ip_address()),
+            )?;
+            state.serialize_field(
+                "remote_ip_address",
+                &SerdeField::<kind::HexBytes, _>::new(&self.remote_ip_address()),
+            )?;
+            state.serialize_field(
+                "local_port",
+                &SerdeField::<kind::Hex, _>::new(&self.local_port()),
+            )?;
+            state.serialize_field(
+                "remote_port",
+                &SerdeField::<kind::Hex, _>::new(&self.remote_port()),
+            )?;
+            state.serialize_field(
+                "protocol",
+                &SerdeField::<kind::Hex, _>::new(&self.protocol()),
+            )?;
+            state.serialize_field(
+                "ip_address_origin",
+                &SerdeField::<kind::Enum, _>::new(&self.ip_address_origin()),
+            )?;
+            state.serialize_field(
+                "gateway_ip_address",
+                &SerdeField::<kind::HexBytes, _>::new(&self.gateway_ip_address()),
+            )?;
+            state.serialize_field(
+                "subnet_mask",
+                &SerdeField::<kind::HexBytes, _>::new(&self.subnet_mask()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Ipv4 {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -1597,18 +2166,59 @@ pub mod messaging {
     impl fmt::Debug for Ipv6 {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Ipv6")
-                .field("local_ip_address", &{ self.local_ip_address })
-                .field("remote_ip_address", &{ self.remote_ip_address })
-                .field("local_port", &{ self.local_port })
-                .field("remote_port", &{ self.remote_port })
-                .field("protocol", &{ self.protocol })
-                .field("ip_address_origin", &{ self.ip_address_origin })
-                .field("prefix_length", &{ self.prefix_length })
-                .field("gateway_ip_address", &{ self.gateway_ip_address })
+                .field("local_ip_address", &self.local_ip_address())
+                .field("remote_ip_address", &self.remote_ip_address())
+                .field("local_port", &self.local_port())
+                .field("remote_port", &self.remote_port())
+                .field("protocol", &self.protocol())
+                .field("ip_address_origin", &self.ip_address_origin())
+                .field("prefix_length", &self.prefix_length())
+                .field("gateway_ip_address", &self.gateway_ip_address())
                 .finish()
         }
     }
 
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Ipv6 {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Ipv6", 8usize)?;
+            state.serialize_field(
+                "local_ip_address",
+                &SerdeField::<kind::HexBytes, _>::new(&self.local_ip_address()),
+            )?;
+            state.serialize_field(
+                "remote_ip_address",
+                &SerdeField::<kind::HexBytes, _>::new(&self.remote_ip_address()),
+            )?;
+            state.serialize_field(
+                "local_port",
+                &SerdeField::<kind::Hex, _>::new(&self.local_port()),
+            )?;
+            state.serialize_field(
+                "remote_port",
+                &SerdeField::<kind::Hex, _>::new(&self.remote_port()),
+            )?;
+            state.serialize_field(
+                "protocol",
+                &SerdeField::<kind::Hex, _>::new(&self.protocol()),
+            )?;
+            state.serialize_field(
+                "ip_address_origin",
+                &SerdeField::<kind::Enum, _>::new(&self.ip_address_origin()),
+            )?;
+            state.serialize_field(
+                "prefix_length",
+                &SerdeField::<kind::Hex, _>::new(&self.prefix_length()),
+            )?;
+            state.serialize_field(
+                "gateway_ip_address",
+                &SerdeField::<kind::HexBytes, _>::new(&self.gateway_ip_address()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Ipv6 {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -1646,11 +2256,32 @@ pub mod messaging {
     impl fmt::Debug for Vlan {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Vlan")
-                .field("vlan_id", &{ self.vlan_id })
+                .field("vlan_id", &self.vlan_id())
                 .finish()
         }
     }
 
+    impl fmt::Display for Vlan {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("Vlan(")?;
+            <kind::Decimal as FormatField<_>>::fmt_field(&self.vlan_id(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Vlan {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Vlan", 1usize)?;
+            state.serialize_field(
+                "vlan_id",
+                &SerdeField::<kind::Decimal, _>::new(&self.vlan_id()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Vlan {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -1718,15 +2349,60 @@ pub mod messaging {
     impl fmt::Debug for Infiniband {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Infiniband")
-                .field("resource_flags", &{ self.resource_flags })
-                .field("port_gid", &{ self.port_gid })
-                .field("ioc_guid_or_service_id", &{ self.ioc_guid_or_service_id })
-                .field("target_port_id", &{ self.target_port_id })
-                .field("device_id", &{ self.device_id })
+                .field("resource_flags", &self.resource_flags())
+                .field("port_gid", &self.port_gid())
+                .field("ioc_guid_or_service_id", &self.ioc_guid_or_service_id())
+                .field("target_port_id", &self.target_port_id())
+                .field("device_id", &self.device_id())
                 .finish()
         }
     }
 
+    impl fmt::Display for Infiniband {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("Infiniband(")?;
+            <kind::Enum as FormatField<_>>::fmt_field(&self.resource_flags(), f)?;
+            f.write_str(",")?;
+            <kind::GuidBytes as FormatField<_>>::fmt_field(&self.port_gid(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.ioc_guid_or_service_id(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.target_port_id(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.device_id(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Infiniband {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Infiniband", 5usize)?;
+            state.serialize_field(
+                "resource_flags",
+                &SerdeField::<kind::Enum, _>::new(&self.resource_flags()),
+            )?;
+            state.serialize_field(
+                "port_gid",
+                &SerdeField::<kind::GuidBytes, _>::new(&self.port_gid()),
+            )?;
+            state.serialize_field(
+                "ioc_guid_or_service_id",
+                &SerdeField::<kind::Hex, _>::new(&self.ioc_guid_or_service_id()),
+            )?;
+            state.serialize_field(
+                "target_port_id",
+                &SerdeField::<kind::Hex, _>::new(&self.target_port_id()),
+            )?;
+            state.serialize_field(
+                "device_id",
+                &SerdeField::<kind::Hex, _>::new(&self.device_id()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Infiniband {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -1786,15 +2462,50 @@ pub mod messaging {
     impl fmt::Debug for Uart {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Uart")
-                .field("_reserved", &{ self._reserved })
-                .field("baud_rate", &{ self.baud_rate })
-                .field("data_bits", &{ self.data_bits })
-                .field("parity", &{ self.parity })
-                .field("stop_bits", &{ self.stop_bits })
+                .field("baud_rate", &self.baud_rate())
+                .field("data_bits", &self.data_bits())
+                .field("parity", &self.parity())
+                .field("stop_bits", &self.stop_bits())
                 .finish()
         }
     }
 
+    impl fmt::Display for Uart {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("Uart(")?;
+            <kind::Decimal as FormatField<_>>::fmt_field(&self.baud_rate(), f)?;
+            f.write_str(",")?;
+            <kind::Decimal as FormatField<_>>::fmt_field(&self.data_bits(), f)?;
+            f.write_str(",")?;
+            <kind::Enum as FormatField<_>>::fmt_field(&self.parity(), f)?;
+            f.write_str(",")?;
+            <kind::Enum as FormatField<_>>::fmt_field(&self.stop_bits(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Uart {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Uart", 4usize)?;
+            state.serialize_field(
+                "baud_rate",
+                &SerdeField::<kind::Decimal, _>::new(&self.baud_rate()),
+            )?;
+            state.serialize_field(
+                "data_bits",
+                &SerdeField::<kind::Decimal, _>::new(&self.data_bits()),
+            )?;
+            state.serialize_field("parity", &SerdeField::<kind::Enum, _>::new(&self.parity()))?;
+            state.serialize_field(
+                "stop_bits",
+                &SerdeField::<kind::Enum, _>::new(&self.stop_bits()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Uart {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -1840,17 +2551,29 @@ pub mod messaging {
     impl fmt::Debug for Vendor {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Vendor")
-                .field("vendor_guid", &{ self.vendor_guid })
-                .field("vendor_defined_data", {
-                    let ptr = addr_of!(self.vendor_defined_data);
-                    let (ptr, len) = ptr_meta::to_raw_parts(ptr);
-                    let byte_len = size_of::<u8>() * len;
-                    unsafe { &slice::from_raw_parts(ptr.cast::<u8>(), byte_len) }
-                })
+                .field("vendor_guid", &self.vendor_guid())
+                .field("vendor_defined_data", &self.vendor_defined_data())
                 .finish()
         }
     }
 
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Vendor {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Vendor", 2usize)?;
+            state.serialize_field(
+                "vendor_guid",
+                &SerdeField::<kind::Guid, _>::new(&self.vendor_guid()),
+            )?;
+            state.serialize_field(
+                "vendor_defined_data",
+                &SerdeField::<kind::HexBytes, _>::new(&self.vendor_defined_data()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Vendor {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -1914,14 +2637,36 @@ pub mod messaging {
     impl fmt::Debug for SasEx {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("SasEx")
-                .field("sas_address", &{ self.sas_address })
-                .field("logical_unit_number", &{ self.logical_unit_number })
-                .field("info", &{ self.info })
-                .field("relative_target_port", &{ self.relative_target_port })
+                .field("sas_address", &self.sas_address())
+                .field("logical_unit_number", &self.logical_unit_number())
+                .field("info", &self.info())
+                .field("relative_target_port", &self.relative_target_port())
                 .finish()
         }
     }
 
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for SasEx {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("SasEx", 4usize)?;
+            state.serialize_field(
+                "sas_address",
+                &SerdeField::<kind::HexBytes, _>::new(&self.sas_address()),
+            )?;
+            state.serialize_field(
+                "logical_unit_number",
+                &SerdeField::<kind::HexBytes, _>::new(&self.logical_unit_number()),
+            )?;
+            state.serialize_field("info", &SerdeField::<kind::Hex, _>::new(&self.info()))?;
+            state.serialize_field(
+                "relative_target_port",
+                &SerdeField::<kind::Hex, _>::new(&self.relative_target_port()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &SasEx {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -1993,20 +2738,44 @@ pub mod messaging {
     impl fmt::Debug for Iscsi {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Iscsi")
-                .field("protocol", &{ self.protocol })
-                .field("options", &{ self.options })
-                .field("logical_unit_number", &{ self.logical_unit_number })
-                .field("target_portal_group_tag", &{ self.target_portal_group_tag })
-                .field("iscsi_target_name", {
-                    let ptr = addr_of!(self.iscsi_target_name);
-                    let (ptr, len) = ptr_meta::to_raw_parts(ptr);
-                    let byte_len = size_of::<u8>() * len;
-                    unsafe { &slice::from_raw_parts(ptr.cast::<u8>(), byte_len) }
-                })
+                .field("protocol", &self.protocol())
+                .field("options", &self.options())
+                .field("logical_unit_number", &self.logical_unit_number())
+                .field("target_portal_group_tag", &self.target_portal_group_tag())
+                .field("iscsi_target_name", &self.iscsi_target_name())
                 .finish()
         }
     }
 
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Iscsi {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Iscsi", 5usize)?;
+            state.serialize_field(
+                "protocol",
+                &SerdeField::<kind::Enum, _>::new(&self.protocol()),
+            )?;
+            state.serialize_field(
+                "options",
+                &SerdeField::<kind::Enum, _>::new(&self.options()),
+            )?;
+            state.serialize_field(
+                "logical_unit_number",
+                &SerdeField::<kind::HexBytes, _>::new(&self.logical_unit_number()),
+            )?;
+            state.serialize_field(
+                "target_portal_group_tag",
+                &SerdeField::<kind::Hex, _>::new(&self.target_portal_group_tag()),
+            )?;
+            state.serialize_field(
+                "iscsi_target_name",
+                &SerdeField::<kind::HexBytes, _>::new(&self.iscsi_target_name()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Iscsi {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -2058,14 +2827,42 @@ pub mod messaging {
     impl fmt::Debug for NvmeNamespace {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("NvmeNamespace")
-                .field("namespace_identifier", &{ self.namespace_identifier })
-                .field("ieee_extended_unique_identifier", &{
-                    self.ieee_extended_unique_identifier
-                })
+                .field("namespace_identifier", &self.namespace_identifier())
+                .field(
+                    "ieee_extended_unique_identifier",
+                    &self.ieee_extended_unique_identifier(),
+                )
                 .finish()
         }
     }
 
+    impl fmt::Display for NvmeNamespace {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("NVMe(")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.namespace_identifier(), f)?;
+            f.write_str(",")?;
+            <kind::Eui64 as FormatField<_>>::fmt_field(&self.ieee_extended_unique_identifier(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for NvmeNamespace {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("NvmeNamespace", 2usize)?;
+            state.serialize_field(
+                "namespace_identifier",
+                &SerdeField::<kind::Hex, _>::new(&self.namespace_identifier()),
+            )?;
+            state.serialize_field(
+                "ieee_extended_unique_identifier",
+                &SerdeField::<kind::Eui64, _>::new(&self.ieee_extended_unique_identifier()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &NvmeNamespace {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -2104,16 +2901,29 @@ pub mod messaging {
     impl fmt::Debug for Uri {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Uri")
-                .field("value", {
-                    let ptr = addr_of!(self.value);
-                    let (ptr, len) = ptr_meta::to_raw_parts(ptr);
-                    let byte_len = size_of::<u8>() * len;
-                    unsafe { &slice::from_raw_parts(ptr.cast::<u8>(), byte_len) }
-                })
+                .field("value", &DebugAscii(self.value()))
                 .finish()
         }
     }
 
+    impl fmt::Display for Uri {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("Uri(")?;
+            <kind::Ascii as FormatField<_>>::fmt_field(&self.value(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Uri {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Uri", 1usize)?;
+            state.serialize_field("value", &SerdeField::<kind::Ascii, _>::new(&self.value()))?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Uri {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -2163,12 +2973,39 @@ pub mod messaging {
     impl fmt::Debug for Ufs {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Ufs")
-                .field("target_id", &{ self.target_id })
-                .field("logical_unit_number", &{ self.logical_unit_number })
+                .field("target_id", &self.target_id())
+                .field("logical_unit_number", &self.logical_unit_number())
                 .finish()
         }
     }
 
+    impl fmt::Display for Ufs {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("UFS(")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.target_id(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.logical_unit_number(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Ufs {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Ufs", 2usize)?;
+            state.serialize_field(
+                "target_id",
+                &SerdeField::<kind::Hex, _>::new(&self.target_id()),
+            )?;
+            state.serialize_field(
+                "logical_unit_number",
+                &SerdeField::<kind::Hex, _>::new(&self.logical_unit_number()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Ufs {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -2206,11 +3043,32 @@ pub mod messaging {
     impl fmt::Debug for Sd {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Sd")
-                .field("slot_number", &{ self.slot_number })
+                .field("slot_number", &self.slot_number())
                 .finish()
         }
     }
 
+    impl fmt::Display for Sd {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("SD(")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.slot_number(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Sd {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Sd", 1usize)?;
+            state.serialize_field(
+                "slot_number",
+                &SerdeField::<kind::Hex, _>::new(&self.slot_number()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Sd {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -2248,11 +3106,32 @@ pub mod messaging {
     impl fmt::Debug for Bluetooth {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Bluetooth")
-                .field("device_address", &{ self.device_address })
+                .field("device_address", &self.device_address())
                 .finish()
         }
     }
 
+    impl fmt::Display for Bluetooth {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("Bluetooth(")?;
+            <kind::HexBytes as FormatField<_>>::fmt_field(&self.device_address(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Bluetooth {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Bluetooth", 1usize)?;
+            state.serialize_field(
+                "device_address",
+                &SerdeField::<kind::HexBytes, _>::new(&self.device_address()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Bluetooth {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -2290,11 +3169,29 @@ pub mod messaging {
     impl fmt::Debug for Wifi {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Wifi")
-                .field("ssid", &{ self.ssid })
+                .field("ssid", &DebugAscii(&self.ssid()))
                 .finish()
         }
     }
 
+    impl fmt::Display for Wifi {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("Wi-Fi(")?;
+            <kind::Ascii as FormatField<_>>::fmt_field(&self.ssid(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Wifi {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Wifi", 1usize)?;
+            state.serialize_field("ssid", &SerdeField::<kind::Ascii, _>::new(&self.ssid()))?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Wifi {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -2332,11 +3229,32 @@ pub mod messaging {
     impl fmt::Debug for Emmc {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Emmc")
-                .field("slot_number", &{ self.slot_number })
+                .field("slot_number", &self.slot_number())
                 .finish()
         }
     }
 
+    impl fmt::Display for Emmc {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("eMMC(")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.slot_number(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Emmc {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Emmc", 1usize)?;
+            state.serialize_field(
+                "slot_number",
+                &SerdeField::<kind::Hex, _>::new(&self.slot_number()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Emmc {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -2381,12 +3299,39 @@ pub mod messaging {
     impl fmt::Debug for BluetoothLe {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("BluetoothLe")
-                .field("device_address", &{ self.device_address })
-                .field("address_type", &{ self.address_type })
+                .field("device_address", &self.device_address())
+                .field("address_type", &self.address_type())
                 .finish()
         }
     }
 
+    impl fmt::Display for BluetoothLe {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("BluetoothLE(")?;
+            <kind::HexBytes as FormatField<_>>::fmt_field(&self.device_address(), f)?;
+            f.write_str(",")?;
+            <kind::Enum as FormatField<_>>::fmt_field(&self.address_type(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for BluetoothLe {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("BluetoothLe", 2usize)?;
+            state.serialize_field(
+                "device_address",
+                &SerdeField::<kind::HexBytes, _>::new(&self.device_address()),
+            )?;
+            state.serialize_field(
+                "address_type",
+                &SerdeField::<kind::Enum, _>::new(&self.address_type()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &BluetoothLe {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -2434,13 +3379,8 @@ pub mod messaging {
     impl fmt::Debug for Dns {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Dns")
-                .field("address_type", &{ self.address_type })
-                .field("addresses", {
-                    let ptr = addr_of!(self.addresses);
-                    let (ptr, len) = ptr_meta::to_raw_parts(ptr);
-                    let byte_len = size_of::<IpAddress>() * len;
-                    unsafe { &slice::from_raw_parts(ptr.cast::<u8>(), byte_len) }
-                })
+                .field("address_type", &self.address_type())
+                .field("addresses", &self.addresses())
                 .finish()
         }
     }
@@ -2487,11 +3427,29 @@ pub mod messaging {
     impl fmt::Debug for NvdimmNamespace {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("NvdimmNamespace")
-                .field("uuid", &{ self.uuid })
+                .field("uuid", &self.uuid())
                 .finish()
         }
     }
 
+    impl fmt::Display for NvdimmNamespace {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("NvdimmNamespace(")?;
+            <kind::HexBytes as FormatField<_>>::fmt_field(&self.uuid(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for NvdimmNamespace {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("NvdimmNamespace", 1usize)?;
+            state.serialize_field("uuid", &SerdeField::<kind::HexBytes, _>::new(&self.uuid()))?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &NvdimmNamespace {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -2538,8 +3496,8 @@ pub mod messaging {
     impl fmt::Debug for RestService {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("RestService")
-                .field("service_type", &{ self.service_type })
-                .field("access_mode", &{ self.access_mode })
+                .field("service_type", &self.service_type())
+                .field("access_mode", &self.access_mode())
                 .field("vendor_guid_and_data", {
                     let ptr = addr_of!(self.vendor_guid_and_data);
                     let (ptr, len) = ptr_meta::to_raw_parts(ptr);
@@ -2609,18 +3567,40 @@ pub mod messaging {
     impl fmt::Debug for NvmeOfNamespace {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("NvmeOfNamespace")
-                .field("nidt", &{ self.nidt })
-                .field("nid", &{ self.nid })
-                .field("subsystem_nqn", {
-                    let ptr = addr_of!(self.subsystem_nqn);
-                    let (ptr, len) = ptr_meta::to_raw_parts(ptr);
-                    let byte_len = size_of::<u8>() * len;
-                    unsafe { &slice::from_raw_parts(ptr.cast::<u8>(), byte_len) }
-                })
+                .field("nidt", &self.nidt())
+                .field("nid", &self.nid())
+                .field("subsystem_nqn", &DebugAscii(self.subsystem_nqn()))
                 .finish()
         }
     }
 
+    impl fmt::Display for NvmeOfNamespace {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("NVMEoF(")?;
+            <kind::CStr8 as FormatField<_>>::fmt_field(&self.subsystem_nqn(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.nidt(), f)?;
+            f.write_str(",")?;
+            <kind::HexBytes as FormatField<_>>::fmt_field(&self.nid(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for NvmeOfNamespace {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("NvmeOfNamespace", 3usize)?;
+            state.serialize_field("nidt", &SerdeField::<kind::Hex, _>::new(&self.nidt()))?;
+            state.serialize_field("nid", &SerdeField::<kind::HexBytes, _>::new(&self.nid()))?;
+            state.serialize_field(
+                "subsystem_nqn",
+                &SerdeField::<kind::CStr8, _>::new(&self.subsystem_nqn()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &NvmeOfNamespace {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -2773,11 +3753,11 @@ pub mod media {
     impl fmt::Debug for HardDrive {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("HardDrive")
-                .field("partition_number", &{ self.partition_number })
-                .field("partition_start", &{ self.partition_start })
-                .field("partition_size", &{ self.partition_size })
+                .field("partition_number", &self.partition_number())
+                .field("partition_start", &self.partition_start())
+                .field("partition_size", &self.partition_size())
                 .field("partition_signature", &{ self.partition_signature })
-                .field("partition_format", &{ self.partition_format })
+                .field("partition_format", &self.partition_format())
                 .field("signature_type", &{ self.signature_type })
                 .finish()
         }
@@ -2835,13 +3815,46 @@ pub mod media {
     impl fmt::Debug for CdRom {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("CdRom")
-                .field("boot_entry", &{ self.boot_entry })
-                .field("partition_start", &{ self.partition_start })
-                .field("partition_size", &{ self.partition_size })
+                .field("boot_entry", &self.boot_entry())
+                .field("partition_start", &self.partition_start())
+                .field("partition_size", &self.partition_size())
                 .finish()
         }
     }
 
+    impl fmt::Display for CdRom {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("CDROM(")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.boot_entry(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.partition_start(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.partition_size(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for CdRom {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("CdRom", 3usize)?;
+            state.serialize_field(
+                "boot_entry",
+                &SerdeField::<kind::Hex, _>::new(&self.boot_entry()),
+            )?;
+            state.serialize_field(
+                "partition_start",
+                &SerdeField::<kind::Hex, _>::new(&self.partition_start()),
+            )?;
+            state.serialize_field(
+                "partition_size",
+                &SerdeField::<kind::Hex, _>::new(&self.partition_size()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &CdRom {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -2887,17 +3900,43 @@ pub mod media {
     impl fmt::Debug for Vendor {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Vendor")
-                .field("vendor_guid", &{ self.vendor_guid })
-                .field("vendor_defined_data", {
-                    let ptr = addr_of!(self.vendor_defined_data);
-                    let (ptr, len) = ptr_meta::to_raw_parts(ptr);
-                    let byte_len = size_of::<u8>() * len;
-                    unsafe { &slice::from_raw_parts(ptr.cast::<u8>(), byte_len) }
-                })
+                .field("vendor_guid", &self.vendor_guid())
+                .field("vendor_defined_data", &self.vendor_defined_data())
                 .finish()
         }
     }
 
+    impl fmt::Display for Vendor {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("VenMedia(")?;
+            <kind::Guid as FormatField<_>>::fmt_field(&self.vendor_guid(), f)?;
+            let value = self.vendor_defined_data();
+            if !value.is_empty() {
+                f.write_str(",")?;
+                <kind::HexBytes as FormatField<_>>::fmt_field(&value, f)?;
+            }
+
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Vendor {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Vendor", 2usize)?;
+            state.serialize_field(
+                "vendor_guid",
+                &SerdeField::<kind::Guid, _>::new(&self.vendor_guid()),
+            )?;
+            state.serialize_field(
+                "vendor_defined_data",
+                &SerdeField::<kind::HexBytes, _>::new(&self.vendor_defined_data()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Vendor {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -2943,12 +3982,7 @@ pub mod media {
     impl fmt::Debug for FilePath {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("FilePath")
-                .field("path_name", {
-                    let ptr = addr_of!(self.path_name);
-                    let (ptr, len) = ptr_meta::to_raw_parts(ptr);
-                    let byte_len = size_of::<u16>() * len;
-                    unsafe { &slice::from_raw_parts(ptr.cast::<u8>(), byte_len) }
-                })
+                .field("path_name", &DebugUcs2(self.path_name()))
                 .finish()
         }
     }
@@ -2995,11 +4029,32 @@ pub mod media {
     impl fmt::Debug for Protocol {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("Protocol")
-                .field("protocol_guid", &{ self.protocol_guid })
+                .field("protocol_guid", &self.protocol_guid())
                 .finish()
         }
     }
 
+    impl fmt::Display for Protocol {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("Media(")?;
+            <kind::Guid as FormatField<_>>::fmt_field(&self.protocol_guid(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for Protocol {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("Protocol", 1usize)?;
+            state.serialize_field(
+                "protocol_guid",
+                &SerdeField::<kind::Guid, _>::new(&self.protocol_guid()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &Protocol {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -3038,16 +4093,29 @@ pub mod media {
     impl fmt::Debug for PiwgFirmwareFile {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("PiwgFirmwareFile")
-                .field("data", {
-                    let ptr = addr_of!(self.data);
-                    let (ptr, len) = ptr_meta::to_raw_parts(ptr);
-                    let byte_len = size_of::<u8>() * len;
-                    unsafe { &slice::from_raw_parts(ptr.cast::<u8>(), byte_len) }
-                })
+                .field("data", &self.data())
                 .finish()
         }
     }
 
+    impl fmt::Display for PiwgFirmwareFile {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("FvFile(")?;
+            <kind::GuidBytes as FormatField<_>>::fmt_field(&self.data(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for PiwgFirmwareFile {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("PiwgFirmwareFile", 1usize)?;
+            state.serialize_field("data", &SerdeField::<kind::GuidBytes, _>::new(&self.data()))?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &PiwgFirmwareFile {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -3092,16 +4160,29 @@ pub mod media {
     impl fmt::Debug for PiwgFirmwareVolume {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("PiwgFirmwareVolume")
-                .field("data", {
-                    let ptr = addr_of!(self.data);
-                    let (ptr, len) = ptr_meta::to_raw_parts(ptr);
-                    let byte_len = size_of::<u8>() * len;
-                    unsafe { &slice::from_raw_parts(ptr.cast::<u8>(), byte_len) }
-                })
+                .field("data", &self.data())
                 .finish()
         }
     }
 
+    impl fmt::Display for PiwgFirmwareVolume {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("Fv(")?;
+            <kind::GuidBytes as FormatField<_>>::fmt_field(&self.data(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for PiwgFirmwareVolume {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("PiwgFirmwareVolume", 1usize)?;
+            state.serialize_field("data", &SerdeField::<kind::GuidBytes, _>::new(&self.data()))?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &PiwgFirmwareVolume {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -3153,13 +4234,39 @@ pub mod media {
     impl fmt::Debug for RelativeOffsetRange {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("RelativeOffsetRange")
-                .field("_reserved", &{ self._reserved })
-                .field("starting_offset", &{ self.starting_offset })
-                .field("ending_offset", &{ self.ending_offset })
+                .field("starting_offset", &self.starting_offset())
+                .field("ending_offset", &self.ending_offset())
                 .finish()
         }
     }
 
+    impl fmt::Display for RelativeOffsetRange {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("Offset(")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.starting_offset(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.ending_offset(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for RelativeOffsetRange {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("RelativeOffsetRange", 2usize)?;
+            state.serialize_field(
+                "starting_offset",
+                &SerdeField::<kind::Hex, _>::new(&self.starting_offset()),
+            )?;
+            state.serialize_field(
+                "ending_offset",
+                &SerdeField::<kind::Hex, _>::new(&self.ending_offset()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &RelativeOffsetRange {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -3218,14 +4325,53 @@ pub mod media {
     impl fmt::Debug for RamDisk {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("RamDisk")
-                .field("starting_address", &{ self.starting_address })
-                .field("ending_address", &{ self.ending_address })
-                .field("disk_type", &{ self.disk_type })
-                .field("disk_instance", &{ self.disk_instance })
+                .field("starting_address", &self.starting_address())
+                .field("ending_address", &self.ending_address())
+                .field("disk_type", &self.disk_type())
+                .field("disk_instance", &self.disk_instance())
                 .finish()
         }
     }
 
+    impl fmt::Display for RamDisk {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("RamDisk(")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.starting_address(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.ending_address(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.disk_instance(), f)?;
+            f.write_str(",")?;
+            <kind::Enum as FormatField<_>>::fmt_field(&self.disk_type(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for RamDisk {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("RamDisk", 4usize)?;
+            state.serialize_field(
+                "starting_address",
+                &SerdeField::<kind::Hex, _>::new(&self.starting_address()),
+            )?;
+            state.serialize_field(
+                "ending_address",
+                &SerdeField::<kind::Hex, _>::new(&self.ending_address()),
+            )?;
+            state.serialize_field(
+                "disk_type",
+                &SerdeField::<kind::Enum, _>::new(&self.disk_type()),
+            )?;
+            state.serialize_field(
+                "disk_instance",
+                &SerdeField::<kind::Hex, _>::new(&self.disk_instance()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &RamDisk {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -3268,6 +4414,7 @@ pub mod media {
 
     /// Hard drive partition signature.
     #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub enum PartitionSignature {
         /// No disk signature.
         None,
@@ -3330,18 +4477,46 @@ pub mod bios_boot_spec {
     impl fmt::Debug for BootSpecification {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             f.debug_struct("BootSpecification")
-                .field("device_type", &{ self.device_type })
-                .field("status_flag", &{ self.status_flag })
-                .field("description_string", {
-                    let ptr = addr_of!(self.description_string);
-                    let (ptr, len) = ptr_meta::to_raw_parts(ptr);
-                    let byte_len = size_of::<u8>() * len;
-                    unsafe { &slice::from_raw_parts(ptr.cast::<u8>(), byte_len) }
-                })
+                .field("device_type", &self.device_type())
+                .field("status_flag", &self.status_flag())
+                .field("description_string", &DebugAscii(self.description_string()))
                 .finish()
         }
     }
 
+    impl fmt::Display for BootSpecification {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("BBS(")?;
+            <kind::BbsDeviceType as FormatField<_>>::fmt_field(&self.device_type(), f)?;
+            f.write_str(",")?;
+            <kind::CStr8 as FormatField<_>>::fmt_field(&self.description_string(), f)?;
+            f.write_str(",")?;
+            <kind::Hex as FormatField<_>>::fmt_field(&self.status_flag(), f)?;
+            f.write_str(")")
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for BootSpecification {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("BootSpecification", 3usize)?;
+            state.serialize_field(
+                "device_type",
+                &SerdeField::<kind::BbsDeviceType, _>::new(&self.device_type()),
+            )?;
+            state.serialize_field(
+                "status_flag",
+                &SerdeField::<kind::Hex, _>::new(&self.status_flag()),
+            )?;
+            state.serialize_field(
+                "description_string",
+                &SerdeField::<kind::CStr8, _>::new(&self.description_string()),
+            )?;
+            state.end()
+        }
+    }
+
     impl TryFrom<&DevicePathNode> for &BootSpecification {
         type Error = NodeConversionError;
         fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -3487,6 +4662,374 @@ pub enum DevicePathNodeEnum<'a> {
     BiosBootSpecBootSpecification(&'a bios_boot_spec::BootSpecification),
 }
 
+impl fmt::Display for DevicePathNodeEnum<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EndInstance(node) => fmt::Display::fmt(node, f),
+            Self::EndEntire(node) => fmt::Display::fmt(node, f),
+            Self::HardwarePci(node) => fmt::Display::fmt(node, f),
+            Self::HardwarePccard(node) => fmt::Display::fmt(node, f),
+            Self::HardwareMemoryMapped(node) => fmt::Display::fmt(node, f),
+            Self::HardwareVendor(node) => fmt::Display::fmt(node, f),
+            Self::HardwareController(node) => fmt::Display::fmt(node, f),
+            Self::HardwareBmc(node) => fmt::Display::fmt(node, f),
+            Self::AcpiAcpi(node) => fmt::Display::fmt(node, f),
+            Self::AcpiExpanded(node) => fmt::Display::fmt(node, f),
+            Self::AcpiAdr(node) => fmt::Display::fmt(node, f),
+            Self::AcpiNvdimm(node) => fmt::Display::fmt(node, f),
+            Self::MessagingAtapi(node) => fmt::Display::fmt(node, f),
+            Self::MessagingScsi(node) => fmt::Display::fmt(node, f),
+            Self::MessagingFibreChannel(node) => fmt::Display::fmt(node, f),
+            Self::MessagingFibreChannelEx(node) => fmt::Display::fmt(node, f),
+            Self::MessagingIeee1394(node) => fmt::Display::fmt(node, f),
+            Self::MessagingUsb(node) => fmt::Display::fmt(node, f),
+            Self::MessagingSata(node) => fmt::Display::fmt(node, f),
+            Self::MessagingUsbWwid(node) => fmt::Display::fmt(node, f),
+            Self::MessagingDeviceLogicalUnit(node) => fmt::Display::fmt(node, f),
+            Self::MessagingUsbClass(node) => fmt::Display::fmt(node, f),
+            Self::MessagingI2o(node) => fmt::Display::fmt(node, f),
+            Self::MessagingMacAddress(node) => fmt::Display::fmt(node, f),
+            Self::MessagingIpv4(node) => fmt::Display::fmt(node, f),
+            Self::MessagingIpv6(node) => fmt::Display::fmt(node, f),
+            Self::MessagingVlan(node) => fmt::Display::fmt(node, f),
+            Self::MessagingInfiniband(node) => fmt::Display::fmt(node, f),
+            Self::MessagingUart(node) => fmt::Display::fmt(node, f),
+            Self::MessagingVendor(node) => fmt::Display::fmt(node, f),
+            Self::MessagingSasEx(node) => fmt::Display::fmt(node, f),
+            Self::MessagingIscsi(node) => fmt::Display::fmt(node, f),
+            Self::MessagingNvmeNamespace(node) => fmt::Display::fmt(node, f),
+            Self::MessagingUri(node) => fmt::Display::fmt(node, f),
+            Self::MessagingUfs(node) => fmt::Display::fmt(node, f),
+            Self::MessagingSd(node) => fmt::Display::fmt(node, f),
+            Self::MessagingBluetooth(node) => fmt::Display::fmt(node, f),
+            Self::MessagingWifi(node) => fmt::Display::fmt(node, f),
+            Self::MessagingEmmc(node) => fmt::Display::fmt(node, f),
+            Self::MessagingBluetoothLe(node) => fmt::Display::fmt(node, f),
+            Self::MessagingDns(node) => fmt::Display::fmt(node, f),
+            Self::MessagingNvdimmNamespace(node) => fmt::Display::fmt(node, f),
+            Self::MessagingRestService(node) => fmt::Display::fmt(node, f),
+            Self::MessagingNvmeOfNamespace(node) => fmt::Display::fmt(node, f),
+            Self::MediaHardDrive(node) => fmt::Display::fmt(node, f),
+            Self::MediaCdRom(node) => fmt::Display::fmt(node, f),
+            Self::MediaVendor(node) => fmt::Display::fmt(node, f),
+            Self::MediaFilePath(node) => fmt::Display::fmt(node, f),
+            Self::MediaProtocol(node) => fmt::Display::fmt(node, f),
+            Self::MediaPiwgFirmwareFile(node) => fmt::Display::fmt(node, f),
+            Self::MediaPiwgFirmwareVolume(node) => fmt::Display::fmt(node, f),
+            Self::MediaRelativeOffsetRange(node) => fmt::Display::fmt(node, f),
+            Self::MediaRamDisk(node) => fmt::Display::fmt(node, f),
+            Self::BiosBootSpecBootSpecification(node) => fmt::Display::fmt(node, f),
+        }
+    }
+}
+
+/// Serializes the node as an enum variant with the same name as
+/// the `DevicePathNodeEnum` variant.
+#[cfg(feature = "serde")]
+impl serde::Serialize for DevicePathNodeEnum<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::EndInstance(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 0u32, "EndInstance", node)
+            }
+            Self::EndEntire(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 1u32, "EndEntire", node)
+            }
+            Self::HardwarePci(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 2u32, "HardwarePci", node)
+            }
+            Self::HardwarePccard(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 3u32, "HardwarePccard", node)
+            }
+            Self::HardwareMemoryMapped(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                4u32,
+                "HardwareMemoryMapped",
+                node,
+            ),
+            Self::HardwareVendor(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 5u32, "HardwareVendor", node)
+            }
+            Self::HardwareController(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                6u32,
+                "HardwareController",
+                node,
+            ),
+            Self::HardwareBmc(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 7u32, "HardwareBmc", node)
+            }
+            Self::AcpiAcpi(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 8u32, "AcpiAcpi", node)
+            }
+            Self::AcpiExpanded(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 9u32, "AcpiExpanded", node)
+            }
+            Self::AcpiAdr(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 10u32, "AcpiAdr", node)
+            }
+            Self::AcpiNvdimm(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 11u32, "AcpiNvdimm", node)
+            }
+            Self::MessagingAtapi(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                12u32,
+                "MessagingAtapi",
+                node,
+            ),
+            Self::MessagingScsi(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 13u32, "MessagingScsi", node)
+            }
+            Self::MessagingFibreChannel(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                14u32,
+                "MessagingFibreChannel",
+                node,
+            ),
+            Self::MessagingFibreChannelEx(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                15u32,
+                "MessagingFibreChannelEx",
+                node,
+            ),
+            Self::MessagingIeee1394(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                16u32,
+                "MessagingIeee1394",
+                node,
+            ),
+            Self::MessagingUsb(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 17u32, "MessagingUsb", node)
+            }
+            Self::MessagingSata(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 18u32, "MessagingSata", node)
+            }
+            Self::MessagingUsbWwid(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                19u32,
+                "MessagingUsbWwid",
+                node,
+            ),
+            Self::MessagingDeviceLogicalUnit(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                20u32,
+                "MessagingDeviceLogicalUnit",
+                node,
+            ),
+            Self::MessagingUsbClass(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                21u32,
+                "MessagingUsbClass",
+                node,
+            ),
+            Self::MessagingI2o(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 22u32, "MessagingI2o", node)
+            }
+            Self::MessagingMacAddress(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                23u32,
+                "MessagingMacAddress",
+                node,
+            ),
+            Self::MessagingIpv4(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 24u32, "MessagingIpv4", node)
+            }
+            Self::MessagingIpv6(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 25u32, "MessagingIpv6", node)
+            }
+            Self::MessagingVlan(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 26u32, "MessagingVlan", node)
+            }
+            Self::MessagingInfiniband(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                27u32,
+                "MessagingInfiniband",
+                node,
+            ),
+            Self::MessagingUart(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 28u32, "MessagingUart", node)
+            }
+            Self::MessagingVendor(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                29u32,
+                "MessagingVendor",
+                node,
+            ),
+            Self::MessagingSasEx(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                30u32,
+                "MessagingSasEx",
+                node,
+            ),
+            Self::MessagingIscsi(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                31u32,
+                "MessagingIscsi",
+                node,
+            ),
+            Self::MessagingNvmeNamespace(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                32u32,
+                "MessagingNvmeNamespace",
+                node,
+            ),
+            Self::MessagingUri(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 33u32, "MessagingUri", node)
+            }
+            Self::MessagingUfs(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 34u32, "MessagingUfs", node)
+            }
+            Self::MessagingSd(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 35u32, "MessagingSd", node)
+            }
+            Self::MessagingBluetooth(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                36u32,
+                "MessagingBluetooth",
+                node,
+            ),
+            Self::MessagingWifi(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 37u32, "MessagingWifi", node)
+            }
+            Self::MessagingEmmc(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 38u32, "MessagingEmmc", node)
+            }
+            Self::MessagingBluetoothLe(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                39u32,
+                "MessagingBluetoothLe",
+                node,
+            ),
+            Self::MessagingDns(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 40u32, "MessagingDns", node)
+            }
+            Self::MessagingNvdimmNamespace(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                41u32,
+                "MessagingNvdimmNamespace",
+                node,
+            ),
+            Self::MessagingRestService(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                42u32,
+                "MessagingRestService",
+                node,
+            ),
+            Self::MessagingNvmeOfNamespace(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                43u32,
+                "MessagingNvmeOfNamespace",
+                node,
+            ),
+            Self::MediaHardDrive(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                44u32,
+                "MediaHardDrive",
+                node,
+            ),
+            Self::MediaCdRom(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 45u32, "MediaCdRom", node)
+            }
+            Self::MediaVendor(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 46u32, "MediaVendor", node)
+            }
+            Self::MediaFilePath(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 47u32, "MediaFilePath", node)
+            }
+            Self::MediaProtocol(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 48u32, "MediaProtocol", node)
+            }
+            Self::MediaPiwgFirmwareFile(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                49u32,
+                "MediaPiwgFirmwareFile",
+                node,
+            ),
+            Self::MediaPiwgFirmwareVolume(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                50u32,
+                "MediaPiwgFirmwareVolume",
+                node,
+            ),
+            Self::MediaRelativeOffsetRange(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                51u32,
+                "MediaRelativeOffsetRange",
+                node,
+            ),
+            Self::MediaRamDisk(node) => {
+                serializer.serialize_newtype_variant("DevicePathNode", 52u32, "MediaRamDisk", node)
+            }
+            Self::BiosBootSpecBootSpecification(node) => serializer.serialize_newtype_variant(
+                "DevicePathNode",
+                53u32,
+                "BiosBootSpecBootSpecification",
+                node,
+            ),
+        }
+    }
+}
+
+/// Names of the [`DevicePathNodeEnum`] variants, in order,
+/// followed by the name used for unknown nodes.
+#[cfg(feature = "serde")]
+pub(super) const NODE_VARIANT_NAMES: &[&str] = &[
+    "EndInstance",
+    "EndEntire",
+    "HardwarePci",
+    "HardwarePccard",
+    "HardwareMemoryMapped",
+    "HardwareVendor",
+    "HardwareController",
+    "HardwareBmc",
+    "AcpiAcpi",
+    "AcpiExpanded",
+    "AcpiAdr",
+    "AcpiNvdimm",
+    "MessagingAtapi",
+    "MessagingScsi",
+    "MessagingFibreChannel",
+    "MessagingFibreChannelEx",
+    "MessagingIeee1394",
+    "MessagingUsb",
+    "MessagingSata",
+    "MessagingUsbWwid",
+    "MessagingDeviceLogicalUnit",
+    "MessagingUsbClass",
+    "MessagingI2o",
+    "MessagingMacAddress",
+    "MessagingIpv4",
+    "MessagingIpv6",
+    "MessagingVlan",
+    "MessagingInfiniband",
+    "MessagingUart",
+    "MessagingVendor",
+    "MessagingSasEx",
+    "MessagingIscsi",
+    "MessagingNvmeNamespace",
+    "MessagingUri",
+    "MessagingUfs",
+    "MessagingSd",
+    "MessagingBluetooth",
+    "MessagingWifi",
+    "MessagingEmmc",
+    "MessagingBluetoothLe",
+    "MessagingDns",
+    "MessagingNvdimmNamespace",
+    "MessagingRestService",
+    "MessagingNvmeOfNamespace",
+    "MediaHardDrive",
+    "MediaCdRom",
+    "MediaVendor",
+    "MediaFilePath",
+    "MediaProtocol",
+    "MediaPiwgFirmwareFile",
+    "MediaPiwgFirmwareVolume",
+    "MediaRelativeOffsetRange",
+    "MediaRamDisk",
+    "BiosBootSpecBootSpecification",
+    "Unknown",
+];
 impl TryFrom<&DevicePathNode> for DevicePathNodeEnum<'_> {
     type Error = NodeConversionError;
     fn try_from(node: &DevicePathNode) -> Result<Self, Self::Error> {
@@ -3644,6 +5187,1440 @@ impl TryFrom<&DevicePathNode> for DevicePathNodeEnum<'_> {
     }
 }
 
+/// Parse the arguments of a node with the text name `name`
+/// and append the node to `out`. Returns `false` if `name`
+/// is not the name of a node with generated text conversion.
+#[cfg(feature = "alloc")]
+pub(super) fn node_from_text(
+    name: &str,
+    args: crate::proto::device_path::text::parse::TextArgs<'_>,
+    out: &mut alloc::vec::Vec<u8>,
+) -> Result<bool, crate::proto::device_path::text::ParseDevicePathError> {
+    use crate::proto::device_path::text::field::ParseField;
+    use crate::proto::device_path::text::parse::push_node;
+    use alloc::vec::Vec;
+    match name {
+        "Pci" => {
+            let [device, function] = args.into_array::<2usize>()?;
+            let device: u8 = <kind::Hex as ParseField<u8>>::parse_field(device)?;
+            let function: u8 = <kind::Hex as ParseField<u8>>::parse_field(function)?;
+            push_node(out, &build::hardware::Pci { function, device })?;
+        }
+
+        "PcCard" => {
+            let [function] = args.into_array::<1usize>()?;
+            let function: u8 = <kind::Hex as ParseField<u8>>::parse_field(function)?;
+            push_node(out, &build::hardware::Pccard { function })?;
+        }
+
+        "MemoryMapped" => {
+            let [memory_type, start_address, end_address] = args.into_array::<3usize>()?;
+            let memory_type: MemoryType =
+                <kind::Enum as ParseField<MemoryType>>::parse_field(memory_type)?;
+            let start_address: u64 = <kind::Hex as ParseField<u64>>::parse_field(start_address)?;
+            let end_address: u64 = <kind::Hex as ParseField<u64>>::parse_field(end_address)?;
+            push_node(
+                out,
+                &build::hardware::MemoryMapped {
+                    memory_type,
+                    start_address,
+                    end_address,
+                },
+            )?;
+        }
+
+        "VenHw" => {
+            let [vendor_guid, vendor_defined_data] = args.into_array::<2usize>()?;
+            let vendor_guid: Guid = <kind::Guid as ParseField<Guid>>::parse_field(vendor_guid)?;
+            let vendor_defined_data: Vec<u8> =
+                <kind::HexBytes as ParseField<Vec<u8>>>::parse_field(vendor_defined_data)?;
+            push_node(
+                out,
+                &build::hardware::Vendor {
+                    vendor_guid,
+                    vendor_defined_data: &vendor_defined_data,
+                },
+            )?;
+        }
+
+        "Ctrl" => {
+            let [controller_number] = args.into_array::<1usize>()?;
+            let controller_number: u32 =
+                <kind::Hex as ParseField<u32>>::parse_field(controller_number)?;
+            push_node(out, &build::hardware::Controller { controller_number })?;
+        }
+
+        "BMC" => {
+            let [interface_type, base_address] = args.into_array::<2usize>()?;
+            let interface_type: device_path::hardware::BmcInterfaceType =
+                <kind::Enum as ParseField<device_path::hardware::BmcInterfaceType>>::parse_field(
+                    interface_type,
+                )?;
+            let base_address: u64 = <kind::Hex as ParseField<u64>>::parse_field(base_address)?;
+            push_node(
+                out,
+                &build::hardware::Bmc {
+                    interface_type,
+                    base_address,
+                },
+            )?;
+        }
+
+        "NVDIMM" => {
+            let [nfit_device_handle] = args.into_array::<1usize>()?;
+            let nfit_device_handle: u32 =
+                <kind::Hex as ParseField<u32>>::parse_field(nfit_device_handle)?;
+            push_node(out, &build::acpi::Nvdimm { nfit_device_handle })?;
+        }
+
+        "Ata" => {
+            let [primary_secondary, master_slave, logical_unit_number] =
+                args.into_array::<3usize>()?;
+            let primary_secondary: device_path::messaging::PrimarySecondary =
+                <kind::Enum as ParseField<device_path::messaging::PrimarySecondary>>::parse_field(
+                    primary_secondary,
+                )?;
+            let master_slave: device_path::messaging::MasterSlave =
+                <kind::Enum as ParseField<device_path::messaging::MasterSlave>>::parse_field(
+                    master_slave,
+                )?;
+            let logical_unit_number: u16 =
+                <kind::Hex as ParseField<u16>>::parse_field(logical_unit_number)?;
+            push_node(
+                out,
+                &build::messaging::Atapi {
+                    primary_secondary,
+                    master_slave,
+                    logical_unit_number,
+                },
+            )?;
+        }
+
+        "Scsi" => {
+            let [target_id, logical_unit_number] = args.into_array::<2usize>()?;
+            let target_id: u16 = <kind::Hex as ParseField<u16>>::parse_field(target_id)?;
+            let logical_unit_number: u16 =
+                <kind::Hex as ParseField<u16>>::parse_field(logical_unit_number)?;
+            push_node(
+                out,
+                &build::messaging::Scsi {
+                    target_id,
+                    logical_unit_number,
+                },
+            )?;
+        }
+
+        "Fibre" => {
+            let [world_wide_name, logical_unit_number] = args.into_array::<2usize>()?;
+            let world_wide_name: u64 =
+                <kind::Hex as ParseField<u64>>::parse_field(world_wide_name)?;
+            let logical_unit_number: u64 =
+                <kind::Hex as ParseField<u64>>::parse_field(logical_unit_number)?;
+            push_node(
+                out,
+                &build::messaging::FibreChannel {
+                    world_wide_name,
+                    logical_unit_number,
+                },
+            )?;
+        }
+
+        "FibreEx" => {
+            let [world_wide_name, logical_unit_number] = args.into_array::<2usize>()?;
+            let world_wide_name: [u8; 8usize] =
+                <kind::HexBytes as ParseField<[u8; 8usize]>>::parse_field(world_wide_name)?;
+            let logical_unit_number: [u8; 8usize] =
+                <kind::HexBytes as ParseField<[u8; 8usize]>>::parse_field(logical_unit_number)?;
+            push_node(
+                out,
+                &build::messaging::FibreChannelEx {
+                    world_wide_name,
+                    logical_unit_number,
+                },
+            )?;
+        }
+
+        "I1394" => {
+            let [guid] = args.into_array::<1usize>()?;
+            let guid: [u8; 8usize] =
+                <kind::HexBytes as ParseField<[u8; 8usize]>>::parse_field(guid)?;
+            push_node(out, &build::messaging::Ieee1394 { guid })?;
+        }
+
+        "USB" => {
+            let [parent_port_number, interface] = args.into_array::<2usize>()?;
+            let parent_port_number: u8 =
+                <kind::Hex as ParseField<u8>>::parse_field(parent_port_number)?;
+            let interface: u8 = <kind::Hex as ParseField<u8>>::parse_field(interface)?;
+            push_node(
+                out,
+                &build::messaging::Usb {
+                    parent_port_number,
+                    interface,
+                },
+            )?;
+        }
+
+        "Sata" => {
+            let [hba_port_number, port_multiplier_port_number, logical_unit_number] =
+                args.into_array::<3usize>()?;
+            let hba_port_number: u16 =
+                <kind::Hex as ParseField<u16>>::parse_field(hba_port_number)?;
+            let port_multiplier_port_number: u16 =
+                <kind::Hex as ParseField<u16>>::parse_field(port_multiplier_port_number)?;
+            let logical_unit_number: u16 =
+                <kind::Hex as ParseField<u16>>::parse_field(logical_unit_number)?;
+            push_node(
+                out,
+                &build::messaging::Sata {
+                    hba_port_number,
+                    port_multiplier_port_number,
+                    logical_unit_number,
+                },
+            )?;
+        }
+
+        "UsbWwid" => {
+            let [device_vendor_id, device_product_id, interface_number, serial_number] =
+                args.into_array::<4usize>()?;
+            let device_vendor_id: u16 =
+                <kind::Hex as ParseField<u16>>::parse_field(device_vendor_id)?;
+            let device_product_id: u16 =
+                <kind::Hex as ParseField<u16>>::parse_field(device_product_id)?;
+            let interface_number: u16 =
+                <kind::Hex as ParseField<u16>>::parse_field(interface_number)?;
+            let serial_number: Vec<u16> =
+                <kind::Str as ParseField<Vec<u16>>>::parse_field(serial_number)?;
+            push_node(
+                out,
+                &build::messaging::UsbWwid {
+                    interface_number,
+                    device_vendor_id,
+                    device_product_id,
+                    serial_number: &serial_number,
+                },
+            )?;
+        }
+
+        "Unit" => {
+            let [logical_unit_number] = args.into_array::<1usize>()?;
+            let logical_unit_number: u8 =
+                <kind::Hex as ParseField<u8>>::parse_field(logical_unit_number)?;
+            push_node(
+                out,
+                &build::messaging::DeviceLogicalUnit {
+                    logical_unit_number,
+                },
+            )?;
+        }
+
+        "UsbClass" => {
+            let [vendor_id, product_id, device_class, device_subclass, device_protocol] =
+                args.into_array::<5usize>()?;
+            let vendor_id: u16 = <kind::Hex as ParseField<u16>>::parse_field(vendor_id)?;
+            let product_id: u16 = <kind::Hex as ParseField<u16>>::parse_field(product_id)?;
+            let device_class: u8 = <kind::Hex as ParseField<u8>>::parse_field(device_class)?;
+            let device_subclass: u8 = <kind::Hex as ParseField<u8>>::parse_field(device_subclass)?;
+            let device_protocol: u8 = <kind::Hex as ParseField<u8>>::parse_field(device_protocol)?;
+            push_node(
+                out,
+                &build::messaging::UsbClass {
+                    vendor_id,
+                    product_id,
+                    device_class,
+                    device_subclass,
+                    device_protocol,
+                },
+            )?;
+        }
+
+        "I2O" => {
+            let [target_id] = args.into_array::<1usize>()?;
+            let target_id: u32 = <kind::Hex as ParseField<u32>>::parse_field(target_id)?;
+            push_node(out, &build::messaging::I2o { target_id })?;
+        }
+
+        "Vlan" => {
+            let [vlan_id] = args.into_array::<1usize>()?;
+            let vlan_id: u16 = <kind::Decimal as ParseField<u16>>::parse_field(vlan_id)?;
+            push_node(out, &build::messaging::Vlan { vlan_id })?;
+        }
+
+        "Infiniband" => {
+            let [resource_flags, port_gid, ioc_guid_or_service_id, target_port_id, device_id] =
+                args.into_array::<5usize>()?;
+            let resource_flags : device_path :: messaging :: InfinibandResourceFlags = < kind :: Enum as ParseField < device_path :: messaging :: InfinibandResourceFlags >> :: parse_field (resource_flags) ? ;
+            let port_gid: [u8; 16usize] =
+                <kind::GuidBytes as ParseField<[u8; 16usize]>>::parse_field(port_gid)?;
+            let ioc_guid_or_service_id: u64 =
+                <kind::Hex as ParseField<u64>>::parse_field(ioc_guid_or_service_id)?;
+            let target_port_id: u64 = <kind::Hex as ParseField<u64>>::parse_field(target_port_id)?;
+            let device_id: u64 = <kind::Hex as ParseField<u64>>::parse_field(device_id)?;
+            push_node(
+                out,
+                &build::messaging::Infiniband {
+                    resource_flags,
+                    port_gid,
+                    ioc_guid_or_service_id,
+                    target_port_id,
+                    device_id,
+                },
+            )?;
+        }
+
+        "Uart" => {
+            let [baud_rate, data_bits, parity, stop_bits] = args.into_array::<4usize>()?;
+            let baud_rate: u64 = <kind::Decimal as ParseField<u64>>::parse_field(baud_rate)?;
+            let data_bits: u8 = <kind::Decimal as ParseField<u8>>::parse_field(data_bits)?;
+            let parity: device_path::messaging::Parity =
+                <kind::Enum as ParseField<device_path::messaging::Parity>>::parse_field(parity)?;
+            let stop_bits: device_path::messaging::StopBits =
+                <kind::Enum as ParseField<device_path::messaging::StopBits>>::parse_field(
+                    stop_bits,
+                )?;
+            push_node(
+                out,
+                &build::messaging::Uart {
+                    baud_rate,
+                    data_bits,
+                    parity,
+                    stop_bits,
+                },
+            )?;
+        }
+
+        "NVMe" => {
+            let [namespace_identifier, ieee_extended_unique_identifier] =
+                args.into_array::<2usize>()?;
+            let namespace_identifier: u32 =
+                <kind::Hex as ParseField<u32>>::parse_field(namespace_identifier)?;
+            let ieee_extended_unique_identifier: u64 =
+                <kind::Eui64 as ParseField<u64>>::parse_field(ieee_extended_unique_identifier)?;
+            push_node(
+                out,
+                &build::messaging::NvmeNamespace {
+                    namespace_identifier,
+                    ieee_extended_unique_identifier,
+                },
+            )?;
+        }
+
+        "Uri" => {
+            let [value] = args.into_array::<1usize>()?;
+            let value: Vec<u8> = <kind::Ascii as ParseField<Vec<u8>>>::parse_field(value)?;
+            push_node(out, &build::messaging::Uri { value: &value })?;
+        }
+
+        "UFS" => {
+            let [target_id, logical_unit_number] = args.into_array::<2usize>()?;
+            let target_id: u8 = <kind::Hex as ParseField<u8>>::parse_field(target_id)?;
+            let logical_unit_number: u8 =
+                <kind::Hex as ParseField<u8>>::parse_field(logical_unit_number)?;
+            push_node(
+                out,
+                &build::messaging::Ufs {
+                    target_id,
+                    logical_unit_number,
+                },
+            )?;
+        }
+
+        "SD" => {
+            let [slot_number] = args.into_array::<1usize>()?;
+            let slot_number: u8 = <kind::Hex as ParseField<u8>>::parse_field(slot_number)?;
+            push_node(out, &build::messaging::Sd { slot_number })?;
+        }
+
+        "Bluetooth" => {
+            let [device_address] = args.into_array::<1usize>()?;
+            let device_address: [u8; 6usize] =
+                <kind::HexBytes as ParseField<[u8; 6usize]>>::parse_field(device_address)?;
+            push_node(out, &build::messaging::Bluetooth { device_address })?;
+        }
+
+        "Wi-Fi" => {
+            let [ssid] = args.into_array::<1usize>()?;
+            let ssid: [u8; 32usize] =
+                <kind::Ascii as ParseField<[u8; 32usize]>>::parse_field(ssid)?;
+            push_node(out, &build::messaging::Wifi { ssid })?;
+        }
+
+        "eMMC" => {
+            let [slot_number] = args.into_array::<1usize>()?;
+            let slot_number: u8 = <kind::Hex as ParseField<u8>>::parse_field(slot_number)?;
+            push_node(out, &build::messaging::Emmc { slot_number })?;
+        }
+
+        "BluetoothLE" => {
+            let [device_address, address_type] = args.into_array::<2usize>()?;
+            let device_address: [u8; 6usize] =
+                <kind::HexBytes as ParseField<[u8; 6usize]>>::parse_field(device_address)?;
+            let address_type : device_path :: messaging :: BluetoothLeAddressType = < kind :: Enum as ParseField < device_path :: messaging :: BluetoothLeAddressType >> :: parse_field (address_type) ? ;
+            push_node(
+                out,
+                &build::messaging::BluetoothLe {
+                    device_address,
+                    address_type,
+                },
+            )?;
+        }
+
+        "NvdimmNamespace" => {
+            let [uuid] = args.into_array::<1usize>()?;
+            let uuid: [u8; 16usize] =
+                <kind::HexBytes as ParseField<[u8; 16usize]>>::parse_field(uuid)?;
+            push_node(out, &build::messaging::NvdimmNamespace { uuid })?;
+        }
+
+        "NVMEoF" => {
+            let [subsystem_nqn, nidt, nid] = args.into_array::<3usize>()?;
+            let subsystem_nqn: Vec<u8> =
+                <kind::CStr8 as ParseField<Vec<u8>>>::parse_field(subsystem_nqn)?;
+            let nidt: u8 = <kind::Hex as ParseField<u8>>::parse_field(nidt)?;
+            let nid: [u8; 16usize] =
+                <kind::HexBytes as ParseField<[u8; 16usize]>>::parse_field(nid)?;
+            push_node(
+                out,
+                &build::messaging::NvmeOfNamespace {
+                    nidt,
+                    nid,
+                    subsystem_nqn: &subsystem_nqn,
+                },
+            )?;
+        }
+
+        "CDROM" => {
+            let [boot_entry, partition_start, partition_size] = args.into_array::<3usize>()?;
+            let boot_entry: u32 = <kind::Hex as ParseField<u32>>::parse_field(boot_entry)?;
+            let partition_start: u64 =
+                <kind::Hex as ParseField<u64>>::parse_field(partition_start)?;
+            let partition_size: u64 = <kind::Hex as ParseField<u64>>::parse_field(partition_size)?;
+            push_node(
+                out,
+                &build::media::CdRom {
+                    boot_entry,
+                    partition_start,
+                    partition_size,
+                },
+            )?;
+        }
+
+        "VenMedia" => {
+            let [vendor_guid, vendor_defined_data] = args.into_array::<2usize>()?;
+            let vendor_guid: Guid = <kind::Guid as ParseField<Guid>>::parse_field(vendor_guid)?;
+            let vendor_defined_data: Vec<u8> =
+                <kind::HexBytes as ParseField<Vec<u8>>>::parse_field(vendor_defined_data)?;
+            push_node(
+                out,
+                &build::media::Vendor {
+                    vendor_guid,
+                    vendor_defined_data: &vendor_defined_data,
+                },
+            )?;
+        }
+
+        "Media" => {
+            let [protocol_guid] = args.into_array::<1usize>()?;
+            let protocol_guid: Guid = <kind::Guid as ParseField<Guid>>::parse_field(protocol_guid)?;
+            push_node(out, &build::media::Protocol { protocol_guid })?;
+        }
+
+        "FvFile" => {
+            let [data] = args.into_array::<1usize>()?;
+            let data: Vec<u8> = <kind::GuidBytes as ParseField<Vec<u8>>>::parse_field(data)?;
+            push_node(out, &build::media::PiwgFirmwareFile { data: &data })?;
+        }
+
+        "Fv" => {
+            let [data] = args.into_array::<1usize>()?;
+            let data: Vec<u8> = <kind::GuidBytes as ParseField<Vec<u8>>>::parse_field(data)?;
+            push_node(out, &build::media::PiwgFirmwareVolume { data: &data })?;
+        }
+
+        "Offset" => {
+            let [starting_offset, ending_offset] = args.into_array::<2usize>()?;
+            let starting_offset: u64 =
+                <kind::Hex as ParseField<u64>>::parse_field(starting_offset)?;
+            let ending_offset: u64 = <kind::Hex as ParseField<u64>>::parse_field(ending_offset)?;
+            push_node(
+                out,
+                &build::media::RelativeOffsetRange {
+                    starting_offset,
+                    ending_offset,
+                },
+            )?;
+        }
+
+        "RamDisk" => {
+            let [starting_address, ending_address, disk_instance, disk_type] =
+                args.into_array::<4usize>()?;
+            let starting_address: u64 =
+                <kind::Hex as ParseField<u64>>::parse_field(starting_address)?;
+            let ending_address: u64 = <kind::Hex as ParseField<u64>>::parse_field(ending_address)?;
+            let disk_instance: u16 = <kind::Hex as ParseField<u16>>::parse_field(disk_instance)?;
+            let disk_type: device_path::media::RamDiskType = <kind::Enum as ParseField<
+                device_path::media::RamDiskType,
+            >>::parse_field(disk_type)?;
+            push_node(
+                out,
+                &build::media::RamDisk {
+                    starting_address,
+                    ending_address,
+                    disk_type,
+                    disk_instance,
+                },
+            )?;
+        }
+
+        "BBS" => {
+            let [device_type, description_string, status_flag] = args.into_array::<3usize>()?;
+            let device_type: u16 =
+                <kind::BbsDeviceType as ParseField<u16>>::parse_field(device_type)?;
+            let description_string: Vec<u8> =
+                <kind::CStr8 as ParseField<Vec<u8>>>::parse_field(description_string)?;
+            let status_flag: u16 = <kind::Hex as ParseField<u16>>::parse_field(status_flag)?;
+            push_node(
+                out,
+                &build::bios_boot_spec::BootSpecification {
+                    device_type,
+                    status_flag,
+                    description_string: &description_string,
+                },
+            )?;
+        }
+
+        _ => return Ok(false),
+    }
+
+    Ok(true)
+}
+
+/// Deserialize the fields of the node whose `DevicePathNodeEnum`
+/// variant is named `name` and append the node to `out`. Nodes
+/// without generated `serde` impls are passed on to
+/// `custom_node_from_serde`.
+#[cfg(all(feature = "serde", feature = "alloc"))]
+pub(super) fn node_from_serde<'de, A: serde::de::VariantAccess<'de>>(
+    name: &str,
+    variant: A,
+    out: &mut crate::proto::device_path::DevicePathBuf,
+) -> Result<(), A::Error> {
+    use crate::proto::device_path::serde_impls::field::Deserialized;
+    use crate::proto::device_path::serde_impls::node::custom_node_from_serde;
+    use alloc::vec::Vec;
+    use serde::de::Error;
+    match name {
+        "EndInstance" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Instance")]
+            struct Fields {}
+
+            let Fields {} = variant.newtype_variant()?;
+            out.push(&build::end::Instance {})
+                .map_err(A::Error::custom)?;
+        }
+
+        "EndEntire" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Entire")]
+            struct Fields {}
+
+            let Fields {} = variant.newtype_variant()?;
+            out.push(&build::end::Entire {}).map_err(A::Error::custom)?;
+        }
+
+        "HardwarePci" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Pci")]
+            struct Fields {
+                function: Deserialized<kind::Hex, u8>,
+                device: Deserialized<kind::Hex, u8>,
+            }
+
+            let Fields { function, device } = variant.newtype_variant()?;
+            out.push(&build::hardware::Pci {
+                function: function.0,
+                device: device.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "HardwarePccard" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Pccard")]
+            struct Fields {
+                function: Deserialized<kind::Hex, u8>,
+            }
+
+            let Fields { function } = variant.newtype_variant()?;
+            out.push(&build::hardware::Pccard {
+                function: function.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "HardwareMemoryMapped" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "MemoryMapped")]
+            struct Fields {
+                memory_type: Deserialized<kind::Enum, MemoryType>,
+                start_address: Deserialized<kind::Hex, u64>,
+                end_address: Deserialized<kind::Hex, u64>,
+            }
+
+            let Fields {
+                memory_type,
+                start_address,
+                end_address,
+            } = variant.newtype_variant()?;
+            out.push(&build::hardware::MemoryMapped {
+                memory_type: memory_type.0,
+                start_address: start_address.0,
+                end_address: end_address.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "HardwareVendor" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Vendor")]
+            struct Fields {
+                vendor_guid: Deserialized<kind::Guid, Guid>,
+                vendor_defined_data: Deserialized<kind::HexBytes, Vec<u8>>,
+            }
+
+            let Fields {
+                vendor_guid,
+                vendor_defined_data,
+            } = variant.newtype_variant()?;
+            out.push(&build::hardware::Vendor {
+                vendor_guid: vendor_guid.0,
+                vendor_defined_data: &vendor_defined_data.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "HardwareController" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Controller")]
+            struct Fields {
+                controller_number: Deserialized<kind::Hex, u32>,
+            }
+
+            let Fields { controller_number } = variant.newtype_variant()?;
+            out.push(&build::hardware::Controller {
+                controller_number: controller_number.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "HardwareBmc" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Bmc")]
+            struct Fields {
+                interface_type: Deserialized<kind::Enum, device_path::hardware::BmcInterfaceType>,
+                base_address: Deserialized<kind::Hex, u64>,
+            }
+
+            let Fields {
+                interface_type,
+                base_address,
+            } = variant.newtype_variant()?;
+            out.push(&build::hardware::Bmc {
+                interface_type: interface_type.0,
+                base_address: base_address.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "AcpiAcpi" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Acpi")]
+            struct Fields {
+                hid: Deserialized<kind::Hex, u32>,
+                uid: Deserialized<kind::Hex, u32>,
+            }
+
+            let Fields { hid, uid } = variant.newtype_variant()?;
+            out.push(&build::acpi::Acpi {
+                hid: hid.0,
+                uid: uid.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "AcpiNvdimm" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Nvdimm")]
+            struct Fields {
+                nfit_device_handle: Deserialized<kind::Hex, u32>,
+            }
+
+            let Fields { nfit_device_handle } = variant.newtype_variant()?;
+            out.push(&build::acpi::Nvdimm {
+                nfit_device_handle: nfit_device_handle.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingAtapi" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Atapi")]
+            struct Fields {
+                primary_secondary:
+                    Deserialized<kind::Enum, device_path::messaging::PrimarySecondary>,
+                master_slave: Deserialized<kind::Enum, device_path::messaging::MasterSlave>,
+                logical_unit_number: Deserialized<kind::Hex, u16>,
+            }
+
+            let Fields {
+                primary_secondary,
+                master_slave,
+                logical_unit_number,
+            } = variant.newtype_variant()?;
+            out.push(&build::messaging::Atapi {
+                primary_secondary: primary_secondary.0,
+                master_slave: master_slave.0,
+                logical_unit_number: logical_unit_number.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingScsi" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Scsi")]
+            struct Fields {
+                target_id: Deserialized<kind::Hex, u16>,
+                logical_unit_number: Deserialized<kind::Hex, u16>,
+            }
+
+            let Fields {
+                target_id,
+                logical_unit_number,
+            } = variant.newtype_variant()?;
+            out.push(&build::messaging::Scsi {
+                target_id: target_id.0,
+                logical_unit_number: logical_unit_number.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingFibreChannel" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "FibreChannel")]
+            struct Fields {
+                world_wide_name: Deserialized<kind::Hex, u64>,
+                logical_unit_number: Deserialized<kind::Hex, u64>,
+            }
+
+            let Fields {
+                world_wide_name,
+                logical_unit_number,
+            } = variant.newtype_variant()?;
+            out.push(&build::messaging::FibreChannel {
+                world_wide_name: world_wide_name.0,
+                logical_unit_number: logical_unit_number.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingFibreChannelEx" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "FibreChannelEx")]
+            struct Fields {
+                world_wide_name: Deserialized<kind::HexBytes, [u8; 8usize]>,
+                logical_unit_number: Deserialized<kind::HexBytes, [u8; 8usize]>,
+            }
+
+            let Fields {
+                world_wide_name,
+                logical_unit_number,
+            } = variant.newtype_variant()?;
+            out.push(&build::messaging::FibreChannelEx {
+                world_wide_name: world_wide_name.0,
+                logical_unit_number: logical_unit_number.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingIeee1394" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Ieee1394")]
+            struct Fields {
+                guid: Deserialized<kind::HexBytes, [u8; 8usize]>,
+            }
+
+            let Fields { guid } = variant.newtype_variant()?;
+            out.push(&build::messaging::Ieee1394 { guid: guid.0 })
+                .map_err(A::Error::custom)?;
+        }
+
+        "MessagingUsb" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Usb")]
+            struct Fields {
+                parent_port_number: Deserialized<kind::Hex, u8>,
+                interface: Deserialized<kind::Hex, u8>,
+            }
+
+            let Fields {
+                parent_port_number,
+                interface,
+            } = variant.newtype_variant()?;
+            out.push(&build::messaging::Usb {
+                parent_port_number: parent_port_number.0,
+                interface: interface.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingSata" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Sata")]
+            struct Fields {
+                hba_port_number: Deserialized<kind::Hex, u16>,
+                port_multiplier_port_number: Deserialized<kind::Hex, u16>,
+                logical_unit_number: Deserialized<kind::Hex, u16>,
+            }
+
+            let Fields {
+                hba_port_number,
+                port_multiplier_port_number,
+                logical_unit_number,
+            } = variant.newtype_variant()?;
+            out.push(&build::messaging::Sata {
+                hba_port_number: hba_port_number.0,
+                port_multiplier_port_number: port_multiplier_port_number.0,
+                logical_unit_number: logical_unit_number.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingUsbWwid" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "UsbWwid")]
+            struct Fields {
+                interface_number: Deserialized<kind::Hex, u16>,
+                device_vendor_id: Deserialized<kind::Hex, u16>,
+                device_product_id: Deserialized<kind::Hex, u16>,
+                serial_number: Deserialized<kind::Str, Vec<u16>>,
+            }
+
+            let Fields {
+                interface_number,
+                device_vendor_id,
+                device_product_id,
+                serial_number,
+            } = variant.newtype_variant()?;
+            out.push(&build::messaging::UsbWwid {
+                interface_number: interface_number.0,
+                device_vendor_id: device_vendor_id.0,
+                device_product_id: device_product_id.0,
+                serial_number: &serial_number.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingDeviceLogicalUnit" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "DeviceLogicalUnit")]
+            struct Fields {
+                logical_unit_number: Deserialized<kind::Hex, u8>,
+            }
+
+            let Fields {
+                logical_unit_number,
+            } = variant.newtype_variant()?;
+            out.push(&build::messaging::DeviceLogicalUnit {
+                logical_unit_number: logical_unit_number.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingUsbClass" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "UsbClass")]
+            struct Fields {
+                vendor_id: Deserialized<kind::Hex, u16>,
+                product_id: Deserialized<kind::Hex, u16>,
+                device_class: Deserialized<kind::Hex, u8>,
+                device_subclass: Deserialized<kind::Hex, u8>,
+                device_protocol: Deserialized<kind::Hex, u8>,
+            }
+
+            let Fields {
+                vendor_id,
+                product_id,
+                device_class,
+                device_subclass,
+                device_protocol,
+            } = variant.newtype_variant()?;
+            out.push(&build::messaging::UsbClass {
+                vendor_id: vendor_id.0,
+                product_id: product_id.0,
+                device_class: device_class.0,
+                device_subclass: device_subclass.0,
+                device_protocol: device_protocol.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingI2o" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "I2o")]
+            struct Fields {
+                target_id: Deserialized<kind::Hex, u32>,
+            }
+
+            let Fields { target_id } = variant.newtype_variant()?;
+            out.push(&build::messaging::I2o {
+                target_id: target_id.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingMacAddress" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "MacAddress")]
+            struct Fields {
+                mac_address: Deserialized<kind::HexBytes, [u8; 32usize]>,
+                interface_type: Deserialized<kind::Hex, u8>,
+            }
+
+            let Fields {
+                mac_address,
+                interface_type,
+            } = variant.newtype_variant()?;
+            out.push(&build::messaging::MacAddress {
+                mac_address: mac_address.0,
+                interface_type: interface_type.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingIpv4" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Ipv4")]
+            struct Fields {
+                local_ip_address: Deserialized<kind::HexBytes, [u8; 4usize]>,
+                remote_ip_address: Deserialized<kind::HexBytes, [u8; 4usize]>,
+                local_port: Deserialized<kind::Hex, u16>,
+                remote_port: Deserialized<kind::Hex, u16>,
+                protocol: Deserialized<kind::Hex, u16>,
+                ip_address_origin:
+                    Deserialized<kind::Enum, device_path::messaging::Ipv4AddressOrigin>,
+                gateway_ip_address: Deserialized<kind::HexBytes, [u8; 4usize]>,
+                subnet_mask: Deserialized<kind::HexBytes, [u8; 4usize]>,
+            }
+
+            let Fields {
+                local_ip_address,
+                remote_ip_address,
+                local_port,
+                remote_port,
+                protocol,
+                ip_address_origin,
+                gateway_ip_address,
+                subnet_mask,
+            } = variant.newtype_variant()?;
+            out.push(&build::messaging::Ipv4 {
+                local_ip_address: local_ip_address.0,
+                remote_ip_address: remote_ip_address.0,
+                local_port: local_port.0,
+                remote_port: remote_port.0,
+                protocol: protocol.0,
+                ip_address_origin: ip_address_origin.0,
+                gateway_ip_address: gateway_ip_address.0,
+                subnet_mask: subnet_mask.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingIpv6" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Ipv6")]
+            struct Fields {
+                local_ip_address: Deserialized<kind::HexBytes, [u8; 16usize]>,
+                remote_ip_address: Deserialized<kind::HexBytes, [u8; 16usize]>,
+                local_port: Deserialized<kind::Hex, u16>,
+                remote_port: Deserialized<kind::Hex, u16>,
+                protocol: Deserialized<kind::Hex, u16>,
+                ip_address_origin:
+                    Deserialized<kind::Enum, device_path::messaging::Ipv6AddressOrigin>,
+                prefix_length: Deserialized<kind::Hex, u8>,
+                gateway_ip_address: Deserialized<kind::HexBytes, [u8; 16usize]>,
+            }
+
+            let Fields {
+                local_ip_address,
+                remote_ip_address,
+                local_port,
+                remote_port,
+                protocol,
+                ip_address_origin,
+                prefix_length,
+                gateway_ip_address,
+            } = variant.newtype_variant()?;
+            out.push(&build::messaging::Ipv6 {
+                local_ip_address: local_ip_address.0,
+                remote_ip_address: remote_ip_address.0,
+                local_port: local_port.0,
+                remote_port: remote_port.0,
+                protocol: protocol.0,
+                ip_address_origin: ip_address_origin.0,
+                prefix_length: prefix_length.0,
+                gateway_ip_address: gateway_ip_address.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingVlan" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Vlan")]
+            struct Fields {
+                vlan_id: Deserialized<kind::Decimal, u16>,
+            }
+
+            let Fields { vlan_id } = variant.newtype_variant()?;
+            out.push(&build::messaging::Vlan { vlan_id: vlan_id.0 })
+                .map_err(A::Error::custom)?;
+        }
+
+        "MessagingInfiniband" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Infiniband")]
+            struct Fields {
+                resource_flags:
+                    Deserialized<kind::Enum, device_path::messaging::InfinibandResourceFlags>,
+                port_gid: Deserialized<kind::GuidBytes, [u8; 16usize]>,
+                ioc_guid_or_service_id: Deserialized<kind::Hex, u64>,
+                target_port_id: Deserialized<kind::Hex, u64>,
+                device_id: Deserialized<kind::Hex, u64>,
+            }
+
+            let Fields {
+                resource_flags,
+                port_gid,
+                ioc_guid_or_service_id,
+                target_port_id,
+                device_id,
+            } = variant.newtype_variant()?;
+            out.push(&build::messaging::Infiniband {
+                resource_flags: resource_flags.0,
+                port_gid: port_gid.0,
+                ioc_guid_or_service_id: ioc_guid_or_service_id.0,
+                target_port_id: target_port_id.0,
+                device_id: device_id.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingUart" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Uart")]
+            struct Fields {
+                baud_rate: Deserialized<kind::Decimal, u64>,
+                data_bits: Deserialized<kind::Decimal, u8>,
+                parity: Deserialized<kind::Enum, device_path::messaging::Parity>,
+                stop_bits: Deserialized<kind::Enum, device_path::messaging::StopBits>,
+            }
+
+            let Fields {
+                baud_rate,
+                data_bits,
+                parity,
+                stop_bits,
+            } = variant.newtype_variant()?;
+            out.push(&build::messaging::Uart {
+                baud_rate: baud_rate.0,
+                data_bits: data_bits.0,
+                parity: parity.0,
+                stop_bits: stop_bits.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingVendor" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Vendor")]
+            struct Fields {
+                vendor_guid: Deserialized<kind::Guid, Guid>,
+                vendor_defined_data: Deserialized<kind::HexBytes, Vec<u8>>,
+            }
+
+            let Fields {
+                vendor_guid,
+                vendor_defined_data,
+            } = variant.newtype_variant()?;
+            out.push(&build::messaging::Vendor {
+                vendor_guid: vendor_guid.0,
+                vendor_defined_data: &vendor_defined_data.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingSasEx" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "SasEx")]
+            struct Fields {
+                sas_address: Deserialized<kind::HexBytes, [u8; 8usize]>,
+                logical_unit_number: Deserialized<kind::HexBytes, [u8; 8usize]>,
+                info: Deserialized<kind::Hex, u16>,
+                relative_target_port: Deserialized<kind::Hex, u16>,
+            }
+
+            let Fields {
+                sas_address,
+                logical_unit_number,
+                info,
+                relative_target_port,
+            } = variant.newtype_variant()?;
+            out.push(&build::messaging::SasEx {
+                sas_address: sas_address.0,
+                logical_unit_number: logical_unit_number.0,
+                info: info.0,
+                relative_target_port: relative_target_port.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingIscsi" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Iscsi")]
+            struct Fields {
+                protocol: Deserialized<kind::Enum, device_path::messaging::IscsiProtocol>,
+                options: Deserialized<kind::Enum, device_path::messaging::IscsiLoginOptions>,
+                logical_unit_number: Deserialized<kind::HexBytes, [u8; 8usize]>,
+                target_portal_group_tag: Deserialized<kind::Hex, u16>,
+                iscsi_target_name: Deserialized<kind::HexBytes, Vec<u8>>,
+            }
+
+            let Fields {
+                protocol,
+                options,
+                logical_unit_number,
+                target_portal_group_tag,
+                iscsi_target_name,
+            } = variant.newtype_variant()?;
+            out.push(&build::messaging::Iscsi {
+                protocol: protocol.0,
+                options: options.0,
+                logical_unit_number: logical_unit_number.0,
+                target_portal_group_tag: target_portal_group_tag.0,
+                iscsi_target_name: &iscsi_target_name.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingNvmeNamespace" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "NvmeNamespace")]
+            struct Fields {
+                namespace_identifier: Deserialized<kind::Hex, u32>,
+                ieee_extended_unique_identifier: Deserialized<kind::Eui64, u64>,
+            }
+
+            let Fields {
+                namespace_identifier,
+                ieee_extended_unique_identifier,
+            } = variant.newtype_variant()?;
+            out.push(&build::messaging::NvmeNamespace {
+                namespace_identifier: namespace_identifier.0,
+                ieee_extended_unique_identifier: ieee_extended_unique_identifier.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingUri" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Uri")]
+            struct Fields {
+                value: Deserialized<kind::Ascii, Vec<u8>>,
+            }
+
+            let Fields { value } = variant.newtype_variant()?;
+            out.push(&build::messaging::Uri { value: &value.0 })
+                .map_err(A::Error::custom)?;
+        }
+
+        "MessagingUfs" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Ufs")]
+            struct Fields {
+                target_id: Deserialized<kind::Hex, u8>,
+                logical_unit_number: Deserialized<kind::Hex, u8>,
+            }
+
+            let Fields {
+                target_id,
+                logical_unit_number,
+            } = variant.newtype_variant()?;
+            out.push(&build::messaging::Ufs {
+                target_id: target_id.0,
+                logical_unit_number: logical_unit_number.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingSd" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Sd")]
+            struct Fields {
+                slot_number: Deserialized<kind::Hex, u8>,
+            }
+
+            let Fields { slot_number } = variant.newtype_variant()?;
+            out.push(&build::messaging::Sd {
+                slot_number: slot_number.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingBluetooth" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Bluetooth")]
+            struct Fields {
+                device_address: Deserialized<kind::HexBytes, [u8; 6usize]>,
+            }
+
+            let Fields { device_address } = variant.newtype_variant()?;
+            out.push(&build::messaging::Bluetooth {
+                device_address: device_address.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingWifi" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Wifi")]
+            struct Fields {
+                ssid: Deserialized<kind::Ascii, [u8; 32usize]>,
+            }
+
+            let Fields { ssid } = variant.newtype_variant()?;
+            out.push(&build::messaging::Wifi { ssid: ssid.0 })
+                .map_err(A::Error::custom)?;
+        }
+
+        "MessagingEmmc" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Emmc")]
+            struct Fields {
+                slot_number: Deserialized<kind::Hex, u8>,
+            }
+
+            let Fields { slot_number } = variant.newtype_variant()?;
+            out.push(&build::messaging::Emmc {
+                slot_number: slot_number.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingBluetoothLe" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "BluetoothLe")]
+            struct Fields {
+                device_address: Deserialized<kind::HexBytes, [u8; 6usize]>,
+                address_type:
+                    Deserialized<kind::Enum, device_path::messaging::BluetoothLeAddressType>,
+            }
+
+            let Fields {
+                device_address,
+                address_type,
+            } = variant.newtype_variant()?;
+            out.push(&build::messaging::BluetoothLe {
+                device_address: device_address.0,
+                address_type: address_type.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MessagingNvdimmNamespace" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "NvdimmNamespace")]
+            struct Fields {
+                uuid: Deserialized<kind::HexBytes, [u8; 16usize]>,
+            }
+
+            let Fields { uuid } = variant.newtype_variant()?;
+            out.push(&build::messaging::NvdimmNamespace { uuid: uuid.0 })
+                .map_err(A::Error::custom)?;
+        }
+
+        "MessagingNvmeOfNamespace" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "NvmeOfNamespace")]
+            struct Fields {
+                nidt: Deserialized<kind::Hex, u8>,
+                nid: Deserialized<kind::HexBytes, [u8; 16usize]>,
+                subsystem_nqn: Deserialized<kind::CStr8, Vec<u8>>,
+            }
+
+            let Fields {
+                nidt,
+                nid,
+                subsystem_nqn,
+            } = variant.newtype_variant()?;
+            out.push(&build::messaging::NvmeOfNamespace {
+                nidt: nidt.0,
+                nid: nid.0,
+                subsystem_nqn: &subsystem_nqn.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MediaCdRom" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "CdRom")]
+            struct Fields {
+                boot_entry: Deserialized<kind::Hex, u32>,
+                partition_start: Deserialized<kind::Hex, u64>,
+                partition_size: Deserialized<kind::Hex, u64>,
+            }
+
+            let Fields {
+                boot_entry,
+                partition_start,
+                partition_size,
+            } = variant.newtype_variant()?;
+            out.push(&build::media::CdRom {
+                boot_entry: boot_entry.0,
+                partition_start: partition_start.0,
+                partition_size: partition_size.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MediaVendor" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Vendor")]
+            struct Fields {
+                vendor_guid: Deserialized<kind::Guid, Guid>,
+                vendor_defined_data: Deserialized<kind::HexBytes, Vec<u8>>,
+            }
+
+            let Fields {
+                vendor_guid,
+                vendor_defined_data,
+            } = variant.newtype_variant()?;
+            out.push(&build::media::Vendor {
+                vendor_guid: vendor_guid.0,
+                vendor_defined_data: &vendor_defined_data.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MediaProtocol" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Protocol")]
+            struct Fields {
+                protocol_guid: Deserialized<kind::Guid, Guid>,
+            }
+
+            let Fields { protocol_guid } = variant.newtype_variant()?;
+            out.push(&build::media::Protocol {
+                protocol_guid: protocol_guid.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MediaPiwgFirmwareFile" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "PiwgFirmwareFile")]
+            struct Fields {
+                data: Deserialized<kind::GuidBytes, Vec<u8>>,
+            }
+
+            let Fields { data } = variant.newtype_variant()?;
+            out.push(&build::media::PiwgFirmwareFile { data: &data.0 })
+                .map_err(A::Error::custom)?;
+        }
+
+        "MediaPiwgFirmwareVolume" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "PiwgFirmwareVolume")]
+            struct Fields {
+                data: Deserialized<kind::GuidBytes, Vec<u8>>,
+            }
+
+            let Fields { data } = variant.newtype_variant()?;
+            out.push(&build::media::PiwgFirmwareVolume { data: &data.0 })
+                .map_err(A::Error::custom)?;
+        }
+
+        "MediaRelativeOffsetRange" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "RelativeOffsetRange")]
+            struct Fields {
+                starting_offset: Deserialized<kind::Hex, u64>,
+                ending_offset: Deserialized<kind::Hex, u64>,
+            }
+
+            let Fields {
+                starting_offset,
+                ending_offset,
+            } = variant.newtype_variant()?;
+            out.push(&build::media::RelativeOffsetRange {
+                starting_offset: starting_offset.0,
+                ending_offset: ending_offset.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "MediaRamDisk" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "RamDisk")]
+            struct Fields {
+                starting_address: Deserialized<kind::Hex, u64>,
+                ending_address: Deserialized<kind::Hex, u64>,
+                disk_type: Deserialized<kind::Enum, device_path::media::RamDiskType>,
+                disk_instance: Deserialized<kind::Hex, u16>,
+            }
+
+            let Fields {
+                starting_address,
+                ending_address,
+                disk_type,
+                disk_instance,
+            } = variant.newtype_variant()?;
+            out.push(&build::media::RamDisk {
+                starting_address: starting_address.0,
+                ending_address: ending_address.0,
+                disk_type: disk_type.0,
+                disk_instance: disk_instance.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        "BiosBootSpecBootSpecification" => {
+            #[derive(serde :: Deserialize)]
+            #[serde(deny_unknown_fields, rename = "BootSpecification")]
+            struct Fields {
+                device_type: Deserialized<kind::BbsDeviceType, u16>,
+                status_flag: Deserialized<kind::Hex, u16>,
+                description_string: Deserialized<kind::CStr8, Vec<u8>>,
+            }
+
+            let Fields {
+                device_type,
+                status_flag,
+                description_string,
+            } = variant.newtype_variant()?;
+            out.push(&build::bios_boot_spec::BootSpecification {
+                device_type: device_type.0,
+                status_flag: status_flag.0,
+                description_string: &description_string.0,
+            })
+            .map_err(A::Error::custom)?;
+        }
+
+        _ => return custom_node_from_serde(name, variant, out),
+    }
+
+    Ok(())
+}
+
 /// Build device paths from their component nodes.
 pub mod build {
     use super::*;
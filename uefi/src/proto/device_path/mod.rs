@@ -66,8 +66,9 @@
 //! All of these types use a packed layout and may appear on any byte
 //! boundary.
 //!
-//! Note: the API provided by this module is currently mostly limited to
-//! reading existing device paths rather than constructing new ones.
+//! New device paths can be constructed with
+//! [`build::DevicePathBuilder`], or (if the `alloc` feature is enabled)
+//! edited in place with [`DevicePathBuf`].
 //!
 //! [`END_ENTIRE`]: DeviceSubType::END_ENTIRE
 //! [`END_INSTANCE`]: DeviceSubType::END_INSTANCE
@@ -79,7 +80,14 @@ pub mod build;
 pub mod text;
 pub mod util;
 
+#[cfg(feature = "alloc")]
+mod buf;
 mod device_path_gen;
+#[cfg(feature = "serde")]
+mod serde_impls;
+
+#[cfg(feature = "alloc")]
+pub use buf::DevicePathBuf;
 
 pub use device_path_gen::{
     acpi, bios_boot_spec, end, hardware, media, messaging, DevicePathNodeEnum,
@@ -88,20 +96,27 @@ pub use uefi_raw::protocol::device_path::{DeviceSubType, DeviceType};
 
 use crate::mem::PoolAllocation;
 use crate::proto::{unsafe_protocol, ProtocolPointer};
+use crate::Handle;
 use core::ffi::c_void;
 use core::fmt::{self, Debug, Display, Formatter};
 use core::ops::Deref;
+use media::PartitionSignature;
 use ptr_meta::Pointee;
 
 use uefi_raw::protocol::device_path::DevicePathProtocol;
 #[cfg(feature = "alloc")]
 use {
     crate::boot::{self, OpenProtocolAttributes, OpenProtocolParams, ScopedProtocol, SearchType},
+    crate::proto::device_path::build::{BuildError, DevicePathBuilder},
     crate::proto::device_path::text::{AllowShortcuts, DevicePathToText, DisplayOnly},
     crate::proto::device_path::util::DevicePathUtilities,
-    crate::{CString16, Identify},
+    crate::proto::loaded_image::LoadedImage,
+    crate::proto::media::fs::SimpleFileSystem,
+    crate::{CStr16, CString16, Guid, Identify},
     alloc::borrow::ToOwned,
     alloc::boxed::Box,
+    alloc::vec,
+    alloc::vec::Vec,
     core::mem,
 };
 
@@ -282,8 +297,62 @@ impl DevicePathNode {
         DevicePathNodeEnum::try_from(self)
     }
 
+    /// Check if this node refers to the same device as `other`.
+    ///
+    /// This is like `==`, except that fields which don't affect which
+    /// device a node refers to are ignored:
+    /// * [`HardDrive`] nodes with a GPT signature match if the
+    ///   signatures are equal. Nodes with an MBR signature match if the
+    ///   signatures and partition numbers are equal. The partition
+    ///   start and size are ignored in both cases.
+    /// * [`FilePath`] nodes match if the paths are equal, ignoring ASCII
+    ///   case and anything after a null character.
+    /// * [`MacAddress`] nodes with interface type 0 or 1 (Ethernet)
+    ///   compare only the first 6 bytes of the address.
+    ///
+    /// All other nodes must be byte-for-byte equal.
+    ///
+    /// [`FilePath`]: media::FilePath
+    /// [`HardDrive`]: media::HardDrive
+    /// [`MacAddress`]: messaging::MacAddress
+    #[must_use]
+    pub fn matches(&self, other: &Self) -> bool {
+        if self.full_type() != other.full_type() {
+            return false;
+        }
+
+        match (self.as_enum(), other.as_enum()) {
+            (
+                Ok(DevicePathNodeEnum::MediaHardDrive(a)),
+                Ok(DevicePathNodeEnum::MediaHardDrive(b)),
+            ) => match (a.partition_signature(), b.partition_signature()) {
+                (PartitionSignature::Guid(a), PartitionSignature::Guid(b)) => a == b,
+                (PartitionSignature::Mbr(sig_a), PartitionSignature::Mbr(sig_b)) => {
+                    sig_a == sig_b && a.partition_number() == b.partition_number()
+                }
+                _ => self == other,
+            },
+            (
+                Ok(DevicePathNodeEnum::MediaFilePath(a)),
+                Ok(DevicePathNodeEnum::MediaFilePath(b)),
+            ) => folded_path_name(a).eq(folded_path_name(b)),
+            (
+                Ok(DevicePathNodeEnum::MessagingMacAddress(a)),
+                Ok(DevicePathNodeEnum::MessagingMacAddress(b)),
+            ) => {
+                let len = if a.interface_type() <= 1 { 6 } else { 32 };
+                a.interface_type() == b.interface_type()
+                    && a.mac_address()[..len] == b.mac_address()[..len]
+            }
+            _ => self == other,
+        }
+    }
+
     /// Transforms the device path node to its string representation using the
     /// [`DevicePathToText`] protocol.
+    ///
+    /// To convert without relying on the firmware, use the [`Display`]
+    /// implementation instead (e.g. with `format!`).
     #[cfg(feature = "alloc")]
     pub fn to_string(
         &self,
@@ -304,6 +373,20 @@ impl DevicePathNode {
     }
 }
 
+/// Characters of a file path node up to the first null, with ASCII
+/// letters lowercased.
+fn folded_path_name(node: &media::FilePath) -> impl Iterator<Item = u16> + '_ {
+    let chars = node.path_name();
+    let len = chars.len();
+    (0..len)
+        .filter_map(move |i| chars.get(i))
+        .take_while(|c| *c != 0)
+        .map(|c| match u8::try_from(c) {
+            Ok(c) => u16::from(c.to_ascii_lowercase()),
+            Err(_) => c,
+        })
+}
+
 impl Debug for DevicePathNode {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         f.debug_struct("DevicePathNode")
@@ -375,6 +458,43 @@ impl DevicePathInstance {
         let data = data.into_boxed_slice();
         unsafe { mem::transmute(data) }
     }
+
+    /// Returns a boxed copy of the instance as a standalone
+    /// [`DevicePath`]. If the instance ends with an [`END_INSTANCE`]
+    /// node, it's replaced with an [`END_ENTIRE`] node.
+    ///
+    /// Combined with [`DevicePath::instance_iter`], this splits a
+    /// multi-instance path into single-instance paths:
+    ///
+    /// ```
+    /// use uefi::proto::device_path::{DevicePath, DevicePathInstance};
+    ///
+    /// let path: Box<DevicePath> = "PciRoot(0x0)/Pci(0x2,0x0),PciRoot(0x0)/Pci(0x3,0x0)"
+    ///     .parse()
+    ///     .unwrap();
+    /// let paths: Vec<Box<DevicePath>> = path
+    ///     .instance_iter()
+    ///     .map(DevicePathInstance::to_device_path)
+    ///     .collect();
+    /// assert_eq!(paths.len(), 2);
+    /// assert_eq!(format!("{}", paths[1]), "PciRoot(0x0)/Pci(0x3,0x0)");
+    /// ```
+    ///
+    /// [`END_ENTIRE`]: DeviceSubType::END_ENTIRE
+    /// [`END_INSTANCE`]: DeviceSubType::END_INSTANCE
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn to_device_path(&self) -> Box<DevicePath> {
+        let end_offset: usize = self
+            .node_iter()
+            .map(|node| usize::from(node.length()))
+            .sum();
+        let mut data = self.data.to_owned();
+        if let Some(sub_type) = data.get_mut(end_offset + 1) {
+            *sub_type = DeviceSubType::END_ENTIRE.0;
+        }
+        unsafe { DevicePath::from_boxed_bytes(data.into_boxed_slice()) }
+    }
 }
 
 impl Debug for DevicePathInstance {
@@ -530,11 +650,302 @@ impl DevicePath {
     pub fn to_boxed(&self) -> Box<Self> {
         let data = self.data.to_owned();
         let data = data.into_boxed_slice();
+        unsafe { Self::from_boxed_bytes(data) }
+    }
+
+    /// Convert boxed bytes to a boxed [`DevicePath`].
+    ///
+    /// # Safety
+    ///
+    /// `data` must contain a valid device path, ending with an
+    /// [`END_ENTIRE`] node.
+    ///
+    /// [`END_ENTIRE`]: DeviceSubType::END_ENTIRE
+    #[cfg(feature = "alloc")]
+    unsafe fn from_boxed_bytes(data: Box<[u8]>) -> Box<Self> {
         unsafe { mem::transmute(data) }
     }
 
+    /// Size in bytes of all nodes before the final [`END_ENTIRE`] node.
+    ///
+    /// [`END_ENTIRE`]: DeviceSubType::END_ENTIRE
+    #[cfg(feature = "alloc")]
+    fn nodes_len(&self) -> usize {
+        self.node_iter()
+            .map(|node| usize::from(node.length()))
+            .sum()
+    }
+
+    /// Check if the path contains more than one [`DevicePathInstance`].
+    #[must_use]
+    pub fn is_multi_instance(&self) -> bool {
+        self.instance_iter().nth(1).is_some()
+    }
+
+    /// Check if the nodes of `prefix` match the first nodes of this
+    /// path. Nodes are compared with [`DevicePathNode::matches`].
+    ///
+    /// This can be used to check whether a device is a child of another
+    /// device, e.g. whether a partition is on a particular disk.
+    ///
+    /// An empty `prefix` matches any path.
+    #[must_use]
+    pub fn starts_with(&self, prefix: &Self) -> bool {
+        self.strip_prefix(prefix).is_some()
+    }
+
+    /// If the nodes of `prefix` match the first nodes of this path,
+    /// returns the rest of the path. Nodes are compared with
+    /// [`DevicePathNode::matches`].
+    #[must_use]
+    pub fn strip_prefix(&self, prefix: &Self) -> Option<&Self> {
+        let mut nodes = self.node_iter();
+        let mut offset = 0;
+        for prefix_node in prefix.node_iter() {
+            let node = nodes.next()?;
+            if !node.matches(prefix_node) {
+                return None;
+            }
+            offset += usize::from(node.length());
+        }
+
+        let rest = &self.data[offset..];
+        Some(unsafe { &*ptr_meta::from_raw_parts(rest.as_ptr().cast(), rest.len()) })
+    }
+
+    /// Locate the handle of the device on this path that supports
+    /// protocol `P`, and return it along with the rest of the path
+    /// after the part that matched.
+    ///
+    /// This is a wrapper around [`boot::locate_device_path`]; see that
+    /// function for details.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_FOUND`]: no matching handles.
+    ///
+    /// [`boot::locate_device_path`]: crate::boot::locate_device_path
+    /// [`Status::NOT_FOUND`]: crate::Status::NOT_FOUND
+    pub fn locate_device_path<P: ProtocolPointer + ?Sized>(
+        &self,
+    ) -> crate::Result<(Handle, &Self)> {
+        let mut remaining = self;
+        let handle = crate::boot::locate_device_path::<P>(&mut remaining)?;
+        Ok((handle, remaining))
+    }
+
+    /// Expand a short-form device path into full device paths by
+    /// scanning the device paths of all handles.
+    ///
+    /// Boot options may use short-form paths that omit the path to the
+    /// device. Two forms are supported:
+    /// * Hard drive media short form: the path starts with a
+    ///   [`HardDrive`] node. Each handle whose device path ends with a
+    ///   node that [matches] it gives a candidate made of the handle's
+    ///   path followed by the rest of the short-form path.
+    /// * File path short form: the path starts with a [`FilePath`] node.
+    ///   Each handle that supports [`SimpleFileSystem`] gives a
+    ///   candidate made of the handle's path followed by the short-form
+    ///   path. The candidates are not checked for whether the file
+    ///   exists.
+    ///
+    /// Any other path is not short-form and is returned unchanged as the
+    /// only candidate.
+    ///
+    /// The candidates are returned in handle order. The result may be
+    /// empty if no handle matches.
+    ///
+    /// # Errors
+    ///
+    /// Returns errors from [`boot::locate_handle_buffer`].
+    ///
+    /// [`FilePath`]: media::FilePath
+    /// [`HardDrive`]: media::HardDrive
+    /// [`SimpleFileSystem`]: crate::proto::media::fs::SimpleFileSystem
+    /// [matches]: DevicePathNode::matches
+    #[cfg(feature = "alloc")]
+    pub fn expand_short_form(&self) -> crate::Result<Vec<Box<Self>>> {
+        let Some(first) = self.node_iter().next() else {
+            return Ok(vec![self.to_boxed()]);
+        };
+        let is_hard_drive = match first.full_type() {
+            (DeviceType::MEDIA, DeviceSubType::MEDIA_HARD_DRIVE) => true,
+            (DeviceType::MEDIA, DeviceSubType::MEDIA_FILE_PATH) => false,
+            _ => return Ok(vec![self.to_boxed()]),
+        };
+
+        let handles = boot::locate_handle_buffer(SearchType::ByProtocol(&Self::GUID))?;
+        let mut candidates = Vec::new();
+        for &handle in handles.iter() {
+            let params = OpenProtocolParams {
+                handle,
+                agent: boot::image_handle(),
+                controller: None,
+            };
+            let Ok(handle_path) = (unsafe {
+                boot::open_protocol::<Self>(params, OpenProtocolAttributes::GetProtocol)
+            }) else {
+                continue;
+            };
+
+            let rest = if is_hard_drive {
+                let Some(last) = handle_path.node_iter().last() else {
+                    continue;
+                };
+                if !last.matches(first) {
+                    continue;
+                }
+                &self.data[usize::from(first.length())..]
+            } else {
+                if !boot::test_protocol::<SimpleFileSystem>(params).unwrap_or(false) {
+                    continue;
+                }
+                &self.data
+            };
+
+            let mut data = handle_path.data[..handle_path.nodes_len()].to_vec();
+            data.extend_from_slice(rest);
+            candidates.push(unsafe { Self::from_boxed_bytes(data.into_boxed_slice()) });
+        }
+
+        Ok(candidates)
+    }
+
+    /// Create a hard drive media [short-form] path to the file `path` on
+    /// the GPT partition with the unique partition GUID `partition_guid`.
+    ///
+    /// The path consists of a [`HardDrive`] node followed by a
+    /// [`FilePath`] node. The partition number, start and size of the
+    /// `HardDrive` node are set to zero, since only the partition GUID is
+    /// known. [`expand_short_form`] matches GPT partitions by their GUID
+    /// only, but some firmware also compares the start and size when it
+    /// resolves a short-form path. Pass the result through
+    /// [`expand_short_form`] to get a full path before using it in a boot
+    /// option or loading an image from it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uefi::{cstr16, guid};
+    /// use uefi::proto::device_path::DevicePath;
+    ///
+    /// # fn main() -> Result<(), uefi::proto::device_path::build::BuildError> {
+    /// let path = DevicePath::for_file_on_partition(
+    ///     guid!("6e4f1a25-4e80-4a6c-9e8d-0c7a8a6c6f33"),
+    ///     cstr16!(r"\EFI\BOOT\BOOTX64.EFI"),
+    /// )?;
+    /// assert_eq!(
+    ///     format!("{path}"),
+    ///     r"HD(0,GPT,6E4F1A25-4E80-4A6C-9E8D-0C7A8A6C6F33,0x0,0x0)/\EFI\BOOT\BOOTX64.EFI"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`FilePath`]: media::FilePath
+    /// [`HardDrive`]: media::HardDrive
+    /// [`expand_short_form`]: Self::expand_short_form
+    /// [short-form]: Self::expand_short_form
+    #[cfg(feature = "alloc")]
+    pub fn for_file_on_partition(
+        partition_guid: Guid,
+        path: &CStr16,
+    ) -> Result<Box<Self>, BuildError> {
+        let mut v = Vec::new();
+        let path = DevicePathBuilder::with_vec(&mut v)
+            .push(&build::media::HardDrive {
+                partition_number: 0,
+                partition_start: 0,
+                partition_size: 0,
+                partition_signature: PartitionSignature::Guid(partition_guid),
+                partition_format: media::PartitionFormat::GPT,
+            })?
+            .push(&build::media::FilePath { path_name: path })?
+            .finalize()?;
+        Ok(path.to_boxed())
+    }
+
+    /// Create a full path to the file `path` on the device that the image
+    /// `image_handle` was loaded from.
+    ///
+    /// The path is made of the device path of [`LoadedImage::device`]
+    /// followed by a [`FilePath`] node. This can be used to load another
+    /// image that lives next to the current one, e.g. with
+    /// `DevicePath::for_image_handle_file(boot::image_handle(), path)`.
+    ///
+    /// # Errors
+    ///
+    /// See [`ImageFilePathError`].
+    ///
+    /// [`FilePath`]: media::FilePath
+    /// [`LoadedImage::device`]: crate::proto::loaded_image::LoadedImage::device
+    #[cfg(feature = "alloc")]
+    pub fn for_image_handle_file(
+        image_handle: Handle,
+        path: &CStr16,
+    ) -> Result<Box<Self>, ImageFilePathError> {
+        let open = |handle| OpenProtocolParams {
+            handle,
+            agent: boot::image_handle(),
+            controller: None,
+        };
+        let loaded_image = unsafe {
+            boot::open_protocol::<LoadedImage>(
+                open(image_handle),
+                OpenProtocolAttributes::GetProtocol,
+            )
+        }
+        .map_err(ImageFilePathError::CantOpenProtocol)?;
+        let device = loaded_image.device().ok_or(ImageFilePathError::NoDevice)?;
+        let device_path = unsafe {
+            boot::open_protocol::<Self>(open(device), OpenProtocolAttributes::GetProtocol)
+        }
+        .map_err(ImageFilePathError::CantOpenProtocol)?;
+
+        let mut v = Vec::new();
+        let mut builder = DevicePathBuilder::with_vec(&mut v);
+        for node in device_path.node_iter() {
+            builder = builder.push(&node)?;
+        }
+        let path = builder
+            .push(&build::media::FilePath { path_name: path })?
+            .finalize()?;
+        Ok(path.to_boxed())
+    }
+
+    /// Create a path containing a single [`Uri`] node, e.g. for HTTP
+    /// boot. The firmware boot manager expands the URI to a full path
+    /// through one of the network interfaces.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uefi::proto::device_path::DevicePath;
+    ///
+    /// # fn main() -> Result<(), uefi::proto::device_path::build::BuildError> {
+    /// let path = DevicePath::for_uri("http://192.168.0.1/boot.efi")?;
+    /// assert_eq!(format!("{path}"), "Uri(http://192.168.0.1/boot.efi)");
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Uri`]: messaging::Uri
+    #[cfg(feature = "alloc")]
+    pub fn for_uri(url: &str) -> Result<Box<Self>, BuildError> {
+        let mut v = Vec::new();
+        let path = DevicePathBuilder::with_vec(&mut v)
+            .push(&build::messaging::Uri {
+                value: url.as_bytes(),
+            })?
+            .finalize()?;
+        Ok(path.to_boxed())
+    }
+
     /// Transforms the device path to its string representation using the
     /// [`DevicePathToText`] protocol.
+    ///
+    /// To convert without relying on the firmware, use the [`Display`]
+    /// implementation instead (e.g. with `format!`).
     #[cfg(feature = "alloc")]
     pub fn to_string(
         &self,
@@ -852,6 +1263,46 @@ impl core::error::Error for DevicePathUtilitiesError {
     }
 }
 
+/// Errors that may occur in [`DevicePath::for_image_handle_file`].
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+pub enum ImageFilePathError {
+    /// The [`LoadedImage`] protocol of the image, or the [`DevicePath`]
+    /// protocol of its device, could not be opened.
+    ///
+    /// [`LoadedImage`]: crate::proto::loaded_image::LoadedImage
+    CantOpenProtocol(crate::Error),
+    /// The image has no device handle.
+    NoDevice,
+    /// The path could not be built.
+    Build(BuildError),
+}
+
+#[cfg(feature = "alloc")]
+impl From<BuildError> for ImageFilePathError {
+    fn from(err: BuildError) -> Self {
+        Self::Build(err)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Display for ImageFilePathError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl core::error::Error for ImageFilePathError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::CantOpenProtocol(e) => Some(e),
+            Self::Build(e) => Some(e),
+            Self::NoDevice => None,
+        }
+    }
+}
+
 /// Helper function to open the [`DevicePathUtilities`] protocol using the boot
 /// services.
 #[cfg(feature = "alloc")]
@@ -1072,4 +1523,118 @@ mod tests {
             NodeConversionError::DifferentType
         );
     }
+
+    fn parse(text: &str) -> Box<DevicePath> {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_node_matches() {
+        let matches = |a: &str, b: &str| {
+            let (a, b) = (parse(a), parse(b));
+            let a = a.node_iter().next().unwrap();
+            let b = b.node_iter().next().unwrap();
+            assert_eq!(a.matches(b), b.matches(a));
+            a.matches(b)
+        };
+
+        // GPT partitions only compare the signature.
+        let gpt = "HD(1,GPT,6E4F1A25-4E80-4A6C-9E8D-0C7A8A6C6F33,0x800,0x1000)";
+        assert!(matches(
+            gpt,
+            "HD(2,GPT,6E4F1A25-4E80-4A6C-9E8D-0C7A8A6C6F33,0x0,0x0)"
+        ));
+        assert!(!matches(
+            gpt,
+            "HD(1,GPT,11111111-4E80-4A6C-9E8D-0C7A8A6C6F33,0x800,0x1000)"
+        ));
+
+        // MBR partitions also compare the partition number.
+        let mbr = "HD(1,MBR,0x1234ABCD,0x3F,0x1000)";
+        assert!(matches(mbr, "HD(1,MBR,0x1234ABCD,0x0,0x0)"));
+        assert!(!matches(mbr, "HD(2,MBR,0x1234ABCD,0x3F,0x1000)"));
+
+        // File paths ignore ASCII case.
+        assert!(matches(
+            "\\EFI\\BOOT\\BOOTX64.EFI",
+            "\\efi\\boot\\bootx64.efi"
+        ));
+        assert!(!matches(
+            "\\EFI\\BOOT\\BOOTX64.EFI",
+            "\\efi\\boot\\bootia32.efi"
+        ));
+
+        // Other nodes must be equal.
+        assert!(matches("Pci(0x1,0x0)", "Pci(0x1,0x0)"));
+        assert!(!matches("Pci(0x1,0x0)", "Pci(0x1,0x1)"));
+        assert!(!matches("Pci(0x1,0x0)", "PcCard(0x1)"));
+    }
+
+    #[test]
+    fn test_mac_node_matches() {
+        let dp = parse("MAC(525400123456,0x1)");
+        let a = dp.node_iter().next().unwrap();
+
+        // Bytes past the six used by an Ethernet address are ignored.
+        let mut raw = Vec::new();
+        add_node(&mut raw, a.device_type().0, a.sub_type().0, a.data());
+        raw[4 + 31] = 0xff;
+        let b = <&DevicePathNode>::try_from(raw.as_slice()).unwrap();
+        assert!(a.matches(b));
+        assert_ne!(a, b);
+
+        raw[4] = 0;
+        let b = <&DevicePathNode>::try_from(raw.as_slice()).unwrap();
+        assert!(!a.matches(b));
+    }
+
+    #[test]
+    fn test_starts_with() {
+        let disk = parse("PciRoot(0x0)/Pci(0x1,0x0)/NVMe(0x1,00-00-00-00-00-00-00-00)");
+        let partition = parse(
+            "PciRoot(0x0)/Pci(0x1,0x0)/NVMe(0x1,00-00-00-00-00-00-00-00)/HD(1,GPT,6E4F1A25-4E80-4A6C-9E8D-0C7A8A6C6F33,0x800,0x1000)/\\EFI",
+        );
+        let other_disk = parse("PciRoot(0x0)/Pci(0x2,0x0)/NVMe(0x1,00-00-00-00-00-00-00-00)");
+
+        assert!(partition.starts_with(&disk));
+        assert!(partition.starts_with(&partition));
+        assert!(partition.starts_with(&parse("")));
+        assert!(!partition.starts_with(&other_disk));
+        assert!(!disk.starts_with(&partition));
+
+        let rest = partition.strip_prefix(&disk).unwrap();
+        assert_eq!(
+            alloc::format!("{rest}"),
+            "HD(1,GPT,6E4F1A25-4E80-4A6C-9E8D-0C7A8A6C6F33,0x800,0x1000)/\\EFI"
+        );
+        assert_eq!(rest.node_iter().count(), 2);
+        assert!(partition
+            .strip_prefix(&partition)
+            .unwrap()
+            .node_iter()
+            .next()
+            .is_none());
+        assert!(partition.strip_prefix(&other_disk).is_none());
+    }
+
+    #[test]
+    fn test_instance_to_device_path() {
+        let raw_data = create_raw_device_path();
+        let dp = <&DevicePath>::try_from(raw_data.as_slice()).unwrap();
+        assert!(dp.is_multi_instance());
+
+        let paths: Vec<_> = dp
+            .instance_iter()
+            .map(DevicePathInstance::to_device_path)
+            .collect();
+        assert_eq!(paths.len(), 2);
+        for path in &paths {
+            assert!(!path.is_multi_instance());
+            assert_eq!(path.node_iter().count(), 2);
+            assert_eq!(path.as_bytes()[14..], [0x7f, 0xff, 4, 0]);
+        }
+        let nodes: Vec<_> = paths[1].node_iter().collect();
+        check_node(nodes[0], 0xa2, 0xb2, &[30, 31]);
+        check_node(nodes[1], 0xa3, 0xb3, &[40, 41, 42, 43]);
+    }
 }
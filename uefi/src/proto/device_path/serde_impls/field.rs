@@ -0,0 +1,342 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Serialization of individual node fields.
+//!
+//! Fields use the same [`kind`] markers as the text representation, so
+//! that e.g. a field shown as a GUID in text is also serialized as a
+//! GUID. Newtype enums and flags are serialized as their raw value.
+
+use crate::data_types::UnalignedSlice;
+use crate::mem::memory_map::MemoryType;
+use crate::proto::device_path::hardware::BmcInterfaceType;
+use crate::proto::device_path::media::RamDiskType;
+use crate::proto::device_path::messaging::{
+    BluetoothLeAddressType, InfinibandResourceFlags, Ipv4AddressOrigin, Ipv6AddressOrigin,
+    IscsiLoginOptions, IscsiProtocol, MasterSlave, Parity, PrimarySecondary, StopBits,
+};
+use crate::proto::device_path::text::field::{fmt_ascii, fmt_ucs2, kind};
+use crate::Guid;
+use core::fmt;
+use core::marker::PhantomData;
+use serde::{Serialize, Serializer};
+
+#[cfg(feature = "alloc")]
+use {
+    crate::proto::device_path::text::field::{parse_ascii, to_array},
+    alloc::string::String,
+    alloc::vec::Vec,
+    serde::de::{self, Deserialize, Deserializer, Error, SeqAccess, Visitor},
+};
+
+/// Serialize a field value of type `T`.
+pub(crate) trait SerializeField<T: ?Sized> {
+    fn serialize_field<S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error>;
+}
+
+/// Deserialize a field value of type `T`. Slice fields are
+/// deserialized into a `Vec`.
+#[cfg(feature = "alloc")]
+pub(crate) trait DeserializeField<T> {
+    fn deserialize_field<'de, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error>;
+}
+
+/// Field value that serializes according to the kind `K`.
+pub(crate) struct SerdeField<'a, K, T: ?Sized>(&'a T, PhantomData<K>);
+
+impl<'a, K, T: ?Sized> SerdeField<'a, K, T> {
+    pub(crate) const fn new(value: &'a T) -> Self {
+        Self(value, PhantomData)
+    }
+}
+
+impl<K: SerializeField<T>, T: ?Sized> Serialize for SerdeField<'_, K, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        K::serialize_field(self.0, serializer)
+    }
+}
+
+/// Field value deserialized according to the kind `K`.
+#[cfg(feature = "alloc")]
+pub(crate) struct Deserialized<K, T>(pub(crate) T, PhantomData<K>);
+
+#[cfg(feature = "alloc")]
+impl<'de, K: DeserializeField<T>, T> Deserialize<'de> for Deserialized<K, T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        K::deserialize_field(deserializer).map(|value| Self(value, PhantomData))
+    }
+}
+
+/// Implement a kind for types that are serialized as themselves.
+macro_rules! impl_plain_fields {
+    ($kind:ident: $($ty:ty),*) => {
+        $(
+            impl SerializeField<$ty> for kind::$kind {
+                fn serialize_field<S: Serializer>(
+                    value: &$ty,
+                    serializer: S,
+                ) -> Result<S::Ok, S::Error> {
+                    value.serialize(serializer)
+                }
+            }
+
+            #[cfg(feature = "alloc")]
+            impl DeserializeField<$ty> for kind::$kind {
+                fn deserialize_field<'de, D: Deserializer<'de>>(
+                    deserializer: D,
+                ) -> Result<$ty, D::Error> {
+                    <$ty>::deserialize(deserializer)
+                }
+            }
+        )*
+    };
+}
+
+impl_plain_fields!(Hex: u8, u16, u32, u64);
+impl_plain_fields!(Decimal: u8, u16, u32, u64);
+impl_plain_fields!(Guid: Guid);
+impl_plain_fields!(Eui64: u64);
+impl_plain_fields!(BbsDeviceType: u16);
+
+/// Implement the `Enum` kind for newtype enums, which are serialized
+/// as their inner value.
+macro_rules! impl_newtype_enum_fields {
+    ($($ty:ident: $inner:ty),*) => {
+        $(
+            impl SerializeField<$ty> for kind::Enum {
+                fn serialize_field<S: Serializer>(
+                    value: &$ty,
+                    serializer: S,
+                ) -> Result<S::Ok, S::Error> {
+                    value.0.serialize(serializer)
+                }
+            }
+
+            #[cfg(feature = "alloc")]
+            impl DeserializeField<$ty> for kind::Enum {
+                fn deserialize_field<'de, D: Deserializer<'de>>(
+                    deserializer: D,
+                ) -> Result<$ty, D::Error> {
+                    <$inner>::deserialize(deserializer).map($ty)
+                }
+            }
+        )*
+    };
+}
+
+impl_newtype_enum_fields!(
+    MemoryType: u32,
+    BmcInterfaceType: u8,
+    BluetoothLeAddressType: u8,
+    PrimarySecondary: u8,
+    MasterSlave: u8,
+    Parity: u8,
+    StopBits: u8,
+    Ipv4AddressOrigin: u8,
+    Ipv6AddressOrigin: u8,
+    IscsiProtocol: u16,
+    RamDiskType: Guid
+);
+
+/// Implement the `Enum` kind for flags, which are serialized as their
+/// bits. Unknown bits are retained.
+macro_rules! impl_flags_fields {
+    ($($ty:ident: $bits:ty),*) => {
+        $(
+            impl SerializeField<$ty> for kind::Enum {
+                fn serialize_field<S: Serializer>(
+                    value: &$ty,
+                    serializer: S,
+                ) -> Result<S::Ok, S::Error> {
+                    value.bits().serialize(serializer)
+                }
+            }
+
+            #[cfg(feature = "alloc")]
+            impl DeserializeField<$ty> for kind::Enum {
+                fn deserialize_field<'de, D: Deserializer<'de>>(
+                    deserializer: D,
+                ) -> Result<$ty, D::Error> {
+                    <$bits>::deserialize(deserializer).map($ty::from_bits_retain)
+                }
+            }
+        )*
+    };
+}
+
+impl_flags_fields!(InfinibandResourceFlags: u32, IscsiLoginOptions: u16);
+
+/// Visitor that accepts either a byte buffer or a sequence of bytes,
+/// so that bytes round-trip through formats like JSON that serialize
+/// them as a sequence.
+#[cfg(feature = "alloc")]
+struct BytesVisitor;
+
+#[cfg(feature = "alloc")]
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("bytes")
+    }
+
+    fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: Error>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(b) = seq.next_element()? {
+            bytes.push(b);
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub(crate) fn deserialize_bytes<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<u8>, D::Error> {
+    deserializer.deserialize_bytes(BytesVisitor)
+}
+
+/// Copy deserialized bytes into a zero-filled array.
+#[cfg(feature = "alloc")]
+fn deserialize_array<'de, D: Deserializer<'de>, const N: usize>(
+    deserializer: D,
+) -> Result<[u8; N], D::Error> {
+    let bytes = deserialize_bytes(deserializer)?;
+    to_array(&bytes).map_err(|_| D::Error::invalid_length(bytes.len(), &"at most N bytes"))
+}
+
+/// Implement a bytes kind for `&[u8]`, `[u8; N]`, and `Vec<u8>`.
+macro_rules! impl_bytes_fields {
+    ($($kind:ident),*) => {
+        $(
+            impl SerializeField<&[u8]> for kind::$kind {
+                fn serialize_field<S: Serializer>(
+                    value: &&[u8],
+                    serializer: S,
+                ) -> Result<S::Ok, S::Error> {
+                    serializer.serialize_bytes(value)
+                }
+            }
+
+            impl<const N: usize> SerializeField<[u8; N]> for kind::$kind {
+                fn serialize_field<S: Serializer>(
+                    value: &[u8; N],
+                    serializer: S,
+                ) -> Result<S::Ok, S::Error> {
+                    serializer.serialize_bytes(value)
+                }
+            }
+
+            #[cfg(feature = "alloc")]
+            impl DeserializeField<Vec<u8>> for kind::$kind {
+                fn deserialize_field<'de, D: Deserializer<'de>>(
+                    deserializer: D,
+                ) -> Result<Vec<u8>, D::Error> {
+                    deserialize_bytes(deserializer)
+                }
+            }
+
+            #[cfg(feature = "alloc")]
+            impl<const N: usize> DeserializeField<[u8; N]> for kind::$kind {
+                fn deserialize_field<'de, D: Deserializer<'de>>(
+                    deserializer: D,
+                ) -> Result<[u8; N], D::Error> {
+                    deserialize_array(deserializer)
+                }
+            }
+        )*
+    };
+}
+
+impl_bytes_fields!(HexBytes, GuidBytes);
+
+/// Displays a UCS-2 field, for use with [`Serializer::collect_str`].
+struct DisplayUcs2<'a, 'b>(&'a UnalignedSlice<'b, u16>);
+
+impl fmt::Display for DisplayUcs2<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_ucs2(self.0, f)
+    }
+}
+
+impl SerializeField<UnalignedSlice<'_, u16>> for kind::Str {
+    fn serialize_field<S: Serializer>(
+        value: &UnalignedSlice<'_, u16>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&DisplayUcs2(value))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl DeserializeField<Vec<u16>> for kind::Str {
+    fn deserialize_field<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u16>, D::Error> {
+        String::deserialize(deserializer).map(|s| s.encode_utf16().collect())
+    }
+}
+
+/// Displays an ASCII field, for use with [`Serializer::collect_str`].
+struct DisplayAscii<'a>(&'a [u8]);
+
+impl fmt::Display for DisplayAscii<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_ascii(self.0, f)
+    }
+}
+
+#[cfg(feature = "alloc")]
+fn deserialize_ascii<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    parse_ascii(&s).map_err(|_| D::Error::invalid_value(de::Unexpected::Str(&s), &"ASCII string"))
+}
+
+impl SerializeField<&[u8]> for kind::Ascii {
+    fn serialize_field<S: Serializer>(value: &&[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&DisplayAscii(value))
+    }
+}
+
+impl<const N: usize> SerializeField<[u8; N]> for kind::Ascii {
+    fn serialize_field<S: Serializer>(value: &[u8; N], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&DisplayAscii(value))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl DeserializeField<Vec<u8>> for kind::Ascii {
+    fn deserialize_field<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        deserialize_ascii(deserializer)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> DeserializeField<[u8; N]> for kind::Ascii {
+    fn deserialize_field<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; N], D::Error> {
+        let bytes = deserialize_ascii(deserializer)?;
+        to_array(&bytes).map_err(|_| D::Error::invalid_length(bytes.len(), &"at most N bytes"))
+    }
+}
+
+impl SerializeField<&[u8]> for kind::CStr8 {
+    fn serialize_field<S: Serializer>(value: &&[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&DisplayAscii(value))
+    }
+}
+
+/// The null terminator is not part of the serialized string, and is
+/// added back when deserializing.
+#[cfg(feature = "alloc")]
+impl DeserializeField<Vec<u8>> for kind::CStr8 {
+    fn deserialize_field<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let mut bytes = deserialize_ascii(deserializer)?;
+        bytes.push(0);
+        Ok(bytes)
+    }
+}
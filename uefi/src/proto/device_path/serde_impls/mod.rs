@@ -0,0 +1,411 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! [`serde`] support for device paths, enabled with the `serde` feature.
+//!
+//! A [`DevicePath`] or [`DevicePathInstance`] is serialized as a
+//! sequence of nodes. The terminating [`END_ENTIRE`] node is not
+//! included, but the [`END_INSTANCE`] nodes separating the instances
+//! of a multi-instance path are.
+//!
+//! Each [`DevicePathNode`] is serialized as a variant of an enum named
+//! `DevicePathNode`, using the name of the corresponding
+//! [`DevicePathNodeEnum`] variant and a struct of the node's fields.
+//! Nodes that are not known to [`DevicePathNodeEnum`], or that fail to
+//! convert to it, are serialized as the `Unknown` variant with the raw
+//! `device_type`, `sub_type`, and `data`. For example, in JSON:
+//!
+//! ```json
+//! [
+//!     {"AcpiAcpi": {"hid": 2604662989, "uid": 0}},
+//!     {"HardwarePci": {"function": 2, "device": 31}},
+//!     {"Unknown": {"device_type": 1, "sub_type": 255, "data": [1, 2]}}
+//! ]
+//! ```
+//!
+//! With the `alloc` feature, [`DevicePathBuf`] and `Box<DevicePath>`
+//! implement [`Deserialize`]. Nodes are rebuilt with the same builders
+//! as [`DevicePathBuilder`], so a path round-trips through any format
+//! supported by `serde`.
+//!
+//! [`DevicePathBuilder`]: super::build::DevicePathBuilder
+//! [`DevicePathBuf`]: super::DevicePathBuf
+//! [`DevicePathNodeEnum`]: super::DevicePathNodeEnum
+//! [`Deserialize`]: serde::Deserialize
+//! [`END_ENTIRE`]: super::DeviceSubType::END_ENTIRE
+//! [`END_INSTANCE`]: super::DeviceSubType::END_INSTANCE
+
+pub(crate) mod field;
+pub(crate) mod node;
+
+use super::device_path_gen::NODE_VARIANT_NAMES;
+use super::{DevicePath, DevicePathInstance, DevicePathNode, DevicePathNodeIterator};
+use serde::ser::SerializeSeq;
+use serde::{Serialize, Serializer};
+
+#[cfg(feature = "alloc")]
+use {
+    super::device_path_gen::node_from_serde,
+    super::DevicePathBuf,
+    alloc::boxed::Box,
+    core::fmt,
+    serde::de::{self, DeserializeSeed, Deserializer, EnumAccess, SeqAccess, Visitor},
+    serde::Deserialize,
+};
+
+/// Name of the variant used for unknown nodes.
+const UNKNOWN_VARIANT_NAME: &str = "Unknown";
+
+/// Index of the variant used for unknown nodes, for formats that
+/// identify variants by index.
+#[allow(clippy::cast_possible_truncation)]
+const UNKNOWN_VARIANT_INDEX: u32 = (NODE_VARIANT_NAMES.len() - 1) as u32;
+
+impl Serialize for DevicePathNode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.as_enum() {
+            Ok(node) => node.serialize(serializer),
+            Err(_) => node::serialize_unknown(self, serializer),
+        }
+    }
+}
+
+fn serialize_nodes<S: Serializer>(
+    nodes: DevicePathNodeIterator<'_>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut seq = serializer.serialize_seq(None)?;
+    for node in nodes {
+        seq.serialize_element(node)?;
+    }
+    seq.end()
+}
+
+impl Serialize for DevicePathInstance {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_nodes(self.node_iter(), serializer)
+    }
+}
+
+impl Serialize for DevicePath {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_nodes(self.node_iter(), serializer)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Serialize for DevicePathBuf {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_device_path().serialize(serializer)
+    }
+}
+
+/// Name of a node variant, deserialized either from a string or from
+/// the variant index.
+#[cfg(feature = "alloc")]
+struct VariantName(&'static str);
+
+#[cfg(feature = "alloc")]
+impl<'de> Deserialize<'de> for VariantName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct NameVisitor;
+
+        impl Visitor<'_> for NameVisitor {
+            type Value = VariantName;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("device path node variant")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<VariantName, E> {
+                usize::try_from(v)
+                    .ok()
+                    .and_then(|i| NODE_VARIANT_NAMES.get(i))
+                    .map(|name| VariantName(name))
+                    .ok_or_else(|| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<VariantName, E> {
+                NODE_VARIANT_NAMES
+                    .iter()
+                    .find(|name| **name == v)
+                    .map(|name| VariantName(name))
+                    .ok_or_else(|| E::unknown_variant(v, NODE_VARIANT_NAMES))
+            }
+        }
+
+        deserializer.deserialize_identifier(NameVisitor)
+    }
+}
+
+/// Deserializes one node and appends it to the path.
+#[cfg(feature = "alloc")]
+struct NodeSeed<'a>(&'a mut DevicePathBuf);
+
+#[cfg(feature = "alloc")]
+impl<'de> DeserializeSeed<'de> for NodeSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_enum("DevicePathNode", NODE_VARIANT_NAMES, self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'de> Visitor<'de> for NodeSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("device path node")
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<(), A::Error> {
+        let (VariantName(name), variant) = data.variant()?;
+        node_from_serde(name, variant, self.0)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'de> Deserialize<'de> for DevicePathBuf {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PathVisitor;
+
+        impl<'de> Visitor<'de> for PathVisitor {
+            type Value = DevicePathBuf;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("sequence of device path nodes")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<DevicePathBuf, A::Error> {
+                let mut path = DevicePathBuf::new();
+                while seq.next_element_seed(NodeSeed(&mut path))?.is_some() {}
+                Ok(path)
+            }
+        }
+
+        deserializer.deserialize_seq(PathVisitor)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'de> Deserialize<'de> for Box<DevicePath> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        DevicePathBuf::deserialize(deserializer).map(DevicePathBuf::into_boxed_device_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::device_path::{DeviceSubType, DeviceType};
+    use serde_test::{assert_de_tokens_error, assert_tokens, Token};
+
+    fn node(variant: &'static str) -> Token {
+        Token::NewtypeVariant {
+            name: "DevicePathNode",
+            variant,
+        }
+    }
+
+    #[test]
+    fn test_generated_and_custom_nodes() {
+        let path: DevicePathBuf =
+            "Pci(0x1f,0x2)/HD(1,GPT,5D1D4F95-54A9-4A3F-A3C4-AB6C3E5C2C57,0x800,0x1000)/\\a"
+                .parse()
+                .unwrap();
+        assert_tokens(
+            &path,
+            &[
+                Token::Seq { len: None },
+                node("HardwarePci"),
+                Token::Struct {
+                    name: "Pci",
+                    len: 2,
+                },
+                Token::Str("function"),
+                Token::U8(2),
+                Token::Str("device"),
+                Token::U8(0x1f),
+                Token::StructEnd,
+                node("MediaHardDrive"),
+                Token::Struct {
+                    name: "HardDrive",
+                    len: 5,
+                },
+                Token::Str("partition_number"),
+                Token::U32(1),
+                Token::Str("partition_start"),
+                Token::U64(0x800),
+                Token::Str("partition_size"),
+                Token::U64(0x1000),
+                Token::Str("partition_signature"),
+                Token::NewtypeVariant {
+                    name: "PartitionSignature",
+                    variant: "Guid",
+                },
+                Token::Str("5d1d4f95-54a9-4a3f-a3c4-ab6c3e5c2c57"),
+                Token::Str("partition_format"),
+                Token::U8(2),
+                Token::StructEnd,
+                node("MediaFilePath"),
+                Token::Struct {
+                    name: "FilePath",
+                    len: 1,
+                },
+                Token::Str("path_name"),
+                Token::Str("\\a"),
+                Token::StructEnd,
+                Token::SeqEnd,
+            ],
+        );
+    }
+
+    #[test]
+    fn test_multi_instance() {
+        let path: DevicePathBuf = "Pci(0x0,0x0),VenHw(E0C14753-F9BE-11D2-9A0C-0090273FC14D,0102)"
+            .parse()
+            .unwrap();
+        assert_tokens(
+            &path,
+            &[
+                Token::Seq { len: None },
+                node("HardwarePci"),
+                Token::Struct {
+                    name: "Pci",
+                    len: 2,
+                },
+                Token::Str("function"),
+                Token::U8(0),
+                Token::Str("device"),
+                Token::U8(0),
+                Token::StructEnd,
+                node("EndInstance"),
+                Token::Struct {
+                    name: "Instance",
+                    len: 0,
+                },
+                Token::StructEnd,
+                node("HardwareVendor"),
+                Token::Struct {
+                    name: "Vendor",
+                    len: 2,
+                },
+                Token::Str("vendor_guid"),
+                Token::Str("e0c14753-f9be-11d2-9a0c-0090273fc14d"),
+                Token::Str("vendor_defined_data"),
+                Token::Bytes(&[1, 2]),
+                Token::StructEnd,
+                Token::SeqEnd,
+            ],
+        );
+    }
+
+    #[test]
+    fn test_unknown_node() {
+        let path: DevicePathBuf = [&[0x01, 0xff, 6, 0, 1, 2][..], &[0x7f, 0xff, 4, 0]]
+            .concat()
+            .try_into()
+            .unwrap();
+        let first = path.node_iter().next().unwrap();
+        assert_eq!(
+            first.full_type(),
+            (DeviceType::HARDWARE, DeviceSubType(0xff))
+        );
+
+        assert_tokens(
+            &path,
+            &[
+                Token::Seq { len: None },
+                node("Unknown"),
+                Token::Struct {
+                    name: "Unknown",
+                    len: 3,
+                },
+                Token::Str("device_type"),
+                Token::U8(1),
+                Token::Str("sub_type"),
+                Token::U8(0xff),
+                Token::Str("data"),
+                Token::Bytes(&[1, 2]),
+                Token::StructEnd,
+                Token::SeqEnd,
+            ],
+        );
+
+        // End nodes can't be passed in as unknown nodes.
+        assert_de_tokens_error::<DevicePathBuf>(
+            &[
+                Token::Seq { len: None },
+                node("Unknown"),
+                Token::Struct {
+                    name: "Unknown",
+                    len: 3,
+                },
+                Token::Str("device_type"),
+                Token::U8(0x7f),
+                Token::Str("sub_type"),
+                Token::U8(0xff),
+                Token::Str("data"),
+                Token::Bytes(&[]),
+                Token::StructEnd,
+            ],
+            "end nodes must not be serialized as unknown",
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_de_tokens_error::<DevicePathBuf>(
+            &[Token::Seq { len: None }, node("NotANode")],
+            "unknown variant `NotANode`, expected one of `EndInstance`, `EndEntire`, \
+             `HardwarePci`, `HardwarePccard`, `HardwareMemoryMapped`, `HardwareVendor`, \
+             `HardwareController`, `HardwareBmc`, `AcpiAcpi`, `AcpiExpanded`, `AcpiAdr`, \
+             `AcpiNvdimm`, `MessagingAtapi`, `MessagingScsi`, `MessagingFibreChannel`, \
+             `MessagingFibreChannelEx`, `MessagingIeee1394`, `MessagingUsb`, `MessagingSata`, \
+             `MessagingUsbWwid`, `MessagingDeviceLogicalUnit`, `MessagingUsbClass`, \
+             `MessagingI2o`, `MessagingMacAddress`, `MessagingIpv4`, `MessagingIpv6`, \
+             `MessagingVlan`, `MessagingInfiniband`, `MessagingUart`, `MessagingVendor`, \
+             `MessagingSasEx`, `MessagingIscsi`, `MessagingNvmeNamespace`, `MessagingUri`, \
+             `MessagingUfs`, `MessagingSd`, `MessagingBluetooth`, `MessagingWifi`, \
+             `MessagingEmmc`, `MessagingBluetoothLe`, `MessagingDns`, `MessagingNvdimmNamespace`, \
+             `MessagingRestService`, `MessagingNvmeOfNamespace`, `MediaHardDrive`, \
+             `MediaCdRom`, `MediaVendor`, `MediaFilePath`, `MediaProtocol`, \
+             `MediaPiwgFirmwareFile`, `MediaPiwgFirmwareVolume`, `MediaRelativeOffsetRange`, \
+             `MediaRamDisk`, `BiosBootSpecBootSpecification`, `Unknown`",
+        );
+
+        assert_de_tokens_error::<DevicePathBuf>(
+            &[
+                Token::Seq { len: None },
+                node("AcpiAdr"),
+                Token::Struct {
+                    name: "Adr",
+                    len: 1,
+                },
+                Token::Str("adr"),
+                Token::Seq { len: Some(0) },
+                Token::SeqEnd,
+                Token::StructEnd,
+            ],
+            "invalid length 0, expected at least one ADR value",
+        );
+
+        assert_de_tokens_error::<DevicePathBuf>(
+            &[
+                Token::Seq { len: None },
+                node("EndEntire"),
+                Token::Struct {
+                    name: "Entire",
+                    len: 0,
+                },
+                Token::StructEnd,
+            ],
+            "unexpected END_ENTIRE",
+        );
+
+        assert_eq!(
+            NODE_VARIANT_NAMES[UNKNOWN_VARIANT_INDEX as usize],
+            UNKNOWN_VARIANT_NAME
+        );
+    }
+}
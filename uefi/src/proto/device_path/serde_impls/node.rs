@@ -0,0 +1,339 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Serialization of nodes whose fields need custom handling.
+
+use super::field::SerdeField;
+use crate::proto::device_path::acpi::Expanded;
+use crate::proto::device_path::media::{FilePath, HardDrive};
+use crate::proto::device_path::messaging::{Dns, DnsAddressType, RestService};
+use crate::proto::device_path::text::field::kind;
+use crate::proto::device_path::{acpi, DevicePathNode};
+use crate::Guid;
+use serde::ser::{SerializeSeq, SerializeStruct};
+use serde::{Serialize, Serializer};
+
+#[cfg(feature = "alloc")]
+use {
+    super::field::{deserialize_bytes, Deserialized},
+    crate::proto::device_path::build::{self, acpi::AdrSlice, messaging::RestServiceVendorData},
+    crate::proto::device_path::media::{PartitionFormat, PartitionSignature},
+    crate::proto::device_path::messaging::{RestServiceAccessMode, RestServiceType},
+    crate::proto::device_path::{DevicePathBuf, DeviceType},
+    crate::proto::network::IpAddress,
+    crate::CString16,
+    alloc::string::String,
+    alloc::vec::Vec,
+    serde::de::{Error, VariantAccess},
+    serde::Deserialize,
+};
+
+impl Serialize for Expanded {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("Expanded", 6)?;
+        s.serialize_field("hid", &self.hid())?;
+        s.serialize_field("uid", &self.uid())?;
+        s.serialize_field("cid", &self.cid())?;
+        s.serialize_field(
+            "hid_str",
+            &SerdeField::<kind::CStr8, _>::new(&self.hid_str()),
+        )?;
+        s.serialize_field(
+            "uid_str",
+            &SerdeField::<kind::CStr8, _>::new(&self.uid_str()),
+        )?;
+        s.serialize_field(
+            "cid_str",
+            &SerdeField::<kind::CStr8, _>::new(&self.cid_str()),
+        )?;
+        s.end()
+    }
+}
+
+impl Serialize for acpi::Adr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        /// The individual ADR values.
+        struct Values<'a>(&'a acpi::Adr);
+
+        impl Serialize for Values<'_> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let adr = self.0.adr();
+                let mut seq = serializer.serialize_seq(Some(adr.len()))?;
+                for value in adr.iter() {
+                    seq.serialize_element(&value)?;
+                }
+                seq.end()
+            }
+        }
+
+        let mut s = serializer.serialize_struct("Adr", 1)?;
+        s.serialize_field("adr", &Values(self))?;
+        s.end()
+    }
+}
+
+impl Serialize for Dns {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        /// The addresses, each serialized as 4 (IPv4) or 16 (IPv6)
+        /// bytes.
+        struct Addresses<'a>(&'a Dns);
+
+        impl Serialize for Addresses<'_> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let len = if self.0.address_type() == DnsAddressType::IPV4 {
+                    4
+                } else {
+                    16
+                };
+                let addresses = self.0.addresses();
+                let mut seq = serializer.serialize_seq(Some(addresses.len()))?;
+                for address in addresses.iter() {
+                    seq.serialize_element(&SerdeField::<kind::HexBytes, _>::new(
+                        &&address.0[..len],
+                    ))?;
+                }
+                seq.end()
+            }
+        }
+
+        let mut s = serializer.serialize_struct("Dns", 2)?;
+        s.serialize_field("address_type", &self.address_type().0)?;
+        s.serialize_field("addresses", &Addresses(self))?;
+        s.end()
+    }
+}
+
+/// Vendor data of a [`RestService`] node.
+#[derive(Serialize)]
+struct RestServiceVendor<'a> {
+    vendor_guid: Guid,
+    vendor_defined_data: SerdeField<'a, kind::HexBytes, &'a [u8]>,
+}
+
+impl Serialize for RestService {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let vendor_data = self.vendor_guid_and_data();
+        let vendor = vendor_data
+            .as_ref()
+            .map(|(vendor_guid, data)| RestServiceVendor {
+                vendor_guid: *vendor_guid,
+                vendor_defined_data: SerdeField::new(data),
+            });
+
+        let mut s = serializer.serialize_struct("RestService", 3)?;
+        s.serialize_field("service_type", &self.service_type().0)?;
+        s.serialize_field("access_mode", &self.access_mode().0)?;
+        s.serialize_field("vendor_guid_and_data", &vendor)?;
+        s.end()
+    }
+}
+
+impl Serialize for HardDrive {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("HardDrive", 5)?;
+        s.serialize_field("partition_number", &self.partition_number())?;
+        s.serialize_field("partition_start", &self.partition_start())?;
+        s.serialize_field("partition_size", &self.partition_size())?;
+        s.serialize_field("partition_signature", &self.partition_signature())?;
+        s.serialize_field("partition_format", &self.partition_format().0)?;
+        s.end()
+    }
+}
+
+impl Serialize for FilePath {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("FilePath", 1)?;
+        s.serialize_field(
+            "path_name",
+            &SerdeField::<kind::Str, _>::new(&self.path_name()),
+        )?;
+        s.end()
+    }
+}
+
+/// Serialize a node that isn't known to [`DevicePathNodeEnum`] as its
+/// raw type, subtype, and data.
+///
+/// [`DevicePathNodeEnum`]: crate::proto::device_path::DevicePathNodeEnum
+pub(super) fn serialize_unknown<S: Serializer>(
+    node: &DevicePathNode,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    /// Fields of an unknown node.
+    struct Unknown<'a>(&'a DevicePathNode);
+
+    impl Serialize for Unknown<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut s = serializer.serialize_struct("Unknown", 3)?;
+            s.serialize_field("device_type", &self.0.device_type().0)?;
+            s.serialize_field("sub_type", &self.0.sub_type().0)?;
+            s.serialize_field(
+                "data",
+                &SerdeField::<kind::HexBytes, _>::new(&self.0.data()),
+            )?;
+            s.end()
+        }
+    }
+
+    serializer.serialize_newtype_variant(
+        "DevicePathNode",
+        super::UNKNOWN_VARIANT_INDEX,
+        super::UNKNOWN_VARIANT_NAME,
+        &Unknown(node),
+    )
+}
+
+/// Deserialize the nodes that have a hand-written [`Serialize`] impl,
+/// as well as unknown nodes, and append the node to `out`.
+#[cfg(feature = "alloc")]
+pub(crate) fn custom_node_from_serde<'de, A: VariantAccess<'de>>(
+    name: &str,
+    variant: A,
+    out: &mut DevicePathBuf,
+) -> Result<(), A::Error> {
+    match name {
+        "AcpiExpanded" => {
+            #[derive(Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Expanded")]
+            struct Fields {
+                hid: u32,
+                uid: u32,
+                cid: u32,
+                hid_str: Deserialized<kind::CStr8, Vec<u8>>,
+                uid_str: Deserialized<kind::CStr8, Vec<u8>>,
+                cid_str: Deserialized<kind::CStr8, Vec<u8>>,
+            }
+
+            let fields: Fields = variant.newtype_variant()?;
+            out.push(&build::acpi::Expanded {
+                hid: fields.hid,
+                uid: fields.uid,
+                cid: fields.cid,
+                hid_str: &fields.hid_str.0,
+                uid_str: &fields.uid_str.0,
+                cid_str: &fields.cid_str.0,
+            })
+        }
+        "AcpiAdr" => {
+            #[derive(Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Adr")]
+            struct Fields {
+                adr: Vec<u32>,
+            }
+
+            let fields: Fields = variant.newtype_variant()?;
+            let adr = AdrSlice::new(&fields.adr)
+                .ok_or_else(|| A::Error::invalid_length(0, &"at least one ADR value"))?;
+            out.push(&build::acpi::Adr { adr })
+        }
+        "MessagingDns" => {
+            #[derive(Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Dns")]
+            struct Fields {
+                address_type: u8,
+                addresses: Vec<Deserialized<kind::HexBytes, [u8; 16]>>,
+            }
+
+            let fields: Fields = variant.newtype_variant()?;
+            let addresses: Vec<_> = fields
+                .addresses
+                .into_iter()
+                .map(|address| IpAddress(address.0))
+                .collect();
+            out.push(&build::messaging::Dns {
+                address_type: DnsAddressType(fields.address_type),
+                addresses: &addresses,
+            })
+        }
+        "MessagingRestService" => {
+            #[derive(Deserialize)]
+            #[serde(deny_unknown_fields, rename = "RestServiceVendor")]
+            struct Vendor {
+                vendor_guid: Guid,
+                #[serde(deserialize_with = "deserialize_bytes")]
+                vendor_defined_data: Vec<u8>,
+            }
+
+            #[derive(Deserialize)]
+            #[serde(deny_unknown_fields, rename = "RestService")]
+            struct Fields {
+                service_type: u8,
+                access_mode: u8,
+                vendor_guid_and_data: Option<Vendor>,
+            }
+
+            let fields: Fields = variant.newtype_variant()?;
+            out.push(&build::messaging::RestService {
+                service_type: RestServiceType(fields.service_type),
+                access_mode: RestServiceAccessMode(fields.access_mode),
+                vendor_guid_and_data: fields.vendor_guid_and_data.as_ref().map(|vendor| {
+                    RestServiceVendorData {
+                        vendor_guid: vendor.vendor_guid,
+                        vendor_defined_data: &vendor.vendor_defined_data,
+                    }
+                }),
+            })
+        }
+        "MediaHardDrive" => {
+            #[derive(Deserialize)]
+            #[serde(deny_unknown_fields, rename = "HardDrive")]
+            struct Fields {
+                partition_number: u32,
+                partition_start: u64,
+                partition_size: u64,
+                partition_signature: PartitionSignature,
+                partition_format: u8,
+            }
+
+            let fields: Fields = variant.newtype_variant()?;
+            out.push(&build::media::HardDrive {
+                partition_number: fields.partition_number,
+                partition_start: fields.partition_start,
+                partition_size: fields.partition_size,
+                partition_signature: fields.partition_signature,
+                partition_format: PartitionFormat(fields.partition_format),
+            })
+        }
+        "MediaFilePath" => {
+            #[derive(Deserialize)]
+            #[serde(deny_unknown_fields, rename = "FilePath")]
+            struct Fields {
+                path_name: String,
+            }
+
+            let fields: Fields = variant.newtype_variant()?;
+            let path_name = CString16::try_from(fields.path_name.as_str())
+                .map_err(|_| A::Error::custom("path_name is not a valid UCS-2 string"))?;
+            out.push(&build::media::FilePath {
+                path_name: &path_name,
+            })
+        }
+        super::UNKNOWN_VARIANT_NAME => {
+            #[derive(Deserialize)]
+            #[serde(deny_unknown_fields, rename = "Unknown")]
+            struct Fields {
+                device_type: u8,
+                sub_type: u8,
+                #[serde(deserialize_with = "deserialize_bytes")]
+                data: Vec<u8>,
+            }
+
+            let fields: Fields = variant.newtype_variant()?;
+            if DeviceType(fields.device_type) == DeviceType::END {
+                return Err(A::Error::custom(
+                    "end nodes must not be serialized as unknown",
+                ));
+            }
+            let length = u16::try_from(fields.data.len() + 4)
+                .map_err(|_| A::Error::custom("node data is too long"))?;
+            let mut bytes = Vec::with_capacity(usize::from(length));
+            bytes.extend([fields.device_type, fields.sub_type]);
+            bytes.extend(length.to_le_bytes());
+            bytes.extend(&fields.data);
+            let node = <&DevicePathNode>::try_from(bytes.as_slice())
+                .map_err(|_| A::Error::custom("invalid node length"))?;
+            out.push(&node)
+        }
+        _ => return Err(A::Error::unknown_variant(name, super::NODE_VARIANT_NAMES)),
+    }
+    .map_err(A::Error::custom)
+}
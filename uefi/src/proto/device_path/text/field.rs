@@ -0,0 +1,524 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Text representation of individual node fields.
+//!
+//! Each field of a node is converted to and from text according to a
+//! [`kind`]. For most fields the kind is derived from the field's type
+//! by `xtask`, but it can be overridden in the device path spec with the
+//! `text_kind` attribute.
+
+use crate::data_types::UnalignedSlice;
+use crate::mem::memory_map::MemoryType;
+use crate::proto::device_path::hardware::BmcInterfaceType;
+use crate::proto::device_path::media::RamDiskType;
+use crate::proto::device_path::messaging::{
+    BluetoothLeAddressType, InfinibandResourceFlags, MasterSlave, Parity, PrimarySecondary,
+    StopBits,
+};
+use crate::Guid;
+use core::fmt::{self, Write};
+
+#[cfg(feature = "alloc")]
+use {super::ParseDevicePathError, alloc::vec::Vec};
+
+/// Marker types selecting how a field is formatted and parsed.
+pub(crate) mod kind {
+    /// Integer formatted as `0x`-prefixed hex.
+    pub(crate) struct Hex;
+
+    /// Integer formatted as decimal.
+    pub(crate) struct Decimal;
+
+    /// GUID in the standard uppercase form.
+    pub(crate) struct Guid;
+
+    /// Enum formatted either by name or by its numeric value.
+    pub(crate) struct Enum;
+
+    /// Bytes formatted as a string of hex digits, two per byte.
+    pub(crate) struct HexBytes;
+
+    /// Quoted UCS-2 string.
+    pub(crate) struct Str;
+
+    /// Unquoted ASCII string, up to the first null byte.
+    pub(crate) struct Ascii;
+
+    /// Quoted null-terminated ASCII string.
+    pub(crate) struct CStr8;
+
+    /// Bytes that hold a GUID. Formatted as a GUID if the length is
+    /// right, otherwise as hex bytes.
+    pub(crate) struct GuidBytes;
+
+    /// 64-bit identifier formatted as dash-separated byte pairs, most
+    /// significant byte first, e.g. `00-11-22-33-44-55-66-77`.
+    pub(crate) struct Eui64;
+
+    /// BIOS Boot Specification device type, formatted by name or as a
+    /// hex number.
+    pub(crate) struct BbsDeviceType;
+}
+
+/// Format a field value of type `T`.
+pub(crate) trait FormatField<T: ?Sized> {
+    fn fmt_field(value: &T, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+}
+
+/// Parse a field value of type `T`. Slice fields are parsed into a
+/// `Vec`.
+#[cfg(feature = "alloc")]
+pub(crate) trait ParseField<T> {
+    fn parse_field(s: &str) -> Result<T, ParseDevicePathError>;
+}
+
+/// Parse an integer. Values prefixed with `0x` are hex, anything else
+/// is decimal. An empty string is treated as zero.
+#[cfg(feature = "alloc")]
+pub(crate) fn parse_int<T: TryFrom<u64>>(s: &str) -> Result<T, ParseDevicePathError> {
+    let value = if s.is_empty() {
+        0
+    } else if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16).map_err(|_| ParseDevicePathError::InvalidArgument)?
+    } else {
+        s.parse()
+            .map_err(|_| ParseDevicePathError::InvalidArgument)?
+    };
+    T::try_from(value).map_err(|_| ParseDevicePathError::InvalidArgument)
+}
+
+macro_rules! impl_int_fields {
+    ($($ty:ty),*) => {
+        $(
+            impl FormatField<$ty> for kind::Hex {
+                fn fmt_field(value: &$ty, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    write!(f, "{value:#x}")
+                }
+            }
+
+            impl FormatField<$ty> for kind::Decimal {
+                fn fmt_field(value: &$ty, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    write!(f, "{value}")
+                }
+            }
+
+            #[cfg(feature = "alloc")]
+            impl ParseField<$ty> for kind::Hex {
+                fn parse_field(s: &str) -> Result<$ty, ParseDevicePathError> {
+                    parse_int(s)
+                }
+            }
+
+            #[cfg(feature = "alloc")]
+            impl ParseField<$ty> for kind::Decimal {
+                fn parse_field(s: &str) -> Result<$ty, ParseDevicePathError> {
+                    parse_int(s)
+                }
+            }
+        )*
+    };
+}
+
+impl_int_fields!(u8, u16, u32, u64);
+
+/// Write a GUID in uppercase, which is the form used by the UEFI
+/// Specification's text representation.
+pub(crate) fn fmt_guid(guid: Guid, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for c in guid.to_ascii_hex_lower() {
+        f.write_char(char::from(c.to_ascii_uppercase()))?;
+    }
+    Ok(())
+}
+
+/// Parse a GUID, ignoring case.
+#[cfg(feature = "alloc")]
+pub(crate) fn parse_guid(s: &str) -> Result<Guid, ParseDevicePathError> {
+    Guid::try_parse(s).map_err(|_| ParseDevicePathError::InvalidArgument)
+}
+
+impl FormatField<Guid> for kind::Guid {
+    fn fmt_field(value: &Guid, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_guid(*value, f)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ParseField<Guid> for kind::Guid {
+    fn parse_field(s: &str) -> Result<Guid, ParseDevicePathError> {
+        parse_guid(s)
+    }
+}
+
+/// Implement the `Enum` kind for newtype enums that are formatted as a
+/// hex number.
+macro_rules! impl_hex_enum_fields {
+    ($($ty:ident),*) => {
+        $(
+            impl FormatField<$ty> for kind::Enum {
+                fn fmt_field(value: &$ty, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    write!(f, "{:#x}", value.0)
+                }
+            }
+
+            #[cfg(feature = "alloc")]
+            impl ParseField<$ty> for kind::Enum {
+                fn parse_field(s: &str) -> Result<$ty, ParseDevicePathError> {
+                    parse_int(s).map($ty)
+                }
+            }
+        )*
+    };
+}
+
+impl_hex_enum_fields!(MemoryType, BmcInterfaceType, BluetoothLeAddressType);
+
+/// Implement the `Enum` kind for newtype enums that are formatted by
+/// name. Unnamed values are formatted as a hex number.
+macro_rules! impl_named_enum_fields {
+    ($ty:ident { $($value:ident => $name:literal),* $(,)? }) => {
+        impl FormatField<$ty> for kind::Enum {
+            fn fmt_field(value: &$ty, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match *value {
+                    $($ty::$value => f.write_str($name),)*
+                    _ => write!(f, "{:#x}", value.0),
+                }
+            }
+        }
+
+        #[cfg(feature = "alloc")]
+        impl ParseField<$ty> for kind::Enum {
+            fn parse_field(s: &str) -> Result<$ty, ParseDevicePathError> {
+                match s {
+                    $($name => Ok($ty::$value),)*
+                    _ => parse_int(s).map($ty),
+                }
+            }
+        }
+    };
+}
+
+impl_named_enum_fields!(PrimarySecondary {
+    PRIMARY => "Primary",
+    SECONDARY => "Secondary",
+});
+
+impl_named_enum_fields!(MasterSlave {
+    MASTER => "Master",
+    SLAVE => "Slave",
+});
+
+impl_named_enum_fields!(Parity {
+    DEFAULT => "D",
+    NO => "N",
+    EVEN => "E",
+    ODD => "O",
+    MARK => "M",
+    SPACE => "S",
+});
+
+impl_named_enum_fields!(StopBits {
+    DEFAULT => "D",
+    ONE => "1",
+    ONE_POINT_FIVE => "1.5",
+    TWO => "2",
+});
+
+impl FormatField<InfinibandResourceFlags> for kind::Enum {
+    fn fmt_field(value: &InfinibandResourceFlags, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x}", value.bits())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ParseField<InfinibandResourceFlags> for kind::Enum {
+    fn parse_field(s: &str) -> Result<InfinibandResourceFlags, ParseDevicePathError> {
+        parse_int(s).map(InfinibandResourceFlags::from_bits_retain)
+    }
+}
+
+impl FormatField<RamDiskType> for kind::Enum {
+    fn fmt_field(value: &RamDiskType, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_guid(value.0, f)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ParseField<RamDiskType> for kind::Enum {
+    fn parse_field(s: &str) -> Result<RamDiskType, ParseDevicePathError> {
+        parse_guid(s).map(RamDiskType)
+    }
+}
+
+/// Names of the BIOS Boot Specification device types.
+const BBS_DEVICE_TYPES: [(&str, u16); 6] = [
+    ("Floppy", 0x01),
+    ("HD", 0x02),
+    ("CDROM", 0x03),
+    ("PCMCIA", 0x04),
+    ("USB", 0x05),
+    ("Network", 0x06),
+];
+
+impl FormatField<u16> for kind::BbsDeviceType {
+    fn fmt_field(value: &u16, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match BBS_DEVICE_TYPES.iter().find(|(_, t)| t == value) {
+            Some((name, _)) => f.write_str(name),
+            None => write!(f, "{value:#x}"),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ParseField<u16> for kind::BbsDeviceType {
+    fn parse_field(s: &str) -> Result<u16, ParseDevicePathError> {
+        match BBS_DEVICE_TYPES.iter().find(|(name, _)| *name == s) {
+            Some((_, device_type)) => Ok(*device_type),
+            None => parse_int(s),
+        }
+    }
+}
+
+/// Write bytes as hex digits, two per byte.
+pub(crate) fn fmt_hex_bytes(bytes: &[u8], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for b in bytes {
+        write!(f, "{b:02x}")?;
+    }
+    Ok(())
+}
+
+/// Parse a string of hex digits, two per byte.
+#[cfg(feature = "alloc")]
+pub(crate) fn parse_hex_bytes(s: &str) -> Result<Vec<u8>, ParseDevicePathError> {
+    if s.len() % 2 != 0 {
+        return Err(ParseDevicePathError::InvalidArgument);
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            s.get(i..i + 2)
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .ok_or(ParseDevicePathError::InvalidArgument)
+        })
+        .collect()
+}
+
+/// Copy `bytes` into a zero-filled array. Fails if there are more
+/// than `N` bytes.
+#[cfg(feature = "alloc")]
+pub(crate) fn to_array<const N: usize>(bytes: &[u8]) -> Result<[u8; N], ParseDevicePathError> {
+    let mut array = [0; N];
+    array
+        .get_mut(..bytes.len())
+        .ok_or(ParseDevicePathError::InvalidArgument)?
+        .copy_from_slice(bytes);
+    Ok(array)
+}
+
+impl FormatField<&[u8]> for kind::HexBytes {
+    fn fmt_field(value: &&[u8], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_hex_bytes(value, f)
+    }
+}
+
+impl<const N: usize> FormatField<[u8; N]> for kind::HexBytes {
+    fn fmt_field(value: &[u8; N], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_hex_bytes(value, f)
+    }
+}
+
+impl FormatField<u64> for kind::Eui64 {
+    fn fmt_field(value: &u64, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, b) in value.to_be_bytes().iter().enumerate() {
+            if index != 0 {
+                f.write_char('-')?;
+            }
+            write!(f, "{b:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// An empty string is treated as zero.
+#[cfg(feature = "alloc")]
+impl ParseField<u64> for kind::Eui64 {
+    fn parse_field(s: &str) -> Result<u64, ParseDevicePathError> {
+        if s.is_empty() {
+            return Ok(0);
+        }
+        let bytes = s
+            .split('-')
+            .map(|digits| match digits.len() {
+                1 | 2 => u8::from_str_radix(digits, 16).ok(),
+                _ => None,
+            })
+            .collect::<Option<Vec<u8>>>()
+            .and_then(|bytes| <[u8; 8]>::try_from(bytes).ok())
+            .ok_or(ParseDevicePathError::InvalidArgument)?;
+        Ok(u64::from_be_bytes(bytes))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ParseField<Vec<u8>> for kind::HexBytes {
+    fn parse_field(s: &str) -> Result<Vec<u8>, ParseDevicePathError> {
+        parse_hex_bytes(s)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> ParseField<[u8; N]> for kind::HexBytes {
+    fn parse_field(s: &str) -> Result<[u8; N], ParseDevicePathError> {
+        to_array(&parse_hex_bytes(s)?)
+    }
+}
+
+/// Remove the quotes around a string argument, if present.
+#[cfg(feature = "alloc")]
+pub(crate) fn unquote(s: &str) -> &str {
+    s.strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or(s)
+}
+
+/// Write a UCS-2 string, stopping at the first null character.
+pub(crate) fn fmt_ucs2(chars: &UnalignedSlice<'_, u16>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let chars = (0..chars.len())
+        .filter_map(|i| chars.get(i))
+        .take_while(|c| *c != 0);
+    for c in char::decode_utf16(chars) {
+        f.write_char(c.unwrap_or(char::REPLACEMENT_CHARACTER))?;
+    }
+    Ok(())
+}
+
+/// Wrapper that formats a UCS-2 field as a quoted string in `Debug`
+/// output.
+pub(crate) struct DebugUcs2<'a>(pub(crate) UnalignedSlice<'a, u16>);
+
+impl fmt::Debug for DebugUcs2<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char('"')?;
+        fmt_ucs2(&self.0, f)?;
+        f.write_char('"')
+    }
+}
+
+impl FormatField<UnalignedSlice<'_, u16>> for kind::Str {
+    fn fmt_field(value: &UnalignedSlice<'_, u16>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char('"')?;
+        fmt_ucs2(value, f)?;
+        f.write_char('"')
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ParseField<Vec<u16>> for kind::Str {
+    fn parse_field(s: &str) -> Result<Vec<u16>, ParseDevicePathError> {
+        Ok(unquote(s).encode_utf16().collect())
+    }
+}
+
+/// Write ASCII bytes, stopping at the first null byte. Bytes above
+/// 0x7f are written as the corresponding Latin-1 character.
+pub(crate) fn fmt_ascii(bytes: &[u8], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for b in bytes.iter().take_while(|b| **b != 0) {
+        f.write_char(char::from(*b))?;
+    }
+    Ok(())
+}
+
+/// Wrapper that formats an ASCII field as a quoted string in `Debug`
+/// output.
+pub(crate) struct DebugAscii<'a>(pub(crate) &'a [u8]);
+
+impl fmt::Debug for DebugAscii<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char('"')?;
+        fmt_ascii(self.0, f)?;
+        f.write_char('"')
+    }
+}
+
+/// Convert a string to single-byte characters. Fails if any character
+/// does not fit in a byte.
+#[cfg(feature = "alloc")]
+pub(crate) fn parse_ascii(s: &str) -> Result<Vec<u8>, ParseDevicePathError> {
+    s.chars()
+        .map(|c| u8::try_from(c).map_err(|_| ParseDevicePathError::InvalidArgument))
+        .collect()
+}
+
+impl FormatField<&[u8]> for kind::Ascii {
+    fn fmt_field(value: &&[u8], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_ascii(value, f)
+    }
+}
+
+impl<const N: usize> FormatField<[u8; N]> for kind::Ascii {
+    fn fmt_field(value: &[u8; N], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_ascii(value, f)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ParseField<Vec<u8>> for kind::Ascii {
+    fn parse_field(s: &str) -> Result<Vec<u8>, ParseDevicePathError> {
+        parse_ascii(s)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> ParseField<[u8; N]> for kind::Ascii {
+    fn parse_field(s: &str) -> Result<[u8; N], ParseDevicePathError> {
+        to_array(&parse_ascii(s)?)
+    }
+}
+
+impl FormatField<&[u8]> for kind::CStr8 {
+    fn fmt_field(value: &&[u8], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char('"')?;
+        fmt_ascii(value, f)?;
+        f.write_char('"')
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ParseField<Vec<u8>> for kind::CStr8 {
+    fn parse_field(s: &str) -> Result<Vec<u8>, ParseDevicePathError> {
+        let mut bytes = parse_ascii(unquote(s))?;
+        bytes.push(0);
+        Ok(bytes)
+    }
+}
+
+impl FormatField<&[u8]> for kind::GuidBytes {
+    fn fmt_field(value: &&[u8], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match <[u8; 16]>::try_from(*value) {
+            Ok(bytes) => fmt_guid(Guid::from_bytes(bytes), f),
+            Err(_) => fmt_hex_bytes(value, f),
+        }
+    }
+}
+
+impl<const N: usize> FormatField<[u8; N]> for kind::GuidBytes {
+    fn fmt_field(value: &[u8; N], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        <Self as FormatField<&[u8]>>::fmt_field(&value.as_slice(), f)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ParseField<Vec<u8>> for kind::GuidBytes {
+    fn parse_field(s: &str) -> Result<Vec<u8>, ParseDevicePathError> {
+        match parse_guid(s) {
+            Ok(guid) => Ok(guid.to_bytes().to_vec()),
+            Err(_) => parse_hex_bytes(s),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> ParseField<[u8; N]> for kind::GuidBytes {
+    fn parse_field(s: &str) -> Result<[u8; N], ParseDevicePathError> {
+        to_array(&<Self as ParseField<Vec<u8>>>::parse_field(s)?)
+    }
+}
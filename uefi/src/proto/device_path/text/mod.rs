@@ -1,6 +1,40 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-//! Protocols for converting between UEFI strings and [`DevicePath`]/[`DevicePathNode`].
+//! Conversion between text and [`DevicePath`]/[`DevicePathNode`].
+//!
+//! The text representation of device paths is defined in the UEFI
+//! Specification, e.g. `PciRoot(0x0)/Pci(0x1f,0x2)/Sata(0x0,0xffff,0x0)`.
+//!
+//! [`DevicePath`], [`DevicePathInstance`], [`DevicePathNode`], and the
+//! specific node types all implement [`Display`], and `Box<DevicePath>`
+//! implements [`FromStr`]. These conversions are implemented in Rust and
+//! work even if the firmware does not provide the [`DevicePathToText`]
+//! and [`DevicePathFromText`] protocols:
+//!
+//! ```
+//! use uefi::proto::device_path::DevicePath;
+//!
+//! # fn main() -> Result<(), uefi::proto::device_path::text::ParseDevicePathError> {
+//! let text = "PciRoot(0x0)/Pci(0x1f,0x2)/Sata(0x0,0xffff,0x0)";
+//! let path: Box<DevicePath> = text.parse()?;
+//! assert_eq!(path.node_iter().count(), 3);
+//! assert_eq!(format!("{path}"), text);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Note that the inherent [`DevicePath::to_string`] method, which uses
+//! [`DevicePathToText`], shadows [`ToString::to_string`]. Use `format!`
+//! to get the text from the [`Display`] implementation.
+//!
+//! The protocols are also provided by this module. Their output may
+//! differ from the Rust implementation, e.g. in whether shortcut names
+//! like `VenPcAnsi()` are used.
+//!
+//! [`Display`]: core::fmt::Display
+//! [`DevicePathInstance`]: super::DevicePathInstance
+//! [`FromStr`]: core::str::FromStr
+//! [`ToString::to_string`]: alloc::string::ToString::to_string
 
 // Note on return types: the specification of the conversion functions
 // is a little unusual in that they return a pointer rather than
@@ -20,6 +54,14 @@ use uefi_raw::protocol::device_path::{DevicePathFromTextProtocol, DevicePathToTe
 
 use super::{PoolDevicePath, PoolDevicePathNode};
 
+pub(crate) mod field;
+mod node;
+#[cfg(feature = "alloc")]
+pub(crate) mod parse;
+
+#[cfg(feature = "alloc")]
+pub use parse::ParseDevicePathError;
+
 /// Parameter for [`DevicePathToText`] that alters the output format.
 ///
 /// * `DisplayOnly(false)` produces parseable output.
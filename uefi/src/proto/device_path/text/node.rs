@@ -0,0 +1,519 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! [`Display`] implementations for device paths and for the nodes whose
+//! text representation can't be generated from the device path spec.
+//!
+//! [`Display`]: fmt::Display
+
+use super::field::{fmt_ascii, fmt_guid, fmt_hex_bytes, fmt_ucs2};
+use crate::proto::device_path::media::PartitionSignature;
+use crate::proto::device_path::messaging::{
+    DnsAddressType, Ipv4AddressOrigin, Ipv6AddressOrigin, IscsiLoginOptions, IscsiProtocol,
+    RestServiceAccessMode, RestServiceType,
+};
+use crate::proto::device_path::{
+    acpi, end, media, messaging, DevicePath, DevicePathInstance, DevicePathNode, DeviceSubType,
+    DeviceType,
+};
+use crate::{guid, Guid};
+use core::fmt::{self, Display, Formatter, Write};
+use core::net::{Ipv4Addr, Ipv6Addr};
+
+/// Compressed EISA ID of the `PNP` vendor prefix.
+pub(crate) const PNP_EISA_ID: u32 = 0x41d0;
+
+/// ACPI nodes with a `PNP` hardware ID that have a shortcut name. The
+/// number is the product part of the hardware ID.
+pub(crate) const ACPI_SHORTCUTS: [(&str, u16); 6] = [
+    ("PciRoot", 0x0a03),
+    ("PcieRoot", 0x0a08),
+    ("Floppy", 0x0604),
+    ("Keyboard", 0x0301),
+    ("Serial", 0x0501),
+    ("ParallelPort", 0x0401),
+];
+
+/// Vendor-defined messaging nodes with a shortcut name. The shortcut is
+/// only used if the node has no vendor-defined data.
+pub(crate) const VENDOR_MSG_SHORTCUTS: [(&str, Guid); 4] = [
+    ("VenPcAnsi", guid!("e0c14753-f9be-11d2-9a0c-0090273fc14d")),
+    ("VenVt100", guid!("dfa66065-b419-11d3-9a2d-0090273fc14d")),
+    (
+        "VenVt100Plus",
+        guid!("7baec70b-57e0-4c76-8e87-2f9e28088343"),
+    ),
+    ("VenUtf8", guid!("ad15a0d6-8bec-4acf-a073-d01de77e2d88")),
+];
+
+/// Vendor GUID of the vendor-defined messaging node for SAS devices,
+/// whose text representation is `SAS(...)`.
+pub(crate) const SAS_VENDOR_GUID: Guid = guid!("d487ddb4-008b-11d9-afdc-001083ffca4d");
+
+/// Names used by the generic text representation of nodes, indexed by
+/// device type. Nodes of other device types use `Path`.
+pub(crate) const GENERIC_NAMES: [(&str, DeviceType); 5] = [
+    ("HardwarePath", DeviceType::HARDWARE),
+    ("AcpiPath", DeviceType::ACPI),
+    ("Msg", DeviceType::MESSAGING),
+    ("MediaPath", DeviceType::MEDIA),
+    ("BbsPath", DeviceType::BIOS_BOOT_SPEC),
+];
+
+/// Whether a null-terminated ASCII string is empty.
+fn is_empty_ascii(s: &[u8]) -> bool {
+    s.first().map_or(true, |b| *b == 0)
+}
+
+/// Write a compressed EISA ID, e.g. `PNP0A03`. IDs that can't be
+/// decoded are written as a hex number.
+fn fmt_eisa_id(id: u32, f: &mut Formatter<'_>) -> fmt::Result {
+    let letters = [(id >> 10) & 0x1f, (id >> 5) & 0x1f, id & 0x1f];
+    if letters.iter().all(|c| (1..=26).contains(c)) {
+        for c in letters {
+            f.write_char(char::from(b'A' - 1 + c as u8))?;
+        }
+        write!(f, "{:04X}", id >> 16)
+    } else {
+        write!(f, "{id:#x}")
+    }
+}
+
+/// Write the generic text representation of a node, which can
+/// represent any node.
+pub(crate) fn fmt_generic(node: &DevicePathNode, f: &mut Formatter<'_>) -> fmt::Result {
+    let (device_type, sub_type) = node.full_type();
+    match GENERIC_NAMES.iter().find(|(_, t)| *t == device_type) {
+        Some((name, _)) => write!(f, "{name}({}", sub_type.0)?,
+        None => write!(f, "Path({},{}", device_type.0, sub_type.0)?,
+    }
+    if !node.data().is_empty() {
+        f.write_char(',')?;
+        fmt_hex_bytes(node.data(), f)?;
+    }
+    f.write_char(')')
+}
+
+/// Write an IPv4 address, with the port appended if it's not zero.
+fn fmt_ipv4(addr: [u8; 4], port: u16, f: &mut Formatter<'_>) -> fmt::Result {
+    let addr = Ipv4Addr::from(addr);
+    if port == 0 {
+        write!(f, "{addr}")
+    } else {
+        write!(f, "{addr}:{port}")
+    }
+}
+
+/// Write an IPv6 address, with the port appended if it's not zero.
+fn fmt_ipv6(addr: [u8; 16], port: u16, f: &mut Formatter<'_>) -> fmt::Result {
+    let addr = Ipv6Addr::from(addr);
+    if port == 0 {
+        write!(f, "{addr}")
+    } else {
+        write!(f, "[{addr}]:{port}")
+    }
+}
+
+/// Write an IP protocol number, using a name for TCP and UDP.
+fn fmt_ip_protocol(protocol: u16, f: &mut Formatter<'_>) -> fmt::Result {
+    match protocol {
+        6 => f.write_str("TCP"),
+        17 => f.write_str("UDP"),
+        _ => write!(f, "{protocol:#x}"),
+    }
+}
+
+/// Write the device topology of a SAS node as the four arguments
+/// `SAS/SATA,Internal/External,Direct/Expanded,DriveBay`. Topologies
+/// that can't be decoded are written as a hex number followed by
+/// zeros.
+fn fmt_sas_topology(info: u16, f: &mut Formatter<'_>) -> fmt::Result {
+    let topology = info & 0x0f;
+    let reserved = info & 0x80 != 0;
+    if topology == 0 && !reserved {
+        f.write_str("NoTopology,0,0,0")
+    } else if topology <= 2 && !reserved {
+        let flag = |bit: u16, set, unset| if info & bit != 0 { set } else { unset };
+        write!(
+            f,
+            "{},{},{},",
+            flag(0x10, "SATA", "SAS"),
+            flag(0x20, "External", "Internal"),
+            flag(0x40, "Expanded", "Direct"),
+        )?;
+        // The drive bay is stored minus one.
+        if topology == 1 {
+            f.write_char('0')
+        } else {
+            write!(f, "{:#x}", (info >> 8) + 1)
+        }
+    } else {
+        write!(f, "{info:#x},0,0,0")
+    }
+}
+
+/// Write the vendor-defined data of a SAS node, which consists of a
+/// reserved `u32`, the SAS address and LUN as `u64`s, the device
+/// topology and the relative target port.
+fn fmt_sas(data: &[u8; 24], f: &mut Formatter<'_>) -> fmt::Result {
+    let u16_at = |i: usize| u16::from_le_bytes([data[i], data[i + 1]]);
+    let u64_at = |i: usize| {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&data[i..i + 8]);
+        u64::from_le_bytes(bytes)
+    };
+    let reserved = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+
+    write!(
+        f,
+        "SAS({:#x},{:#x},{:#x},",
+        u64_at(4),
+        u64_at(12),
+        u16_at(22)
+    )?;
+    fmt_sas_topology(u16_at(20), f)?;
+    write!(f, ",{reserved:#x})")
+}
+
+impl Display for DevicePath {
+    /// Write the text representation of the device path, as defined in
+    /// the UEFI Specification. Instances are separated by `,` and nodes
+    /// by `/`.
+    ///
+    /// Unlike [`DevicePath::to_string`], this does not use any protocol
+    /// provided by the firmware.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, instance) in self.instance_iter().enumerate() {
+            if index != 0 {
+                f.write_char(',')?;
+            }
+            Display::fmt(instance, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for DevicePathInstance {
+    /// Write the text representation of the instance, with nodes
+    /// separated by `/`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, node) in self.node_iter().enumerate() {
+            if index != 0 {
+                f.write_char('/')?;
+            }
+            Display::fmt(node, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for DevicePathNode {
+    /// Write the text representation of the node, as defined in the
+    /// UEFI Specification. Nodes that are not supported, or that fail
+    /// to convert to their specific type, use the generic form, e.g.
+    /// `HardwarePath(6,0102)`.
+    ///
+    /// Unlike [`DevicePathNode::to_string`], this does not use any
+    /// protocol provided by the firmware.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.as_enum() {
+            Ok(node) => Display::fmt(&node, f),
+            Err(_) => fmt_generic(self, f),
+        }
+    }
+}
+
+impl Display for end::Instance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_char(',')
+    }
+}
+
+impl Display for end::Entire {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Path({},{})",
+            DeviceType::END.0,
+            DeviceSubType::END_ENTIRE.0
+        )
+    }
+}
+
+impl Display for acpi::Acpi {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let hid = self.hid();
+        if hid & 0xffff == PNP_EISA_ID {
+            let product = (hid >> 16) as u16;
+            if let Some((name, _)) = ACPI_SHORTCUTS.iter().find(|(_, p)| *p == product) {
+                return write!(f, "{name}({:#x})", self.uid());
+            }
+        }
+        f.write_str("Acpi(")?;
+        fmt_eisa_id(hid, f)?;
+        write!(f, ",{:#x})", self.uid())
+    }
+}
+
+impl Display for acpi::Expanded {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let short = is_empty_ascii(self.hid_str())
+            && is_empty_ascii(self.cid_str())
+            && !is_empty_ascii(self.uid_str())
+            && self.uid() == 0;
+
+        f.write_str(if short { "AcpiExp(" } else { "AcpiEx(" })?;
+        fmt_eisa_id(self.hid(), f)?;
+        f.write_char(',')?;
+        if self.cid() == 0 {
+            f.write_char('0')?;
+        } else {
+            fmt_eisa_id(self.cid(), f)?;
+        }
+        f.write_char(',')?;
+        if short {
+            fmt_ascii(self.uid_str(), f)?;
+        } else {
+            write!(f, "{:#x},", self.uid())?;
+            fmt_ascii(self.hid_str(), f)?;
+            f.write_char(',')?;
+            fmt_ascii(self.cid_str(), f)?;
+            f.write_char(',')?;
+            fmt_ascii(self.uid_str(), f)?;
+        }
+        f.write_char(')')
+    }
+}
+
+impl Display for acpi::Adr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("AcpiAdr(")?;
+        let adr = self.adr();
+        for index in 0..adr.len() {
+            if index != 0 {
+                f.write_char(',')?;
+            }
+            write!(f, "{:#x}", adr.get(index).unwrap_or_default())?;
+        }
+        f.write_char(')')
+    }
+}
+
+impl Display for messaging::MacAddress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // Ethernet (0) and IEEE 802.5 (1) addresses are six bytes, the
+        // size of other addresses is not known.
+        let interface_type = self.interface_type();
+        let len = if interface_type <= 1 { 6 } else { 32 };
+
+        f.write_str("MAC(")?;
+        fmt_hex_bytes(&self.mac_address()[..len], f)?;
+        write!(f, ",{interface_type:#x})")
+    }
+}
+
+impl Display for messaging::Ipv4 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("IPv4(")?;
+        fmt_ipv4(self.remote_ip_address(), self.remote_port(), f)?;
+        f.write_char(',')?;
+        fmt_ip_protocol(self.protocol(), f)?;
+        match self.ip_address_origin() {
+            Ipv4AddressOrigin::STATIC => f.write_str(",Static,")?,
+            Ipv4AddressOrigin::DHCP => f.write_str(",DHCP,")?,
+            origin => write!(f, ",{:#x},", origin.0)?,
+        }
+        fmt_ipv4(self.local_ip_address(), self.local_port(), f)?;
+        write!(
+            f,
+            ",{},{})",
+            Ipv4Addr::from(self.gateway_ip_address()),
+            Ipv4Addr::from(self.subnet_mask())
+        )
+    }
+}
+
+impl Display for messaging::Ipv6 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("IPv6(")?;
+        fmt_ipv6(self.remote_ip_address(), self.remote_port(), f)?;
+        f.write_char(',')?;
+        fmt_ip_protocol(self.protocol(), f)?;
+        match self.ip_address_origin() {
+            Ipv6AddressOrigin::MANUAL => f.write_str(",Static,")?,
+            Ipv6AddressOrigin::STATELESS_AUTO_CONFIGURATION => {
+                f.write_str(",StatelessAutoConfigure,")?
+            }
+            Ipv6AddressOrigin::STATEFUL_CONFIGURATION => f.write_str(",StatefulAutoConfigure,")?,
+            origin => write!(f, ",{:#x},", origin.0)?,
+        }
+        fmt_ipv6(self.local_ip_address(), self.local_port(), f)?;
+        write!(
+            f,
+            ",{:#x},{})",
+            self.prefix_length(),
+            Ipv6Addr::from(self.gateway_ip_address())
+        )
+    }
+}
+
+impl Display for messaging::Vendor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let vendor_guid = self.vendor_guid();
+        let data = self.vendor_defined_data();
+
+        if data.is_empty() {
+            if let Some((name, _)) = VENDOR_MSG_SHORTCUTS
+                .iter()
+                .find(|(_, guid)| *guid == vendor_guid)
+            {
+                return write!(f, "{name}()");
+            }
+        }
+
+        if vendor_guid == SAS_VENDOR_GUID {
+            if let Ok(data) = data.try_into() {
+                return fmt_sas(data, f);
+            }
+        }
+
+        f.write_str("VenMsg(")?;
+        fmt_guid(vendor_guid, f)?;
+        if !data.is_empty() {
+            f.write_char(',')?;
+            fmt_hex_bytes(data, f)?;
+        }
+        f.write_char(')')
+    }
+}
+
+impl Display for messaging::SasEx {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // The address and LUN are written as numbers, with the first
+        // byte being the most significant.
+        f.write_str("SasEx(0x")?;
+        fmt_hex_bytes(&self.sas_address(), f)?;
+        f.write_str(",0x")?;
+        fmt_hex_bytes(&self.logical_unit_number(), f)?;
+        write!(f, ",{:#x},", self.relative_target_port())?;
+        fmt_sas_topology(self.info(), f)?;
+        f.write_char(')')
+    }
+}
+
+impl Display for messaging::Iscsi {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let options = self.options();
+        let digest = |flag| {
+            if options.contains(flag) {
+                "CRC32C"
+            } else {
+                "None"
+            }
+        };
+        let auth = if options.contains(IscsiLoginOptions::AUTH_METHOD_NONE) {
+            "None"
+        } else if options.contains(IscsiLoginOptions::CHAP_UNI) {
+            "CHAP_UNI"
+        } else {
+            "CHAP_BI"
+        };
+
+        f.write_str("iSCSI(")?;
+        fmt_ascii(self.iscsi_target_name(), f)?;
+        write!(f, ",{:#x},", self.target_portal_group_tag())?;
+        fmt_hex_bytes(&self.logical_unit_number(), f)?;
+        write!(
+            f,
+            ",{},{},{auth},",
+            digest(IscsiLoginOptions::HEADER_DIGEST_USING_CRC32),
+            digest(IscsiLoginOptions::DATA_DIGEST_USING_CRC32),
+        )?;
+        match self.protocol() {
+            IscsiProtocol::TCP => f.write_str("TCP")?,
+            protocol => write!(f, "{:#x}", protocol.0)?,
+        }
+        f.write_char(')')
+    }
+}
+
+impl Display for messaging::Dns {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let is_ipv6 = self.address_type() == DnsAddressType::IPV6;
+        let addresses = self.addresses();
+
+        f.write_str("Dns(")?;
+        let addresses = (0..addresses.len()).filter_map(|index| addresses.get(index));
+        for (index, addr) in addresses.enumerate() {
+            if index != 0 {
+                f.write_char(',')?;
+            }
+            if is_ipv6 {
+                write!(f, "{}", Ipv6Addr::from(addr))?;
+            } else {
+                write!(f, "{}", Ipv4Addr::from(addr))?;
+            }
+        }
+        f.write_char(')')
+    }
+}
+
+impl Display for messaging::RestService {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("RestService(")?;
+        match self.service_type() {
+            RestServiceType::REDFISH => f.write_str("RedFish")?,
+            RestServiceType::ODATA => f.write_str("OData")?,
+            RestServiceType::VENDOR => f.write_str("Vendor")?,
+            service_type => write!(f, "{:#x}", service_type.0)?,
+        }
+        match self.access_mode() {
+            RestServiceAccessMode::IN_BAND => f.write_str(",In-Band")?,
+            RestServiceAccessMode::OUT_OF_BAND => f.write_str(",Out-of-Band")?,
+            access_mode => write!(f, ",{:#x}", access_mode.0)?,
+        }
+        if let Some((vendor_guid, data)) = self.vendor_guid_and_data() {
+            f.write_char(',')?;
+            fmt_guid(vendor_guid, f)?;
+            if !data.is_empty() {
+                f.write_char(',')?;
+                fmt_hex_bytes(data, f)?;
+            }
+        }
+        f.write_char(')')
+    }
+}
+
+impl Display for media::HardDrive {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "HD({},", self.partition_number())?;
+        match self.partition_signature() {
+            PartitionSignature::Mbr(signature) => {
+                write!(f, "MBR,{:#010x}", u32::from_le_bytes(signature))?;
+            }
+            PartitionSignature::Guid(signature) => {
+                f.write_str("GPT,")?;
+                fmt_guid(signature, f)?;
+            }
+            PartitionSignature::None => f.write_str("0x0,0")?,
+            PartitionSignature::Unknown {
+                signature_type,
+                signature,
+            } => {
+                write!(f, "{signature_type:#x},")?;
+                fmt_hex_bytes(&signature, f)?;
+            }
+        }
+        write!(
+            f,
+            ",{:#x},{:#x})",
+            self.partition_start(),
+            self.partition_size()
+        )
+    }
+}
+
+impl Display for media::FilePath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_ucs2(&self.path_name(), f)
+    }
+}
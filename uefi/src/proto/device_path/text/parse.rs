@@ -0,0 +1,861 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Parsing of the text representation of device paths.
+
+use super::field::{parse_ascii, parse_guid, parse_hex_bytes, parse_int};
+use super::node::{
+    ACPI_SHORTCUTS, GENERIC_NAMES, PNP_EISA_ID, SAS_VENDOR_GUID, VENDOR_MSG_SHORTCUTS,
+};
+use crate::proto::device_path::build::{self, BuildNode};
+use crate::proto::device_path::device_path_gen::node_from_text;
+use crate::proto::device_path::media::{PartitionFormat, PartitionSignature};
+use crate::proto::device_path::messaging::{
+    DnsAddressType, Ipv4AddressOrigin, Ipv6AddressOrigin, IscsiLoginOptions, IscsiProtocol,
+    RestServiceAccessMode, RestServiceType,
+};
+use crate::proto::device_path::{DevicePath, DeviceSubType, DeviceType};
+use crate::proto::network::IpAddress;
+use crate::CString16;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use core::mem;
+use core::net::{Ipv4Addr, Ipv6Addr};
+use core::str::FromStr;
+
+/// Error returned when parsing the text representation of a
+/// [`DevicePath`] fails.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ParseDevicePathError {
+    /// The text is not a valid device path, e.g. a node is missing its
+    /// closing parenthesis.
+    InvalidSyntax,
+
+    /// A node argument is not valid for the node type.
+    InvalidArgument,
+
+    /// A node has more arguments than the node type accepts.
+    TooManyArguments,
+
+    /// A node's length is too big to fit in a [`u16`].
+    NodeTooBig,
+}
+
+impl Display for ParseDevicePathError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::InvalidSyntax => "invalid device path syntax",
+                Self::InvalidArgument => "invalid device path node argument",
+                Self::TooManyArguments => "too many device path node arguments",
+                Self::NodeTooBig => "a node was too big",
+            }
+        )
+    }
+}
+
+impl core::error::Error for ParseDevicePathError {}
+
+/// Iterator that splits a string at each `separator` that is not inside
+/// parentheses or quotes.
+struct SplitTopLevel<'a> {
+    remaining: Option<&'a str>,
+    separator: char,
+}
+
+impl<'a> Iterator for SplitTopLevel<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let s = self.remaining?;
+
+        let mut depth = 0usize;
+        let mut in_quotes = false;
+        for (index, c) in s.char_indices() {
+            match c {
+                '"' => in_quotes = !in_quotes,
+                '(' if !in_quotes => depth += 1,
+                ')' if !in_quotes => depth = depth.saturating_sub(1),
+                c if c == self.separator && depth == 0 && !in_quotes => {
+                    self.remaining = Some(&s[index + c.len_utf8()..]);
+                    return Some(&s[..index]);
+                }
+                _ => {}
+            }
+        }
+
+        self.remaining = None;
+        Some(s)
+    }
+}
+
+const fn split_top_level(s: &str, separator: char) -> SplitTopLevel<'_> {
+    SplitTopLevel {
+        remaining: Some(s),
+        separator,
+    }
+}
+
+/// The comma-separated arguments of a node, e.g. `0x1,0x0` for
+/// `Pci(0x1,0x0)`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TextArgs<'a>(&'a str);
+
+impl<'a> TextArgs<'a> {
+    /// Iterate over the arguments, with surrounding whitespace removed.
+    fn iter(self) -> impl Iterator<Item = &'a str> {
+        let is_empty = self.0.trim().is_empty();
+        split_top_level(self.0, ',')
+            .filter(move |_| !is_empty)
+            .map(str::trim)
+    }
+
+    /// Get exactly `N` arguments. Missing trailing arguments are
+    /// returned as empty strings.
+    pub(crate) fn into_array<const N: usize>(self) -> Result<[&'a str; N], ParseDevicePathError> {
+        let mut args = [""; N];
+        let mut iter = self.iter();
+        for (arg, value) in args.iter_mut().zip(&mut iter) {
+            *arg = value;
+        }
+        if iter.next().is_some() {
+            return Err(ParseDevicePathError::TooManyArguments);
+        }
+        Ok(args)
+    }
+}
+
+/// Append a node to `out`.
+pub(crate) fn push_node(
+    out: &mut Vec<u8>,
+    node: &dyn BuildNode,
+) -> Result<(), ParseDevicePathError> {
+    let node_size = usize::from(
+        node.size_in_bytes()
+            .map_err(|_| ParseDevicePathError::NodeTooBig)?,
+    );
+
+    let old_size = out.len();
+    out.reserve(node_size);
+    node.write_data(&mut out.spare_capacity_mut()[..node_size]);
+    unsafe {
+        out.set_len(old_size + node_size);
+    }
+    Ok(())
+}
+
+/// Append a node from its type, subtype, and data.
+fn push_raw_node(
+    out: &mut Vec<u8>,
+    device_type: DeviceType,
+    sub_type: DeviceSubType,
+    data: &[u8],
+) -> Result<(), ParseDevicePathError> {
+    let len = u16::try_from(data.len() + 4).map_err(|_| ParseDevicePathError::NodeTooBig)?;
+    out.extend([device_type.0, sub_type.0]);
+    out.extend(len.to_le_bytes());
+    out.extend(data);
+    Ok(())
+}
+
+/// Parse a compressed EISA ID, e.g. `PNP0A03`. Numeric IDs are also
+/// accepted.
+fn parse_eisa_id(s: &str) -> Result<u32, ParseDevicePathError> {
+    let bytes = s.as_bytes();
+    if bytes.len() == 7 && bytes[..3].iter().all(u8::is_ascii_uppercase) {
+        let product =
+            u32::from_str_radix(&s[3..], 16).map_err(|_| ParseDevicePathError::InvalidArgument)?;
+        let letter = |i: usize| u32::from(bytes[i] - b'A' + 1);
+        Ok((product << 16) | (letter(0) << 10) | (letter(1) << 5) | letter(2))
+    } else {
+        parse_int(s)
+    }
+}
+
+/// Parse an IPv4 address with an optional port, e.g. `192.168.0.1:80`.
+/// An empty string is the unspecified address.
+fn parse_ipv4(s: &str) -> Result<([u8; 4], u16), ParseDevicePathError> {
+    if s.is_empty() {
+        return Ok(([0; 4], 0));
+    }
+    let (addr, port) = match s.split_once(':') {
+        Some((addr, port)) => (addr, parse_int(port)?),
+        None => (s, 0),
+    };
+    let addr = Ipv4Addr::from_str(addr).map_err(|_| ParseDevicePathError::InvalidArgument)?;
+    Ok((addr.octets(), port))
+}
+
+/// Parse an IPv6 address with an optional port, e.g. `[::1]:80`. An
+/// empty string is the unspecified address.
+fn parse_ipv6(s: &str) -> Result<([u8; 16], u16), ParseDevicePathError> {
+    if s.is_empty() {
+        return Ok(([0; 16], 0));
+    }
+    let (addr, port) = match s.strip_prefix('[').and_then(|s| s.split_once("]:")) {
+        Some((addr, port)) => (addr, parse_int(port)?),
+        None => (s, 0),
+    };
+    let addr = Ipv6Addr::from_str(addr).map_err(|_| ParseDevicePathError::InvalidArgument)?;
+    Ok((addr.octets(), port))
+}
+
+fn parse_ip_protocol(s: &str) -> Result<u16, ParseDevicePathError> {
+    match s {
+        "TCP" => Ok(6),
+        "UDP" => Ok(17),
+        _ => parse_int(s),
+    }
+}
+
+/// Parse the four device topology arguments of a SAS node. This is the
+/// inverse of `fmt_sas_topology`.
+fn parse_sas_topology(
+    sas_sata: &str,
+    location: &str,
+    connect: &str,
+    drive_bay: &str,
+) -> Result<u16, ParseDevicePathError> {
+    let flag = |s: &str, set, unset, bit| {
+        if s == set {
+            Ok(bit)
+        } else if s == unset {
+            Ok(0)
+        } else {
+            Err(ParseDevicePathError::InvalidArgument)
+        }
+    };
+    match sas_sata {
+        "NoTopology" => Ok(0),
+        "SAS" | "SATA" => {
+            // The drive bay is stored minus one. Zero means that there
+            // is no drive bay information.
+            let mut info = match parse_int::<u16>(drive_bay)? {
+                0 => 0x1,
+                bay @ 1..=0x100 => 0x2 | ((bay - 1) << 8),
+                _ => return Err(ParseDevicePathError::InvalidArgument),
+            };
+            info |= flag(sas_sata, "SATA", "SAS", 0x10)?;
+            info |= flag(location, "External", "Internal", 0x20)?;
+            info |= flag(connect, "Expanded", "Direct", 0x40)?;
+            Ok(info)
+        }
+        _ => parse_int(sas_sata),
+    }
+}
+
+/// Parse the arguments of a node that doesn't have a generated text
+/// conversion, and append the node to `out`. Returns `false` if `name`
+/// is not the name of such a node.
+fn custom_node_from_text(
+    name: &str,
+    args: TextArgs<'_>,
+    out: &mut Vec<u8>,
+) -> Result<bool, ParseDevicePathError> {
+    if let Some((_, product)) = ACPI_SHORTCUTS.iter().find(|(n, _)| *n == name) {
+        let [uid] = args.into_array()?;
+        push_node(
+            out,
+            &build::acpi::Acpi {
+                hid: (u32::from(*product) << 16) | PNP_EISA_ID,
+                uid: parse_int(uid)?,
+            },
+        )?;
+        return Ok(true);
+    }
+
+    if let Some((_, vendor_guid)) = VENDOR_MSG_SHORTCUTS.iter().find(|(n, _)| *n == name) {
+        let [] = args.into_array()?;
+        push_node(
+            out,
+            &build::messaging::Vendor {
+                vendor_guid: *vendor_guid,
+                vendor_defined_data: &[],
+            },
+        )?;
+        return Ok(true);
+    }
+
+    if let Some((_, device_type)) = GENERIC_NAMES.iter().find(|(n, _)| *n == name) {
+        let [sub_type, data] = args.into_array()?;
+        push_raw_node(
+            out,
+            *device_type,
+            DeviceSubType(parse_int(sub_type)?),
+            &parse_hex_bytes(data)?,
+        )?;
+        return Ok(true);
+    }
+
+    match name {
+        "Path" => {
+            let [device_type, sub_type, data] = args.into_array()?;
+            let device_type = DeviceType(parse_int(device_type)?);
+            if device_type == DeviceType::END {
+                return Err(ParseDevicePathError::InvalidArgument);
+            }
+            push_raw_node(
+                out,
+                device_type,
+                DeviceSubType(parse_int(sub_type)?),
+                &parse_hex_bytes(data)?,
+            )?;
+        }
+        "Acpi" => {
+            let [hid, uid] = args.into_array()?;
+            push_node(
+                out,
+                &build::acpi::Acpi {
+                    hid: parse_eisa_id(hid)?,
+                    uid: parse_int(uid)?,
+                },
+            )?;
+        }
+        "AcpiExp" => {
+            let [hid, cid, uid_str] = args.into_array()?;
+            let mut uid_str = parse_ascii(uid_str)?;
+            uid_str.push(0);
+            push_node(
+                out,
+                &build::acpi::Expanded {
+                    hid: parse_eisa_id(hid)?,
+                    uid: 0,
+                    cid: parse_eisa_id(cid)?,
+                    hid_str: b"\0",
+                    uid_str: &uid_str,
+                    cid_str: b"\0",
+                },
+            )?;
+        }
+        "AcpiEx" => {
+            let [hid, cid, uid, hid_str, cid_str, uid_str] = args.into_array()?;
+            let cstr = |s| {
+                let mut bytes = parse_ascii(s)?;
+                bytes.push(0);
+                Ok::<_, ParseDevicePathError>(bytes)
+            };
+            push_node(
+                out,
+                &build::acpi::Expanded {
+                    hid: parse_eisa_id(hid)?,
+                    uid: parse_int(uid)?,
+                    cid: parse_eisa_id(cid)?,
+                    hid_str: &cstr(hid_str)?,
+                    uid_str: &cstr(uid_str)?,
+                    cid_str: &cstr(cid_str)?,
+                },
+            )?;
+        }
+        "AcpiAdr" => {
+            let adr = args
+                .iter()
+                .map(parse_int)
+                .collect::<Result<Vec<u32>, _>>()?;
+            let adr =
+                build::acpi::AdrSlice::new(&adr).ok_or(ParseDevicePathError::InvalidArgument)?;
+            push_node(out, &build::acpi::Adr { adr })?;
+        }
+        "MAC" => {
+            let [mac_address, interface_type] = args.into_array()?;
+            let bytes = parse_hex_bytes(mac_address)?;
+            let mut mac_address = [0; 32];
+            mac_address
+                .get_mut(..bytes.len())
+                .ok_or(ParseDevicePathError::InvalidArgument)?
+                .copy_from_slice(&bytes);
+            push_node(
+                out,
+                &build::messaging::MacAddress {
+                    mac_address,
+                    interface_type: parse_int(interface_type)?,
+                },
+            )?;
+        }
+        "IPv4" => {
+            let [remote, protocol, origin, local, gateway, subnet] = args.into_array()?;
+            let (remote_ip_address, remote_port) = parse_ipv4(remote)?;
+            let (local_ip_address, local_port) = parse_ipv4(local)?;
+            push_node(
+                out,
+                &build::messaging::Ipv4 {
+                    local_ip_address,
+                    remote_ip_address,
+                    local_port,
+                    remote_port,
+                    protocol: parse_ip_protocol(protocol)?,
+                    ip_address_origin: match origin {
+                        "Static" => Ipv4AddressOrigin::STATIC,
+                        "DHCP" => Ipv4AddressOrigin::DHCP,
+                        _ => Ipv4AddressOrigin(parse_int(origin)?),
+                    },
+                    gateway_ip_address: parse_ipv4(gateway)?.0,
+                    subnet_mask: parse_ipv4(subnet)?.0,
+                },
+            )?;
+        }
+        "IPv6" => {
+            let [remote, protocol, origin, local, prefix_length, gateway] = args.into_array()?;
+            let (remote_ip_address, remote_port) = parse_ipv6(remote)?;
+            let (local_ip_address, local_port) = parse_ipv6(local)?;
+            push_node(
+                out,
+                &build::messaging::Ipv6 {
+                    local_ip_address,
+                    remote_ip_address,
+                    local_port,
+                    remote_port,
+                    protocol: parse_ip_protocol(protocol)?,
+                    ip_address_origin: match origin {
+                        "Static" => Ipv6AddressOrigin::MANUAL,
+                        "StatelessAutoConfigure" => Ipv6AddressOrigin::STATELESS_AUTO_CONFIGURATION,
+                        "StatefulAutoConfigure" => Ipv6AddressOrigin::STATEFUL_CONFIGURATION,
+                        _ => Ipv6AddressOrigin(parse_int(origin)?),
+                    },
+                    prefix_length: parse_int(prefix_length)?,
+                    gateway_ip_address: parse_ipv6(gateway)?.0,
+                },
+            )?;
+        }
+        "SasEx" => {
+            let [address, lun, rtp, sas_sata, location, connect, drive_bay] = args.into_array()?;
+            push_node(
+                out,
+                &build::messaging::SasEx {
+                    sas_address: parse_int::<u64>(address)?.to_be_bytes(),
+                    logical_unit_number: parse_int::<u64>(lun)?.to_be_bytes(),
+                    info: parse_sas_topology(sas_sata, location, connect, drive_bay)?,
+                    relative_target_port: parse_int(rtp)?,
+                },
+            )?;
+        }
+        "SAS" => {
+            let [address, lun, rtp, sas_sata, location, connect, drive_bay, reserved] =
+                args.into_array()?;
+            let info = parse_sas_topology(sas_sata, location, connect, drive_bay)?;
+            let mut data = Vec::with_capacity(24);
+            data.extend(parse_int::<u32>(reserved)?.to_le_bytes());
+            data.extend(parse_int::<u64>(address)?.to_le_bytes());
+            data.extend(parse_int::<u64>(lun)?.to_le_bytes());
+            data.extend(info.to_le_bytes());
+            data.extend(parse_int::<u16>(rtp)?.to_le_bytes());
+            push_node(
+                out,
+                &build::messaging::Vendor {
+                    vendor_guid: SAS_VENDOR_GUID,
+                    vendor_defined_data: &data,
+                },
+            )?;
+        }
+        "VenMsg" => {
+            let [vendor_guid, data] = args.into_array()?;
+            push_node(
+                out,
+                &build::messaging::Vendor {
+                    vendor_guid: parse_guid(vendor_guid)?,
+                    vendor_defined_data: &parse_hex_bytes(data)?,
+                },
+            )?;
+        }
+        "iSCSI" => {
+            let [name, tag, lun, header_digest, data_digest, auth, protocol] = args.into_array()?;
+            let mut options = IscsiLoginOptions::empty();
+            let mut digest = |s, flag| {
+                match s {
+                    "CRC32C" => options |= flag,
+                    "None" | "" => {}
+                    _ => return Err(ParseDevicePathError::InvalidArgument),
+                }
+                Ok(())
+            };
+            digest(header_digest, IscsiLoginOptions::HEADER_DIGEST_USING_CRC32)?;
+            digest(data_digest, IscsiLoginOptions::DATA_DIGEST_USING_CRC32)?;
+            match auth {
+                "None" => options |= IscsiLoginOptions::AUTH_METHOD_NONE,
+                "CHAP_UNI" => options |= IscsiLoginOptions::CHAP_UNI,
+                "CHAP_BI" | "" => {}
+                _ => return Err(ParseDevicePathError::InvalidArgument),
+            }
+            let lun = parse_hex_bytes(lun)?;
+            let mut logical_unit_number = [0; 8];
+            logical_unit_number
+                .get_mut(..lun.len())
+                .ok_or(ParseDevicePathError::InvalidArgument)?
+                .copy_from_slice(&lun);
+            push_node(
+                out,
+                &build::messaging::Iscsi {
+                    protocol: match protocol {
+                        "TCP" | "" => IscsiProtocol::TCP,
+                        _ => IscsiProtocol(parse_int(protocol)?),
+                    },
+                    options,
+                    logical_unit_number,
+                    target_portal_group_tag: parse_int(tag)?,
+                    iscsi_target_name: &parse_ascii(name)?,
+                },
+            )?;
+        }
+        "Dns" => {
+            let is_ipv6 = args.iter().any(|addr| addr.contains(':'));
+            let addresses = args
+                .iter()
+                .map(|addr| {
+                    Ok(if is_ipv6 {
+                        IpAddress::new_v6(parse_ipv6(addr)?.0)
+                    } else {
+                        IpAddress::new_v4(parse_ipv4(addr)?.0)
+                    })
+                })
+                .collect::<Result<Vec<_>, ParseDevicePathError>>()?;
+            push_node(
+                out,
+                &build::messaging::Dns {
+                    address_type: if is_ipv6 {
+                        DnsAddressType::IPV6
+                    } else {
+                        DnsAddressType::IPV4
+                    },
+                    addresses: &addresses,
+                },
+            )?;
+        }
+        "RestService" => {
+            let [service_type, access_mode, vendor_guid, data] = args.into_array()?;
+            let service_type = match service_type {
+                "RedFish" => RestServiceType::REDFISH,
+                "OData" => RestServiceType::ODATA,
+                "Vendor" => RestServiceType::VENDOR,
+                _ => RestServiceType(parse_int(service_type)?),
+            };
+            let access_mode = match access_mode {
+                "In-Band" => RestServiceAccessMode::IN_BAND,
+                "Out-of-Band" => RestServiceAccessMode::OUT_OF_BAND,
+                _ => RestServiceAccessMode(parse_int(access_mode)?),
+            };
+            let data = parse_hex_bytes(data)?;
+            let vendor_guid_and_data = if service_type == RestServiceType::VENDOR {
+                Some(build::messaging::RestServiceVendorData {
+                    vendor_guid: parse_guid(vendor_guid)?,
+                    vendor_defined_data: &data,
+                })
+            } else {
+                None
+            };
+            push_node(
+                out,
+                &build::messaging::RestService {
+                    service_type,
+                    access_mode,
+                    vendor_guid_and_data,
+                },
+            )?;
+        }
+        "HD" => {
+            let [number, signature_type, signature, start, size] = args.into_array()?;
+            let (partition_signature, partition_format) = match signature_type {
+                "MBR" => (
+                    PartitionSignature::Mbr(parse_int::<u32>(signature)?.to_le_bytes()),
+                    PartitionFormat::MBR,
+                ),
+                "GPT" => (
+                    PartitionSignature::Guid(parse_guid(signature)?),
+                    PartitionFormat::GPT,
+                ),
+                _ => match parse_int(signature_type)? {
+                    0 => (PartitionSignature::None, PartitionFormat(0)),
+                    signature_type => {
+                        let bytes = parse_hex_bytes(signature)?;
+                        (
+                            PartitionSignature::Unknown {
+                                signature_type,
+                                signature: bytes
+                                    .try_into()
+                                    .map_err(|_| ParseDevicePathError::InvalidArgument)?,
+                            },
+                            PartitionFormat(0),
+                        )
+                    }
+                },
+            };
+            push_node(
+                out,
+                &build::media::HardDrive {
+                    partition_number: parse_int(number)?,
+                    partition_start: parse_int(start)?,
+                    partition_size: parse_int(size)?,
+                    partition_signature,
+                    partition_format,
+                },
+            )?;
+        }
+        _ => return Ok(false),
+    }
+
+    Ok(true)
+}
+
+/// Append a file path node. Used for any node text that is not
+/// recognized as another node type.
+fn push_file_path(text: &str, out: &mut Vec<u8>) -> Result<(), ParseDevicePathError> {
+    let path_name = CString16::try_from(text).map_err(|_| ParseDevicePathError::InvalidArgument)?;
+    push_node(
+        out,
+        &build::media::FilePath {
+            path_name: &path_name,
+        },
+    )
+}
+
+/// Whether `name` has the form of a node name, e.g. `Pci` or `Wi-Fi`.
+fn is_node_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Parse the text of a single node and append it to `out`.
+fn parse_node(text: &str, out: &mut Vec<u8>) -> Result<(), ParseDevicePathError> {
+    // Text that doesn't start with a node name, such as `\EFI\BOOT`,
+    // is a file path.
+    let Some((name, args)) = text.split_once('(').filter(|(name, _)| is_node_name(name)) else {
+        return push_file_path(text, out);
+    };
+    let args = TextArgs(
+        args.strip_suffix(')')
+            .ok_or(ParseDevicePathError::InvalidSyntax)?,
+    );
+
+    if node_from_text(name, args, out)? || custom_node_from_text(name, args, out)? {
+        Ok(())
+    } else {
+        push_file_path(text, out)
+    }
+}
+
+impl FromStr for Box<DevicePath> {
+    type Err = ParseDevicePathError;
+
+    /// Parse the text representation of a device path, as defined in
+    /// the UEFI Specification. This is the inverse of the [`Display`]
+    /// implementation of [`DevicePath`].
+    ///
+    /// Instances are separated by `,` and nodes by `/`. As in the
+    /// reference implementation, node text that doesn't match any known
+    /// node name is treated as a file path.
+    ///
+    /// Unlike [`DevicePathFromText`], this does not use any protocol
+    /// provided by the firmware.
+    ///
+    /// [`DevicePathFromText`]: super::DevicePathFromText
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut data = Vec::new();
+
+        for (index, instance) in split_top_level(s.trim(), ',').enumerate() {
+            if index != 0 {
+                push_node(&mut data, &build::end::Instance)?;
+            }
+            for node in split_top_level(instance, '/') {
+                let node = node.trim();
+                if !node.is_empty() {
+                    parse_node(node, &mut data)?;
+                }
+            }
+        }
+        push_node(&mut data, &build::end::Entire)?;
+
+        let data: Box<[u8]> = data.into_boxed_slice();
+        Ok(unsafe { mem::transmute::<Box<[u8]>, Self>(data) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::device_path::{DevicePathNodeEnum, DeviceSubType};
+    use alloc::format;
+
+    fn parse(s: &str) -> Box<DevicePath> {
+        s.parse().unwrap()
+    }
+
+    /// Parse `s` and check that formatting the result gives back `s`.
+    #[track_caller]
+    fn check_round_trip(s: &str) {
+        assert_eq!(format!("{}", parse(s)), s);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        check_round_trip(
+            "PciRoot(0x0)/Pci(0x1,0x0)/HD(1,GPT,6E4F1A25-4E80-4A6C-9E8D-0C7A8A6C6F33,0x800,0x100000)/\\EFI\\BOOT\\BOOTX64.EFI",
+        );
+        check_round_trip("PciRoot(0x0)/Pci(0x1f,0x2)/Sata(0x0,0xffff,0x0)");
+        check_round_trip(
+            "PcieRoot(0x1)/Pci(0x0,0x0)/NVMe(0x1,00-11-22-33-44-55-66-77)/HD(2,MBR,0x1234abcd,0x3f,0x1000)",
+        );
+        check_round_trip("Acpi(PNP0A0A,0x0)/Ata(Primary,Slave,0x0)/CDROM(0x0,0x0,0x0)");
+        check_round_trip("AcpiExp(PNP0A03,0,PCI0)/USB(0x1,0x0)/Unit(0x2)");
+        check_round_trip("AcpiEx(PNP0A03,PNP0A08,0x1,HID,CID,UID)/AcpiAdr(0x80010100,0x2)");
+        check_round_trip(
+            "MemoryMapped(0xb,0x1000,0x1fff)/VenHw(01020304-0506-0708-090A-0B0C0D0E0F10,aabb)",
+        );
+        check_round_trip("Uart(115200,8,N,1)/VenPcAnsi()");
+        check_round_trip("VenMsg(01020304-0506-0708-090A-0B0C0D0E0F10,00ff)");
+        check_round_trip(
+            "MAC(525400123456,0x1)/IPv4(192.168.0.1:80,TCP,DHCP,0.0.0.0,0.0.0.0,0.0.0.0)",
+        );
+        check_round_trip("MAC(525400123456,0x0)/IPv6([2001:db8::1]:443,UDP,StatelessAutoConfigure,::,0x40,fe80::1)");
+        check_round_trip("Dns(192.168.0.1,8.8.8.8)/Uri(http://example.com/boot.efi)");
+        check_round_trip(
+            "iSCSI(iqn.2024-01.com.example:target,0x1,0000000000000000,CRC32C,None,CHAP_UNI,TCP)",
+        );
+        check_round_trip(
+            "RestService(Vendor,Out-of-Band,01020304-0506-0708-090A-0B0C0D0E0F10,0102)",
+        );
+        check_round_trip("MAC(525400123456,0x1)/Vlan(100)");
+        check_round_trip("Infiniband(0x1,01020304-0506-0708-090A-0B0C0D0E0F10,0x1,0x2,0x3)");
+        check_round_trip(
+            "PciRoot(0x0)/Pci(0x3,0x0)/SasEx(0x5000c500a1b2c3d4,0x0001000000000000,0x1,SATA,External,Expanded,0x3)",
+        );
+        check_round_trip("SasEx(0x5000c500a1b2c3d4,0x0000000000000000,0x0,NoTopology,0,0,0)");
+        check_round_trip("SasEx(0x5000c500a1b2c3d4,0x0000000000000000,0x0,SAS,Internal,Direct,0)");
+        check_round_trip("SAS(0x5000c500a1b2c3d4,0x0,0x0,SAS,Internal,Direct,0,0x0)");
+        check_round_trip("SAS(0x5000c500a1b2c3d4,0x2,0x1,0x5,0,0,0,0x0)");
+        check_round_trip("UsbWwid(0x1234,0x5678,0x0,\"SN123\")/Wi-Fi(home)");
+        check_round_trip("BBS(USB,\"USB Drive\",0x0)");
+        check_round_trip("BBS(0x81,\"Other\",0x0)");
+        check_round_trip(
+            "Fv(7CB8BDC9-F8EB-4F34-AAEA-3EE4AF6516A1)/FvFile(462CAA21-7614-4503-836E-8AB6F4662331)",
+        );
+        check_round_trip("RamDisk(0x1000,0x1fff,0x0,77AB535A-45FC-624B-5560-F7B281D1F96E)");
+        check_round_trip("HardwarePath(6,0102)/Path(8,1,aa)");
+    }
+
+    #[test]
+    fn test_multiple_instances() {
+        let path = parse("PciRoot(0x0)/Pci(0x2,0x0),PciRoot(0x0)/Pci(0x3,0x0)");
+        assert_eq!(path.instance_iter().count(), 2);
+        assert_eq!(path.node_iter().count(), 5);
+        assert_eq!(
+            format!("{path}"),
+            "PciRoot(0x0)/Pci(0x2,0x0),PciRoot(0x0)/Pci(0x3,0x0)"
+        );
+    }
+
+    #[test]
+    fn test_parse_nodes() {
+        let path = parse("Pci(0x1f, 2)/\\EFI\\BOOT\\BOOTX64.EFI");
+        let mut nodes = path.node_iter();
+
+        let DevicePathNodeEnum::HardwarePci(pci) = nodes.next().unwrap().as_enum().unwrap() else {
+            panic!("wrong node type");
+        };
+        assert_eq!(pci.device(), 0x1f);
+        assert_eq!(pci.function(), 2);
+
+        let node = nodes.next().unwrap();
+        assert_eq!(node.sub_type(), DeviceSubType::MEDIA_FILE_PATH);
+        assert_eq!(format!("{node}"), "\\EFI\\BOOT\\BOOTX64.EFI");
+
+        assert!(nodes.next().is_none());
+    }
+
+    /// Check the node data of the text forms where EDK2 doesn't simply
+    /// print each field in order.
+    #[test]
+    fn test_parse_edk2_forms() {
+        let path = parse("NVMe(0x1,00-11-22-33-44-55-66-77)");
+        let DevicePathNodeEnum::MessagingNvmeNamespace(nvme) =
+            path.node_iter().next().unwrap().as_enum().unwrap()
+        else {
+            panic!("wrong node type");
+        };
+        assert_eq!(
+            nvme.ieee_extended_unique_identifier(),
+            0x0011_2233_4455_6677
+        );
+
+        let path = parse("Vlan(100)");
+        let DevicePathNodeEnum::MessagingVlan(vlan) =
+            path.node_iter().next().unwrap().as_enum().unwrap()
+        else {
+            panic!("wrong node type");
+        };
+        assert_eq!(vlan.vlan_id(), 100);
+
+        let path = parse("BBS(HD,\"x\",0x0)");
+        let DevicePathNodeEnum::BiosBootSpecBootSpecification(bbs) =
+            path.node_iter().next().unwrap().as_enum().unwrap()
+        else {
+            panic!("wrong node type");
+        };
+        assert_eq!(bbs.device_type(), 2);
+        assert_eq!(format!("{path}"), "BBS(HD,\"x\",0x0)");
+
+        let path = parse("SasEx(0x5000c500a1b2c3d4,0x1,0x1,SATA,External,Expanded,0x3)");
+        let DevicePathNodeEnum::MessagingSasEx(sas) =
+            path.node_iter().next().unwrap().as_enum().unwrap()
+        else {
+            panic!("wrong node type");
+        };
+        assert_eq!(
+            sas.sas_address(),
+            [0x50, 0x00, 0xc5, 0x00, 0xa1, 0xb2, 0xc3, 0xd4]
+        );
+        assert_eq!(sas.logical_unit_number(), [0, 0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(sas.info(), 0x0272);
+        assert_eq!(sas.relative_target_port(), 1);
+
+        let path = parse("SAS(0x5000c500a1b2c3d4,0x2,0x1,SAS,Internal,Direct,0,0x0)");
+        let vendor = path.node_iter().next().unwrap();
+        assert_eq!(vendor.sub_type(), DeviceSubType::MESSAGING_VENDOR);
+        assert_eq!(
+            vendor.data(),
+            [
+                // Vendor GUID.
+                0xb4, 0xdd, 0x87, 0xd4, 0x8b, 0x00, 0xd9, 0x11, 0xaf, 0xdc, 0x00, 0x10, 0x83, 0xff,
+                0xca, 0x4d, //
+                // Reserved.
+                0, 0, 0, 0, //
+                // SAS address.
+                0xd4, 0xc3, 0xb2, 0xa1, 0x00, 0xc5, 0x00, 0x50, //
+                // LUN.
+                2, 0, 0, 0, 0, 0, 0, 0, //
+                // Device topology, then RTP.
+                0x01, 0x00, 0x01, 0x00,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_empty() {
+        let path = parse("");
+        assert_eq!(path.node_iter().count(), 0);
+        assert_eq!(path.as_bytes(), [0x7f, 0xff, 4, 0]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let parse = |s: &str| s.parse::<Box<DevicePath>>().unwrap_err();
+        assert_eq!(parse("Pci(0x1,0x2"), ParseDevicePathError::InvalidSyntax);
+        assert_eq!(
+            parse("Pci(0x1,0x2,0x3)"),
+            ParseDevicePathError::TooManyArguments
+        );
+        assert_eq!(
+            parse("Pci(0x100,0x0)"),
+            ParseDevicePathError::InvalidArgument
+        );
+        assert_eq!(
+            parse("VenHw(not-a-guid)"),
+            ParseDevicePathError::InvalidArgument
+        );
+        assert_eq!(
+            parse("Path(127,255)"),
+            ParseDevicePathError::InvalidArgument
+        );
+    }
+}
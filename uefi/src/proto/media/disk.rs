@@ -2,6 +2,8 @@
 
 //! Disk I/O protocols.
 
+use crate::io::{Read, Seek, SeekFrom, Write};
+use crate::proto::media::block::BlockIOMedia;
 use crate::proto::unsafe_protocol;
 use crate::util::opt_nonnull_to_ptr;
 use crate::{Event, Result, Status, StatusExt};
@@ -72,6 +74,100 @@ impl DiskIo {
     }
 }
 
+/// A seekable byte stream over a [`DiskIo`] device.
+///
+/// This implements the [`Read`], [`Write`], and [`Seek`] traits from
+/// [`uefi::io`], so that generic parsers can read directly from a disk or
+/// partition. Reads and writes are clamped to the size of the medium.
+///
+/// [`uefi::io`]: crate::io
+#[derive(Debug)]
+pub struct DiskIoCursor<'a> {
+    disk_io: &'a mut DiskIo,
+    media_id: u32,
+    size: u64,
+    pos: u64,
+}
+
+impl<'a> DiskIoCursor<'a> {
+    /// Creates a cursor at position zero for the medium described by
+    /// `media`, which is usually obtained from the [`BlockIO`] protocol on
+    /// the same handle.
+    ///
+    /// [`BlockIO`]: crate::proto::media::block::BlockIO
+    #[must_use]
+    pub fn new(disk_io: &'a mut DiskIo, media: &BlockIOMedia) -> Self {
+        let size = (media.last_block() + 1) * u64::from(media.block_size());
+        Self::with_size(disk_io, media.media_id(), size)
+    }
+
+    /// Creates a cursor at position zero for a medium with the given ID and
+    /// size in bytes.
+    #[must_use]
+    pub const fn with_size(disk_io: &'a mut DiskIo, media_id: u32, size: u64) -> Self {
+        Self {
+            disk_io,
+            media_id,
+            size,
+            pos: 0,
+        }
+    }
+
+    /// Returns the size of the medium in bytes.
+    #[must_use]
+    pub const fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the current position.
+    #[must_use]
+    pub const fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Returns the number of bytes that can be transferred at the current
+    /// position, limited to `len`.
+    fn clamp(&self, len: usize) -> usize {
+        let remaining = self.size.saturating_sub(self.pos);
+        usize::try_from(remaining).map_or(len, |remaining| remaining.min(len))
+    }
+}
+
+impl Read for DiskIoCursor<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.clamp(buf.len());
+        self.disk_io
+            .read_disk(self.media_id, self.pos, &mut buf[..n])?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Write for DiskIoCursor<'_> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let n = self.clamp(buf.len());
+        self.disk_io
+            .write_disk(self.media_id, self.pos, &buf[..n])?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+
+    /// Does nothing. [`DiskIo`] writes are not cached; use
+    /// [`BlockIO::flush_blocks`] to flush the device.
+    ///
+    /// [`BlockIO::flush_blocks`]: crate::proto::media::block::BlockIO::flush_blocks
+    fn flush(&mut self) -> Result {
+        Ok(())
+    }
+}
+
+impl Seek for DiskIoCursor<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        self.pos = pos.resolve(self.pos, self.size)?;
+        Ok(self.pos)
+    }
+}
+
 /// Asynchronous transaction token for disk I/O 2 operations.
 #[repr(C)]
 #[derive(Debug)]
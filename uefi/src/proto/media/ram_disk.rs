@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! RAM disk protocol.
+
+use crate::mem::PoolAllocation;
+use crate::proto::device_path::media::RamDiskType;
+use crate::proto::device_path::{DevicePath, PoolDevicePath};
+use crate::proto::unsafe_protocol;
+use crate::{Result, Status, StatusExt};
+use core::ptr::{self, NonNull};
+use uefi_raw::protocol::media::RamDiskProtocol;
+
+/// RAM Disk Protocol.
+///
+/// Registers a range of memory as a disk. The firmware creates a new handle
+/// with a [`BlockIO`] protocol and a device path ending in a
+/// [`RamDisk`] node for it, so that partitions and file systems on the disk
+/// become accessible like on any other disk.
+///
+/// See [`disk::ram`] for a safe interface that also works if the firmware
+/// does not implement this protocol.
+///
+/// [`BlockIO`]: crate::proto::media::block::BlockIO
+/// [`RamDisk`]: crate::proto::device_path::media::RamDisk
+/// [`disk::ram`]: crate::disk::ram
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(RamDiskProtocol::GUID)]
+pub struct RamDisk(RamDiskProtocol);
+
+impl RamDisk {
+    /// Register the `size` bytes at `base` as a RAM disk of type
+    /// `disk_type`.
+    ///
+    /// If `parent` is given, the device path of the RAM disk is made of
+    /// `parent` followed by a [`RamDisk`] node. The device path is
+    /// returned.
+    ///
+    /// # Safety
+    ///
+    /// The memory must stay valid and must not be accessed other than
+    /// through the disk until it is unregistered with [`unregister`].
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::UNSUPPORTED`]: `disk_type` is not supported.
+    /// * [`Status::INVALID_PARAMETER`]: `size` is zero.
+    /// * [`Status::ALREADY_STARTED`]: a RAM disk with the same device path
+    ///   is already registered.
+    /// * [`Status::OUT_OF_RESOURCES`]: not enough resources to register the
+    ///   disk.
+    ///
+    /// [`RamDisk`]: crate::proto::device_path::media::RamDisk
+    /// [`unregister`]: Self::unregister
+    pub unsafe fn register(
+        &self,
+        base: u64,
+        size: u64,
+        disk_type: RamDiskType,
+        parent: Option<&DevicePath>,
+    ) -> Result<PoolDevicePath> {
+        let parent = parent.map_or(ptr::null(), |p| p.as_ffi_ptr().cast());
+        let mut device_path = ptr::null();
+        unsafe { (self.0.register)(base, size, &disk_type.0, parent, &mut device_path) }
+            .to_result()?;
+        NonNull::new(device_path.cast_mut())
+            .map(|p| PoolDevicePath(PoolAllocation::new(p.cast())))
+            .ok_or_else(|| Status::DEVICE_ERROR.into())
+    }
+
+    /// Unregister the RAM disk with the device path `device_path`. The
+    /// firmware removes the disk's handle; its memory can be reused
+    /// afterwards.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::UNSUPPORTED`]: `device_path` is not a RAM disk path.
+    /// * [`Status::NOT_FOUND`]: no RAM disk with this device path is
+    ///   registered.
+    pub fn unregister(&self, device_path: &DevicePath) -> Result {
+        unsafe { (self.0.unregister)(device_path.as_ffi_ptr().cast()) }.to_result()
+    }
+}
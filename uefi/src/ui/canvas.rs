@@ -0,0 +1,721 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Double-buffered drawing on a [`GraphicsOutput`] device.
+//!
+//! A [`Canvas`] draws into a buffer in memory and only copies it to the
+//! screen when [`Canvas::flush`] is called, which avoids flicker and
+//! tearing. It keeps track of the regions that changed since the last
+//! flush, and only copies those. Regions filled with a single color are
+//! drawn with a [`BltOp::VideoFill`] rather than copied.
+//!
+//! By default the buffer is copied with [`GraphicsOutput::blt`].
+//! [`Canvas::with_direct_writes`] makes it write to the framebuffer
+//! instead, converting the pixels to the format of the current mode.
+//!
+//! ```no_run
+//! use uefi::boot;
+//! use uefi::proto::console::gop::{BltPixel, GraphicsOutput};
+//! use uefi::ui::canvas::Canvas;
+//!
+//! # fn main() -> uefi::Result {
+//! let handle = boot::get_handle_for_protocol::<GraphicsOutput>()?;
+//! let mut gop = boot::open_protocol_exclusive::<GraphicsOutput>(handle)?;
+//! let mut canvas = Canvas::new(&mut gop);
+//! let (width, height) = canvas.size();
+//!
+//! canvas.clear(BltPixel::new(0, 0, 64));
+//! canvas.fill_rect((10, 10), (200, 100), BltPixel::new(255, 255, 255));
+//! canvas.draw_line((0, 0), (width - 1, height - 1), BltPixel::new(255, 0, 0));
+//! canvas.fill_circle((width / 2, height / 2), 50, BltPixel::new(0, 255, 0));
+//! canvas.flush()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`GraphicsOutput`]: crate::proto::console::gop::GraphicsOutput
+//! [`GraphicsOutput::blt`]: crate::proto::console::gop::GraphicsOutput::blt
+//! [`BltOp::VideoFill`]: crate::proto::console::gop::BltOp::VideoFill
+
+use super::pixel::{self, blt, Encoding};
+use crate::proto::console::gop::{BltOp, BltPixel, BltRegion, GraphicsOutput, PixelFormat};
+use crate::Result;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
+use core::marker::PhantomData;
+
+/// Number of dirty regions tracked before they are merged into one.
+const MAX_DIRTY_REGIONS: usize = 16;
+
+/// A drawing buffer that is copied to a [`GraphicsOutput`] device. See the
+/// [module documentation].
+///
+/// All coordinates are in pixels, with `(0, 0)` at the top left. Drawing
+/// is clipped to the canvas.
+///
+/// [`GraphicsOutput`]: crate::proto::console::gop::GraphicsOutput
+/// [module documentation]: self
+pub struct Canvas<'a> {
+    width: usize,
+    height: usize,
+    pixels: Vec<BltPixel>,
+    dirty: Vec<Region>,
+    output: Output<'a>,
+}
+
+/// Where the canvas is flushed to.
+enum Output<'a> {
+    Blt(&'a mut GraphicsOutput),
+    FrameBuffer {
+        base: *mut u8,
+        stride: usize,
+        encoding: Encoding,
+        _lifetime: PhantomData<&'a mut u8>,
+    },
+}
+
+/// A changed region of the canvas.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct Region {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    /// The color of all pixels of the region, if they have the same color.
+    fill: Option<BltPixel>,
+}
+
+impl Region {
+    const fn contains(&self, other: &Self) -> bool {
+        other.x >= self.x
+            && other.y >= self.y
+            && other.x + other.width <= self.x + self.width
+            && other.y + other.height <= self.y + self.height
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Self {
+            x,
+            y,
+            width: (self.x + self.width).max(other.x + other.width) - x,
+            height: (self.y + self.height).max(other.y + other.height) - y,
+            fill: None,
+        }
+    }
+}
+
+impl<'a> Canvas<'a> {
+    /// Create a canvas with the resolution of the current mode of `gop`.
+    ///
+    /// The buffer is initially black, and nothing is flushed until it is
+    /// drawn on. Use [`clear`] or [`flush_all`] to draw the whole screen.
+    ///
+    /// [`clear`]: Self::clear
+    /// [`flush_all`]: Self::flush_all
+    #[must_use]
+    pub fn new(gop: &'a mut GraphicsOutput) -> Self {
+        let (width, height) = gop.current_mode_info().resolution();
+        Self::with_output(width, height, Output::Blt(gop))
+    }
+
+    fn with_output(width: usize, height: usize, output: Output<'a>) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![BltPixel::new(0, 0, 0); width * height],
+            dirty: Vec::new(),
+            output,
+        }
+    }
+
+    /// Flush by writing to the framebuffer directly, rather than with
+    /// [`GraphicsOutput::blt`].
+    ///
+    /// The pixels are converted to the [`PixelFormat`] of the current mode.
+    /// Nothing is changed if the mode is [`PixelFormat::BltOnly`]. Writing
+    /// directly can be faster on some firmware, but the framebuffer may be
+    /// slow to access.
+    ///
+    /// [`GraphicsOutput::blt`]: crate::proto::console::gop::GraphicsOutput::blt
+    #[must_use]
+    pub fn with_direct_writes(mut self) -> Self {
+        if let Output::Blt(gop) = &mut self.output {
+            let info = gop.current_mode_info();
+            if info.pixel_format() != PixelFormat::BltOnly {
+                let mut fb = gop.frame_buffer();
+                let stride = info.stride();
+                // Don't write outside of the framebuffer if the firmware
+                // reports an inconsistent mode.
+                let rows = fb.size() / (stride * pixel::PIXEL_SIZE).max(1);
+                self.height = self.height.min(rows);
+                self.width = self.width.min(stride);
+                self.pixels.truncate(self.width * self.height);
+                self.output = Output::FrameBuffer {
+                    base: fb.as_mut_ptr(),
+                    stride,
+                    encoding: Encoding::new(&info),
+                    _lifetime: PhantomData,
+                };
+            }
+        }
+        self
+    }
+
+    /// Width and height of the canvas.
+    #[must_use]
+    pub const fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// The pixels of the canvas, row by row.
+    #[must_use]
+    pub fn pixels(&self) -> &[BltPixel] {
+        &self.pixels
+    }
+
+    /// The color of a pixel, or `None` if it is outside of the canvas.
+    #[must_use]
+    pub fn pixel(&self, x: usize, y: usize) -> Option<BltPixel> {
+        (x < self.width && y < self.height).then(|| self.pixels[y * self.width + x])
+    }
+
+    /// Whether anything was drawn since the last flush.
+    #[must_use]
+    pub fn is_dirty(&self) -> bool {
+        !self.dirty.is_empty()
+    }
+
+    /// Fill the whole canvas with `color`.
+    pub fn clear(&mut self, color: BltPixel) {
+        self.fill_rect((0, 0), (self.width, self.height), color);
+    }
+
+    /// Set the color of a pixel.
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: BltPixel) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = color;
+            self.mark_dirty(x, y, 1, 1, None);
+        }
+    }
+
+    /// Fill a rectangle with its top left corner at `dest` and a size of
+    /// `dims` with `color`.
+    pub fn fill_rect(&mut self, dest: (usize, usize), dims: (usize, usize), color: BltPixel) {
+        let Some((x, y, width, height)) = self.clip(dest, dims) else {
+            return;
+        };
+        for row in self.pixels[y * self.width..]
+            .chunks_mut(self.width)
+            .take(height)
+        {
+            row[x..x + width].fill(color);
+        }
+        self.mark_dirty(x, y, width, height, Some(color));
+    }
+
+    /// Draw the outline of a rectangle with its top left corner at `dest`
+    /// and a size of `dims`, one pixel wide.
+    pub fn draw_rect(&mut self, dest: (usize, usize), dims: (usize, usize), color: BltPixel) {
+        let (x, y) = dest;
+        let (width, height) = dims;
+        if width == 0 || height == 0 {
+            return;
+        }
+        self.fill_rect((x, y), (width, 1), color);
+        self.fill_rect((x, y + height - 1), (width, 1), color);
+        self.fill_rect((x, y), (1, height), color);
+        self.fill_rect((x + width - 1, y), (1, height), color);
+    }
+
+    /// Draw a line from `from` to `to`, including both ends.
+    pub fn draw_line(&mut self, from: (usize, usize), to: (usize, usize), color: BltPixel) {
+        let (mut x, mut y) = (from.0 as isize, from.1 as isize);
+        let (x1, y1) = (to.0 as isize, to.1 as isize);
+        let dx = (x1 - x).abs();
+        let dy = -(y1 - y).abs();
+        let step_x = if x < x1 { 1 } else { -1 };
+        let step_y = if y < y1 { 1 } else { -1 };
+        let mut error = dx + dy;
+        loop {
+            self.put(x, y, color);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * error;
+            if e2 >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if e2 <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+        let (left, top) = (from.0.min(to.0), from.1.min(to.1));
+        let (right, bottom) = (from.0.max(to.0), from.1.max(to.1));
+        self.mark_clipped(left, top, right - left + 1, bottom - top + 1);
+    }
+
+    /// Draw the outline of a circle, one pixel wide.
+    pub fn draw_circle(&mut self, center: (usize, usize), radius: usize, color: BltPixel) {
+        let (cx, cy) = (center.0 as isize, center.1 as isize);
+        for (x, y) in circle_points(radius) {
+            for (px, py) in [(x, y), (y, x)] {
+                self.put(cx + px, cy + py, color);
+                self.put(cx - px, cy + py, color);
+                self.put(cx + px, cy - py, color);
+                self.put(cx - px, cy - py, color);
+            }
+        }
+        self.mark_circle(center, radius);
+    }
+
+    /// Draw a filled circle.
+    pub fn fill_circle(&mut self, center: (usize, usize), radius: usize, color: BltPixel) {
+        let (cx, cy) = (center.0 as isize, center.1 as isize);
+        for (x, y) in circle_points(radius) {
+            for (half_width, dy) in [(x, y), (y, x)] {
+                for py in [cy - dy, cy + dy] {
+                    for px in cx - half_width..=cx + half_width {
+                        self.put(px, py, color);
+                    }
+                }
+            }
+        }
+        self.mark_circle(center, radius);
+    }
+
+    /// Copy an image of `dims` pixels, stored row by row in `image`, to
+    /// `dest`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `image` has fewer than `dims.0 * dims.1` pixels.
+    pub fn draw_image(&mut self, dest: (usize, usize), dims: (usize, usize), image: &[BltPixel]) {
+        self.blend_image(dest, dims, image, |_, source, _| source);
+    }
+
+    /// Draw an image of `dims` pixels, stored row by row in `image`, at
+    /// `dest`, blending it with the canvas.
+    ///
+    /// `alpha` has the opacity of each pixel of the image, from 0 for
+    /// transparent to 255 for opaque.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `image` or `alpha` have fewer than `dims.0 * dims.1`
+    /// elements.
+    pub fn draw_image_with_alpha(
+        &mut self,
+        dest: (usize, usize),
+        dims: (usize, usize),
+        image: &[BltPixel],
+        alpha: &[u8],
+    ) {
+        assert!(alpha.len() >= dims.0 * dims.1, "alpha buffer too small");
+        self.blend_image(dest, dims, image, |index, source, target| {
+            blend(source, target, alpha[index])
+        });
+    }
+
+    fn blend_image(
+        &mut self,
+        dest: (usize, usize),
+        dims: (usize, usize),
+        image: &[BltPixel],
+        mut f: impl FnMut(usize, BltPixel, BltPixel) -> BltPixel,
+    ) {
+        assert!(image.len() >= dims.0 * dims.1, "image buffer too small");
+        let Some((x, y, width, height)) = self.clip(dest, dims) else {
+            return;
+        };
+        for row in 0..height {
+            for column in 0..width {
+                let index = row * dims.0 + column;
+                let target = &mut self.pixels[(y + row) * self.width + x + column];
+                *target = f(index, image[index], *target);
+            }
+        }
+        self.mark_dirty(x, y, width, height, None);
+    }
+
+    /// Copy the regions that changed since the last flush to the screen.
+    ///
+    /// # Errors
+    ///
+    /// Errors from [`GraphicsOutput::blt`] are returned. Blitting fails
+    /// with [`Status::UNSUPPORTED`] after boot services have been exited.
+    /// The regions that were not copied stay dirty.
+    ///
+    /// [`GraphicsOutput::blt`]: crate::proto::console::gop::GraphicsOutput::blt
+    /// [`Status::UNSUPPORTED`]: crate::Status::UNSUPPORTED
+    pub fn flush(&mut self) -> Result {
+        while let Some(&region) = self.dirty.first() {
+            self.flush_region(region)?;
+            self.dirty.remove(0);
+        }
+        Ok(())
+    }
+
+    /// Copy the whole canvas to the screen.
+    ///
+    /// # Errors
+    ///
+    /// See [`flush`].
+    ///
+    /// [`flush`]: Self::flush
+    pub fn flush_all(&mut self) -> Result {
+        self.dirty.clear();
+        self.mark_dirty(0, 0, self.width, self.height, None);
+        self.flush()
+    }
+
+    fn flush_region(&mut self, region: Region) -> Result {
+        let Region {
+            x,
+            y,
+            width,
+            height,
+            fill,
+        } = region;
+        match &mut self.output {
+            Output::Blt(gop) => {
+                let op = match fill {
+                    Some(color) => BltOp::VideoFill {
+                        color,
+                        dest: (x, y),
+                        dims: (width, height),
+                    },
+                    None => BltOp::BufferToVideo {
+                        buffer: &self.pixels,
+                        src: BltRegion::SubRectangle {
+                            coords: (x, y),
+                            px_stride: self.width,
+                        },
+                        dest: (x, y),
+                        dims: (width, height),
+                    },
+                };
+                blt(gop, op)
+            }
+            Output::FrameBuffer {
+                base,
+                stride,
+                encoding,
+                ..
+            } => {
+                let fill = fill.map(|color| encoding.encode(color));
+                for py in y..y + height {
+                    for px in x..x + width {
+                        let value = fill
+                            .unwrap_or_else(|| encoding.encode(self.pixels[py * self.width + px]));
+                        unsafe { pixel::write(*base, *stride, px, py, value) };
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Clip a rectangle to the canvas, returning its position and size.
+    fn clip(
+        &self,
+        (x, y): (usize, usize),
+        (width, height): (usize, usize),
+    ) -> Option<(usize, usize, usize, usize)> {
+        let width = width.min(self.width.saturating_sub(x));
+        let height = height.min(self.height.saturating_sub(y));
+        (width > 0 && height > 0).then_some((x, y, width, height))
+    }
+
+    /// Set a pixel that may be outside of the canvas, without marking it
+    /// dirty.
+    fn put(&mut self, x: isize, y: isize, color: BltPixel) {
+        if let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y)) {
+            if x < self.width && y < self.height {
+                self.pixels[y * self.width + x] = color;
+            }
+        }
+    }
+
+    fn mark_circle(&mut self, (x, y): (usize, usize), radius: usize) {
+        let (left, top) = (x.saturating_sub(radius), y.saturating_sub(radius));
+        let size = |center: usize, start: usize| center + radius + 1 - start;
+        self.mark_clipped(left, top, size(x, left), size(y, top));
+    }
+
+    fn mark_clipped(&mut self, x: usize, y: usize, width: usize, height: usize) {
+        if let Some((x, y, width, height)) = self.clip((x, y), (width, height)) {
+            self.mark_dirty(x, y, width, height, None);
+        }
+    }
+
+    /// Record that a region, which must be within the canvas, changed.
+    fn mark_dirty(
+        &mut self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        fill: Option<BltPixel>,
+    ) {
+        let region = Region {
+            x,
+            y,
+            width,
+            height,
+            fill,
+        };
+        // The regions are flushed in order, so a region can only be
+        // skipped if the last one covers it.
+        if let Some(last) = self.dirty.last() {
+            if last.fill.is_none() && last.contains(&region) {
+                return;
+            }
+        }
+        self.dirty.retain(|old| !region.contains(old));
+        if self.dirty.len() < MAX_DIRTY_REGIONS {
+            self.dirty.push(region);
+        } else {
+            let bounds = self
+                .dirty
+                .iter()
+                .fold(region, |bounds, old| bounds.union(old));
+            self.dirty.clear();
+            self.dirty.push(bounds);
+        }
+    }
+}
+
+impl Debug for Canvas<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Canvas")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("dirty", &self.dirty)
+            .field(
+                "direct_writes",
+                &matches!(self.output, Output::FrameBuffer { .. }),
+            )
+            .finish()
+    }
+}
+
+/// Points of the first octant of a circle, from the top going right, with
+/// `y` going down from `radius` to about `radius / sqrt(2)`. The other
+/// octants are found by symmetry.
+fn circle_points(radius: usize) -> impl Iterator<Item = (isize, isize)> {
+    let radius = radius as isize;
+    let mut x = 0;
+    let mut y = radius;
+    let mut error = 1 - radius;
+    core::iter::from_fn(move || {
+        if x > y {
+            return None;
+        }
+        let point = (x, y);
+        x += 1;
+        if error < 0 {
+            error += 2 * x + 1;
+        } else {
+            y -= 1;
+            error += 2 * (x - y) + 1;
+        }
+        Some(point)
+    })
+}
+
+/// Blend `source` over `target` with an opacity of `alpha`.
+fn blend(source: BltPixel, target: BltPixel, alpha: u8) -> BltPixel {
+    let alpha = u16::from(alpha);
+    let mix =
+        |s: u8, t: u8| ((u16::from(s) * alpha + u16::from(t) * (255 - alpha) + 127) / 255) as u8;
+    BltPixel::new(
+        mix(source.red, target.red),
+        mix(source.green, target.green),
+        mix(source.blue, target.blue),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    const BLACK: BltPixel = BltPixel::new(0, 0, 0);
+    const WHITE: BltPixel = BltPixel::new(0xff, 0xff, 0xff);
+    const RED: BltPixel = BltPixel::new(0xff, 0, 0);
+
+    fn canvas(width: usize, height: usize, framebuffer: &mut Vec<u32>) -> Canvas<'_> {
+        *framebuffer = vec![0; (width + 2) * height];
+        Canvas::with_output(
+            width,
+            height,
+            Output::FrameBuffer {
+                base: framebuffer.as_mut_ptr().cast(),
+                stride: width + 2,
+                encoding: Encoding::Rgb,
+                _lifetime: PhantomData,
+            },
+        )
+    }
+
+    /// Render the canvas as text, with `#` for white pixels, `o` for red
+    /// pixels and `.` for anything else.
+    fn render(canvas: &Canvas) -> Vec<String> {
+        canvas
+            .pixels()
+            .chunks(canvas.width)
+            .map(|row| {
+                row.iter()
+                    .map(|&p| match p {
+                        WHITE => '#',
+                        RED => 'o',
+                        _ => '.',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn region(x: usize, y: usize, width: usize, height: usize, fill: Option<BltPixel>) -> Region {
+        Region {
+            x,
+            y,
+            width,
+            height,
+            fill,
+        }
+    }
+
+    #[test]
+    fn test_rects() {
+        let mut fb = Vec::new();
+        let mut canvas = canvas(6, 4, &mut fb);
+        canvas.fill_rect((4, 2), (10, 10), WHITE);
+        canvas.draw_rect((0, 0), (4, 3), RED);
+        canvas.fill_rect((7, 0), (1, 1), WHITE);
+        assert_eq!(render(&canvas), ["oooo..", "o..o..", "oooo##", "....##"]);
+        assert_eq!(canvas.pixel(4, 3), Some(WHITE));
+        assert_eq!(canvas.pixel(6, 0), None);
+        assert_eq!(
+            canvas.dirty,
+            [
+                region(4, 2, 2, 2, Some(WHITE)),
+                region(0, 0, 4, 1, Some(RED)),
+                region(0, 2, 4, 1, Some(RED)),
+                region(0, 0, 1, 3, Some(RED)),
+                region(3, 0, 1, 3, Some(RED)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_line() {
+        let mut fb = Vec::new();
+        let mut canvas = canvas(6, 4, &mut fb);
+        canvas.draw_line((5, 3), (0, 0), WHITE);
+        assert_eq!(render(&canvas), ["#.....", ".##...", "...##.", ".....#"]);
+        canvas.draw_line((1, 3), (1, 1), RED);
+        assert_eq!(render(&canvas), ["#.....", ".o#...", ".o.##.", ".o...#"]);
+        // The second line is within the first one's region.
+        assert_eq!(canvas.dirty, [region(0, 0, 6, 4, None)]);
+    }
+
+    #[test]
+    fn test_circles() {
+        let mut fb = Vec::new();
+        let mut canvas = canvas(9, 9, &mut fb);
+        canvas.draw_circle((4, 4), 3, WHITE);
+        assert_eq!(
+            render(&canvas),
+            [
+                ".........",
+                "...###...",
+                "..#...#..",
+                ".#.....#.",
+                ".#.....#.",
+                ".#.....#.",
+                "..#...#..",
+                "...###...",
+                ".........",
+            ]
+        );
+        canvas.fill_circle((4, 4), 2, RED);
+        canvas.fill_circle((0, 8), 1, RED);
+        assert_eq!(
+            render(&canvas),
+            [
+                ".........",
+                "...###...",
+                "..#ooo#..",
+                ".#ooooo#.",
+                ".#ooooo#.",
+                ".#ooooo#.",
+                "..#ooo#..",
+                "o..###...",
+                "oo.......",
+            ]
+        );
+        assert_eq!(
+            canvas.dirty,
+            [region(1, 1, 7, 7, None), region(0, 7, 2, 2, None)]
+        );
+    }
+
+    #[test]
+    fn test_images() {
+        let mut fb = Vec::new();
+        let mut canvas = canvas(4, 3, &mut fb);
+        canvas.clear(RED);
+        let image = [WHITE, BLACK, BLACK, WHITE];
+        canvas.draw_image((3, 2), (2, 2), &image);
+        assert_eq!(render(&canvas), ["oooo", "oooo", "ooo#"]);
+
+        canvas.draw_image_with_alpha((0, 0), (2, 2), &image, &[255, 0, 128, 255]);
+        assert_eq!(canvas.pixel(0, 0), Some(WHITE));
+        assert_eq!(canvas.pixel(1, 0), Some(RED));
+        assert_eq!(canvas.pixel(0, 1), Some(BltPixel::new(127, 0, 0)));
+        assert_eq!(canvas.pixel(1, 1), Some(WHITE));
+    }
+
+    #[test]
+    fn test_dirty_regions() {
+        let mut fb = Vec::new();
+        let mut canvas = canvas(100, 100, &mut fb);
+        canvas.set_pixel(5, 5, WHITE);
+        canvas.fill_rect((0, 0), (10, 10), RED);
+        // The pixel is covered by the rectangle.
+        assert_eq!(canvas.dirty, [region(0, 0, 10, 10, Some(RED))]);
+        // A pixel in a filled region can't be skipped.
+        canvas.set_pixel(5, 5, WHITE);
+        assert_eq!(canvas.dirty.len(), 2);
+
+        // Too many regions are merged.
+        for i in 0..MAX_DIRTY_REGIONS {
+            canvas.set_pixel(20 + i, 50 + i, WHITE);
+        }
+        assert!(canvas.dirty.len() < MAX_DIRTY_REGIONS);
+        assert_eq!(canvas.dirty[0].fill, None);
+        assert!(canvas.dirty[0].contains(&region(0, 0, 21, 51, None)));
+
+        canvas.clear(BLACK);
+        assert_eq!(canvas.dirty, [region(0, 0, 100, 100, Some(BLACK))]);
+    }
+
+    #[test]
+    fn test_flush() {
+        let mut fb = Vec::new();
+        let mut canvas = canvas(3, 2, &mut fb);
+        canvas.fill_rect((0, 0), (3, 2), RED);
+        canvas.set_pixel(1, 1, WHITE);
+        canvas.flush().unwrap();
+        assert!(!canvas.is_dirty());
+        let red = Encoding::Rgb.encode(RED);
+        let white = Encoding::Rgb.encode(WHITE);
+        // Rows have two pixels of padding.
+        assert_eq!(fb, [red, red, red, 0, 0, red, white, red, 0, 0]);
+    }
+}
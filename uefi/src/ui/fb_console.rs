@@ -0,0 +1,552 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Text console drawn on a graphics framebuffer.
+//!
+//! [`FbConsole`] renders text with a built-in 8x8 bitmap font on a
+//! [`GraphicsOutput`] device. This is useful if the firmware does not
+//! provide a text console on the display, or after boot services have been
+//! exited, when the text output protocols can no longer be used.
+//!
+//! The console draws directly to the framebuffer if the current mode
+//! allows it, and uses [`GraphicsOutput::blt`] otherwise. Only the first
+//! kind of console can be used after exiting boot services; create it with
+//! [`FbConsole::from_raw_parts`] so it doesn't borrow the protocol.
+//!
+//! Text is written with [`core::fmt::Write`]. Line feeds, carriage
+//! returns, tabs and backspaces are handled, long lines are wrapped and
+//! the console scrolls when the last row is full. Characters outside of
+//! printable ASCII are drawn as `?`.
+//!
+//! With the `logger` feature, a console can receive the log messages of
+//! [`helpers`] by passing it to [`helpers::set_log_console`].
+//!
+//! ```no_run
+//! use core::fmt::Write;
+//! use uefi::boot;
+//! use uefi::proto::console::gop::{BltPixel, GraphicsOutput};
+//! use uefi::ui::fb_console::FbConsole;
+//!
+//! # fn main() -> uefi::Result {
+//! let handle = boot::get_handle_for_protocol::<GraphicsOutput>()?;
+//! let mut gop = boot::open_protocol_exclusive::<GraphicsOutput>(handle)?;
+//! let mut console = FbConsole::new(&mut gop).with_scale(2);
+//! console.set_colors(BltPixel::new(255, 255, 255), BltPixel::new(0, 0, 128));
+//! console.clear()?;
+//! writeln!(console, "Hello from the framebuffer!").unwrap();
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`helpers`]: crate::helpers
+//! [`helpers::set_log_console`]: crate::helpers::set_log_console
+
+use super::font::{FIRST_CHAR, GLYPHS, GLYPH_SIZE};
+use super::pixel::{self, blt, Encoding, PIXEL_SIZE};
+use crate::proto::console::gop::{
+    BltOp, BltPixel, BltRegion, GraphicsOutput, ModeInfo, PixelFormat,
+};
+use crate::Result;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ptr;
+
+/// Largest scale factor of the font.
+pub const MAX_SCALE: usize = 4;
+
+/// Width and height of a character cell at the largest scale.
+const MAX_CELL_SIZE: usize = GLYPH_SIZE * MAX_SCALE;
+
+/// Number of columns a tab moves to.
+const TAB_WIDTH: usize = 8;
+
+/// Text console on a [`GraphicsOutput`] device. See the [module
+/// documentation].
+///
+/// [module documentation]: self
+#[derive(Debug)]
+pub struct FbConsole<'a> {
+    target: Target<'a>,
+    layout: Layout,
+    scale: usize,
+    cursor: (usize, usize),
+    foreground: BltPixel,
+    background: BltPixel,
+}
+
+/// Where pixels are drawn.
+#[derive(Debug)]
+enum Target<'a> {
+    FrameBuffer {
+        base: *mut u8,
+        _lifetime: PhantomData<&'a mut u8>,
+    },
+    Blt(&'a mut GraphicsOutput),
+}
+
+/// Size and pixel format of the screen.
+#[derive(Debug, Copy, Clone)]
+struct Layout {
+    width: usize,
+    height: usize,
+    /// Pixels per row of the framebuffer.
+    stride: usize,
+    encoding: Encoding,
+}
+
+impl<'a> FbConsole<'a> {
+    /// Create a console on the current mode of `gop`.
+    ///
+    /// The console draws directly to the framebuffer, unless the mode is
+    /// [`PixelFormat::BltOnly`]. The scale of the font is chosen so that
+    /// there are at least 80 columns, or is 1 on smaller screens. The
+    /// screen is not cleared.
+    #[must_use]
+    pub fn new(gop: &'a mut GraphicsOutput) -> Self {
+        let info = gop.current_mode_info();
+        let mut layout = Layout::new(&info);
+        let target = if info.pixel_format() == PixelFormat::BltOnly {
+            Target::Blt(gop)
+        } else {
+            let mut fb = gop.frame_buffer();
+            layout.fit_to(fb.size());
+            Target::FrameBuffer {
+                base: fb.as_mut_ptr(),
+                _lifetime: PhantomData,
+            }
+        };
+        Self::with_layout(target, layout)
+    }
+
+    /// Create a console drawing directly to the framebuffer at `base`,
+    /// which is `size` bytes long and has the format described by `info`.
+    ///
+    /// Unlike [`new`], this does not borrow the [`GraphicsOutput`]
+    /// protocol, so the console can be used after exiting boot services.
+    /// The base, size and mode information must be retrieved with
+    /// [`GraphicsOutput::frame_buffer`] and
+    /// [`GraphicsOutput::current_mode_info`] before then. Rows of pixels
+    /// that don't fit in `size` bytes are not used.
+    ///
+    /// # Safety
+    ///
+    /// `base` must point to `size` bytes of framebuffer memory that stays
+    /// valid, and keeps the format described by `info`, for as long as the
+    /// console is used.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pixel format of `info` is [`PixelFormat::BltOnly`].
+    ///
+    /// [`new`]: Self::new
+    #[must_use]
+    pub unsafe fn from_raw_parts(base: *mut u8, size: usize, info: &ModeInfo) -> Self {
+        assert!(
+            info.pixel_format() != PixelFormat::BltOnly,
+            "Cannot access the framebuffer in a Blt-only mode"
+        );
+        let mut layout = Layout::new(info);
+        layout.fit_to(size);
+        let target = Target::FrameBuffer {
+            base,
+            _lifetime: PhantomData,
+        };
+        Self::with_layout(target, layout)
+    }
+
+    fn with_layout(target: Target<'a>, layout: Layout) -> Self {
+        let scale = (layout.width / (80 * GLYPH_SIZE)).clamp(1, MAX_SCALE);
+        Self {
+            target,
+            layout,
+            scale,
+            cursor: (0, 0),
+            foreground: BltPixel::new(0xaa, 0xaa, 0xaa),
+            background: BltPixel::new(0, 0, 0),
+        }
+    }
+
+    /// Draw characters at `scale` times the size of the font, which is 8
+    /// by 8 pixels. The scale is clamped to `1..=MAX_SCALE`.
+    ///
+    /// This changes the number of rows and columns; the cursor is moved
+    /// to the top left of the screen.
+    #[must_use]
+    pub fn with_scale(mut self, scale: usize) -> Self {
+        self.scale = scale.clamp(1, MAX_SCALE);
+        self.cursor = (0, 0);
+        self
+    }
+
+    /// Whether the console draws through [`GraphicsOutput::blt`], which
+    /// cannot be used after exiting boot services.
+    #[cfg_attr(not(feature = "logger"), allow(dead_code))]
+    pub(crate) const fn uses_blt(&self) -> bool {
+        matches!(self.target, Target::Blt(_))
+    }
+
+    /// Number of columns and rows of text.
+    #[must_use]
+    pub const fn size(&self) -> (usize, usize) {
+        let cell = self.cell_size();
+        (self.layout.width / cell, self.layout.height / cell)
+    }
+
+    /// Column and row of the cursor.
+    #[must_use]
+    pub const fn cursor_position(&self) -> (usize, usize) {
+        self.cursor
+    }
+
+    /// Move the cursor. Positions outside of the screen are clamped to the
+    /// last column or row.
+    pub fn set_cursor_position(&mut self, column: usize, row: usize) {
+        let (columns, rows) = self.size();
+        self.cursor = (
+            column.min(columns.saturating_sub(1)),
+            row.min(rows.saturating_sub(1)),
+        );
+    }
+
+    /// Set the colors of text written afterwards and of the background
+    /// uncovered by [`clear`] and scrolling.
+    ///
+    /// [`clear`]: Self::clear
+    pub const fn set_colors(&mut self, foreground: BltPixel, background: BltPixel) {
+        self.foreground = foreground;
+        self.background = background;
+    }
+
+    /// Fill the screen with the background color and move the cursor to
+    /// the top left.
+    ///
+    /// # Errors
+    ///
+    /// Errors from [`GraphicsOutput::blt`] are returned. Blitting fails
+    /// with [`Status::UNSUPPORTED`] after boot services have been exited.
+    ///
+    /// [`Status::UNSUPPORTED`]: crate::Status::UNSUPPORTED
+    pub fn clear(&mut self) -> Result {
+        self.cursor = (0, 0);
+        self.fill(0, 0, self.layout.width, self.layout.height, self.background)
+    }
+
+    /// Write `text` at the cursor.
+    ///
+    /// # Errors
+    ///
+    /// Errors from [`GraphicsOutput::blt`] are returned. Blitting fails
+    /// with [`Status::UNSUPPORTED`] after boot services have been exited.
+    ///
+    /// [`Status::UNSUPPORTED`]: crate::Status::UNSUPPORTED
+    pub fn print(&mut self, text: &str) -> Result {
+        let (columns, rows) = self.size();
+        if columns == 0 || rows == 0 {
+            return Ok(());
+        }
+        for c in text.chars() {
+            match c {
+                '\n' => self.new_line()?,
+                '\r' => self.cursor.0 = 0,
+                '\t' => {
+                    // Tabs don't wrap to the next row.
+                    let next = (self.cursor.0 / TAB_WIDTH + 1) * TAB_WIDTH;
+                    while self.cursor.0 < next.min(columns) {
+                        self.put_char(' ')?;
+                    }
+                }
+                '\x08' => self.cursor.0 = self.cursor.0.saturating_sub(1),
+                c => self.put_char(c)?,
+            }
+        }
+        Ok(())
+    }
+
+    const fn cell_size(&self) -> usize {
+        GLYPH_SIZE * self.scale
+    }
+
+    /// Draw `c` at the cursor and advance it, wrapping at the end of the
+    /// row.
+    fn put_char(&mut self, c: char) -> Result {
+        if self.cursor.0 >= self.size().0 {
+            self.new_line()?;
+        }
+        let index = u32::from(c).wrapping_sub(u32::from(FIRST_CHAR)) as usize;
+        let glyph = GLYPHS
+            .get(index)
+            .unwrap_or(&GLYPHS[usize::from(b'?' - b' ')]);
+        self.draw_glyph(glyph)?;
+        self.cursor.0 += 1;
+        Ok(())
+    }
+
+    fn new_line(&mut self) -> Result {
+        self.cursor.0 = 0;
+        if self.cursor.1 + 1 < self.size().1 {
+            self.cursor.1 += 1;
+            Ok(())
+        } else {
+            self.scroll()
+        }
+    }
+
+    fn draw_glyph(&mut self, glyph: &[u8; GLYPH_SIZE]) -> Result {
+        let cell = self.cell_size();
+        let scale = self.scale;
+        let (x, y) = (self.cursor.0 * cell, self.cursor.1 * cell);
+        let is_set = |px: usize, py: usize| glyph[py / scale] & (1 << (px / scale)) != 0;
+
+        match &mut self.target {
+            Target::FrameBuffer { base, .. } => {
+                let foreground = self.layout.encoding.encode(self.foreground);
+                let background = self.layout.encoding.encode(self.background);
+                for py in 0..cell {
+                    for px in 0..cell {
+                        let value = if is_set(px, py) {
+                            foreground
+                        } else {
+                            background
+                        };
+                        unsafe { self.layout.write(*base, x + px, y + py, value) };
+                    }
+                }
+                Ok(())
+            }
+            Target::Blt(gop) => {
+                let mut buffer = [self.background; MAX_CELL_SIZE * MAX_CELL_SIZE];
+                for py in 0..cell {
+                    for px in 0..cell {
+                        if is_set(px, py) {
+                            buffer[py * cell + px] = self.foreground;
+                        }
+                    }
+                }
+                blt(
+                    gop,
+                    BltOp::BufferToVideo {
+                        buffer: &buffer[..cell * cell],
+                        src: BltRegion::Full,
+                        dest: (x, y),
+                        dims: (cell, cell),
+                    },
+                )
+            }
+        }
+    }
+
+    /// Move all rows of text up by one and clear the last row.
+    fn scroll(&mut self) -> Result {
+        let cell = self.cell_size();
+        let (columns, rows) = self.size();
+        let (width, height) = (columns * cell, rows * cell);
+        match &mut self.target {
+            Target::FrameBuffer { base, .. } => {
+                let base = *base;
+                let row_bytes = self.layout.stride * PIXEL_SIZE;
+                unsafe {
+                    ptr::copy(
+                        base.add(cell * row_bytes),
+                        base,
+                        (height - cell) * row_bytes,
+                    )
+                };
+            }
+            Target::Blt(gop) => blt(
+                gop,
+                BltOp::VideoToVideo {
+                    src: (0, cell),
+                    dest: (0, 0),
+                    dims: (width, height - cell),
+                },
+            )?,
+        }
+        self.fill(0, height - cell, width, cell, self.background)
+    }
+
+    fn fill(&mut self, x: usize, y: usize, width: usize, height: usize, color: BltPixel) -> Result {
+        match &mut self.target {
+            Target::FrameBuffer { base, .. } => {
+                let value = self.layout.encoding.encode(color);
+                for py in y..y + height {
+                    for px in x..x + width {
+                        unsafe { self.layout.write(*base, px, py, value) };
+                    }
+                }
+                Ok(())
+            }
+            Target::Blt(gop) => blt(
+                gop,
+                BltOp::VideoFill {
+                    color,
+                    dest: (x, y),
+                    dims: (width, height),
+                },
+            ),
+        }
+    }
+}
+
+impl fmt::Write for FbConsole<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.print(s).map_err(|_| fmt::Error)
+    }
+}
+
+impl Layout {
+    fn new(info: &ModeInfo) -> Self {
+        let (width, height) = info.resolution();
+        Self {
+            width,
+            height,
+            stride: info.stride(),
+            encoding: Encoding::new(info),
+        }
+    }
+
+    /// Limit the height to the rows that fit in `size` bytes.
+    fn fit_to(&mut self, size: usize) {
+        let row_bytes = self.stride * PIXEL_SIZE;
+        if let Some(rows) = size.checked_div(row_bytes) {
+            self.height = self.height.min(rows);
+        }
+        self.width = self.width.min(self.stride);
+    }
+
+    /// Write a pixel to the framebuffer.
+    ///
+    /// # Safety
+    ///
+    /// `base` must point to a framebuffer with this layout, and the pixel
+    /// must be on the screen.
+    unsafe fn write(&self, base: *mut u8, x: usize, y: usize, value: u32) {
+        debug_assert!(x < self.width && y < self.height);
+        unsafe { pixel::write(base, self.stride, x, y, value) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::fmt::Write;
+
+    const WHITE: BltPixel = BltPixel::new(0xff, 0xff, 0xff);
+    const BLUE: BltPixel = BltPixel::new(0, 0, 0xff);
+
+    /// A console on an in-memory framebuffer.
+    fn console(
+        columns: usize,
+        rows: usize,
+        encoding: Encoding,
+        buffer: &mut Vec<u32>,
+    ) -> FbConsole<'_> {
+        let (width, height) = (columns * GLYPH_SIZE, rows * GLYPH_SIZE);
+        // Leave some padding at the end of each row.
+        let stride = width + 3;
+        *buffer = vec![0; stride * height];
+        let target = Target::FrameBuffer {
+            base: buffer.as_mut_ptr().cast(),
+            _lifetime: PhantomData,
+        };
+        let layout = Layout {
+            width,
+            height,
+            stride,
+            encoding,
+        };
+        let mut console = FbConsole::with_layout(target, layout);
+        console.set_colors(WHITE, BLUE);
+        console
+    }
+
+    /// Render the text of the framebuffer by comparing each cell with the
+    /// glyphs.
+    fn screen(buffer: &[u32], columns: usize, rows: usize) -> Vec<String> {
+        let stride = columns * GLYPH_SIZE + 3;
+        let foreground = Encoding::Bgr.encode(WHITE);
+        (0..rows)
+            .map(|row| {
+                (0..columns)
+                    .map(|column| {
+                        let mut glyph = [0; GLYPH_SIZE];
+                        for (y, bits) in glyph.iter_mut().enumerate() {
+                            for x in 0..GLYPH_SIZE {
+                                let offset =
+                                    (row * GLYPH_SIZE + y) * stride + column * GLYPH_SIZE + x;
+                                if buffer[offset] == foreground {
+                                    *bits |= 1 << x;
+                                }
+                            }
+                        }
+                        let index = GLYPHS.iter().position(|g| *g == glyph).unwrap();
+                        char::from(b' ' + index as u8)
+                    })
+                    .collect::<String>()
+                    .trim_end()
+                    .into()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_print() {
+        let mut buffer = Vec::new();
+        let mut console = console(10, 3, Encoding::Bgr, &mut buffer);
+        assert_eq!(console.size(), (10, 3));
+        console.clear().unwrap();
+        write!(console, "Hi\tx\r!\n{}\u{e9}\x08|", 42).unwrap();
+        assert_eq!(console.cursor_position(), (3, 1));
+        assert_eq!(screen(&buffer, 10, 3), ["!i      x", "42|", ""]);
+        // The padding at the end of the rows is not touched.
+        assert_eq!(buffer[10 * GLYPH_SIZE], 0);
+        assert_eq!(buffer[1], Encoding::Bgr.encode(BLUE));
+    }
+
+    #[test]
+    fn test_wrap_and_scroll() {
+        let mut buffer = Vec::new();
+        let mut console = console(4, 2, Encoding::Bgr, &mut buffer);
+        console.clear().unwrap();
+        console.print("abcd").unwrap();
+        // The line is wrapped when the next character is written.
+        assert_eq!(console.cursor_position(), (4, 0));
+        console.print("\nefghij").unwrap();
+        assert_eq!(console.cursor_position(), (2, 1));
+        assert_eq!(screen(&buffer, 4, 2), ["efgh", "ij"]);
+    }
+
+    #[test]
+    fn test_scale_and_cursor() {
+        let mut buffer = Vec::new();
+        let mut console = console(8, 4, Encoding::Rgb, &mut buffer).with_scale(2);
+        assert_eq!(console.size(), (4, 2));
+        console.set_cursor_position(10, 10);
+        assert_eq!(console.cursor_position(), (3, 1));
+        console.print("|").unwrap();
+
+        // Each pixel of the glyph covers 2x2 pixels of the screen.
+        let stride = 8 * GLYPH_SIZE + 3;
+        let cell = 2 * GLYPH_SIZE;
+        let at = |x, y| buffer[(cell + y) * stride + 3 * cell + x];
+        let foreground = Encoding::Rgb.encode(WHITE);
+        for (x, y) in [(6, 0), (7, 0), (6, 1), (7, 1), (8, 0)] {
+            assert_eq!(at(x, y), foreground);
+        }
+        assert_ne!(at(5, 0), foreground);
+        assert_ne!(at(10, 0), foreground);
+    }
+
+    #[test]
+    fn test_fit_to() {
+        let mut layout = Layout {
+            width: 640,
+            height: 480,
+            stride: 640,
+            encoding: Encoding::Rgb,
+        };
+        layout.fit_to(640 * 4 * 100 + 10);
+        assert_eq!(layout.height, 100);
+    }
+}
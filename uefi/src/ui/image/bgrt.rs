@@ -0,0 +1,333 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Access to the firmware's boot logo through the ACPI Boot Graphics Resource
+//! Table (BGRT).
+//!
+//! The firmware shows a logo while booting, and describes it in the BGRT so
+//! that later boot stages can keep it on the screen. An application that
+//! clears the screen can draw the logo again at the same position:
+//!
+//! ```no_run
+//! use uefi::boot;
+//! use uefi::proto::console::gop::{BltOp, BltRegion, GraphicsOutput};
+//! use uefi::ui::image::bgrt::Bgrt;
+//!
+//! # fn main() -> uefi::Result {
+//! let handle = boot::get_handle_for_protocol::<GraphicsOutput>()?;
+//! let mut gop = boot::open_protocol_exclusive::<GraphicsOutput>(handle)?;
+//!
+//! if let Some(bgrt) = Bgrt::find() {
+//!     // SAFETY: the table was provided by the firmware, and boot services
+//!     // are active, so the image has not been freed.
+//!     if let Ok(logo) = unsafe { bgrt.image() } {
+//!         gop.blt(BltOp::BufferToVideo {
+//!             buffer: logo.pixels(),
+//!             src: BltRegion::Full,
+//!             dest: bgrt.position(),
+//!             dims: logo.size(),
+//!         })?;
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{Image, ImageError};
+use crate::system;
+use crate::table::cfg::{ACPI2_GUID, ACPI_GUID};
+use core::{ptr, slice};
+
+/// Size of the header that all ACPI tables start with.
+const TABLE_HEADER_SIZE: usize = 36;
+
+/// The ACPI Boot Graphics Resource Table, which describes the logo shown by
+/// the firmware.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Bgrt {
+    version: u16,
+    status: u8,
+    image_type: u8,
+    image_address: u64,
+    offset_x: u32,
+    offset_y: u32,
+}
+
+impl Bgrt {
+    /// Signature of the table.
+    pub const SIGNATURE: [u8; 4] = *b"BGRT";
+
+    /// Size of the table in bytes.
+    pub const SIZE: usize = 56;
+
+    /// Find the BGRT through the ACPI tables in the system's configuration
+    /// table.
+    ///
+    /// Returns `None` if the firmware has no ACPI tables or no valid BGRT.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the system table has not been set.
+    #[must_use]
+    pub fn find() -> Option<Self> {
+        let rsdp = system::with_config_table(|entries| {
+            // Prefer the ACPI 2 RSDP, which can point to the XSDT.
+            [ACPI2_GUID, ACPI_GUID].iter().find_map(|guid| {
+                entries
+                    .iter()
+                    .find(|entry| entry.guid == *guid)
+                    .map(|entry| entry.address.cast::<u8>())
+            })
+        })?;
+        // SAFETY: the firmware's ACPI tables are valid and identity mapped.
+        unsafe { find_in_rsdp(rsdp) }
+    }
+
+    /// Parse a BGRT from the bytes of the table, including the header.
+    ///
+    /// Returns `None` if the signature, length or checksum is invalid.
+    #[must_use]
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        if data.len() < Self::SIZE || data[..4] != Self::SIGNATURE || checksum(data) != 0 {
+            return None;
+        }
+        let u32_at =
+            |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+        Some(Self {
+            version: u16::from_le_bytes([data[36], data[37]]),
+            status: data[38],
+            image_type: data[39],
+            image_address: u64::from_le_bytes(data[40..48].try_into().unwrap()),
+            offset_x: u32_at(48),
+            offset_y: u32_at(52),
+        })
+    }
+
+    /// Version of the table. Currently always 1.
+    #[must_use]
+    pub const fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Whether the logo is currently shown on the screen.
+    #[must_use]
+    pub const fn is_displayed(&self) -> bool {
+        self.status & 1 != 0
+    }
+
+    /// Clockwise rotation of the logo relative to the screen, in degrees: 0,
+    /// 90, 180 or 270.
+    #[must_use]
+    pub const fn orientation(&self) -> u16 {
+        ((self.status >> 1) & 0b11) as u16 * 90
+    }
+
+    /// Format of the image. The only format defined by the ACPI specification
+    /// is `0`, a BMP image.
+    #[must_use]
+    pub const fn image_type(&self) -> u8 {
+        self.image_type
+    }
+
+    /// Physical address of the image.
+    #[must_use]
+    pub const fn image_address(&self) -> u64 {
+        self.image_address
+    }
+
+    /// Position of the upper left corner of the logo on the screen, in
+    /// pixels.
+    #[must_use]
+    pub const fn position(&self) -> (usize, usize) {
+        (self.offset_x as usize, self.offset_y as usize)
+    }
+
+    /// Decode the logo.
+    ///
+    /// # Errors
+    ///
+    /// [`ImageError::Unsupported`] is returned if the image is not a BMP
+    /// image. See [`ImageError`] for the other errors.
+    ///
+    /// # Safety
+    ///
+    /// [`image_address`] must point to a readable BMP image. The firmware
+    /// usually allocates the image as boot services data, so this is not the
+    /// case after exiting boot services, or if the table did not come from
+    /// the firmware.
+    ///
+    /// [`image_address`]: Self::image_address
+    pub unsafe fn image(&self) -> Result<Image, ImageError> {
+        if self.image_type != 0 {
+            return Err(ImageError::Unsupported);
+        }
+        let base = self.image_address as usize as *const u8;
+        if base.is_null() {
+            return Err(ImageError::InvalidHeader);
+        }
+        // The file header starts with the signature and the size of the file.
+        let header = unsafe { slice::from_raw_parts(base, 6) };
+        if !header.starts_with(b"BM") {
+            return Err(ImageError::UnknownFormat);
+        }
+        let size = u32::from_le_bytes(header[2..6].try_into().unwrap()) as usize;
+        let data = unsafe { slice::from_raw_parts(base, size.max(header.len())) };
+        Image::decode_bmp(data)
+    }
+}
+
+/// Find the BGRT in the tables listed by the XSDT or RSDT that the RSDP at
+/// `rsdp` points to.
+///
+/// # Safety
+///
+/// `rsdp` must be null or point to a readable RSDP, which points to readable
+/// tables.
+unsafe fn find_in_rsdp(rsdp: *const u8) -> Option<Bgrt> {
+    if rsdp.is_null() {
+        return None;
+    }
+    // The ACPI 1 part of the RSDP.
+    let header = unsafe { slice::from_raw_parts(rsdp, 20) };
+    if header[..8] != *b"RSD PTR " || checksum(header) != 0 {
+        return None;
+    }
+    let revision = header[15];
+    let rsdt = u32::from_le_bytes(header[16..20].try_into().unwrap());
+    let xsdt = if revision >= 2 {
+        unsafe { ptr::read_unaligned(rsdp.add(24).cast::<u64>()) }
+    } else {
+        0
+    };
+
+    // Entries of the XSDT are 64-bit addresses, those of the RSDT 32-bit.
+    let (root, signature, entry_size) = if xsdt != 0 {
+        (xsdt, b"XSDT", 8)
+    } else {
+        (u64::from(rsdt), b"RSDT", 4)
+    };
+    let root = unsafe { table(root) }?;
+    if root[..4] != *signature {
+        return None;
+    }
+    root[TABLE_HEADER_SIZE..]
+        .chunks_exact(entry_size)
+        .map(|entry| {
+            let mut address = [0; 8];
+            address[..entry_size].copy_from_slice(entry);
+            u64::from_le_bytes(address)
+        })
+        .filter_map(|address| unsafe { table(address) })
+        .find(|table| table[..4] == Bgrt::SIGNATURE)
+        .and_then(Bgrt::from_bytes)
+}
+
+/// Get the bytes of the ACPI table at `address`, if its checksum is valid.
+///
+/// # Safety
+///
+/// `address` must be zero or point to a readable ACPI table.
+unsafe fn table<'a>(address: u64) -> Option<&'a [u8]> {
+    let base = address as usize as *const u8;
+    if base.is_null() {
+        return None;
+    }
+    let header = unsafe { slice::from_raw_parts(base, TABLE_HEADER_SIZE) };
+    let length = u32::from_le_bytes(header[4..8].try_into().unwrap()) as usize;
+    if length < TABLE_HEADER_SIZE {
+        return None;
+    }
+    let table = unsafe { slice::from_raw_parts(base, length) };
+    (checksum(table) == 0).then_some(table)
+}
+
+/// Sum of the bytes of an ACPI structure, which is zero if it is valid.
+fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0, |sum, &byte| sum.wrapping_add(byte))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::console::gop::BltPixel;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    /// Build an ACPI table with a valid checksum.
+    fn build_table(signature: &[u8; 4], contents: &[u8]) -> Vec<u8> {
+        let mut table = vec![0; TABLE_HEADER_SIZE];
+        table[..4].copy_from_slice(signature);
+        let length = (TABLE_HEADER_SIZE + contents.len()) as u32;
+        table[4..8].copy_from_slice(&length.to_le_bytes());
+        table.extend_from_slice(contents);
+        table[9] = 0_u8.wrapping_sub(checksum(&table));
+        table
+    }
+
+    fn build_bgrt(status: u8, image_address: u64) -> Vec<u8> {
+        let mut contents = vec![1, 0, status, 0];
+        contents.extend_from_slice(&image_address.to_le_bytes());
+        contents.extend_from_slice(&100_u32.to_le_bytes());
+        contents.extend_from_slice(&200_u32.to_le_bytes());
+        build_table(b"BGRT", &contents)
+    }
+
+    /// A 1x1 BMP with a red pixel.
+    fn build_bmp() -> Vec<u8> {
+        let mut bmp = b"BM".to_vec();
+        bmp.extend_from_slice(&58_u32.to_le_bytes());
+        bmp.extend_from_slice(&[0, 0, 0, 0, 54, 0, 0, 0, 40, 0, 0, 0]);
+        bmp.extend_from_slice(&[1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 24, 0]);
+        bmp.resize(54, 0);
+        bmp.extend_from_slice(&[0, 0, 255, 0]);
+        bmp
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        let table = build_bgrt(0b101, 0x1234);
+        let bgrt = Bgrt::from_bytes(&table).unwrap();
+        assert_eq!(bgrt.version(), 1);
+        assert!(bgrt.is_displayed());
+        assert_eq!(bgrt.orientation(), 180);
+        assert_eq!(bgrt.image_type(), 0);
+        assert_eq!(bgrt.image_address(), 0x1234);
+        assert_eq!(bgrt.position(), (100, 200));
+
+        let mut corrupt = table.clone();
+        corrupt[40] ^= 1;
+        assert_eq!(Bgrt::from_bytes(&corrupt), None);
+        assert_eq!(Bgrt::from_bytes(&table[..40]), None);
+        assert_eq!(Bgrt::from_bytes(&build_table(b"FACP", &[0; 20])), None);
+    }
+
+    #[test]
+    fn test_find_and_decode() {
+        let bmp = build_bmp();
+        let bgrt = build_bgrt(1, bmp.as_ptr() as u64);
+        let other = build_table(b"APIC", &[0; 8]);
+        let mut entries = vec![];
+        for table in [&other, &bgrt] {
+            entries.extend_from_slice(&(table.as_ptr() as u64).to_le_bytes());
+        }
+        let xsdt = build_table(b"XSDT", &entries);
+
+        let mut rsdp = b"RSD PTR ".to_vec();
+        rsdp.resize(15, 0);
+        rsdp.push(2);
+        rsdp.resize(20, 0);
+        rsdp[8] = 0_u8.wrapping_sub(checksum(&rsdp));
+        rsdp.extend_from_slice(&36_u32.to_le_bytes());
+        rsdp.extend_from_slice(&(xsdt.as_ptr() as u64).to_le_bytes());
+        rsdp.resize(36, 0);
+
+        let found = unsafe { find_in_rsdp(rsdp.as_ptr()) }.unwrap();
+        assert_eq!(found, Bgrt::from_bytes(&bgrt).unwrap());
+        let image = unsafe { found.image() }.unwrap();
+        assert_eq!(image.pixels(), [BltPixel::new(255, 0, 0)]);
+
+        // An invalid RSDP checksum.
+        rsdp[8] ^= 1;
+        assert_eq!(unsafe { find_in_rsdp(rsdp.as_ptr()) }, None);
+        assert_eq!(unsafe { find_in_rsdp(ptr::null()) }, None);
+    }
+}
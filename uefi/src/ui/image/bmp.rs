@@ -0,0 +1,499 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! BMP decoder.
+
+use super::{check_size, Image, ImageError};
+use crate::proto::console::gop::BltPixel;
+use alloc::vec::Vec;
+
+pub(super) const SIGNATURE: &[u8] = b"BM";
+
+/// Size of the file header, before the DIB header.
+const FILE_HEADER_SIZE: usize = 14;
+
+/// Size of `BITMAPCOREHEADER`, used by OS/2 and early Windows versions.
+const CORE_HEADER_SIZE: usize = 12;
+/// Size of `BITMAPINFOHEADER`. Later headers extend it.
+const INFO_HEADER_SIZE: usize = 40;
+
+const BI_RGB: u32 = 0;
+const BI_RLE8: u32 = 1;
+const BI_RLE4: u32 = 2;
+const BI_BITFIELDS: u32 = 3;
+const BI_ALPHABITFIELDS: u32 = 6;
+
+/// Fields of the DIB header that are needed for decoding.
+struct Header {
+    width: usize,
+    height: usize,
+    top_down: bool,
+    bit_count: u16,
+    compression: u32,
+    colors_used: usize,
+}
+
+/// A color channel in a 16 or 32-bit pixel.
+#[derive(Clone, Copy)]
+struct Bitfield {
+    mask: u32,
+    shift: u32,
+    max: u32,
+}
+
+impl Bitfield {
+    const fn new(mask: u32) -> Self {
+        let shift = if mask == 0 { 0 } else { mask.trailing_zeros() };
+        Self {
+            mask,
+            shift,
+            max: mask >> shift,
+        }
+    }
+
+    /// Extract the channel from `pixel`, scaled to 8 bits.
+    fn extract(self, pixel: u32) -> u8 {
+        if self.max == 0 {
+            return 0;
+        }
+        let value = u64::from((pixel & self.mask) >> self.shift);
+        (value * 255 / u64::from(self.max)) as u8
+    }
+}
+
+/// Decode a BMP image.
+pub(super) fn decode(data: &[u8]) -> Result<Image, ImageError> {
+    if !data.starts_with(SIGNATURE) {
+        return Err(ImageError::UnknownFormat);
+    }
+    let pixel_offset = read_u32(data, 10)? as usize;
+    let header_size = read_u32(data, FILE_HEADER_SIZE)? as usize;
+    let header = data
+        .get(FILE_HEADER_SIZE..FILE_HEADER_SIZE + header_size)
+        .ok_or(ImageError::Truncated)?;
+    let header = parse_header(header)?;
+    let pixel_count = check_size(header.width, header.height)?;
+
+    // Masks of the red, green, blue and alpha channels.
+    let mut after_header = FILE_HEADER_SIZE + header_size;
+    let masks = match (header.compression, header.bit_count) {
+        (BI_RGB, 1 | 4 | 8 | 24) | (BI_RLE8, 8) | (BI_RLE4, 4) => [0; 4],
+        (BI_RGB, 16) => [0x7c00, 0x03e0, 0x001f, 0],
+        (BI_RGB, 32) => [0x00ff_0000, 0x0000_ff00, 0x0000_00ff, 0],
+        (BI_BITFIELDS | BI_ALPHABITFIELDS, 16 | 32) => {
+            let count = if header.compression == BI_ALPHABITFIELDS || header_size >= 56 {
+                4
+            } else {
+                3
+            };
+            // The masks are part of newer headers, and follow the info
+            // header otherwise.
+            let start = if header_size == INFO_HEADER_SIZE {
+                after_header += count * 4;
+                after_header - count * 4
+            } else {
+                FILE_HEADER_SIZE + INFO_HEADER_SIZE
+            };
+            let mut masks = [0; 4];
+            for (i, mask) in masks.iter_mut().enumerate().take(count) {
+                *mask = read_u32(data, start + i * 4)?;
+            }
+            masks
+        }
+        (BI_RGB | BI_RLE8 | BI_RLE4 | BI_BITFIELDS | BI_ALPHABITFIELDS, _) => {
+            return Err(ImageError::InvalidHeader)
+        }
+        _ => return Err(ImageError::Unsupported),
+    };
+    let [red, green, blue, alpha] = masks.map(Bitfield::new);
+
+    let palette = if header.bit_count <= 8 {
+        let entry_size = if header_size == CORE_HEADER_SIZE {
+            3
+        } else {
+            4
+        };
+        let count = match header.colors_used {
+            0 => 1 << header.bit_count,
+            n => n.min(256),
+        };
+        let palette = data
+            .get(after_header..after_header + count * entry_size)
+            .ok_or(ImageError::Truncated)?;
+        palette
+            .chunks_exact(entry_size)
+            .map(|entry| BltPixel::new(entry[2], entry[1], entry[0]))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let lookup = |index: usize| palette.get(index).copied().ok_or(ImageError::InvalidData);
+
+    let data = data.get(pixel_offset..).ok_or(ImageError::Truncated)?;
+    let mut pixels = Vec::with_capacity(pixel_count);
+    let mut alpha_values = Vec::new();
+    if matches!(header.compression, BI_RLE8 | BI_RLE4) {
+        let indices = decode_rle(
+            data,
+            header.width,
+            header.height,
+            header.compression == BI_RLE4,
+        )?;
+        for index in indices {
+            pixels.push(lookup(usize::from(index))?);
+        }
+    } else {
+        let bits = usize::from(header.bit_count);
+        let row_len = (header.width * bits).div_ceil(8);
+        // Rows are padded to a multiple of four bytes.
+        let stride = (header.width * bits).div_ceil(32) * 4;
+        for y in 0..header.height {
+            let source_row = if header.top_down {
+                y
+            } else {
+                header.height - 1 - y
+            };
+            let start = source_row * stride;
+            let row = data
+                .get(start..start + row_len)
+                .ok_or(ImageError::Truncated)?;
+            for x in 0..header.width {
+                let pixel = match header.bit_count {
+                    1 | 4 | 8 => {
+                        let bit = x * bits;
+                        let shift = 8 - bits - bit % 8;
+                        let index = (row[bit / 8] >> shift) & ((1 << bits) - 1) as u8;
+                        lookup(usize::from(index))?
+                    }
+                    24 => BltPixel::new(row[x * 3 + 2], row[x * 3 + 1], row[x * 3]),
+                    _ => {
+                        let value = if bits == 16 {
+                            u32::from(u16::from_le_bytes([row[x * 2], row[x * 2 + 1]]))
+                        } else {
+                            u32::from_le_bytes(row[x * 4..x * 4 + 4].try_into().unwrap())
+                        };
+                        if alpha.max != 0 {
+                            alpha_values.push(alpha.extract(value));
+                        }
+                        BltPixel::new(
+                            red.extract(value),
+                            green.extract(value),
+                            blue.extract(value),
+                        )
+                    }
+                };
+                pixels.push(pixel);
+            }
+        }
+    }
+
+    // Some programs write an alpha mask without setting the alpha values,
+    // so an image that is fully transparent is treated as opaque.
+    let alpha =
+        (!alpha_values.is_empty() && alpha_values.iter().any(|&a| a != 0)).then_some(alpha_values);
+    Ok(Image {
+        width: header.width,
+        height: header.height,
+        pixels,
+        alpha,
+    })
+}
+
+fn parse_header(header: &[u8]) -> Result<Header, ImageError> {
+    let read_u16 = |offset: usize| u16::from_le_bytes([header[offset], header[offset + 1]]);
+    if header.len() == CORE_HEADER_SIZE {
+        let planes = read_u16(8);
+        if planes != 1 {
+            return Err(ImageError::InvalidHeader);
+        }
+        return Ok(Header {
+            width: usize::from(read_u16(4)),
+            height: usize::from(read_u16(6)),
+            top_down: false,
+            bit_count: read_u16(10),
+            compression: BI_RGB,
+            colors_used: 0,
+        });
+    }
+    if header.len() < INFO_HEADER_SIZE {
+        return Err(ImageError::InvalidHeader);
+    }
+    let width = read_u32(header, 4)? as i32;
+    let height = read_u32(header, 8)? as i32;
+    let planes = read_u16(12);
+    let compression = read_u32(header, 16)?;
+    if width < 0 || height == i32::MIN || planes != 1 {
+        return Err(ImageError::InvalidHeader);
+    }
+    // Compressed images are always stored bottom-up.
+    let top_down = height < 0;
+    if top_down && matches!(compression, BI_RLE8 | BI_RLE4) {
+        return Err(ImageError::InvalidHeader);
+    }
+    Ok(Header {
+        width: width as usize,
+        height: height.unsigned_abs() as usize,
+        top_down,
+        bit_count: read_u16(14),
+        compression,
+        colors_used: read_u32(header, 32)? as usize,
+    })
+}
+
+/// Decode RLE8 or RLE4 data into palette indices, with the top row first.
+/// Pixels that are skipped get index 0.
+fn decode_rle(
+    data: &[u8],
+    width: usize,
+    height: usize,
+    four_bits: bool,
+) -> Result<Vec<u8>, ImageError> {
+    let mut indices = alloc::vec![0; width * height];
+    // Position counted from the bottom left corner.
+    let (mut x, mut y) = (0, 0);
+    let mut put = |x: &mut usize, y: usize, index: u8| {
+        if *x < width && y < height {
+            indices[(height - 1 - y) * width + *x] = index;
+        }
+        *x += 1;
+    };
+    // Index of the `i`th pixel in a byte.
+    let nibble = |byte: u8, i: usize| match (four_bits, i % 2) {
+        (false, _) => byte,
+        (true, 0) => byte >> 4,
+        (true, _) => byte & 0x0f,
+    };
+
+    let mut bytes = data.iter().copied();
+    let mut next = || bytes.next().ok_or(ImageError::Truncated);
+    loop {
+        let count = usize::from(next()?);
+        let value = next()?;
+        if count > 0 {
+            // Encoded mode: repeat the pixels in `value`.
+            for i in 0..count {
+                put(&mut x, y, nibble(value, i));
+            }
+            continue;
+        }
+        match value {
+            // End of line.
+            0 => {
+                x = 0;
+                y += 1;
+            }
+            // End of bitmap.
+            1 => break,
+            // Delta.
+            2 => {
+                x += usize::from(next()?);
+                y += usize::from(next()?);
+            }
+            // Absolute mode: `value` pixels follow, padded to 16 bits.
+            _ => {
+                let count = usize::from(value);
+                let len = if four_bits { count.div_ceil(2) } else { count };
+                let mut byte = 0;
+                for i in 0..count {
+                    if !four_bits || i % 2 == 0 {
+                        byte = next()?;
+                    }
+                    put(&mut x, y, nibble(byte, i));
+                }
+                if len % 2 == 1 {
+                    next()?;
+                }
+            }
+        }
+    }
+    Ok(indices)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, ImageError> {
+    let bytes = data.get(offset..offset + 4).ok_or(ImageError::Truncated)?;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const BLACK: BltPixel = BltPixel::new(0, 0, 0);
+    const WHITE: BltPixel = BltPixel::new(255, 255, 255);
+    const RED: BltPixel = BltPixel::new(255, 0, 0);
+    const BLUE: BltPixel = BltPixel::new(0, 0, 255);
+
+    /// Build a `BITMAPINFOHEADER`.
+    fn info_header(width: i32, height: i32, bit_count: u16, compression: u32) -> Vec<u8> {
+        let mut header = vec![];
+        header.extend_from_slice(&40_u32.to_le_bytes());
+        header.extend_from_slice(&width.to_le_bytes());
+        header.extend_from_slice(&height.to_le_bytes());
+        header.extend_from_slice(&1_u16.to_le_bytes());
+        header.extend_from_slice(&bit_count.to_le_bytes());
+        header.extend_from_slice(&compression.to_le_bytes());
+        header.resize(40, 0);
+        header
+    }
+
+    /// Build a BMP file from the DIB header, what follows it (masks and
+    /// palette) and the pixel data.
+    fn build(header: &[u8], extra: &[u8], pixels: &[u8]) -> Vec<u8> {
+        let offset = (14 + header.len() + extra.len()) as u32;
+        let mut bmp = b"BM".to_vec();
+        bmp.extend_from_slice(&(offset + pixels.len() as u32).to_le_bytes());
+        bmp.extend_from_slice(&[0; 4]);
+        bmp.extend_from_slice(&offset.to_le_bytes());
+        bmp.extend_from_slice(header);
+        bmp.extend_from_slice(extra);
+        bmp.extend_from_slice(pixels);
+        bmp
+    }
+
+    /// Palette with black, white, red and blue.
+    const PALETTE: [u8; 16] = [0, 0, 0, 0, 255, 255, 255, 0, 0, 0, 255, 0, 255, 0, 0, 0];
+
+    #[test]
+    fn test_24_bit() {
+        // 2x2, bottom-up, with rows padded from 6 to 8 bytes.
+        let pixels = [
+            0, 0, 255, 255, 0, 0, 0, 0, //
+            255, 255, 255, 0, 0, 0, 0, 0,
+        ];
+        let image = decode(&build(&info_header(2, 2, 24, BI_RGB), &[], &pixels)).unwrap();
+        assert_eq!(image.size(), (2, 2));
+        assert_eq!(image.pixels(), [WHITE, BLACK, RED, BLUE]);
+        assert_eq!(image.alpha(), None);
+    }
+
+    #[test]
+    fn test_palette() {
+        // 1 bit, top-down.
+        let pixels = [0b1010_0000, 0, 0, 0, 0b0110_0000, 0, 0, 0];
+        let image = decode(&build(
+            &info_header(3, -2, 1, BI_RGB),
+            &PALETTE[..8],
+            &pixels,
+        ))
+        .unwrap();
+        assert_eq!(image.pixels(), [WHITE, BLACK, WHITE, BLACK, WHITE, WHITE]);
+
+        // 4 bits, using a palette of four colors.
+        let mut header = info_header(3, 1, 4, BI_RGB);
+        header[32] = 4;
+        let image = decode(&build(&header, &PALETTE, &[0x23, 0x10, 0, 0])).unwrap();
+        assert_eq!(image.pixels(), [RED, BLUE, WHITE]);
+
+        let image = decode(&build(&header, &PALETTE, &[0x24, 0x10, 0, 0]));
+        assert_eq!(image, Err(ImageError::InvalidData));
+
+        // 8 bits with a core header, which has three bytes per entry.
+        let mut header = vec![12, 0, 0, 0, 2, 0, 1, 0, 1, 0, 8, 0];
+        let mut palette = vec![0; 256 * 3];
+        palette[3..6].copy_from_slice(&[255, 0, 0]);
+        let image = decode(&build(&header, &palette, &[1, 0, 0, 0])).unwrap();
+        assert_eq!(image.pixels(), [BLUE, BLACK]);
+
+        header[8] = 2;
+        let image = decode(&build(&header, &palette, &[1, 0, 0, 0]));
+        assert_eq!(image, Err(ImageError::InvalidHeader));
+    }
+
+    #[test]
+    fn test_rle() {
+        // 4x3 RLE8: a run of two, absolute mode with three pixels and
+        // padding, end of line, delta to the last pixel of the top row.
+        let mut header = info_header(4, 3, 8, BI_RLE8);
+        header[32] = 4;
+        let data = [2, 2, 0, 3, 1, 3, 1, 0, 0, 0, 0, 2, 3, 1, 1, 1, 0, 1];
+        let image = decode(&build(&header, &PALETTE, &data)).unwrap();
+        #[rustfmt::skip]
+        assert_eq!(
+            image.pixels(),
+            [
+                BLACK, BLACK, BLACK, WHITE,
+                BLACK, BLACK, BLACK, BLACK,
+                RED, RED, WHITE, BLUE,
+            ]
+        );
+
+        // 3x1 RLE4, a run alternating between two colors.
+        let mut header = info_header(3, 1, 4, BI_RLE4);
+        header[32] = 4;
+        let image = decode(&build(&header, &PALETTE, &[3, 0x21, 0, 1])).unwrap();
+        assert_eq!(image.pixels(), [RED, WHITE, RED]);
+
+        // Missing end of bitmap.
+        let image = decode(&build(&header, &PALETTE, &[3, 0x21]));
+        assert_eq!(image, Err(ImageError::Truncated));
+    }
+
+    #[test]
+    fn test_bitfields() {
+        // 16 bits, 5-6-5, with the masks after the info header.
+        let masks = [0x00, 0xf8, 0, 0, 0xe0, 0x07, 0, 0, 0x1f, 0, 0, 0];
+        let pixels = [0x00, 0xf8, 0xe0, 0x07, 0x1f, 0x00, 0, 0];
+        let image = decode(&build(
+            &info_header(3, 1, 16, BI_BITFIELDS),
+            &masks,
+            &pixels,
+        ))
+        .unwrap();
+        assert_eq!(image.pixels(), [RED, BltPixel::new(0, 255, 0), BLUE]);
+
+        // 16 bits, default 5-5-5.
+        let image = decode(&build(
+            &info_header(1, 1, 16, BI_RGB),
+            &[],
+            &[0xff, 0x7f, 0, 0],
+        ))
+        .unwrap();
+        assert_eq!(image.pixels(), [WHITE]);
+    }
+
+    #[test]
+    fn test_alpha() {
+        // 32 bits with a V5 header, which includes the masks.
+        let mut header = info_header(2, 1, 32, BI_BITFIELDS);
+        header[0] = 124;
+        for mask in [0x00ff_0000_u32, 0x0000_ff00, 0x0000_00ff, 0xff00_0000] {
+            header.extend_from_slice(&mask.to_le_bytes());
+        }
+        header.resize(124, 0);
+        let pixels = [0, 0, 255, 128, 255, 0, 0, 0];
+        let image = decode(&build(&header, &[], &pixels)).unwrap();
+        assert_eq!(image.pixels(), [RED, BLUE]);
+        assert_eq!(image.alpha().unwrap(), [128, 0]);
+
+        // An alpha channel that is zero everywhere is ignored.
+        let pixels = [0, 0, 255, 0, 255, 0, 0, 0];
+        let image = decode(&build(&header, &[], &pixels)).unwrap();
+        assert_eq!(image.alpha(), None);
+
+        // Without bitfields, the fourth byte is unused.
+        let pixels = [0, 0, 255, 128];
+        let image = decode(&build(&info_header(1, 1, 32, BI_RGB), &[], &pixels)).unwrap();
+        assert_eq!(image.pixels(), [RED]);
+        assert_eq!(image.alpha(), None);
+    }
+
+    #[test]
+    fn test_errors() {
+        let bmp = build(&info_header(2, 2, 24, BI_RGB), &[], &[0; 16]);
+        assert_eq!(decode(&bmp[..20]), Err(ImageError::Truncated));
+        assert_eq!(decode(&bmp[..60]), Err(ImageError::Truncated));
+
+        // JPEG compression.
+        let bmp = build(&info_header(1, 1, 24, 4), &[], &[0; 4]);
+        assert_eq!(decode(&bmp), Err(ImageError::Unsupported));
+
+        let bmp = build(&info_header(1, 1, 7, BI_RGB), &[], &[0; 4]);
+        assert_eq!(decode(&bmp), Err(ImageError::InvalidHeader));
+
+        let bmp = build(&info_header(20000, 1, 24, BI_RGB), &[], &[]);
+        assert_eq!(decode(&bmp), Err(ImageError::TooLarge));
+
+        let bmp = build(&info_header(1, -1, 8, BI_RLE8), &PALETTE, &[0, 1]);
+        assert_eq!(decode(&bmp), Err(ImageError::InvalidHeader));
+    }
+}
@@ -0,0 +1,393 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Decompression of zlib streams (RFC 1950 and 1951).
+
+use super::ImageError;
+use alloc::vec::Vec;
+
+/// Longest Huffman code in bits.
+const MAX_BITS: usize = 15;
+
+/// Base lengths of length symbols 257 to 285.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+
+/// Extra bits of length symbols 257 to 285.
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+/// Base distances of distance symbols 0 to 29.
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+
+/// Extra bits of distance symbols 0 to 29.
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Order in which code length code lengths are stored.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Decompress a zlib stream, checking its Adler-32 checksum.
+///
+/// `size_hint` is the expected size of the output, used to allocate it.
+pub(super) fn zlib_decompress(data: &[u8], size_hint: usize) -> Result<Vec<u8>, ImageError> {
+    let [cmf, flg, ..] = *data else {
+        return Err(ImageError::Truncated);
+    };
+    let method = cmf & 0x0f;
+    let window_bits = (cmf >> 4) + 8;
+    let preset_dictionary = flg & 0x20 != 0;
+    if (u16::from(cmf) << 8 | u16::from(flg)) % 31 != 0
+        || method != 8
+        || window_bits > 15
+        || preset_dictionary
+    {
+        return Err(ImageError::InvalidData);
+    }
+
+    let mut out = Vec::with_capacity(size_hint);
+    let mut bits = BitReader::new(&data[2..]);
+    inflate(&mut bits, &mut out)?;
+
+    let checksum = bits.aligned_bytes(4)?;
+    let checksum = u32::from_be_bytes(checksum.try_into().unwrap());
+    if adler32(&out) != checksum {
+        return Err(ImageError::ChecksumMismatch);
+    }
+    Ok(out)
+}
+
+/// Decompress a raw DEFLATE stream, appending to `out`.
+fn inflate(bits: &mut BitReader, out: &mut Vec<u8>) -> Result<(), ImageError> {
+    loop {
+        let last = bits.bits(1)? == 1;
+        match bits.bits(2)? {
+            0 => stored_block(bits, out)?,
+            1 => {
+                let (literals, distances) = fixed_codes();
+                compressed_block(bits, out, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_codes(bits)?;
+                compressed_block(bits, out, &literals, &distances)?;
+            }
+            _ => return Err(ImageError::InvalidData),
+        }
+        if last {
+            return Ok(());
+        }
+    }
+}
+
+fn stored_block(bits: &mut BitReader, out: &mut Vec<u8>) -> Result<(), ImageError> {
+    let header = bits.aligned_bytes(4)?;
+    let len = u16::from_le_bytes([header[0], header[1]]);
+    let nlen = u16::from_le_bytes([header[2], header[3]]);
+    if len != !nlen {
+        return Err(ImageError::InvalidData);
+    }
+    out.extend_from_slice(bits.aligned_bytes(usize::from(len))?);
+    Ok(())
+}
+
+fn compressed_block(
+    bits: &mut BitReader,
+    out: &mut Vec<u8>,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), ImageError> {
+    loop {
+        let symbol = usize::from(literals.decode(bits)?);
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            257..=285 => {
+                let index = symbol - 257;
+                let len =
+                    usize::from(LENGTH_BASE[index]) + bits.bits(LENGTH_EXTRA[index])? as usize;
+
+                let index = usize::from(distances.decode(bits)?);
+                if index >= DISTANCE_BASE.len() {
+                    return Err(ImageError::InvalidData);
+                }
+                let distance =
+                    usize::from(DISTANCE_BASE[index]) + bits.bits(DISTANCE_EXTRA[index])? as usize;
+                if distance > out.len() {
+                    return Err(ImageError::InvalidData);
+                }
+
+                // The source and destination may overlap, which repeats
+                // the last `distance` bytes.
+                let start = out.len() - distance;
+                out.reserve(len);
+                for i in 0..len {
+                    out.push(out[start + i]);
+                }
+            }
+            _ => return Err(ImageError::InvalidData),
+        }
+    }
+}
+
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [0; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    // The fixed codes are complete, so building them can't fail.
+    let literals = Huffman::new(&lengths).unwrap();
+    let distances = Huffman::new(&[5; 30]).unwrap();
+    (literals, distances)
+}
+
+fn dynamic_codes(bits: &mut BitReader) -> Result<(Huffman, Huffman), ImageError> {
+    let literal_count = bits.bits(5)? as usize + 257;
+    let distance_count = bits.bits(5)? as usize + 1;
+    let code_length_count = bits.bits(4)? as usize + 4;
+    if literal_count > 286 || distance_count > 30 {
+        return Err(ImageError::InvalidData);
+    }
+
+    let mut code_lengths = [0; 19];
+    for &index in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[index] = bits.bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths)?;
+
+    // The literal and distance code lengths are a single sequence, and
+    // repeats may cross from one to the other.
+    let mut lengths = [0; 286 + 30];
+    let total = literal_count + distance_count;
+    let mut i = 0;
+    while i < total {
+        let symbol = code_lengths.decode(bits)?;
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths[..i].last().ok_or(ImageError::InvalidData)?;
+                (previous, 3 + bits.bits(2)? as usize)
+            }
+            17 => (0, 3 + bits.bits(3)? as usize),
+            _ => (0, 11 + bits.bits(7)? as usize),
+        };
+        if i + repeat > total {
+            return Err(ImageError::InvalidData);
+        }
+        lengths[i..i + repeat].fill(value);
+        i += repeat;
+    }
+    if lengths[256] == 0 {
+        // The end of block symbol must have a code.
+        return Err(ImageError::InvalidData);
+    }
+
+    let literals = Huffman::new(&lengths[..literal_count])?;
+    let distances = Huffman::new(&lengths[literal_count..total])?;
+    Ok((literals, distances))
+}
+
+/// A canonical Huffman code.
+struct Huffman {
+    /// Number of codes of each length.
+    counts: [u16; MAX_BITS + 1],
+    /// Symbols ordered by code.
+    symbols: [u16; 288],
+}
+
+impl Huffman {
+    /// Build the code from the code length of each symbol, where 0 means
+    /// the symbol is not used.
+    ///
+    /// Incomplete codes are accepted, since encoders produce them for
+    /// distance codes with a single symbol; decoding a missing code fails.
+    fn new(lengths: &[u8]) -> Result<Self, ImageError> {
+        let mut counts = [0; MAX_BITS + 1];
+        for &len in lengths {
+            counts[usize::from(len)] += 1;
+        }
+        counts[0] = 0;
+
+        // Check that no length has more codes than are available.
+        let mut left: i32 = 1;
+        for &count in &counts[1..] {
+            left = (left << 1) - i32::from(count);
+            if left < 0 {
+                return Err(ImageError::InvalidData);
+            }
+        }
+
+        let mut offsets = [0; MAX_BITS + 1];
+        for len in 1..MAX_BITS {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = [0; 288];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                let offset = &mut offsets[usize::from(len)];
+                symbols[usize::from(*offset)] = symbol as u16;
+                *offset += 1;
+            }
+        }
+        Ok(Self { counts, symbols })
+    }
+
+    fn decode(&self, bits: &mut BitReader) -> Result<u16, ImageError> {
+        // Codes of each length are consecutive, starting at `first`.
+        let mut code = 0;
+        let mut first = 0;
+        let mut index = 0;
+        for &count in &self.counts[1..] {
+            code |= bits.bits(1)? as i32;
+            let count = i32::from(count);
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(ImageError::InvalidData)
+    }
+}
+
+/// Reads bits from a byte slice, least significant bit first.
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    buffer: u32,
+    count: u8,
+}
+
+impl<'a> BitReader<'a> {
+    const fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            position: 0,
+            buffer: 0,
+            count: 0,
+        }
+    }
+
+    /// Read `n` bits, at most 16.
+    fn bits(&mut self, n: u8) -> Result<u32, ImageError> {
+        while self.count < n {
+            let byte = *self.data.get(self.position).ok_or(ImageError::Truncated)?;
+            self.position += 1;
+            self.buffer |= u32::from(byte) << self.count;
+            self.count += 8;
+        }
+        let value = self.buffer & ((1 << n) - 1);
+        self.buffer >>= n;
+        self.count -= n;
+        Ok(value)
+    }
+
+    /// Skip to the next byte boundary and read `len` bytes.
+    fn aligned_bytes(&mut self, len: usize) -> Result<&'a [u8], ImageError> {
+        // Whole bytes in the buffer have not been used yet.
+        self.position -= usize::from(self.count / 8);
+        self.buffer = 0;
+        self.count = 0;
+        let bytes = self
+            .data
+            .get(self.position..self.position + len)
+            .ok_or(ImageError::Truncated)?;
+        self.position += len;
+        Ok(bytes)
+    }
+}
+
+/// Calculate the Adler-32 checksum of `data`.
+pub(super) fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1, 0);
+    // Reduce before the sums can overflow.
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += u32::from(byte);
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    /// `b"hello hello hello"`, compressed with zlib level 0 and 1.
+    const STORED: [u8; 28] = [
+        0x78, 0x01, 0x01, 0x11, 0x00, 0xee, 0xff, 0x68, 0x65, 0x6c, 0x6c, 0x6f, 0x20, 0x68, 0x65,
+        0x6c, 0x6c, 0x6f, 0x20, 0x68, 0x65, 0x6c, 0x6c, 0x6f, 0x3a, 0x2e, 0x06, 0x7d,
+    ];
+    const FIXED: [u8; 16] = [
+        0x78, 0x01, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x90, 0x00, 0x3a, 0x2e, 0x06,
+        0x7d,
+    ];
+
+    /// 4096 bytes of `(i * i) % 7`, compressed with zlib level 9.
+    const DYNAMIC: [u8; 34] = [
+        0x78, 0xda, 0xed, 0xc5, 0xb1, 0x0d, 0x00, 0x00, 0x08, 0x03, 0xa0, 0xda, 0xf8, 0xff, 0xcd,
+        0xfe, 0x61, 0x60, 0x21, 0xb3, 0xed, 0x4e, 0x24, 0x49, 0x92, 0x24, 0x49, 0xd2, 0xdf, 0x0e,
+        0x1e, 0xff, 0x1f, 0xff,
+    ];
+
+    #[test]
+    fn test_stored_and_fixed() {
+        let expected = b"hello hello hello";
+        assert_eq!(zlib_decompress(&STORED, 0).unwrap(), expected);
+        assert_eq!(zlib_decompress(&FIXED, 0).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_dynamic() {
+        let expected: Vec<u8> = (0..4096u32).map(|i| ((i * i) % 7) as u8).collect();
+        assert_eq!(zlib_decompress(&DYNAMIC, 4096).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(zlib_decompress(&[0x78], 0), Err(ImageError::Truncated));
+        // Invalid header check.
+        assert_eq!(
+            zlib_decompress(&[0x78, 0x02, 0x03, 0x00], 0),
+            Err(ImageError::InvalidData)
+        );
+        // Truncated data.
+        assert_eq!(zlib_decompress(&FIXED[..10], 0), Err(ImageError::Truncated));
+        // Wrong checksum.
+        let mut data = STORED;
+        data[27] ^= 1;
+        assert_eq!(zlib_decompress(&data, 0), Err(ImageError::ChecksumMismatch));
+        // Distance too far back.
+        let mut out = vec![];
+        let mut bits = BitReader::new(&[0x03, 0x02, 0x00]);
+        assert!(inflate(&mut bits, &mut out).is_err());
+    }
+
+    #[test]
+    fn test_adler32() {
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        let data = vec![0xff; 100_000];
+        let (mut a, mut b) = (1u64, 0u64);
+        for &byte in &data {
+            a = (a + u64::from(byte)) % 65521;
+            b = (b + a) % 65521;
+        }
+        assert_eq!(adler32(&data), ((b << 16) | a) as u32);
+    }
+}
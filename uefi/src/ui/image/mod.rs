@@ -0,0 +1,390 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Decoding of BMP and PNG images, for example to show a logo.
+//!
+//! [`Image::decode`] detects the format of the data and decodes it into
+//! [`BltPixel`]s, which can be drawn with [`GraphicsOutput::blt`] or a
+//! [`Canvas`]. Images can be scaled, for example to fit the current
+//! graphics mode with [`Image::scale_to_mode`].
+//!
+//! Supported formats:
+//! - BMP: 1, 4, 8, 16, 24 and 32 bits per pixel, uncompressed, with bit
+//!   fields or compressed with RLE4 or RLE8. The alpha channel of 32-bit
+//!   images is used if the header has an alpha mask.
+//! - PNG: all color types and bit depths, interlaced or not, with
+//!   transparency from the alpha channel or a `tRNS` chunk. Ancillary
+//!   chunks such as gamma and color profiles are ignored.
+//!
+//! The firmware's boot logo can be read from the ACPI BGRT table with
+//! [`bgrt::Bgrt`].
+//!
+//! ```no_run
+//! use uefi::boot;
+//! use uefi::proto::console::gop::GraphicsOutput;
+//! use uefi::ui::canvas::Canvas;
+//! use uefi::ui::image::Image;
+//!
+//! # fn main() -> uefi::Result {
+//! # let data = &[];
+//! let handle = boot::get_handle_for_protocol::<GraphicsOutput>()?;
+//! let mut gop = boot::open_protocol_exclusive::<GraphicsOutput>(handle)?;
+//! let logo = Image::decode(data).expect("invalid logo");
+//!
+//! let mut canvas = Canvas::new(&mut gop);
+//! let (width, height) = canvas.size();
+//! let x = width.saturating_sub(logo.width()) / 2;
+//! let y = height.saturating_sub(logo.height()) / 2;
+//! match logo.alpha() {
+//!     Some(alpha) => canvas.draw_image_with_alpha((x, y), logo.size(), logo.pixels(), alpha),
+//!     None => canvas.draw_image((x, y), logo.size(), logo.pixels()),
+//! }
+//! canvas.flush()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`BltPixel`]: crate::proto::console::gop::BltPixel
+//! [`GraphicsOutput::blt`]: crate::proto::console::gop::GraphicsOutput::blt
+//! [`Canvas`]: super::canvas::Canvas
+
+pub mod bgrt;
+
+mod bmp;
+mod inflate;
+mod png;
+
+use crate::proto::console::gop::{BltPixel, ModeInfo};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+/// Largest width or height of a decoded image.
+const MAX_DIMENSION: usize = 1 << 14;
+
+/// A decoded image.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<BltPixel>,
+    alpha: Option<Vec<u8>>,
+}
+
+impl Image {
+    /// Create an image of `width` by `height` pixels, stored row by row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of pixels does not match the size.
+    #[must_use]
+    pub fn new(width: usize, height: usize, pixels: Vec<BltPixel>) -> Self {
+        assert_eq!(pixels.len(), width * height, "wrong number of pixels");
+        Self {
+            width,
+            height,
+            pixels,
+            alpha: None,
+        }
+    }
+
+    /// Add the opacity of each pixel, from 0 for transparent to 255 for
+    /// opaque.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of values does not match the size.
+    #[must_use]
+    pub fn with_alpha(mut self, alpha: Vec<u8>) -> Self {
+        assert_eq!(
+            alpha.len(),
+            self.pixels.len(),
+            "wrong number of alpha values"
+        );
+        self.alpha = Some(alpha);
+        self
+    }
+
+    /// Decode a BMP or PNG image.
+    ///
+    /// # Errors
+    ///
+    /// [`ImageError::UnknownFormat`] is returned if `data` is neither a BMP
+    /// nor a PNG image. See [`ImageError`] for the other errors.
+    pub fn decode(data: &[u8]) -> Result<Self, ImageError> {
+        if data.starts_with(png::SIGNATURE) {
+            png::decode(data)
+        } else if data.starts_with(bmp::SIGNATURE) {
+            bmp::decode(data)
+        } else {
+            Err(ImageError::UnknownFormat)
+        }
+    }
+
+    /// Decode a BMP image.
+    ///
+    /// # Errors
+    ///
+    /// See [`ImageError`].
+    pub fn decode_bmp(data: &[u8]) -> Result<Self, ImageError> {
+        bmp::decode(data)
+    }
+
+    /// Decode a PNG image.
+    ///
+    /// # Errors
+    ///
+    /// See [`ImageError`].
+    pub fn decode_png(data: &[u8]) -> Result<Self, ImageError> {
+        png::decode(data)
+    }
+
+    /// Width of the image in pixels.
+    #[must_use]
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Height of the image in pixels.
+    #[must_use]
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Width and height of the image in pixels.
+    #[must_use]
+    pub const fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// The pixels of the image, row by row.
+    #[must_use]
+    pub fn pixels(&self) -> &[BltPixel] {
+        &self.pixels
+    }
+
+    /// The opacity of each pixel, if the image has transparency.
+    #[must_use]
+    pub fn alpha(&self) -> Option<&[u8]> {
+        self.alpha.as_deref()
+    }
+
+    /// Get the pixels of the image, row by row.
+    #[must_use]
+    pub fn into_pixels(self) -> Vec<BltPixel> {
+        self.pixels
+    }
+
+    /// Resize the image to `width` by `height` pixels, with bilinear
+    /// interpolation.
+    #[must_use]
+    pub fn scale(&self, width: usize, height: usize) -> Self {
+        if (width, height) == self.size() {
+            return self.clone();
+        }
+        if self.pixels.is_empty() || width == 0 || height == 0 {
+            return Self {
+                width,
+                height,
+                pixels: alloc::vec![BltPixel::new(0, 0, 0); width * height],
+                alpha: self.alpha.as_ref().map(|_| alloc::vec![0; width * height]),
+            };
+        }
+
+        let columns: Vec<_> = (0..width)
+            .map(|x| Sample::new(x, width, self.width))
+            .collect();
+        let mut pixels = Vec::with_capacity(width * height);
+        let mut alpha = self
+            .alpha
+            .as_ref()
+            .map(|_| Vec::with_capacity(width * height));
+        for y in 0..height {
+            let row = Sample::new(y, height, self.height);
+            for column in &columns {
+                let corners = [
+                    row.first * self.width + column.first,
+                    row.first * self.width + column.second,
+                    row.second * self.width + column.first,
+                    row.second * self.width + column.second,
+                ];
+                let mix = |channel: &dyn Fn(usize) -> u8| {
+                    let [a, b, c, d] = corners.map(|i| u32::from(channel(i)));
+                    let top = column.mix(a, b);
+                    let bottom = column.mix(c, d);
+                    row.mix(top, bottom) as u8
+                };
+                pixels.push(BltPixel::new(
+                    mix(&|i| self.pixels[i].red),
+                    mix(&|i| self.pixels[i].green),
+                    mix(&|i| self.pixels[i].blue),
+                ));
+                if let (Some(alpha), Some(source)) = (&mut alpha, &self.alpha) {
+                    alpha.push(mix(&|i| source[i]));
+                }
+            }
+        }
+        Self {
+            width,
+            height,
+            pixels,
+            alpha,
+        }
+    }
+
+    /// Resize the image to the largest size that fits in `width` by
+    /// `height` pixels, keeping its aspect ratio.
+    #[must_use]
+    pub fn scale_to_fit(&self, width: usize, height: usize) -> Self {
+        if self.width == 0 || self.height == 0 {
+            return self.clone();
+        }
+        // Compare width / self.width with height / self.height.
+        let (width, height) = if width * self.height <= height * self.width {
+            (width, (self.height * width / self.width).max(1))
+        } else {
+            ((self.width * height / self.height).max(1), height)
+        };
+        self.scale(width, height)
+    }
+
+    /// Resize the image to the largest size that fits on the screen in the
+    /// graphics mode described by `info`, keeping its aspect ratio.
+    #[must_use]
+    pub fn scale_to_mode(&self, info: &ModeInfo) -> Self {
+        let (width, height) = info.resolution();
+        self.scale_to_fit(width, height)
+    }
+}
+
+/// Position of a destination pixel in the source image, for bilinear
+/// interpolation.
+struct Sample {
+    /// Source pixel before the position.
+    first: usize,
+    /// Source pixel after the position.
+    second: usize,
+    /// Weight of `second`, out of 256.
+    weight: u32,
+}
+
+impl Sample {
+    fn new(dest: usize, dest_len: usize, source_len: usize) -> Self {
+        // Map the center of the destination pixel to the source, in units
+        // of 1/256 pixel.
+        let center = ((2 * dest + 1) * source_len * 256 / (2 * dest_len)).saturating_sub(128);
+        let first = (center / 256).min(source_len - 1);
+        Self {
+            first,
+            second: (first + 1).min(source_len - 1),
+            weight: (center % 256) as u32,
+        }
+    }
+
+    const fn mix(&self, a: u32, b: u32) -> u32 {
+        (a * (256 - self.weight) + b * self.weight + 128) / 256
+    }
+}
+
+/// Errors that can occur when decoding an image.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ImageError {
+    /// The data is not in a supported image format.
+    UnknownFormat,
+
+    /// The data ends before the end of the image.
+    Truncated,
+
+    /// A header has invalid values.
+    InvalidHeader,
+
+    /// The image uses a feature that is not supported, such as an unknown
+    /// compression method or bit depth.
+    Unsupported,
+
+    /// The compressed or filtered image data is invalid.
+    InvalidData,
+
+    /// A checksum of the image data does not match.
+    ChecksumMismatch,
+
+    /// The image is larger than 16384 pixels in either dimension.
+    TooLarge,
+}
+
+impl Display for ImageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownFormat => f.write_str("unknown image format"),
+            Self::Truncated => f.write_str("image data truncated"),
+            Self::InvalidHeader => f.write_str("invalid image header"),
+            Self::Unsupported => f.write_str("unsupported image feature"),
+            Self::InvalidData => f.write_str("invalid image data"),
+            Self::ChecksumMismatch => f.write_str("image checksum mismatch"),
+            Self::TooLarge => f.write_str("image too large"),
+        }
+    }
+}
+
+impl core::error::Error for ImageError {}
+
+/// Check the dimensions of an image, returning the number of pixels.
+const fn check_size(width: usize, height: usize) -> Result<usize, ImageError> {
+    if width == 0 || height == 0 {
+        Err(ImageError::InvalidHeader)
+    } else if width > MAX_DIMENSION || height > MAX_DIMENSION {
+        Err(ImageError::TooLarge)
+    } else {
+        Ok(width * height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const BLACK: BltPixel = BltPixel::new(0, 0, 0);
+    const WHITE: BltPixel = BltPixel::new(255, 255, 255);
+
+    #[test]
+    fn test_decode_unknown() {
+        assert_eq!(Image::decode(b"GIF89a"), Err(ImageError::UnknownFormat));
+        assert_eq!(Image::decode(b""), Err(ImageError::UnknownFormat));
+    }
+
+    #[test]
+    fn test_scale() {
+        let image = Image::new(2, 1, vec![BLACK, WHITE]).with_alpha(vec![0, 255]);
+
+        let wide = image.scale(4, 2);
+        assert_eq!(wide.size(), (4, 2));
+        let reds: Vec<u8> = wide.pixels().iter().map(|p| p.red).collect();
+        assert_eq!(reds, [0, 64, 191, 255, 0, 64, 191, 255]);
+        assert_eq!(wide.alpha().unwrap(), [0, 64, 191, 255, 0, 64, 191, 255]);
+
+        let small = image.scale(1, 1);
+        assert_eq!(small.pixels(), [BltPixel::new(128, 128, 128)]);
+
+        assert_eq!(image.scale(2, 1), image);
+        assert_eq!(image.scale(0, 3).pixels(), []);
+    }
+
+    #[test]
+    fn test_scale_to_fit() {
+        let image = Image::new(4, 2, vec![WHITE; 8]);
+        assert_eq!(image.scale_to_fit(100, 100).size(), (100, 50));
+        assert_eq!(image.scale_to_fit(10, 2).size(), (4, 2));
+        assert_eq!(image.scale_to_fit(1, 1).size(), (1, 1));
+        assert!(image
+            .scale_to_fit(1000, 3)
+            .pixels()
+            .iter()
+            .all(|&p| p == WHITE));
+    }
+
+    #[test]
+    fn test_check_size() {
+        assert_eq!(check_size(3, 2), Ok(6));
+        assert_eq!(check_size(0, 2), Err(ImageError::InvalidHeader));
+        assert_eq!(check_size(1 << 15, 1), Err(ImageError::TooLarge));
+    }
+}
@@ -0,0 +1,601 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! PNG decoder.
+
+use super::{check_size, inflate, Image, ImageError};
+use crate::proto::console::gop::BltPixel;
+use crate::util::crc32;
+use alloc::vec::Vec;
+
+pub(super) const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Start, in pixels, and spacing of the columns and rows of the seven passes
+/// of Adam7 interlacing.
+const ADAM7: [(usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+/// Contents of the `IHDR` chunk.
+struct Header {
+    width: usize,
+    height: usize,
+    bit_depth: u8,
+    color_type: ColorType,
+    interlaced: bool,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum ColorType {
+    Gray,
+    Rgb,
+    Indexed,
+    GrayAlpha,
+    Rgba,
+}
+
+impl ColorType {
+    const fn channels(self) -> usize {
+        match self {
+            Self::Gray | Self::Indexed => 1,
+            Self::GrayAlpha => 2,
+            Self::Rgb => 3,
+            Self::Rgba => 4,
+        }
+    }
+}
+
+/// Transparency from the `tRNS` chunk.
+enum Transparency {
+    None,
+    /// Alpha value of each palette entry.
+    Palette(Vec<u8>),
+    /// Gray or RGB value of the transparent color.
+    Color([u16; 3]),
+}
+
+impl Header {
+    fn parse(data: &[u8]) -> Result<Self, ImageError> {
+        let [w0, w1, w2, w3, h0, h1, h2, h3, bit_depth, color_type, compression, filter, interlace] =
+            *data
+        else {
+            return Err(ImageError::InvalidHeader);
+        };
+        let width = u32::from_be_bytes([w0, w1, w2, w3]) as usize;
+        let height = u32::from_be_bytes([h0, h1, h2, h3]) as usize;
+        let color_type = match color_type {
+            0 => ColorType::Gray,
+            2 => ColorType::Rgb,
+            3 => ColorType::Indexed,
+            4 => ColorType::GrayAlpha,
+            6 => ColorType::Rgba,
+            _ => return Err(ImageError::InvalidHeader),
+        };
+        let valid_depth = match color_type {
+            ColorType::Gray => matches!(bit_depth, 1 | 2 | 4 | 8 | 16),
+            ColorType::Indexed => matches!(bit_depth, 1 | 2 | 4 | 8),
+            _ => matches!(bit_depth, 8 | 16),
+        };
+        if !valid_depth || interlace > 1 {
+            return Err(ImageError::InvalidHeader);
+        }
+        if compression != 0 || filter != 0 {
+            return Err(ImageError::Unsupported);
+        }
+        check_size(width, height)?;
+        Ok(Self {
+            width,
+            height,
+            bit_depth,
+            color_type,
+            interlaced: interlace == 1,
+        })
+    }
+
+    /// Number of bytes in a row of `width` pixels, without the filter type.
+    const fn row_bytes(&self, width: usize) -> usize {
+        (width * self.color_type.channels() * self.bit_depth as usize).div_ceil(8)
+    }
+
+    /// Distance in bytes to the corresponding byte of the previous pixel, as
+    /// used by the filters.
+    fn pixel_bytes(&self) -> usize {
+        (self.color_type.channels() * usize::from(self.bit_depth) / 8).max(1)
+    }
+
+    /// Columns and rows of each pass of the image, with the size of the pass.
+    fn passes(&self) -> impl Iterator<Item = ((usize, usize, usize, usize), (usize, usize))> + '_ {
+        let passes: &[_] = if self.interlaced {
+            &ADAM7
+        } else {
+            &[(0, 0, 1, 1)]
+        };
+        passes.iter().map(|&(x, y, dx, dy)| {
+            let width = self.width.saturating_sub(x).div_ceil(dx);
+            let height = self.height.saturating_sub(y).div_ceil(dy);
+            ((x, y, dx, dy), (width, height))
+        })
+    }
+}
+
+/// Decode a PNG image.
+pub(super) fn decode(data: &[u8]) -> Result<Image, ImageError> {
+    let mut data = data
+        .strip_prefix(SIGNATURE)
+        .ok_or(ImageError::UnknownFormat)?;
+
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut transparency = Transparency::None;
+    let mut compressed = Vec::new();
+    loop {
+        let (Chunk { kind, contents }, rest) = next_chunk(data)?;
+        data = rest;
+        if kind != *b"IHDR" && header.is_none() {
+            return Err(ImageError::InvalidHeader);
+        }
+        match &kind {
+            b"IHDR" if header.is_none() => header = Some(Header::parse(contents)?),
+            b"PLTE" => {
+                if contents.is_empty() || contents.len() % 3 != 0 || contents.len() > 256 * 3 {
+                    return Err(ImageError::InvalidHeader);
+                }
+                palette = contents;
+            }
+            b"tRNS" => transparency = parse_transparency(header.as_ref().unwrap(), contents)?,
+            b"IDAT" => compressed.extend_from_slice(contents),
+            b"IEND" => break,
+            // Ancillary chunks, marked by a lowercase first letter, can be
+            // ignored.
+            _ if kind[0].is_ascii_lowercase() => {}
+            _ => return Err(ImageError::Unsupported),
+        }
+    }
+    let header = header.unwrap();
+    if header.color_type == ColorType::Indexed && palette.is_empty() {
+        return Err(ImageError::InvalidHeader);
+    }
+
+    let size: usize = header
+        .passes()
+        .filter(|(_, (width, _))| *width > 0)
+        .map(|(_, (width, height))| (header.row_bytes(width) + 1) * height)
+        .sum();
+    let mut filtered = inflate::zlib_decompress(&compressed, size)?;
+    if filtered.len() < size {
+        return Err(ImageError::Truncated);
+    }
+
+    let pixel_count = header.width * header.height;
+    let has_alpha = matches!(header.color_type, ColorType::GrayAlpha | ColorType::Rgba)
+        || !matches!(transparency, Transparency::None);
+    let mut pixels = alloc::vec![BltPixel::new(0, 0, 0); pixel_count];
+    let mut alpha = has_alpha.then(|| alloc::vec![255; pixel_count]);
+
+    let mut offset = 0;
+    for ((x0, y0, dx, dy), (width, height)) in header.passes() {
+        if width == 0 || height == 0 {
+            continue;
+        }
+        let row_bytes = header.row_bytes(width);
+        let pass = &mut filtered[offset..offset + (row_bytes + 1) * height];
+        offset += pass.len();
+        unfilter(pass, row_bytes, header.pixel_bytes())?;
+
+        for (row_index, row) in pass.chunks_exact(row_bytes + 1).enumerate() {
+            let row = &row[1..];
+            let y = y0 + row_index * dy;
+            for column in 0..width {
+                let index = y * header.width + x0 + column * dx;
+                let (pixel, opacity) = read_pixel(&header, row, column, palette, &transparency)?;
+                pixels[index] = pixel;
+                if let Some(alpha) = &mut alpha {
+                    alpha[index] = opacity;
+                }
+            }
+        }
+    }
+
+    Ok(Image {
+        width: header.width,
+        height: header.height,
+        pixels,
+        alpha,
+    })
+}
+
+/// A chunk of a PNG file.
+struct Chunk<'a> {
+    kind: [u8; 4],
+    contents: &'a [u8],
+}
+
+/// Split the next chunk off `data`, checking its CRC. Returns the chunk and
+/// the rest of the data.
+fn next_chunk(data: &[u8]) -> Result<(Chunk<'_>, &[u8]), ImageError> {
+    let length = data.get(..4).ok_or(ImageError::Truncated)?;
+    let length = u32::from_be_bytes(length.try_into().unwrap()) as usize;
+    let end = length.checked_add(8).ok_or(ImageError::InvalidHeader)?;
+    // The CRC covers the type and the contents.
+    let checked = data.get(4..end).ok_or(ImageError::Truncated)?;
+    let crc = data.get(end..end + 4).ok_or(ImageError::Truncated)?;
+    if crc32(checked) != u32::from_be_bytes(crc.try_into().unwrap()) {
+        return Err(ImageError::ChecksumMismatch);
+    }
+    let chunk = Chunk {
+        kind: checked[..4].try_into().unwrap(),
+        contents: &checked[4..],
+    };
+    Ok((chunk, &data[end + 4..]))
+}
+
+fn parse_transparency(header: &Header, data: &[u8]) -> Result<Transparency, ImageError> {
+    let sample = |i: usize| -> Result<u16, ImageError> {
+        let bytes = data
+            .get(i * 2..i * 2 + 2)
+            .ok_or(ImageError::InvalidHeader)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    };
+    match header.color_type {
+        ColorType::Indexed => Ok(Transparency::Palette(data.to_vec())),
+        ColorType::Gray => Ok(Transparency::Color([sample(0)?; 3])),
+        ColorType::Rgb => Ok(Transparency::Color([sample(0)?, sample(1)?, sample(2)?])),
+        ColorType::GrayAlpha | ColorType::Rgba => Err(ImageError::InvalidHeader),
+    }
+}
+
+/// Undo the filtering of the rows of an image or a pass, in place. Each row
+/// starts with its filter type.
+fn unfilter(data: &mut [u8], row_bytes: usize, pixel_bytes: usize) -> Result<(), ImageError> {
+    let stride = row_bytes + 1;
+    for start in (0..data.len()).step_by(stride) {
+        let (previous, current) = data.split_at_mut(start);
+        let previous = previous
+            .len()
+            .checked_sub(row_bytes)
+            .map(|i| &previous[i..]);
+        let (filter, row) = current[..stride].split_first_mut().unwrap();
+        // Bytes of the previous row and pixel before the image are zero.
+        let above = |i: usize| previous.map_or(0, |row| row[i]);
+        match *filter {
+            0 => {}
+            1 => {
+                for i in pixel_bytes..row_bytes {
+                    row[i] = row[i].wrapping_add(row[i - pixel_bytes]);
+                }
+            }
+            2 => {
+                for (i, byte) in row.iter_mut().enumerate() {
+                    *byte = byte.wrapping_add(above(i));
+                }
+            }
+            3 => {
+                for i in 0..row_bytes {
+                    let left = if i >= pixel_bytes {
+                        row[i - pixel_bytes]
+                    } else {
+                        0
+                    };
+                    let average = (u16::from(left) + u16::from(above(i))) / 2;
+                    row[i] = row[i].wrapping_add(average as u8);
+                }
+            }
+            4 => {
+                for i in 0..row_bytes {
+                    let (left, upper_left) = if i >= pixel_bytes {
+                        (row[i - pixel_bytes], above(i - pixel_bytes))
+                    } else {
+                        (0, 0)
+                    };
+                    row[i] = row[i].wrapping_add(paeth(left, above(i), upper_left));
+                }
+            }
+            _ => return Err(ImageError::InvalidData),
+        }
+        // Mark the row as unfiltered.
+        *filter = 0;
+    }
+    Ok(())
+}
+
+/// The Paeth predictor: whichever of the left, above and upper left bytes
+/// is closest to `left + above - upper_left`.
+fn paeth(left: u8, above: u8, upper_left: u8) -> u8 {
+    let (a, b, c) = (i16::from(left), i16::from(above), i16::from(upper_left));
+    let p = a + b - c;
+    let (pa, pb, pc) = ((p - a).abs(), (p - b).abs(), (p - c).abs());
+    if pa <= pb && pa <= pc {
+        left
+    } else if pb <= pc {
+        above
+    } else {
+        upper_left
+    }
+}
+
+/// Read the pixel in `column` of an unfiltered row, with its opacity.
+fn read_pixel(
+    header: &Header,
+    row: &[u8],
+    column: usize,
+    palette: &[u8],
+    transparency: &Transparency,
+) -> Result<(BltPixel, u8), ImageError> {
+    let channels = header.color_type.channels();
+    let depth = header.bit_depth;
+    let sample = |channel: usize| -> u16 {
+        let index = column * channels + channel;
+        match depth {
+            16 => u16::from_be_bytes([row[index * 2], row[index * 2 + 1]]),
+            8 => u16::from(row[index]),
+            _ => {
+                // Smaller samples are packed from the most significant bit.
+                let bit = index * usize::from(depth);
+                let shift = 8 - usize::from(depth) - bit % 8;
+                u16::from(row[bit / 8] >> shift) & ((1 << depth) - 1)
+            }
+        }
+    };
+    // Scale a sample to 8 bits.
+    let scale = |value: u16| -> u8 {
+        match depth {
+            16 => (value >> 8) as u8,
+            8 => value as u8,
+            _ => (u32::from(value) * 255 / ((1 << depth) - 1)) as u8,
+        }
+    };
+    let color_key = |values: [u16; 3]| match transparency {
+        Transparency::Color(key) if *key == values => 0,
+        _ => 255,
+    };
+
+    Ok(match header.color_type {
+        ColorType::Gray => {
+            let gray = sample(0);
+            let value = scale(gray);
+            (BltPixel::new(value, value, value), color_key([gray; 3]))
+        }
+        ColorType::Rgb => {
+            let rgb = [sample(0), sample(1), sample(2)];
+            let [r, g, b] = rgb.map(scale);
+            (BltPixel::new(r, g, b), color_key(rgb))
+        }
+        ColorType::Indexed => {
+            let index = usize::from(sample(0));
+            let rgb = palette
+                .get(index * 3..index * 3 + 3)
+                .ok_or(ImageError::InvalidData)?;
+            let opacity = match transparency {
+                Transparency::Palette(alpha) => alpha.get(index).copied().unwrap_or(255),
+                _ => 255,
+            };
+            (BltPixel::new(rgb[0], rgb[1], rgb[2]), opacity)
+        }
+        ColorType::GrayAlpha => {
+            let value = scale(sample(0));
+            (BltPixel::new(value, value, value), scale(sample(1)))
+        }
+        ColorType::Rgba => {
+            let [r, g, b, a] = [0, 1, 2, 3].map(|channel| scale(sample(channel)));
+            (BltPixel::new(r, g, b), a)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    /// 2x2 RGBA image written by zlib. The second row uses the Sub filter.
+    const RGBA: [u8; 77] = [
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44,
+        0x52, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x02, 0x08, 0x06, 0x00, 0x00, 0x00, 0x72,
+        0xb6, 0x0d, 0x24, 0x00, 0x00, 0x00, 0x14, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0xf8,
+        0xcf, 0xc0, 0xf0, 0x1f, 0x08, 0x1b, 0x18, 0xc1, 0x34, 0x10, 0x00, 0x00, 0x3f, 0xe0, 0x08,
+        0x7a, 0xf0, 0x91, 0xdb, 0x5d, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42,
+        0x60, 0x82,
+    ];
+
+    fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+        png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        let start = png.len();
+        png.extend_from_slice(kind);
+        png.extend_from_slice(data);
+        let crc = crc32(&png[start..]);
+        png.extend_from_slice(&crc.to_be_bytes());
+    }
+
+    /// Build a PNG from filtered rows, stored without compression.
+    fn build(
+        (width, height): (u32, u32),
+        bit_depth: u8,
+        color_type: u8,
+        interlace: u8,
+        extra: &[(&[u8; 4], &[u8])],
+        rows: &[u8],
+    ) -> Vec<u8> {
+        let mut png = SIGNATURE.to_vec();
+        let mut header = vec![];
+        header.extend_from_slice(&width.to_be_bytes());
+        header.extend_from_slice(&height.to_be_bytes());
+        header.extend_from_slice(&[bit_depth, color_type, 0, 0, interlace]);
+        chunk(&mut png, b"IHDR", &header);
+        for (kind, data) in extra {
+            chunk(&mut png, kind, data);
+        }
+
+        let mut zlib = vec![0x78, 0x01, 0x01];
+        let len = rows.len() as u16;
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(rows);
+        zlib.extend_from_slice(&inflate::adler32(rows).to_be_bytes());
+        // Split the data over two chunks.
+        let (first, second) = zlib.split_at(zlib.len() / 2);
+        chunk(&mut png, b"IDAT", first);
+        chunk(&mut png, b"IDAT", second);
+        chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    #[test]
+    fn test_compressed() {
+        let image = decode(&RGBA).unwrap();
+        assert_eq!(image.size(), (2, 2));
+        assert_eq!(
+            image.pixels(),
+            [
+                BltPixel::new(255, 0, 0),
+                BltPixel::new(0, 255, 0),
+                BltPixel::new(0, 0, 255),
+                BltPixel::new(255, 255, 254),
+            ]
+        );
+        assert_eq!(image.alpha().unwrap(), [255, 128, 0, 255]);
+    }
+
+    #[test]
+    fn test_filters() {
+        // Gray 3x5, with each filter type in turn. Every row unfilters to
+        // 10, 20, 30 plus 10 times the row index.
+        let rows = [
+            0,
+            10,
+            20,
+            30, //
+            1,
+            20,
+            10,
+            10, //
+            2,
+            10,
+            10,
+            10, //
+            3,
+            40 - 15,
+            50 - 40,
+            60 - 50, //
+            4,
+            50 - 40,
+            60 - 50,
+            70 - 60,
+        ];
+        let image = Image::decode(&build((3, 5), 8, 0, 0, &[], &rows)).unwrap();
+        let grays: Vec<u8> = image.pixels().iter().map(|p| p.red).collect();
+        assert_eq!(
+            grays,
+            [10, 20, 30, 20, 30, 40, 30, 40, 50, 40, 50, 60, 50, 60, 70]
+        );
+        assert_eq!(image.alpha(), None);
+
+        let mut invalid = rows;
+        invalid[4] = 5;
+        assert_eq!(
+            decode(&build((3, 5), 8, 0, 0, &[], &invalid)),
+            Err(ImageError::InvalidData)
+        );
+    }
+
+    #[test]
+    fn test_paeth() {
+        assert_eq!(paeth(10, 20, 10), 20);
+        assert_eq!(paeth(20, 10, 10), 20);
+        assert_eq!(paeth(10, 10, 20), 10);
+        assert_eq!(paeth(5, 30, 30), 5);
+        assert_eq!(paeth(5, 30, 20), 20);
+    }
+
+    #[test]
+    fn test_palette() {
+        // 1-bit palette, where the second entry is transparent.
+        let palette: &[u8] = &[0, 0, 0, 255, 128, 0];
+        let extra: &[(&[u8; 4], &[u8])] = &[(b"PLTE", palette), (b"tRNS", &[255, 0])];
+        let rows = [0, 0b1010_0000, 0, 0b0101_0000];
+        let image = decode(&build((4, 2), 1, 3, 0, extra, &rows)).unwrap();
+        let orange = BltPixel::new(255, 128, 0);
+        let black = BltPixel::new(0, 0, 0);
+        assert_eq!(
+            image.pixels(),
+            [orange, black, orange, black, black, orange, black, orange]
+        );
+        assert_eq!(image.alpha().unwrap(), [0, 255, 0, 255, 255, 0, 255, 0]);
+
+        assert_eq!(
+            decode(&build((4, 2), 1, 3, 0, &[], &rows)),
+            Err(ImageError::InvalidHeader)
+        );
+    }
+
+    #[test]
+    fn test_depths() {
+        // 2-bit gray.
+        let image = decode(&build((4, 1), 2, 0, 0, &[], &[0, 0b00_01_10_11])).unwrap();
+        let grays: Vec<u8> = image.pixels().iter().map(|p| p.red).collect();
+        assert_eq!(grays, [0, 85, 170, 255]);
+
+        // 16-bit RGB with a transparent color.
+        let key: &[u8] = &[0x12, 0x34, 0, 0, 0xff, 0xff];
+        let rows = [
+            0, 0x12, 0x34, 0, 0, 0xff, 0xff, 0x12, 0x35, 0, 0, 0xff, 0xff,
+        ];
+        let image = decode(&build((2, 1), 16, 2, 0, &[(b"tRNS", key)], &rows)).unwrap();
+        assert_eq!(image.pixels(), [BltPixel::new(0x12, 0, 0xff); 2]);
+        assert_eq!(image.alpha().unwrap(), [0, 255]);
+
+        // 8-bit gray with alpha.
+        let image = decode(&build((1, 1), 8, 4, 0, &[], &[0, 200, 100])).unwrap();
+        assert_eq!(image.pixels(), [BltPixel::new(200, 200, 200)]);
+        assert_eq!(image.alpha().unwrap(), [100]);
+
+        assert_eq!(
+            decode(&build((1, 1), 4, 2, 0, &[], &[0, 0])),
+            Err(ImageError::InvalidHeader)
+        );
+    }
+
+    #[test]
+    fn test_interlaced() {
+        // Gray 3x3, where each pixel is its index. Passes 2 and 3 are
+        // empty, so the passes contain (0, 0); (2, 0); (0, 2), (2, 2);
+        // (1, 0), (1, 2) on two rows and the middle row, in that order.
+        let rows = [
+            0, 0, //
+            0, 2, //
+            0, 6, 8, //
+            0, 1, 0, 7, //
+            0, 3, 4, 5,
+        ];
+        let image = decode(&build((3, 3), 8, 0, 1, &[], &rows)).unwrap();
+        let grays: Vec<u8> = image.pixels().iter().map(|p| p.red).collect();
+        assert_eq!(grays, [0, 1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(decode(&RGBA[..40]), Err(ImageError::Truncated));
+
+        let mut corrupt = RGBA;
+        corrupt[50] ^= 1;
+        assert_eq!(decode(&corrupt), Err(ImageError::ChecksumMismatch));
+
+        // A critical chunk that is not known.
+        let png = build((1, 1), 8, 0, 0, &[(b"ABCD", &[])], &[0, 0]);
+        assert_eq!(decode(&png), Err(ImageError::Unsupported));
+        // Ancillary chunks are skipped.
+        let png = build((1, 1), 8, 0, 0, &[(b"gAMA", &[0, 0, 0xb1, 0x8f])], &[0, 0]);
+        assert!(decode(&png).is_ok());
+
+        // Too little image data.
+        let png = build((2, 1), 8, 0, 0, &[], &[0, 0]);
+        assert_eq!(decode(&png), Err(ImageError::Truncated));
+
+        let png = build((0, 1), 8, 0, 0, &[], &[]);
+        assert_eq!(decode(&png), Err(ImageError::InvalidHeader));
+    }
+}
@@ -0,0 +1,491 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Reading a line of text from the console.
+//!
+//! [`LineEditor`] reads a line of text from an [`Input`], echoing it to an
+//! [`Output`]. The line can be edited with the cursor keys, Home, End,
+//! Backspace and Delete; Insert toggles between insert and overwrite
+//! mode. Up and Down recall previous lines. Enter finishes the line and
+//! Escape cancels it.
+//!
+//! ```no_run
+//! use uefi::proto::console::text::{Input, Output};
+//! use uefi::ui::line_edit::LineEditor;
+//! use uefi::Char16;
+//!
+//! fn login(input: &mut Input, output: &mut Output) -> uefi::Result<bool> {
+//!     let mut editor = LineEditor::new().with_max_len(32);
+//!     let Some(user) = editor.prompt(input, output, "User: ")? else {
+//!         return Ok(false);
+//!     };
+//!
+//!     let mut password = LineEditor::new().with_mask(Char16::try_from('*').unwrap());
+//!     let Some(password) = password.prompt(input, output, "Password: ")? else {
+//!         return Ok(false);
+//!     };
+//!     Ok(user == "root" && password == "hunter2")
+//! }
+//! ```
+//!
+//! [`Input`]: crate::proto::console::text::Input
+//! [`Output`]: crate::proto::console::text::Output
+
+use super::terminal::{Terminal, UefiTerminal};
+use crate::proto::console::text::{Input, Key, Output, ScanCode};
+use crate::{Char16, Result};
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+const CARRIAGE_RETURN: u16 = 0x0d;
+const BACKSPACE: u16 = 0x08;
+const REPLACEMENT_CHARACTER: u16 = 0xfffd;
+
+/// Reads lines of text from the console. See the [module documentation].
+///
+/// The editor keeps the history of the lines it has read, so the same
+/// editor should be reused for prompts that share a history.
+///
+/// [module documentation]: self
+#[derive(Debug, Clone)]
+pub struct LineEditor {
+    max_len: Option<usize>,
+    mask: Option<Char16>,
+    history: Vec<Vec<Char16>>,
+    history_size: usize,
+}
+
+impl Default for LineEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineEditor {
+    /// Create an editor without a length limit or mask, that remembers up
+    /// to 32 lines of history.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            max_len: None,
+            mask: None,
+            history: Vec::new(),
+            history_size: 32,
+        }
+    }
+
+    /// Limit lines to `max_len` characters. Further characters are
+    /// ignored.
+    ///
+    /// Lines are also limited to what fits on the screen.
+    #[must_use]
+    pub const fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    /// Display `mask` in place of each character, e.g. for passwords.
+    ///
+    /// Masked lines are not added to the history, and the history cannot
+    /// be recalled.
+    #[must_use]
+    pub const fn with_mask(mut self, mask: Char16) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// Remember up to `size` lines of history. Zero disables the history.
+    #[must_use]
+    pub fn with_history_size(mut self, size: usize) -> Self {
+        self.history_size = size;
+        self.truncate_history();
+        self
+    }
+
+    /// Add `line` to the history, as if it had been entered.
+    ///
+    /// Characters that are not valid UCS-2 are replaced with U+FFFD.
+    pub fn add_history(&mut self, line: &str) {
+        self.push_history(to_ucs2(line));
+    }
+
+    /// Remove all lines from the history.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Write `prompt` and read a line after it. See [`read_line`].
+    ///
+    /// # Errors
+    ///
+    /// See [`read_line`].
+    ///
+    /// [`read_line`]: Self::read_line
+    pub fn prompt(
+        &mut self,
+        input: &mut Input,
+        output: &mut Output,
+        prompt: &str,
+    ) -> Result<Option<String>> {
+        self.run(&mut UefiTerminal { input, output }, prompt, "")
+    }
+
+    /// Read a line starting at the cursor position.
+    ///
+    /// Returns the line without a line terminator, or `None` if Escape was
+    /// pressed. Either way, the cursor is left at the start of the next
+    /// row.
+    ///
+    /// # Errors
+    ///
+    /// Errors from reading keys and writing to the console are returned.
+    pub fn read_line(&mut self, input: &mut Input, output: &mut Output) -> Result<Option<String>> {
+        self.run(&mut UefiTerminal { input, output }, "", "")
+    }
+
+    /// Read a line like [`read_line`], starting with `initial` as the text
+    /// to edit, e.g. to change a kernel command line.
+    ///
+    /// # Errors
+    ///
+    /// See [`read_line`].
+    ///
+    /// [`read_line`]: Self::read_line
+    pub fn edit_line(
+        &mut self,
+        input: &mut Input,
+        output: &mut Output,
+        initial: &str,
+    ) -> Result<Option<String>> {
+        self.run(&mut UefiTerminal { input, output }, "", initial)
+    }
+
+    fn run(
+        &mut self,
+        term: &mut impl Terminal,
+        prompt: &str,
+        initial: &str,
+    ) -> Result<Option<String>> {
+        term.write(&to_ucs2(prompt))?;
+
+        let mut session = Session::new(term, self.max_len);
+        session.buf = to_ucs2(initial);
+        session.buf.truncate(session.max_len);
+        session.cursor = session.buf.len();
+        session.render(self.mask)?;
+
+        let mut history_pos = None;
+        let mut saved = Vec::new();
+        let line = loop {
+            match session.term.read_key()? {
+                Key::Printable(c) => match u16::from(c) {
+                    CARRIAGE_RETURN => break Some(session.buf.clone()),
+                    BACKSPACE => session.backspace(),
+                    // Ignore other control characters.
+                    code if code < 0x20 || code == 0x7f => {}
+                    _ => session.insert(c),
+                },
+                Key::Special(ScanCode::LEFT) => session.cursor = session.cursor.saturating_sub(1),
+                Key::Special(ScanCode::RIGHT) => {
+                    session.cursor = (session.cursor + 1).min(session.buf.len());
+                }
+                Key::Special(ScanCode::HOME) => session.cursor = 0,
+                Key::Special(ScanCode::END) => session.cursor = session.buf.len(),
+                Key::Special(ScanCode::DELETE) => session.delete(),
+                Key::Special(ScanCode::INSERT) => session.insert_mode = !session.insert_mode,
+                Key::Special(ScanCode::UP) if self.mask.is_none() => {
+                    let pos = match history_pos {
+                        None if !self.history.is_empty() => {
+                            saved = session.buf.clone();
+                            self.history.len() - 1
+                        }
+                        Some(pos) if pos > 0 => pos - 1,
+                        _ => continue,
+                    };
+                    history_pos = Some(pos);
+                    session.replace(&self.history[pos]);
+                }
+                Key::Special(ScanCode::DOWN) if self.mask.is_none() => match history_pos {
+                    Some(pos) if pos + 1 < self.history.len() => {
+                        history_pos = Some(pos + 1);
+                        session.replace(&self.history[pos + 1]);
+                    }
+                    Some(_) => {
+                        history_pos = None;
+                        session.replace(&saved);
+                    }
+                    None => continue,
+                },
+                Key::Special(ScanCode::ESCAPE) => break None,
+                Key::Special(_) => continue,
+            }
+            session.render(self.mask)?;
+        };
+
+        session.finish()?;
+        Ok(line.map(|line| {
+            let s = line.iter().copied().map(char::from).collect();
+            if self.mask.is_none() {
+                self.push_history(line);
+            }
+            s
+        }))
+    }
+
+    fn push_history(&mut self, line: Vec<Char16>) {
+        if line.is_empty() || self.history.last() == Some(&line) {
+            return;
+        }
+        self.history.push(line);
+        self.truncate_history();
+    }
+
+    fn truncate_history(&mut self) {
+        let excess = self.history.len().saturating_sub(self.history_size);
+        self.history.drain(..excess);
+    }
+}
+
+/// The state of the line being edited.
+struct Session<'a, T: Terminal> {
+    term: &'a mut T,
+    buf: Vec<Char16>,
+    cursor: usize,
+    insert_mode: bool,
+    max_len: usize,
+    /// Position of the first character of the line.
+    start: (usize, usize),
+    /// Number of characters drawn by the last render.
+    drawn: usize,
+}
+
+impl<'a, T: Terminal> Session<'a, T> {
+    fn new(term: &'a mut T, max_len: Option<usize>) -> Self {
+        let (columns, rows) = term.size();
+        let start = term.cursor_position();
+        // Keep one cell free after the line, so that writing it never
+        // scrolls the screen past its start.
+        let screen_len = (columns * rows).saturating_sub(start.0 + 1);
+        Self {
+            term,
+            buf: Vec::new(),
+            cursor: 0,
+            insert_mode: true,
+            max_len: max_len.unwrap_or(usize::MAX).min(screen_len),
+            start,
+            drawn: 0,
+        }
+    }
+
+    fn insert(&mut self, c: Char16) {
+        if !self.insert_mode && self.cursor < self.buf.len() {
+            self.buf[self.cursor] = c;
+        } else if self.buf.len() < self.max_len {
+            self.buf.insert(self.cursor, c);
+        } else {
+            return;
+        }
+        self.cursor += 1;
+    }
+
+    fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.buf.remove(self.cursor);
+        }
+    }
+
+    fn delete(&mut self) {
+        if self.cursor < self.buf.len() {
+            self.buf.remove(self.cursor);
+        }
+    }
+
+    fn replace(&mut self, line: &[Char16]) {
+        self.buf.clear();
+        self.buf
+            .extend_from_slice(&line[..line.len().min(self.max_len)]);
+        self.cursor = self.buf.len();
+    }
+
+    /// Screen position of the character at `index`.
+    fn position(&self, index: usize) -> (usize, usize) {
+        let columns = self.term.size().0;
+        let offset = self.start.0 + index;
+        (offset % columns, self.start.1 + offset / columns)
+    }
+
+    /// Redraw the line and place the cursor.
+    fn render(&mut self, mask: Option<Char16>) -> Result {
+        let (columns, rows) = self.term.size();
+        let len = self.buf.len().max(self.drawn);
+
+        // Scroll up if the line does not fit below its start.
+        let needed_rows = (self.start.0 + len + 1).div_ceil(columns);
+        while self.start.1 > 0 && self.start.1 + needed_rows > rows {
+            self.term.set_cursor_position(0, rows - 1)?;
+            self.term.new_line()?;
+            self.start.1 -= 1;
+        }
+
+        self.term.set_cursor_position(self.start.0, self.start.1)?;
+        match mask {
+            Some(mask) => self.term.write(&vec![mask; self.buf.len()])?,
+            None => self.term.write(&self.buf)?,
+        }
+        let space = Char16::try_from(' ').unwrap();
+        self.term
+            .write(&vec![space; self.drawn.saturating_sub(self.buf.len())])?;
+        self.drawn = self.buf.len();
+
+        let (column, row) = self.position(self.cursor);
+        self.term.set_cursor_position(column, row)
+    }
+
+    /// Move the cursor to the start of the row after the line.
+    fn finish(&mut self) -> Result {
+        let (column, row) = self.position(self.buf.len());
+        self.term.set_cursor_position(column, row)?;
+        self.term.new_line()
+    }
+}
+
+/// Convert `s` to UCS-2, replacing characters outside of the BMP.
+fn to_ucs2(s: &str) -> Vec<Char16> {
+    s.chars()
+        .map(|c| {
+            Char16::try_from(c).unwrap_or_else(|_| Char16::try_from(REPLACEMENT_CHARACTER).unwrap())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::terminal::tests::FakeTerminal;
+
+    fn special(term: &mut FakeTerminal, scan_code: ScanCode) {
+        term.keys.push_back(Key::Special(scan_code));
+    }
+
+    #[test]
+    fn test_editing() {
+        let mut term = FakeTerminal::new(20, 5);
+        term.type_str("helo");
+        special(&mut term, ScanCode::LEFT);
+        term.type_str("l");
+        special(&mut term, ScanCode::END);
+        term.type_str(" world\x08\x08");
+        special(&mut term, ScanCode::HOME);
+        special(&mut term, ScanCode::DELETE);
+        term.type_str("H\r");
+
+        let mut editor = LineEditor::new();
+        let line = editor.run(&mut term, "> ", "").unwrap();
+        assert_eq!(line.as_deref(), Some("Hello wor"));
+        assert_eq!(term.row(0), "> Hello wor");
+        assert_eq!(term.cursor, (0, 1));
+    }
+
+    #[test]
+    fn test_overwrite_and_max_len() {
+        let mut term = FakeTerminal::new(20, 5);
+        special(&mut term, ScanCode::HOME);
+        special(&mut term, ScanCode::INSERT);
+        term.type_str("XY");
+        special(&mut term, ScanCode::END);
+        term.type_str("12345\r");
+
+        let mut editor = LineEditor::new().with_max_len(6);
+        let line = editor.run(&mut term, "", "abcd").unwrap();
+        assert_eq!(line.as_deref(), Some("XYcd12"));
+    }
+
+    #[test]
+    fn test_escape() {
+        let mut term = FakeTerminal::new(20, 5);
+        term.type_str("abc\x1b");
+        special(&mut term, ScanCode::ESCAPE);
+
+        let mut editor = LineEditor::new();
+        assert_eq!(editor.run(&mut term, "", "").unwrap(), None);
+        assert_eq!(term.cursor, (0, 1));
+        assert!(editor.history.is_empty());
+    }
+
+    #[test]
+    fn test_mask() {
+        let mut term = FakeTerminal::new(20, 5);
+        term.type_str("secret");
+        special(&mut term, ScanCode::UP);
+        term.type_str("\r");
+
+        let mut editor = LineEditor::new().with_mask(Char16::try_from('*').unwrap());
+        editor.add_history("old");
+        let line = editor.run(&mut term, "Password: ", "").unwrap();
+        assert_eq!(line.as_deref(), Some("secret"));
+        assert_eq!(term.row(0), "Password: ******");
+        assert_eq!(editor.history.len(), 1);
+    }
+
+    #[test]
+    fn test_history() {
+        let mut term = FakeTerminal::new(20, 5);
+        let mut editor = LineEditor::new().with_history_size(2);
+        for line in ["one\r", "two\r", "three\r", "three\r"] {
+            term.type_str(line);
+            editor.run(&mut term, "", "").unwrap();
+        }
+        assert_eq!(editor.history.len(), 2);
+
+        // Go back to "two", edit it, then go forward past "three" to the
+        // line being typed.
+        term.type_str("new");
+        special(&mut term, ScanCode::UP);
+        special(&mut term, ScanCode::UP);
+        special(&mut term, ScanCode::UP);
+        special(&mut term, ScanCode::DOWN);
+        special(&mut term, ScanCode::DOWN);
+        term.type_str("!\r");
+        assert_eq!(
+            editor.run(&mut term, "", "").unwrap().as_deref(),
+            Some("new!")
+        );
+
+        term.type_str("x");
+        special(&mut term, ScanCode::UP);
+        special(&mut term, ScanCode::UP);
+        term.type_str("\r");
+        assert_eq!(
+            editor.run(&mut term, "", "").unwrap().as_deref(),
+            Some("three")
+        );
+    }
+
+    #[test]
+    fn test_wrap_and_scroll() {
+        let mut term = FakeTerminal::new(10, 3);
+        term.set_cursor_position(0, 2).unwrap();
+        term.type_str("abcdefghijklmnop");
+        special(&mut term, ScanCode::HOME);
+        term.type_str("\r");
+
+        let mut editor = LineEditor::new();
+        let line = editor.run(&mut term, "$ ", "").unwrap();
+        assert_eq!(line.as_deref(), Some("abcdefghijklmnop"));
+        assert_eq!(term.row(0), "$ abcdefgh");
+        assert_eq!(term.row(1), "ijklmnop");
+        assert_eq!(term.cursor, (0, 2));
+    }
+
+    #[test]
+    fn test_screen_limit() {
+        let mut term = FakeTerminal::new(4, 2);
+        term.type_str("abcdefghij\r");
+
+        let mut editor = LineEditor::new();
+        let line = editor.run(&mut term, "", "").unwrap();
+        // One cell is kept free after the line.
+        assert_eq!(line.as_deref(), Some("abcdefg"));
+    }
+}
@@ -0,0 +1,36 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! User interface helpers built on the console protocols.
+//!
+//! - [`canvas`]: double-buffered drawing on a graphics device. Requires
+//!   the `alloc` feature.
+//! - [`fb_console`]: a text console drawn with a bitmap font on a
+//!   graphics framebuffer, usable after exiting boot services.
+//! - [`image`]: decoding and scaling of BMP and PNG images, and access to
+//!   the firmware's boot logo. Requires the `alloc` feature.
+//! - [`line_edit`]: reading a line of text with cursor movement, history
+//!   and password masking. Requires the `alloc` feature.
+//! - [`splitter`]: mirroring of text to several output devices, serial
+//!   ports and debugcon, and reading keys from several input devices.
+//!   Requires the `alloc` feature.
+//! - [`tui`]: bordered windows, selection menus, yes/no dialogs and
+//!   progress bars. Requires the `alloc` feature.
+
+#[cfg(feature = "alloc")]
+pub mod canvas;
+pub mod fb_console;
+#[cfg(feature = "alloc")]
+pub mod image;
+#[cfg(feature = "alloc")]
+pub mod line_edit;
+
+mod font;
+mod pixel;
+
+#[cfg(feature = "alloc")]
+mod terminal;
+
+#[cfg(feature = "alloc")]
+pub mod splitter;
+#[cfg(feature = "alloc")]
+pub mod tui;
@@ -0,0 +1,125 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Drawing to graphics framebuffers.
+
+use crate::proto::console::gop::{
+    BltOp, BltPixel, GraphicsOutput, ModeInfo, PixelBitmask, PixelFormat,
+};
+use crate::{boot, Result, Status};
+
+/// Size of the pixels of the framebuffer in bytes.
+pub(super) const PIXEL_SIZE: usize = 4;
+
+/// How colors are stored in framebuffer pixels.
+#[derive(Debug, Copy, Clone)]
+pub(super) enum Encoding {
+    Rgb,
+    Bgr,
+    Bitmask(PixelBitmask),
+    Blt,
+}
+
+impl Encoding {
+    pub(super) fn new(info: &ModeInfo) -> Self {
+        match info.pixel_format() {
+            PixelFormat::Rgb => Self::Rgb,
+            PixelFormat::Bgr => Self::Bgr,
+            PixelFormat::Bitmask => Self::Bitmask(info.pixel_bitmask().unwrap()),
+            PixelFormat::BltOnly => Self::Blt,
+        }
+    }
+
+    /// The value of a framebuffer pixel showing `color`.
+    pub(super) fn encode(&self, color: BltPixel) -> u32 {
+        let (red, green, blue) = (
+            u32::from(color.red),
+            u32::from(color.green),
+            u32::from(color.blue),
+        );
+        match self {
+            Self::Rgb => red | (green << 8) | (blue << 16),
+            Self::Bgr | Self::Blt => blue | (green << 8) | (red << 16),
+            Self::Bitmask(mask) => {
+                scale_to_mask(red, mask.red)
+                    | scale_to_mask(green, mask.green)
+                    | scale_to_mask(blue, mask.blue)
+            }
+        }
+    }
+}
+
+/// Scale an 8-bit color channel to the bits set in `mask`.
+const fn scale_to_mask(value: u32, mask: u32) -> u32 {
+    if mask == 0 {
+        return 0;
+    }
+    let shift = mask.trailing_zeros();
+    let bits = (mask >> shift).count_ones();
+    let value = if bits <= 8 {
+        value >> (8 - bits)
+    } else {
+        // Repeat the high bits to fill wider channels.
+        (value << (bits - 8)) | (value >> 16_u32.saturating_sub(bits))
+    };
+    (value << shift) & mask
+}
+
+/// Write the pixel at `x` and `y` of a framebuffer with `stride` pixels
+/// per row.
+///
+/// # Safety
+///
+/// The pixel must be within the framebuffer at `base`.
+pub(super) unsafe fn write(base: *mut u8, stride: usize, x: usize, y: usize, value: u32) {
+    let offset = (y * stride + x) * PIXEL_SIZE;
+    unsafe { base.add(offset).cast::<u32>().write_volatile(value) }
+}
+
+/// Run a blit operation, if boot services are still active.
+pub(super) fn blt(gop: &mut GraphicsOutput, op: BltOp) -> Result {
+    if !boot::are_boot_services_active() {
+        return Err(Status::UNSUPPORTED.into());
+    }
+    gop.blt(op)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let color = BltPixel::new(0x12, 0x34, 0x56);
+        assert_eq!(Encoding::Rgb.encode(color), 0x0056_3412);
+        assert_eq!(Encoding::Bgr.encode(color), 0x0012_3456);
+
+        // 5:6:5
+        let mask = PixelBitmask {
+            red: 0xf800,
+            green: 0x07e0,
+            blue: 0x001f,
+            reserved: 0,
+        };
+        assert_eq!(
+            Encoding::Bitmask(mask).encode(BltPixel::new(0xff, 0xff, 0xff)),
+            0xffff
+        );
+        assert_eq!(
+            Encoding::Bitmask(mask).encode(BltPixel::new(0x80, 0x40, 0x08)),
+            (0x10 << 11) | (0x10 << 5) | 0x01
+        );
+
+        // 10:10:10
+        let mask = PixelBitmask {
+            red: 0x3ff << 20,
+            green: 0x3ff << 10,
+            blue: 0x3ff,
+            reserved: 0xc000_0000,
+        };
+        assert_eq!(
+            Encoding::Bitmask(mask).encode(BltPixel::new(0xff, 0xff, 0xff)),
+            0x3fff_ffff
+        );
+        assert_eq!(Encoding::Bitmask(mask).encode(BltPixel::new(0, 0, 0)), 0);
+    }
+}
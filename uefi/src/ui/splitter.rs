@@ -0,0 +1,368 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Mirroring of console output to several devices, and reading keys from
+//! several input devices.
+//!
+//! [`system::with_stdout`] only reaches the console that the firmware chose
+//! for the system table. For headless debugging it is useful to write to
+//! every text output, to serial ports and to the debugcon device at once.
+//! [`ConsoleSplitter`] does that, and can replace the backend of
+//! [`print!`] and [`println!`] through [`helpers::set_print_output`].
+//! [`InputMux`] reads keys from all text inputs.
+//!
+//! The devices are opened without an exclusive lock, so the drivers that use
+//! them, such as the firmware's own console, keep working.
+//!
+//! [`helpers::set_print_output`]: crate::helpers::set_print_output
+//! [`print!`]: crate::print!
+//! [`println!`]: crate::println!
+//! [`system::with_stdout`]: crate::system::with_stdout
+
+use crate::boot::{self, OpenProtocolAttributes, OpenProtocolParams, ScopedProtocol};
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use crate::helpers::debugcon::DebugconWriter;
+use crate::proto::console::serial::{Escape, Serial};
+use crate::proto::console::text::{Color, Input, Key, Output};
+use crate::proto::device_path::DevicePath;
+use crate::proto::ProtocolPointer;
+use crate::{Event, Handle, Result, ResultExt, Status};
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+/// Open a protocol without disconnecting the drivers that use it.
+fn open_shared<P: ProtocolPointer + ?Sized>(handle: Handle) -> Result<ScopedProtocol<P>> {
+    unsafe {
+        boot::open_protocol::<P>(
+            OpenProtocolParams {
+                handle,
+                agent: boot::image_handle(),
+                controller: None,
+            },
+            OpenProtocolAttributes::GetProtocol,
+        )
+    }
+}
+
+/// Handles with protocol `P` that belong to a device.
+///
+/// The firmware's console splitter installs the text protocols on virtual
+/// handles without a device path, in addition to the handles of the
+/// devices. Skipping those avoids writing or reading everything twice.
+fn device_handles<P: ProtocolPointer + ?Sized>() -> Result<Vec<Handle>> {
+    let mut handles = boot::find_handles::<P>()?;
+    handles.retain(|&handle| {
+        boot::test_protocol::<DevicePath>(OpenProtocolParams {
+            handle,
+            agent: boot::image_handle(),
+            controller: None,
+        })
+        .unwrap_or(false)
+    });
+    Ok(handles)
+}
+
+fn contains<P: ProtocolPointer + ?Sized>(protocols: &[ScopedProtocol<P>], handle: Handle) -> bool {
+    protocols
+        .iter()
+        .any(|protocol| protocol.open_params().handle == handle)
+}
+
+/// Write `s` with `write`, turning each line feed into a carriage return
+/// and a line feed for serial terminals.
+fn write_crlf(s: &str, mut write: impl FnMut(&[u8]) -> Result) -> Result {
+    let mut lines = s.split('\n');
+    if let Some(first) = lines.next() {
+        if !first.is_empty() {
+            write(first.as_bytes())?;
+        }
+    }
+    for line in lines {
+        write(b"\r\n")?;
+        if !line.is_empty() {
+            write(line.as_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// Writer that mirrors text to several [`Output`] devices, [`Serial`]
+/// ports and, on x86, the debugcon device.
+///
+/// Text is written to every device even if writing to one of them fails;
+/// the error is reported afterwards.
+///
+/// # Example
+///
+/// ```no_run
+/// use core::fmt::Write;
+/// use uefi::boot;
+/// use uefi::proto::console::serial::Serial;
+/// use uefi::ui::splitter::ConsoleSplitter;
+///
+/// # fn example() -> uefi::Result {
+/// let mut splitter = ConsoleSplitter::new();
+/// splitter.add_outputs()?;
+/// for handle in boot::find_handles::<Serial>()? {
+///     splitter.add_serial(handle)?;
+/// }
+/// writeln!(splitter, "Hello from every console").unwrap();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct ConsoleSplitter {
+    outputs: Vec<ScopedProtocol<Output>>,
+    serials: Vec<ScopedProtocol<Serial>>,
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    debugcon: bool,
+}
+
+impl ConsoleSplitter {
+    /// Create a splitter without any devices.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            outputs: Vec::new(),
+            serials: Vec::new(),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            debugcon: false,
+        }
+    }
+
+    /// Add the [`Output`] protocol of every device that has one.
+    ///
+    /// Handles without a device path, such as the virtual handle that the
+    /// firmware's console splitter installs for stdout, are skipped, since
+    /// the text would otherwise appear twice on the devices behind it.
+    pub fn add_outputs(&mut self) -> Result {
+        for handle in device_handles::<Output>()? {
+            self.add_output(handle)?;
+        }
+        Ok(())
+    }
+
+    /// Add the [`Output`] protocol on `handle`. Handles that were added
+    /// already are ignored.
+    pub fn add_output(&mut self, handle: Handle) -> Result {
+        if !contains(&self.outputs, handle) {
+            self.outputs.push(open_shared(handle)?);
+        }
+        Ok(())
+    }
+
+    /// Add the [`Serial`] protocol on `handle`. Handles that were added
+    /// already are ignored.
+    ///
+    /// Line feeds are written as a carriage return and a line feed. If the
+    /// firmware already uses the port as a console, the text is written
+    /// twice: once through the port's [`Output`] and once directly.
+    pub fn add_serial(&mut self, handle: Handle) -> Result {
+        if !contains(&self.serials, handle) {
+            self.serials.push(open_shared(handle)?);
+        }
+        Ok(())
+    }
+
+    /// Also write to the QEMU debugcon device and the debug-console of
+    /// cloud-hypervisor.
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    pub const fn set_debugcon(&mut self, enabled: bool) {
+        self.debugcon = enabled;
+    }
+
+    /// The number of [`Output`] devices and [`Serial`] ports.
+    #[must_use]
+    pub fn device_count(&self) -> usize {
+        self.outputs.len() + self.serials.len()
+    }
+
+    /// Clear the screens of all devices.
+    pub fn clear(&mut self) -> Result {
+        self.for_each(|output| output.clear(), Escape::ClearScreen)
+    }
+
+    /// Set the colors of text written afterwards on all devices.
+    pub fn set_color(&mut self, foreground: Color, background: Color) -> Result {
+        let escape = Escape::SetColors {
+            foreground,
+            background,
+        };
+        self.for_each(|output| output.set_color(foreground, background), escape)
+    }
+
+    /// Call `f` on every [`Output`] and write `escape` to every [`Serial`]
+    /// port, returning the first error.
+    fn for_each(&mut self, mut f: impl FnMut(&mut Output) -> Result, escape: Escape) -> Result {
+        let mut result = Ok(());
+        for output in &mut self.outputs {
+            result = result.and(f(output));
+        }
+        for serial in &mut self.serials {
+            let written = write!(serial, "{escape}").map_err(|_| Status::DEVICE_ERROR.into());
+            result = result.and(written);
+        }
+        result
+    }
+}
+
+impl Write for ConsoleSplitter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut result = Ok(());
+        for output in &mut self.outputs {
+            result = result.and(output.write_str(s));
+        }
+        for serial in &mut self.serials {
+            let written = write_crlf(s, |data| serial.write(data).discard_errdata());
+            result = result.and(written.map_err(|_| fmt::Error));
+        }
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        if self.debugcon {
+            result = result.and(DebugconWriter.write_str(s));
+        }
+        result
+    }
+}
+
+/// Reader of keys from several [`Input`] devices.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::proto::console::text::Key;
+/// use uefi::ui::splitter::InputMux;
+///
+/// # fn example() -> uefi::Result {
+/// let mut input = InputMux::new();
+/// input.add_inputs()?;
+/// match input.wait_for_key()? {
+///     Key::Printable(c) => uefi::println!("{c}"),
+///     Key::Special(key) => uefi::println!("{key:?}"),
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct InputMux {
+    inputs: Vec<ScopedProtocol<Input>>,
+    /// Input to read first, so that one device cannot starve the others.
+    next: usize,
+}
+
+impl InputMux {
+    /// Create a multiplexer without any devices.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            inputs: Vec::new(),
+            next: 0,
+        }
+    }
+
+    /// Add the [`Input`] protocol of every device that has one.
+    ///
+    /// As in [`ConsoleSplitter::add_outputs`], handles without a device
+    /// path are skipped.
+    pub fn add_inputs(&mut self) -> Result {
+        for handle in device_handles::<Input>()? {
+            self.add_input(handle)?;
+        }
+        Ok(())
+    }
+
+    /// Add the [`Input`] protocol on `handle`. Handles that were added
+    /// already are ignored.
+    pub fn add_input(&mut self, handle: Handle) -> Result {
+        if !contains(&self.inputs, handle) {
+            self.inputs.push(open_shared(handle)?);
+        }
+        Ok(())
+    }
+
+    /// The number of [`Input`] devices.
+    #[must_use]
+    pub fn device_count(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// Reset all devices, returning the first error.
+    pub fn reset(&mut self, extended_verification: bool) -> Result {
+        let mut result = Ok(());
+        for input in &mut self.inputs {
+            result = result.and(input.reset(extended_verification));
+        }
+        result
+    }
+
+    /// Read the next key from any device, if one was pressed.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::DEVICE_ERROR`]: a device failed.
+    pub fn read_key(&mut self) -> Result<Option<Key>> {
+        let count = self.inputs.len();
+        for i in 0..count {
+            let index = (self.next + i) % count;
+            if let Some(key) = self.inputs[index].read_key()? {
+                self.next = (index + 1) % count;
+                return Ok(Some(key));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Wait for a key to be pressed on any device and return it.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_FOUND`]: no devices were added.
+    /// * [`Status::UNSUPPORTED`]: a device has no key event.
+    /// * [`Status::DEVICE_ERROR`]: a device failed.
+    pub fn wait_for_key(&mut self) -> Result<Key> {
+        if self.inputs.is_empty() {
+            return Err(Status::NOT_FOUND.into());
+        }
+        loop {
+            if let Some(key) = self.read_key()? {
+                return Ok(key);
+            }
+            let mut events = self
+                .inputs
+                .iter()
+                .map(|input| input.wait_for_key_event().ok_or(Status::UNSUPPORTED.into()))
+                .collect::<Result<Vec<Event>>>()?;
+            let index = boot::wait_for_event(&mut events).discard_errdata()?;
+            self.next = index;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_crlf() {
+        let mut out = Vec::new();
+        let mut write = |s: &str| {
+            out.clear();
+            write_crlf(s, |data| {
+                out.extend_from_slice(data);
+                Ok(())
+            })
+            .unwrap();
+            out.clone()
+        };
+        assert_eq!(write("abc"), b"abc");
+        assert_eq!(write("a\nb\n"), b"a\r\nb\r\n");
+        assert_eq!(write("\n\n"), b"\r\n\r\n");
+        assert_eq!(write(""), b"");
+
+        let mut calls = 0;
+        let result = write_crlf("a\nb", |_| {
+            calls += 1;
+            Err(Status::DEVICE_ERROR.into())
+        });
+        assert_eq!(result, Err(Status::DEVICE_ERROR.into()));
+        assert_eq!(calls, 1);
+    }
+}
@@ -0,0 +1,238 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Text console abstraction shared by the widgets.
+
+use crate::proto::console::text::{Color, Input, Key, Output};
+use crate::{boot, CStr16, Char16, Result, ResultExt, Status};
+use alloc::vec::Vec;
+
+/// A text console that widgets draw on and read keys from.
+///
+/// This is implemented for the [`Input`] and [`Output`] protocols, and by
+/// an in-memory screen in tests.
+pub(super) trait Terminal {
+    /// Wait for and return the next key.
+    fn read_key(&mut self) -> Result<Key>;
+
+    /// The number of columns and rows.
+    fn size(&self) -> (usize, usize);
+
+    /// The column and row of the cursor.
+    fn cursor_position(&self) -> (usize, usize);
+
+    /// Move the cursor.
+    fn set_cursor_position(&mut self, column: usize, row: usize) -> Result;
+
+    /// Write `text` at the cursor, wrapping at the end of a row.
+    fn write(&mut self, text: &[Char16]) -> Result;
+
+    /// Move the cursor to the start of the next row, scrolling the screen
+    /// if it is on the last row.
+    fn new_line(&mut self) -> Result;
+
+    /// Set the colors of text written afterwards.
+    fn set_color(&mut self, foreground: Color, background: Color) -> Result;
+
+    /// Whether the cursor is shown.
+    fn cursor_visible(&self) -> bool;
+
+    /// Show or hide the cursor.
+    fn enable_cursor(&mut self, visible: bool) -> Result;
+
+    /// Whether all characters of `text` can be displayed.
+    fn supports(&mut self, text: &[Char16]) -> bool;
+}
+
+/// [`Terminal`] on the UEFI console protocols.
+pub(super) struct UefiTerminal<'a> {
+    pub(super) input: &'a mut Input,
+    pub(super) output: &'a mut Output,
+}
+
+impl Terminal for UefiTerminal<'_> {
+    fn read_key(&mut self) -> Result<Key> {
+        loop {
+            if let Some(key) = self.input.read_key()? {
+                return Ok(key);
+            }
+            let event = self.input.wait_for_key_event().ok_or(Status::UNSUPPORTED)?;
+            boot::wait_for_event(&mut [event]).discard_errdata()?;
+        }
+    }
+
+    fn size(&self) -> (usize, usize) {
+        // Devices must support 80x25, so use it if the mode is unknown.
+        match self.output.current_mode() {
+            Ok(Some(mode)) => (mode.columns(), mode.rows()),
+            _ => (80, 25),
+        }
+    }
+
+    fn cursor_position(&self) -> (usize, usize) {
+        self.output.cursor_position()
+    }
+
+    fn set_cursor_position(&mut self, column: usize, row: usize) -> Result {
+        self.output.set_cursor_position(column, row)
+    }
+
+    fn write(&mut self, text: &[Char16]) -> Result {
+        const BUF_SIZE: usize = 128;
+        let mut buf = [0u16; BUF_SIZE + 1];
+        for chunk in text.chunks(BUF_SIZE) {
+            for (dst, c) in buf.iter_mut().zip(chunk) {
+                *dst = u16::from(*c);
+            }
+            buf[chunk.len()] = 0;
+            let s = crate::CStr16::from_u16_with_nul(&buf[..=chunk.len()])
+                .map_err(|_| Status::INVALID_PARAMETER)?;
+            self.output.output_string_lossy(s)?;
+        }
+        Ok(())
+    }
+
+    fn new_line(&mut self) -> Result {
+        self.output.output_string(crate::cstr16!("\r\n"))
+    }
+
+    fn set_color(&mut self, foreground: Color, background: Color) -> Result {
+        self.output.set_color(foreground, background)
+    }
+
+    fn cursor_visible(&self) -> bool {
+        self.output.cursor_visible()
+    }
+
+    fn enable_cursor(&mut self, visible: bool) -> Result {
+        self.output.enable_cursor(visible)
+    }
+
+    fn supports(&mut self, text: &[Char16]) -> bool {
+        let mut buf: Vec<u16> = text.iter().map(|&c| u16::from(c)).collect();
+        buf.push(0);
+        CStr16::from_u16_with_nul(&buf).is_ok_and(|s| self.output.test_string(s).unwrap_or(false))
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use alloc::collections::VecDeque;
+    use alloc::string::String;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    /// An in-memory [`Terminal`] that replays a list of keys.
+    pub(crate) struct FakeTerminal {
+        pub(crate) cells: Vec<Vec<char>>,
+        /// Attribute of each cell, in the same format as the console's.
+        pub(crate) attributes: Vec<Vec<u8>>,
+        pub(crate) attribute: u8,
+        pub(crate) cursor_visible: bool,
+        /// Characters outside of this range are not supported.
+        pub(crate) max_char: u16,
+        pub(crate) cursor: (usize, usize),
+        pub(crate) keys: VecDeque<Key>,
+    }
+
+    impl FakeTerminal {
+        pub(crate) fn new(columns: usize, rows: usize) -> Self {
+            Self {
+                cells: vec![vec![' '; columns]; rows],
+                attributes: vec![vec![0x07; columns]; rows],
+                attribute: 0x07,
+                cursor_visible: true,
+                max_char: 0xffff,
+                cursor: (0, 0),
+                keys: VecDeque::new(),
+            }
+        }
+
+        /// Queue the characters of `s` as printable keys.
+        pub(crate) fn type_str(&mut self, s: &str) {
+            for c in s.chars() {
+                self.keys
+                    .push_back(Key::Printable(Char16::try_from(c).unwrap()));
+            }
+        }
+
+        /// The text of `row`, without trailing spaces.
+        pub(crate) fn row(&self, row: usize) -> String {
+            let s: String = self.cells[row].iter().collect();
+            String::from(s.trim_end())
+        }
+
+        fn scroll(&mut self) {
+            let columns = self.size().0;
+            self.cells.remove(0);
+            self.cells.push(vec![' '; columns]);
+            self.attributes.remove(0);
+            self.attributes.push(vec![self.attribute; columns]);
+        }
+    }
+
+    impl Terminal for FakeTerminal {
+        fn read_key(&mut self) -> Result<Key> {
+            self.keys.pop_front().ok_or(Status::NOT_READY.into())
+        }
+
+        fn size(&self) -> (usize, usize) {
+            (self.cells[0].len(), self.cells.len())
+        }
+
+        fn cursor_position(&self) -> (usize, usize) {
+            self.cursor
+        }
+
+        fn set_cursor_position(&mut self, column: usize, row: usize) -> Result {
+            let (columns, rows) = self.size();
+            if column >= columns || row >= rows {
+                return Err(Status::UNSUPPORTED.into());
+            }
+            self.cursor = (column, row);
+            Ok(())
+        }
+
+        fn write(&mut self, text: &[Char16]) -> Result {
+            for &c in text {
+                let (column, row) = self.cursor;
+                self.cells[row][column] = char::from(c);
+                self.attributes[row][column] = self.attribute;
+                if column + 1 < self.size().0 {
+                    self.cursor.0 += 1;
+                } else {
+                    self.new_line()?;
+                }
+            }
+            Ok(())
+        }
+
+        fn new_line(&mut self) -> Result {
+            self.cursor.0 = 0;
+            if self.cursor.1 + 1 < self.size().1 {
+                self.cursor.1 += 1;
+            } else {
+                self.scroll();
+            }
+            Ok(())
+        }
+
+        fn set_color(&mut self, foreground: Color, background: Color) -> Result {
+            self.attribute = ((background as u8) << 4) | foreground as u8;
+            Ok(())
+        }
+
+        fn cursor_visible(&self) -> bool {
+            self.cursor_visible
+        }
+
+        fn enable_cursor(&mut self, visible: bool) -> Result {
+            self.cursor_visible = visible;
+            Ok(())
+        }
+
+        fn supports(&mut self, text: &[Char16]) -> bool {
+            text.iter().all(|&c| u16::from(c) <= self.max_char)
+        }
+    }
+}
@@ -0,0 +1,799 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Text-mode widgets: windows, menus, dialogs and progress bars.
+//!
+//! [`Tui`] draws on an [`Output`] and reads keys from an [`Input`]. Windows
+//! are drawn with box-drawing characters if the console can display them,
+//! and with ASCII characters otherwise. All drawing is clipped to the
+//! current text mode.
+//!
+//! ```no_run
+//! use uefi::proto::console::text::{Input, Output};
+//! use uefi::ui::tui::Tui;
+//!
+//! fn select_entry(input: &mut Input, output: &mut Output) -> uefi::Result<Option<usize>> {
+//!     let mut tui = Tui::new(input, output);
+//!     let entries = ["Linux", "Linux (recovery)", "Firmware setup"];
+//!     let rect = tui.screen().centered(40, 7);
+//!     let Some(entry) = tui.menu(rect, "Boot", &entries, 0)? else {
+//!         return Ok(None);
+//!     };
+//!     if entry == 2 && !tui.confirm("Reboot", "Reboot into firmware setup?", false)? {
+//!         return Ok(None);
+//!     }
+//!     Ok(Some(entry))
+//! }
+//! ```
+//!
+//! [`Input`]: crate::proto::console::text::Input
+//! [`Output`]: crate::proto::console::text::Output
+
+use super::terminal::{Terminal, UefiTerminal};
+use crate::proto::console::text::{Color, Input, Key, Output, ScanCode};
+use crate::{Char16, Result};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
+
+const CARRIAGE_RETURN: u16 = 0x0d;
+const TAB: u16 = 0x09;
+
+/// A rectangle on the screen, in character cells.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Rect {
+    /// Column of the left edge.
+    pub column: usize,
+    /// Row of the top edge.
+    pub row: usize,
+    /// Width in columns.
+    pub width: usize,
+    /// Height in rows.
+    pub height: usize,
+}
+
+impl Rect {
+    /// Create a rectangle.
+    #[must_use]
+    pub const fn new(column: usize, row: usize, width: usize, height: usize) -> Self {
+        Self {
+            column,
+            row,
+            width,
+            height,
+        }
+    }
+
+    /// Whether the rectangle contains no cells.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// The area inside a border of one cell.
+    #[must_use]
+    pub const fn inner(&self) -> Self {
+        Self::new(
+            self.column + 1,
+            self.row + 1,
+            self.width.saturating_sub(2),
+            self.height.saturating_sub(2),
+        )
+    }
+
+    /// A rectangle of `width` by `height` centered in this one. It is
+    /// shrunk to fit if necessary.
+    #[must_use]
+    pub fn centered(&self, width: usize, height: usize) -> Self {
+        let width = width.min(self.width);
+        let height = height.min(self.height);
+        Self::new(
+            self.column + (self.width - width) / 2,
+            self.row + (self.height - height) / 2,
+            width,
+            height,
+        )
+    }
+
+    /// The cells that are in both rectangles.
+    #[must_use]
+    pub fn intersect(&self, other: &Self) -> Self {
+        let column = self.column.max(other.column);
+        let row = self.row.max(other.row);
+        let right = (self.column + self.width).min(other.column + other.width);
+        let bottom = (self.row + self.height).min(other.row + other.height);
+        Self::new(
+            column,
+            row,
+            right.saturating_sub(column),
+            bottom.saturating_sub(row),
+        )
+    }
+}
+
+/// Foreground and background color of text.
+///
+/// Only the first 8 [`Color`]s can be used as background.
+#[derive(Debug, Copy, Clone)]
+pub struct Style {
+    /// Text color.
+    pub foreground: Color,
+    /// Background color.
+    pub background: Color,
+}
+
+impl Style {
+    /// Create a style.
+    #[must_use]
+    pub const fn new(foreground: Color, background: Color) -> Self {
+        Self {
+            foreground,
+            background,
+        }
+    }
+}
+
+/// Colors used by [`Tui`].
+#[derive(Debug, Copy, Clone)]
+pub struct Theme {
+    /// Text and background of windows.
+    pub window: Style,
+    /// Window borders.
+    pub border: Style,
+    /// Window titles.
+    pub title: Style,
+    /// Selected menu items and dialog buttons.
+    pub selected: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            window: Style::new(Color::LightGray, Color::Blue),
+            border: Style::new(Color::White, Color::Blue),
+            title: Style::new(Color::Yellow, Color::Blue),
+            selected: Style::new(Color::Black, Color::LightGray),
+        }
+    }
+}
+
+/// Characters used to draw borders, scroll markers and progress bars.
+struct Glyphs {
+    horizontal: u16,
+    vertical: u16,
+    top_left: u16,
+    top_right: u16,
+    bottom_left: u16,
+    bottom_right: u16,
+    up: u16,
+    down: u16,
+    full: u16,
+    empty: u16,
+}
+
+impl Glyphs {
+    const fn all(&self) -> [u16; 10] {
+        [
+            self.horizontal,
+            self.vertical,
+            self.top_left,
+            self.top_right,
+            self.bottom_left,
+            self.bottom_right,
+            self.up,
+            self.down,
+            self.full,
+            self.empty,
+        ]
+    }
+}
+
+static UNICODE_GLYPHS: Glyphs = Glyphs {
+    horizontal: 0x2500,
+    vertical: 0x2502,
+    top_left: 0x250c,
+    top_right: 0x2510,
+    bottom_left: 0x2514,
+    bottom_right: 0x2518,
+    up: 0x25b2,
+    down: 0x25bc,
+    full: 0x2588,
+    empty: 0x2591,
+};
+
+static ASCII_GLYPHS: Glyphs = Glyphs {
+    horizontal: b'-' as u16,
+    vertical: b'|' as u16,
+    top_left: b'+' as u16,
+    top_right: b'+' as u16,
+    bottom_left: b'+' as u16,
+    bottom_right: b'+' as u16,
+    up: b'^' as u16,
+    down: b'v' as u16,
+    full: b'#' as u16,
+    empty: b'.' as u16,
+};
+
+/// Draws widgets on the console. See the [module documentation].
+///
+/// [module documentation]: self
+pub struct Tui<'a>(Ui<UefiTerminal<'a>>);
+
+impl<'a> Tui<'a> {
+    /// Create a TUI on `output`, reading keys from `input`.
+    ///
+    /// This checks with [`Output::test_string`] whether box-drawing
+    /// characters can be displayed.
+    ///
+    /// [`Output::test_string`]: crate::proto::console::text::Output::test_string
+    #[must_use]
+    pub fn new(input: &'a mut Input, output: &'a mut Output) -> Self {
+        Self(Ui::new(UefiTerminal { input, output }))
+    }
+
+    /// Use the colors of `theme`.
+    #[must_use]
+    pub const fn with_theme(mut self, theme: Theme) -> Self {
+        self.0.theme = theme;
+        self
+    }
+
+    /// The whole screen of the current text mode.
+    #[must_use]
+    pub fn screen(&self) -> Rect {
+        self.0.screen()
+    }
+
+    /// Whether box-drawing characters are used, rather than ASCII.
+    #[must_use]
+    pub fn is_unicode(&self) -> bool {
+        core::ptr::eq(self.0.glyphs, &UNICODE_GLYPHS)
+    }
+
+    /// Fill `rect` with spaces in the window colors.
+    ///
+    /// # Errors
+    ///
+    /// Errors from writing to the console are returned.
+    pub fn clear(&mut self, rect: Rect) -> Result {
+        self.0.clear(rect)
+    }
+
+    /// Write `text` at `column` and `row` in the window colors. Text that
+    /// does not fit on the row is cut off.
+    ///
+    /// # Errors
+    ///
+    /// Errors from writing to the console are returned.
+    pub fn print(&mut self, column: usize, row: usize, text: &str) -> Result {
+        let style = self.0.theme.window;
+        self.0.put(column, row, &to_ucs2(text), style)
+    }
+
+    /// Draw a window with a border and a `title` in the top border, and
+    /// clear its interior. Returns the interior.
+    ///
+    /// # Errors
+    ///
+    /// Errors from writing to the console are returned.
+    pub fn window(&mut self, rect: Rect, title: &str) -> Result<Rect> {
+        self.0.window(rect, title)
+    }
+
+    /// Show a menu of `items` in a window, with `initial` selected, and
+    /// let the user pick one.
+    ///
+    /// The selection is moved with the arrow keys, Page Up, Page Down,
+    /// Home and End. The menu scrolls if it has more items than fit in the
+    /// window. Returns the index of the item chosen with Enter, or `None`
+    /// if Escape was pressed or there are no items.
+    ///
+    /// # Errors
+    ///
+    /// Errors from reading keys and writing to the console are returned.
+    pub fn menu<S: AsRef<str>>(
+        &mut self,
+        rect: Rect,
+        title: &str,
+        items: &[S],
+        initial: usize,
+    ) -> Result<Option<usize>> {
+        let items: Vec<_> = items.iter().map(|item| to_ucs2(item.as_ref())).collect();
+        self.0.menu(rect, title, &items, initial)
+    }
+
+    /// Show a dialog with `message` and Yes and No buttons, centered on
+    /// the screen, with Yes selected if `default` is true.
+    ///
+    /// The selection is changed with the left and right arrow keys or Tab,
+    /// and confirmed with Enter. Y and N choose a button directly, and
+    /// Escape chooses No. Long lines of the message are wrapped.
+    ///
+    /// # Errors
+    ///
+    /// Errors from reading keys and writing to the console are returned.
+    pub fn confirm(&mut self, title: &str, message: &str, default: bool) -> Result<bool> {
+        self.0.confirm(title, message, default)
+    }
+
+    /// Draw a progress bar of `width` cells at `column` and `row`, showing
+    /// that `done` out of `total` units of work are complete. The bar is
+    /// followed by the percentage.
+    ///
+    /// # Errors
+    ///
+    /// Errors from writing to the console are returned.
+    pub fn progress_bar(
+        &mut self,
+        column: usize,
+        row: usize,
+        width: usize,
+        done: u64,
+        total: u64,
+    ) -> Result {
+        self.0.progress_bar(column, row, width, done, total)
+    }
+}
+
+impl Debug for Tui<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tui")
+            .field("screen", &self.screen())
+            .field("unicode", &self.is_unicode())
+            .field("theme", &self.0.theme)
+            .finish()
+    }
+}
+
+/// The implementation of [`Tui`] on any [`Terminal`].
+struct Ui<T> {
+    term: T,
+    glyphs: &'static Glyphs,
+    theme: Theme,
+}
+
+impl<T: Terminal> Ui<T> {
+    fn new(mut term: T) -> Self {
+        let unicode = UNICODE_GLYPHS.all().map(c16);
+        let glyphs = if term.supports(&unicode) {
+            &UNICODE_GLYPHS
+        } else {
+            &ASCII_GLYPHS
+        };
+        Self {
+            term,
+            glyphs,
+            theme: Theme::default(),
+        }
+    }
+
+    fn screen(&self) -> Rect {
+        let (columns, rows) = self.term.size();
+        Rect::new(0, 0, columns, rows)
+    }
+
+    /// Write `text` at a position, clipped to the screen.
+    fn put(&mut self, column: usize, row: usize, text: &[Char16], style: Style) -> Result {
+        let (columns, rows) = self.term.size();
+        if column >= columns || row >= rows {
+            return Ok(());
+        }
+        let mut len = columns - column;
+        // Writing to the last cell of the screen scrolls it.
+        if row == rows - 1 {
+            len -= 1;
+        }
+        let text = &text[..text.len().min(len)];
+        if text.is_empty() {
+            return Ok(());
+        }
+        self.term.set_cursor_position(column, row)?;
+        self.term.set_color(style.foreground, style.background)?;
+        self.term.write(text)
+    }
+
+    fn clear(&mut self, rect: Rect) -> Result {
+        let rect = rect.intersect(&self.screen());
+        let blank = vec![c16(b' '.into()); rect.width];
+        for row in rect.row..rect.row + rect.height {
+            self.put(rect.column, row, &blank, self.theme.window)?;
+        }
+        Ok(())
+    }
+
+    fn window(&mut self, rect: Rect, title: &str) -> Result<Rect> {
+        let rect = rect.intersect(&self.screen());
+        if rect.width < 2 || rect.height < 2 {
+            self.clear(rect)?;
+            return Ok(rect.inner());
+        }
+        let g = self.glyphs;
+        let inner_width = rect.width - 2;
+        let border = self.theme.border;
+
+        let mut line = vec![c16(g.horizontal); rect.width];
+        line[0] = c16(g.top_left);
+        line[rect.width - 1] = c16(g.top_right);
+        self.put(rect.column, rect.row, &line, border)?;
+
+        line[0] = c16(g.bottom_left);
+        line[rect.width - 1] = c16(g.bottom_right);
+        self.put(rect.column, rect.row + rect.height - 1, &line, border)?;
+
+        let vertical = [c16(g.vertical)];
+        for row in rect.row + 1..rect.row + rect.height - 1 {
+            self.put(rect.column, row, &vertical, border)?;
+            self.put(rect.column + rect.width - 1, row, &vertical, border)?;
+        }
+        self.clear(rect.inner())?;
+
+        if !title.is_empty() && inner_width > 2 {
+            let mut text = vec![c16(b' '.into())];
+            text.extend(to_ucs2(title));
+            text.push(c16(b' '.into()));
+            text.truncate(inner_width - 1);
+            self.put(rect.column + 2, rect.row, &text, self.theme.title)?;
+        }
+        Ok(rect.inner())
+    }
+
+    /// Draw `text` padded with spaces to `width`.
+    fn put_padded(
+        &mut self,
+        column: usize,
+        row: usize,
+        width: usize,
+        text: &[Char16],
+        style: Style,
+    ) -> Result {
+        let mut line = vec![c16(b' '.into()); width];
+        let len = text.len().min(width);
+        line[..len].copy_from_slice(&text[..len]);
+        self.put(column, row, &line, style)
+    }
+
+    /// Run `f` with the cursor hidden.
+    fn without_cursor<R>(&mut self, f: impl FnOnce(&mut Self) -> Result<R>) -> Result<R> {
+        let visible = self.term.cursor_visible();
+        // Not all consoles can hide the cursor.
+        let _ = self.term.enable_cursor(false);
+        let result = f(self);
+        if visible {
+            let _ = self.term.enable_cursor(true);
+        }
+        result
+    }
+
+    fn menu(
+        &mut self,
+        rect: Rect,
+        title: &str,
+        items: &[Vec<Char16>],
+        initial: usize,
+    ) -> Result<Option<usize>> {
+        let inner = self.window(rect, title)?;
+        if inner.is_empty() || items.is_empty() {
+            return Ok(None);
+        }
+        let page = inner.height;
+        let last = items.len() - 1;
+        let mut selected = initial.min(last);
+        let mut top = 0;
+
+        self.without_cursor(|ui| loop {
+            if selected < top {
+                top = selected;
+            } else if selected >= top + page {
+                top = selected + 1 - page;
+            }
+
+            for i in 0..page {
+                let index = top + i;
+                let style = if index == selected {
+                    ui.theme.selected
+                } else {
+                    ui.theme.window
+                };
+                let mut text = vec![c16(b' '.into())];
+                if let Some(item) = items.get(index) {
+                    text.extend_from_slice(item);
+                }
+                ui.put_padded(inner.column, inner.row + i, inner.width, &text, style)?;
+            }
+
+            // Scroll markers in the right border.
+            let border_column = inner.column + inner.width;
+            let g = ui.glyphs;
+            let up = if top > 0 { g.up } else { g.vertical };
+            let down = if top + page <= last {
+                g.down
+            } else {
+                g.vertical
+            };
+            ui.put(border_column, inner.row, &[c16(up)], ui.theme.border)?;
+            ui.put(
+                border_column,
+                inner.row + page - 1,
+                &[c16(down)],
+                ui.theme.border,
+            )?;
+
+            match ui.term.read_key()? {
+                Key::Special(ScanCode::UP) => selected = selected.saturating_sub(1),
+                Key::Special(ScanCode::DOWN) => selected = (selected + 1).min(last),
+                Key::Special(ScanCode::PAGE_UP) => selected = selected.saturating_sub(page),
+                Key::Special(ScanCode::PAGE_DOWN) => selected = (selected + page).min(last),
+                Key::Special(ScanCode::HOME) => selected = 0,
+                Key::Special(ScanCode::END) => selected = last,
+                Key::Special(ScanCode::ESCAPE) => return Ok(None),
+                Key::Printable(c) if u16::from(c) == CARRIAGE_RETURN => return Ok(Some(selected)),
+                _ => {}
+            }
+        })
+    }
+
+    fn confirm(&mut self, title: &str, message: &str, default: bool) -> Result<bool> {
+        const YES: &str = "< Yes >";
+        const NO: &str = "< No >";
+        const BUTTONS_WIDTH: usize = YES.len() + 2 + NO.len();
+
+        let screen = self.screen();
+        let max_text_width = screen.width.saturating_sub(6).max(1);
+        let lines = wrap(&to_ucs2(message), max_text_width);
+        let text_width = lines
+            .iter()
+            .map(Vec::len)
+            .chain([title.chars().count() + 2, BUTTONS_WIDTH])
+            .max()
+            .unwrap_or(0);
+        let rect = screen.centered(text_width + 4, lines.len() + 4);
+        let inner = self.window(rect, title)?;
+        if inner.is_empty() {
+            return Ok(default);
+        }
+
+        for (i, line) in lines.iter().enumerate().take(inner.height - 1) {
+            self.put(inner.column + 1, inner.row + i, line, self.theme.window)?;
+        }
+
+        let button_row = inner.row + inner.height - 1;
+        let yes_column = inner.column + inner.width.saturating_sub(BUTTONS_WIDTH) / 2;
+        let no_column = yes_column + YES.len() + 2;
+        let mut yes = default;
+        self.without_cursor(|ui| loop {
+            let (yes_style, no_style) = if yes {
+                (ui.theme.selected, ui.theme.window)
+            } else {
+                (ui.theme.window, ui.theme.selected)
+            };
+            ui.put(yes_column, button_row, &to_ucs2(YES), yes_style)?;
+            ui.put(no_column, button_row, &to_ucs2(NO), no_style)?;
+
+            match ui.term.read_key()? {
+                Key::Special(ScanCode::LEFT | ScanCode::RIGHT) => yes = !yes,
+                Key::Special(ScanCode::ESCAPE) => return Ok(false),
+                Key::Printable(c) => match char::from(c) {
+                    '\r' => return Ok(yes),
+                    'y' | 'Y' => return Ok(true),
+                    'n' | 'N' => return Ok(false),
+                    _ if u16::from(c) == TAB => yes = !yes,
+                    _ => {}
+                },
+                Key::Special(_) => {}
+            }
+        })
+    }
+
+    fn progress_bar(
+        &mut self,
+        column: usize,
+        row: usize,
+        width: usize,
+        done: u64,
+        total: u64,
+    ) -> Result {
+        let percent = if total == 0 {
+            0
+        } else {
+            (u128::from(done.min(total)) * 100 / u128::from(total)) as usize
+        };
+        // The bar is followed by a space and up to "100%".
+        let bar_width = width.saturating_sub(5);
+        let filled = bar_width * percent / 100;
+
+        let mut text = vec![c16(self.glyphs.full); filled];
+        text.resize(bar_width, c16(self.glyphs.empty));
+        text.extend(to_ucs2(&alloc::format!("{percent:>4}%")));
+        text.truncate(width);
+        self.put(column, row, &text, self.theme.window)
+    }
+}
+
+/// Convert a character known to be valid UCS-2.
+fn c16(c: u16) -> Char16 {
+    Char16::try_from(c).unwrap()
+}
+
+/// Convert `s` to UCS-2, replacing characters outside of the BMP.
+fn to_ucs2(s: &str) -> Vec<Char16> {
+    s.chars()
+        .map(|c| Char16::try_from(c).unwrap_or_else(|_| c16(0xfffd)))
+        .collect()
+}
+
+/// Split `text` into lines of at most `width` characters, breaking at
+/// line feeds and, where possible, at spaces.
+fn wrap(text: &[Char16], width: usize) -> Vec<Vec<Char16>> {
+    let mut lines = Vec::new();
+    for mut line in text.split(|&c| c == '\n') {
+        loop {
+            if line.len() <= width {
+                lines.push(line.to_vec());
+                break;
+            }
+            let split = line[..=width]
+                .iter()
+                .rposition(|&c| c == ' ')
+                .filter(|&i| i > 0);
+            match split {
+                Some(i) => {
+                    lines.push(line[..i].to_vec());
+                    line = &line[i + 1..];
+                }
+                None => {
+                    lines.push(line[..width].to_vec());
+                    line = &line[width..];
+                }
+            }
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::terminal::tests::FakeTerminal;
+    use alloc::string::String;
+
+    fn ui(columns: usize, rows: usize) -> Ui<FakeTerminal> {
+        Ui::new(FakeTerminal::new(columns, rows))
+    }
+
+    fn special(ui: &mut Ui<FakeTerminal>, scan_code: ScanCode) {
+        ui.term.keys.push_back(Key::Special(scan_code));
+    }
+
+    fn items(items: &[&str]) -> Vec<Vec<Char16>> {
+        items.iter().map(|s| to_ucs2(s)).collect()
+    }
+
+    #[test]
+    fn test_rect() {
+        let screen = Rect::new(0, 0, 80, 25);
+        assert_eq!(screen.centered(40, 5), Rect::new(20, 10, 40, 5));
+        assert_eq!(screen.centered(100, 30), screen);
+        assert_eq!(Rect::new(1, 1, 2, 1).inner(), Rect::new(2, 2, 0, 0));
+        assert!(Rect::new(1, 1, 2, 1).inner().is_empty());
+        assert_eq!(
+            Rect::new(70, 20, 20, 10).intersect(&screen),
+            Rect::new(70, 20, 10, 5)
+        );
+        assert!(Rect::new(90, 0, 5, 5).intersect(&screen).is_empty());
+    }
+
+    #[test]
+    fn test_window() {
+        let mut ui = ui(12, 5);
+        assert!(core::ptr::eq(ui.glyphs, &UNICODE_GLYPHS));
+        let inner = ui.window(Rect::new(1, 1, 10, 4), "Title").unwrap();
+        assert_eq!(inner, Rect::new(2, 2, 8, 2));
+        assert_eq!(ui.term.row(1), " ┌─ Title ┐");
+        assert_eq!(ui.term.row(2), " │        │");
+        assert_eq!(ui.term.row(4), " └────────┘");
+        assert_eq!(ui.term.attributes[1][4], 0x1e);
+        assert_eq!(ui.term.attributes[2][4], 0x17);
+    }
+
+    #[test]
+    fn test_ascii_and_clipping() {
+        let mut term = FakeTerminal::new(8, 3);
+        term.max_char = 0x7f;
+        let mut ui = Ui::new(term);
+        assert!(core::ptr::eq(ui.glyphs, &ASCII_GLYPHS));
+
+        // The window is clipped to the screen, and the last cell of the
+        // screen is not written.
+        ui.window(Rect::new(4, 0, 10, 10), "A long title").unwrap();
+        assert_eq!(ui.term.row(0), "    +--+");
+        assert_eq!(ui.term.row(1), "    |  |");
+        assert_eq!(ui.term.row(2), "    +--");
+    }
+
+    #[test]
+    fn test_menu() {
+        let mut ui = ui(20, 6);
+        let items = items(&["one", "two", "three", "four", "five"]);
+        special(&mut ui, ScanCode::DOWN);
+        special(&mut ui, ScanCode::DOWN);
+        special(&mut ui, ScanCode::DOWN);
+        special(&mut ui, ScanCode::UP);
+        ui.term.type_str("\r");
+        let choice = ui.menu(Rect::new(0, 0, 12, 5), "Menu", &items, 0).unwrap();
+        assert_eq!(choice, Some(2));
+
+        // The menu scrolled to show the selection.
+        assert_eq!(ui.term.row(1), "│ two      ▲");
+        assert_eq!(ui.term.row(2), "│ three    │");
+        assert_eq!(ui.term.row(3), "│ four     ▼");
+        assert_eq!(ui.term.attributes[2][2], 0x70);
+        assert!(ui.term.cursor_visible);
+
+        special(&mut ui, ScanCode::END);
+        special(&mut ui, ScanCode::PAGE_UP);
+        special(&mut ui, ScanCode::ESCAPE);
+        assert_eq!(
+            ui.menu(Rect::new(0, 0, 12, 5), "Menu", &items, 1).unwrap(),
+            None
+        );
+
+        special(&mut ui, ScanCode::END);
+        special(&mut ui, ScanCode::PAGE_UP);
+        ui.term.type_str("\r");
+        assert_eq!(
+            ui.menu(Rect::new(0, 0, 12, 5), "Menu", &items, 1).unwrap(),
+            Some(1)
+        );
+
+        assert_eq!(
+            ui.menu(Rect::new(0, 0, 12, 5), "", &items[..0], 0).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_confirm() {
+        let mut ui = ui(30, 10);
+        special(&mut ui, ScanCode::RIGHT);
+        ui.term.type_str("\r");
+        assert!(!ui.confirm("Q", "Really continue with this?", true).unwrap());
+        let rows: Vec<String> = (0..10).map(|row| ui.term.row(row)).collect();
+        assert_eq!(rows[2], "   ┌─ Q ──────────────────┐");
+        assert_eq!(rows[3], "   │ Really continue with │");
+        assert_eq!(rows[4], "   │ this?                │");
+        assert_eq!(rows[6], "   │   < Yes >  < No >    │");
+        assert_eq!(ui.term.attributes[6][10], 0x17);
+        assert_eq!(ui.term.attributes[6][18], 0x70);
+
+        ui.term.type_str("\t\r");
+        assert!(ui.confirm("Q", "Sure?", false).unwrap());
+        ui.term.type_str("y");
+        assert!(ui.confirm("Q", "Sure?", false).unwrap());
+        special(&mut ui, ScanCode::ESCAPE);
+        assert!(!ui.confirm("Q", "Sure?", true).unwrap());
+    }
+
+    #[test]
+    fn test_progress_bar() {
+        let mut ui = ui(20, 3);
+        ui.progress_bar(0, 0, 15, 1, 2).unwrap();
+        assert_eq!(ui.term.row(0), "█████░░░░░  50%");
+        ui.progress_bar(0, 1, 15, 3, 0).unwrap();
+        assert_eq!(ui.term.row(1), "░░░░░░░░░░   0%");
+        ui.progress_bar(0, 1, 15, 30, 20).unwrap();
+        assert_eq!(ui.term.row(1), "██████████ 100%");
+    }
+
+    #[test]
+    fn test_wrap() {
+        let lines = |text: &str, width| -> Vec<String> {
+            wrap(&to_ucs2(text), width)
+                .iter()
+                .map(|line| line.iter().copied().map(char::from).collect())
+                .collect()
+        };
+        assert_eq!(lines("a b c d", 3), ["a b", "c d"]);
+        assert_eq!(lines("abcdefg", 3), ["abc", "def", "g"]);
+        assert_eq!(lines("ab\ncd", 10), ["ab", "cd"]);
+        assert_eq!(lines("", 10), [""]);
+    }
+}
@@ -33,6 +33,48 @@ pub fn opt_nonnull_to_ptr<T>(opt: Option<NonNull<T>>) -> *mut T {
     opt.map(NonNull::as_ptr).unwrap_or(ptr::null_mut())
 }
 
+/// Lookup table for [`crc32`], one entry per byte value.
+#[cfg(feature = "alloc")]
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Update a running CRC32 with `data`. Start with `0` and feed the
+/// result of each call into the next.
+///
+/// This is the same CRC32 (IEEE 802.3) that is used by the UEFI
+/// `CalculateCrc32` boot service, but is implemented in Rust so that it
+/// can be used without boot services and in host tests.
+#[cfg(feature = "alloc")]
+pub fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+    let crc = data.iter().fold(!crc, |crc, b| {
+        CRC32_TABLE[usize::from((crc as u8) ^ b)] ^ (crc >> 8)
+    });
+    !crc
+}
+
+/// Calculate the CRC32 of `data`. See [`crc32_update`].
+#[cfg(feature = "alloc")]
+pub fn crc32(data: &[u8]) -> u32 {
+    crc32_update(0, data)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -42,4 +84,12 @@ mod tests {
         assert_eq!(usize_from_u32(0), 0usize);
         assert_eq!(usize_from_u32(u32::MAX), 4294967295usize);
     }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32_update(crc32(b"1234"), b"56789"), 0xcbf4_3926);
+    }
 }
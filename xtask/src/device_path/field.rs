@@ -3,7 +3,7 @@
 use crate::device_path::util::is_doc_attr;
 use proc_macro2::{Span, TokenStream};
 use quote::{quote, ToTokens, TokenStreamExt};
-use syn::{Attribute, Expr, ExprLit, Field, Ident, Lit, Path, Type, TypeArray};
+use syn::{Attribute, Expr, ExprLit, Field, Ident, Lit, LitStr, Path, Type, TypeArray};
 
 /// A fixed-size non-array type.
 ///
@@ -74,12 +74,22 @@ impl BaseType {
     }
 
     pub fn is_u8(&self) -> bool {
+        self.is_ident("u8")
+    }
+
+    fn is_ident(&self, name: &str) -> bool {
         if let Some(ident) = self.path.get_ident() {
-            ident == "u8"
+            ident == name
         } else {
             false
         }
     }
+
+    fn is_int(&self) -> bool {
+        ["u8", "u16", "u32", "u64"]
+            .iter()
+            .any(|name| self.is_ident(name))
+    }
 }
 
 impl ToTokens for BaseType {
@@ -291,6 +301,52 @@ impl NodeField {
         ))
     }
 
+    /// Whether the field uses the default getter and builder code, which
+    /// is required for fields with a generated text representation.
+    pub fn has_default_impls(&self) -> bool {
+        matches!(self.attr.build_type, BuildType::Packed)
+            && self.attr.get_func == GetFunc::Auto
+            && !self.attr.custom_build_impl
+            && !self.attr.custom_build_size_impl
+    }
+
+    /// Get the kind used to format and parse the field in the text
+    /// representation of a node. This is the name of a type in
+    /// `uefi::proto::device_path::text::field::kind`.
+    pub fn text_kind(&self) -> Ident {
+        self.kind()
+            .unwrap_or_else(|| panic!("no default text kind for field: {}", self.name))
+    }
+
+    /// Get the kind of the field, which selects how the field is
+    /// converted to and from text, and (with the `serde` feature) how
+    /// it is serialized. Returns `None` if the field type has no default
+    /// kind and the kind is not set with the `text_kind` attribute.
+    pub fn kind(&self) -> Option<Ident> {
+        if let Some(kind) = &self.attr.text_kind {
+            return Some(kind.clone());
+        }
+
+        let kind = match &self.packed_ty {
+            PackedType::Base(base) if base.is_int() => "Hex",
+            PackedType::Base(base) if base.is_ident("Guid") => "Guid",
+            PackedType::Base(_) => "Enum",
+            PackedType::Array(base, _) | PackedType::Slice(base) if base.is_u8() => "HexBytes",
+            PackedType::Slice(base) if base.is_ident("u16") => "Str",
+            _ => return None,
+        };
+        Some(Ident::new(kind, Span::call_site()))
+    }
+
+    /// Get the owned type that a text argument is parsed (or a field
+    /// is deserialized) into. DST fields are parsed into a `Vec`.
+    pub fn text_parse_ty(&self) -> TokenStream {
+        match &self.packed_ty {
+            PackedType::Slice(base) => quote!(Vec<#base>),
+            packed_ty => quote!(#packed_ty),
+        }
+    }
+
     /// Generate code to calculate the size of DST fields. Returns
     /// `None` for non-DST fields.
     pub fn gen_builder_dynamic_size(&self) -> Option<TokenStream> {
@@ -339,6 +395,10 @@ pub struct FieldNodeAttr {
     /// field when building a node calls a custom method named
     /// `build_size_<field>`. False by default.
     pub custom_build_size_impl: bool,
+
+    /// Kind used to format and parse the field in the text
+    /// representation. Inferred from the field type by default.
+    text_kind: Option<Ident>,
 }
 
 impl Default for FieldNodeAttr {
@@ -348,6 +408,7 @@ impl Default for FieldNodeAttr {
             build_type: BuildType::Packed,
             custom_build_impl: false,
             custom_build_size_impl: false,
+            text_kind: None,
         }
     }
 }
@@ -373,6 +434,10 @@ impl FieldNodeAttr {
                 out.custom_build_impl = true;
             } else if path.is_ident("custom_build_size_impl") {
                 out.custom_build_size_impl = true;
+            } else if path.is_ident("text_kind") {
+                let value = meta.value()?;
+                let lit: LitStr = value.parse()?;
+                out.text_kind = Some(Ident::new(&lit.value(), Span::call_site()));
             } else if path.is_ident("build_type") {
                 let value = meta.value()?;
                 let lit: Lit = value.parse()?;
@@ -176,6 +176,37 @@ impl NodeGroup {
             })
         });
 
+        let variant_names: Vec<_> = groups
+            .iter()
+            .flat_map(|module| {
+                module
+                    .nodes
+                    .iter()
+                    .map(|node| variant_name(module, node).to_string())
+            })
+            .collect();
+
+        let serialize_arms = groups
+            .iter()
+            .flat_map(|module| module.nodes.iter().map(|node| variant_name(module, node)))
+            .enumerate()
+            .map(|(index, variant_name)| {
+                let index = u32::try_from(index).unwrap();
+                let name = variant_name.to_string();
+                quote!(
+                    Self::#variant_name(node) => serializer.serialize_newtype_variant(
+                        "DevicePathNode", #index, #name, node,
+                    )
+                )
+            });
+
+        let display_arms = groups.iter().flat_map(|module| {
+            module.nodes.iter().map(|node| {
+                let variant_name = variant_name(module, node);
+                quote!(Self::#variant_name(node) => fmt::Display::fmt(node, f))
+            })
+        });
+
         quote!(
             /// Enum of references to all the different device path node
             /// types. Return type of [`DevicePathNode::as_enum`].
@@ -184,6 +215,30 @@ impl NodeGroup {
                 #(#variants),*
             }
 
+            impl fmt::Display for DevicePathNodeEnum<'_> {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    match self {
+                        #(#display_arms),*
+                    }
+                }
+            }
+
+            /// Serializes the node as an enum variant with the same name as
+            /// the `DevicePathNodeEnum` variant.
+            #[cfg(feature = "serde")]
+            impl serde::Serialize for DevicePathNodeEnum<'_> {
+                fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    match self {
+                        #(#serialize_arms),*
+                    }
+                }
+            }
+
+            /// Names of the [`DevicePathNodeEnum`] variants, in order,
+            /// followed by the name used for unknown nodes.
+            #[cfg(feature = "serde")]
+            pub(super) const NODE_VARIANT_NAMES: &[&str] = &[#(#variant_names,)* "Unknown"];
+
             impl TryFrom<&DevicePathNode> for DevicePathNodeEnum<'_> {
                 type Error = NodeConversionError;
 
@@ -196,6 +251,74 @@ impl NodeGroup {
             }
         )
     }
+
+    /// Generate the `node_from_text` function, which parses the
+    /// arguments of all nodes that have a text template.
+    pub fn gen_node_from_text(groups: &[NodeGroup]) -> TokenStream {
+        let arms = groups
+            .iter()
+            .flat_map(|module| module.nodes.iter().filter_map(Node::gen_text_parse_arm));
+
+        quote!(
+            /// Parse the arguments of a node with the text name `name`
+            /// and append the node to `out`. Returns `false` if `name`
+            /// is not the name of a node with generated text conversion.
+            #[cfg(feature = "alloc")]
+            pub(super) fn node_from_text(
+                name: &str,
+                args: crate::proto::device_path::text::parse::TextArgs<'_>,
+                out: &mut alloc::vec::Vec<u8>,
+            ) -> Result<bool, crate::proto::device_path::text::ParseDevicePathError> {
+                use crate::proto::device_path::text::field::ParseField;
+                use crate::proto::device_path::text::parse::push_node;
+                use alloc::vec::Vec;
+
+                match name {
+                    #(#arms)*
+                    _ => return Ok(false),
+                }
+                Ok(true)
+            }
+        )
+    }
+
+    /// Generate the `node_from_serde` function, which deserializes the
+    /// fields of all nodes that have generated `serde` impls.
+    pub fn gen_node_from_serde(groups: &[NodeGroup]) -> TokenStream {
+        let arms = groups.iter().flat_map(|module| {
+            module.nodes.iter().filter_map(|node| {
+                let variant_name = Ident::new(
+                    &format!("{}{}", module.device_type.camel_name(), node.struct_ident),
+                    Span::call_site(),
+                );
+                node.gen_serde_de_arm(&variant_name)
+            })
+        });
+
+        quote!(
+            /// Deserialize the fields of the node whose `DevicePathNodeEnum`
+            /// variant is named `name` and append the node to `out`. Nodes
+            /// without generated `serde` impls are passed on to
+            /// `custom_node_from_serde`.
+            #[cfg(all(feature = "serde", feature = "alloc"))]
+            pub(super) fn node_from_serde<'de, A: serde::de::VariantAccess<'de>>(
+                name: &str,
+                variant: A,
+                out: &mut crate::proto::device_path::DevicePathBuf,
+            ) -> Result<(), A::Error> {
+                use crate::proto::device_path::serde_impls::field::Deserialized;
+                use crate::proto::device_path::serde_impls::node::custom_node_from_serde;
+                use alloc::vec::Vec;
+                use serde::de::Error;
+
+                match name {
+                    #(#arms)*
+                    _ => return custom_node_from_serde(name, variant, out),
+                }
+                Ok(())
+            }
+        )
+    }
 }
 
 fn is_build_attr(attr: &Attribute) -> bool {
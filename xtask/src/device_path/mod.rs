@@ -68,6 +68,8 @@ fn gen_uefi_raw_code_as_string(groups: &[NodeGroup]) -> Result<String> {
 fn gen_uefi_code_as_string(groups: &[NodeGroup]) -> Result<String> {
     let packed_modules = groups.iter().map(NodeGroup::gen_packed_module);
     let node_enum = NodeGroup::gen_node_enum(groups);
+    let node_from_text = NodeGroup::gen_node_from_text(groups);
+    let node_from_serde = NodeGroup::gen_node_from_serde(groups);
     let build_modules = groups.iter().map(NodeGroup::gen_builder_module);
 
     let code = quote!(
@@ -79,6 +81,9 @@ fn gen_uefi_code_as_string(groups: &[NodeGroup]) -> Result<String> {
             self, DevicePathHeader, DevicePathNode, DeviceSubType, DeviceType,
             NodeConversionError,
         };
+        use crate::proto::device_path::text::field::{kind, DebugAscii, DebugUcs2, FormatField};
+        #[cfg(feature = "serde")]
+        use crate::proto::device_path::serde_impls::field::SerdeField;
         use crate::proto::network::IpAddress;
         use crate::mem::memory_map::MemoryType;
         use core::mem::{size_of, size_of_val};
@@ -90,6 +95,10 @@ fn gen_uefi_code_as_string(groups: &[NodeGroup]) -> Result<String> {
 
         #node_enum
 
+        #node_from_text
+
+        #node_from_serde
+
         /// Build device paths from their component nodes.
         pub mod build {
             use super::*;
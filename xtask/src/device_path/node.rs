@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use super::field::NodeField;
+use super::field::{GetFunc, NodeField};
 use super::group::DeviceType;
 use crate::device_path::util::is_doc_attr;
 use heck::ToShoutySnakeCase;
@@ -28,6 +28,59 @@ pub struct Node {
     /// Size (in bytes) of the node, including the four-byte
     /// header. Dynamically-sized fields are treated as zero bytes.
     static_size: usize,
+
+    /// Text representation of the node. If not set, the text
+    /// conversion must be implemented manually.
+    text: Option<NodeText>,
+}
+
+/// Text representation of a node, e.g. `Pci(device,function)`.
+struct NodeText {
+    /// Name of the node in the text representation.
+    name: String,
+
+    /// Arguments in the order they appear in the text representation.
+    args: Vec<NodeTextArg>,
+}
+
+struct NodeTextArg {
+    /// Name of the field.
+    field: Ident,
+
+    /// Whether the argument is left out if the field is empty. Marked
+    /// with a trailing `?` in the template.
+    optional: bool,
+}
+
+impl NodeText {
+    /// Parse a text template like `VenHw(vendor_guid,vendor_defined_data?)`.
+    fn parse(template: &str) -> Self {
+        let (name, args) = template
+            .strip_suffix(')')
+            .and_then(|t| t.split_once('('))
+            .unwrap_or_else(|| panic!("invalid text template: {template}"));
+
+        let args = args
+            .split(',')
+            .map(str::trim)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| {
+                let (field, optional) = match arg.strip_suffix('?') {
+                    Some(field) => (field, true),
+                    None => (arg, false),
+                };
+                NodeTextArg {
+                    field: Ident::new(field, Span::call_site()),
+                    optional,
+                }
+            })
+            .collect();
+
+        Self {
+            name: name.to_owned(),
+            args,
+        }
+    }
 }
 
 impl Node {
@@ -46,6 +99,7 @@ impl Node {
             struct_ident,
             fields: Vec::new(),
             static_size: 0,
+            text: None,
         };
 
         for attr in &struct_item.attrs {
@@ -54,6 +108,7 @@ impl Node {
                 if let Some(st) = attr.sub_type {
                     out.sub_type = Ident::new(&st, Span::call_site());
                 }
+                out.text = attr.text.as_deref().map(NodeText::parse);
             } else if is_doc_attr(attr) {
                 out.docs.push(attr.clone());
             } else {
@@ -72,9 +127,73 @@ impl Node {
         // that no fields were left out of the node specification.
         assert_eq!(out.calculate_static_size(), out.static_size);
 
+        out.check_text();
+
         out
     }
 
+    /// Check that the text template refers to each visible field
+    /// exactly once, and that all of those fields can be formatted and
+    /// parsed with the generated code.
+    fn check_text(&self) {
+        let Some(text) = &self.text else {
+            return;
+        };
+
+        for field in &self.fields {
+            if field.is_hidden() {
+                continue;
+            }
+            let count = text.args.iter().filter(|a| a.field == field.name).count();
+            assert_eq!(
+                count, 1,
+                "{}: field {} must appear exactly once in the text template",
+                self.struct_ident, field.name
+            );
+            assert!(
+                field.has_default_impls(),
+                "{}: field {} needs a custom text implementation",
+                self.struct_ident,
+                field.name
+            );
+        }
+
+        for (index, arg) in text.args.iter().enumerate() {
+            let field = self.field(&arg.field);
+            assert!(
+                !field.is_hidden(),
+                "{}: hidden field {} in text template",
+                self.struct_ident,
+                field.name
+            );
+            if arg.optional {
+                assert!(
+                    field.is_slice() && text.args[index..].iter().all(|a| a.optional),
+                    "{}: only trailing slice fields can be optional",
+                    self.struct_ident
+                );
+            }
+        }
+    }
+
+    fn field(&self, name: &Ident) -> &NodeField {
+        self.fields
+            .iter()
+            .find(|field| field.name == *name)
+            .unwrap_or_else(|| panic!("{}: unknown field {name}", self.struct_ident))
+    }
+
+    /// Whether the `serde` impls for the node can be generated. This
+    /// requires all visible fields to use the default getter and
+    /// builder code, and to have a kind. Other nodes are serialized by
+    /// hand-written code in `uefi::proto::device_path::serde_impls`.
+    fn has_serde_impl(&self) -> bool {
+        self.fields
+            .iter()
+            .filter(|field| !field.is_hidden())
+            .all(|field| field.has_default_impls() && field.kind().is_some())
+    }
+
     fn is_dst(&self) -> bool {
         if let Some(last) = self.fields.last() {
             last.is_slice()
@@ -187,21 +306,42 @@ impl Node {
     }
 
     /// Generate a `fmt::Debug` impl for the packed struct.
+    ///
+    /// Fields with a getter are formatted using the getter's return
+    /// value, with string fields formatted as strings. Other fields are
+    /// formatted from the raw packed data. Hidden fields are skipped.
     fn gen_packed_struct_debug_impl(&self) -> TokenStream {
         let struct_ident = &self.struct_ident;
         let struct_name = struct_ident.to_string();
 
-        let mut field_calls: Vec<_> = self
+        let mut needs_data = false;
+        let field_calls: Vec<_> = self
             .fields
             .iter()
             .filter_map(|field| {
+                if field.is_hidden() {
+                    return None;
+                }
+
                 let field_ident = &field.name;
                 let field_name = field_ident.to_string();
                 let field_val = quote!(self.#field_ident);
                 let slice_elem_ty = field.slice_elem_ty();
 
-                let dbg_val = if field.is_slice() {
+                let dbg_val = if field.attr.get_func != GetFunc::None {
+                    let kind = field.kind().map(|kind| kind.to_string());
+                    match kind.as_deref() {
+                        Some("Str") => quote!(&DebugUcs2(self.#field_ident())),
+                        // Slices are returned by reference, arrays by value.
+                        Some("Ascii" | "CStr8") if field.is_slice() => {
+                            quote!(&DebugAscii(self.#field_ident()))
+                        }
+                        Some("Ascii" | "CStr8") => quote!(&DebugAscii(&self.#field_ident())),
+                        _ => quote!(&self.#field_ident()),
+                    }
+                } else if field.is_slice() {
                     if self.has_dst_group() {
+                        needs_data = true;
                         return None;
                     }
 
@@ -224,15 +364,18 @@ impl Node {
             })
             .collect();
 
-        if self.has_dst_group() {
-            field_calls.push(quote!(field("data", &&self.data)));
-        }
+        let data_call = if needs_data {
+            Some(quote!(.field("data", &&self.data)))
+        } else {
+            None
+        };
 
         quote!(
             impl fmt::Debug for #struct_ident {
                 fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
                     f.debug_struct(#struct_name)
                         #(.#field_calls)*
+                        #data_call
                         .finish()
                 }
             }
@@ -295,15 +438,204 @@ impl Node {
         )
     }
 
+    /// Generate a `fmt::Display` impl for the packed struct that writes
+    /// the text representation of the node. Returns `None` if the node
+    /// has no text template.
+    fn gen_packed_struct_display_impl(&self) -> Option<TokenStream> {
+        let text = self.text.as_ref()?;
+        let struct_ident = &self.struct_ident;
+        let open = format!("{}(", text.name);
+
+        let args = text.args.iter().enumerate().map(|(index, arg)| {
+            let field_ident = &arg.field;
+            let kind = self.field(field_ident).text_kind();
+            let sep = if index == 0 {
+                None
+            } else {
+                Some(quote!(f.write_str(",")?;))
+            };
+
+            if arg.optional {
+                quote!(
+                    let value = self.#field_ident();
+                    if !value.is_empty() {
+                        #sep
+                        <kind::#kind as FormatField<_>>::fmt_field(&value, f)?;
+                    }
+                )
+            } else {
+                quote!(
+                    #sep
+                    <kind::#kind as FormatField<_>>::fmt_field(&self.#field_ident(), f)?;
+                )
+            }
+        });
+
+        Some(quote!(
+            impl fmt::Display for #struct_ident {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.write_str(#open)?;
+                    #(#args)*
+                    f.write_str(")")
+                }
+            }
+        ))
+    }
+
+    /// Generate a match arm for `node_from_text` that parses the
+    /// arguments of the node and appends the built node. Returns `None`
+    /// if the node has no text template.
+    pub fn gen_text_parse_arm(&self) -> Option<TokenStream> {
+        let text = self.text.as_ref()?;
+        let name = &text.name;
+        let module_ident = self.device_type.module_ident();
+        let struct_ident = &self.struct_ident;
+
+        let num_args = text.args.len();
+        let arg_idents: Vec<_> = text.args.iter().map(|arg| &arg.field).collect();
+
+        let parse_args = text.args.iter().map(|arg| {
+            let field = self.field(&arg.field);
+            let field_ident = &field.name;
+            let kind = field.text_kind();
+            let ty = field.text_parse_ty();
+            quote!(
+                let #field_ident: #ty = <kind::#kind as ParseField<#ty>>::parse_field(#field_ident)?;
+            )
+        });
+
+        let field_inits = self
+            .fields
+            .iter()
+            .filter(|field| !field.is_hidden())
+            .map(|field| {
+                let field_ident = &field.name;
+                if field.is_slice() {
+                    quote!(#field_ident: &#field_ident)
+                } else {
+                    quote!(#field_ident)
+                }
+            });
+
+        Some(quote!(
+            #name => {
+                let [#(#arg_idents),*] = args.into_array::<#num_args>()?;
+                #(#parse_args)*
+                push_node(out, &build::#module_ident::#struct_ident {
+                    #(#field_inits),*
+                })?;
+            }
+        ))
+    }
+
+    /// Generate a `serde::Serialize` impl for the packed struct that
+    /// serializes each visible field as a struct field. Returns `None`
+    /// if the impl must be written by hand.
+    fn gen_packed_struct_serialize_impl(&self) -> Option<TokenStream> {
+        if !self.has_serde_impl() {
+            return None;
+        }
+
+        let struct_ident = &self.struct_ident;
+        let struct_name = struct_ident.to_string();
+        let fields: Vec<_> = self.fields.iter().filter(|f| !f.is_hidden()).collect();
+        let num_fields = fields.len();
+        // Avoid an `unused_mut` warning for nodes without fields.
+        let state = if fields.is_empty() {
+            quote!(state)
+        } else {
+            quote!(mut state)
+        };
+
+        let serialize_fields = fields.iter().map(|field| {
+            let field_ident = &field.name;
+            let field_name = field_ident.to_string();
+            let kind = field.kind().unwrap();
+            quote!(
+                state.serialize_field(
+                    #field_name,
+                    &SerdeField::<kind::#kind, _>::new(&self.#field_ident()),
+                )?;
+            )
+        });
+
+        Some(quote!(
+            #[cfg(feature = "serde")]
+            impl serde::Serialize for #struct_ident {
+                fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    use serde::ser::SerializeStruct;
+
+                    let #state = serializer.serialize_struct(#struct_name, #num_fields)?;
+                    #(#serialize_fields)*
+                    state.end()
+                }
+            }
+        ))
+    }
+
+    /// Generate a match arm for `node_from_serde` that deserializes the
+    /// fields of the node and appends the built node. `variant_name` is
+    /// the name of the node's `DevicePathNodeEnum` variant. Returns
+    /// `None` if the node is deserialized by hand-written code.
+    pub fn gen_serde_de_arm(&self, variant_name: &Ident) -> Option<TokenStream> {
+        if !self.has_serde_impl() {
+            return None;
+        }
+
+        let name = variant_name.to_string();
+        let module_ident = self.device_type.module_ident();
+        let struct_ident = &self.struct_ident;
+        let struct_name = struct_ident.to_string();
+        let fields: Vec<_> = self.fields.iter().filter(|f| !f.is_hidden()).collect();
+
+        let field_defs = fields.iter().map(|field| {
+            let field_ident = &field.name;
+            let kind = field.kind().unwrap();
+            let ty = field.text_parse_ty();
+            quote!(#field_ident: Deserialized<kind::#kind, #ty>)
+        });
+
+        let field_names = fields.iter().map(|field| &field.name);
+
+        let field_inits = fields.iter().map(|field| {
+            let field_ident = &field.name;
+            if field.is_slice() {
+                quote!(#field_ident: &#field_ident.0)
+            } else {
+                quote!(#field_ident: #field_ident.0)
+            }
+        });
+
+        Some(quote!(
+            #name => {
+                #[derive(serde::Deserialize)]
+                #[serde(deny_unknown_fields, rename = #struct_name)]
+                struct Fields {
+                    #(#field_defs),*
+                }
+
+                let Fields { #(#field_names),* } = variant.newtype_variant()?;
+                out.push(&build::#module_ident::#struct_ident {
+                    #(#field_inits),*
+                })
+                .map_err(A::Error::custom)?;
+            }
+        ))
+    }
+
     pub fn gen_packed_code(&self) -> TokenStream {
         let s = self.gen_packed_struct();
         let s_impl = self.gen_packed_struct_impl();
         let dbg_impl = self.gen_packed_struct_debug_impl();
+        let display_impl = self.gen_packed_struct_display_impl();
+        let serialize_impl = self.gen_packed_struct_serialize_impl();
         let try_impl = self.gen_packed_struct_try_from_impl();
         quote!(
             #s
             #s_impl
             #dbg_impl
+            #display_impl
+            #serialize_impl
             #try_impl
         )
     }
@@ -511,6 +843,7 @@ impl Node {
 struct NodeAttr {
     static_size: usize,
     sub_type: Option<String>,
+    text: Option<String>,
 }
 
 /// Parse a `node` attribute. Returns `None` for any other attribute, or
@@ -522,6 +855,7 @@ fn parse_node_attr(attr: &Attribute) -> Option<NodeAttr> {
 
     let mut static_size = None;
     let mut sub_type = None;
+    let mut text = None;
     attr.parse_nested_meta(|meta| {
         if meta.path.is_ident("static_size") {
             let value = meta.value()?;
@@ -534,6 +868,11 @@ fn parse_node_attr(attr: &Attribute) -> Option<NodeAttr> {
             let lit: LitStr = value.parse()?;
             sub_type = Some(lit.value());
             Ok(())
+        } else if meta.path.is_ident("text") {
+            let value = meta.value()?;
+            let lit: LitStr = value.parse()?;
+            text = Some(lit.value());
+            Ok(())
         } else {
             Err(meta.error("invalid struct node attribute"))
         }
@@ -543,6 +882,7 @@ fn parse_node_attr(attr: &Attribute) -> Option<NodeAttr> {
     Some(NodeAttr {
         static_size: static_size?,
         sub_type,
+        text,
     })
 }
 
@@ -20,7 +20,7 @@ mod end {
 
 mod hardware {
     /// PCI hardware device path node.
-    #[node(static_size = 6)]
+    #[node(static_size = 6, text = "Pci(device,function)")]
     struct Pci {
         /// PCI function number.
         function: u8,
@@ -30,14 +30,17 @@ mod hardware {
     }
 
     /// PCCARD hardware device path node.
-    #[node(static_size = 5)]
+    #[node(static_size = 5, text = "PcCard(function)")]
     struct Pccard {
         /// Function number starting from 0.
         function: u8,
     }
 
     /// Memory mapped hardware device path node.
-    #[node(static_size = 24)]
+    #[node(
+        static_size = 24,
+        text = "MemoryMapped(memory_type,start_address,end_address)"
+    )]
     struct MemoryMapped {
         /// Memory type.
         memory_type: MemoryType,
@@ -50,7 +53,7 @@ mod hardware {
     }
 
     /// Vendor-defined hardware device path node.
-    #[node(static_size = 20)]
+    #[node(static_size = 20, text = "VenHw(vendor_guid,vendor_defined_data?)")]
     struct Vendor {
         /// Vendor-assigned GUID that defines the data that follows.
         vendor_guid: Guid,
@@ -60,7 +63,7 @@ mod hardware {
     }
 
     /// Controller hardware device path node.
-    #[node(static_size = 8)]
+    #[node(static_size = 8, text = "Ctrl(controller_number)")]
     struct Controller {
         /// Controller number.
         controller_number: u32,
@@ -68,7 +71,7 @@ mod hardware {
 
     /// Baseboard Management Controller (BMC) host interface hardware
     /// device path node.
-    #[node(static_size = 13)]
+    #[node(static_size = 13, text = "BMC(interface_type,base_address)")]
     struct Bmc {
         /// Host interface type.
         interface_type: device_path::hardware::BmcInterfaceType,
@@ -314,7 +317,7 @@ mod acpi {
     }
 
     /// NVDIMM ACPI device path node.
-    #[node(static_size = 8)]
+    #[node(static_size = 8, text = "NVDIMM(nfit_device_handle)")]
     struct Nvdimm {
         /// NFIT device handle.
         nfit_device_handle: u32,
@@ -323,7 +326,10 @@ mod acpi {
 
 mod messaging {
     /// ATAPI messaging device path node.
-    #[node(static_size = 8)]
+    #[node(
+        static_size = 8,
+        text = "Ata(primary_secondary,master_slave,logical_unit_number)"
+    )]
     struct Atapi {
         /// Whether the ATAPI device is primary or secondary.
         primary_secondary: device_path::messaging::PrimarySecondary,
@@ -356,7 +362,7 @@ mod messaging {
     }
 
     /// SCSI messaging device path node.
-    #[node(static_size = 8)]
+    #[node(static_size = 8, text = "Scsi(target_id,logical_unit_number)")]
     struct Scsi {
         /// Target ID on the SCSI bus.
         target_id: u16,
@@ -366,7 +372,7 @@ mod messaging {
     }
 
     /// Fibre channel messaging device path node.
-    #[node(static_size = 24)]
+    #[node(static_size = 24, text = "Fibre(world_wide_name,logical_unit_number)")]
     struct FibreChannel {
         _reserved: u32,
 
@@ -378,7 +384,10 @@ mod messaging {
     }
 
     /// Fibre channel extended messaging device path node.
-    #[node(static_size = 24)]
+    #[node(
+        static_size = 24,
+        text = "FibreEx(world_wide_name,logical_unit_number)"
+    )]
     struct FibreChannelEx {
         _reserved: u32,
 
@@ -390,7 +399,7 @@ mod messaging {
     }
 
     /// 1394 messaging device path node.
-    #[node(static_size = 16, sub_type = "MESSAGING_1394")]
+    #[node(static_size = 16, sub_type = "MESSAGING_1394", text = "I1394(guid)")]
     struct Ieee1394 {
         _reserved: u32,
 
@@ -400,7 +409,7 @@ mod messaging {
     }
 
     /// USB messaging device path node.
-    #[node(static_size = 6)]
+    #[node(static_size = 6, text = "USB(parent_port_number,interface)")]
     struct Usb {
         /// USB parent port number.
         parent_port_number: u8,
@@ -410,7 +419,10 @@ mod messaging {
     }
 
     /// SATA messaging device path node.
-    #[node(static_size = 10)]
+    #[node(
+        static_size = 10,
+        text = "Sata(hba_port_number,port_multiplier_port_number,logical_unit_number)"
+    )]
     struct Sata {
         /// The HBA port number that facilitates the connection to the
         /// device or a port multiplier. The value 0xffff is reserved.
@@ -426,7 +438,10 @@ mod messaging {
     }
 
     /// USB World Wide ID (WWID) messaging device path node.
-    #[node(static_size = 10)]
+    #[node(
+        static_size = 10,
+        text = "UsbWwid(device_vendor_id,device_product_id,interface_number,serial_number)"
+    )]
     struct UsbWwid {
         /// USB interface number.
         interface_number: u16,
@@ -442,14 +457,17 @@ mod messaging {
     }
 
     /// Device logical unit messaging device path node.
-    #[node(static_size = 5)]
+    #[node(static_size = 5, text = "Unit(logical_unit_number)")]
     struct DeviceLogicalUnit {
         /// Logical Unit Number.
         logical_unit_number: u8,
     }
 
     /// USB class messaging device path node.
-    #[node(static_size = 11)]
+    #[node(
+        static_size = 11,
+        text = "UsbClass(vendor_id,product_id,device_class,device_subclass,device_protocol)"
+    )]
     struct UsbClass {
         /// USB vendor ID.
         vendor_id: u16,
@@ -468,7 +486,7 @@ mod messaging {
     }
 
     /// I2O messaging device path node.
-    #[node(static_size = 8)]
+    #[node(static_size = 8, text = "I2O(target_id)")]
     struct I2o {
         /// Target ID (TID).
         target_id: u32,
@@ -571,20 +589,25 @@ mod messaging {
     }
 
     /// VLAN messaging device path node.
-    #[node(static_size = 6)]
+    #[node(static_size = 6, text = "Vlan(vlan_id)")]
     struct Vlan {
         /// VLAN identifier (0-4094).
+        #[node(text_kind = "Decimal")]
         vlan_id: u16,
     }
 
     /// InfiniBand messaging device path node.
-    #[node(static_size = 48)]
+    #[node(
+        static_size = 48,
+        text = "Infiniband(resource_flags,port_gid,ioc_guid_or_service_id,target_port_id,device_id)"
+    )]
     struct Infiniband {
         /// Flags to identify/manage InfiniBand elements.
         resource_flags: device_path::messaging::InfinibandResourceFlags,
 
         /// 128-bit Global Identifier for remote fabric port. Note that
         /// this is not the same as a UEFI GUID.
+        #[node(text_kind = "GuidBytes")]
         port_gid: [u8; 16],
 
         /// IOC GUID if bit 0 of `resource_flags` is unset, or Service
@@ -621,14 +644,16 @@ mod messaging {
     }
 
     /// UART messaging device path node.
-    #[node(static_size = 19)]
+    #[node(static_size = 19, text = "Uart(baud_rate,data_bits,parity,stop_bits)")]
     struct Uart {
         _reserved: u32,
 
         /// Baud rate setting, or 0 to use the device's default.
+        #[node(text_kind = "Decimal")]
         baud_rate: u64,
 
         /// Number of data bits, or 0 to use the device's default.
+        #[node(text_kind = "Decimal")]
         data_bits: u8,
 
         /// Parity setting.
@@ -777,7 +802,10 @@ mod messaging {
     }
 
     /// NVM Express namespace messaging device path node.
-    #[node(static_size = 16)]
+    #[node(
+        static_size = 16,
+        text = "NVMe(namespace_identifier,ieee_extended_unique_identifier)"
+    )]
     struct NvmeNamespace {
         /// Namespace identifier (NSID). The values 0 and 0xffff_ffff
         /// are invalid.
@@ -785,18 +813,20 @@ mod messaging {
 
         /// IEEE Extended Unique Identifier (EUI-64), or 0 if the device
         /// does not have a EUI-64.
+        #[node(text_kind = "Eui64")]
         ieee_extended_unique_identifier: u64,
     }
 
     /// Uniform Resource Identifier (URI) messaging device path node.
-    #[node(static_size = 4)]
+    #[node(static_size = 4, text = "Uri(value)")]
     struct Uri {
         /// URI as defined by [RFC 3986](https://www.rfc-editor.org/rfc/rfc3986).
+        #[node(text_kind = "Ascii")]
         value: [u8],
     }
 
     /// Universal Flash Storage (UFS) messaging device path node.
-    #[node(static_size = 6)]
+    #[node(static_size = 6, text = "UFS(target_id,logical_unit_number)")]
     struct Ufs {
         /// Target ID on the UFS interface (PUN).
         target_id: u8,
@@ -806,35 +836,36 @@ mod messaging {
     }
 
     /// Secure Digital (SD) messaging device path node.
-    #[node(static_size = 5)]
+    #[node(static_size = 5, text = "SD(slot_number)")]
     struct Sd {
         /// Slot number.
         slot_number: u8,
     }
 
     /// Bluetooth messaging device path node.
-    #[node(static_size = 10)]
+    #[node(static_size = 10, text = "Bluetooth(device_address)")]
     struct Bluetooth {
         /// 48-bit bluetooth device address.
         device_address: [u8; 6],
     }
 
     /// Wi-Fi messaging device path node.
-    #[node(static_size = 36)]
+    #[node(static_size = 36, text = "Wi-Fi(ssid)")]
     struct Wifi {
         /// Service set identifier (SSID).
+        #[node(text_kind = "Ascii")]
         ssid: [u8; 32],
     }
 
     /// Embedded Multi-Media Card (eMMC) messaging device path node.
-    #[node(static_size = 5)]
+    #[node(static_size = 5, text = "eMMC(slot_number)")]
     struct Emmc {
         /// Slot number.
         slot_number: u8,
     }
 
     /// BluetoothLE messaging device path node.
-    #[node(static_size = 11)]
+    #[node(static_size = 11, text = "BluetoothLE(device_address,address_type)")]
     struct BluetoothLe {
         /// 48-bit bluetooth device address.
         device_address: [u8; 6],
@@ -876,7 +907,7 @@ mod messaging {
     }
 
     /// NVDIMM namespace messaging device path node.
-    #[node(static_size = 20)]
+    #[node(static_size = 20, text = "NvdimmNamespace(uuid)")]
     struct NvdimmNamespace {
         /// Namespace unique label identifier.
         uuid: [u8; 16],
@@ -1004,7 +1035,7 @@ mod messaging {
 
     /// NVME over Fabric (NVMe-oF) namespace messaging device path node.
     // The spec says 20, but it seems to be wrong.
-    #[node(static_size = 21)]
+    #[node(static_size = 21, text = "NVMEoF(subsystem_nqn,nidt,nid)")]
     struct NvmeOfNamespace {
         /// Namespace Identifier Type (NIDT).
         nidt: u8,
@@ -1014,6 +1045,7 @@ mod messaging {
 
         /// Unique identifier of an NVM subsystem stored as a
         /// null-terminated UTF-8 string. Maximum length of 224 bytes.
+        #[node(text_kind = "CStr8")]
         subsystem_nqn: [u8],
     }
 }
@@ -1096,6 +1128,7 @@ mod media {
 
     /// Hard drive partition signature.
     #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub enum PartitionSignature {
         /// No disk signature.
         None,
@@ -1127,7 +1160,10 @@ mod media {
     }
 
     /// CD-ROM media device path node.
-    #[node(static_size = 24)]
+    #[node(
+        static_size = 24,
+        text = "CDROM(boot_entry,partition_start,partition_size)"
+    )]
     struct CdRom {
         /// Boot entry number from the boot catalog, or 0 for the
         /// default entry.
@@ -1141,7 +1177,7 @@ mod media {
     }
 
     /// Vendor-defined media device path node.
-    #[node(static_size = 20)]
+    #[node(static_size = 20, text = "VenMedia(vendor_guid,vendor_defined_data?)")]
     struct Vendor {
         /// Vendor-assigned GUID that defines the data that follows.
         vendor_guid: Guid,
@@ -1159,28 +1195,30 @@ mod media {
     }
 
     /// Media protocol media device path node.
-    #[node(static_size = 20)]
+    #[node(static_size = 20, text = "Media(protocol_guid)")]
     struct Protocol {
         /// The ID of the protocol.
         protocol_guid: Guid,
     }
 
     /// PIWG firmware file media device path node.
-    #[node(static_size = 4)]
+    #[node(static_size = 4, text = "FvFile(data)")]
     struct PiwgFirmwareFile {
         /// Contents are defined in the UEFI PI Specification.
+        #[node(text_kind = "GuidBytes")]
         data: [u8],
     }
 
     /// PIWG firmware volume media device path node.
-    #[node(static_size = 4)]
+    #[node(static_size = 4, text = "Fv(data)")]
     struct PiwgFirmwareVolume {
         /// Contents are defined in the UEFI PI Specification.
+        #[node(text_kind = "GuidBytes")]
         data: [u8],
     }
 
     /// Relative offset range media device path node.
-    #[node(static_size = 24)]
+    #[node(static_size = 24, text = "Offset(starting_offset,ending_offset)")]
     struct RelativeOffsetRange {
         _reserved: u32,
 
@@ -1192,7 +1230,10 @@ mod media {
     }
 
     /// RAM disk media device path node.
-    #[node(static_size = 38)]
+    #[node(
+        static_size = 38,
+        text = "RamDisk(starting_address,ending_address,disk_instance,disk_type)"
+    )]
     struct RamDisk {
         /// Starting memory address.
         starting_address: u64,
@@ -1227,9 +1268,14 @@ mod media {
 
 mod bios_boot_spec {
     /// BIOS Boot Specification device path node.
-    #[node(static_size = 8, sub_type = "BIOS_BOOT_SPECIFICATION")]
+    #[node(
+        static_size = 8,
+        sub_type = "BIOS_BOOT_SPECIFICATION",
+        text = "BBS(device_type,description_string,status_flag)"
+    )]
     struct BootSpecification {
         /// Device type as defined by the BIOS Boot Specification.
+        #[node(text_kind = "BbsDeviceType")]
         device_type: u16,
 
         /// Status flags as defined by the BIOS Boot Specification.
@@ -1237,6 +1283,7 @@ mod bios_boot_spec {
 
         /// Description of the boot device encoded as a null-terminated
         /// ASCII string.
+        #[node(text_kind = "CStr8")]
         description_string: [u8],
     }
 }